@@ -4,6 +4,7 @@
 use clap::Parser;
 use sendspin::protocol::client::ProtocolClient;
 use sendspin::protocol::messages::{AudioFormatSpec, ClientHello, DeviceInfo, PlayerV1Support};
+use sendspin::protocol::Role;
 
 /// Sendspin basic client
 #[derive(Parser, Debug)]
@@ -29,7 +30,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client_id: uuid::Uuid::new_v4().to_string(),
         name: args.name.clone(),
         version: 1,
-        supported_roles: vec!["player@v1".to_string()],
+        supported_roles: vec![Role::Player(1)],
         device_info: Some(DeviceInfo {
             product_name: Some(args.name.clone()),
             manufacturer: Some("Sendspin".to_string()),
@@ -41,12 +42,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 channels: 2,
                 sample_rate: 48000,
                 bit_depth: 24,
+                endian: None,
             }],
             buffer_capacity: 100,
             supported_commands: vec!["play".to_string(), "pause".to_string()],
         }),
+        controller_v1_support: None,
         artwork_v1_support: None,
         visualizer_v1_support: None,
+        payload_encryption: Vec::new(),
+        encodings: Vec::new(),
     };
 
     println!("Connecting to {}...", args.server);
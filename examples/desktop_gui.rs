@@ -0,0 +1,427 @@
+// ABOUTME: egui desktop reference client
+// ABOUTME: Plays audio and shows artwork, progress, volume, device picker, and sync quality
+
+use clap::Parser;
+use eframe::egui;
+use parking_lot::Mutex;
+use sendspin::audio::decode::{Decoder, PcmDecoder, PcmEndian};
+use sendspin::audio::{AudioBuffer, AudioFormat, AudioOutput, Codec, CpalOutput};
+use sendspin::events::{ClientEvent, ConnectionStatus, ObserverRegistry};
+use sendspin::metadata::{format_duration, NowPlaying};
+use sendspin::protocol::client::{ClientConfig, ClientParts, ProtocolClient};
+use sendspin::protocol::messages::{
+    ArtworkV1Support, AudioFormatSpec, ClientCommand, ClientHello, ClientState, ControllerCommand,
+    ControllerCommandKind, ControllerV1Support, DeviceInfo, Message, PlayerState, PlayerSyncState,
+    PlayerV1Support,
+};
+use sendspin::protocol::Role;
+use sendspin::scheduler::{AudioScheduler, Scheduler};
+use sendspin::sync::SyncQuality;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Sendspin desktop client
+#[derive(Parser, Debug)]
+#[command(name = "desktop_gui")]
+#[command(about = "Desktop Sendspin player with artwork and controls", long_about = None)]
+struct Args {
+    /// WebSocket URL of the Sendspin server
+    #[arg(short, long, default_value = "ws://localhost:8927/sendspin")]
+    server: String,
+
+    /// Client name
+    #[arg(short, long, default_value = "Sendspin-RS Desktop")]
+    name: String,
+}
+
+/// Everything the window displays
+#[derive(Debug, Default)]
+struct UiState {
+    connected: bool,
+    now_playing: Option<NowPlaying>,
+    volume: u8,
+    muted: bool,
+    supported_commands: Vec<String>,
+    /// Latest artwork and a generation number used as its texture URI
+    artwork: Option<(u64, Arc<[u8]>)>,
+    sync: Option<(SyncQuality, i64)>,
+}
+
+impl UiState {
+    fn apply(&mut self, event: &ClientEvent) {
+        match event {
+            ClientEvent::MetadataChanged(now) => self.now_playing = Some((**now).clone()),
+            ClientEvent::VolumeChanged { volume, muted } => {
+                self.volume = *volume;
+                self.muted = *muted;
+            }
+            ClientEvent::ConnectionChanged(status) => {
+                self.connected = *status == ConnectionStatus::Connected;
+            }
+            _ => {}
+        }
+    }
+
+    fn supports(&self, kind: &ControllerCommandKind) -> bool {
+        self.supported_commands.iter().any(|c| kind == c.as_str())
+    }
+}
+
+/// Window state and the channel to the network task
+struct DesktopApp {
+    state: Arc<Mutex<UiState>>,
+    commands: UnboundedSender<ControllerCommand>,
+    devices: Vec<String>,
+    device: Arc<Mutex<Option<String>>>,
+}
+
+impl DesktopApp {
+    fn send(&self, command: ControllerCommand) {
+        let _ = self.commands.send(command);
+    }
+
+    fn connection_indicator(&self, ui: &mut egui::Ui, state: &UiState) {
+        let (color, text) = match (state.connected, state.sync) {
+            (false, _) => (egui::Color32::GRAY, "Disconnected".to_string()),
+            (true, None) => (egui::Color32::YELLOW, "Syncing clock".to_string()),
+            (true, Some((quality, rtt))) => (
+                match quality {
+                    SyncQuality::Good => egui::Color32::GREEN,
+                    SyncQuality::Degraded => egui::Color32::YELLOW,
+                    SyncQuality::Lost => egui::Color32::RED,
+                },
+                format!("{:?} sync, RTT {:.1}ms", quality, rtt as f64 / 1000.0),
+            ),
+        };
+        ui.horizontal(|ui| {
+            ui.colored_label(color, "●");
+            ui.label(text);
+        });
+    }
+
+    fn now_playing(&self, ui: &mut egui::Ui, state: &UiState) {
+        if let Some((generation, ref bytes)) = state.artwork {
+            ui.add(
+                egui::Image::from_bytes(
+                    format!("bytes://artwork/{}", generation),
+                    egui::load::Bytes::Shared(Arc::clone(bytes)),
+                )
+                .max_size(egui::vec2(240.0, 240.0)),
+            );
+        }
+        let Some(ref now) = state.now_playing else {
+            ui.heading("Nothing playing");
+            return;
+        };
+        ui.heading(now.title.as_deref().unwrap_or("Unknown title"));
+        ui.label(now.artist.as_deref().unwrap_or(""));
+        ui.label(now.album.as_deref().unwrap_or(""));
+
+        // Advance the last reported position locally between updates
+        if let Some(ref progress) = now.progress {
+            let speed = progress.playback_speed.unwrap_or(1.0);
+            let elapsed = now.received_at.elapsed().as_micros() as f64 * speed;
+            let position = (progress.position + elapsed as i64).min(progress.duration);
+            let fraction = if progress.duration > 0 {
+                position as f32 / progress.duration as f32
+            } else {
+                0.0
+            };
+            ui.add(egui::ProgressBar::new(fraction).text(format!(
+                "{} / {}",
+                format_duration(position),
+                format_duration(progress.duration)
+            )));
+        }
+    }
+
+    fn controls(&self, ui: &mut egui::Ui, state: &UiState) {
+        ui.horizontal(|ui| {
+            for (label, kind) in [
+                ("⏮", ControllerCommandKind::Previous),
+                ("▶", ControllerCommandKind::Play),
+                ("⏸", ControllerCommandKind::Pause),
+                ("⏭", ControllerCommandKind::Next),
+            ] {
+                if ui
+                    .add_enabled(state.supports(&kind), egui::Button::new(label))
+                    .clicked()
+                {
+                    self.send(ControllerCommand::new(kind));
+                }
+            }
+        });
+
+        let mut volume = state.volume;
+        let slider = ui.add_enabled(
+            state.supports(&ControllerCommandKind::Volume),
+            egui::Slider::new(&mut volume, 0..=100).text("Volume"),
+        );
+        if slider.changed() {
+            self.send(ControllerCommand::volume(volume));
+        }
+        let mut muted = state.muted;
+        if ui
+            .add_enabled(
+                state.supports(&ControllerCommandKind::Mute),
+                egui::Checkbox::new(&mut muted, "Mute"),
+            )
+            .changed()
+        {
+            self.send(ControllerCommand::mute(muted));
+        }
+    }
+
+    fn device_picker(&self, ui: &mut egui::Ui) {
+        let mut device = self.device.lock();
+        let selected = device
+            .clone()
+            .unwrap_or_else(|| "System default".to_string());
+        egui::ComboBox::from_label("Output device")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut *device, None, "System default");
+                for name in &self.devices {
+                    ui.selectable_value(&mut *device, Some(name.clone()), name);
+                }
+            });
+    }
+}
+
+impl eframe::App for DesktopApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let state = Arc::clone(&self.state);
+        let state = state.lock();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.connection_indicator(ui, &state);
+            ui.separator();
+            self.now_playing(ui, &state);
+            ui.separator();
+            self.controls(ui, &state);
+            self.device_picker(ui);
+        });
+        // Keep the progress bar moving between server updates
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+}
+
+/// Play scheduled buffers on the selected device, reopening it when the choice changes
+fn playback_thread(scheduler: Arc<dyn Scheduler>, device: Arc<Mutex<Option<String>>>) {
+    let mut output: Option<(Option<String>, CpalOutput)> = None;
+    loop {
+        let wanted = device.lock().clone();
+        if output.as_ref().is_some_and(|(name, _)| *name != wanted) {
+            output = None;
+        }
+        if let Some(buffer) = scheduler.next_ready() {
+            if output.is_none() {
+                match CpalOutput::with_device(buffer.format.clone(), wanted.as_deref()) {
+                    Ok(out) => output = Some((wanted, out)),
+                    Err(e) => log::error!("Failed to open audio output: {}", e),
+                }
+            }
+            if let Some((_, ref mut out)) = output {
+                if let Err(e) = out.write_timed(buffer.timestamp, &buffer.samples) {
+                    log::error!("Output error: {}", e);
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Run the protocol side: feed the UI, play audio, and forward controller commands
+async fn run_client(
+    args: Args,
+    events: ObserverRegistry<ClientEvent>,
+    state: Arc<Mutex<UiState>>,
+    scheduler: Arc<dyn Scheduler>,
+    mut commands: UnboundedReceiver<ControllerCommand>,
+    repaint: egui::Context,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let hello = ClientHello {
+        client_id: uuid::Uuid::new_v4().to_string(),
+        name: args.name.clone(),
+        version: 1,
+        supported_roles: vec![
+            Role::Player(1),
+            Role::Controller(1),
+            Role::Metadata(1),
+            Role::Artwork(1),
+        ],
+        device_info: Some(DeviceInfo {
+            product_name: Some(args.name),
+            manufacturer: Some("Sendspin".to_string()),
+            software_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+        player_v1_support: Some(PlayerV1Support {
+            supported_formats: vec![AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 24,
+                endian: None,
+            }],
+            buffer_capacity: 100,
+            supported_commands: vec!["volume".to_string(), "mute".to_string()],
+        }),
+        controller_v1_support: Some(ControllerV1Support {
+            supported_commands: vec![
+                ControllerCommandKind::Play,
+                ControllerCommandKind::Pause,
+                ControllerCommandKind::Next,
+                ControllerCommandKind::Previous,
+                ControllerCommandKind::Volume,
+                ControllerCommandKind::Mute,
+            ],
+            displays_metadata: true,
+        }),
+        artwork_v1_support: Some(ArtworkV1Support { channels: vec![0] }),
+        visualizer_v1_support: None,
+        payload_encryption: Vec::new(),
+        encodings: Vec::new(),
+    };
+
+    let config = ClientConfig {
+        clock_sync_interval: Some(Duration::from_secs(5)),
+        ..ClientConfig::default()
+    };
+    let client = ProtocolClient::connect_with_config(&args.server, hello, config).await?;
+    let ClientParts {
+        messages: mut message_rx,
+        audio: mut audio_rx,
+        artwork: mut artwork_rx,
+        clock: clock_sync,
+        sender: ws_tx,
+        ..
+    } = client.into_parts();
+    events.notify(&ClientEvent::ConnectionChanged(ConnectionStatus::Connected));
+
+    ws_tx
+        .send_message(Message::ClientState(ClientState {
+            player: Some(PlayerState {
+                state: PlayerSyncState::Synchronized,
+                volume: Some(100),
+                muted: Some(false),
+                buffered_ms: None,
+            }),
+        }))
+        .await?;
+
+    let mut decoder: Option<(AudioFormat, PcmDecoder)> = None;
+    let mut artwork_generation = 0;
+    loop {
+        tokio::select! {
+            msg = message_rx.recv() => {
+                let Some(msg) = msg else { break };
+                events.dispatch(&msg);
+                match msg {
+                    Message::ServerState(server_state) => {
+                        if let Some(controller) = server_state.controller {
+                            state.lock().supported_commands = controller.supported_commands;
+                        }
+                    }
+                    Message::StreamStart(start) => {
+                        decoder = start.player.filter(|p| p.codec == "pcm").map(|p| {
+                            let mut format = AudioFormat::new(
+                                Codec::Pcm,
+                                p.sample_rate,
+                                p.channels,
+                                p.bit_depth,
+                            );
+                            format.endian = p.endian;
+                            let endian = p.endian.unwrap_or(PcmEndian::Little);
+                            (format, PcmDecoder::with_endian(p.bit_depth, endian))
+                        });
+                    }
+                    Message::StreamClear(_) | Message::StreamEnd(_) => scheduler.clear(),
+                    Message::ServerTime(_) => {
+                        let sync = clock_sync.lock().await;
+                        state.lock().sync = sync.rtt_micros().map(|rtt| (sync.quality(), rtt));
+                    }
+                    _ => {}
+                }
+                repaint.request_repaint();
+            }
+            Some(chunk) = audio_rx.recv() => {
+                let Some((ref format, ref decoder)) = decoder else { continue };
+                let Some(play_at) = clock_sync.lock().await.server_to_local_instant(chunk.timestamp) else {
+                    continue;
+                };
+                match decoder.decode(&chunk.data) {
+                    Ok(samples) => scheduler.schedule(AudioBuffer {
+                        timestamp: chunk.timestamp,
+                        play_at,
+                        samples,
+                        format: format.clone(),
+                    }),
+                    Err(e) => log::warn!("Dropping undecodable chunk: {}", e),
+                }
+            }
+            Some(chunk) = artwork_rx.recv() => {
+                artwork_generation += 1;
+                state.lock().artwork = (!chunk.is_clear()).then_some((artwork_generation, chunk.data));
+                repaint.request_repaint();
+            }
+            Some(command) = commands.recv() => {
+                let msg = Message::ClientCommand(ClientCommand { controller: Some(command) });
+                ws_tx.send_message(msg).await?;
+            }
+        }
+    }
+
+    events.notify(&ClientEvent::ConnectionChanged(
+        ConnectionStatus::Disconnected,
+    ));
+    repaint.request_repaint();
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let args = Args::parse();
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    let scheduler: Arc<dyn Scheduler> = Arc::new(AudioScheduler::new());
+    let device = Arc::new(Mutex::new(None));
+    let playback_scheduler = Arc::clone(&scheduler);
+    let playback_device = Arc::clone(&device);
+    std::thread::spawn(move || playback_thread(playback_scheduler, playback_device));
+
+    eframe::run_native(
+        "Sendspin",
+        eframe::NativeOptions::default(),
+        Box::new(move |cc| {
+            egui_extras::install_image_loaders(&cc.egui_ctx);
+
+            let state = Arc::new(Mutex::new(UiState::default()));
+            let events = ObserverRegistry::<ClientEvent>::new();
+            let ctx = cc.egui_ctx.clone();
+            events.observe_target(&state, move |state, event| {
+                state.lock().apply(event);
+                ctx.request_repaint();
+            });
+
+            let (command_tx, command_rx) = unbounded_channel();
+            let network_state = Arc::clone(&state);
+            let repaint = cc.egui_ctx.clone();
+            runtime.spawn(async move {
+                if let Err(e) =
+                    run_client(args, events, network_state, scheduler, command_rx, repaint).await
+                {
+                    log::error!("Client error: {}", e);
+                }
+            });
+
+            Ok(Box::new(DesktopApp {
+                state,
+                commands: command_tx,
+                devices: CpalOutput::device_names(),
+                device,
+            }))
+        }),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// ABOUTME: egui integration example for the weak observer registry
+// ABOUTME: Shows live metadata, volume, and connection status without owning receivers
+
+use clap::Parser;
+use eframe::egui;
+use parking_lot::Mutex;
+use sendspin::events::{ClientEvent, ConnectionStatus, ObserverRegistry};
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{ClientHello, ControllerV1Support, DeviceInfo};
+use sendspin::protocol::Role;
+use std::sync::Arc;
+
+/// Sendspin egui observer example
+#[derive(Parser, Debug)]
+#[command(name = "egui_observers")]
+#[command(about = "Show now-playing state from a Sendspin server in a window", long_about = None)]
+struct Args {
+    /// WebSocket URL of the Sendspin server
+    #[arg(short, long, default_value = "ws://localhost:8927/sendspin")]
+    server: String,
+
+    /// Client name
+    #[arg(short, long, default_value = "Sendspin-RS Observer")]
+    name: String,
+}
+
+/// What the window shows, updated by observers
+#[derive(Debug, Default)]
+struct UiState {
+    connected: bool,
+    title: Option<String>,
+    artist: Option<String>,
+    volume: Option<(u8, bool)>,
+}
+
+impl UiState {
+    fn apply(&mut self, event: &ClientEvent) {
+        match event {
+            ClientEvent::MetadataChanged(now) => {
+                self.title = now.title.clone();
+                self.artist = now.artist.clone();
+            }
+            ClientEvent::VolumeChanged { volume, muted } => self.volume = Some((*volume, *muted)),
+            ClientEvent::ConnectionChanged(status) => {
+                self.connected = *status == ConnectionStatus::Connected;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The app owns its state; the registry only holds a weak reference to it
+struct ObserverApp {
+    state: Arc<Mutex<UiState>>,
+}
+
+impl eframe::App for ObserverApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let state = self.state.lock();
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.label(if state.connected {
+                "Connected"
+            } else {
+                "Disconnected"
+            });
+            ui.separator();
+            ui.heading(state.title.as_deref().unwrap_or("Nothing playing"));
+            ui.label(state.artist.as_deref().unwrap_or(""));
+            if let Some((volume, muted)) = state.volume {
+                let mut volume = volume as f32;
+                ui.add_enabled(false, egui::Slider::new(&mut volume, 0.0..=100.0));
+                if muted {
+                    ui.label("Muted");
+                }
+            }
+        });
+    }
+}
+
+/// Forward protocol messages to observers until the connection closes
+async fn run_client(url: String, name: String, events: ObserverRegistry<ClientEvent>) {
+    let hello = ClientHello {
+        client_id: uuid::Uuid::new_v4().to_string(),
+        name: name.clone(),
+        version: 1,
+        supported_roles: vec![Role::Controller(1), Role::Metadata(1)],
+        device_info: Some(DeviceInfo {
+            product_name: Some(name),
+            manufacturer: Some("Sendspin".to_string()),
+            software_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+        player_v1_support: None,
+        controller_v1_support: Some(ControllerV1Support {
+            supported_commands: Vec::new(),
+            displays_metadata: true,
+        }),
+        artwork_v1_support: None,
+        visualizer_v1_support: None,
+        payload_encryption: Vec::new(),
+        encodings: Vec::new(),
+    };
+
+    let mut client = match ProtocolClient::connect(&url, hello).await {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("Failed to connect to {}: {}", url, e);
+            return;
+        }
+    };
+    events.notify(&ClientEvent::ConnectionChanged(ConnectionStatus::Connected));
+
+    while let Some(msg) = client.recv_message().await {
+        events.dispatch(&msg);
+    }
+    events.notify(&ClientEvent::ConnectionChanged(
+        ConnectionStatus::Disconnected,
+    ));
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+
+    let args = Args::parse();
+    let events = ObserverRegistry::<ClientEvent>::new();
+
+    // Networking runs on its own runtime; the GUI stays on the main thread
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.spawn(run_client(args.server, args.name, events.clone()));
+
+    eframe::run_native(
+        "Sendspin",
+        eframe::NativeOptions::default(),
+        Box::new(move |cc| {
+            let state = Arc::new(Mutex::new(UiState::default()));
+            let ctx = cc.egui_ctx.clone();
+            events.observe_target(&state, move |state, event| {
+                state.lock().apply(event);
+                ctx.request_repaint();
+            });
+            Ok(Box::new(ObserverApp { state }))
+        }),
+    )?;
+    Ok(())
+}
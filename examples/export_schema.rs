@@ -0,0 +1,42 @@
+// ABOUTME: Protocol schema export tool
+// ABOUTME: Writes JSON Schema and TypeScript declarations for all protocol messages
+
+use clap::{Parser, ValueEnum};
+use sendspin::protocol::schema;
+use std::path::PathBuf;
+
+/// Output format
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Format {
+    /// JSON Schema (draft 2020-12)
+    Json,
+    /// TypeScript declarations
+    Ts,
+}
+
+/// Sendspin schema export
+#[derive(Parser, Debug)]
+#[command(name = "export_schema")]
+#[command(about = "Export protocol message definitions as JSON Schema or TypeScript", long_about = None)]
+struct Args {
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "json")]
+    format: Format,
+
+    /// Output file (stdout if omitted)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let text = match args.format {
+        Format::Json => serde_json::to_string_pretty(&schema::json_schema())? + "\n",
+        Format::Ts => schema::typescript(),
+    };
+    match args.output {
+        Some(path) => std::fs::write(path, text)?,
+        None => print!("{}", text),
+    }
+    Ok(())
+}
@@ -2,10 +2,11 @@
 // ABOUTME: Just connects and prints everything the server sends
 
 use clap::Parser;
-use sendspin::protocol::client::ProtocolClient;
+use sendspin::prelude::*;
 use sendspin::protocol::messages::{
-    AudioFormatSpec, ClientHello, ClientState, DeviceInfo, Message, PlayerState, PlayerSyncState,
-    PlayerV1Support,
+    AudioFormatSpec, ClientState, DeviceInfo, GroupUpdate, PlayerState, PlayerSyncState,
+    PlayerV1Support, ServerCommand, ServerGoodbye, ServerState, StreamClear, StreamEnd,
+    StreamStart,
 };
 
 /// Minimal Sendspin test client
@@ -18,7 +19,7 @@ struct Args {
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
     let args = Args::parse();
@@ -27,7 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client_id: uuid::Uuid::new_v4().to_string(),
         name: "Minimal Test Client".to_string(),
         version: 1,
-        supported_roles: vec!["player@v1".to_string()],
+        supported_roles: vec![Role::Player(1)],
         device_info: Some(DeviceInfo {
             product_name: Some("Minimal Test".to_string()),
             manufacturer: Some("Sendspin".to_string()),
@@ -39,50 +40,99 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 channels: 2,
                 sample_rate: 48000,
                 bit_depth: 24,
+                endian: None,
             }],
             buffer_capacity: 100,
             supported_commands: vec!["play".to_string()],
         }),
+        controller_v1_support: None,
         artwork_v1_support: None,
         visualizer_v1_support: None,
+        payload_encryption: Vec::new(),
+        encodings: Vec::new(),
     };
 
     println!("Connecting to {}...", args.server);
     let client = ProtocolClient::connect(&args.server, hello).await?;
     println!("Connected! Server said hello.");
 
-    // Split client
-    let (mut message_rx, mut audio_rx, _clock_sync, ws_tx) = client.split();
-
     // Send client/state (handshake step 3)
     let client_state = Message::ClientState(ClientState {
         player: Some(PlayerState {
             state: PlayerSyncState::Synchronized,
             volume: Some(100),
             muted: Some(false),
+            buffered_ms: None,
         }),
     });
-    ws_tx.send_message(client_state).await?;
+    client.send_message(&client_state).await?;
     println!("Sent client/state");
 
     println!("\nListening for ALL messages from server...\n");
 
     // Just print everything we receive
-    loop {
-        tokio::select! {
-            Some(msg) = message_rx.recv() => {
-                println!("[TEXT MESSAGE] {:?}", msg);
-            }
-            Some(chunk) = audio_rx.recv() => {
-                println!("[AUDIO CHUNK] timestamp={} size={} bytes",
-                    chunk.timestamp, chunk.data.len());
-            }
-            else => {
-                println!("Connection closed");
-                break;
-            }
-        }
+    client.run(Printer).await?;
+    Ok(())
+}
+
+/// Prints every event of the connection
+struct Printer;
+
+impl ClientHandler for Printer {
+    async fn on_server_state(&mut self, state: ServerState, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", state);
+        Ok(())
     }
 
-    Ok(())
+    async fn on_server_command(
+        &mut self,
+        command: ServerCommand,
+        _ctx: &ClientContext,
+    ) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", command);
+        Ok(())
+    }
+
+    async fn on_group_update(&mut self, update: GroupUpdate, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", update);
+        Ok(())
+    }
+
+    async fn on_stream_start(&mut self, start: StreamStart, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", start);
+        Ok(())
+    }
+
+    async fn on_stream_clear(&mut self, clear: StreamClear, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", clear);
+        Ok(())
+    }
+
+    async fn on_stream_end(&mut self, end: StreamEnd, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", end);
+        Ok(())
+    }
+
+    async fn on_message(&mut self, message: Message, _ctx: &ClientContext) -> Result<()> {
+        println!("[TEXT MESSAGE] {:?}", message);
+        Ok(())
+    }
+
+    async fn on_audio_chunk(&mut self, chunk: AudioChunk, _ctx: &ClientContext) -> Result<()> {
+        println!(
+            "[AUDIO CHUNK] timestamp={} size={} bytes",
+            chunk.timestamp,
+            chunk.data.len()
+        );
+        Ok(())
+    }
+
+    async fn on_disconnect(
+        &mut self,
+        _goodbye: Option<ServerGoodbye>,
+        _ctx: &ClientContext,
+    ) -> Result<()> {
+        println!("Connection closed");
+        Ok(())
+    }
 }
@@ -2,26 +2,22 @@
 // ABOUTME: Connects to server, receives audio, and plays it back
 
 use clap::Parser;
-use sendspin::audio::decode::{Decoder, PcmDecoder, PcmEndian};
-use sendspin::audio::{AudioBuffer, AudioFormat, AudioOutput, Codec, CpalOutput};
-use sendspin::protocol::client::ProtocolClient;
+use sendspin::audio::{AudioFormat, AudioOutput, CpalOutput, Sample, ToneVerifier};
+use sendspin::audit::AuditLog;
+use sendspin::config::Config;
+use sendspin::metadata::{MetadataExporter, MetadataTracker};
+use sendspin::player::{Player, PlayerEvent};
+use sendspin::protocol::client::{ClientConfig, ProtocolClient};
 use sendspin::protocol::messages::{
-    AudioFormatSpec, ClientHello, ClientState, ClientTime, DeviceInfo, Message, PlayerState,
-    PlayerSyncState, PlayerV1Support,
+    ClientHello, ClientState, DeviceInfo, GoodbyeReason, Message, PlayerState, PlayerSyncState,
+    PlayerV1Support,
 };
-use sendspin::scheduler::AudioScheduler;
-use std::sync::Arc;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tokio::time::interval;
-
-/// Environment variable helpers
-fn env_u64(key: &str, default: u64) -> u64 {
-    std::env::var(key)
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(default)
-}
+use sendspin::protocol::{Keepalive, Role};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+/// Environment variable helper
 fn env_bool(key: &str) -> bool {
     std::env::var(key)
         .ok()
@@ -32,15 +28,40 @@ fn env_bool(key: &str) -> bool {
 /// Sendspin audio player
 #[derive(Parser, Debug)]
 #[command(name = "player")]
+#[command(disable_version_flag = true)]
 #[command(about = "Connect to Sendspin server and play audio", long_about = None)]
 struct Args {
-    /// WebSocket URL of the Sendspin server
-    #[arg(short, long, default_value = "ws://localhost:8927/sendspin")]
-    server: String,
+    /// Settings file (TOML); SS_* environment variables and flags take precedence
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// WebSocket URL of the Sendspin server [default: ws://localhost:8927/sendspin]
+    #[arg(short, long)]
+    server: Option<String>,
+
+    /// Client name [default: Sendspin-RS Player]
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Latency profile: standard, or tv to match a low-latency sender [default: standard]
+    #[arg(long)]
+    profile: Option<String>,
 
-    /// Client name
-    #[arg(short, long, default_value = "Sendspin-RS Player")]
-    name: String,
+    /// Check output against a `send --input tone` reference tone at this frequency (Hz)
+    #[arg(long)]
+    verify_tone: Option<f64>,
+
+    /// Shared 64-hex-digit key to request payload encryption
+    #[arg(long)]
+    payload_key: Option<String>,
+
+    /// Print version information and exit
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version, also print compiled-in features and detected audio devices
+    #[arg(long)]
+    verbose: bool,
 }
 
 #[tokio::main]
@@ -48,346 +69,284 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
     let args = Args::parse();
+    if args.version {
+        if args.verbose {
+            println!("{}", sendspin::diagnostics::report());
+        } else {
+            println!("{}", env!("CARGO_PKG_VERSION"));
+        }
+        return Ok(());
+    }
+
+    // Settings file, then SS_* environment variables, then flags
+    let mut settings = match args.config {
+        Some(ref path) => Config::load(path)?,
+        None => Config::default(),
+    }
+    .with_env()?;
+    if args.server.is_some() {
+        settings.client.server = args.server;
+    }
+    if args.name.is_some() {
+        settings.client.name = args.name;
+    }
+    if args.profile.is_some() {
+        settings.player.profile = args.profile;
+    }
+    if args.payload_key.is_some() {
+        settings.client.payload_key = args.payload_key;
+    }
+    let server = settings
+        .client
+        .server
+        .clone()
+        .unwrap_or_else(|| "ws://localhost:8927/sendspin".to_string());
+    let name = settings
+        .client
+        .name
+        .clone()
+        .unwrap_or_else(|| "Sendspin-RS Player".to_string());
+    let player_config = settings.player_config()?;
 
     let hello = ClientHello {
-        client_id: uuid::Uuid::new_v4().to_string(),
-        name: args.name.clone(),
+        client_id: settings
+            .client
+            .client_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name: name.clone(),
         version: 1,
-        supported_roles: vec!["player@v1".to_string()],
+        supported_roles: vec![Role::Player(1)],
         device_info: Some(DeviceInfo {
-            product_name: Some(args.name.clone()),
+            product_name: Some(name),
             manufacturer: Some("Sendspin".to_string()),
             software_version: Some("0.1.0".to_string()),
         }),
         player_v1_support: Some(PlayerV1Support {
-            supported_formats: vec![AudioFormatSpec {
-                codec: "pcm".to_string(),
-                channels: 2,
-                sample_rate: 48000,
-                bit_depth: 24,
-            }],
+            supported_formats: Player::supported_formats(),
             buffer_capacity: 100,
             supported_commands: vec!["play".to_string(), "pause".to_string()],
         }),
+        controller_v1_support: None,
         artwork_v1_support: None,
         visualizer_v1_support: None,
+        payload_encryption: Vec::new(),
+        encodings: Vec::new(),
     };
 
-    println!("Connecting to {}...", args.server);
-    let client = ProtocolClient::connect(&args.server, hello).await?;
-    println!("Connected!");
-
-    // Split client into separate receivers for concurrent processing
-    let (mut message_rx, mut audio_rx, clock_sync, ws_tx) = client.split();
-
-    // Send initial client/state message (handshake step 3)
-    let client_state = Message::ClientState(ClientState {
-        player: Some(PlayerState {
-            state: PlayerSyncState::Synchronized,
-            volume: Some(100),
-            muted: Some(false),
+    println!("Connecting to {}...", server);
+    let defaults = settings.client_config()?;
+    let config = ClientConfig {
+        // Send client/time now and every 5 seconds; replies update the clock sync
+        clock_sync_interval: defaults
+            .clock_sync_interval
+            .or(Some(Duration::from_secs(5))),
+        // Handshake step 3: report the initial player state before connect returns
+        initial_state: Some(ClientState {
+            player: Some(PlayerState {
+                state: PlayerSyncState::Synchronized,
+                volume: Some(100),
+                muted: Some(false),
+                buffered_ms: None,
+            }),
         }),
-    });
-    ws_tx.send_message(client_state).await?;
-    println!("Sent initial client/state");
+        // Give up on a server that has been silent for 30 seconds
+        keepalive: defaults
+            .keepalive
+            .or(Some(Keepalive::new(Duration::from_secs(10)))),
+        ..defaults
+    };
+    let client = ProtocolClient::connect_with_config(&server, hello, config).await?;
+    let session = client.session();
+    println!(
+        "Connected to {} with roles {:?}",
+        session.server_name(),
+        session.active_roles()
+    );
+    for role in session.refused_roles() {
+        println!("Server refused role {}", role);
+    }
 
-    // Send immediate initial clock sync
-    let client_transmitted = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_micros() as i64;
-    let time_msg = Message::ClientTime(ClientTime { client_transmitted });
-    ws_tx.send_message(time_msg).await?;
-    println!("Sent initial client/time for clock sync");
+    // Keep a handle on negotiated stream formats for status output
+    let streams = client.stream_tracker();
+    let clock_sync = client.clock_sync();
+    // Kept for saying goodbye on Ctrl-C while the player owns the client
+    let sender = client.sender();
 
     println!("Waiting for stream to start...");
 
-    // Spawn clock sync task that sends client/time every 5 seconds
-    tokio::spawn(async move {
-        let mut interval = interval(Duration::from_secs(5));
-        loop {
-            interval.tick().await;
-
-            // Get current Unix epoch microseconds
-            let client_transmitted = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_micros() as i64;
-
-            let time_msg = Message::ClientTime(ClientTime { client_transmitted });
-
-            // Send time sync message
-            if let Err(e) = ws_tx.send_message(time_msg).await {
-                log::error!("Failed to send time sync: {}", e);
-                break;
-            }
-        }
+    // Opt-in audit log, dumped to SS_AUDIT_DUMP on SIGUSR1
+    let audit = std::env::var("SS_AUDIT_DUMP").ok().map(|path| {
+        let audit = AuditLog::new();
+        #[cfg(unix)]
+        audit.spawn_signal_dump(&path);
+        println!("Audit log enabled: send SIGUSR1 to write {}", path);
+        audit
     });
 
-    // Create shared scheduler
-    let scheduler = Arc::new(AudioScheduler::new());
-    let scheduler_clone = Arc::clone(&scheduler);
+    // Optional now-playing export for overlays (SS_NOW_PLAYING_FILE, SS_NOW_PLAYING_TEMPLATE)
+    let metadata = MetadataTracker::new();
+    if let Ok(path) = std::env::var("SS_NOW_PLAYING_FILE") {
+        let template = std::env::var("SS_NOW_PLAYING_TEMPLATE")
+            .unwrap_or_else(|_| "{artist} - {title} ({progress})".to_string());
+        MetadataExporter::new(template, &path).spawn(metadata.clone());
+        println!("Exporting now-playing metadata to {}", path);
+    }
 
-    // Spawn playback thread (not tokio task, since CpalOutput is !Send)
-    let playback_handle = std::thread::spawn(move || {
-        let mut output: Option<CpalOutput> = None;
+    println!(
+        "Player config: min_lead={}ms, start_buffer={}ms",
+        player_config.profile.min_lead.as_millis(),
+        player_config.profile.start_buffer.as_millis()
+    );
 
-        loop {
-            if let Some(buffer) = scheduler_clone.next_ready() {
-                // Lazily initialize output when first buffer arrives
-                if output.is_none() {
-                    match CpalOutput::new(buffer.format.clone()) {
-                        Ok(out) => {
-                            println!("Audio output initialized");
-                            output = Some(out);
-                        }
-                        Err(e) => {
-                            log::error!("Failed to create audio output: {}", e);
-                            break;
-                        }
-                    }
-                }
+    let mut player = Player::new(player_config);
+    if let Some(ref audit) = audit {
+        player = player.with_audit(audit.clone());
+    }
 
-                if let Some(ref mut out) = output {
-                    if let Err(e) = out.write(&buffer.samples) {
-                        log::error!("Output error: {}", e);
-                    }
+    // Optional A/V sync diagnostics from the output callback, and the tone check
+    let log_output_timing = env_bool("SS_LOG_OUTPUT_TIMING");
+    let output_clock = Arc::clone(&clock_sync);
+    let tone = Arc::new(Mutex::new(None::<ToneVerifier>));
+    let output_tone = Arc::clone(&tone);
+    let verify_tone = args.verify_tone;
+    player = player.with_output(move |format| {
+        let out = CpalOutput::new(format.clone())?;
+        if log_output_timing {
+            let clock = Arc::clone(&output_clock);
+            let mut last_log = Instant::now();
+            out.set_timing_callback(move |timing| {
+                if last_log.elapsed() < Duration::from_secs(1) {
+                    return;
                 }
+                last_log = Instant::now();
+                let error = clock
+                    .try_lock()
+                    .ok()
+                    .and_then(|sync| timing.sync_error_micros(&sync));
+                println!(
+                    "Output callback at server ts={:?} frames={} delay={:?} sync_error={:?}µs",
+                    timing.server_timestamp, timing.frames, timing.playback_delay, error
+                );
+            });
+        }
+        if let Some(frequency) = verify_tone {
+            match ToneVerifier::new(frequency, format.sample_rate, format.channels) {
+                Ok(verifier) => *output_tone.lock().unwrap() = Some(verifier),
+                Err(e) => log::error!("Tone check disabled: {}", e),
             }
-            // Per spec: 1ms polling to reduce enqueue jitter
-            std::thread::sleep(Duration::from_millis(1));
         }
+        Ok(Box::new(CheckedOutput {
+            inner: out,
+            tone: Arc::clone(&output_tone),
+        }) as Box<dyn AudioOutput>)
     });
 
-    // Configuration from environment variables
-    let min_lead_ms = env_u64("SS_PLAY_MIN_LEAD_MS", 200);
-    let start_buffer_ms = env_u64("SS_PLAY_START_BUFFER_MS", 500);
-    let log_lead = env_bool("SS_LOG_LEAD");
-
-    println!(
-        "Player config: min_lead={}ms, start_buffer={}ms, log_lead={}",
-        min_lead_ms, start_buffer_ms, log_lead
-    );
-
-    // Message handling variables
-    let mut decoder: Option<PcmDecoder> = None;
-    let mut audio_format: Option<AudioFormat> = None;
-    let mut endian_locked: Option<PcmEndian> = None; // Auto-detect on first chunk
-    let mut buffered_duration_us: u64 = 0; // Track buffered audio duration in microseconds
-    let mut playback_started = false; // Track if we've started playback
-    let mut next_play_time: Option<Instant> = None; // Track when next chunk should play
-    let mut first_chunk_logged = false; // Track if we've logged the first chunk
-
-    loop {
-        // Process messages and audio chunks concurrently
-        tokio::select! {
-            Some(msg) = message_rx.recv() => {
-                match msg {
-                    Message::StreamStart(stream_start) => {
-                        if let Some(ref player_config) = stream_start.player {
-                            println!(
-                                "Stream starting: codec='{}' {}Hz {}ch {}bit",
-                                player_config.codec,
-                                player_config.sample_rate,
-                                player_config.channels,
-                                player_config.bit_depth
-                            );
-
-                            // Validate codec before proceeding
-                            if player_config.codec != "pcm" {
-                                log::error!("ERROR: Unsupported codec '{}' - only 'pcm' is supported!", player_config.codec);
-                                log::error!("Server is sending compressed audio that we can't decode!");
-                                continue;
-                            }
-
-                            if player_config.bit_depth != 16 && player_config.bit_depth != 24 {
-                                log::error!("ERROR: Unsupported bit depth {} - only 16 or 24-bit PCM supported!", player_config.bit_depth);
-                                continue;
-                            }
-
-                            audio_format = Some(AudioFormat {
-                                codec: Codec::Pcm,
-                                sample_rate: player_config.sample_rate,
-                                channels: player_config.channels,
-                                bit_depth: player_config.bit_depth,
-                                codec_header: None,
-                            });
-
-                            // Decoder will be created on first chunk after auto-detecting endianness
-                            decoder = None;
-                            endian_locked = None;
-                            buffered_duration_us = 0; // Reset on new stream
-                            playback_started = false;
-                            next_play_time = None;
-                            first_chunk_logged = false; // Reset for new stream
-                            println!("Waiting for first audio chunk to auto-detect endianness...");
-                        } else {
-                            println!("Received stream/start without player config");
-                        }
-                    }
-                    Message::ServerTime(server_time) => {
-                        // Get t4 (client receive time) in Unix microseconds
-                        let t4 = SystemTime::now()
-                            .duration_since(UNIX_EPOCH)
-                            .unwrap()
-                            .as_micros() as i64;
-
-                        // Update clock sync with all four timestamps
-                        let t1 = server_time.client_transmitted;
-                        let t2 = server_time.server_received;
-                        let t3 = server_time.server_transmitted;
-
-                        clock_sync.lock().await.update(t1, t2, t3, t4);
-
-                        // Log sync quality
-                        let sync = clock_sync.lock().await;
-                        if let Some(rtt) = sync.rtt_micros() {
-                            let quality = sync.quality();
-                            println!(
-                                "Clock sync updated: RTT={:.2}ms, quality={:?}",
-                                rtt as f64 / 1000.0,
-                                quality
-                            );
-                        }
-                    }
-                    _ => {
-                        println!("Received message: {:?}", msg);
-                    }
+    let mut events = player.events();
+    tokio::spawn(async move {
+        while let Some(event) = events.recv().await {
+            match event {
+                PlayerEvent::StreamStarted(format) => {
+                    println!(
+                        "Stream starting: {:?} {}Hz {}ch {}bit",
+                        format.codec, format.sample_rate, format.channels, format.bit_depth
+                    );
+                    println!("Active streams: {}", streams.current());
                 }
-            }
-            Some(chunk) = audio_rx.recv() => {
-                // Log first chunk bytes for diagnostics
-                if !first_chunk_logged {
-                    println!("\n=== FIRST AUDIO CHUNK DIAGNOSTICS ===");
-                    println!("Chunk timestamp: {} µs", chunk.timestamp);
-                    println!("Chunk data length: {} bytes", chunk.data.len());
-                    let preview_len = chunk.data.len().min(32);
-                    print!("First {} bytes (hex): ", preview_len);
-                    for byte in &chunk.data[..preview_len] {
-                        print!("{:02X} ", byte);
-                    }
-                    println!("\n=====================================\n");
-                    first_chunk_logged = true;
+                PlayerEvent::UnsupportedFormat(reason) => {
+                    println!("Cannot play stream: {}", reason);
                 }
-
-                if let Some(ref fmt) = audio_format {
-                    // Frame sanity check
-                    let bytes_per_sample = match fmt.bit_depth {
-                        16 => 2,
-                        24 => 3,
-                        _ => {
-                            log::warn!("Unsupported bit depth: {}", fmt.bit_depth);
-                            continue;
-                        }
-                    } as usize;
-                    let frame_size = bytes_per_sample * fmt.channels as usize;
-
-                    if chunk.data.len() % frame_size != 0 {
-                        log::error!(
-                            "BAD FRAME: {} bytes not multiple of frame size {} ({}-bit, {}ch)",
-                            chunk.data.len(), frame_size, fmt.bit_depth, fmt.channels
-                        );
-                        continue; // Don't decode garbage
-                    }
-
-                    // One-time endianness setup on first chunk
-                    // Per spec: macOS and most systems use Little-Endian PCM
-                    // Only use Big-Endian if explicitly signaled by server
-                    if endian_locked.is_none() {
-                        // Default to Little-Endian (standard for macOS/Windows/Linux)
-                        let endian = PcmEndian::Little;
-                        endian_locked = Some(endian);
-                        decoder = Some(PcmDecoder::with_endian(fmt.bit_depth, endian));
-                        println!("Using Little-Endian PCM (standard for modern systems)");
+                PlayerEvent::PlaybackStarted { buffered } => println!(
+                    "Prebuffering complete ({:.1}ms buffered), starting playback!",
+                    buffered.as_secs_f64() * 1000.0
+                ),
+                PlayerEvent::StreamEnded => println!("Stream ended"),
+                PlayerEvent::StreamCleared => println!("Buffers cleared"),
+                PlayerEvent::OutputOpened(_) => println!("Audio output initialized"),
+                PlayerEvent::DecodeError(_) => {}
+                PlayerEvent::SyncStateChanged(state) => {
+                    println!("Reported sync state {:?} to the server", state);
+                }
+                PlayerEvent::Latency(report) => {
+                    println!(
+                        "Latency: mean={:.1}ms max={:.1}ms target={}ms over_target={} dropped_late={}{}",
+                        report.mean.as_secs_f64() * 1000.0,
+                        report.max.as_secs_f64() * 1000.0,
+                        report.target.as_millis(),
+                        report.over_target,
+                        report.dropped,
+                        if report.meets_target() { "" } else { " (target missed)" }
+                    );
+                    if let Some(ref verifier) = *tone.lock().unwrap() {
+                        println!("Tone check: {}", verifier.report());
                     }
                 }
-
-                if let (Some(ref dec), Some(ref fmt)) = (&decoder, &audio_format) {
-                    match dec.decode(&chunk.data) {
-                        Ok(samples) => {
-                            // Calculate chunk duration in microseconds
-                            // samples.len() includes all channels
-                            let frames = samples.len() / fmt.channels as usize;
-                            let duration_micros = (frames as u64 * 1_000_000) / fmt.sample_rate as u64;
-                            let duration = Duration::from_micros(duration_micros);
-
-                            // Try to use clock sync to determine play_at time
+                PlayerEvent::Message(msg) => {
+                    metadata.apply(&msg);
+                    match *msg {
+                        Message::ServerTime(_) => {
+                            // The client already applied the reply; log sync quality
                             let sync = clock_sync.lock().await;
-                            let play_at = if let Some(instant) = sync.server_to_local_instant(chunk.timestamp) {
-                                // Clock sync is ready, use synchronized timestamp
-                                instant
-                            } else {
-                                // No clock sync yet, fall back to continuous scheduling
-                                if next_play_time.is_none() {
-                                    // Start from now + initial buffer
-                                    next_play_time = Some(Instant::now() + Duration::from_millis(start_buffer_ms));
-                                }
-                                let play_time = next_play_time.unwrap();
-                                next_play_time = Some(play_time + duration);
-                                play_time
-                            };
-                            drop(sync); // Release lock
-
-                            // Add safety window: ensure we never schedule in the past
-                            // Per spec: minimum lead (env SS_PLAY_MIN_LEAD_MS) to prevent late-chunk drops
-                            let min_lead = Duration::from_millis(min_lead_ms);
-                            let now = Instant::now();
-                            let play_at = if play_at <= now + min_lead {
-                                now + min_lead
-                            } else {
-                                play_at
-                            };
-
-                            // Track buffered duration
-                            buffered_duration_us += duration_micros;
-
-                            // Check if we've buffered enough to start playback
-                            if !playback_started && buffered_duration_us >= start_buffer_ms * 1000 {
-                                playback_started = true;
-                                println!(
-                                    "Prebuffering complete ({:.1}ms buffered), starting playback!",
-                                    buffered_duration_us as f64 / 1000.0
-                                );
-                            }
-
-                            // Track and log lead time
-                            let lead = play_at.saturating_duration_since(Instant::now());
-                            let lead_us = lead.as_micros() as u64;
-                            if log_lead {
+                            if let Some(rtt) = sync.rtt_micros() {
                                 println!(
-                                    "Enqueued chunk ts={} lead={}µs ({:.1}ms) buffered={:.1}ms len={} bytes",
-                                    chunk.timestamp,
-                                    lead_us,
-                                    lead_us as f64 / 1000.0,
-                                    buffered_duration_us as f64 / 1000.0,
-                                    chunk.data.len()
+                                    "Clock sync updated: RTT={:.2}ms, quality={:?}",
+                                    rtt as f64 / 1000.0,
+                                    sync.quality()
                                 );
                             }
-
-                            let buffer = AudioBuffer {
-                                timestamp: chunk.timestamp,
-                                play_at,
-                                samples,
-                                format: fmt.clone(),
-                            };
-
-                            scheduler.schedule(buffer);
                         }
-                        Err(e) => {
-                            log::error!("Decode error: {}", e);
+                        Message::ServerGoodbye(goodbye) => {
+                            println!("Server said goodbye ({:?}), disconnecting", goodbye.reason);
                         }
+                        Message::StreamStart(_) | Message::StreamEnd(_) => {}
+                        msg => println!("Received message: {:?}", msg),
                     }
                 }
             }
-            else => {
-                // Both channels closed
-                break;
+        }
+    });
+
+    tokio::select! {
+        result = player.run(client) => result?,
+        _ = tokio::signal::ctrl_c() => {
+            if let Err(e) = sender.close(GoodbyeReason::Shutdown).await {
+                log::warn!("Failed to say goodbye: {}", e);
             }
         }
     }
-
-    // Note: playback_handle will be cleaned up when program exits
-    // We don't join() here since the thread runs an infinite loop
-    drop(playback_handle);
     Ok(())
 }
+
+/// Speaker output that also feeds the optional tone check
+struct CheckedOutput {
+    inner: CpalOutput,
+    tone: Arc<Mutex<Option<ToneVerifier>>>,
+}
+
+impl AudioOutput for CheckedOutput {
+    fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), sendspin::Error> {
+        if let Some(ref mut verifier) = *self.tone.lock().unwrap() {
+            verifier.push(samples);
+        }
+        self.inner.write(samples)
+    }
+
+    fn write_timed(
+        &mut self,
+        timestamp: i64,
+        samples: &Arc<[Sample]>,
+    ) -> Result<(), sendspin::Error> {
+        if let Some(ref mut verifier) = *self.tone.lock().unwrap() {
+            verifier.push(samples);
+        }
+        self.inner.write_timed(timestamp, samples)
+    }
+
+    fn latency_micros(&self) -> u64 {
+        self.inner.latency_micros()
+    }
+
+    fn format(&self) -> &AudioFormat {
+        self.inner.format()
+    }
+}
@@ -0,0 +1,137 @@
+// ABOUTME: Interactive time-travel debugger for captured audit logs
+// ABOUTME: Replays scheduling decisions and answers why a chunk was dropped or delayed
+
+use clap::Parser;
+use sendspin::audit::{CapturedSession, Outcome, Replay, ReplayConfig};
+use std::io::{BufRead, Write};
+use std::time::Duration;
+
+/// Sendspin scheduling replay
+#[derive(Parser, Debug)]
+#[command(name = "replay")]
+#[command(about = "Replay an audit log through the scheduler and inspect its decisions", long_about = None)]
+struct Args {
+    /// Audit log written by the player on SIGUSR1 (SS_AUDIT_DUMP)
+    dump: String,
+
+    /// Override the recorded late policy (milliseconds, 0 plays everything)
+    #[arg(long)]
+    max_late_ms: Option<u64>,
+
+    /// Scheduler poll interval (microseconds)
+    #[arg(long, default_value_t = 1000)]
+    poll_us: u64,
+
+    /// Run these commands instead of reading from stdin
+    #[arg(short, long)]
+    command: Vec<String>,
+}
+
+const HELP: &str = "\
+commands:
+  summary          counts of played, delayed, and dropped chunks
+  drops            list dropped chunks
+  late [ms]        list chunks played more than ms late (default 5)
+  why <ts>         explain what happened to the chunk with server timestamp ts
+  at <seconds>     decision inputs at a point in the capture
+  help             show this help
+  quit             exit";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let session = CapturedSession::load(&args.dump)?;
+
+    let mut config = ReplayConfig::from_session(&session)
+        .with_poll_interval(Duration::from_micros(args.poll_us));
+    if let Some(ms) = args.max_late_ms {
+        config = config.with_max_late((ms > 0).then(|| Duration::from_millis(ms)));
+    }
+    let replay = Replay::run(&session, config);
+    println!(
+        "Replayed {} entries, {} chunks over {:.3}s",
+        session.entries.len(),
+        replay.chunks().len(),
+        replay.end() as f64 / 1_000_000.0
+    );
+
+    if !args.command.is_empty() {
+        for command in &args.command {
+            println!("> {}", command);
+            run_command(&replay, command);
+        }
+        return Ok(());
+    }
+
+    println!("{}", HELP);
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            break;
+        }
+        if !run_command(&replay, line.trim()) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Run one command; returns false to exit
+fn run_command(replay: &Replay, line: &str) -> bool {
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (None, _) => {}
+        (Some("quit" | "exit" | "q"), _) => return false,
+        (Some("help" | "?"), _) => println!("{}", HELP),
+        (Some("summary"), _) => {
+            let played = replay
+                .chunks()
+                .iter()
+                .filter(|c| matches!(c.outcome, Outcome::Played { .. }))
+                .count();
+            println!(
+                "played={} delayed(>5ms)={} dropped={} pending={}",
+                played,
+                replay.delayed(Duration::from_millis(5)).count(),
+                replay.dropped().count(),
+                replay
+                    .chunks()
+                    .iter()
+                    .filter(|c| c.outcome == Outcome::Pending)
+                    .count()
+            );
+        }
+        (Some("drops"), _) => {
+            for chunk in replay.dropped() {
+                println!("{}", chunk);
+            }
+        }
+        (Some("late"), threshold) => {
+            let ms = threshold.and_then(|t| t.parse().ok()).unwrap_or(5);
+            for chunk in replay.delayed(Duration::from_millis(ms)) {
+                println!("{}", chunk);
+            }
+        }
+        (Some("why"), Some(ts)) => match ts.parse().ok().and_then(|ts| replay.explain(ts)) {
+            Some(chunk) => {
+                println!("{}", chunk);
+                if let Some(at) = chunk.recorded_output_at {
+                    println!("  recorded output at {:.6}s", at as f64 / 1_000_000.0);
+                }
+                println!("when scheduled:\n{}", replay.inputs_at(chunk.scheduled_at));
+                if let Some(at) = chunk.decided_at {
+                    println!("when decided:\n{}", replay.inputs_at(at));
+                }
+            }
+            None => println!("no chunk with timestamp {}", ts),
+        },
+        (Some("at"), Some(secs)) => match secs.parse::<f64>() {
+            Ok(secs) => println!("{}", replay.inputs_at((secs * 1_000_000.0) as i64)),
+            Err(_) => println!("invalid time: {}", secs),
+        },
+        _ => println!("unknown command, try 'help'"),
+    }
+    true
+}
@@ -0,0 +1,183 @@
+// ABOUTME: Stream sender example
+// ABOUTME: Reads PCM from stdin, a file, or an HTTP radio stream and serves it to players
+
+use clap::Parser;
+use sendspin::protocol::PayloadKey;
+use sendspin::scheduler::LatencyProfile;
+use sendspin::server::{
+    parse_pcm_format, AccessControl, AccessRule, AudioSource, IcecastSource, ReaderSource, Server,
+    ServerConfig, SilenceConfig, SourceDecoders, ToneSource,
+};
+use std::time::Duration;
+
+/// Sendspin stream sender
+#[derive(Parser, Debug)]
+#[command(name = "send")]
+#[command(disable_version_flag = true)]
+#[command(about = "Serve raw PCM from stdin or a file to Sendspin players", long_about = None)]
+struct Args {
+    /// Input file (raw PCM, .wav, or .flac), http:// stream URL, tone[:HZ] test tone, or '-' for stdin
+    #[arg(short, long, default_value = "-")]
+    input: String,
+
+    /// Raw PCM format as <sample>,<rate>,<channels> (sample: s16le, s24le, s32le, or f32le)
+    #[arg(short, long, default_value = "s16le,48000,2")]
+    format: String,
+
+    /// Address to listen on
+    #[arg(short, long, default_value = "0.0.0.0:8927")]
+    bind: String,
+
+    /// Server name announced to players
+    #[arg(short, long, default_value = "Sendspin-RS Sender")]
+    name: String,
+
+    /// Latency profile: standard, or tv for lip-synced low-latency capture
+    #[arg(long, default_value = "standard")]
+    profile: String,
+
+    /// How far ahead of playback audio is sent (milliseconds, overrides the profile)
+    #[arg(long)]
+    lead_ms: Option<u64>,
+
+    /// End the stream after this many seconds of silence (0 disables)
+    #[arg(long, default_value_t = 0.0)]
+    silence_secs: f64,
+
+    /// Peak level below which input counts as silent (dBFS)
+    #[arg(long, default_value_t = -60.0, allow_hyphen_values = true)]
+    silence_threshold_db: f32,
+
+    /// Shared 64-hex-digit key to encrypt audio for players that offer it
+    #[arg(long)]
+    payload_key: Option<String>,
+
+    /// Only accept clients matching this rule: client:<id>, name:<name>, or ip:<addr>[/<prefix>] (repeatable)
+    #[arg(long)]
+    allow: Vec<String>,
+
+    /// Reject clients matching this rule (repeatable, checked before --allow)
+    #[arg(long)]
+    deny: Vec<String>,
+
+    /// Print version information and exit
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version, also print compiled-in features and detected audio devices
+    #[arg(long)]
+    verbose: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+
+    let args = Args::parse();
+    if args.version {
+        if args.verbose {
+            println!("{}", sendspin::diagnostics::report());
+        } else {
+            println!("{}", env!("CARGO_PKG_VERSION"));
+        }
+        return Ok(());
+    }
+    let format = parse_pcm_format(&args.format)?;
+
+    let profile = LatencyProfile::from_name(&args.profile)
+        .ok_or_else(|| format!("Unknown latency profile '{}'", args.profile))?;
+    let mut access = AccessControl::new();
+    for rule in &args.allow {
+        access = access.allow(rule.parse::<AccessRule>()?);
+    }
+    for rule in &args.deny {
+        access = access.deny(rule.parse::<AccessRule>()?);
+    }
+    let mut config = ServerConfig {
+        name: args.name,
+        silence: (args.silence_secs > 0.0).then(|| SilenceConfig {
+            threshold_db: args.silence_threshold_db,
+            hold: Duration::from_secs_f64(args.silence_secs),
+        }),
+        payload_key: args
+            .payload_key
+            .as_deref()
+            .map(PayloadKey::from_hex)
+            .transpose()?,
+        access,
+        ..ServerConfig::default()
+    }
+    .with_profile(&profile);
+    if let Some(lead_ms) = args.lead_ms {
+        config.buffer_lead = Duration::from_millis(lead_ms);
+    }
+    println!(
+        "Profile {}: {}ms chunks, {}ms lead, {}ms latency target",
+        args.profile,
+        config.chunk_duration.as_millis(),
+        config.buffer_lead.as_millis(),
+        profile.target_latency.as_millis()
+    );
+    let server = Server::bind(&args.bind, config).await?;
+    let handle = server.handle();
+
+    let input = args.input.clone();
+    let source: Box<dyn AudioSource> = tokio::task::spawn_blocking(move || {
+        let source: Box<dyn AudioSource> = if input == "-" {
+            Box::new(ReaderSource::new(std::io::stdin(), format)?)
+        } else if input.starts_with("http://") {
+            // Forward ICY stream titles to players as server/state metadata
+            let clock = handle.clock();
+            let source = IcecastSource::connect(&input, &SourceDecoders::default(), move |meta| {
+                handle.set_metadata(meta.to_metadata_state(clock.now_micros()))
+            })?;
+            if let Some(station) = source.station_name() {
+                println!("Station: {}", station);
+            }
+            Box::new(source)
+        } else if let Some(tone) = input.strip_prefix("tone") {
+            // Reference tone for `player --verify-tone`
+            let mut source = ToneSource::new(format)?;
+            if let Some(hz) = tone.strip_prefix(':') {
+                source = source.with_frequency(hz.parse()?)?;
+            }
+            println!("Streaming {}Hz reference tone", source.frequency());
+            Box::new(source)
+        } else if input.ends_with(".wav") {
+            Box::new(ReaderSource::from_wav(std::fs::File::open(&input)?)?)
+        } else if input.ends_with(".flac") {
+            flac_source(&input)?
+        } else {
+            Box::new(ReaderSource::new(std::fs::File::open(&input)?, format)?)
+        };
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(source)
+    })
+    .await??;
+
+    println!(
+        "Serving {} on ws://{}/sendspin",
+        args.input,
+        server.local_addr()
+    );
+
+    server.serve(source).await?;
+    println!("Input ended");
+
+    Ok(())
+}
+
+#[cfg(feature = "symphonia")]
+fn flac_source(
+    path: &str,
+) -> Result<Box<dyn AudioSource>, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(Box::new(sendspin::server::FlacSource::new(
+        std::fs::File::open(path)?,
+    )?))
+}
+
+#[cfg(not(feature = "symphonia"))]
+fn flac_source(
+    _path: &str,
+) -> Result<Box<dyn AudioSource>, Box<dyn std::error::Error + Send + Sync>> {
+    Err("FLAC input needs the symphonia feature".into())
+}
@@ -0,0 +1,863 @@
+// Generated from the sendspin Rust message types. Do not edit.
+
+/**
+ * Top-level protocol message envelope
+ */
+export type Message = {
+  payload: ClientHello;
+  type: "client/hello";
+} | {
+  payload: ServerHello;
+  type: "server/hello";
+} | {
+  payload: ClientTime;
+  type: "client/time";
+} | {
+  payload: ServerTime;
+  type: "server/time";
+} | {
+  payload: ClientState;
+  type: "client/state";
+} | {
+  payload: ServerState;
+  type: "server/state";
+} | {
+  payload: ServerCommand;
+  type: "server/command";
+} | {
+  payload: ClientCommand;
+  type: "client/command";
+} | {
+  payload: StreamStart;
+  type: "stream/start";
+} | {
+  payload: StreamEnd;
+  type: "stream/end";
+} | {
+  payload: StreamClear;
+  type: "stream/clear";
+} | {
+  payload: StreamRequestFormat;
+  type: "stream/request-format";
+} | {
+  payload: GroupUpdate;
+  type: "group/update";
+} | {
+  payload: ClientGoodbye;
+  type: "client/goodbye";
+} | {
+  payload: ServerGoodbye;
+  type: "server/goodbye";
+};
+
+/**
+ * Artwork format request
+ */
+export type ArtworkFormatRequest = {
+  /**
+   * Artwork channel to request
+   */
+  channel: number;
+  /**
+   * Preferred image format (jpeg, png, bmp)
+   */
+  format?: string | null;
+  /**
+   * Display height in pixels
+   */
+  media_height?: number | null;
+  /**
+   * Display width in pixels
+   */
+  media_width?: number | null;
+  /**
+   * Preferred image source
+   */
+  source?: string | null;
+};
+
+/**
+ * Artwork@v1 capabilities
+ */
+export type ArtworkV1Support = {
+  /**
+   * Supported artwork channels (0-3)
+   */
+  channels: Array<number>;
+};
+
+/**
+ * Audio format specification
+ */
+export type AudioFormatSpec = {
+  /**
+   * Bit depth per sample
+   */
+  bit_depth: number;
+  /**
+   * Number of audio channels
+   */
+  channels: number;
+  /**
+   * Codec name (e.g., "pcm", "opus", "flac")
+   */
+  codec: string;
+  /**
+   * Byte order of PCM samples; unstated means little-endian
+   */
+  endian?: PcmEndian | null;
+  /**
+   * Sample rate in Hz
+   */
+  sample_rate: number;
+};
+
+/**
+ * Client command message (controller commands to server)
+ */
+export type ClientCommand = {
+  /**
+   * Controller command
+   */
+  controller?: ControllerCommand | null;
+};
+
+/**
+ * Client goodbye message
+ */
+export type ClientGoodbye = {
+  /**
+   * Reason for disconnection
+   */
+  reason: GoodbyeReason;
+  /**
+   * Opt-in session quality summary
+   * 
+   * Extension field: servers that do not know it ignore it.
+   */
+  x_telemetry?: SessionTelemetry | null;
+};
+
+/**
+ * Client hello message
+ */
+export type ClientHello = {
+  /**
+   * Artwork capabilities (if client supports artwork@v1 role)
+   */
+  "artwork@v1_support"?: ArtworkV1Support | null;
+  /**
+   * Unique client identifier
+   */
+  client_id: string;
+  /**
+   * Controller capabilities (if client supports controller@v1 role)
+   */
+  "controller@v1_support"?: ControllerV1Support | null;
+  /**
+   * Device information (optional)
+   */
+  device_info?: DeviceInfo | null;
+  /**
+   * Human-readable client name
+   */
+  name: string;
+  /**
+   * Player capabilities (if client supports player@v1 role)
+   */
+  "player@v1_support"?: PlayerV1Support | null;
+  /**
+   * List of supported roles with versions (e.g., "player@v1", "controller@v1")
+   */
+  supported_roles: Array<Role>;
+  /**
+   * Protocol version number
+   */
+  version: number;
+  /**
+   * Visualizer capabilities (if client supports visualizer@v1 role)
+   */
+  "visualizer@v1_support"?: VisualizerV1Support | null;
+  /**
+   * Binary message encodings the client can use instead of JSON
+   * 
+   * Extension field: servers that do not know it ignore it.
+   */
+  x_encodings?: Array<string>;
+  /**
+   * Payload ciphers the client can decrypt
+   * 
+   * Extension field: servers that do not know it ignore it.
+   */
+  x_payload_encryption?: Array<string>;
+};
+
+/**
+ * Client state update message (wraps role-specific state)
+ */
+export type ClientState = {
+  /**
+   * Player state (if player role active)
+   */
+  player?: PlayerState | null;
+};
+
+/**
+ * Client time sync message
+ */
+export type ClientTime = {
+  /**
+   * Client transmission timestamp (Unix microseconds)
+   */
+  client_transmitted: number;
+};
+
+/**
+ * Connection reason enum
+ */
+export type ConnectionReason = "discovery" | "playback";
+
+/**
+ * Kind of content described by metadata
+ */
+export type ContentType = "music" | "podcast" | "audiobook" | "radio" | "other";
+
+/**
+ * Controller command from client
+ */
+export type ControllerCommand = {
+  /**
+   * Command to execute
+   */
+  command: ControllerCommandKind;
+  /**
+   * Optional mute state for mute command
+   */
+  mute?: boolean | null;
+  /**
+   * Relative offset in microseconds for seek command (negative seeks back)
+   */
+  offset?: number | null;
+  /**
+   * Target position in microseconds for seek command
+   */
+  position?: number | null;
+  /**
+   * Optional volume level (0-100) for volume command
+   */
+  volume?: number | null;
+  /**
+   * Target group for the join group command
+   */
+  x_group_id?: string | null;
+};
+
+export type ControllerCommandKind = "play" | "pause" | "stop" | "next" | "previous" | "volume" | "mute" | "seek" | "repeat_off" | "repeat_one" | "repeat_all" | "shuffle" | "unshuffle" | "switch" | "x_join_group" | "x_leave_group" | (string & {});
+
+/**
+ * Controller state from server
+ */
+export type ControllerState = {
+  /**
+   * Whether audio is muted
+   */
+  muted: boolean;
+  /**
+   * List of supported commands
+   */
+  supported_commands: Array<string>;
+  /**
+   * Current volume level (0-100)
+   */
+  volume: number;
+};
+
+/**
+ * Controller@v1 capabilities
+ */
+export type ControllerV1Support = {
+  /**
+   * Whether the controller shows now-playing metadata
+   */
+  displays_metadata?: boolean;
+  /**
+   * Commands the controller can send
+   */
+  supported_commands: Array<ControllerCommandKind>;
+};
+
+/**
+ * Device information (all fields optional per spec)
+ */
+export type DeviceInfo = {
+  /**
+   * Manufacturer name
+   */
+  manufacturer?: string | null;
+  /**
+   * Product name (e.g., "Sendspin-RS Player")
+   */
+  product_name?: string | null;
+  /**
+   * Software version string
+   */
+  software_version?: string | null;
+};
+
+/**
+ * FFT window function used for visualizer data
+ */
+export type FftWindow = "hann" | "hamming" | "blackman" | "rectangular" | "other";
+
+/**
+ * Goodbye reason
+ */
+export type GoodbyeReason = "another_server" | "shutdown" | "restart" | "user_request";
+
+/**
+ * A client in a group, as listed in group/update
+ */
+export type GroupMember = {
+  /**
+   * Client identifier
+   */
+  client_id: string;
+  /**
+   * Mute state last reported by the client
+   */
+  muted?: boolean | null;
+  /**
+   * Human-readable client name
+   */
+  name: string;
+  /**
+   * Volume last reported by the client (0-100)
+   */
+  volume?: number | null;
+};
+
+/**
+ * Group update notification
+ */
+export type GroupUpdate = {
+  /**
+   * Group identifier
+   */
+  group_id?: string | null;
+  /**
+   * Human-readable group name
+   */
+  group_name?: string | null;
+  /**
+   * Current playback state of the group
+   */
+  playback_state?: PlaybackState | null;
+  /**
+   * Clients currently in the group
+   */
+  x_members?: Array<GroupMember> | null;
+};
+
+/**
+ * A single timestamped lyric line
+ */
+export type LyricLine = {
+  /**
+   * Line text
+   */
+  text: string;
+  /**
+   * Track position at which the line starts (microseconds)
+   */
+  time: number;
+};
+
+/**
+ * Lyrics attached to track metadata
+ */
+export type Lyrics = {
+  /**
+   * Language tag (e.g., "en")
+   */
+  language?: string | null;
+  /**
+   * Timestamped lines, in order
+   */
+  lines?: Array<LyricLine>;
+  /**
+   * Unsynchronized lyrics text
+   */
+  text?: string | null;
+};
+
+/**
+ * Metadata state from server
+ */
+export type MetadataState = {
+  /**
+   * Album name
+   */
+  album?: string | null;
+  /**
+   * Artist name
+   */
+  artist?: string | null;
+  /**
+   * Artwork URL
+   */
+  artwork_url?: string | null;
+  /**
+   * Composer
+   */
+  composer?: string | null;
+  /**
+   * Kind of content playing
+   */
+  content_type?: ContentType | null;
+  /**
+   * Disc number within a multi-disc release
+   */
+  disc_number?: number | null;
+  /**
+   * Genre
+   */
+  genre?: string | null;
+  /**
+   * Lyrics for the current track
+   */
+  lyrics?: Lyrics | null;
+  /**
+   * Current track progress in microseconds
+   */
+  progress?: TrackProgress | null;
+  /**
+   * Repeat mode
+   */
+  repeat?: RepeatMode | null;
+  /**
+   * Shuffle state
+   */
+  shuffle?: boolean | null;
+  /**
+   * Where the content comes from (e.g., a streaming service or station name)
+   */
+  source?: string | null;
+  /**
+   * Server timestamp for progress calculation (microseconds)
+   */
+  timestamp: number;
+  /**
+   * Track title
+   */
+  title?: string | null;
+  /**
+   * Track number info (e.g., "3/12")
+   */
+  track?: string | null;
+  /**
+   * Release year
+   */
+  year?: number | null;
+};
+
+/**
+ * Payload encryption parameters confirmed by the server
+ */
+export type PayloadEncryptionParams = {
+  /**
+   * Cipher name (e.g., "xchacha20poly1305")
+   */
+  cipher: string;
+  /**
+   * Per-connection nonce salt, hex encoded
+   */
+  salt: string;
+};
+
+/**
+ * PCM endianness
+ */
+export type PcmEndian = "little" | "big";
+
+/**
+ * Group playback state
+ */
+export type PlaybackState = "playing" | "paused" | "stopped";
+
+/**
+ * Player-specific command from server
+ */
+export type PlayerCommand = {
+  /**
+   * Command to execute
+   */
+  command: PlayerCommandKind;
+  /**
+   * Optional mute state
+   */
+  mute?: boolean | null;
+  /**
+   * Relative offset in microseconds for seek command (negative seeks back)
+   */
+  offset?: number | null;
+  /**
+   * Target position in microseconds for seek command
+   */
+  position?: number | null;
+  /**
+   * Optional volume level (0-100)
+   */
+  volume?: number | null;
+};
+
+export type PlayerCommandKind = "play" | "pause" | "stop" | "volume" | "mute" | "seek" | (string & {});
+
+/**
+ * Player format request
+ */
+export type PlayerFormatRequest = {
+  /**
+   * Preferred bit depth
+   */
+  bit_depth?: number | null;
+  /**
+   * Preferred channel count
+   */
+  channels?: number | null;
+  /**
+   * Preferred codec
+   */
+  codec?: string | null;
+  /**
+   * Preferred byte order of PCM samples
+   */
+  endian?: PcmEndian | null;
+  /**
+   * Preferred sample rate
+   */
+  sample_rate?: number | null;
+};
+
+/**
+ * Player state
+ */
+export type PlayerState = {
+  /**
+   * Whether audio is muted
+   */
+  muted?: boolean | null;
+  /**
+   * Sync state: "synchronized" or "error"
+   */
+  state: PlayerSyncState;
+  /**
+   * Current volume level (0-100)
+   */
+  volume?: number | null;
+  /**
+   * Audio buffered ahead of playback, in milliseconds
+   * 
+   * Extension field; servers that do not know it ignore it.
+   */
+  x_buffered_ms?: number | null;
+};
+
+/**
+ * Player synchronization state
+ */
+export type PlayerSyncState = "synchronized" | "error";
+
+/**
+ * Player@v1 capabilities
+ */
+export type PlayerV1Support = {
+  /**
+   * Buffer capacity in chunks
+   */
+  buffer_capacity: number;
+  /**
+   * List of supported playback commands
+   */
+  supported_commands: Array<string>;
+  /**
+   * List of supported audio formats
+   */
+  supported_formats: Array<AudioFormatSpec>;
+};
+
+/**
+ * Repeat mode
+ */
+export type RepeatMode = "off" | "one" | "all";
+
+export type Role = "player@v1" | "artwork@v1" | "controller@v1" | "metadata@v1" | "visualizer@v1" | (string & {});
+
+/**
+ * Server command message (wraps role-specific commands)
+ */
+export type ServerCommand = {
+  /**
+   * Player command (if targeting player role)
+   */
+  player?: PlayerCommand | null;
+};
+
+/**
+ * Server goodbye message
+ */
+export type ServerGoodbye = {
+  /**
+   * Reason the server is disconnecting
+   */
+  reason: ServerGoodbyeReason;
+};
+
+/**
+ * Server goodbye reason
+ */
+export type ServerGoodbyeReason = "shutdown" | "restart" | "client_removed" | "other";
+
+/**
+ * Server hello message
+ */
+export type ServerHello = {
+  /**
+   * List of roles activated by server for this client
+   */
+  active_roles: Array<Role>;
+  /**
+   * Reason for connection: 'discovery' or 'playback'
+   */
+  connection_reason: ConnectionReason;
+  /**
+   * Human-readable server name
+   */
+  name: string;
+  /**
+   * Unique server identifier
+   */
+  server_id: string;
+  /**
+   * Protocol version number
+   */
+  version: number;
+  /**
+   * Message encoding both ends use after the hello exchange (JSON if absent)
+   * 
+   * Extension field, only sent when the server picked one of the client's encodings.
+   */
+  x_encoding?: string | null;
+  /**
+   * Payload encryption chosen for this connection's binary frames
+   * 
+   * Extension field, only sent when the client offered a cipher.
+   */
+  x_payload_encryption?: PayloadEncryptionParams | null;
+};
+
+/**
+ * Server state update message (metadata and controller info)
+ */
+export type ServerState = {
+  /**
+   * Controller state (supported commands, volume, etc.)
+   */
+  controller?: ControllerState | null;
+  /**
+   * Metadata state (track info, progress, etc.)
+   */
+  metadata?: MetadataState | null;
+};
+
+/**
+ * Server time sync response
+ */
+export type ServerTime = {
+  /**
+   * Original client transmission timestamp
+   */
+  client_transmitted: number;
+  /**
+   * Server reception timestamp (server loop microseconds)
+   */
+  server_received: number;
+  /**
+   * Server transmission timestamp (server loop microseconds)
+   */
+  server_transmitted: number;
+};
+
+/**
+ * Client-side playback quality over one session
+ */
+export type SessionTelemetry = {
+  /**
+   * Mean clock sync round-trip time in microseconds
+   */
+  avg_rtt_micros?: number | null;
+  /**
+   * Chunks dropped for arriving too late
+   */
+  dropped_chunks?: number;
+  /**
+   * Session length in seconds
+   */
+  duration_secs?: number;
+  /**
+   * Reconnects to the server during the session
+   */
+  reconnects?: number;
+  /**
+   * Times the output ran out of audio
+   */
+  underruns?: number;
+};
+
+/**
+ * Stream artwork configuration
+ */
+export type StreamArtworkConfig = {
+  /**
+   * Active artwork channels
+   */
+  channels: Array<number>;
+};
+
+/**
+ * Stream clear message (clear buffers)
+ */
+export type StreamClear = {
+  /**
+   * Roles for which buffers should be cleared (optional, all if not specified)
+   */
+  roles?: Array<Role> | null;
+};
+
+/**
+ * Stream end message
+ */
+export type StreamEnd = {
+  /**
+   * Roles for which streaming has ended (optional, all if not specified)
+   */
+  roles?: Array<Role> | null;
+};
+
+/**
+ * Stream player configuration
+ */
+export type StreamPlayerConfig = {
+  /**
+   * Bit depth per sample
+   */
+  bit_depth: number;
+  /**
+   * Number of audio channels
+   */
+  channels: number;
+  /**
+   * Audio codec name
+   */
+  codec: string;
+  /**
+   * Optional codec-specific header (base64 encoded)
+   */
+  codec_header?: string | null;
+  /**
+   * Byte order of PCM samples ("little" or "big"), if the server states it
+   */
+  endian?: PcmEndian | null;
+  /**
+   * Sample rate in Hz
+   */
+  sample_rate: number;
+};
+
+/**
+ * Stream format request from client
+ */
+export type StreamRequestFormat = {
+  /**
+   * Requested artwork format
+   */
+  artwork?: ArtworkFormatRequest | null;
+  /**
+   * Requested player format
+   */
+  player?: PlayerFormatRequest | null;
+};
+
+/**
+ * Stream start message
+ */
+export type StreamStart = {
+  /**
+   * Artwork stream configuration (optional - only if artwork role active)
+   */
+  artwork?: StreamArtworkConfig | null;
+  /**
+   * Player stream configuration (optional - only if player role active)
+   */
+  player?: StreamPlayerConfig | null;
+  /**
+   * Visualizer stream configuration (optional - only if visualizer role active)
+   */
+  visualizer?: StreamVisualizerConfig | null;
+};
+
+/**
+ * Stream visualizer configuration
+ * 
+ * Every field defaults when absent, so a partial or differently shaped block
+ * never rejects the stream/start carrying it; frames are then skipped.
+ */
+export type StreamVisualizerConfig = {
+  /**
+   * Number of FFT bins in each visualizer frame (0 if not stated)
+   */
+  bins?: number;
+  /**
+   * Upper edge of the last bin in Hz (0 if not stated)
+   */
+  max_frequency_hz?: number;
+  /**
+   * Lower edge of the first bin in Hz
+   */
+  min_frequency_hz?: number;
+  /**
+   * Visualizer frames per second (0 if not stated)
+   */
+  rate_hz?: number;
+  /**
+   * Window function applied before the FFT
+   */
+  window?: FftWindow;
+};
+
+/**
+ * Track progress information
+ */
+export type TrackProgress = {
+  /**
+   * Total duration in microseconds
+   */
+  duration: number;
+  /**
+   * Playback speed multiplier (1.0 = normal, 0.0 = paused)
+   */
+  playback_speed?: number | null;
+  /**
+   * Current position in microseconds
+   */
+  position: number;
+};
+
+/**
+ * Visualizer@v1 capabilities
+ */
+export type VisualizerV1Support = {
+  /**
+   * Buffer capacity for visualization data
+   */
+  buffer_capacity: number;
+};
@@ -0,0 +1,217 @@
+// ABOUTME: PCM format conversion
+// ABOUTME: Bit depth, endianness, channel count, and sample rate conversion between layouts
+
+use crate::audio::decode::PcmEndian;
+use crate::audio::resample::Linear;
+use crate::audio::{AudioFormat, Sample};
+use crate::error::Error;
+
+/// Layout of raw interleaved PCM bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmSpec {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of interleaved channels
+    pub channels: u8,
+    /// Bits per sample (8, 16, 24 or 32)
+    pub bit_depth: u8,
+    /// Byte order of each sample
+    pub endian: PcmEndian,
+}
+
+impl PcmSpec {
+    /// Describe the PCM layout of an audio format
+    pub fn from_format(format: &AudioFormat, endian: PcmEndian) -> Self {
+        Self {
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            bit_depth: format.bit_depth,
+            endian,
+        }
+    }
+}
+
+fn check_bit_depth(bit_depth: u8) -> Result<(), Error> {
+    match bit_depth {
+        8 | 16 | 24 | 32 => Ok(()),
+        other => Err(Error::Protocol(format!("Unsupported bit depth: {}", other))),
+    }
+}
+
+fn check_channels(channels: u8) -> Result<(), Error> {
+    if channels == 0 {
+        return Err(Error::Protocol(
+            "Channel count must be non-zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Decode raw PCM bytes into 24-bit samples
+///
+/// 8-bit PCM is unsigned, the other depths signed; 32-bit input drops its low
+/// byte. A trailing partial sample is ignored.
+pub fn to_samples(data: &[u8], bit_depth: u8, endian: PcmEndian) -> Result<Vec<Sample>, Error> {
+    check_bit_depth(bit_depth)?;
+    let samples = match (bit_depth, endian) {
+        (8, _) => data.iter().map(|&b| Sample::from_u8(b)).collect(),
+        (16, PcmEndian::Little) => data
+            .chunks_exact(2)
+            .map(|c| Sample::from_i16(i16::from_le_bytes([c[0], c[1]])))
+            .collect(),
+        (16, PcmEndian::Big) => data
+            .chunks_exact(2)
+            .map(|c| Sample::from_i16(i16::from_be_bytes([c[0], c[1]])))
+            .collect(),
+        (32, PcmEndian::Little) => data
+            .chunks_exact(4)
+            .map(|c| Sample::from_i32(i32::from_le_bytes([c[0], c[1], c[2], c[3]])))
+            .collect(),
+        (32, PcmEndian::Big) => data
+            .chunks_exact(4)
+            .map(|c| Sample::from_i32(i32::from_be_bytes([c[0], c[1], c[2], c[3]])))
+            .collect(),
+        (_, PcmEndian::Little) => data
+            .chunks_exact(3)
+            .map(|c| Sample::from_i24_le([c[0], c[1], c[2]]))
+            .collect(),
+        (_, PcmEndian::Big) => data
+            .chunks_exact(3)
+            .map(|c| Sample::from_i24_be([c[0], c[1], c[2]]))
+            .collect(),
+    };
+    Ok(samples)
+}
+
+/// Decode raw 32-bit float PCM bytes into 24-bit samples
+///
+/// Values outside -1.0..=1.0 are clipped and NaN becomes silence. A trailing
+/// partial sample is ignored.
+pub fn float_to_samples(data: &[u8], endian: PcmEndian) -> Vec<Sample> {
+    data.chunks_exact(4)
+        .map(|c| {
+            let bytes = [c[0], c[1], c[2], c[3]];
+            // NaN converts to zero
+            Sample::from_f32(match endian {
+                PcmEndian::Little => f32::from_le_bytes(bytes),
+                PcmEndian::Big => f32::from_be_bytes(bytes),
+            })
+        })
+        .collect()
+}
+
+/// Encode 24-bit samples as raw 32-bit float PCM bytes
+pub fn float_to_bytes(samples: &[Sample], endian: PcmEndian) -> Vec<u8> {
+    let mut out = Vec::with_capacity(samples.len() * 4);
+    for sample in samples {
+        let value = sample.clamp().0 as f32 / Sample::MAX.0 as f32;
+        out.extend_from_slice(&match endian {
+            PcmEndian::Little => value.to_le_bytes(),
+            PcmEndian::Big => value.to_be_bytes(),
+        });
+    }
+    out
+}
+
+/// Encode 24-bit samples as raw PCM bytes
+///
+/// Samples are clamped to the 24-bit range; 8 and 16-bit output truncate the low
+/// bits and 32-bit output pads with a zero low byte.
+pub fn to_bytes(samples: &[Sample], bit_depth: u8, endian: PcmEndian) -> Result<Vec<u8>, Error> {
+    check_bit_depth(bit_depth)?;
+    let width = bit_depth as usize / 8;
+    let mut out = Vec::with_capacity(samples.len() * width);
+    for sample in samples {
+        let sample = sample.clamp();
+        match (bit_depth, endian) {
+            (8, _) => out.push(sample.to_u8()),
+            (16, PcmEndian::Little) => out.extend_from_slice(&sample.to_i16().to_le_bytes()),
+            (16, PcmEndian::Big) => out.extend_from_slice(&sample.to_i16().to_be_bytes()),
+            (32, PcmEndian::Little) => out.extend_from_slice(&sample.to_i32().to_le_bytes()),
+            (32, PcmEndian::Big) => out.extend_from_slice(&sample.to_i32().to_be_bytes()),
+            (_, PcmEndian::Little) => out.extend_from_slice(&sample.0.to_le_bytes()[..3]),
+            (_, PcmEndian::Big) => out.extend_from_slice(&sample.0.to_be_bytes()[1..]),
+        }
+    }
+    Ok(out)
+}
+
+/// Reverse the byte order of every sample in place
+pub fn swap_endian(data: &mut [u8], bit_depth: u8) -> Result<(), Error> {
+    check_bit_depth(bit_depth)?;
+    for sample in data.chunks_exact_mut(bit_depth as usize / 8) {
+        sample.reverse();
+    }
+    Ok(())
+}
+
+/// Change the number of interleaved channels
+///
+/// Mono is duplicated to every output channel and any input is averaged down to
+/// mono. Otherwise the first channels are kept and extra output channels are silent.
+pub fn remix_channels(samples: &[Sample], from: u8, to: u8) -> Result<Vec<Sample>, Error> {
+    check_channels(from)?;
+    check_channels(to)?;
+    if from == to {
+        return Ok(samples.to_vec());
+    }
+
+    let (from, to) = (from as usize, to as usize);
+    let frames = samples.chunks_exact(from);
+    let mut out = Vec::with_capacity(frames.len() * to);
+    for frame in frames {
+        if to == 1 {
+            let sum: i64 = frame.iter().map(|s| s.0 as i64).sum();
+            out.push(Sample((sum / from as i64) as i32));
+        } else if from == 1 {
+            out.extend(std::iter::repeat_n(frame[0], to));
+        } else {
+            out.extend((0..to).map(|c| frame.get(c).copied().unwrap_or(Sample::ZERO)));
+        }
+    }
+    Ok(out)
+}
+
+/// Resample interleaved samples with linear interpolation
+///
+/// Each call is independent, so converting a stream chunk by chunk can produce small
+/// discontinuities at chunk boundaries; use a [`Resampler`](super::Resampler) for streams.
+/// The last input frame is held to fill out the final output frames.
+pub fn resample_linear(
+    samples: &[Sample],
+    channels: u8,
+    from_rate: u32,
+    to_rate: u32,
+) -> Result<Vec<Sample>, Error> {
+    check_channels(channels)?;
+    if from_rate == 0 || to_rate == 0 {
+        return Err(Error::Protocol("Sample rate must be non-zero".to_string()));
+    }
+    if from_rate == to_rate {
+        return Ok(samples.to_vec());
+    }
+
+    let channels = channels as usize;
+    let in_frames = samples.len() / channels;
+    if in_frames == 0 {
+        return Ok(Vec::new());
+    }
+    let out_frames = (in_frames as u64 * to_rate as u64 / from_rate as u64) as usize;
+    let mut out = Linear::default().process(samples, channels, from_rate, to_rate);
+    let last = &samples[(in_frames - 1) * channels..in_frames * channels];
+    while out.len() < out_frames * channels {
+        out.extend_from_slice(last);
+    }
+    Ok(out)
+}
+
+/// Convert raw PCM bytes from one layout to another
+///
+/// Applies bit depth, endianness, channel, and sample rate conversion as needed.
+pub fn convert(data: &[u8], from: &PcmSpec, to: &PcmSpec) -> Result<Vec<u8>, Error> {
+    check_bit_depth(to.bit_depth)?;
+    let samples = to_samples(data, from.bit_depth, from.endian)?;
+    let samples = remix_channels(&samples, from.channels, to.channels)?;
+    let samples = resample_linear(&samples, to.channels, from.sample_rate, to.sample_rate)?;
+    to_bytes(&samples, to.bit_depth, to.endian)
+}
@@ -0,0 +1,57 @@
+// ABOUTME: AAC codec header parsing for the symphonia decoder backend
+// ABOUTME: Parses the AudioSpecificConfig codec header; ADTS streams need no header
+
+use crate::error::Error;
+
+/// Sampling frequencies indexed by `samplingFrequencyIndex` (ISO 14496-3)
+const SAMPLE_RATES: [u32; 13] = [
+    96_000, 88_200, 64_000, 48_000, 44_100, 32_000, 24_000, 22_050, 16_000, 12_000, 11_025, 8_000,
+    7_350,
+];
+
+/// The AudioSpecificConfig of a raw AAC stream (ISO 14496-3, 1.6.2.1)
+///
+/// Servers send it base64 encoded as the `codec_header` of stream/start. Only
+/// the leading fields are parsed; the decoder gets the full bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AacConfig {
+    /// Audio object type (2 is AAC-LC, 5 is HE-AAC)
+    pub object_type: u8,
+    /// Core sample rate in Hz
+    pub sample_rate: u32,
+    /// Channel configuration; 0 means a program config element follows
+    pub channel_config: u8,
+}
+
+impl AacConfig {
+    /// Parse an AudioSpecificConfig
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let truncated = || Error::Protocol("AAC codec header is truncated".to_string());
+        let mut bits = bytes
+            .iter()
+            .flat_map(|b| (0..8).rev().map(move |i| (b >> i) & 1));
+        let mut read = |n: u32| -> Result<u32, Error> {
+            (0..n).try_fold(0, |acc, _| {
+                Ok(acc << 1 | bits.next().ok_or_else(truncated)? as u32)
+            })
+        };
+        let mut object_type = read(5)?;
+        if object_type == 31 {
+            object_type = 32 + read(6)?;
+        }
+        let index = read(4)? as usize;
+        let sample_rate = if index == 15 {
+            read(24)?
+        } else {
+            *SAMPLE_RATES.get(index).ok_or_else(|| {
+                Error::Protocol(format!("Reserved AAC sample rate index {}", index))
+            })?
+        };
+        let channel_config = read(4)? as u8;
+        Ok(Self {
+            object_type: object_type as u8,
+            sample_rate,
+            channel_config,
+        })
+    }
+}
@@ -0,0 +1,94 @@
+// ABOUTME: Apple Lossless (ALAC) codec header parsing for the symphonia decoder backend
+// ABOUTME: Parses the ALACSpecificConfig magic cookie, bare or inside MP4 atoms
+
+use crate::error::Error;
+
+/// The ALACSpecificConfig "magic cookie" describing an ALAC stream
+///
+/// Servers send it base64 encoded as the `codec_header` of stream/start, either
+/// bare (24 bytes) or inside the `alac` atom of an MP4 sample description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlacConfig {
+    /// Samples per channel in a full frame
+    pub frame_length: u32,
+    /// Bits per sample (16, 20, 24, or 32)
+    pub bit_depth: u8,
+    /// Rice history multiplier
+    pub pb: u8,
+    /// Rice initial history
+    pub mb: u8,
+    /// Rice parameter limit
+    pub kb: u8,
+    /// Number of channels
+    pub channels: u8,
+    /// Longest run of zeros
+    pub max_run: u16,
+    /// Largest frame in bytes (0 if unknown)
+    pub max_frame_bytes: u32,
+    /// Average bit rate (0 if unknown)
+    pub avg_bit_rate: u32,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+}
+
+/// The ALACSpecificConfig in a codec header, without `frma` and `alac` atom headers
+pub(crate) fn cookie(mut bytes: &[u8]) -> &[u8] {
+    for atom in [b"frma", b"alac"] {
+        if bytes.len() >= 12 && &bytes[4..8] == atom {
+            // frma holds a format code; alac holds version and flags
+            bytes = &bytes[12..];
+        }
+    }
+    bytes
+}
+
+impl AlacConfig {
+    /// Parse an ALACSpecificConfig, skipping `frma` and `alac` atom headers
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = cookie(bytes);
+        if bytes.len() < 24 {
+            return Err(Error::Protocol(
+                "ALAC codec header is truncated".to_string(),
+            ));
+        }
+        let u32_at =
+            |i: usize| u32::from_be_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        if bytes[4] != 0 {
+            return Err(Error::Protocol(format!(
+                "Unsupported ALAC version {}",
+                bytes[4]
+            )));
+        }
+        let config = Self {
+            frame_length: u32_at(0),
+            bit_depth: bytes[5],
+            pb: bytes[6],
+            mb: bytes[7],
+            kb: bytes[8],
+            channels: bytes[9],
+            max_run: u16::from_be_bytes([bytes[10], bytes[11]]),
+            max_frame_bytes: u32_at(12),
+            avg_bit_rate: u32_at(16),
+            sample_rate: u32_at(20),
+        };
+        if ![16, 20, 24, 32].contains(&config.bit_depth) {
+            return Err(Error::Protocol(format!(
+                "Unsupported ALAC bit depth {}",
+                config.bit_depth
+            )));
+        }
+        if config.channels == 0 || config.channels > 8 {
+            return Err(Error::Protocol(format!(
+                "Unsupported ALAC channel count {}",
+                config.channels
+            )));
+        }
+        if config.frame_length == 0 || config.frame_length > 1 << 16 {
+            return Err(Error::Protocol(format!(
+                "Unsupported ALAC frame length {}",
+                config.frame_length
+            )));
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,89 @@
+// ABOUTME: FLAC STREAMINFO parsing for the codec header of FLAC streams
+// ABOUTME: Accepts the bare block, the block with its header, or the fLaC stream start
+
+use crate::error::Error;
+
+/// Length of the STREAMINFO block body
+const STREAMINFO_LEN: usize = 34;
+
+/// The STREAMINFO metadata block of a FLAC stream
+///
+/// Servers send it base64 encoded as the `codec_header` of stream/start, bare
+/// or preceded by its metadata block header and optionally the `fLaC` marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlacStreamInfo {
+    /// Smallest block in the stream, in samples
+    pub min_block_size: u16,
+    /// Largest block in the stream, in samples
+    pub max_block_size: u16,
+    /// Smallest frame in bytes (0 if unknown)
+    pub min_frame_size: u32,
+    /// Largest frame in bytes (0 if unknown)
+    pub max_frame_size: u32,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: u8,
+    /// Bits per sample
+    pub bits_per_sample: u8,
+    /// Samples per channel in the stream (0 if unknown, as for live streams)
+    pub total_samples: u64,
+    /// MD5 of the decoded audio (all zero if unknown)
+    pub md5: [u8; 16],
+}
+
+/// The 34-byte STREAMINFO body in a codec header
+pub(crate) fn stream_info_block(bytes: &[u8]) -> Result<&[u8], Error> {
+    let bytes = bytes.strip_prefix(b"fLaC").unwrap_or(bytes);
+    let block = match bytes.len() {
+        STREAMINFO_LEN => bytes,
+        // Metadata block header: last-block flag and type 0, then a 24-bit length
+        len if len >= STREAMINFO_LEN + 4 && bytes[0] & 0x7F == 0 => {
+            let length = u32::from_be_bytes([0, bytes[1], bytes[2], bytes[3]]) as usize;
+            if length != STREAMINFO_LEN {
+                return Err(Error::Protocol(format!(
+                    "FLAC STREAMINFO block is {} bytes",
+                    length
+                )));
+            }
+            &bytes[4..4 + STREAMINFO_LEN]
+        }
+        _ => {
+            return Err(Error::Protocol(
+                "Codec header is not a FLAC STREAMINFO block".to_string(),
+            ))
+        }
+    };
+    Ok(block)
+}
+
+impl FlacStreamInfo {
+    /// Parse a STREAMINFO block
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let b = stream_info_block(bytes)?;
+        let packed = u64::from_be_bytes([b[10], b[11], b[12], b[13], b[14], b[15], b[16], b[17]]);
+        let info = Self {
+            min_block_size: u16::from_be_bytes([b[0], b[1]]),
+            max_block_size: u16::from_be_bytes([b[2], b[3]]),
+            min_frame_size: u32::from_be_bytes([0, b[4], b[5], b[6]]),
+            max_frame_size: u32::from_be_bytes([0, b[7], b[8], b[9]]),
+            sample_rate: (packed >> 44) as u32,
+            channels: ((packed >> 41) & 0x7) as u8 + 1,
+            bits_per_sample: ((packed >> 36) & 0x1F) as u8 + 1,
+            total_samples: packed & 0xF_FFFF_FFFF,
+            md5: b[18..34].try_into().expect("STREAMINFO is 34 bytes"),
+        };
+        if info.sample_rate == 0 {
+            return Err(Error::Protocol(
+                "FLAC STREAMINFO has no sample rate".to_string(),
+            ));
+        }
+        if info.max_block_size < 16 || info.min_block_size > info.max_block_size {
+            return Err(Error::Protocol(format!(
+                "FLAC STREAMINFO has bad block sizes {}..{}",
+                info.min_block_size, info.max_block_size
+            )));
+        }
+        Ok(info)
+    }
+}
@@ -1,17 +1,71 @@
 // ABOUTME: Audio decoder implementations
-// ABOUTME: PCM, Opus, FLAC decoders (Phase 1: PCM only)
+// ABOUTME: PCM, Opus, Vorbis, and symphonia-backed decoders behind a common Decoder trait
 
+/// AAC config parsing
+pub mod aac;
+/// ALAC config parsing
+pub mod alac;
+/// FLAC STREAMINFO parsing
+pub mod flac;
+/// Opus header parsing, and the libopus decoder with the "opus" feature
+pub mod opus;
 /// PCM decoder implementation
 pub mod pcm;
+/// Decode error events and recovery policy
+pub mod recovery;
+/// Compressed codecs through symphonia, with the "symphonia" feature
+#[cfg(feature = "symphonia")]
+pub mod symphonia;
+/// Vorbis header parsing, and the lewton decoder with the "vorbis" feature
+pub mod vorbis;
 
-pub use pcm::{PcmDecoder, PcmEndian};
+#[cfg(feature = "symphonia")]
+pub use self::symphonia::{SymphoniaDecoder, SYMPHONIA_CODECS};
+pub use aac::AacConfig;
+pub use alac::AlacConfig;
+pub use flac::FlacStreamInfo;
+#[cfg(feature = "opus")]
+pub use opus::OpusDecoder;
+pub use opus::OpusHead;
+pub use pcm::{detect_endian, AutoEndianPcmDecoder, PcmDecoder, PcmEndian};
+pub use recovery::{DecodeErrorEvent, DecodeErrorPolicy, DecodeErrorTracker, RecoveryAction};
+#[cfg(feature = "vorbis")]
+pub use vorbis::VorbisDecoder;
+pub use vorbis::VorbisHeaders;
 
 use crate::audio::Sample;
 use crate::error::Error;
 use std::sync::Arc;
 
+/// Codecs this build can decode
+pub const SUPPORTED_CODECS: &[&str] = &[
+    "pcm",
+    "pcm_float",
+    #[cfg(feature = "opus")]
+    "opus",
+    #[cfg(any(feature = "vorbis", feature = "symphonia"))]
+    "vorbis",
+    #[cfg(feature = "symphonia")]
+    "aac",
+    #[cfg(feature = "symphonia")]
+    "alac",
+    #[cfg(feature = "symphonia")]
+    "flac",
+    #[cfg(feature = "symphonia")]
+    "mp3",
+];
+
 /// Decoder trait for audio codecs
 pub trait Decoder {
     /// Decode raw audio data into samples
     fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error>;
+
+    /// Decode the next bytes of a stream whose chunks may split frames
+    ///
+    /// Decoders that buffer input keep an incomplete trailing frame and decode
+    /// it with the next call. The default decodes `data` on its own, for
+    /// decoders whose input is whole packets.
+    fn feed(&self, data: &[u8]) -> Result<Vec<Sample>, Error> {
+        Ok(self.decode(data)?.to_vec())
+    }
 }
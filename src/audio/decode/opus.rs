@@ -0,0 +1,215 @@
+// ABOUTME: Opus decoder backed by libopus through audiopus (feature "opus")
+// ABOUTME: Parses the OpusHead codec header for channel count, pre-skip, and output gain
+
+use crate::error::Error;
+
+/// Sample rate Opus timestamps and pre-skip are counted in
+pub const OPUS_RATE: u32 = 48_000;
+
+/// The OpusHead identification header (RFC 7845, section 5.1)
+///
+/// Servers send it base64 encoded as the `codec_header` of stream/start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpusHead {
+    /// Number of output channels
+    pub channels: u8,
+    /// Samples (at 48kHz) to discard from the start of the decoded stream
+    pub pre_skip: u16,
+    /// Sample rate of the original input, for information only
+    pub input_sample_rate: u32,
+    /// Gain to apply to the output, in Q7.8 dB
+    pub output_gain: i16,
+    /// Channel mapping family; 0 is mono or stereo in a single stream
+    pub mapping_family: u8,
+}
+
+impl OpusHead {
+    /// Parse an OpusHead packet
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < 19 || &bytes[..8] != b"OpusHead" {
+            return Err(Error::Protocol(
+                "Codec header is not an OpusHead".to_string(),
+            ));
+        }
+        // Only the major version (upper nibble) breaks compatibility
+        if bytes[8] >> 4 != 0 {
+            return Err(Error::Protocol(format!(
+                "Unsupported OpusHead version {}",
+                bytes[8]
+            )));
+        }
+        let head = Self {
+            channels: bytes[9],
+            pre_skip: u16::from_le_bytes([bytes[10], bytes[11]]),
+            input_sample_rate: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+            output_gain: i16::from_le_bytes([bytes[16], bytes[17]]),
+            mapping_family: bytes[18],
+        };
+        if head.channels == 0 {
+            return Err(Error::Protocol("OpusHead has no channels".to_string()));
+        }
+        Ok(head)
+    }
+}
+
+#[cfg(feature = "opus")]
+pub use decoder::OpusDecoder;
+
+#[cfg(feature = "opus")]
+mod decoder {
+    use super::{OpusHead, OPUS_RATE};
+    use crate::audio::decode::Decoder;
+    use crate::audio::{AudioFormat, Sample};
+    use crate::error::Error;
+    use audiopus::coder::{Decoder as RawDecoder, GenericCtl};
+    use audiopus::packet::Packet;
+    use audiopus::{Channels, MutSignals, SampleRate};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    /// Longest Opus packet: 120ms at 48kHz
+    const MAX_FRAME_SIZE: usize = 5760;
+
+    fn opus_error(context: &str, e: audiopus::Error) -> Error {
+        Error::Protocol(format!("{}: {}", context, e))
+    }
+
+    struct State {
+        decoder: RawDecoder,
+        /// Frames still to drop from the start of the stream
+        skip: usize,
+    }
+
+    /// Opus decoder using libopus through audiopus
+    ///
+    /// Each chunk holds one Opus packet. An empty chunk stands for a lost packet
+    /// and is concealed by libopus.
+    pub struct OpusDecoder {
+        channels: u8,
+        sample_rate: u32,
+        /// Frames of pre-skip at the decode rate
+        pre_skip: usize,
+        state: Mutex<State>,
+    }
+
+    impl OpusDecoder {
+        /// Create a decoder for a stream format
+        ///
+        /// The sample rate must be one Opus decodes at (8, 12, 16, 24, or 48kHz).
+        /// A `codec_header` holding an OpusHead sets the pre-skip and output gain;
+        /// only mono and stereo (mapping family 0) are supported.
+        pub fn new(format: &AudioFormat) -> Result<Self, Error> {
+            let rate = match format.sample_rate {
+                8_000 => SampleRate::Hz8000,
+                12_000 => SampleRate::Hz12000,
+                16_000 => SampleRate::Hz16000,
+                24_000 => SampleRate::Hz24000,
+                48_000 => SampleRate::Hz48000,
+                rate => return Err(Error::Protocol(format!("Opus cannot decode at {}Hz", rate))),
+            };
+            let head = format
+                .codec_header
+                .as_deref()
+                .map(OpusHead::parse)
+                .transpose()?;
+            if let Some(ref head) = head {
+                if head.mapping_family != 0 {
+                    return Err(Error::Protocol(format!(
+                        "Unsupported Opus channel mapping family {}",
+                        head.mapping_family
+                    )));
+                }
+                if head.channels != format.channels {
+                    return Err(Error::Protocol(format!(
+                        "OpusHead has {} channels, stream/start {}",
+                        head.channels, format.channels
+                    )));
+                }
+            }
+            let channels = match format.channels {
+                1 => Channels::Mono,
+                2 => Channels::Stereo,
+                channels => {
+                    return Err(Error::Protocol(format!(
+                        "Opus supports 1 or 2 channels, got {}",
+                        channels
+                    )))
+                }
+            };
+
+            let decoder = RawDecoder::new(rate, channels)
+                .map_err(|e| opus_error("Cannot create Opus decoder", e))?;
+            if let Some(gain) = head.as_ref().map(|h| h.output_gain).filter(|&g| g != 0) {
+                decoder
+                    .set_gain(gain as i32)
+                    .map_err(|e| opus_error("Cannot set Opus output gain", e))?;
+            }
+            let pre_skip = head.as_ref().map_or(0, |h| {
+                h.pre_skip as usize * format.sample_rate as usize / OPUS_RATE as usize
+            });
+            Ok(Self {
+                channels: format.channels,
+                sample_rate: format.sample_rate,
+                pre_skip,
+                state: Mutex::new(State {
+                    decoder,
+                    skip: pre_skip,
+                }),
+            })
+        }
+
+        /// Forget decoder history, e.g. after a seek; pre-skip applies again
+        pub fn reset(&self) {
+            let mut state = self.state.lock();
+            if let Err(e) = state.decoder.reset_state() {
+                log::warn!("Cannot reset Opus decoder: {}", e);
+            }
+            state.skip = self.pre_skip;
+        }
+    }
+
+    impl Decoder for OpusDecoder {
+        fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error> {
+            let channels = self.channels as usize;
+            let mut state = self.state.lock();
+            // libopus conceals a lost packet for as long as the output buffer,
+            // so size it to the previous packet, or 20ms before the first
+            let (packet, max_frames) = if data.is_empty() {
+                let last = state
+                    .decoder
+                    .last_packet_duration()
+                    .ok()
+                    .filter(|&frames| frames > 0)
+                    .unwrap_or(self.sample_rate / 50) as usize;
+                (None, last.min(MAX_FRAME_SIZE))
+            } else {
+                let packet =
+                    Packet::try_from(data).map_err(|e| opus_error("Bad Opus packet", e))?;
+                (Some(packet), MAX_FRAME_SIZE)
+            };
+            let mut pcm = vec![0f32; max_frames * channels];
+            let output = MutSignals::try_from(&mut pcm[..])
+                .map_err(|e| opus_error("Bad Opus output buffer", e))?;
+            let frames = state
+                .decoder
+                .decode_float(packet, output, false)
+                .map_err(|e| opus_error("Opus decode failed", e))?;
+            let skip = state.skip.min(frames);
+            state.skip -= skip;
+            Ok(pcm[skip * channels..frames * channels]
+                .iter()
+                .map(|&s| Sample::from_f32(s))
+                .collect())
+        }
+    }
+
+    impl std::fmt::Debug for OpusDecoder {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("OpusDecoder")
+                .field("channels", &self.channels)
+                .field("sample_rate", &self.sample_rate)
+                .field("pre_skip", &self.pre_skip)
+                .finish_non_exhaustive()
+        }
+    }
+}
@@ -1,13 +1,26 @@
 // ABOUTME: PCM decoder implementation
-// ABOUTME: Supports 16-bit and 24-bit PCM decoding with zero-copy where possible
+// ABOUTME: Supports 8-bit unsigned, 16/24/32-bit signed and 32-bit float PCM decoding
+// ABOUTME: Detects the byte order of integer PCM when not given; holds partial frames when fed
 
+use crate::audio::convert;
 use crate::audio::decode::Decoder;
 use crate::audio::Sample;
 use crate::error::Error;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// Frames needed before [`detect_endian`] gives an answer
+const DETECT_MIN_FRAMES: usize = 64;
+/// How much rougher the other byte order must decode to count as wrong
+const DETECT_RATIO: u128 = 4;
+/// Chunks [`AutoEndianPcmDecoder`] inspects before settling on little-endian
+const DETECT_CHUNKS: usize = 8;
+
 /// PCM endianness
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
 pub enum PcmEndian {
     /// Little-endian byte order
     Little,
@@ -15,73 +28,198 @@ pub enum PcmEndian {
     Big,
 }
 
-/// PCM audio decoder supporting 16-bit and 24-bit formats
+/// Bytes of a trailing partial frame, held until the rest arrives
+#[derive(Default)]
+struct Partial(Mutex<Vec<u8>>);
+
+impl Partial {
+    /// The held bytes and `data`, cut back to whole frames of `frame_size` bytes
+    fn whole_frames(&self, data: &[u8], frame_size: usize) -> Vec<u8> {
+        let mut held = self.0.lock();
+        held.extend_from_slice(data);
+        let whole = held.len() - held.len() % frame_size;
+        let rest = held.split_off(whole);
+        std::mem::replace(&mut *held, rest)
+    }
+}
+
+impl Clone for Partial {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().clone()))
+    }
+}
+
+/// PCM audio decoder supporting 8-bit unsigned, 16/24/32-bit signed and 32-bit float formats
+///
+/// `decode` ignores a trailing partial sample; `feed` keeps a trailing partial
+/// frame for the next call.
 #[derive(Clone)]
 pub struct PcmDecoder {
     bit_depth: u8,
     endian: PcmEndian,
+    float: bool,
+    channels: u8,
+    partial: Partial,
 }
 
 impl PcmDecoder {
-    /// Create a new PCM decoder with the specified bit depth (16 or 24), defaulting to little-endian
+    /// Create a new PCM decoder with the specified bit depth (8, 16, 24 or 32), defaulting to little-endian
     pub fn new(bit_depth: u8) -> Self {
+        Self::with_endian(bit_depth, PcmEndian::Little)
+    }
+
+    /// Create a new PCM decoder with explicit endianness
+    pub fn with_endian(bit_depth: u8, endian: PcmEndian) -> Self {
         Self {
             bit_depth,
-            endian: PcmEndian::Little,
+            endian,
+            float: false,
+            channels: 1,
+            partial: Partial::default(),
         }
     }
 
-    /// Create a new PCM decoder with explicit endianness
-    pub fn with_endian(bit_depth: u8, endian: PcmEndian) -> Self {
-        Self { bit_depth, endian }
+    /// Create a decoder for 32-bit float PCM (`pcm_float`), nominally -1.0..=1.0
+    pub fn float(endian: PcmEndian) -> Self {
+        Self {
+            bit_depth: 32,
+            endian,
+            float: true,
+            channels: 1,
+            partial: Partial::default(),
+        }
+    }
+
+    /// Set the number of interleaved channels, so `feed` holds back whole frames
+    pub fn with_channels(mut self, channels: u8) -> Self {
+        self.channels = channels.max(1);
+        self
+    }
+
+    fn samples(&self, data: &[u8]) -> Result<Vec<Sample>, Error> {
+        if self.float {
+            Ok(convert::float_to_samples(data, self.endian))
+        } else {
+            convert::to_samples(data, self.bit_depth, self.endian)
+        }
     }
 }
 
 impl Decoder for PcmDecoder {
     fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error> {
-        match (self.bit_depth, self.endian) {
-            (16, PcmEndian::Little) => {
-                // Convert 16-bit little-endian PCM to Sample
-                let samples: Vec<Sample> = data
-                    .chunks_exact(2)
-                    .map(|c| {
-                        let i16_val = i16::from_le_bytes([c[0], c[1]]);
-                        Sample::from_i16(i16_val)
-                    })
-                    .collect();
-                Ok(Arc::from(samples.into_boxed_slice()))
-            }
-            (16, PcmEndian::Big) => {
-                // Convert 16-bit big-endian PCM to Sample
-                let samples: Vec<Sample> = data
-                    .chunks_exact(2)
-                    .map(|c| {
-                        let i16_val = i16::from_be_bytes([c[0], c[1]]);
-                        Sample::from_i16(i16_val)
-                    })
-                    .collect();
-                Ok(Arc::from(samples.into_boxed_slice()))
-            }
-            (24, PcmEndian::Little) => {
-                // Convert 24-bit little-endian PCM to Sample
-                let samples: Vec<Sample> = data
-                    .chunks_exact(3)
-                    .map(|c| Sample::from_i24_le([c[0], c[1], c[2]]))
-                    .collect();
-                Ok(Arc::from(samples.into_boxed_slice()))
-            }
-            (24, PcmEndian::Big) => {
-                // Convert 24-bit big-endian PCM to Sample
-                let samples: Vec<Sample> = data
-                    .chunks_exact(3)
-                    .map(|c| Sample::from_i24_be([c[0], c[1], c[2]]))
-                    .collect();
-                Ok(Arc::from(samples.into_boxed_slice()))
-            }
-            _ => Err(Error::Protocol(format!(
-                "Unsupported bit depth: {}",
-                self.bit_depth
-            ))),
+        Ok(Arc::from(self.samples(data)?.into_boxed_slice()))
+    }
+
+    fn feed(&self, data: &[u8]) -> Result<Vec<Sample>, Error> {
+        let frame_size = (self.bit_depth as usize / 8).max(1) * self.channels as usize;
+        self.samples(&self.partial.whole_frames(data, frame_size))
+    }
+}
+
+/// Guess the byte order of integer PCM from how smoothly it decodes
+///
+/// Consecutive frames of real audio are close relative to their level, while
+/// the wrong byte order swaps high and low bytes and decodes to noise. Returns
+/// None for 8-bit data and other depths than 16, 24 and 32 bits, silence, and
+/// chunks too short or too noisy to tell.
+pub fn detect_endian(data: &[u8], bit_depth: u8, channels: u8) -> Option<PcmEndian> {
+    if !matches!(bit_depth, 16 | 24 | 32) || channels == 0 {
+        return None;
+    }
+    let channels = channels as usize;
+    if data.len() / (bit_depth as usize / 8 * channels) < DETECT_MIN_FRAMES {
+        return None;
+    }
+    // Differences between neighbouring samples of each channel, and the level
+    // they are measured against: the wrong byte order can also decode quieter
+    let roughness = |endian| -> Option<(u128, u128)> {
+        let samples = convert::to_samples(data, bit_depth, endian).ok()?;
+        let diff = samples
+            .iter()
+            .zip(&samples[channels..])
+            .map(|(a, b)| (b.0 as i64 - a.0 as i64).unsigned_abs() as u128)
+            .sum();
+        let level = samples.iter().map(|s| s.0.unsigned_abs() as u128).sum();
+        Some((diff, level))
+    };
+    let (little_diff, little_level) = roughness(PcmEndian::Little)?;
+    let (big_diff, big_level) = roughness(PcmEndian::Big)?;
+    // Compare diff / level without dividing
+    let little = little_diff * big_level;
+    let big = big_diff * little_level;
+    if little * DETECT_RATIO < big {
+        Some(PcmEndian::Little)
+    } else if big * DETECT_RATIO < little {
+        Some(PcmEndian::Big)
+    } else {
+        None
+    }
+}
+
+/// Integer PCM decoder for streams that do not state their byte order
+///
+/// Decodes little-endian, the protocol default, until [`detect_endian`] gives
+/// a clear answer for a chunk, and keeps little-endian if the first few chunks
+/// never do.
+pub struct AutoEndianPcmDecoder {
+    bit_depth: u8,
+    channels: u8,
+    state: Mutex<Detection>,
+    partial: Partial,
+}
+
+struct Detection {
+    endian: Option<PcmEndian>,
+    chunks: usize,
+}
+
+impl AutoEndianPcmDecoder {
+    /// Create a decoder for `channels` interleaved channels of `bit_depth`-bit PCM
+    pub fn new(bit_depth: u8, channels: u8) -> Self {
+        Self {
+            bit_depth,
+            channels,
+            state: Mutex::new(Detection {
+                endian: None,
+                chunks: 0,
+            }),
+            partial: Partial::default(),
         }
     }
+
+    /// The byte order settled on, if detection has finished
+    pub fn endian(&self) -> Option<PcmEndian> {
+        self.state.lock().endian
+    }
+
+    fn samples(&self, data: &[u8]) -> Result<Vec<Sample>, Error> {
+        let endian = {
+            let mut state = self.state.lock();
+            match state.endian {
+                Some(endian) => endian,
+                None => {
+                    state.chunks += 1;
+                    let detected = detect_endian(data, self.bit_depth, self.channels);
+                    if detected.is_some() || state.chunks >= DETECT_CHUNKS {
+                        let endian = detected.unwrap_or(PcmEndian::Little);
+                        log::debug!("PCM byte order: {:?} after {} chunks", endian, state.chunks);
+                        state.endian = Some(endian);
+                    }
+                    detected.unwrap_or(PcmEndian::Little)
+                }
+            }
+        };
+        convert::to_samples(data, self.bit_depth, endian)
+    }
+}
+
+impl Decoder for AutoEndianPcmDecoder {
+    fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error> {
+        Ok(Arc::from(self.samples(data)?.into_boxed_slice()))
+    }
+
+    fn feed(&self, data: &[u8]) -> Result<Vec<Sample>, Error> {
+        let frame_size = (self.bit_depth as usize / 8).max(1) * self.channels.max(1) as usize;
+        self.samples(&self.partial.whole_frames(data, frame_size))
+    }
 }
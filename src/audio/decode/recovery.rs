@@ -0,0 +1,140 @@
+// ABOUTME: Decode error reporting and recovery policy
+// ABOUTME: Typed decode error events and consecutive-error fallback to PCM
+
+use crate::audio::Codec;
+use crate::error::Error;
+use crate::protocol::messages::{Message, PlayerFormatRequest, StreamRequestFormat};
+
+/// What the caller should do with a chunk that failed to decode
+///
+/// [`DecodeErrorTracker`] only decides; the player pipeline carries the action out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Drop the chunk
+    Skipped,
+    /// Play silence of the chunk's duration in its place
+    Concealed,
+    /// Too many consecutive failures; the stream should be abandoned
+    StreamAborted,
+}
+
+/// Structured description of a decode failure
+#[derive(Debug, Clone)]
+pub struct DecodeErrorEvent {
+    /// Codec that failed to decode the chunk
+    pub codec: Codec,
+    /// Human-readable error cause
+    pub cause: String,
+    /// Server timestamp of the failed chunk (microseconds)
+    pub timestamp: i64,
+    /// Action to take for this chunk
+    pub action: RecoveryAction,
+    /// Number of consecutive failures including this one
+    pub consecutive: u32,
+}
+
+/// Policy controlling how decode failures are handled
+#[derive(Debug, Clone)]
+pub struct DecodeErrorPolicy {
+    /// Ask for failed chunks to be replaced with silence instead of dropped
+    pub conceal: bool,
+    /// Consecutive failures after which the stream is aborted
+    pub max_consecutive: u32,
+    /// Format to request from the server once the stream is aborted (None = no request)
+    pub fallback: Option<PlayerFormatRequest>,
+}
+
+impl Default for DecodeErrorPolicy {
+    fn default() -> Self {
+        Self {
+            conceal: false,
+            max_consecutive: 5,
+            fallback: Some(PlayerFormatRequest {
+                codec: Some("pcm".to_string()),
+                channels: None,
+                sample_rate: None,
+                bit_depth: None,
+                endian: None,
+            }),
+        }
+    }
+}
+
+/// Tracks consecutive decode failures and decides recovery actions
+#[derive(Debug, Clone)]
+pub struct DecodeErrorTracker {
+    policy: DecodeErrorPolicy,
+    consecutive: u32,
+}
+
+impl DecodeErrorTracker {
+    /// Create a tracker with the given policy
+    pub fn new(policy: DecodeErrorPolicy) -> Self {
+        Self {
+            policy,
+            consecutive: 0,
+        }
+    }
+
+    /// Record a successfully decoded chunk (resets the consecutive-error count)
+    pub fn record_success(&mut self) {
+        self.consecutive = 0;
+    }
+
+    /// Record a failed chunk and decide what to do with it
+    pub fn record_failure(
+        &mut self,
+        codec: Codec,
+        error: &Error,
+        timestamp: i64,
+    ) -> DecodeErrorEvent {
+        self.consecutive += 1;
+
+        let action =
+            if self.policy.max_consecutive > 0 && self.consecutive >= self.policy.max_consecutive {
+                RecoveryAction::StreamAborted
+            } else if self.policy.conceal {
+                RecoveryAction::Concealed
+            } else {
+                RecoveryAction::Skipped
+            };
+
+        DecodeErrorEvent {
+            codec,
+            cause: error.to_string(),
+            timestamp,
+            action,
+            consecutive: self.consecutive,
+        }
+    }
+
+    /// Build the `stream/request-format` message to send after an aborted stream
+    ///
+    /// Returns `None` if the event did not abort the stream or no fallback is configured.
+    pub fn fallback_request(&self, event: &DecodeErrorEvent) -> Option<Message> {
+        if event.action != RecoveryAction::StreamAborted {
+            return None;
+        }
+        let player = self.policy.fallback.clone()?;
+        Some(Message::StreamRequestFormat(StreamRequestFormat {
+            player: Some(player),
+            artwork: None,
+        }))
+    }
+
+    /// Get the current number of consecutive failures
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive
+    }
+
+    /// Reset state (e.g., on a new stream/start)
+    pub fn reset(&mut self) {
+        self.consecutive = 0;
+    }
+}
+
+impl Default for DecodeErrorTracker {
+    fn default() -> Self {
+        Self::new(DecodeErrorPolicy::default())
+    }
+}
@@ -0,0 +1,205 @@
+// ABOUTME: Decoder backend routing compressed codecs through symphonia (feature "symphonia")
+// ABOUTME: Maps stream formats and codec headers to symphonia codecs and converts their output
+
+use super::{alac, flac, AacConfig, AlacConfig, Decoder, FlacStreamInfo, VorbisHeaders};
+use crate::audio::{AudioFormat, Codec, Sample};
+use crate::error::Error;
+use ::symphonia::core::audio::{Channels, SampleBuffer};
+use ::symphonia::core::codecs::{
+    CodecParameters, CodecType, Decoder as CodecDecoder, DecoderOptions, CODEC_TYPE_AAC,
+    CODEC_TYPE_ALAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_VORBIS,
+};
+use ::symphonia::core::formats::Packet;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Codecs the symphonia backend decodes
+pub const SYMPHONIA_CODECS: &[Codec] = &[
+    Codec::Flac,
+    Codec::Mp3,
+    Codec::Aac,
+    Codec::Alac,
+    Codec::Vorbis,
+];
+
+fn codec_type(codec: Codec) -> Option<CodecType> {
+    Some(match codec {
+        Codec::Flac => CODEC_TYPE_FLAC,
+        Codec::Mp3 => CODEC_TYPE_MP3,
+        Codec::Aac => CODEC_TYPE_AAC,
+        Codec::Alac => CODEC_TYPE_ALAC,
+        Codec::Vorbis => CODEC_TYPE_VORBIS,
+        Codec::Pcm | Codec::PcmFloat | Codec::Opus => return None,
+    })
+}
+
+/// The codec header in the form symphonia expects as extra data
+fn extra_data(codec: Codec, header: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(match codec {
+        // Identification and setup packets back to back
+        Codec::Vorbis => {
+            let headers = VorbisHeaders::parse(header)?;
+            [headers.identification, headers.setup].concat()
+        }
+        // The bare 24-byte magic cookie
+        Codec::Alac => {
+            let cookie = alac::cookie(header);
+            cookie[..cookie.len().min(24)].to_vec()
+        }
+        // The STREAMINFO block, without the stream marker and block header
+        Codec::Flac => flac::stream_info_block(header)?.to_vec(),
+        _ => header.to_vec(),
+    })
+}
+
+/// Split ADTS-framed AAC into raw access units (None if `data` is not ADTS)
+fn adts_frames(mut data: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut frames = Vec::new();
+    while !data.is_empty() {
+        if data.len() < 7 || data[0] != 0xFF || data[1] & 0xF6 != 0xF0 {
+            return None;
+        }
+        let protection_absent = data[1] & 1 == 1;
+        let header = if protection_absent { 7 } else { 9 };
+        let length =
+            ((data[3] as usize & 0x03) << 11) | (data[4] as usize) << 3 | (data[5] as usize) >> 5;
+        if length < header || length > data.len() {
+            return None;
+        }
+        frames.push(&data[header..length]);
+        data = &data[length..];
+    }
+    Some(frames)
+}
+
+/// Decoder for every codec in [`SYMPHONIA_CODECS`], backed by symphonia
+///
+/// Each chunk holds one packet, except ADTS-framed AAC (sent without a
+/// `codec_header`), where a chunk may hold several frames. The `codec_header`
+/// is the AudioSpecificConfig for AAC, the magic cookie for ALAC, STREAMINFO
+/// for FLAC, and the Xiph-laced headers for Vorbis; MP3 needs none.
+pub struct SymphoniaDecoder {
+    codec: Codec,
+    channels: u8,
+    adts: bool,
+    decoder: Mutex<Box<dyn CodecDecoder>>,
+}
+
+impl SymphoniaDecoder {
+    /// Create a decoder for a stream format
+    pub fn new(format: &AudioFormat) -> Result<Self, Error> {
+        let codec_type = codec_type(format.codec).ok_or_else(|| {
+            Error::Protocol(format!(
+                "symphonia backend does not decode {}",
+                format.codec.name()
+            ))
+        })?;
+        if format.channels == 0 || format.channels > 8 {
+            return Err(Error::Protocol(format!(
+                "Unsupported channel count {}",
+                format.channels
+            )));
+        }
+        let mut params = CodecParameters::new();
+        params
+            .for_codec(codec_type)
+            .with_sample_rate(format.sample_rate)
+            .with_channels(Channels::from_bits_truncate((1 << format.channels) - 1));
+        if let Some(ref header) = format.codec_header {
+            match format.codec {
+                Codec::Flac => {
+                    let info = FlacStreamInfo::parse(header)?;
+                    if info.channels != format.channels || info.sample_rate != format.sample_rate {
+                        return Err(Error::Protocol(format!(
+                            "FLAC STREAMINFO says {}ch {}Hz, stream/start {}ch {}Hz",
+                            info.channels, info.sample_rate, format.channels, format.sample_rate
+                        )));
+                    }
+                }
+                Codec::Alac => {
+                    let config = AlacConfig::parse(header)?;
+                    if config.channels != format.channels {
+                        return Err(Error::Protocol(format!(
+                            "ALAC codec header says {} channels, stream/start {}",
+                            config.channels, format.channels
+                        )));
+                    }
+                }
+                Codec::Aac => {
+                    AacConfig::parse(header)?;
+                }
+                _ => {}
+            }
+            params.with_extra_data(extra_data(format.codec, header)?.into_boxed_slice());
+        }
+        let decoder = ::symphonia::default::get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .map_err(|e| {
+                Error::Protocol(format!(
+                    "Cannot create {} decoder: {}",
+                    format.codec.name(),
+                    e
+                ))
+            })?;
+        Ok(Self {
+            codec: format.codec,
+            channels: format.channels,
+            adts: format.codec == Codec::Aac && format.codec_header.is_none(),
+            decoder: Mutex::new(decoder),
+        })
+    }
+
+    /// Forget decoder history, e.g. after a seek
+    pub fn reset(&self) {
+        self.decoder.lock().reset();
+    }
+
+    fn decode_packet(
+        &self,
+        decoder: &mut dyn CodecDecoder,
+        packet: &[u8],
+        out: &mut Vec<Sample>,
+    ) -> Result<(), Error> {
+        let decoded = decoder
+            .decode(&Packet::new_from_slice(0, 0, 0, packet))
+            .map_err(|e| Error::Protocol(format!("{} decode failed: {}", self.codec.name(), e)))?;
+        let spec = *decoded.spec();
+        if spec.channels.count() != self.channels as usize {
+            return Err(Error::Protocol(format!(
+                "{} stream has {} channels, stream/start {}",
+                self.codec.name(),
+                spec.channels.count(),
+                self.channels
+            )));
+        }
+        let mut buffer = SampleBuffer::<i32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        out.extend(buffer.samples().iter().map(|&s| Sample(s >> 8)));
+        Ok(())
+    }
+}
+
+impl Decoder for SymphoniaDecoder {
+    fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error> {
+        let mut decoder = self.decoder.lock();
+        let mut samples = Vec::new();
+        match adts_frames(data).filter(|_| self.adts) {
+            Some(frames) => {
+                for frame in frames {
+                    self.decode_packet(decoder.as_mut(), frame, &mut samples)?;
+                }
+            }
+            None => self.decode_packet(decoder.as_mut(), data, &mut samples)?,
+        }
+        Ok(samples.into())
+    }
+}
+
+impl std::fmt::Debug for SymphoniaDecoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SymphoniaDecoder")
+            .field("codec", &self.codec)
+            .field("channels", &self.channels)
+            .finish_non_exhaustive()
+    }
+}
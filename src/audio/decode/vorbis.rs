@@ -0,0 +1,155 @@
+// ABOUTME: Vorbis decoder backed by the pure-Rust lewton crate (feature "vorbis")
+// ABOUTME: Splits the codec header into the identification, comment, and setup packets
+
+use crate::error::Error;
+
+/// The three Vorbis header packets a decoder needs before any audio
+///
+/// Servers send them base64 encoded as the `codec_header` of stream/start,
+/// packed with Xiph lacing as in Matroska's `CodecPrivate`: a byte holding the
+/// packet count minus one (2), the sizes of the first two packets as runs of
+/// 255 ended by a smaller byte, then the packets back to back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VorbisHeaders {
+    /// Identification header (packet type 1)
+    pub identification: Vec<u8>,
+    /// Comment header (packet type 3)
+    pub comment: Vec<u8>,
+    /// Setup header with the codebooks (packet type 5)
+    pub setup: Vec<u8>,
+}
+
+impl VorbisHeaders {
+    /// Split a Xiph-laced codec header into its three packets
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let invalid =
+            |what: &str| Error::Protocol(format!("Invalid Vorbis codec header: {}", what));
+        let (&count, mut rest) = bytes.split_first().ok_or_else(|| invalid("empty"))?;
+        if count != 2 {
+            return Err(invalid("expected 3 packets"));
+        }
+        let mut sizes = [0usize; 2];
+        for size in &mut sizes {
+            loop {
+                let (&byte, tail) = rest.split_first().ok_or_else(|| invalid("truncated"))?;
+                rest = tail;
+                *size += byte as usize;
+                if byte < 255 {
+                    break;
+                }
+            }
+        }
+        if sizes[0] + sizes[1] > rest.len() {
+            return Err(invalid("truncated"));
+        }
+        let (identification, rest) = rest.split_at(sizes[0]);
+        let (comment, setup) = rest.split_at(sizes[1]);
+        for (packet, kind) in [(identification, 1), (comment, 3), (setup, 5)] {
+            if packet.len() < 7 || packet[0] != kind || &packet[1..7] != b"vorbis" {
+                return Err(invalid(&format!("packet type {} missing", kind)));
+            }
+        }
+        Ok(Self {
+            identification: identification.to_vec(),
+            comment: comment.to_vec(),
+            setup: setup.to_vec(),
+        })
+    }
+
+    /// Channel count and sample rate from the identification header
+    pub fn channels_and_rate(&self) -> Result<(u8, u32), Error> {
+        let id = &self.identification;
+        if id.len() < 16 {
+            return Err(Error::Protocol(
+                "Vorbis identification header is truncated".to_string(),
+            ));
+        }
+        Ok((id[11], u32::from_le_bytes([id[12], id[13], id[14], id[15]])))
+    }
+}
+
+#[cfg(feature = "vorbis")]
+pub use decoder::VorbisDecoder;
+
+#[cfg(feature = "vorbis")]
+mod decoder {
+    use super::VorbisHeaders;
+    use crate::audio::decode::Decoder;
+    use crate::audio::{AudioFormat, Sample};
+    use crate::error::Error;
+    use lewton::audio::{read_audio_packet_generic, PreviousWindowRight};
+    use lewton::header::{
+        read_header_comment, read_header_ident, read_header_setup, IdentHeader, SetupHeader,
+    };
+    use lewton::samples::InterleavedSamples;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    /// Vorbis decoder using lewton
+    ///
+    /// The header packets come from the `codec_header` (see [`VorbisHeaders`]);
+    /// each chunk holds one audio packet. Channels are output in Vorbis order.
+    pub struct VorbisDecoder {
+        ident: IdentHeader,
+        setup: SetupHeader,
+        window: Mutex<PreviousWindowRight>,
+    }
+
+    impl VorbisDecoder {
+        /// Create a decoder from the headers in a stream format's `codec_header`
+        ///
+        /// Fails if the headers are missing or disagree with the stream format.
+        pub fn new(format: &AudioFormat) -> Result<Self, Error> {
+            let header = format
+                .codec_header
+                .as_deref()
+                .ok_or_else(|| Error::Protocol("Vorbis streams need a codec_header".to_string()))?;
+            let headers = VorbisHeaders::parse(header)?;
+            let (channels, rate) = headers.channels_and_rate()?;
+            if (channels, rate) != (format.channels, format.sample_rate) {
+                return Err(Error::Protocol(format!(
+                    "Vorbis header says {}ch {}Hz, stream/start {}ch {}Hz",
+                    channels, rate, format.channels, format.sample_rate
+                )));
+            }
+            let invalid = |e| Error::Protocol(format!("Invalid Vorbis header: {:?}", e));
+            let ident = read_header_ident(&headers.identification).map_err(invalid)?;
+            read_header_comment(&headers.comment).map_err(invalid)?;
+            let setup = read_header_setup(
+                &headers.setup,
+                ident.audio_channels,
+                (ident.blocksize_0, ident.blocksize_1),
+            )
+            .map_err(invalid)?;
+            Ok(Self {
+                ident,
+                setup,
+                window: Mutex::new(PreviousWindowRight::new()),
+            })
+        }
+
+        /// Forget decoder history, e.g. after a seek
+        pub fn reset(&self) -> Result<(), Error> {
+            *self.window.lock() = PreviousWindowRight::new();
+            Ok(())
+        }
+    }
+
+    impl Decoder for VorbisDecoder {
+        fn decode(&self, data: &[u8]) -> Result<Arc<[Sample]>, Error> {
+            let mut window = self.window.lock();
+            let decoded: InterleavedSamples<f32> =
+                read_audio_packet_generic(&self.ident, &self.setup, data, &mut window)
+                    .map_err(|e| Error::Protocol(format!("Vorbis decode failed: {:?}", e)))?;
+            Ok(decoded.samples.into_iter().map(Sample::from_f32).collect())
+        }
+    }
+
+    impl std::fmt::Debug for VorbisDecoder {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("VorbisDecoder")
+                .field("channels", &self.ident.audio_channels)
+                .finish_non_exhaustive()
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// ABOUTME: Volume ramps that avoid clicks when audio starts or stops
+// ABOUTME: Fades interleaved samples in or out along a linear or exponential curve
+
+use crate::audio::Sample;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Range of an exponential fade; quieter than this is silence
+const EXPONENTIAL_RANGE_DB: f32 = 60.0;
+
+/// Shape of a fade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeCurve {
+    /// Gain changes at a constant rate
+    #[default]
+    Linear,
+    /// Loudness changes at a constant rate (constant dB per second over 60 dB)
+    Exponential,
+}
+
+impl FadeCurve {
+    /// Gain at `position` (0 = silent, 1 = full) along the fade
+    pub fn gain(self, position: f32) -> f32 {
+        let position = position.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => position,
+            FadeCurve::Exponential if position <= 0.0 => 0.0,
+            FadeCurve::Exponential => 10f32.powf((position - 1.0) * EXPONENTIAL_RANGE_DB / 20.0),
+        }
+    }
+}
+
+/// How long a fade takes and its shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fade {
+    /// Time from silence to full volume; zero switches instantly
+    pub duration: Duration,
+    /// Shape of the ramp
+    pub curve: FadeCurve,
+}
+
+impl Fade {
+    /// No fading at all
+    pub const NONE: Fade = Fade {
+        duration: Duration::ZERO,
+        curve: FadeCurve::Linear,
+    };
+}
+
+impl Default for Fade {
+    /// 100ms linear
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_millis(100),
+            curve: FadeCurve::Linear,
+        }
+    }
+}
+
+/// Applies fades to a stream of interleaved samples
+///
+/// Call [`fade_in`](Self::fade_in) or [`fade_out`](Self::fade_out) and pass
+/// every buffer through [`process`](Self::process); the ramp advances with the
+/// audio, so it takes the same time whatever the buffer sizes. A fade that
+/// starts halfway through another one continues from the current gain. After a
+/// fade out the samples stay silent until the next fade in.
+#[derive(Debug, Clone)]
+pub struct Fader {
+    fade: Fade,
+    /// Position along the curve, 0 (silent) to 1 (full)
+    position: f32,
+    /// Where the position is heading
+    target: f32,
+}
+
+impl Fader {
+    /// Start at full volume
+    pub fn new(fade: Fade) -> Self {
+        Self {
+            fade,
+            position: 1.0,
+            target: 1.0,
+        }
+    }
+
+    /// Start silent, waiting for a fade in
+    pub fn silent(fade: Fade) -> Self {
+        Self {
+            fade,
+            position: 0.0,
+            target: 0.0,
+        }
+    }
+
+    /// Ramp up to full volume
+    pub fn fade_in(&mut self) {
+        self.target = 1.0;
+    }
+
+    /// Start over from silence and ramp up, e.g. after the queued audio was dropped
+    pub fn restart(&mut self) {
+        self.position = 0.0;
+        self.target = 1.0;
+    }
+
+    /// Ramp down to silence
+    pub fn fade_out(&mut self) {
+        self.target = 0.0;
+    }
+
+    /// Whether samples pass through unchanged
+    pub fn is_unity(&self) -> bool {
+        self.position >= 1.0 && self.target >= 1.0
+    }
+
+    /// Whether samples come out silent
+    pub fn is_silent(&self) -> bool {
+        self.position <= 0.0 && self.target <= 0.0
+    }
+
+    /// Current gain
+    pub fn gain(&self) -> f32 {
+        self.fade.curve.gain(self.position)
+    }
+
+    /// Apply the fade to interleaved `samples` in place
+    pub fn apply(&mut self, samples: &mut [Sample], channels: u8, sample_rate: u32) {
+        if self.is_unity() {
+            return;
+        }
+        let channels = channels.max(1) as usize;
+        let frames = (self.fade.duration.as_secs_f64() * sample_rate as f64) as f32;
+        // Zero length fades jump straight to the target
+        let step = if frames >= 1.0 { 1.0 / frames } else { 1.0 };
+        for frame in samples.chunks_mut(channels) {
+            if self.position < self.target {
+                self.position = (self.position + step).min(self.target);
+            } else if self.position > self.target {
+                self.position = (self.position - step).max(self.target);
+            }
+            let gain = self.gain();
+            for sample in frame {
+                *sample = Sample((sample.0 as f32 * gain).round() as i32);
+            }
+        }
+    }
+
+    /// Apply the fade to a shared buffer, copying it only when the gain is not unity
+    pub fn process(
+        &mut self,
+        samples: &Arc<[Sample]>,
+        channels: u8,
+        sample_rate: u32,
+    ) -> Arc<[Sample]> {
+        if self.is_unity() {
+            return Arc::clone(samples);
+        }
+        let mut faded = samples.to_vec();
+        self.apply(&mut faded, channels, sample_rate);
+        faded.into()
+    }
+}
@@ -0,0 +1,238 @@
+// ABOUTME: Volume gain stage for the output path
+// ABOUTME: Maps the 0-100 protocol volume onto a logarithmic curve, applies gain in dB, balance, and trim
+
+use crate::audio::Sample;
+use std::sync::Arc;
+
+/// Default range of the logarithmic volume curve
+pub const DEFAULT_VOLUME_RANGE_DB: f32 = 60.0;
+
+/// Highest gain [`Gain::set_gain_db`] accepts; louder clips
+pub const MAX_GAIN_DB: f32 = 12.0;
+
+/// Convert decibels to a linear gain factor
+pub fn db_to_gain(db: f32) -> f32 {
+    if db == f32::NEG_INFINITY {
+        0.0
+    } else {
+        10f32.powf(db / 20.0)
+    }
+}
+
+/// Convert a linear gain factor to decibels (negative infinity for 0)
+pub fn gain_to_db(gain: f32) -> f32 {
+    if gain <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        20.0 * gain.log10()
+    }
+}
+
+/// How the 0-100 protocol volume maps onto gain
+///
+/// Ears hear loudness logarithmically, so a linear mapping crowds all audible
+/// change into the bottom of the range. The logarithmic taper gives every step
+/// the same change in dB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VolumeTaper {
+    /// Gain proportional to volume, for outputs that apply their own curve
+    Linear,
+    /// 100 is 0 dB, 1 is `-range_db`, 0 is silent, evenly spaced in dB in between
+    Logarithmic {
+        /// dB between volume 1 and volume 100
+        range_db: f32,
+    },
+}
+
+impl VolumeTaper {
+    /// Gain in dB for a protocol volume; volumes above 100 count as 100
+    pub fn gain_db(self, volume: u8) -> f32 {
+        let volume = volume.min(100);
+        if volume == 0 {
+            return f32::NEG_INFINITY;
+        }
+        match self {
+            VolumeTaper::Linear => gain_to_db(volume as f32 / 100.0),
+            VolumeTaper::Logarithmic { range_db } => -range_db.abs() * (100 - volume) as f32 / 99.0,
+        }
+    }
+}
+
+impl Default for VolumeTaper {
+    fn default() -> Self {
+        VolumeTaper::Logarithmic {
+            range_db: DEFAULT_VOLUME_RANGE_DB,
+        }
+    }
+}
+
+/// Gain applied to samples before they reach the output
+///
+/// Set from the protocol volume with [`set_volume`](Self::set_volume), or
+/// directly with [`set_gain_db`](Self::set_gain_db). Starts at 0 dB, where
+/// samples pass through untouched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gain {
+    taper: VolumeTaper,
+    db: f32,
+}
+
+impl Gain {
+    /// Unity gain, mapping volumes through `taper`
+    pub fn new(taper: VolumeTaper) -> Self {
+        Self { taper, db: 0.0 }
+    }
+
+    /// Set the gain for a 0-100 protocol volume
+    pub fn set_volume(&mut self, volume: u8) {
+        self.db = self.taper.gain_db(volume);
+    }
+
+    /// Set the gain in dB, up to [`MAX_GAIN_DB`]; negative infinity is silent
+    pub fn set_gain_db(&mut self, db: f32) {
+        if !db.is_nan() {
+            self.db = db.min(MAX_GAIN_DB);
+        }
+    }
+
+    /// Current gain in dB
+    pub fn gain_db(&self) -> f32 {
+        self.db
+    }
+
+    /// Current gain as a linear factor
+    pub fn factor(&self) -> f32 {
+        db_to_gain(self.db)
+    }
+
+    /// Whether samples pass through unchanged
+    pub fn is_unity(&self) -> bool {
+        self.db == 0.0
+    }
+
+    /// Apply the gain to `samples` in place, clamping to the 24-bit range
+    pub fn apply(&self, samples: &mut [Sample]) {
+        if self.is_unity() {
+            return;
+        }
+        let factor = self.factor();
+        for sample in samples {
+            *sample = Sample((sample.0 as f32 * factor).round() as i32).clamp();
+        }
+    }
+
+    /// Apply the gain to a shared buffer, copying it only when the gain is not unity
+    pub fn process(&self, samples: &Arc<[Sample]>) -> Arc<[Sample]> {
+        if self.is_unity() {
+            return Arc::clone(samples);
+        }
+        let mut scaled = samples.to_vec();
+        self.apply(&mut scaled);
+        scaled.into()
+    }
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self::new(VolumeTaper::default())
+    }
+}
+
+/// Channels [`Balance`] keeps a trim for
+pub const MAX_TRIM_CHANNELS: usize = 8;
+
+/// Left/right balance and per-channel trim
+///
+/// For rooms where one speaker is hotter than the other. Balance moves the
+/// stereo image by attenuating the opposite side: -1 is left only, 1 is right
+/// only, 0 leaves both at full level. It applies to the first two channels of
+/// streams with at least two. Trims add a fixed gain in dB to single channels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Balance {
+    balance: f32,
+    trims_db: [f32; MAX_TRIM_CHANNELS],
+}
+
+impl Balance {
+    /// Centered, with no trim
+    pub fn new() -> Self {
+        Self {
+            balance: 0.0,
+            trims_db: [0.0; MAX_TRIM_CHANNELS],
+        }
+    }
+
+    /// Set the balance, clamped to -1.0 (left) ..= 1.0 (right)
+    pub fn set_balance(&mut self, balance: f32) {
+        if !balance.is_nan() {
+            self.balance = balance.clamp(-1.0, 1.0);
+        }
+    }
+
+    /// Current balance
+    pub fn balance(&self) -> f32 {
+        self.balance
+    }
+
+    /// Set the trim of one channel in dB, up to [`MAX_GAIN_DB`]
+    ///
+    /// Channels from [`MAX_TRIM_CHANNELS`] on are ignored.
+    pub fn set_trim_db(&mut self, channel: usize, db: f32) {
+        if let Some(trim) = self.trims_db.get_mut(channel) {
+            if !db.is_nan() {
+                *trim = db.min(MAX_GAIN_DB);
+            }
+        }
+    }
+
+    /// Trim of one channel in dB
+    pub fn trim_db(&self, channel: usize) -> f32 {
+        self.trims_db.get(channel).copied().unwrap_or(0.0)
+    }
+
+    /// Linear gain of `channel` in a stream of `channels`
+    pub fn factor(&self, channel: usize, channels: u8) -> f32 {
+        let side = match channel {
+            0 if channels >= 2 => (1.0 - self.balance).min(1.0),
+            1 if channels >= 2 => (1.0 + self.balance).min(1.0),
+            _ => 1.0,
+        };
+        side * db_to_gain(self.trim_db(channel))
+    }
+
+    /// Whether samples pass through unchanged
+    pub fn is_unity(&self) -> bool {
+        self.balance == 0.0 && self.trims_db.iter().all(|&db| db == 0.0)
+    }
+
+    /// Apply to interleaved `samples` in place, clamping to the 24-bit range
+    pub fn apply(&self, samples: &mut [Sample], channels: u8) {
+        if self.is_unity() {
+            return;
+        }
+        let factors: Vec<f32> = (0..channels.max(1) as usize)
+            .map(|channel| self.factor(channel, channels))
+            .collect();
+        for frame in samples.chunks_mut(factors.len()) {
+            for (sample, factor) in frame.iter_mut().zip(&factors) {
+                *sample = Sample((sample.0 as f32 * factor).round() as i32).clamp();
+            }
+        }
+    }
+
+    /// Apply to a shared buffer, copying it only when something changes
+    pub fn process(&self, samples: &Arc<[Sample]>, channels: u8) -> Arc<[Sample]> {
+        if self.is_unity() {
+            return Arc::clone(samples);
+        }
+        let mut adjusted = samples.to_vec();
+        self.apply(&mut adjusted, channels);
+        adjusted.into()
+    }
+}
+
+impl Default for Balance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
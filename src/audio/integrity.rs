@@ -0,0 +1,238 @@
+// ABOUTME: Audio chunk integrity verification
+// ABOUTME: Frame alignment checks and timestamp continuity diagnostics for PCM chunks
+
+use crate::audio::AudioFormat;
+use crate::error::Error;
+use std::fmt;
+
+/// Byte layout of one interleaved PCM frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameLayout {
+    /// Bit depth per sample
+    pub bit_depth: u8,
+    /// Number of interleaved channels
+    pub channels: u8,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+}
+
+impl FrameLayout {
+    /// Derive the frame layout for a PCM format
+    pub fn for_format(format: &AudioFormat) -> Result<Self, Error> {
+        if !format.codec.is_pcm() {
+            return Err(Error::Protocol(format!(
+                "Frame layout is only defined for PCM, got {:?}",
+                format.codec
+            )));
+        }
+        if format.bit_depth == 0 || !format.bit_depth.is_multiple_of(8) {
+            return Err(Error::Protocol(format!(
+                "Unsupported bit depth for frame layout: {}",
+                format.bit_depth
+            )));
+        }
+        if format.channels == 0 || format.sample_rate == 0 {
+            return Err(Error::Protocol(format!(
+                "Invalid PCM format: {}Hz {}ch",
+                format.sample_rate, format.channels
+            )));
+        }
+        Ok(Self {
+            bit_depth: format.bit_depth,
+            channels: format.channels,
+            sample_rate: format.sample_rate,
+        })
+    }
+
+    /// Bytes per sample for a single channel
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bit_depth as usize / 8
+    }
+
+    /// Bytes per interleaved frame (all channels)
+    pub fn frame_size(&self) -> usize {
+        self.bytes_per_sample() * self.channels as usize
+    }
+
+    /// Verify that a payload holds a whole number of frames
+    ///
+    /// Returns the number of frames on success.
+    pub fn verify(&self, data: &[u8]) -> Result<usize, FrameMismatch> {
+        let frame_size = self.frame_size();
+        let remainder = data.len() % frame_size;
+        if remainder != 0 {
+            return Err(FrameMismatch {
+                len: data.len(),
+                layout: *self,
+                whole_frames: data.len() / frame_size,
+                remainder,
+            });
+        }
+        Ok(data.len() / frame_size)
+    }
+
+    /// Duration of `frames` frames in microseconds
+    pub fn frames_to_micros(&self, frames: usize) -> i64 {
+        (frames as i64 * 1_000_000) / self.sample_rate as i64
+    }
+}
+
+/// Detailed description of a misaligned payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameMismatch {
+    /// Payload length in bytes
+    pub len: usize,
+    /// Expected frame layout
+    pub layout: FrameLayout,
+    /// Number of complete frames in the payload
+    pub whole_frames: usize,
+    /// Trailing bytes that do not form a complete frame
+    pub remainder: usize,
+}
+
+impl fmt::Display for FrameMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes is not a multiple of the {}-byte frame ({}-bit, {}ch): {} whole frames + {} trailing bytes",
+            self.len,
+            self.layout.frame_size(),
+            self.layout.bit_depth,
+            self.layout.channels,
+            self.whole_frames,
+            self.remainder
+        )?;
+        // A remainder that is a whole number of samples points at a channel-count mismatch,
+        // otherwise the payload was truncated or the bit depth is wrong
+        if self
+            .remainder
+            .is_multiple_of(self.layout.bytes_per_sample())
+        {
+            write!(f, " (possible channel count mismatch)")
+        } else {
+            write!(f, " (possible truncation or bit depth mismatch)")
+        }
+    }
+}
+
+/// Result of verifying a single chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkReport {
+    /// Number of frames in the chunk
+    pub frames: usize,
+    /// Chunk duration in microseconds
+    pub duration_micros: i64,
+    /// Difference between this chunk's timestamp and the end of the previous chunk
+    ///
+    /// Positive values are gaps, negative values are overlaps. `None` for the first chunk.
+    pub discontinuity_micros: Option<i64>,
+}
+
+/// Counters collected by [`IntegrityChecker`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntegrityStats {
+    /// Chunks that passed verification
+    pub verified: u64,
+    /// Chunks rejected as misaligned
+    pub misaligned: u64,
+    /// Chunks whose timestamp did not follow the previous chunk within tolerance
+    pub discontinuities: u64,
+}
+
+/// Stateful integrity checker for a PCM stream
+#[derive(Debug, Clone)]
+pub struct IntegrityChecker {
+    layout: FrameLayout,
+    tolerance_micros: i64,
+    expected_next: Option<i64>,
+    stats: IntegrityStats,
+    /// Bytes of a split frame carried into the next chunk, when allowed
+    carried: Option<usize>,
+}
+
+impl IntegrityChecker {
+    /// Create a checker for the given layout with a 1ms continuity tolerance
+    pub fn new(layout: FrameLayout) -> Self {
+        Self {
+            layout,
+            tolerance_micros: 1_000,
+            expected_next: None,
+            stats: IntegrityStats::default(),
+            carried: None,
+        }
+    }
+
+    /// Set the timestamp tolerance before a gap or overlap counts as a discontinuity
+    pub fn with_tolerance_micros(mut self, tolerance_micros: i64) -> Self {
+        self.tolerance_micros = tolerance_micros;
+        self
+    }
+
+    /// Accept chunks that split a frame, for decoders that carry the rest over
+    ///
+    /// Frames are then counted from the bytes of all chunks so far, and no
+    /// chunk is rejected as misaligned.
+    pub fn with_split_frames(mut self) -> Self {
+        self.carried = Some(0);
+        self
+    }
+
+    /// Verify a chunk payload and its timestamp against the previous chunk
+    pub fn check(&mut self, timestamp: i64, data: &[u8]) -> Result<ChunkReport, FrameMismatch> {
+        let frames = match self.carried {
+            Some(carried) => {
+                let frame_size = self.layout.frame_size();
+                let total = carried + data.len();
+                self.carried = Some(total % frame_size);
+                total / frame_size
+            }
+            None => match self.layout.verify(data) {
+                Ok(frames) => frames,
+                Err(mismatch) => {
+                    self.stats.misaligned += 1;
+                    return Err(mismatch);
+                }
+            },
+        };
+
+        let duration_micros = self.layout.frames_to_micros(frames);
+        let discontinuity_micros = self.expected_next.map(|expected| timestamp - expected);
+        if let Some(delta) = discontinuity_micros {
+            if delta.abs() > self.tolerance_micros {
+                self.stats.discontinuities += 1;
+            }
+        }
+
+        self.expected_next = Some(timestamp + duration_micros);
+        self.stats.verified += 1;
+
+        Ok(ChunkReport {
+            frames,
+            duration_micros,
+            discontinuity_micros,
+        })
+    }
+
+    /// Get the frame layout being verified
+    pub fn layout(&self) -> FrameLayout {
+        self.layout
+    }
+
+    /// Get collected counters
+    pub fn stats(&self) -> IntegrityStats {
+        self.stats
+    }
+
+    /// Bytes of a split frame still waiting for the next chunk
+    pub fn carried_bytes(&self) -> usize {
+        self.carried.unwrap_or(0)
+    }
+
+    /// Forget the previous chunk (e.g., after stream/clear)
+    pub fn reset(&mut self) {
+        self.expected_next = None;
+        if let Some(ref mut carried) = self.carried {
+            *carried = 0;
+        }
+    }
+}
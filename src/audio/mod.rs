@@ -1,15 +1,32 @@
 // ABOUTME: Audio types and processing for sendspin-rs
 // ABOUTME: Contains Sample type, AudioFormat, Buffer, and codec definitions
 
+/// PCM format conversion (bit depth, endianness, channels, sample rate)
+pub mod convert;
 /// Audio decoder implementations (PCM, Opus, FLAC)
 pub mod decode;
+/// Click-free fade in and fade out ramps
+pub mod fade;
+/// Volume curve, gain stage, balance, and channel trim
+pub mod gain;
+/// Chunk integrity verification (frame alignment, timestamp continuity)
+pub mod integrity;
 /// Audio output trait and implementations
 pub mod output;
 /// Buffer pool for reusing audio sample buffers
 pub mod pool;
+/// Streaming sample rate conversion for the player
+pub mod resample;
+/// Reference tone generation and verification
+pub mod tone;
 /// Core audio type definitions (Sample, Codec, AudioFormat, AudioBuffer)
 pub mod types;
 
-pub use output::{AudioOutput, CpalOutput};
+pub use fade::{Fade, FadeCurve, Fader};
+pub use gain::{Balance, Gain, VolumeTaper};
+pub use integrity::{FrameLayout, IntegrityChecker};
+pub use output::{AudioOutput, CpalOutput, MultiOutput};
 pub use pool::BufferPool;
+pub use resample::{ResampleQuality, Resampler};
+pub use tone::{ToneReport, ToneVerifier};
 pub use types::{AudioBuffer, AudioFormat, Codec, Sample};
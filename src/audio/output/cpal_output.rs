@@ -1,6 +1,7 @@
 // ABOUTME: cpal-based audio output implementation
 // ABOUTME: Cross-platform audio output using the cpal library
 
+use crate::audio::output::timing::{FrameTimeline, OutputTiming, TimingCallback};
 use crate::audio::output::AudioOutput;
 use crate::audio::{AudioFormat, Sample};
 use crate::error::Error;
@@ -8,22 +9,40 @@ use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Stream, StreamConfig};
 use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Buffers queued for the audio thread, each with its server play time
+type SampleQueue = Arc<Mutex<Receiver<(Option<i64>, Arc<[Sample]>)>>>;
 
 /// cpal-based audio output
 pub struct CpalOutput {
     format: AudioFormat,
     _stream: Stream,
-    sample_tx: SyncSender<Arc<[Sample]>>,
+    sample_tx: SyncSender<(Option<i64>, Arc<[Sample]>)>,
+    sample_rx: SampleQueue,
     latency_micros: Arc<Mutex<u64>>,
+    timing_callback: Arc<Mutex<Option<TimingCallback>>>,
 }
 
 impl CpalOutput {
-    /// Create a new cpal audio output
+    /// Create a new cpal audio output on the default device
     pub fn new(format: AudioFormat) -> Result<Self, Error> {
+        Self::with_device(format, None)
+    }
+
+    /// Create a new cpal audio output on a named device (`None` for the default)
+    pub fn with_device(format: AudioFormat, device_name: Option<&str>) -> Result<Self, Error> {
         let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .ok_or_else(|| Error::Output("No output device available".to_string()))?;
+        let device = match device_name {
+            Some(name) => host
+                .output_devices()
+                .map_err(|e| Error::Output(e.to_string()))?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .ok_or_else(|| Error::Output(format!("Output device not found: {}", name)))?,
+            None => host
+                .default_output_device()
+                .ok_or_else(|| Error::Output("No output device available".to_string()))?,
+        };
 
         // Log device's default supported config to catch format mismatches
         if let Ok(def) = device.default_output_config() {
@@ -33,12 +52,14 @@ impl CpalOutput {
                 def.sample_rate().0,
                 def.channels()
             );
-            if def.sample_rate().0 != format.sample_rate
-                || def.channels() != format.channels as u16
+            if def.sample_rate().0 != format.sample_rate || def.channels() != format.channels as u16
             {
                 log::warn!(
                     "WARN: requested {}Hz/{}ch; device default is {}Hz/{}ch (OS may resample)",
-                    format.sample_rate, format.channels, def.sample_rate().0, def.channels()
+                    format.sample_rate,
+                    format.channels,
+                    def.sample_rate().0,
+                    def.channels()
                 );
             }
         }
@@ -50,48 +71,94 @@ impl CpalOutput {
         };
 
         // Use bounded channel for backpressure (10 buffers max = ~200ms at 20ms chunks)
-        let (sample_tx, sample_rx) = sync_channel::<Arc<[Sample]>>(10);
+        let (sample_tx, sample_rx) = sync_channel::<(Option<i64>, Arc<[Sample]>)>(10);
+        let sample_rx = Arc::new(Mutex::new(sample_rx));
         let latency_micros = Arc::new(Mutex::new(0u64));
-        let latency_clone = Arc::clone(&latency_micros);
+        let timing_callback = Arc::new(Mutex::new(None));
+        let timeline = FrameTimeline::new(format.sample_rate, format.channels);
 
-        let stream = Self::build_stream(&device, &config, sample_rx, latency_clone)?;
+        let stream = Self::build_stream(
+            &device,
+            &config,
+            Arc::clone(&sample_rx),
+            Arc::clone(&latency_micros),
+            Arc::clone(&timing_callback),
+            timeline,
+        )?;
         stream.play().map_err(|e| Error::Output(e.to_string()))?;
 
         Ok(Self {
             format,
             _stream: stream,
             sample_tx,
+            sample_rx,
             latency_micros,
+            timing_callback,
         })
     }
 
+    /// Names of the output devices on the default host
+    pub fn device_names() -> Vec<String> {
+        cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Register a callback that receives the server-timeline position of every
+    /// output callback
+    ///
+    /// Runs on the real-time audio thread, so it must not block. Replaces any
+    /// previously registered callback.
+    pub fn set_timing_callback(&self, callback: impl FnMut(&OutputTiming) + Send + 'static) {
+        if let Ok(mut slot) = self.timing_callback.lock() {
+            *slot = Some(Box::new(callback));
+        }
+    }
+
     fn build_stream(
         device: &Device,
         config: &StreamConfig,
-        sample_rx: Receiver<Arc<[Sample]>>,
-        _latency_micros: Arc<Mutex<u64>>,
+        sample_rx: SampleQueue,
+        latency_micros: Arc<Mutex<u64>>,
+        timing_callback: Arc<Mutex<Option<TimingCallback>>>,
+        mut timeline: FrameTimeline,
     ) -> Result<Stream, Error> {
-        let sample_rx = Arc::new(Mutex::new(sample_rx));
         let mut current_buffer: Option<Arc<[Sample]>> = None;
         let mut buffer_pos = 0;
+        let channels = config.channels.max(1) as usize;
 
         let stream = device
             .build_output_stream(
                 config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    for sample_out in data.iter_mut() {
+                move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                    let stamp = info.timestamp();
+                    let playback_delay = stamp.playback.duration_since(&stamp.callback);
+                    if let (Some(delay), Ok(mut latency)) =
+                        (playback_delay, latency_micros.try_lock())
+                    {
+                        *latency = delay.as_micros() as u64;
+                    }
+
+                    let mut first_timestamp = None;
+                    let mut played = 0;
+                    for (i, sample_out) in data.iter_mut().enumerate() {
                         // Get next sample from current buffer or receive new buffer
                         if current_buffer.is_none()
                             || buffer_pos >= current_buffer.as_ref().unwrap().len()
                         {
                             // Try to get new buffer
                             if let Ok(rx) = sample_rx.lock() {
-                                if let Ok(buf) = rx.try_recv() {
+                                if let Ok((timestamp, buf)) = rx.try_recv() {
+                                    timeline.push(timestamp, buf.len());
                                     current_buffer = Some(buf);
                                     buffer_pos = 0;
                                 }
                             }
                         }
+                        if i == 0 {
+                            first_timestamp = timeline.timestamp();
+                        }
 
                         // Output sample or silence
                         if let Some(ref buf) = current_buffer {
@@ -100,6 +167,7 @@ impl CpalOutput {
                                 // Convert 24-bit sample to f32 (-1.0 to 1.0)
                                 *sample_out = sample.0 as f32 / 8388607.0;
                                 buffer_pos += 1;
+                                played += 1;
                             } else {
                                 *sample_out = 0.0; // Silence
                             }
@@ -107,6 +175,19 @@ impl CpalOutput {
                             *sample_out = 0.0; // Silence
                         }
                     }
+                    timeline.consume(played);
+
+                    // Never block the audio thread on the consumer
+                    if let Ok(mut callback) = timing_callback.try_lock() {
+                        if let Some(callback) = callback.as_mut() {
+                            callback(&OutputTiming {
+                                server_timestamp: first_timestamp,
+                                frames: data.len() / channels,
+                                callback_at: Instant::now(),
+                                playback_delay,
+                            });
+                        }
+                    }
                 },
                 |err| log::error!("Audio stream error: {}", err),
                 None,
@@ -120,10 +201,23 @@ impl CpalOutput {
 impl AudioOutput for CpalOutput {
     fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), Error> {
         self.sample_tx
-            .send(Arc::clone(samples))
+            .send((None, Arc::clone(samples)))
+            .map_err(|_| Error::Output("Failed to send samples to audio thread".to_string()))
+    }
+
+    fn write_timed(&mut self, timestamp: i64, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        self.sample_tx
+            .send((Some(timestamp), Arc::clone(samples)))
             .map_err(|_| Error::Output("Failed to send samples to audio thread".to_string()))
     }
 
+    fn flush(&mut self) {
+        // The buffer the audio thread is playing finishes; queued ones are dropped
+        if let Ok(rx) = self.sample_rx.lock() {
+            while rx.try_recv().is_ok() {}
+        }
+    }
+
     fn latency_micros(&self) -> u64 {
         *self.latency_micros.lock().unwrap()
     }
@@ -3,8 +3,14 @@
 
 /// cpal-based audio output implementation
 pub mod cpal_output;
+/// Fan-out to several time-aligned outputs
+pub mod multi;
+/// Output callback timing for A/V sync
+pub mod timing;
 
 pub use cpal_output::CpalOutput;
+pub use multi::MultiOutput;
+pub use timing::{FrameTimeline, OutputTiming, TimingCallback};
 
 use crate::audio::{AudioFormat, Sample};
 use crate::error::Error;
@@ -15,6 +21,20 @@ pub trait AudioOutput {
     /// Write samples to the audio output
     fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), Error>;
 
+    /// Write samples whose first frame plays at a server timestamp (µs)
+    ///
+    /// Outputs that report callback timing use the timestamp to map their stream
+    /// position onto the server timeline. The default ignores it.
+    fn write_timed(&mut self, timestamp: i64, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        let _ = timestamp;
+        self.write(samples)
+    }
+
+    /// Drop audio written but not played yet (e.g., on stream/clear)
+    ///
+    /// The default does nothing, for outputs without a queue of their own.
+    fn flush(&mut self) {}
+
     /// Get the current output latency in microseconds
     fn latency_micros(&self) -> u64;
 
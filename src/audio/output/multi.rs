@@ -0,0 +1,186 @@
+// ABOUTME: Audio output that plays the same audio on several devices
+// ABOUTME: Delays lower-latency outputs to line up with the slowest and drops failed ones
+
+use crate::audio::output::AudioOutput;
+use crate::audio::{AudioFormat, Sample};
+use crate::error::Error;
+use std::sync::Arc;
+
+/// Realign an output once its delay is off by more than this (µs)
+///
+/// Latency estimates wander by a few hundred microseconds; chasing them would
+/// insert and drop audio all the time.
+const REALIGN_THRESHOLD_MICROS: u64 = 2_000;
+
+/// One device of a [`MultiOutput`]
+struct Branch {
+    name: String,
+    output: Box<dyn AudioOutput>,
+    /// Audio delay inserted ahead of this output so far (µs)
+    delay_micros: u64,
+}
+
+impl Branch {
+    /// Bring the inserted delay to `wanted` µs by padding with silence or
+    /// skipping frames
+    ///
+    /// Returns the samples to write and how far their first frame moved on
+    /// the server timeline (µs).
+    fn align(
+        &mut self,
+        wanted: u64,
+        samples: &Arc<[Sample]>,
+        format: &AudioFormat,
+    ) -> (Arc<[Sample]>, i64) {
+        if wanted.abs_diff(self.delay_micros) < REALIGN_THRESHOLD_MICROS {
+            return (Arc::clone(samples), 0);
+        }
+        let channels = format.channels.max(1) as usize;
+        let rate = format.sample_rate.max(1) as u64;
+        let to_frames = |micros: u64| (micros * rate / 1_000_000) as usize;
+        let to_micros = |frames: usize| frames as u64 * 1_000_000 / rate;
+
+        if wanted > self.delay_micros {
+            let frames = to_frames(wanted - self.delay_micros);
+            self.delay_micros += to_micros(frames);
+            let mut padded = vec![Sample::ZERO; frames * channels];
+            padded.extend_from_slice(samples);
+            (padded.into(), -(to_micros(frames) as i64))
+        } else {
+            let frames = to_frames(self.delay_micros - wanted).min(samples.len() / channels);
+            self.delay_micros -= to_micros(frames);
+            (
+                samples[frames * channels..].into(),
+                to_micros(frames) as i64,
+            )
+        }
+    }
+}
+
+/// Plays the same audio on several outputs, e.g. two DACs in different rooms
+///
+/// Every buffer goes to all outputs at once, so they share the player's
+/// schedule. Outputs with less latency than the slowest one are delayed by the
+/// difference, keeping them time-aligned. An output that fails to write is
+/// dropped and the others keep playing; writing fails only once none are left.
+///
+/// All outputs must play the same format.
+pub struct MultiOutput {
+    format: AudioFormat,
+    branches: Vec<Branch>,
+}
+
+impl MultiOutput {
+    /// Create a fan-out with no outputs yet
+    pub fn new(format: AudioFormat) -> Self {
+        Self {
+            format,
+            branches: Vec::new(),
+        }
+    }
+
+    /// Add an output; `name` identifies it in logs
+    ///
+    /// Fails if the output plays a different format.
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        output: Box<dyn AudioOutput>,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        if output.format() != &self.format {
+            return Err(Error::Output(format!(
+                "Output {} plays {:?}, expected {:?}",
+                name,
+                output.format(),
+                self.format
+            )));
+        }
+        self.branches.push(Branch {
+            name,
+            output,
+            delay_micros: 0,
+        });
+        Ok(())
+    }
+
+    /// Names of the outputs still playing
+    pub fn names(&self) -> Vec<&str> {
+        self.branches.iter().map(|b| b.name.as_str()).collect()
+    }
+
+    /// Number of outputs still playing
+    pub fn len(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// Check if every output has been dropped (or none was added)
+    pub fn is_empty(&self) -> bool {
+        self.branches.is_empty()
+    }
+
+    fn fan_out(&mut self, timestamp: Option<i64>, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        let target = self.latency_micros();
+        let format = &self.format;
+        self.branches.retain_mut(|branch| {
+            let wanted = target.saturating_sub(branch.output.latency_micros());
+            let (samples, shift) = branch.align(wanted, samples, format);
+            if samples.is_empty() {
+                return true;
+            }
+            let written = match timestamp {
+                Some(timestamp) => branch.output.write_timed(timestamp + shift, &samples),
+                None => branch.output.write(&samples),
+            };
+            match written {
+                Ok(()) => true,
+                Err(e) => {
+                    log::error!("Output {} failed, dropping it: {}", branch.name, e);
+                    false
+                }
+            }
+        });
+        if self.branches.is_empty() {
+            return Err(Error::Output("No audio output left".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl AudioOutput for MultiOutput {
+    fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        self.fan_out(None, samples)
+    }
+
+    fn write_timed(&mut self, timestamp: i64, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        self.fan_out(Some(timestamp), samples)
+    }
+
+    fn flush(&mut self) {
+        for branch in &mut self.branches {
+            branch.output.flush();
+        }
+    }
+
+    /// Latency of the slowest output, which the others are aligned to
+    fn latency_micros(&self) -> u64 {
+        self.branches
+            .iter()
+            .map(|b| b.output.latency_micros())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+}
+
+impl std::fmt::Debug for MultiOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiOutput")
+            .field("format", &self.format)
+            .field("outputs", &self.names())
+            .finish()
+    }
+}
@@ -0,0 +1,103 @@
+// ABOUTME: Output callback timing for A/V sync consumers
+// ABOUTME: Maps each output callback to the server timeline using consumed frames
+
+use crate::sync::ClockSync;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Where an output callback sits on the server timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputTiming {
+    /// Server timestamp (µs) of the first frame written by this callback
+    ///
+    /// `None` while the output is playing silence or untimed samples.
+    pub server_timestamp: Option<i64>,
+    /// Number of frames requested by this callback
+    pub frames: usize,
+    /// When the callback ran
+    pub callback_at: Instant,
+    /// Time from the callback until its first frame reaches the speaker, if known
+    pub playback_delay: Option<Duration>,
+}
+
+impl OutputTiming {
+    /// Local instant at which the first frame of this callback is heard
+    pub fn heard_at(&self) -> Instant {
+        self.callback_at + self.playback_delay.unwrap_or_default()
+    }
+
+    /// How far playback is from where clock sync says it should be (µs)
+    ///
+    /// Positive values mean the audio is late. Returns `None` without a timestamp or
+    /// before clock sync is established.
+    pub fn sync_error_micros(&self, sync: &ClockSync) -> Option<i64> {
+        let due = sync.server_to_local_instant(self.server_timestamp?)?;
+        let heard = self.heard_at();
+        Some(if heard >= due {
+            heard.duration_since(due).as_micros() as i64
+        } else {
+            -(due.duration_since(heard).as_micros() as i64)
+        })
+    }
+}
+
+/// Callback invoked once per output callback with its timing
+pub type TimingCallback = Box<dyn FnMut(&OutputTiming) + Send>;
+
+/// Tracks the server timestamp of the next sample an output will play
+///
+/// Outputs push each buffer as it is queued and consume samples as they are
+/// written to the device.
+#[derive(Debug)]
+pub struct FrameTimeline {
+    sample_rate: u32,
+    channels: usize,
+    /// Queued buffers: start timestamp (if any) and samples left to play
+    queued: VecDeque<(Option<i64>, usize, usize)>,
+}
+
+impl FrameTimeline {
+    /// Create a timeline for interleaved samples at the given rate and channel count
+    pub fn new(sample_rate: u32, channels: u8) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1) as usize,
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Queue a buffer of `samples` interleaved samples starting at `timestamp`
+    pub fn push(&mut self, timestamp: Option<i64>, samples: usize) {
+        if samples > 0 {
+            self.queued.push_back((timestamp, samples, 0));
+        }
+    }
+
+    /// Server timestamp of the next sample to play
+    pub fn timestamp(&self) -> Option<i64> {
+        let &(start, _, played) = self.queued.front()?;
+        let frames = (played / self.channels) as i64;
+        Some(start? + frames * 1_000_000 / self.sample_rate as i64)
+    }
+
+    /// Mark `samples` interleaved samples as played
+    pub fn consume(&mut self, mut samples: usize) {
+        while samples > 0 {
+            let Some((_, remaining, played)) = self.queued.front_mut() else {
+                return;
+            };
+            let step = samples.min(*remaining);
+            *remaining -= step;
+            *played += step;
+            samples -= step;
+            if *remaining == 0 {
+                self.queued.pop_front();
+            }
+        }
+    }
+
+    /// Drop all queued buffers
+    pub fn clear(&mut self) {
+        self.queued.clear();
+    }
+}
@@ -0,0 +1,348 @@
+// ABOUTME: Streaming sample rate conversion between decoding and scheduling
+// ABOUTME: Linear interpolation built in; polynomial and sinc quality through rubato (feature "resample")
+
+use crate::audio::Sample;
+use crate::error::Error;
+
+/// How carefully a [`Resampler`] converts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleQuality {
+    /// Linear interpolation: cheap, but dulls the top octave and aliases
+    Linear,
+    /// Cubic polynomial interpolation (needs the "resample" feature)
+    Fast,
+    /// Windowed sinc with 128 taps (needs the "resample" feature)
+    Balanced,
+    /// Windowed sinc with 256 taps and a steeper cutoff (needs the "resample" feature)
+    Best,
+}
+
+impl Default for ResampleQuality {
+    /// Balanced with the "resample" feature, linear without
+    fn default() -> Self {
+        if cfg!(feature = "resample") {
+            ResampleQuality::Balanced
+        } else {
+            ResampleQuality::Linear
+        }
+    }
+}
+
+impl ResampleQuality {
+    /// Look a quality up by its settings name (`linear`, `fast`, `balanced`, `best`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "linear" => ResampleQuality::Linear,
+            "fast" => ResampleQuality::Fast,
+            "balanced" => ResampleQuality::Balanced,
+            "best" => ResampleQuality::Best,
+            _ => return None,
+        })
+    }
+}
+
+/// Converts a stream of interleaved samples from one rate to another
+///
+/// Keeps state between calls, so chunk boundaries are seamless. Output trails
+/// the input, as [`offset_micros`](Self::offset_micros) reports: interpolation
+/// needs the next frame, and rubato qualities hold input back until a full
+/// block has arrived.
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    channels: usize,
+    stage: Stage,
+}
+
+enum Stage {
+    Linear(Linear),
+    #[cfg(feature = "resample")]
+    Rubato(rubato_stage::Rubato),
+}
+
+impl Resampler {
+    /// Create a resampler for `channels` interleaved channels
+    pub fn new(
+        from_rate: u32,
+        to_rate: u32,
+        channels: u8,
+        quality: ResampleQuality,
+    ) -> Result<Self, Error> {
+        if from_rate == 0 || to_rate == 0 {
+            return Err(Error::Config("Sample rate must be non-zero".to_string()));
+        }
+        if channels == 0 {
+            return Err(Error::Config("Channel count must be non-zero".to_string()));
+        }
+        let stage = match quality {
+            ResampleQuality::Linear => Stage::Linear(Linear::default()),
+            #[cfg(feature = "resample")]
+            ResampleQuality::Fast => Stage::Rubato(rubato_stage::Rubato::polynomial(
+                from_rate, to_rate, channels,
+            )?),
+            #[cfg(feature = "resample")]
+            ResampleQuality::Balanced => Stage::Rubato(rubato_stage::Rubato::sinc(
+                from_rate, to_rate, channels, false,
+            )?),
+            #[cfg(feature = "resample")]
+            ResampleQuality::Best => Stage::Rubato(rubato_stage::Rubato::sinc(
+                from_rate, to_rate, channels, true,
+            )?),
+            #[cfg(not(feature = "resample"))]
+            quality => {
+                return Err(Error::Config(format!(
+                    "{:?} resampling needs the \"resample\" feature",
+                    quality
+                )))
+            }
+        };
+        Ok(Self {
+            from_rate,
+            to_rate,
+            channels: channels as usize,
+            stage,
+        })
+    }
+
+    /// Rate of the output in Hz
+    pub fn output_rate(&self) -> u32 {
+        self.to_rate
+    }
+
+    /// Convert the next chunk; a trailing partial frame is ignored
+    pub fn process(&mut self, samples: &[Sample]) -> Result<Vec<Sample>, Error> {
+        match self.stage {
+            Stage::Linear(ref mut linear) => {
+                Ok(linear.process(samples, self.channels, self.from_rate, self.to_rate))
+            }
+            #[cfg(feature = "resample")]
+            Stage::Rubato(ref mut rubato) => rubato.process(samples, self.channels),
+        }
+    }
+
+    /// Where the next output begins relative to the start of the next input, in µs
+    ///
+    /// Usually negative, as output trails input. Add it to the timestamp of a
+    /// chunk to time what `process` returns for it.
+    pub fn offset_micros(&self) -> i64 {
+        match self.stage {
+            Stage::Linear(ref linear) => linear.offset_micros(self.from_rate, self.to_rate),
+            #[cfg(feature = "resample")]
+            Stage::Rubato(ref rubato) => rubato.offset_micros(self.from_rate, self.to_rate),
+        }
+    }
+}
+
+impl std::fmt::Debug for Resampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Resampler")
+            .field("from_rate", &self.from_rate)
+            .field("to_rate", &self.to_rate)
+            .field("channels", &self.channels)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Linear interpolation carried across chunks
+#[derive(Default)]
+pub(crate) struct Linear {
+    /// Last frame of the previous chunk
+    previous: Option<Vec<Sample>>,
+    /// Position of the next output frame from `previous`, in 1/to_rate input frames
+    position: u64,
+}
+
+impl Linear {
+    /// Interpolate `samples`, holding back the last frame for the next call
+    pub(crate) fn process(
+        &mut self,
+        samples: &[Sample],
+        channels: usize,
+        from_rate: u32,
+        to_rate: u32,
+    ) -> Vec<Sample> {
+        let (from_rate, to_rate) = (from_rate as u64, to_rate as u64);
+        let frames: Vec<&[Sample]> = self
+            .previous
+            .as_deref()
+            .into_iter()
+            .chain(samples.chunks_exact(channels))
+            .collect();
+        let Some(last) = frames.last() else {
+            return Vec::new();
+        };
+
+        let mut out = Vec::new();
+        loop {
+            let index = (self.position / to_rate) as usize;
+            if index + 1 >= frames.len() {
+                break;
+            }
+            let frac = (self.position % to_rate) as i64;
+            for (a, b) in frames[index].iter().zip(frames[index + 1]) {
+                let (a, b) = (a.0 as i64, b.0 as i64);
+                out.push(Sample((a + (b - a) * frac / to_rate as i64) as i32));
+            }
+            self.position += from_rate;
+        }
+        self.position -= (frames.len() as u64 - 1) * to_rate;
+        self.previous = Some(last.to_vec());
+        out
+    }
+
+    fn offset_micros(&self, from_rate: u32, to_rate: u32) -> i64 {
+        if self.previous.is_none() {
+            return 0;
+        }
+        // The held frame is one frame before the next input
+        let frames = self.position as i64 - to_rate as i64;
+        frames * 1_000_000 / (to_rate as i64 * from_rate as i64)
+    }
+}
+
+#[cfg(feature = "resample")]
+mod rubato_stage {
+    use crate::audio::Sample;
+    use crate::error::Error;
+    use rubato::{
+        FastFixedIn, PolynomialDegree, SincFixedIn, SincInterpolationParameters,
+        SincInterpolationType, VecResampler, WindowFunction,
+    };
+
+    /// Input frames per block, about 5ms at 48kHz
+    const BLOCK_FRAMES: usize = 256;
+
+    fn ratio(from_rate: u32, to_rate: u32) -> f64 {
+        to_rate as f64 / from_rate as f64
+    }
+
+    /// A rubato resampler fed in fixed blocks
+    pub(super) struct Rubato {
+        resampler: Box<dyn VecResampler<f32>>,
+        /// Planar input waiting for a full block
+        input: Vec<Vec<f32>>,
+        /// Output frames of filter delay still to drop
+        skip: usize,
+        /// Input frames received
+        fed: u64,
+        /// Output frames returned
+        emitted: u64,
+    }
+
+    impl Rubato {
+        /// Cubic polynomial interpolation
+        pub(super) fn polynomial(
+            from_rate: u32,
+            to_rate: u32,
+            channels: u8,
+        ) -> Result<Self, Error> {
+            let resampler = FastFixedIn::new(
+                ratio(from_rate, to_rate),
+                1.0,
+                PolynomialDegree::Cubic,
+                BLOCK_FRAMES,
+                channels as usize,
+            )
+            .map_err(|e| Error::Config(e.to_string()))?;
+            // Unlike the sinc resamplers, this one lags by its output delay
+            let skip = resampler.output_delay();
+            Ok(Self::with_resampler(Box::new(resampler), channels, skip))
+        }
+
+        /// Windowed sinc interpolation, with the longer filter when `best`
+        pub(super) fn sinc(
+            from_rate: u32,
+            to_rate: u32,
+            channels: u8,
+            best: bool,
+        ) -> Result<Self, Error> {
+            let parameters = if best {
+                SincInterpolationParameters {
+                    sinc_len: 256,
+                    f_cutoff: 0.95,
+                    oversampling_factor: 256,
+                    interpolation: SincInterpolationType::Cubic,
+                    window: WindowFunction::BlackmanHarris2,
+                }
+            } else {
+                SincInterpolationParameters {
+                    sinc_len: 128,
+                    f_cutoff: 0.925,
+                    oversampling_factor: 128,
+                    interpolation: SincInterpolationType::Linear,
+                    window: WindowFunction::Blackman2,
+                }
+            };
+            let resampler = SincFixedIn::new(
+                ratio(from_rate, to_rate),
+                1.0,
+                parameters,
+                BLOCK_FRAMES,
+                channels as usize,
+            )
+            .map_err(|e| Error::Config(e.to_string()))?;
+            Ok(Self::with_resampler(Box::new(resampler), channels, 0))
+        }
+
+        fn with_resampler(
+            resampler: Box<dyn VecResampler<f32>>,
+            channels: u8,
+            skip: usize,
+        ) -> Self {
+            Self {
+                resampler,
+                input: vec![Vec::new(); channels as usize],
+                skip,
+                fed: 0,
+                emitted: 0,
+            }
+        }
+
+        pub(super) fn offset_micros(&self, from_rate: u32, to_rate: u32) -> i64 {
+            // Input still in the pending block or inside the filter makes it negative
+            let output = self.emitted as i128 * 1_000_000 / to_rate as i128;
+            let input = self.fed as i128 * 1_000_000 / from_rate as i128;
+            (output - input) as i64
+        }
+
+        fn pending_frames(&self) -> usize {
+            self.input[0].len()
+        }
+
+        pub(super) fn process(
+            &mut self,
+            samples: &[Sample],
+            channels: usize,
+        ) -> Result<Vec<Sample>, Error> {
+            let scale = Sample::MAX.0 as f32;
+            self.fed += (samples.len() / channels) as u64;
+            for frame in samples.chunks_exact(channels) {
+                for (plane, sample) in self.input.iter_mut().zip(frame) {
+                    plane.push(sample.0 as f32 / scale);
+                }
+            }
+
+            let mut out = Vec::new();
+            while self.pending_frames() >= self.resampler.input_frames_next() {
+                let needed = self.resampler.input_frames_next();
+                let block: Vec<Vec<f32>> = self
+                    .input
+                    .iter_mut()
+                    .map(|plane| plane.drain(..needed).collect())
+                    .collect();
+                let planes = self
+                    .resampler
+                    .process(&block, None)
+                    .map_err(|e| Error::Output(format!("Resampling failed: {}", e)))?;
+                let frames = planes[0].len();
+                let skip = self.skip.min(frames);
+                self.skip -= skip;
+                for i in skip..frames {
+                    out.extend(planes.iter().map(|plane| Sample::from_f32(plane[i])));
+                }
+                self.emitted += (frames - skip) as u64;
+            }
+            Ok(out)
+        }
+    }
+}
@@ -0,0 +1,232 @@
+// ABOUTME: Reference tone generation and verification
+// ABOUTME: Phase-continuous sine samples and a checker for dropped/inserted samples and frequency error
+
+use crate::audio::Sample;
+use crate::error::Error;
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+
+/// Default reference tone frequency in Hz
+pub const DEFAULT_TONE_HZ: f64 = 440.0;
+
+/// Tone level below which input counts as silence (about -60 dBFS)
+const SILENCE_AMPLITUDE: f64 = 0.001;
+
+pub(crate) fn check_tone(frequency: f64, sample_rate: u32) -> Result<(), Error> {
+    if sample_rate == 0 {
+        return Err(Error::Protocol("Sample rate must be non-zero".to_string()));
+    }
+    if !(frequency > 0.0 && frequency < sample_rate as f64 / 2.0) {
+        return Err(Error::Protocol(format!(
+            "Tone frequency {}Hz must be between 0 and {}Hz",
+            frequency,
+            sample_rate / 2
+        )));
+    }
+    Ok(())
+}
+
+/// Value in [-1, 1] of a unit sine at `frequency` for frame number `frame`
+///
+/// Computed from the frame number rather than an accumulator, so the tone stays
+/// phase-continuous however it is chunked.
+pub fn tone_value(frequency: f64, sample_rate: u32, frame: u64) -> f64 {
+    let cycles = (frame as f64 * frequency / sample_rate as f64).fract();
+    (TAU * cycles).sin()
+}
+
+/// A gap in the tone: samples missing (positive) or extra (negative)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discontinuity {
+    /// Index (from the first pushed frame) of the first frame after the jump
+    pub frame: u64,
+    /// Frames dropped (positive) or inserted (negative)
+    ///
+    /// Measured modulo one tone period, so pick a tone period longer than the
+    /// gaps you expect.
+    pub offset: i64,
+}
+
+/// Summary of a [`ToneVerifier`] run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToneReport {
+    /// Reference tone frequency in Hz
+    pub frequency: f64,
+    /// Frames checked
+    pub frames: u64,
+    /// Frames below the silence threshold (not checked)
+    pub silent_frames: u64,
+    /// Every phase jump found, in order
+    pub discontinuities: Vec<Discontinuity>,
+    /// Total frames dropped
+    pub dropped: u64,
+    /// Total frames inserted
+    pub inserted: u64,
+    /// Frequency measured from the phase advance, if any tone was seen
+    pub measured_frequency: Option<f64>,
+}
+
+impl ToneReport {
+    /// Frequency error in parts per million
+    pub fn frequency_error_ppm(&self) -> Option<f64> {
+        self.measured_frequency
+            .map(|f| (f - self.frequency) / self.frequency * 1_000_000.0)
+    }
+
+    /// Whether no samples were dropped or inserted
+    pub fn is_clean(&self) -> bool {
+        self.discontinuities.is_empty()
+    }
+}
+
+impl fmt::Display for ToneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tone {}Hz: frames={} silent={} dropped={} inserted={} discontinuities={}",
+            self.frequency,
+            self.frames,
+            self.silent_frames,
+            self.dropped,
+            self.inserted,
+            self.discontinuities.len()
+        )?;
+        if let (Some(measured), Some(ppm)) = (self.measured_frequency, self.frequency_error_ppm()) {
+            write!(f, " measured={:.3}Hz error={:+.1}ppm", measured, ppm)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks decoded output against the reference tone
+///
+/// Each pair of consecutive samples on the first channel gives the tone's phase
+/// independent of its level, so volume changes do not count as errors. A phase step
+/// that differs from the expected one by more than half a sample is reported as
+/// dropped or inserted frames; the small residuals measure frequency error.
+#[derive(Debug, Clone)]
+pub struct ToneVerifier {
+    frequency: f64,
+    channels: usize,
+    /// Expected phase advance per frame (radians)
+    step: f64,
+    /// Previous first-channel value and its frame index
+    previous: Option<(f64, u64)>,
+    /// Last trusted phase and its frame index
+    anchor: Option<(f64, u64)>,
+    /// The last phase estimate straddled a jump and was skipped
+    suspect: bool,
+    residual: f64,
+    residual_frames: u64,
+    frames: u64,
+    silent_frames: u64,
+    discontinuities: Vec<Discontinuity>,
+}
+
+impl ToneVerifier {
+    /// Verify a tone at `frequency` in interleaved audio of the given layout
+    pub fn new(frequency: f64, sample_rate: u32, channels: u8) -> Result<Self, Error> {
+        check_tone(frequency, sample_rate)?;
+        if channels == 0 {
+            return Err(Error::Protocol(
+                "Channel count must be non-zero".to_string(),
+            ));
+        }
+        Ok(Self {
+            frequency,
+            channels: channels as usize,
+            step: TAU * frequency / sample_rate as f64,
+            previous: None,
+            anchor: None,
+            suspect: false,
+            residual: 0.0,
+            residual_frames: 0,
+            frames: 0,
+            silent_frames: 0,
+            discontinuities: Vec::new(),
+        })
+    }
+
+    /// Check the next interleaved samples, continuing from the previous call
+    pub fn push(&mut self, samples: &[Sample]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let value = frame[0].0 as f64 / -(Sample::MIN.0 as f64);
+            let index = self.frames;
+            self.frames += 1;
+            if let Some((previous, previous_index)) = self.previous.replace((value, index)) {
+                self.check_pair(previous, value, previous_index);
+            }
+        }
+    }
+
+    /// Estimate the phase at `index` from it and the following sample
+    fn check_pair(&mut self, x0: f64, x1: f64, index: u64) {
+        // x0 = A sin(phase), x1 = A sin(phase + step)
+        let cosine = (x1 - x0 * self.step.cos()) / self.step.sin();
+        if x0.hypot(cosine) < SILENCE_AMPLITUDE {
+            self.silent_frames += 1;
+            self.anchor = None;
+            self.suspect = false;
+            return;
+        }
+        let phase = x0.atan2(cosine);
+
+        let Some((anchor, anchor_index)) = self.anchor else {
+            self.anchor = Some((phase, index));
+            return;
+        };
+        let frames = index - anchor_index;
+        let deviation = wrap(phase - anchor - self.step * frames as f64);
+        if deviation.abs() <= self.step / 2.0 {
+            self.residual += deviation;
+            self.residual_frames += frames;
+            self.suspect = false;
+        } else if self.suspect {
+            // First clean pair after the jump
+            self.discontinuities.push(Discontinuity {
+                frame: index,
+                offset: (deviation / self.step).round() as i64,
+            });
+            self.suspect = false;
+        } else {
+            // This pair straddles the jump, so its estimate is meaningless
+            self.suspect = true;
+            return;
+        }
+        self.anchor = Some((phase, index));
+    }
+
+    /// Summarize everything checked so far
+    pub fn report(&self) -> ToneReport {
+        let (dropped, inserted) =
+            self.discontinuities
+                .iter()
+                .fold((0, 0), |(dropped, inserted), d| match d.offset {
+                    o if o > 0 => (dropped + o as u64, inserted),
+                    o => (dropped, inserted + o.unsigned_abs()),
+                });
+        let measured_frequency = (self.residual_frames > 0).then(|| {
+            let step = self.step + self.residual / self.residual_frames as f64;
+            self.frequency * step / self.step
+        });
+        ToneReport {
+            frequency: self.frequency,
+            frames: self.frames,
+            silent_frames: self.silent_frames,
+            discontinuities: self.discontinuities.clone(),
+            dropped,
+            inserted,
+            measured_frequency,
+        }
+    }
+}
+
+/// Wrap an angle into (-π, π]
+fn wrap(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(TAU);
+    if wrapped > PI {
+        wrapped - TAU
+    } else {
+        wrapped
+    }
+}
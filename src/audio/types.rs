@@ -1,6 +1,7 @@
 // ABOUTME: Core audio type definitions
 // ABOUTME: Sample (24-bit), AudioFormat, AudioBuffer for zero-copy audio data
 
+use crate::audio::decode::PcmEndian;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -58,6 +59,36 @@ impl Sample {
         (self.0 >> 8) as i16
     }
 
+    /// Convert from unsigned 8-bit sample (128 is silence)
+    #[inline]
+    pub fn from_u8(s: u8) -> Self {
+        Self((s as i32 - 128) << 16)
+    }
+
+    /// Convert to unsigned 8-bit sample (shift right 16 bits, offset by 128)
+    #[inline]
+    pub fn to_u8(self) -> u8 {
+        ((self.0 >> 16) + 128) as u8
+    }
+
+    /// Convert from 32-bit sample (shift right 8 bits)
+    #[inline]
+    pub fn from_i32(s: i32) -> Self {
+        Self(s >> 8)
+    }
+
+    /// Convert to 32-bit sample (shift left 8 bits)
+    #[inline]
+    pub fn to_i32(self) -> i32 {
+        self.0 << 8
+    }
+
+    /// Convert from a float sample in -1.0..=1.0 (clamped)
+    #[inline]
+    pub fn from_f32(s: f32) -> Self {
+        Self((s.clamp(-1.0, 1.0) * Self::MAX.0 as f32) as i32)
+    }
+
     /// Clamp to valid 24-bit range
     #[inline]
     pub fn clamp(self) -> Self {
@@ -70,16 +101,64 @@ impl Sample {
 pub enum Codec {
     /// Uncompressed PCM audio
     Pcm,
+    /// Uncompressed 32-bit float PCM audio
+    PcmFloat,
     /// Opus compressed audio
     Opus,
     /// FLAC lossless compressed audio
     Flac,
     /// MP3 compressed audio
     Mp3,
+    /// Vorbis compressed audio
+    Vorbis,
+    /// AAC compressed audio
+    Aac,
+    /// Apple Lossless audio
+    Alac,
+}
+
+impl Codec {
+    /// Codec name as used on the wire (e.g., "opus")
+    pub fn name(self) -> &'static str {
+        match self {
+            Codec::Pcm => "pcm",
+            Codec::PcmFloat => "pcm_float",
+            Codec::Opus => "opus",
+            Codec::Flac => "flac",
+            Codec::Mp3 => "mp3",
+            Codec::Vorbis => "vorbis",
+            Codec::Aac => "aac",
+            Codec::Alac => "alac",
+        }
+    }
+
+    /// Whether chunks hold raw interleaved samples (integer or float PCM)
+    pub fn is_pcm(self) -> bool {
+        matches!(self, Codec::Pcm | Codec::PcmFloat)
+    }
+
+    /// Look up a codec by its wire name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Codec::Pcm,
+            Codec::PcmFloat,
+            Codec::Opus,
+            Codec::Flac,
+            Codec::Mp3,
+            Codec::Vorbis,
+            Codec::Aac,
+            Codec::Alac,
+        ]
+        .into_iter()
+        .find(|codec| codec.name() == name)
+    }
 }
 
 /// Audio format specification
+///
+/// Build one with [`AudioFormat::new`]; fields may be added in minor releases.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct AudioFormat {
     /// Audio codec used
     pub codec: Codec,
@@ -87,10 +166,44 @@ pub struct AudioFormat {
     pub sample_rate: u32,
     /// Number of audio channels (1 = mono, 2 = stereo)
     pub channels: u8,
-    /// Bit depth per sample (16 or 24)
+    /// Bit depth per sample (8, 16, 24 or 32)
     pub bit_depth: u8,
-    /// Optional codec-specific header data
+    /// Codec-specific header from stream/start, base64 decoded
+    ///
+    /// Opus: an OpusHead, for pre-skip and output gain. Vorbis: the Xiph-laced
+    /// identification, comment, and setup headers. FLAC: STREAMINFO. AAC: the
+    /// AudioSpecificConfig (none for ADTS). ALAC: the magic cookie.
     pub codec_header: Option<Vec<u8>>,
+    /// Byte order of PCM samples, if the server stated it
+    ///
+    /// Without it integer PCM byte order is detected from the audio.
+    pub endian: Option<PcmEndian>,
+}
+
+impl AudioFormat {
+    /// A format with no codec header and no stated byte order
+    pub fn new(codec: Codec, sample_rate: u32, channels: u8, bit_depth: u8) -> Self {
+        Self {
+            codec,
+            sample_rate,
+            channels,
+            bit_depth,
+            codec_header: None,
+            endian: None,
+        }
+    }
+
+    /// Attach the codec header from stream/start
+    pub fn with_codec_header(mut self, codec_header: Vec<u8>) -> Self {
+        self.codec_header = Some(codec_header);
+        self
+    }
+
+    /// State the byte order of PCM samples
+    pub fn with_endian(mut self, endian: PcmEndian) -> Self {
+        self.endian = Some(endian);
+        self
+    }
 }
 
 /// Audio buffer with timestamp (zero-copy via Arc)
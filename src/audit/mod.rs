@@ -0,0 +1,13 @@
+// ABOUTME: Audit logging for bug reports
+// ABOUTME: Rolling history of protocol, sync, scheduling, and output events
+
+/// Rolling audit log recorder
+pub mod recorder;
+/// Deterministic replay of captured sessions
+pub mod replay;
+
+pub use recorder::{AuditEntry, AuditEvent, AuditLog, Direction};
+pub use replay::{
+    BufferState, CapturedEntry, CapturedSession, ChunkDecision, DecisionInputs, Outcome, Replay,
+    ReplayConfig, SyncSnapshot,
+};
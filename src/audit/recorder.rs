@@ -0,0 +1,238 @@
+// ABOUTME: Rolling audit log implementation
+// ABOUTME: Records timestamped events within a time window and dumps them to a file
+
+use crate::diagnostics;
+use crate::error::Error;
+use crate::sync::SyncQuality;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Default audit window (last 60 seconds)
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Direction of a protocol message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Received from the server
+    Inbound,
+    /// Sent to the server
+    Outbound,
+}
+
+/// A single audited event
+#[derive(Debug, Clone)]
+pub enum AuditEvent {
+    /// Protocol message sent or received
+    Protocol {
+        /// Message direction
+        direction: Direction,
+        /// Message type (e.g., "stream/start")
+        message_type: String,
+    },
+    /// Clock sync measurement
+    Sync {
+        /// Measured round-trip time in microseconds
+        rtt_micros: i64,
+        /// Resulting sync quality
+        quality: SyncQuality,
+    },
+    /// Scheduling decision for an audio buffer
+    Schedule {
+        /// Server timestamp of the buffer (microseconds)
+        timestamp: i64,
+        /// Time until playback when scheduled (microseconds, negative if late)
+        lead_micros: i64,
+    },
+    /// Samples handed to the audio output
+    Output {
+        /// Server timestamp of the buffer (microseconds)
+        timestamp: i64,
+        /// Number of samples written
+        samples: usize,
+    },
+    /// Free-form note
+    Note(String),
+}
+
+impl AuditEvent {
+    fn category(&self) -> &'static str {
+        match self {
+            Self::Protocol { .. } => "protocol",
+            Self::Sync { .. } => "sync",
+            Self::Schedule { .. } => "schedule",
+            Self::Output { .. } => "output",
+            Self::Note(_) => "note",
+        }
+    }
+}
+
+/// Audited event with the time it was recorded
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// Local time the event was recorded
+    pub at: Instant,
+    /// The recorded event
+    pub event: AuditEvent,
+}
+
+#[derive(Debug)]
+struct AuditState {
+    entries: VecDeque<AuditEntry>,
+    window: Duration,
+    headers: Vec<(String, String)>,
+}
+
+/// Opt-in rolling audit log
+///
+/// Cloning is cheap; all clones record into the same history.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    state: Arc<Mutex<AuditState>>,
+}
+
+impl AuditLog {
+    /// Create an audit log covering [`DEFAULT_WINDOW`]
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_WINDOW)
+    }
+
+    /// Create an audit log keeping events newer than `window`
+    pub fn with_window(window: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(AuditState {
+                entries: VecDeque::new(),
+                window,
+                headers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Record an event, pruning entries that fell out of the window
+    pub fn record(&self, event: AuditEvent) {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let window = state.window;
+        while let Some(front) = state.entries.front() {
+            if now.duration_since(front.at) > window {
+                state.entries.pop_front();
+            } else {
+                break;
+            }
+        }
+        state.entries.push_back(AuditEntry { at: now, event });
+    }
+
+    /// Set a `key: value` line for the report header, replacing any earlier value
+    ///
+    /// Headers record session context that outlives the window, such as the late
+    /// policy a replay needs.
+    pub fn set_header(&self, key: impl Into<String>, value: impl ToString) {
+        let key = key.into();
+        let value = value.to_string();
+        let mut state = self.state.lock();
+        match state.headers.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value,
+            None => state.headers.push((key, value)),
+        }
+    }
+
+    /// Get a copy of the retained entries, oldest first
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.state.lock().entries.iter().cloned().collect()
+    }
+
+    /// Render the retained history as a self-contained text report
+    pub fn render(&self) -> String {
+        let entries = self.entries();
+        let (window, headers) = {
+            let state = self.state.lock();
+            (state.window, state.headers.clone())
+        };
+        let now = Instant::now();
+        let unix_micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros())
+            .unwrap_or_default();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# sendspin audit log");
+        let _ = writeln!(out, "# crate_version: {}", env!("CARGO_PKG_VERSION"));
+        for line in diagnostics::build_info().to_string().lines().skip(1) {
+            let _ = writeln!(out, "# {}", line);
+        }
+        let _ = writeln!(out, "# dumped_at_unix_us: {}", unix_micros);
+        let _ = writeln!(out, "# window_s: {}", window.as_secs_f64());
+        for (key, value) in &headers {
+            let _ = writeln!(out, "# {}: {}", key, value);
+        }
+        let _ = writeln!(out, "# entries: {}", entries.len());
+        for entry in &entries {
+            // Offsets are relative to the dump time so the newest entry is closest to zero
+            let age = now.duration_since(entry.at);
+            let _ = write!(
+                out,
+                "-{:.6}s [{}] ",
+                age.as_secs_f64(),
+                entry.event.category()
+            );
+            let _ = match &entry.event {
+                AuditEvent::Protocol {
+                    direction,
+                    message_type,
+                } => writeln!(out, "{:?} {}", direction, message_type),
+                AuditEvent::Sync {
+                    rtt_micros,
+                    quality,
+                } => writeln!(out, "rtt={}µs quality={:?}", rtt_micros, quality),
+                AuditEvent::Schedule {
+                    timestamp,
+                    lead_micros,
+                } => writeln!(out, "ts={} lead={}µs", timestamp, lead_micros),
+                AuditEvent::Output { timestamp, samples } => {
+                    writeln!(out, "ts={} samples={}", timestamp, samples)
+                }
+                AuditEvent::Note(note) => writeln!(out, "{}", note),
+            };
+        }
+        out
+    }
+
+    /// Write the rendered report to a file
+    pub fn dump_to(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path.as_ref(), self.render()).map_err(|e| Error::Io(e.to_string()))
+    }
+
+    /// Dump the report to `path` every time the process receives `SIGUSR1`
+    #[cfg(unix)]
+    pub fn spawn_signal_dump(&self, path: impl Into<PathBuf>) -> tokio::task::JoinHandle<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let audit = self.clone();
+        let path = path.into();
+        tokio::spawn(async move {
+            let mut sigusr1 = match signal(SignalKind::user_defined1()) {
+                Ok(s) => s,
+                Err(e) => {
+                    log::error!("Failed to install SIGUSR1 handler: {}", e);
+                    return;
+                }
+            };
+            while sigusr1.recv().await.is_some() {
+                match audit.dump_to(&path) {
+                    Ok(()) => log::info!("Audit log written to {}", path.display()),
+                    Err(e) => log::error!("Failed to write audit log: {}", e),
+                }
+            }
+        })
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
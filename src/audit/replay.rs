@@ -0,0 +1,530 @@
+// ABOUTME: Deterministic replay of captured scheduling sessions
+// ABOUTME: Re-runs the audio scheduler on a virtual clock and explains each chunk's fate
+
+use crate::audio::{AudioBuffer, AudioFormat, Codec};
+use crate::audit::{AuditEvent, AuditLog, Direction};
+use crate::error::Error;
+use crate::scheduler::AudioScheduler;
+use crate::sync::SyncQuality;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Header key recording the scheduler's late policy in microseconds
+pub const MAX_LATE_HEADER: &str = "max_late_us";
+
+/// Default interval between scheduler polls, matching the player's playback loop
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Captured event with its offset from the first entry
+#[derive(Debug, Clone)]
+pub struct CapturedEntry {
+    /// Microseconds since the first captured entry
+    pub at: i64,
+    /// The recorded event
+    pub event: AuditEvent,
+}
+
+/// An audit log loaded for replay
+#[derive(Debug, Clone, Default)]
+pub struct CapturedSession {
+    /// `key: value` lines from the report header
+    pub headers: Vec<(String, String)>,
+    /// Entries, oldest first
+    pub entries: Vec<CapturedEntry>,
+}
+
+impl CapturedSession {
+    /// Capture the current contents of an audit log
+    pub fn from_log(log: &AuditLog) -> Result<Self, Error> {
+        Self::parse(&log.render())
+    }
+
+    /// Load a report written by [`AuditLog::dump_to`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path.as_ref()).map_err(|e| Error::Io(e.to_string()))?;
+        Self::parse(&text)
+    }
+
+    /// Parse a rendered audit report
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let mut session = Self::default();
+        let mut first_age = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('#') {
+                if let Some((key, value)) = header.trim().split_once(": ") {
+                    session.headers.push((key.to_string(), value.to_string()));
+                }
+                continue;
+            }
+            let (age, event) = parse_entry(line).ok_or_else(|| {
+                Error::Storage(format!(
+                    "Invalid audit entry on line {}: {}",
+                    number + 1,
+                    line
+                ))
+            })?;
+            let first = *first_age.get_or_insert(age);
+            session.entries.push(CapturedEntry {
+                at: first - age,
+                event,
+            });
+        }
+        Ok(session)
+    }
+
+    /// Look up a header value
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Late policy the session was recorded with, if any
+    pub fn max_late(&self) -> Option<Duration> {
+        self.header(MAX_LATE_HEADER)?
+            .parse()
+            .ok()
+            .map(Duration::from_micros)
+    }
+}
+
+/// Parse `-{age}s [category] body` into the age in µs and the event
+fn parse_entry(line: &str) -> Option<(i64, AuditEvent)> {
+    let (age, rest) = line.strip_prefix('-')?.split_once("s [")?;
+    let age = (age.parse::<f64>().ok()? * 1_000_000.0).round() as i64;
+    let (category, body) = match rest.split_once("] ") {
+        Some(parts) => parts,
+        None => (rest.strip_suffix(']')?, ""),
+    };
+    let field = |name: &str| {
+        body.split_whitespace()
+            .find_map(|part| part.strip_prefix(name)?.strip_prefix('='))
+            .map(|v| v.trim_end_matches("µs"))
+    };
+    let event = match category {
+        "protocol" => {
+            let (direction, message_type) = body.split_once(' ')?;
+            let direction = match direction {
+                "Inbound" => Direction::Inbound,
+                "Outbound" => Direction::Outbound,
+                _ => return None,
+            };
+            AuditEvent::Protocol {
+                direction,
+                message_type: message_type.to_string(),
+            }
+        }
+        "sync" => AuditEvent::Sync {
+            rtt_micros: field("rtt")?.parse().ok()?,
+            quality: match field("quality")? {
+                "Good" => SyncQuality::Good,
+                "Degraded" => SyncQuality::Degraded,
+                "Lost" => SyncQuality::Lost,
+                _ => return None,
+            },
+        },
+        "schedule" => AuditEvent::Schedule {
+            timestamp: field("ts")?.parse().ok()?,
+            lead_micros: field("lead")?.parse().ok()?,
+        },
+        "output" => AuditEvent::Output {
+            timestamp: field("ts")?.parse().ok()?,
+            samples: field("samples")?.parse().ok()?,
+        },
+        "note" => AuditEvent::Note(body.to_string()),
+        _ => return None,
+    };
+    Some((age, event))
+}
+
+/// Settings for a replay run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayConfig {
+    /// Drop buffers later than this, as [`AudioScheduler::with_max_late`] does
+    pub max_late: Option<Duration>,
+    /// Interval between scheduler polls
+    pub poll_interval: Duration,
+}
+
+impl ReplayConfig {
+    /// Use the late policy recorded in the session
+    pub fn from_session(session: &CapturedSession) -> Self {
+        Self {
+            max_late: session.max_late(),
+            ..Self::default()
+        }
+    }
+
+    /// Replay with a different late policy
+    pub fn with_max_late(mut self, max_late: Option<Duration>) -> Self {
+        self.max_late = max_late;
+        self
+    }
+
+    /// Poll the scheduler at a different interval
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            max_late: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Clock sync measurement in effect at some point of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncSnapshot {
+    /// When the measurement was recorded (µs since capture start)
+    pub at: i64,
+    /// Measured round-trip time in microseconds
+    pub rtt_micros: i64,
+    /// Resulting sync quality
+    pub quality: SyncQuality,
+}
+
+/// What the scheduler decided for a chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Handed to the output, `late_micros` after its play time (negative if early)
+    Played {
+        /// Lateness at hand-off in microseconds
+        late_micros: i64,
+    },
+    /// Discarded for being more than the late policy allows past its play time
+    Dropped {
+        /// Lateness when dropped in microseconds
+        late_micros: i64,
+    },
+    /// Still queued when the replay ended
+    Pending,
+}
+
+/// Queue state seen by the scheduler at some instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferState {
+    /// Buffers scheduled but not yet played or dropped
+    pub queued: usize,
+    /// Server timestamp of the earliest queued buffer
+    pub next_timestamp: Option<i64>,
+    /// Play time of the earliest queued buffer (µs since capture start)
+    pub next_play_at: Option<i64>,
+    /// Play time of the latest queued buffer (µs since capture start)
+    pub last_play_at: Option<i64>,
+}
+
+/// Everything the scheduler's decision depends on at one instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecisionInputs {
+    /// The instant (µs since capture start)
+    pub at: i64,
+    /// Latest clock sync measurement
+    pub sync: Option<SyncSnapshot>,
+    /// Offset between local play time and server timestamp of the latest chunk (µs)
+    pub sync_offset_micros: Option<i64>,
+    /// Queue state
+    pub buffer: BufferState,
+    /// Late policy
+    pub max_late: Option<Duration>,
+}
+
+/// The replayed history of one chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkDecision {
+    /// Server timestamp of the chunk (µs)
+    pub timestamp: i64,
+    /// When the chunk was scheduled (µs since capture start)
+    pub scheduled_at: i64,
+    /// Time until playback when scheduled (µs, negative if already late)
+    pub lead_micros: i64,
+    /// Local play time (µs since capture start)
+    pub play_at: i64,
+    /// When the scheduler played or dropped it (µs since capture start)
+    pub decided_at: Option<i64>,
+    /// What happened to it
+    pub outcome: Outcome,
+    /// When the recorded session handed it to the output, if it did
+    pub recorded_output_at: Option<i64>,
+}
+
+impl ChunkDecision {
+    /// Local play time minus server timestamp (µs)
+    ///
+    /// Jumps between neighbouring chunks point at clock sync corrections.
+    pub fn sync_offset_micros(&self) -> i64 {
+        self.play_at - self.timestamp
+    }
+
+    /// Whether the chunk was played after its play time by more than `threshold`
+    pub fn is_delayed(&self, threshold: Duration) -> bool {
+        matches!(self.outcome, Outcome::Played { late_micros } if late_micros > threshold.as_micros() as i64)
+    }
+
+    /// Whether the chunk was dropped
+    pub fn is_dropped(&self) -> bool {
+        matches!(self.outcome, Outcome::Dropped { .. })
+    }
+}
+
+/// A captured session re-run through [`AudioScheduler`] on a virtual clock
+///
+/// The scheduler is polled every [`ReplayConfig::poll_interval`], so the same session
+/// and config always produce the same decisions.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    config: ReplayConfig,
+    chunks: Vec<ChunkDecision>,
+    syncs: Vec<SyncSnapshot>,
+    end: i64,
+}
+
+impl Replay {
+    /// Replay a session
+    pub fn run(session: &CapturedSession, config: ReplayConfig) -> Self {
+        let mut chunks = Vec::new();
+        let mut syncs = Vec::new();
+        for entry in &session.entries {
+            match entry.event {
+                AuditEvent::Schedule {
+                    timestamp,
+                    lead_micros,
+                } => chunks.push(ChunkDecision {
+                    timestamp,
+                    scheduled_at: entry.at,
+                    lead_micros,
+                    play_at: entry.at + lead_micros,
+                    decided_at: None,
+                    outcome: Outcome::Pending,
+                    recorded_output_at: None,
+                }),
+                AuditEvent::Sync {
+                    rtt_micros,
+                    quality,
+                } => syncs.push(SyncSnapshot {
+                    at: entry.at,
+                    rtt_micros,
+                    quality,
+                }),
+                AuditEvent::Output { timestamp, .. } => {
+                    if let Some(chunk) = chunks
+                        .iter_mut()
+                        .rev()
+                        .find(|c| c.timestamp == timestamp && c.recorded_output_at.is_none())
+                    {
+                        chunk.recorded_output_at = Some(entry.at);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut replay = Self {
+            config,
+            chunks,
+            syncs,
+            end: session.entries.last().map_or(0, |e| e.at),
+        };
+        replay.simulate();
+        replay
+    }
+
+    /// Drive the scheduler over the captured chunks
+    fn simulate(&mut self) {
+        let mut scheduler = AudioScheduler::new();
+        if let Some(max_late) = self.config.max_late {
+            scheduler = scheduler.with_max_late(max_late);
+        }
+        let poll = (self.config.poll_interval.as_micros() as i64).max(1);
+
+        // Virtual clock: capture offsets are mapped onto instants after `origin`
+        let earliest = self.chunks.iter().map(|c| c.play_at).min().unwrap_or(0);
+        let shift = -earliest.min(0);
+        let origin = Instant::now();
+        let instant = |at: i64| origin + Duration::from_micros((at + shift) as u64);
+        let format = AudioFormat {
+            codec: Codec::Pcm,
+            sample_rate: 48000,
+            channels: 2,
+            bit_depth: 16,
+            codec_header: None,
+            endian: None,
+        };
+
+        // Chunks in the scheduler, in the order it will hand them out
+        let mut queued: Vec<usize> = Vec::new();
+        let mut next = 0;
+        let mut now = self.chunks.first().map_or(0, |c| c.scheduled_at);
+        while next < self.chunks.len() || !queued.is_empty() {
+            while let Some(chunk) = self.chunks.get(next).filter(|c| c.scheduled_at <= now) {
+                scheduler.schedule(AudioBuffer {
+                    timestamp: chunk.timestamp,
+                    play_at: instant(chunk.play_at),
+                    samples: Arc::from(Vec::new().into_boxed_slice()),
+                    format: format.clone(),
+                });
+                let pos = queued
+                    .binary_search_by_key(&chunk.timestamp, |&i| self.chunks[i].timestamp)
+                    .unwrap_or_else(|e| e);
+                queued.insert(pos, next);
+                next += 1;
+            }
+
+            let dropped_before = scheduler.dropped();
+            let played = scheduler.next_ready_at(instant(now));
+            let dropped = (scheduler.dropped() - dropped_before) as usize;
+            for index in queued.drain(..dropped) {
+                let chunk = &mut self.chunks[index];
+                chunk.decided_at = Some(now);
+                chunk.outcome = Outcome::Dropped {
+                    late_micros: now - chunk.play_at,
+                };
+            }
+            if played.is_some() {
+                let chunk = &mut self.chunks[queued.remove(0)];
+                chunk.decided_at = Some(now);
+                chunk.outcome = Outcome::Played {
+                    late_micros: now - chunk.play_at,
+                };
+            }
+            now += poll;
+        }
+        self.end = self.end.max(now - poll);
+    }
+
+    /// Settings the replay ran with
+    pub fn config(&self) -> ReplayConfig {
+        self.config
+    }
+
+    /// Every replayed chunk in scheduling order
+    pub fn chunks(&self) -> &[ChunkDecision] {
+        &self.chunks
+    }
+
+    /// End of the replay (µs since capture start)
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// Explain what happened to the chunk with server timestamp `timestamp`
+    ///
+    /// If the timestamp was scheduled more than once, the latest is returned.
+    pub fn explain(&self, timestamp: i64) -> Option<&ChunkDecision> {
+        self.chunks.iter().rev().find(|c| c.timestamp == timestamp)
+    }
+
+    /// Chunks dropped for lateness
+    pub fn dropped(&self) -> impl Iterator<Item = &ChunkDecision> {
+        self.chunks.iter().filter(|c| c.is_dropped())
+    }
+
+    /// Chunks played more than `threshold` after their play time
+    pub fn delayed(&self, threshold: Duration) -> impl Iterator<Item = &ChunkDecision> {
+        self.chunks.iter().filter(move |c| c.is_delayed(threshold))
+    }
+
+    /// Decision inputs as the scheduler saw them at `at` (µs since capture start)
+    ///
+    /// Chunks decided at exactly `at` still count as queued.
+    pub fn inputs_at(&self, at: i64) -> DecisionInputs {
+        let mut buffer = BufferState::default();
+        let mut earliest: Option<&ChunkDecision> = None;
+        for chunk in &self.chunks {
+            if chunk.scheduled_at > at || chunk.decided_at.is_some_and(|d| d < at) {
+                continue;
+            }
+            buffer.queued += 1;
+            if earliest.is_none_or(|e| chunk.timestamp < e.timestamp) {
+                earliest = Some(chunk);
+            }
+            buffer.last_play_at = buffer.last_play_at.max(Some(chunk.play_at));
+        }
+        buffer.next_timestamp = earliest.map(|c| c.timestamp);
+        buffer.next_play_at = earliest.map(|c| c.play_at);
+
+        DecisionInputs {
+            at,
+            sync: self.syncs.iter().rev().find(|s| s.at <= at).copied(),
+            sync_offset_micros: self
+                .chunks
+                .iter()
+                .rev()
+                .find(|c| c.scheduled_at <= at)
+                .map(ChunkDecision::sync_offset_micros),
+            buffer,
+            max_late: self.config.max_late,
+        }
+    }
+}
+
+fn seconds(micros: i64) -> f64 {
+    micros as f64 / 1_000_000.0
+}
+
+impl fmt::Display for DecisionInputs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "at {:.6}s", seconds(self.at))?;
+        match self.sync {
+            Some(sync) => writeln!(
+                f,
+                "  sync: rtt={}µs quality={:?} measured {:.3}s earlier",
+                sync.rtt_micros,
+                sync.quality,
+                seconds(self.at - sync.at)
+            )?,
+            None => writeln!(f, "  sync: no measurement yet")?,
+        }
+        if let Some(offset) = self.sync_offset_micros {
+            writeln!(f, "  sync offset: {}µs", offset)?;
+        }
+        write!(f, "  buffer: {} queued", self.buffer.queued)?;
+        if let (Some(ts), Some(play_at)) = (self.buffer.next_timestamp, self.buffer.next_play_at) {
+            write!(f, ", next ts={} due in {}µs", ts, play_at - self.at)?;
+        }
+        if let Some(last) = self.buffer.last_play_at {
+            write!(f, ", {}µs buffered ahead", (last - self.at).max(0))?;
+        }
+        writeln!(f)?;
+        match self.max_late {
+            Some(max_late) => write!(f, "  late policy: drop after {}µs", max_late.as_micros()),
+            None => write!(f, "  late policy: play everything"),
+        }
+    }
+}
+
+impl fmt::Display for ChunkDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ts={} scheduled at {:.6}s with lead {}µs, due at {:.6}s: ",
+            self.timestamp,
+            seconds(self.scheduled_at),
+            self.lead_micros,
+            seconds(self.play_at)
+        )?;
+        match (self.outcome, self.decided_at) {
+            (Outcome::Played { late_micros }, Some(at)) if late_micros > 0 => {
+                write!(f, "played {}µs late at {:.6}s", late_micros, seconds(at))
+            }
+            (Outcome::Played { .. }, Some(at)) => {
+                write!(f, "played on time at {:.6}s", seconds(at))
+            }
+            (Outcome::Dropped { late_micros }, Some(at)) => {
+                write!(f, "dropped {}µs late at {:.6}s", late_micros, seconds(at))
+            }
+            _ => write!(f, "still queued"),
+        }
+    }
+}
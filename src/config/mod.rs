@@ -0,0 +1,232 @@
+// ABOUTME: Settings files for sendspin applications
+// ABOUTME: Loads client and player settings from TOML with SS_* environment overrides
+
+use crate::audio::{ResampleQuality, VolumeTaper};
+use crate::error::Error;
+use crate::player::PlayerConfig;
+use crate::protocol::client::ClientConfig;
+use crate::protocol::{Keepalive, PayloadKey};
+use crate::scheduler::LatencyProfile;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Settings of a sendspin client application
+///
+/// Read from a TOML file with [`load`](Self::load), then overridden by `SS_*`
+/// environment variables with [`with_env`](Self::with_env). Unset settings keep
+/// the library defaults.
+///
+/// ```toml
+/// [client]
+/// server = "ws://192.168.1.10:8927/sendspin"
+/// name = "Kitchen"
+/// clock_sync_interval_ms = 5000
+///
+/// [player]
+/// profile = "standard"
+/// start_buffer_ms = 300
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Connection settings
+    pub client: ClientSettings,
+    /// Playback settings
+    pub player: PlayerSettings,
+}
+
+/// The `[client]` table
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientSettings {
+    /// WebSocket URL of the server (`SS_SERVER`)
+    pub server: Option<String>,
+    /// Client name shown by the server (`SS_NAME`)
+    pub name: Option<String>,
+    /// Stable client identifier (`SS_CLIENT_ID`)
+    pub client_id: Option<String>,
+    /// Interval between clock sync requests in milliseconds
+    pub clock_sync_interval_ms: Option<u64>,
+    /// Ping interval in milliseconds; the server is given up after three silent intervals
+    pub keepalive_interval_ms: Option<u64>,
+    /// Shared 64-hex-digit key to request payload encryption (`SS_PAYLOAD_KEY`)
+    pub payload_key: Option<String>,
+}
+
+/// The `[player]` table
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerSettings {
+    /// Latency profile name, `standard` or `tv` (`SS_PLAY_PROFILE`)
+    pub profile: Option<String>,
+    /// Output device name (`SS_PLAY_DEVICE`)
+    pub device: Option<String>,
+    /// Minimum scheduling lead in milliseconds (`SS_PLAY_MIN_LEAD_MS`)
+    pub min_lead_ms: Option<u64>,
+    /// Audio to buffer before playback starts, in milliseconds (`SS_PLAY_START_BUFFER_MS`)
+    pub start_buffer_ms: Option<u64>,
+    /// Drop chunks later than this many milliseconds (`SS_PLAY_MAX_LATE_MS`)
+    pub max_late_ms: Option<u64>,
+    /// Interval between latency reports in milliseconds; 0 turns them off
+    pub latency_report_interval_ms: Option<u64>,
+    /// Replace chunks that fail to decode with silence
+    pub conceal_decode_errors: Option<bool>,
+    /// Length of the fade on play, pause, stop, and stream end in milliseconds; 0 turns it off
+    pub fade_ms: Option<u64>,
+    /// dB between volume 1 and volume 100; 0 makes volume linear
+    pub volume_range_db: Option<u32>,
+    /// Left/right balance from -1.0 (left only) to 1.0 (right only)
+    pub balance: Option<f32>,
+    /// Gain in dB added to each channel, first channel first
+    pub channel_trim_db: Option<Vec<f32>>,
+    /// Resample every stream to this rate in Hz, for devices that only run at one
+    pub output_rate: Option<u32>,
+    /// Resampler quality, `linear`, `fast`, `balanced`, or `best`
+    pub resample_quality: Option<String>,
+}
+
+impl Config {
+    /// Parse settings from TOML text
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        toml::from_str(text).map_err(|e| Error::Config(e.to_string()))
+    }
+
+    /// Read settings from a TOML file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Io(format!("Cannot read {}: {}", path.display(), e)))?;
+        Self::from_toml(&text).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Render the settings as TOML, e.g. to write a starting config file
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap_or_default()
+    }
+
+    /// Override settings from the process environment
+    ///
+    /// See [`with_vars`](Self::with_vars) for the variables.
+    pub fn with_env(self) -> Result<Self, Error> {
+        self.with_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Override settings from the variables `lookup` knows
+    ///
+    /// `SS_SERVER`, `SS_NAME`, `SS_CLIENT_ID`, `SS_PAYLOAD_KEY`, `SS_PLAY_PROFILE`,
+    /// `SS_PLAY_DEVICE`, `SS_PLAY_MIN_LEAD_MS`, `SS_PLAY_START_BUFFER_MS`, and
+    /// `SS_PLAY_MAX_LATE_MS`. Fails on a number that does not parse.
+    pub fn with_vars<F>(mut self, lookup: F) -> Result<Self, Error>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let client = &mut self.client;
+        override_with(&lookup, "SS_SERVER", &mut client.server)?;
+        override_with(&lookup, "SS_NAME", &mut client.name)?;
+        override_with(&lookup, "SS_CLIENT_ID", &mut client.client_id)?;
+        override_with(&lookup, "SS_PAYLOAD_KEY", &mut client.payload_key)?;
+        let player = &mut self.player;
+        override_with(&lookup, "SS_PLAY_PROFILE", &mut player.profile)?;
+        override_with(&lookup, "SS_PLAY_DEVICE", &mut player.device)?;
+        override_with(&lookup, "SS_PLAY_MIN_LEAD_MS", &mut player.min_lead_ms)?;
+        override_with(
+            &lookup,
+            "SS_PLAY_START_BUFFER_MS",
+            &mut player.start_buffer_ms,
+        )?;
+        override_with(&lookup, "SS_PLAY_MAX_LATE_MS", &mut player.max_late_ms)?;
+        Ok(self)
+    }
+
+    /// Latency profile with the configured overrides applied
+    pub fn profile(&self) -> Result<LatencyProfile, Error> {
+        let player = &self.player;
+        let mut profile = match player.profile {
+            Some(ref name) => LatencyProfile::from_name(name)
+                .ok_or_else(|| Error::Config(format!("Unknown latency profile '{}'", name)))?,
+            None => LatencyProfile::standard(),
+        };
+        if let Some(ms) = player.min_lead_ms {
+            profile.min_lead = Duration::from_millis(ms);
+        }
+        if let Some(ms) = player.start_buffer_ms {
+            profile.start_buffer = Duration::from_millis(ms);
+        }
+        if let Some(ms) = player.max_late_ms {
+            profile.max_late = Some(Duration::from_millis(ms));
+        }
+        Ok(profile)
+    }
+
+    /// Settings for a [`Player`](crate::player::Player)
+    pub fn player_config(&self) -> Result<PlayerConfig, Error> {
+        let mut config = PlayerConfig {
+            profile: self.profile()?,
+            device: self.player.device.clone(),
+            ..PlayerConfig::default()
+        };
+        if let Some(ms) = self.player.latency_report_interval_ms {
+            config.latency_report_interval = (ms > 0).then(|| Duration::from_millis(ms));
+        }
+        if let Some(conceal) = self.player.conceal_decode_errors {
+            config.decode_errors.conceal = conceal;
+        }
+        if let Some(ms) = self.player.fade_ms {
+            config.fade.duration = Duration::from_millis(ms);
+        }
+        config.volume_taper = match self.player.volume_range_db {
+            Some(0) => VolumeTaper::Linear,
+            Some(db) => VolumeTaper::Logarithmic {
+                range_db: db as f32,
+            },
+            None => VolumeTaper::default(),
+        };
+        if let Some(balance) = self.player.balance {
+            config.balance.set_balance(balance);
+        }
+        for (channel, &db) in self.player.channel_trim_db.iter().flatten().enumerate() {
+            config.balance.set_trim_db(channel, db);
+        }
+        config.output_rate = self.player.output_rate;
+        if let Some(ref name) = self.player.resample_quality {
+            config.resample_quality = ResampleQuality::from_name(name)
+                .ok_or_else(|| Error::Config(format!("Unknown resample quality '{}'", name)))?;
+        }
+        Ok(config)
+    }
+
+    /// Connection options for a [`ProtocolClient`](crate::ProtocolClient)
+    pub fn client_config(&self) -> Result<ClientConfig, Error> {
+        let client = &self.client;
+        let mut config = ClientConfig::default();
+        if let Some(ms) = client.clock_sync_interval_ms {
+            config.clock_sync_interval = Some(Duration::from_millis(ms));
+        }
+        if let Some(ms) = client.keepalive_interval_ms {
+            config.keepalive = Some(Keepalive::new(Duration::from_millis(ms)));
+        }
+        if let Some(ref key) = client.payload_key {
+            config.payload_key = Some(PayloadKey::from_hex(key)?);
+        }
+        Ok(config)
+    }
+}
+
+/// Replace `setting` with the value of `key`, if set
+fn override_with<T, F>(lookup: &F, key: &str, setting: &mut Option<T>) -> Result<(), Error>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+    F: Fn(&str) -> Option<String>,
+{
+    if let Some(value) = lookup(key) {
+        let parsed = value
+            .trim()
+            .parse()
+            .map_err(|e| Error::Config(format!("{}={}: {}", key, value, e)))?;
+        *setting = Some(parsed);
+    }
+    Ok(())
+}
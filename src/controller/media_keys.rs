@@ -0,0 +1,223 @@
+// ABOUTME: Media key and OS "now playing" integration for controllers
+// ABOUTME: Turns key presses into controller commands and mirrors metadata to a media session
+
+use super::{Controller, ServerStateCache};
+use crate::error::Error;
+use crate::group::GroupManager;
+use crate::protocol::messages::{ControllerCommand, ControllerCommandKind};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+/// A media key press or transport request from the OS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaKey {
+    /// Start playback
+    Play,
+    /// Pause playback
+    Pause,
+    /// Pause when playing, play otherwise
+    PlayPause,
+    /// Stop playback
+    Stop,
+    /// Skip to the next track
+    Next,
+    /// Go back to the previous track
+    Previous,
+    /// Raise the group volume by one step
+    VolumeUp,
+    /// Lower the group volume by one step
+    VolumeDown,
+    /// Toggle the group mute state
+    Mute,
+    /// Seek forward by one step
+    SeekForward,
+    /// Seek back by one step
+    SeekBackward,
+    /// Seek to a position in the track
+    SetPosition(Duration),
+}
+
+/// What the OS "now playing" surface shows
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaDisplay {
+    /// Track title
+    pub title: Option<String>,
+    /// Artist name
+    pub artist: Option<String>,
+    /// Album name
+    pub album: Option<String>,
+    /// Artwork URL
+    pub artwork_url: Option<String>,
+    /// Track duration, when known
+    pub duration: Option<Duration>,
+    /// Track position when the server reported it
+    pub position: Option<Duration>,
+    /// Whether the track is playing
+    pub playing: bool,
+}
+
+impl MediaDisplay {
+    /// What to show for the state a controller has cached
+    pub fn from_state(state: &ServerStateCache) -> Self {
+        let Some(ref metadata) = state.metadata else {
+            return Self::default();
+        };
+        let progress = metadata.progress.as_ref();
+        let micros = |us: i64| Duration::from_micros(us.max(0) as u64);
+        Self {
+            title: metadata.title.clone(),
+            artist: metadata.artist.clone(),
+            album: metadata.album.clone(),
+            artwork_url: metadata.artwork_url.clone(),
+            duration: progress
+                .filter(|p| p.duration > 0)
+                .map(|p| micros(p.duration)),
+            position: progress.map(|p| micros(p.position)),
+            playing: progress.is_some_and(|p| !p.is_paused()),
+        }
+    }
+}
+
+/// An OS media session, such as MPRIS, SMTC, or the macOS now-playing center
+///
+/// Implement it over a platform binding (e.g. `souvlaki`) and forward the keys
+/// that binding reports to [`MediaKeys::run`].
+pub trait MediaSession: Send {
+    /// Show `display` in the OS "now playing" surface
+    fn set_now_playing(&mut self, display: &MediaDisplay) -> Result<(), Error>;
+}
+
+/// Translates media keys into controller commands
+///
+/// Only commands the server lists in `supported_commands` are sent. Play/pause
+/// goes by the group playback state when a [`GroupManager`] is attached, and by
+/// the reported track progress otherwise.
+#[derive(Debug, Clone)]
+pub struct MediaKeys {
+    controller: Controller,
+    group: Option<GroupManager>,
+    volume_step: u8,
+    seek_step: Duration,
+}
+
+impl MediaKeys {
+    /// Send commands through `controller`, stepping volume by 5 and seeking by 10s
+    pub fn new(controller: Controller) -> Self {
+        Self {
+            controller,
+            group: None,
+            volume_step: 5,
+            seek_step: Duration::from_secs(10),
+        }
+    }
+
+    /// Decide play/pause by the playback state of `group`
+    pub fn with_group(mut self, group: GroupManager) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Volume change per volume key press (0-100 scale)
+    pub fn with_volume_step(mut self, step: u8) -> Self {
+        self.volume_step = step;
+        self
+    }
+
+    /// Seek distance per seek key press
+    pub fn with_seek_step(mut self, step: Duration) -> Self {
+        self.seek_step = step;
+        self
+    }
+
+    /// Whether the group is playing, as far as this client knows
+    pub fn is_playing(&self) -> bool {
+        match self.group {
+            Some(ref group) => group.state().is_playing(),
+            None => MediaDisplay::from_state(&self.controller.state()).playing,
+        }
+    }
+
+    /// The command a key press stands for (None while the volume or mute
+    /// state it needs is unknown)
+    pub fn command(&self, key: MediaKey) -> Option<ControllerCommand> {
+        let seek_step = self.seek_step.as_micros() as i64;
+        Some(match key {
+            MediaKey::Play => ControllerCommand::new(ControllerCommandKind::Play),
+            MediaKey::Pause => ControllerCommand::new(ControllerCommandKind::Pause),
+            MediaKey::PlayPause => {
+                let pause =
+                    self.is_playing() && self.controller.supports(&ControllerCommandKind::Pause);
+                ControllerCommand::new(if pause {
+                    ControllerCommandKind::Pause
+                } else {
+                    ControllerCommandKind::Play
+                })
+            }
+            MediaKey::Stop => ControllerCommand::new(ControllerCommandKind::Stop),
+            MediaKey::Next => ControllerCommand::new(ControllerCommandKind::Next),
+            MediaKey::Previous => ControllerCommand::new(ControllerCommandKind::Previous),
+            MediaKey::VolumeUp => {
+                let volume = self.controller.volume()?;
+                ControllerCommand::volume(volume.saturating_add(self.volume_step).min(100))
+            }
+            MediaKey::VolumeDown => {
+                let volume = self.controller.volume()?;
+                ControllerCommand::volume(volume.saturating_sub(self.volume_step))
+            }
+            MediaKey::Mute => ControllerCommand::mute(!self.controller.muted()?),
+            MediaKey::SeekForward => ControllerCommand::seek_by(seek_step),
+            MediaKey::SeekBackward => ControllerCommand::seek_by(-seek_step),
+            MediaKey::SetPosition(position) => ControllerCommand::seek(position.as_micros() as i64),
+        })
+    }
+
+    /// Send the command for a key press
+    ///
+    /// Fails without sending if the server does not support it or the state it
+    /// needs has not arrived yet.
+    pub async fn handle(&self, key: MediaKey) -> Result<(), Error> {
+        let command = self
+            .command(key)
+            .ok_or_else(|| Error::Protocol(format!("No controller state for {:?} yet", key)))?;
+        self.controller.send(command).await
+    }
+
+    /// Handle keys from `keys` and keep `session` showing the current track
+    ///
+    /// Runs until `keys` closes. Commands the server refuses are logged and
+    /// skipped, as a key press has nobody to report the error to.
+    pub async fn run(self, mut session: impl MediaSession, mut keys: UnboundedReceiver<MediaKey>) {
+        let mut changes = self.controller.watch();
+        let mut group_changes = self.group.as_ref().map(GroupManager::watch);
+        let mut shown = None;
+        loop {
+            let mut display = MediaDisplay::from_state(&changes.borrow_and_update());
+            if let Some(ref mut group) = group_changes {
+                display.playing = group.borrow_and_update().is_playing();
+            }
+            if shown.as_ref() != Some(&display) {
+                if let Err(e) = session.set_now_playing(&display) {
+                    log::warn!("Cannot update the OS media session: {}", e);
+                }
+                shown = Some(display);
+            }
+            tokio::select! {
+                key = keys.recv() => match key {
+                    Some(key) => {
+                        if let Err(e) = self.handle(key).await {
+                            log::warn!("Media key {:?} ignored: {}", key, e);
+                        }
+                    }
+                    None => break,
+                },
+                Ok(()) = changes.changed() => {}
+                Some(Ok(())) = async {
+                    match group_changes {
+                        Some(ref mut group) => Some(group.changed().await),
+                        None => None,
+                    }
+                } => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// ABOUTME: High-level controller for group playback
+// ABOUTME: Caches the latest server/state and sends only commands the server supports
+
+/// Media keys and the OS "now playing" surface
+pub mod media_keys;
+/// MPRIS media session over D-Bus, with the "mpris" feature
+#[cfg(feature = "mpris")]
+pub mod mpris;
+
+use crate::error::Error;
+use crate::protocol::messages::{
+    ClientCommand, ControllerCommand, ControllerCommandKind, ControllerState, Message,
+    MetadataState,
+};
+use crate::protocol::WsSender;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+pub use media_keys::{MediaDisplay, MediaKey, MediaKeys, MediaSession};
+#[cfg(feature = "mpris")]
+pub use mpris::MprisSession;
+
+/// The latest server/state seen by a [`Controller`]
+#[derive(Debug, Clone, Default)]
+pub struct ServerStateCache {
+    /// Supported commands, volume, and mute state of the group
+    pub controller: Option<ControllerState>,
+    /// Now-playing metadata
+    pub metadata: Option<MetadataState>,
+}
+
+impl ServerStateCache {
+    /// Whether the server accepts `command` from this client
+    pub fn supports(&self, command: &ControllerCommandKind) -> bool {
+        self.controller.as_ref().is_some_and(|state| {
+            state
+                .supported_commands
+                .iter()
+                .any(|name| command == name.as_str())
+        })
+    }
+}
+
+/// Controls the playback of this client's group
+///
+/// Feed it every message from the server with [`apply`](Self::apply); it keeps
+/// the latest controller and metadata state, and its command methods check the
+/// server's `supported_commands` before sending anything. Cloning is cheap; all
+/// clones share the same state.
+///
+/// ```no_run
+/// # async fn example(client: sendspin::ProtocolClient) -> Result<(), sendspin::Error> {
+/// use sendspin::controller::Controller;
+///
+/// let controller = Controller::new(client.sender());
+/// let mut messages = client.into_parts().messages;
+/// while let Some(msg) = messages.recv().await {
+///     controller.apply(&msg);
+///     if controller.volume() == Some(0) {
+///         controller.set_volume(20).await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Controller {
+    sender: WsSender,
+    state: Arc<watch::Sender<ServerStateCache>>,
+}
+
+impl Controller {
+    /// Send commands through `sender`
+    pub fn new(sender: WsSender) -> Self {
+        Self {
+            sender,
+            state: Arc::new(watch::Sender::new(ServerStateCache::default())),
+        }
+    }
+
+    /// Take in a message from the server; only server/state changes anything
+    ///
+    /// Each part of a server/state replaces the cached one; absent parts are kept.
+    pub fn apply(&self, msg: &Message) {
+        let Message::ServerState(state) = msg else {
+            return;
+        };
+        self.state.send_modify(|cache| {
+            if let Some(ref controller) = state.controller {
+                cache.controller = Some(controller.clone());
+            }
+            if let Some(ref metadata) = state.metadata {
+                cache.metadata = Some(metadata.clone());
+            }
+        });
+    }
+
+    /// The latest cached state
+    pub fn state(&self) -> ServerStateCache {
+        self.state.borrow().clone()
+    }
+
+    /// Watch the cached state, e.g. to redraw a UI when it changes
+    pub fn watch(&self) -> watch::Receiver<ServerStateCache> {
+        self.state.subscribe()
+    }
+
+    /// Group volume (0-100), once the server has reported it
+    pub fn volume(&self) -> Option<u8> {
+        self.state.borrow().controller.as_ref().map(|c| c.volume)
+    }
+
+    /// Group mute state, once the server has reported it
+    pub fn muted(&self) -> Option<bool> {
+        self.state.borrow().controller.as_ref().map(|c| c.muted)
+    }
+
+    /// Whether the server accepts `command` from this client
+    pub fn supports(&self, command: &ControllerCommandKind) -> bool {
+        self.state.borrow().supports(command)
+    }
+
+    /// Start playback
+    pub async fn play(&self) -> Result<(), Error> {
+        self.send(ControllerCommand::new(ControllerCommandKind::Play))
+            .await
+    }
+
+    /// Pause playback
+    pub async fn pause(&self) -> Result<(), Error> {
+        self.send(ControllerCommand::new(ControllerCommandKind::Pause))
+            .await
+    }
+
+    /// Stop playback
+    pub async fn stop(&self) -> Result<(), Error> {
+        self.send(ControllerCommand::new(ControllerCommandKind::Stop))
+            .await
+    }
+
+    /// Skip to the next track
+    pub async fn next(&self) -> Result<(), Error> {
+        self.send(ControllerCommand::new(ControllerCommandKind::Next))
+            .await
+    }
+
+    /// Go back to the previous track
+    pub async fn previous(&self) -> Result<(), Error> {
+        self.send(ControllerCommand::new(ControllerCommandKind::Previous))
+            .await
+    }
+
+    /// Set the group volume (0-100)
+    pub async fn set_volume(&self, volume: u8) -> Result<(), Error> {
+        if volume > 100 {
+            return Err(Error::Protocol(format!(
+                "Volume must be 0-100, got {}",
+                volume
+            )));
+        }
+        self.send(ControllerCommand::volume(volume)).await
+    }
+
+    /// Mute or unmute the group
+    pub async fn set_mute(&self, mute: bool) -> Result<(), Error> {
+        self.send(ControllerCommand::mute(mute)).await
+    }
+
+    /// Seek the current track to `position_us` microseconds
+    pub async fn seek(&self, position_us: i64) -> Result<(), Error> {
+        self.send(ControllerCommand::seek(position_us)).await
+    }
+
+    /// Send any controller command the server supports
+    ///
+    /// Fails without sending if no server/state has arrived yet or the server
+    /// does not list the command in `supported_commands`.
+    pub async fn send(&self, command: ControllerCommand) -> Result<(), Error> {
+        {
+            let state = self.state.borrow();
+            if state.controller.is_none() {
+                return Err(Error::Protocol(
+                    "No controller state from the server yet".to_string(),
+                ));
+            }
+            if !state.supports(&command.command) {
+                return Err(Error::Protocol(format!(
+                    "Server does not support the '{}' command",
+                    command.command
+                )));
+            }
+        }
+        self.sender
+            .send_message(Message::ClientCommand(ClientCommand {
+                controller: Some(command),
+            }))
+            .await
+    }
+}
+
+impl std::fmt::Debug for Controller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Controller")
+            .field("state", &*self.state.borrow())
+            .finish_non_exhaustive()
+    }
+}
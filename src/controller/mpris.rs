@@ -0,0 +1,258 @@
+// ABOUTME: MPRIS media session on the D-Bus session bus (feature "mpris")
+// ABOUTME: Publishes now-playing state to Linux desktops and turns their transport calls into media keys
+
+use super::media_keys::{MediaDisplay, MediaKey, MediaSession};
+use crate::error::Error;
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::{MatchRule, SignalArgs};
+use dbus::nonblock::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged;
+use dbus::nonblock::SyncConnection;
+use dbus::Path;
+use dbus_crossroads::{Crossroads, IfaceBuilder};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_ID: &str = "/org/sendspin/track/current";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// State shared by the D-Bus handlers and the session
+struct Player {
+    identity: String,
+    display: Arc<Mutex<MediaDisplay>>,
+    keys: UnboundedSender<MediaKey>,
+}
+
+impl Player {
+    fn key(&self, key: MediaKey) {
+        // The receiver only closes when MediaKeys::run has returned
+        let _ = self.keys.send(key);
+    }
+}
+
+fn playback_status(display: &MediaDisplay) -> String {
+    match (display.playing, display.title.is_some()) {
+        (true, _) => "Playing",
+        (false, true) => "Paused",
+        (false, false) => "Stopped",
+    }
+    .to_string()
+}
+
+fn metadata(display: &MediaDisplay) -> PropMap {
+    let mut map = PropMap::new();
+    let mut insert = |key: &str, value: Box<dyn RefArg>| {
+        map.insert(key.to_string(), Variant(value));
+    };
+    let has_track = display.title.is_some();
+    insert(
+        "mpris:trackid",
+        Box::new(Path::from(if has_track { TRACK_ID } else { NO_TRACK })),
+    );
+    if let Some(ref title) = display.title {
+        insert("xesam:title", Box::new(title.clone()));
+    }
+    if let Some(ref artist) = display.artist {
+        insert("xesam:artist", Box::new(vec![artist.clone()]));
+    }
+    if let Some(ref album) = display.album {
+        insert("xesam:album", Box::new(album.clone()));
+    }
+    if let Some(ref url) = display.artwork_url {
+        insert("mpris:artUrl", Box::new(url.clone()));
+    }
+    if let Some(duration) = display.duration {
+        insert("mpris:length", Box::new(duration.as_micros() as i64));
+    }
+    map
+}
+
+fn position(display: &MediaDisplay) -> i64 {
+    display.position.map_or(0, |p| p.as_micros() as i64)
+}
+
+fn dbus_error(e: dbus::Error) -> Error {
+    Error::Connection(format!("D-Bus: {}", e))
+}
+
+fn build(cr: &mut Crossroads, player: Player) {
+    let root = cr.register(ROOT_IFACE, |b: &mut IfaceBuilder<Player>| {
+        b.method("Raise", (), (), |_, _, _: ()| Ok(()));
+        b.method("Quit", (), (), |_, _, _: ()| Ok(()));
+        b.property("CanQuit").get(|_, _| Ok(false));
+        b.property("CanRaise").get(|_, _| Ok(false));
+        b.property("HasTrackList").get(|_, _| Ok(false));
+        b.property("Identity")
+            .get(|_, player: &mut Player| Ok(player.identity.clone()));
+        b.property("SupportedUriSchemes")
+            .get(|_, _| Ok(Vec::<String>::new()));
+        b.property("SupportedMimeTypes")
+            .get(|_, _| Ok(Vec::<String>::new()));
+    });
+    let transport = cr.register(PLAYER_IFACE, |b: &mut IfaceBuilder<Player>| {
+        for (name, key) in [
+            ("Play", MediaKey::Play),
+            ("Pause", MediaKey::Pause),
+            ("PlayPause", MediaKey::PlayPause),
+            ("Stop", MediaKey::Stop),
+            ("Next", MediaKey::Next),
+            ("Previous", MediaKey::Previous),
+        ] {
+            b.method(name, (), (), move |_, player: &mut Player, _: ()| {
+                player.key(key);
+                Ok(())
+            });
+        }
+        b.method(
+            "Seek",
+            ("Offset",),
+            (),
+            |_, player: &mut Player, (offset,): (i64,)| {
+                let target = position(&player.display.lock()).saturating_add(offset);
+                player.key(MediaKey::SetPosition(Duration::from_micros(
+                    target.max(0) as u64
+                )));
+                Ok(())
+            },
+        );
+        b.method(
+            "SetPosition",
+            ("TrackId", "Position"),
+            (),
+            |_, player: &mut Player, (_, position): (Path<'static>, i64)| {
+                player.key(MediaKey::SetPosition(Duration::from_micros(
+                    position.max(0) as u64,
+                )));
+                Ok(())
+            },
+        );
+        b.method("OpenUri", ("Uri",), (), |_, _, _: (String,)| Ok(()));
+        b.property("PlaybackStatus")
+            .emits_changed_true()
+            .get(|_, player: &mut Player| Ok(playback_status(&player.display.lock())));
+        b.property("Metadata")
+            .emits_changed_true()
+            .get(|_, player: &mut Player| Ok(metadata(&player.display.lock())));
+        b.property("Position")
+            .emits_changed_false()
+            .get(|_, player: &mut Player| Ok(position(&player.display.lock())));
+        b.property("Rate").get(|_, _| Ok(1.0));
+        b.property("MinimumRate").get(|_, _| Ok(1.0));
+        b.property("MaximumRate").get(|_, _| Ok(1.0));
+        for name in [
+            "CanGoNext",
+            "CanGoPrevious",
+            "CanPlay",
+            "CanPause",
+            "CanSeek",
+            "CanControl",
+        ] {
+            b.property(name).get(|_, _| Ok(true));
+        }
+    });
+    cr.insert(OBJECT_PATH, &[root, transport], player);
+}
+
+/// An MPRIS player on the session bus, for [`MediaKeys::run`](super::MediaKeys::run)
+///
+/// Desktop media widgets and the media keys they handle reach the controller
+/// through the key receiver returned by [`MprisSession::connect`].
+pub struct MprisSession {
+    connection: Arc<SyncConnection>,
+    display: Arc<Mutex<MediaDisplay>>,
+    io: JoinHandle<()>,
+}
+
+impl MprisSession {
+    /// Claim `org.mpris.MediaPlayer2.<name>` on the session bus
+    ///
+    /// `identity` is the player name desktops show. Must be called from
+    /// within a Tokio runtime.
+    pub async fn connect(
+        name: &str,
+        identity: &str,
+    ) -> Result<(Self, UnboundedReceiver<MediaKey>), Error> {
+        let (resource, connection) =
+            dbus_tokio::connection::new_session_sync().map_err(dbus_error)?;
+        let io = tokio::spawn(async move {
+            let e = resource.await;
+            log::warn!("Lost the D-Bus connection: {}", e);
+        });
+
+        let (keys, receiver) = mpsc::unbounded_channel();
+        let display = Arc::new(Mutex::new(MediaDisplay::default()));
+        let mut cr = Crossroads::new();
+        build(
+            &mut cr,
+            Player {
+                identity: identity.to_string(),
+                display: display.clone(),
+                keys,
+            },
+        );
+        connection.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |msg, conn| {
+                if cr.handle_message(msg, conn).is_err() {
+                    log::debug!("Unhandled D-Bus method call");
+                }
+                true
+            }),
+        );
+
+        let bus_name = format!("{}.{}", ROOT_IFACE, name);
+        if let Err(e) = connection.request_name(bus_name, false, true, true).await {
+            io.abort();
+            return Err(dbus_error(e));
+        }
+        Ok((
+            Self {
+                connection,
+                display,
+                io,
+            },
+            receiver,
+        ))
+    }
+}
+
+impl MediaSession for MprisSession {
+    fn set_now_playing(&mut self, display: &MediaDisplay) -> Result<(), Error> {
+        *self.display.lock() = display.clone();
+        let mut changed = PropMap::new();
+        changed.insert(
+            "PlaybackStatus".to_string(),
+            Variant(Box::new(playback_status(display))),
+        );
+        changed.insert("Metadata".to_string(), Variant(Box::new(metadata(display))));
+        let signal = PropertiesPropertiesChanged {
+            interface_name: PLAYER_IFACE.to_string(),
+            changed_properties: changed,
+            invalidated_properties: Vec::new(),
+        };
+        self.connection
+            .send(signal.to_emit_message(&Path::from(OBJECT_PATH)))
+            .map(|_| ())
+            .map_err(|()| Error::Connection("D-Bus: cannot send PropertiesChanged".to_string()))
+    }
+}
+
+impl Drop for MprisSession {
+    fn drop(&mut self) {
+        self.io.abort();
+    }
+}
+
+impl std::fmt::Debug for MprisSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MprisSession")
+            .field("unique_name", &self.connection.unique_name().to_string())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,187 @@
+// ABOUTME: Build and runtime environment inspection
+// ABOUTME: Static build info plus probed audio backends, devices, and async runtime
+
+use crate::audio::decode::SUPPORTED_CODECS;
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+use std::fmt;
+
+/// Cargo features enabled at build time
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "gui") {
+        features.push("gui");
+    }
+    if cfg!(feature = "schema") {
+        features.push("schema");
+    }
+    if cfg!(feature = "opus") {
+        features.push("opus");
+    }
+    if cfg!(feature = "vorbis") {
+        features.push("vorbis");
+    }
+    if cfg!(feature = "symphonia") {
+        features.push("symphonia");
+    }
+    if cfg!(feature = "resample") {
+        features.push("resample");
+    }
+    if cfg!(feature = "mpris") {
+        features.push("mpris");
+    }
+    features
+}
+
+/// What this build of the library can do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    /// Crate version
+    pub crate_version: &'static str,
+    /// Sendspin protocol version spoken
+    pub protocol_version: u32,
+    /// Codecs with a decoder compiled in
+    pub codecs: Vec<&'static str>,
+    /// Audio output implementations compiled in
+    pub outputs: Vec<&'static str>,
+    /// Enabled Cargo features
+    pub features: Vec<&'static str>,
+    /// `debug` or `release`
+    pub profile: &'static str,
+    /// Target operating system
+    pub target_os: &'static str,
+    /// Target CPU architecture
+    pub target_arch: &'static str,
+    /// Target family (`unix`, `windows`, `wasm`)
+    pub target_family: &'static str,
+}
+
+/// An audio host API and its output devices
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioBackend {
+    /// Host API name (e.g., ALSA, CoreAudio, WASAPI)
+    pub name: String,
+    /// Whether this is the platform's default host
+    pub default: bool,
+    /// Name of the default output device, if any
+    pub default_output: Option<String>,
+    /// Names of all output devices
+    pub output_devices: Vec<String>,
+}
+
+/// Full diagnostics report: build info plus the detected environment
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    /// Static build information
+    pub build: BuildInfo,
+    /// Audio backends available on this machine
+    pub backends: Vec<AudioBackend>,
+    /// Flavor of the current tokio runtime, if called from within one
+    pub tokio_runtime: Option<String>,
+}
+
+/// Describe this build without touching the audio system
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        protocol_version: 1,
+        codecs: SUPPORTED_CODECS.to_vec(),
+        outputs: vec!["cpal"],
+        features: enabled_features(),
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        },
+        target_os: std::env::consts::OS,
+        target_arch: std::env::consts::ARCH,
+        target_family: std::env::consts::FAMILY,
+    }
+}
+
+/// Probe the audio hosts and devices cpal can see
+fn audio_backends() -> Vec<AudioBackend> {
+    let default_host = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .filter_map(|id| {
+            let host = cpal::host_from_id(id).ok()?;
+            let output_devices = host
+                .output_devices()
+                .map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+                .unwrap_or_default();
+            Some(AudioBackend {
+                name: id.name().to_string(),
+                default: id == default_host,
+                default_output: host.default_output_device().and_then(|d| d.name().ok()),
+                output_devices,
+            })
+        })
+        .collect()
+}
+
+/// Build the full diagnostics report
+///
+/// Probes audio devices, which can take a moment and may print backend warnings.
+pub fn report() -> DiagnosticsReport {
+    DiagnosticsReport {
+        build: build_info(),
+        backends: audio_backends(),
+        tokio_runtime: tokio::runtime::Handle::try_current()
+            .ok()
+            .map(|handle| format!("{:?}", handle.runtime_flavor())),
+    }
+}
+
+fn list(items: &[&str]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "sendspin: {}", self.crate_version)?;
+        writeln!(f, "protocol: v{}", self.protocol_version)?;
+        writeln!(f, "codecs: {}", list(&self.codecs))?;
+        writeln!(f, "outputs: {}", list(&self.outputs))?;
+        writeln!(f, "features: {}", list(&self.features))?;
+        writeln!(f, "profile: {}", self.profile)?;
+        write!(
+            f,
+            "platform: {} {} ({})",
+            self.target_os, self.target_arch, self.target_family
+        )
+    }
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.build)?;
+        writeln!(
+            f,
+            "tokio: {}",
+            self.tokio_runtime.as_deref().unwrap_or("not running")
+        )?;
+        if self.backends.is_empty() {
+            return write!(f, "audio backends: none detected");
+        }
+        write!(f, "audio backends:")?;
+        for backend in &self.backends {
+            write!(
+                f,
+                "\n  {}{}: default output {}, {} output device(s)",
+                backend.name,
+                if backend.default { " (default)" } else { "" },
+                backend.default_output.as_deref().unwrap_or("none"),
+                backend.output_devices.len()
+            )?;
+            for device in &backend.output_devices {
+                write!(f, "\n    - {}", device)?;
+            }
+        }
+        Ok(())
+    }
+}
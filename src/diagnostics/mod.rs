@@ -0,0 +1,7 @@
+// ABOUTME: Startup diagnostics for support and bug reports
+// ABOUTME: Reports compiled-in codecs, outputs, and features plus the runtime environment
+
+/// Build and environment inspection
+pub mod environment;
+
+pub use environment::{build_info, report, AudioBackend, BuildInfo, DiagnosticsReport};
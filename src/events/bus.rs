@@ -0,0 +1,52 @@
+// ABOUTME: Broadcast event bus of a client connection
+// ABOUTME: Lets any number of subscribers receive every client event independently
+
+use super::ClientEvent;
+use tokio::sync::broadcast;
+
+/// Events kept for slow subscribers before they lag
+pub const EVENT_BUS_CAPACITY: usize = 64;
+
+/// Broadcast channel of [`ClientEvent`]s
+///
+/// The protocol client publishes the events of its connection here, from
+/// [`ProtocolClient::event_bus`](crate::protocol::client::ProtocolClient::event_bus).
+/// Every subscriber gets every event published after it subscribed; one that
+/// falls more than [`EVENT_BUS_CAPACITY`] events behind gets
+/// [`RecvError::Lagged`](broadcast::error::RecvError::Lagged) and skips ahead.
+/// Cloning is cheap; all clones publish to the same subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<ClientEvent>,
+}
+
+impl EventBus {
+    /// Create a bus with no subscribers
+    pub fn new() -> Self {
+        Self {
+            tx: broadcast::channel(EVENT_BUS_CAPACITY).0,
+        }
+    }
+
+    /// Subscribe to events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ClientEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Publish an event to every subscriber
+    pub fn publish(&self, event: ClientEvent) {
+        // No subscribers is fine
+        let _ = self.tx.send(event);
+    }
+
+    /// Number of current subscribers
+    pub fn subscribers(&self) -> usize {
+        self.tx.receiver_count()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,96 @@
+// ABOUTME: Client state change events
+// ABOUTME: Typed events derived from protocol messages plus weak observer registration
+
+/// Broadcast event bus
+pub mod bus;
+/// Weak-reference observer registry
+pub mod observers;
+
+pub use bus::EventBus;
+pub use observers::{Observer, ObserverRegistry};
+
+use crate::metadata::NowPlaying;
+use crate::protocol::messages::{GroupUpdate, Message, StreamEnd, StreamStart};
+use crate::sync::SyncQuality;
+
+/// Whether the client is connected to a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Handshake completed
+    Connected,
+    /// Connection closed or the server said goodbye
+    Disconnected,
+    /// The server stopped responding and the connection was dropped
+    Lost,
+    /// The connection ended and a [`ServerPool`](crate::protocol::ServerPool) is
+    /// connecting to another server
+    Reconnecting,
+}
+
+/// A client-side state change that UIs typically display
+#[derive(Debug, Clone)]
+pub enum ClientEvent {
+    /// New now-playing metadata
+    MetadataChanged(Box<NowPlaying>),
+    /// Group volume or mute changed
+    VolumeChanged {
+        /// Volume level (0-100)
+        volume: u8,
+        /// Whether audio is muted
+        muted: bool,
+    },
+    /// Connection status changed
+    ConnectionChanged(ConnectionStatus),
+    /// The server started or reconfigured streams
+    StreamStarted(Box<StreamStart>),
+    /// The server ended streams
+    StreamEnded(StreamEnd),
+    /// Group membership or playback state changed
+    GroupChanged(GroupUpdate),
+    /// Clock synchronization quality changed
+    SyncQualityChanged(SyncQuality),
+}
+
+impl ClientEvent {
+    /// State changes carried by a protocol message
+    ///
+    /// Connection events other than `server/goodbye` and sync quality changes are
+    /// not visible in messages; the code owning the connection publishes those itself.
+    pub fn from_message(msg: &Message) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+        match msg {
+            Message::ServerState(state) => {
+                if let Some(ref metadata) = state.metadata {
+                    events.push(Self::MetadataChanged(Box::new(NowPlaying::from_state(
+                        metadata,
+                    ))));
+                }
+                if let Some(ref controller) = state.controller {
+                    events.push(Self::VolumeChanged {
+                        volume: controller.volume,
+                        muted: controller.muted,
+                    });
+                }
+            }
+            Message::StreamStart(start) => {
+                events.push(Self::StreamStarted(Box::new(start.clone())));
+            }
+            Message::StreamEnd(end) => events.push(Self::StreamEnded(end.clone())),
+            Message::GroupUpdate(update) => events.push(Self::GroupChanged(update.clone())),
+            Message::ServerGoodbye(_) => {
+                events.push(Self::ConnectionChanged(ConnectionStatus::Disconnected));
+            }
+            _ => {}
+        }
+        events
+    }
+}
+
+impl ObserverRegistry<ClientEvent> {
+    /// Notify observers of every state change carried by a protocol message
+    pub fn dispatch(&self, msg: &Message) {
+        for event in ClientEvent::from_message(msg) {
+            self.notify(&event);
+        }
+    }
+}
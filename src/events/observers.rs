@@ -0,0 +1,132 @@
+// ABOUTME: Observer registry holding only weak references to its observers
+// ABOUTME: Observers unregister automatically when their handle or target is dropped
+
+use parking_lot::Mutex;
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, Weak};
+
+/// A registered observer: calls through and reports whether it is still alive
+type Entry<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// Boxed observer callback, kept alive only by its `Observer` handle
+type Callback<E> = Box<dyn Fn(&E) + Send + Sync>;
+
+/// Registry of lightweight callbacks notified of events
+///
+/// The registry never keeps an observer alive. Observers registered with
+/// [`observe`](Self::observe) live as long as the returned [`Observer`] handle;
+/// observers registered with [`observe_target`](Self::observe_target) live as long
+/// as their target. Dead observers are pruned on the next notification, so GUI code
+/// can register from widgets without owning receivers or background tasks.
+///
+/// Callbacks run synchronously on the notifying thread and should return quickly
+/// (e.g., store the value and request a repaint). Cloning is cheap; all clones
+/// share the same observers.
+pub struct ObserverRegistry<E> {
+    entries: Arc<Mutex<Vec<Entry<E>>>>,
+}
+
+/// Handle that keeps an observer registered
+///
+/// Dropping the handle unregisters the observer.
+#[must_use = "the observer is unregistered when its handle is dropped"]
+pub struct Observer {
+    _callback: Arc<dyn Any + Send + Sync>,
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+impl<E: 'static> ObserverRegistry<E> {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Register a callback that stays registered while the returned handle lives
+    pub fn observe<F>(&self, callback: F) -> Observer
+    where
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        let callback: Arc<Callback<E>> = Arc::new(Box::new(callback));
+        let weak = Arc::downgrade(&callback);
+        self.entries.lock().push(Arc::new(move |event: &E| {
+            let Some(callback) = weak.upgrade() else {
+                return false;
+            };
+            callback(event);
+            true
+        }));
+        Observer {
+            _callback: callback,
+        }
+    }
+
+    /// Register a callback on a shared target that stays registered while the target lives
+    ///
+    /// Only a weak reference to `target` is held, so the registry never extends the
+    /// target's lifetime.
+    pub fn observe_target<T, F>(&self, target: &Arc<T>, callback: F)
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T, &E) + Send + Sync + 'static,
+    {
+        let target: Weak<T> = Arc::downgrade(target);
+        self.entries.lock().push(Arc::new(move |event: &E| {
+            let Some(target) = target.upgrade() else {
+                return false;
+            };
+            callback(&target, event);
+            true
+        }));
+    }
+
+    /// Call every live observer with an event and prune dead ones
+    ///
+    /// Observers may register or drop other observers from within a callback.
+    pub fn notify(&self, event: &E) {
+        let entries = self.entries.lock().clone();
+        let dead: Vec<Entry<E>> = entries.into_iter().filter(|entry| !entry(event)).collect();
+        if !dead.is_empty() {
+            self.entries
+                .lock()
+                .retain(|entry| !dead.iter().any(|d| Arc::ptr_eq(entry, d)));
+        }
+    }
+
+    /// Number of registered observers, including dead ones not yet pruned
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Whether no observers are registered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<E: 'static> Default for ObserverRegistry<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for ObserverRegistry<E> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+        }
+    }
+}
+
+impl<E> fmt::Debug for ObserverRegistry<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObserverRegistry({})", self.entries.lock().len())
+    }
+}
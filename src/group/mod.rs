@@ -0,0 +1,141 @@
+// ABOUTME: Group membership and playback state of this client
+// ABOUTME: Merges group/update messages and publishes the result on a watch channel
+
+use crate::events::ConnectionStatus;
+use crate::protocol::messages::{GroupMember, GroupUpdate, Message, PlaybackState};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// What this client knows about its group
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupState {
+    /// Group identifier; `None` until the server names the group
+    pub group_id: Option<String>,
+    /// Human-readable group name
+    pub group_name: Option<String>,
+    /// Playback state of the group
+    pub playback_state: Option<PlaybackState>,
+    /// Clients in the group, when the server lists them
+    pub members: Option<Vec<GroupMember>>,
+}
+
+impl GroupState {
+    /// Whether the group is playing
+    pub fn is_playing(&self) -> bool {
+        self.playback_state == Some(PlaybackState::Playing)
+    }
+
+    /// Merge a group/update into this state; returns whether anything changed
+    ///
+    /// Updates only carry the fields that changed. A different `group_id` means
+    /// the client moved to another group, so nothing of the old one is kept.
+    pub fn apply(&mut self, update: &GroupUpdate) -> bool {
+        let before = self.clone();
+        if update.group_id.is_some() && update.group_id != self.group_id {
+            *self = GroupState {
+                group_id: update.group_id.clone(),
+                ..GroupState::default()
+            };
+        }
+        if let Some(ref name) = update.group_name {
+            self.group_name = Some(name.clone());
+        }
+        if let Some(ref state) = update.playback_state {
+            self.playback_state = Some(state.clone());
+        }
+        if let Some(ref members) = update.members {
+            self.members = Some(members.clone());
+        }
+        *self != before
+    }
+}
+
+/// Tracks the group this client belongs to
+///
+/// Feed it every message from the server with [`apply`](Self::apply), and the
+/// connection status with [`connection_changed`](Self::connection_changed): a
+/// server reached after a reconnect sends its own group/update, so the old
+/// group is forgotten rather than shown as current. Cloning is cheap; all
+/// clones share the same state.
+///
+/// ```no_run
+/// # async fn example(client: sendspin::ProtocolClient) {
+/// use sendspin::group::GroupManager;
+///
+/// let groups = GroupManager::new();
+/// let mut changes = groups.watch();
+/// tokio::spawn(async move {
+///     while changes.changed().await.is_ok() {
+///         println!("Group: {:?}", changes.borrow().group_name);
+///     }
+/// });
+/// let mut messages = client.into_parts().messages;
+/// while let Some(msg) = messages.recv().await {
+///     groups.apply(&msg);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GroupManager {
+    state: Arc<watch::Sender<GroupState>>,
+}
+
+impl GroupManager {
+    /// Create a manager that knows of no group yet
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(GroupState::default())),
+        }
+    }
+
+    /// Take in a message from the server; only group/update changes anything
+    ///
+    /// Watchers are only woken when the state actually changed.
+    pub fn apply(&self, msg: &Message) {
+        if let Message::GroupUpdate(update) = msg {
+            self.state.send_if_modified(|state| state.apply(update));
+        }
+    }
+
+    /// Take in a connection status change; anything but connected forgets the group
+    pub fn connection_changed(&self, status: ConnectionStatus) {
+        if status != ConnectionStatus::Connected {
+            self.reset();
+        }
+    }
+
+    /// Forget the current group
+    pub fn reset(&self) {
+        self.state.send_if_modified(|state| {
+            let changed = *state != GroupState::default();
+            *state = GroupState::default();
+            changed
+        });
+    }
+
+    /// The current group state
+    pub fn state(&self) -> GroupState {
+        self.state.borrow().clone()
+    }
+
+    /// Watch the group state, e.g. to redraw a UI when it changes
+    pub fn watch(&self) -> watch::Receiver<GroupState> {
+        self.state.subscribe()
+    }
+
+    /// Identifier of the current group
+    pub fn group_id(&self) -> Option<String> {
+        self.state.borrow().group_id.clone()
+    }
+
+    /// Playback state of the current group
+    pub fn playback_state(&self) -> Option<PlaybackState> {
+        self.state.borrow().playback_state.clone()
+    }
+}
+
+impl Default for GroupManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,21 +7,88 @@
 //!
 //! This library provides zero-copy audio pipelines, lock-free concurrency, and async I/O
 //! for building high-performance audio streaming clients and servers.
+//!
+//! ## API stability
+//!
+//! The crate has two layers:
+//!
+//! - **High-level API** - everything re-exported from [`prelude`] and the crate root.
+//!   These types follow semver: breaking changes only happen in a new minor version
+//!   while the crate is 0.x, and are called out in the release notes.
+//! - **Wire layer** - the contents of [`protocol::messages`], binary frame parsing in
+//!   [`protocol::client`], and the other submodules. These track the Sendspin spec and
+//!   may change whenever the spec does, including in patch releases.
+//!
+//! Most applications only need `use sendspin::prelude::*;`.
 
 #![warn(missing_docs)]
 
 /// Audio types and processing
 pub mod audio;
+/// Opt-in audit logging for bug reports
+pub mod audit;
+/// Settings files with environment overrides
+pub mod config;
+/// High-level controller for group playback
+pub mod controller;
+/// Build and runtime environment diagnostics
+pub mod diagnostics;
+/// Client state change events and observers
+pub mod events;
+/// Group membership and playback state
+pub mod group;
+/// Now-playing metadata tracking and export
+pub mod metadata;
+/// High-level player: decode, schedule, and output
+pub mod player;
 /// Protocol implementation for WebSocket communication
 pub mod protocol;
 /// Audio scheduler for timed playback
 pub mod scheduler;
+/// Server-side streaming to connected players
+pub mod server;
+/// Pluggable persistence for caches and client state
+pub mod storage;
 /// Clock synchronization utilities
 pub mod sync;
 
+pub use audio::{AudioBuffer, AudioFormat, Codec, Sample};
+pub use config::{ClientSettings, Config, PlayerSettings};
+pub use controller::Controller;
+pub use error::Error;
+pub use events::{ClientEvent, EventBus};
+pub use metadata::{MetadataTracker, NowPlaying};
+pub use player::{Player, PlayerConfig, PlayerEvent};
 pub use protocol::client::ProtocolClient;
 pub use protocol::messages::{ClientHello, ServerHello};
+pub use protocol::{Message, StreamTracker, WsSender};
 pub use scheduler::AudioScheduler;
+pub use sync::ClockSync;
+
+/// Stable high-level API for glob import
+///
+/// `use sendspin::prelude::*;` brings in the types needed to connect, receive, decode
+/// and schedule audio. Everything here is covered by the stability policy in the
+/// crate documentation.
+pub mod prelude {
+    pub use crate::audio::decode::{Decoder, PcmDecoder};
+    pub use crate::audio::{AudioBuffer, AudioFormat, AudioOutput, Codec, CpalOutput, Sample};
+    pub use crate::config::{ClientSettings, Config, PlayerSettings};
+    pub use crate::controller::Controller;
+    pub use crate::error::Error;
+    pub use crate::events::{ClientEvent, EventBus};
+    pub use crate::metadata::{MetadataTracker, NowPlaying, TrackChange};
+    pub use crate::player::{Player, PlayerConfig, PlayerEvent};
+    pub use crate::protocol::client::{AudioChunk, ProtocolClient};
+    pub use crate::protocol::messages::{ClientHello, ServerHello};
+    pub use crate::protocol::{
+        ClientContext, ClientHandler, CurrentStream, Message, Role, RoleList, StreamTracker,
+        WsSender,
+    };
+    pub use crate::scheduler::{AudioScheduler, Scheduler};
+    pub use crate::sync::{ClockSync, SyncQuality};
+    pub use crate::Result;
+}
 
 /// Result type for sendspin operations
 pub type Result<T> = std::result::Result<T, error::Error>;
@@ -52,5 +119,37 @@ pub mod error {
         /// Audio output error
         #[error("Audio output error: {0}")]
         Output(String),
+
+        /// File or other I/O error
+        #[error("I/O error: {0}")]
+        Io(String),
+
+        /// Persistent storage error
+        #[error("Storage error: {0}")]
+        Storage(String),
+
+        /// Invalid configuration file or setting
+        #[error("Configuration error: {0}")]
+        Config(String),
+
+        /// A message had fields this crate does not know (strict parsing only)
+        #[error("Spec drift in {message_type}: unknown fields {}", fields.join(", "))]
+        SpecDrift {
+            /// Wire type of the message (e.g., "stream/start")
+            message_type: String,
+            /// Dotted paths of the unknown fields
+            fields: Vec<String>,
+        },
+
+        /// The peer speaks a protocol version this crate does not support
+        #[error("Unsupported protocol version {version} (supported: {min}-{max})")]
+        UnsupportedVersion {
+            /// Version the two ends would have to use
+            version: u32,
+            /// Lowest supported version
+            min: u32,
+            /// Highest supported version
+            max: u32,
+        },
     }
 }
@@ -0,0 +1,119 @@
+// ABOUTME: Spoken track change announcements
+// ABOUTME: Builds announcement text and speaks it through an external TTS command
+
+use crate::error::Error;
+use crate::metadata::tracker::{MetadataTracker, NowPlaying, TrackChange};
+use crate::player::VolumeControl;
+
+/// Build the spoken announcement for a track (e.g., "Now playing Song by Artist")
+///
+/// Returns None if the track has neither a title nor an artist.
+pub fn announcement_text(track: &NowPlaying) -> Option<String> {
+    match (&track.title, &track.artist) {
+        (Some(title), Some(artist)) => Some(format!("Now playing {} by {}", title, artist)),
+        (Some(title), None) => Some(format!("Now playing {}", title)),
+        (None, Some(artist)) => Some(format!("Now playing {}", artist)),
+        (None, None) => None,
+    }
+}
+
+/// Speaks track changes through an external text-to-speech command
+///
+/// The announcement text is passed as the final argument, so any command that speaks
+/// its argument works (`say` on macOS, `espeak-ng` or `spd-say` on Linux). Speech is
+/// played by the system audio stack alongside the stream; with
+/// [`with_ducking`](Self::with_ducking) the player's output is lowered while it speaks.
+#[derive(Debug, Clone)]
+pub struct SpeechAnnouncer {
+    command: String,
+    args: Vec<String>,
+    preamble: Option<String>,
+    duck: Option<(VolumeControl, f32)>,
+}
+
+impl SpeechAnnouncer {
+    /// Create an announcer using the given TTS command
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            args: Vec::new(),
+            preamble: None,
+            duck: None,
+        }
+    }
+
+    /// Create an announcer using the platform's usual TTS command
+    pub fn system_default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::new("say")
+        } else {
+            Self::new("espeak-ng")
+        }
+    }
+
+    /// Add an argument passed before the announcement text
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Set a short preamble spoken before the track details
+    pub fn with_preamble(mut self, preamble: impl Into<String>) -> Self {
+        self.preamble = Some(preamble.into());
+        self
+    }
+
+    /// Lower the player's output by `db` while speaking, restoring it afterwards
+    pub fn with_ducking(mut self, volume: VolumeControl, db: f32) -> Self {
+        self.duck = Some((volume, db));
+        self
+    }
+
+    /// Build the full text spoken for a track change
+    pub fn text_for(&self, change: &TrackChange) -> Option<String> {
+        let text = announcement_text(&change.current)?;
+        Some(match self.preamble {
+            Some(ref preamble) => format!("{}. {}", preamble, text),
+            None => text,
+        })
+    }
+
+    /// Speak the announcement for a track change and wait for the command to finish
+    pub async fn announce(&self, change: &TrackChange) -> Result<(), Error> {
+        let Some(text) = self.text_for(change) else {
+            return Ok(());
+        };
+        if let Some((ref volume, db)) = self.duck {
+            volume.set_duck_db(db);
+        }
+        let status = tokio::process::Command::new(&self.command)
+            .args(&self.args)
+            .arg(&text)
+            .status()
+            .await;
+        if let Some((ref volume, _)) = self.duck {
+            volume.set_duck_db(0.0);
+        }
+        let status = status.map_err(|e| Error::Io(format!("{}: {}", self.command, e)))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(Error::Io(format!(
+                "{} exited with {}",
+                self.command, status
+            )))
+        }
+    }
+
+    /// Register this announcer as a track change hook on a tracker
+    pub fn install(self, tracker: &MetadataTracker) {
+        tracker.on_track_change(move |change| {
+            let announcer = self.clone();
+            async move {
+                if let Err(e) = announcer.announce(&change).await {
+                    log::warn!("Track announcement failed: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,101 @@
+// ABOUTME: Per-channel artwork store
+// ABOUTME: Keeps the latest image of each artwork channel and announces changes
+
+use crate::protocol::client::ArtworkChunk;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Number of artwork channels in the protocol (0-3)
+pub const ARTWORK_CHANNELS: usize = 4;
+
+/// Change notifications kept for slow subscribers before they lag
+const CHANGE_CAPACITY: usize = 16;
+
+/// An artwork channel changed, from [`ArtworkManager::subscribe`]
+#[derive(Debug, Clone)]
+pub struct ArtworkChange {
+    /// Artwork channel (0-3)
+    pub channel: u8,
+    /// The new image; `None` when the server cleared the channel
+    pub image: Option<ArtworkChunk>,
+}
+
+/// Shared store of the latest artwork per channel
+///
+/// Feed it every [`ArtworkChunk`] with [`handle`](Self::handle). A chunk with an
+/// empty payload clears its channel. Cloning is cheap; all clones share state.
+#[derive(Debug, Clone)]
+pub struct ArtworkManager {
+    images: Arc<Mutex<[Option<ArtworkChunk>; ARTWORK_CHANNELS]>>,
+    changes: broadcast::Sender<ArtworkChange>,
+}
+
+impl Default for ArtworkManager {
+    fn default() -> Self {
+        Self {
+            images: Arc::default(),
+            changes: broadcast::channel(CHANGE_CAPACITY).0,
+        }
+    }
+}
+
+impl ArtworkManager {
+    /// Create a manager with every channel empty
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take in an artwork chunk; returns the change it made
+    ///
+    /// Chunks for channels outside 0-3 and clears of empty channels change nothing.
+    pub fn handle(&self, chunk: ArtworkChunk) -> Option<ArtworkChange> {
+        let change = {
+            let mut images = self.images.lock();
+            let slot = images.get_mut(chunk.channel as usize)?;
+            let channel = chunk.channel;
+            if chunk.data.is_empty() {
+                slot.take()?;
+                ArtworkChange {
+                    channel,
+                    image: None,
+                }
+            } else {
+                *slot = Some(chunk.clone());
+                ArtworkChange {
+                    channel,
+                    image: Some(chunk),
+                }
+            }
+        };
+        // No subscribers is fine
+        let _ = self.changes.send(change.clone());
+        Some(change)
+    }
+
+    /// The latest image on `channel`, if any
+    pub fn current(&self, channel: u8) -> Option<ArtworkChunk> {
+        self.images.lock().get(channel as usize)?.clone()
+    }
+
+    /// Subscribe to channel changes made after this call
+    pub fn subscribe(&self) -> broadcast::Receiver<ArtworkChange> {
+        self.changes.subscribe()
+    }
+
+    /// Clear every channel, e.g. after disconnecting
+    pub fn clear(&self) {
+        let cleared: Vec<u8> = {
+            let mut images = self.images.lock();
+            (0..ARTWORK_CHANNELS as u8)
+                .filter(|&channel| images[channel as usize].take().is_some())
+                .collect()
+        };
+        for channel in cleared {
+            let _ = self.changes.send(ArtworkChange {
+                channel,
+                image: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// ABOUTME: Template-based now-playing exporter
+// ABOUTME: Renders metadata into text files or FIFOs and writes artwork to a fixed path
+
+use crate::error::Error;
+use crate::metadata::format::format_duration;
+use crate::metadata::{MetadataTracker, NowPlaying};
+use crate::protocol::client::ArtworkChunk;
+use crate::protocol::messages::{RepeatMode, TrackProgress};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default template used when none is configured
+pub const DEFAULT_TEMPLATE: &str = "{artist} - {title}";
+
+/// Placeholders understood by [`MetadataExporter`]
+const PLACEHOLDERS: &[&str] = &[
+    "title",
+    "artist",
+    "album",
+    "year",
+    "track",
+    "disc",
+    "genre",
+    "composer",
+    "source",
+    "artwork_url",
+    "position",
+    "duration",
+    "progress",
+    "repeat",
+    "shuffle",
+];
+
+/// Renders now-playing metadata through a user template into a file
+///
+/// Supported placeholders: `{title}`, `{artist}`, `{album}`, `{year}`, `{track}`,
+/// `{disc}`, `{genre}`, `{composer}`, `{source}`, `{position}`, `{duration}`,
+/// `{progress}`, `{repeat}`, `{shuffle}` and `{artwork_url}`.
+/// Missing values render as empty strings.
+#[derive(Debug, Clone)]
+pub struct MetadataExporter {
+    template: String,
+    path: PathBuf,
+    artwork_path: Option<PathBuf>,
+    interval: Duration,
+}
+
+impl MetadataExporter {
+    /// Create an exporter writing `template` to `path` once per second
+    pub fn new(template: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            template: template.into(),
+            path: path.into(),
+            artwork_path: None,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the rate at which the output file is refreshed
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Also export artwork images to a fixed path
+    pub fn with_artwork_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.artwork_path = Some(path.into());
+        self
+    }
+
+    /// Render the template for a snapshot (None renders all fields empty)
+    pub fn render(&self, now_playing: Option<&NowPlaying>) -> String {
+        let field = |name: &str| -> String {
+            let Some(np) = now_playing else {
+                return if PLACEHOLDERS.contains(&name) {
+                    String::new()
+                } else {
+                    format!("{{{}}}", name)
+                };
+            };
+            match name {
+                "title" => np.title.clone().unwrap_or_default(),
+                "artist" => np.artist.clone().unwrap_or_default(),
+                "album" => np.album.clone().unwrap_or_default(),
+                "year" => np.year.map(|y| y.to_string()).unwrap_or_default(),
+                "track" => np.track.clone().unwrap_or_default(),
+                "disc" => np.disc_number.map(|d| d.to_string()).unwrap_or_default(),
+                "genre" => np.genre.clone().unwrap_or_default(),
+                "composer" => np.composer.clone().unwrap_or_default(),
+                "source" => np.source.clone().unwrap_or_default(),
+                "artwork_url" => np.artwork_url.clone().unwrap_or_default(),
+                "position" => np
+                    .progress
+                    .as_ref()
+                    .map(|p| format_duration(p.position))
+                    .unwrap_or_default(),
+                "duration" => np
+                    .progress
+                    .as_ref()
+                    .map(|p| format_duration(p.duration))
+                    .unwrap_or_default(),
+                "progress" => np
+                    .progress
+                    .as_ref()
+                    .map(TrackProgress::display)
+                    .unwrap_or_default(),
+                "repeat" => match np.repeat {
+                    Some(RepeatMode::Off) => "off".to_string(),
+                    Some(RepeatMode::One) => "one".to_string(),
+                    Some(RepeatMode::All) => "all".to_string(),
+                    None => String::new(),
+                },
+                "shuffle" => np.shuffle.map(|s| s.to_string()).unwrap_or_default(),
+                _ => format!("{{{}}}", name),
+            }
+        };
+
+        let mut out = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            match rest[start..].find('}') {
+                Some(end) => {
+                    out.push_str(&field(&rest[start + 1..start + end]));
+                    rest = &rest[start + end + 1..];
+                }
+                None => {
+                    rest = &rest[start..];
+                    break;
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Render and write a snapshot to the output path
+    pub fn write(&self, now_playing: Option<&NowPlaying>) -> Result<(), Error> {
+        write_output(&self.path, self.render(now_playing).as_bytes())
+    }
+
+    /// Write an artwork image to the artwork path (an empty chunk removes the file)
+    ///
+    /// Does nothing if no artwork path is configured.
+    pub fn export_artwork(&self, chunk: &ArtworkChunk) -> Result<(), Error> {
+        let Some(ref path) = self.artwork_path else {
+            return Ok(());
+        };
+        if chunk.is_clear() {
+            return match std::fs::remove_file(path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(Error::Io(e.to_string())),
+            };
+        }
+        write_output(path, &chunk.data)
+    }
+
+    /// Periodically export the tracker's current snapshot
+    ///
+    /// The file is only rewritten when the rendered text changes. Writes run on the
+    /// blocking pool so a FIFO without a reader does not stall the runtime.
+    pub fn spawn(self, tracker: MetadataTracker) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            let mut last: Option<String> = None;
+            loop {
+                interval.tick().await;
+                let rendered = self.render(tracker.current().as_ref());
+                if last.as_ref() == Some(&rendered) {
+                    continue;
+                }
+                let path = self.path.clone();
+                let bytes = rendered.clone().into_bytes();
+                match tokio::task::spawn_blocking(move || write_output(&path, &bytes)).await {
+                    Ok(Ok(())) => last = Some(rendered),
+                    Ok(Err(e)) => log::warn!("Metadata export failed: {}", e),
+                    Err(e) => log::warn!("Metadata export task failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Write a file atomically (temp + rename), or directly for FIFOs and other special files
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    let is_special = std::fs::metadata(path)
+        .map(|m| !m.file_type().is_file())
+        .unwrap_or(false);
+    if is_special {
+        return std::fs::write(path, bytes).map_err(|e| Error::Io(e.to_string()));
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, bytes).map_err(|e| Error::Io(e.to_string()))?;
+    std::fs::rename(&tmp, path).map_err(|e| Error::Io(e.to_string()))
+}
@@ -0,0 +1,158 @@
+// ABOUTME: Progress and duration formatting helpers
+// ABOUTME: Consistent "3:45 / 7:12", percent, remaining, and relative-time rendering
+
+use crate::metadata::NowPlaying;
+use crate::protocol::messages::TrackProgress;
+
+/// Number formatting conventions for a locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Decimal separator (e.g., '.' for en, ',' for de)
+    pub decimal_separator: char,
+    /// Whether a space goes between the number and the percent sign (e.g., "42 %" in fr/de)
+    pub space_before_percent: bool,
+}
+
+impl Locale {
+    /// English conventions ("42.5%")
+    pub const EN: Self = Self {
+        decimal_separator: '.',
+        space_before_percent: false,
+    };
+
+    /// Resolve conventions from a BCP 47 tag (e.g., "de-DE"); unknown languages use English
+    pub fn from_tag(tag: &str) -> Self {
+        let language = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "de" | "fr" | "sv" | "fi" | "nb" | "no" | "da" | "cs" | "pl" | "ru" => Self {
+                decimal_separator: ',',
+                space_before_percent: true,
+            },
+            "es" | "it" | "nl" | "pt" | "tr" | "id" => Self {
+                decimal_separator: ',',
+                space_before_percent: false,
+            },
+            _ => Self::EN,
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::EN
+    }
+}
+
+/// Format microseconds as "m:ss", or "h:mm:ss" for an hour or more
+///
+/// Negative values are rendered with a leading '-'.
+pub fn format_duration(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let total = micros.unsigned_abs() / 1_000_000;
+    let (h, m, s) = (total / 3600, (total / 60) % 60, total % 60);
+    if h > 0 {
+        format!("{}{}:{:02}:{:02}", sign, h, m, s)
+    } else {
+        format!("{}{}:{:02}", sign, m, s)
+    }
+}
+
+/// Format a percentage with one decimal place using locale conventions
+pub fn format_percent(percent: f64, locale: Locale) -> String {
+    let number = format!("{:.1}", percent).replace('.', &locale.decimal_separator.to_string());
+    if locale.space_before_percent {
+        format!("{}\u{a0}%", number)
+    } else {
+        format!("{}%", number)
+    }
+}
+
+/// Format an offset from now as relative time ("just now", "in 2 min", "3 h ago")
+pub fn format_relative(delta_micros: i64) -> String {
+    let secs = delta_micros.unsigned_abs() / 1_000_000;
+    let amount = if secs < 5 {
+        return "just now".to_string();
+    } else if secs < 60 {
+        format!("{} s", secs)
+    } else if secs < 3600 {
+        format!("{} min", secs / 60)
+    } else if secs < 86_400 {
+        format!("{} h", secs / 3600)
+    } else {
+        format!("{} d", secs / 86_400)
+    };
+    if delta_micros < 0 {
+        format!("{} ago", amount)
+    } else {
+        format!("in {}", amount)
+    }
+}
+
+/// Format a Unix time as a wall-clock "HH:MM" in a time zone given by its UTC offset
+pub fn format_clock_time(unix_micros: i64, utc_offset_secs: i32) -> String {
+    let local_secs = unix_micros.div_euclid(1_000_000) + utc_offset_secs as i64;
+    let secs_of_day = local_secs.rem_euclid(86_400);
+    format!("{:02}:{:02}", secs_of_day / 3600, (secs_of_day / 60) % 60)
+}
+
+impl TrackProgress {
+    /// Fraction of the track played, as a percentage (None for unknown duration)
+    pub fn percent_complete(&self) -> Option<f64> {
+        if self.duration <= 0 {
+            return None;
+        }
+        Some((self.position as f64 / self.duration as f64 * 100.0).clamp(0.0, 100.0))
+    }
+
+    /// Time left in the track in microseconds (never negative)
+    pub fn remaining_micros(&self) -> i64 {
+        (self.duration - self.position).max(0)
+    }
+
+    /// Render as "position / duration" (e.g., "3:45 / 7:12")
+    pub fn display(&self) -> String {
+        format!(
+            "{} / {}",
+            format_duration(self.position),
+            format_duration(self.duration)
+        )
+    }
+
+    /// Render the remaining time (e.g., "-3:27")
+    pub fn display_remaining(&self) -> String {
+        format_duration(-self.remaining_micros())
+    }
+
+    /// Playback speed multiplier, 1.0 when the server leaves it out
+    pub fn speed(&self) -> f64 {
+        self.playback_speed.unwrap_or(1.0).max(0.0)
+    }
+
+    /// Whether the server reports the track as paused (speed 0.0)
+    pub fn is_paused(&self) -> bool {
+        self.speed() == 0.0
+    }
+}
+
+impl NowPlaying {
+    /// Render the reported progress as "position / duration"
+    pub fn progress_display(&self) -> Option<String> {
+        self.progress.as_ref().map(TrackProgress::display)
+    }
+
+    /// Percentage of the track played (None if progress or duration is unknown)
+    pub fn percent_complete(&self) -> Option<f64> {
+        self.progress
+            .as_ref()
+            .and_then(TrackProgress::percent_complete)
+    }
+
+    /// Whether the reported progress is paused (false without progress)
+    pub fn is_paused(&self) -> bool {
+        self.progress.as_ref().is_some_and(TrackProgress::is_paused)
+    }
+}
@@ -0,0 +1,19 @@
+// ABOUTME: Now-playing metadata handling
+// ABOUTME: Tracks server metadata state and exports it for external consumers
+
+/// Spoken track change announcements
+pub mod announce;
+/// Latest artwork per channel
+pub mod artwork;
+/// Template-based now-playing exporter (files, FIFOs, artwork)
+pub mod export;
+/// Progress, duration, and relative-time formatting helpers
+pub mod format;
+/// Now-playing metadata store
+pub mod tracker;
+
+pub use announce::SpeechAnnouncer;
+pub use artwork::{ArtworkChange, ArtworkManager};
+pub use export::MetadataExporter;
+pub use format::{format_duration, Locale};
+pub use tracker::{MetadataTracker, NowPlaying, TrackChange};
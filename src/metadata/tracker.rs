@@ -0,0 +1,257 @@
+// ABOUTME: Now-playing metadata store
+// ABOUTME: Ingests server/state metadata and exposes the current track snapshot
+
+use crate::protocol::messages::{
+    ContentType, Lyrics, Message, MetadataState, RepeatMode, TrackProgress,
+};
+use crate::sync::ClockSync;
+use parking_lot::Mutex;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Snapshot of the currently playing track
+#[derive(Debug, Clone)]
+pub struct NowPlaying {
+    /// Server timestamp the metadata refers to (microseconds)
+    pub timestamp: i64,
+    /// Track title
+    pub title: Option<String>,
+    /// Artist name
+    pub artist: Option<String>,
+    /// Album name
+    pub album: Option<String>,
+    /// Artwork URL
+    pub artwork_url: Option<String>,
+    /// Release year
+    pub year: Option<u32>,
+    /// Track number info (e.g., "3/12")
+    pub track: Option<String>,
+    /// Track progress as reported by the server
+    pub progress: Option<TrackProgress>,
+    /// Repeat mode
+    pub repeat: Option<RepeatMode>,
+    /// Shuffle state
+    pub shuffle: Option<bool>,
+    /// Genre
+    pub genre: Option<String>,
+    /// Composer
+    pub composer: Option<String>,
+    /// Disc number within a multi-disc release
+    pub disc_number: Option<u32>,
+    /// Kind of content playing
+    pub content_type: Option<ContentType>,
+    /// Where the content comes from
+    pub source: Option<String>,
+    /// Lyrics for the track
+    pub lyrics: Option<Lyrics>,
+    /// Local time the metadata was received
+    pub received_at: Instant,
+}
+
+impl NowPlaying {
+    /// Build a snapshot from a server metadata update
+    pub fn from_state(state: &MetadataState) -> Self {
+        Self {
+            timestamp: state.timestamp,
+            title: state.title.clone(),
+            artist: state.artist.clone(),
+            album: state.album.clone(),
+            artwork_url: state.artwork_url.clone(),
+            year: state.year,
+            track: state.track.clone(),
+            progress: state.progress.clone(),
+            repeat: state.repeat.clone(),
+            shuffle: state.shuffle,
+            genre: state.genre.clone(),
+            composer: state.composer.clone(),
+            disc_number: state.disc_number,
+            content_type: state.content_type,
+            source: state.source.clone(),
+            lyrics: state.lyrics.clone(),
+            received_at: Instant::now(),
+        }
+    }
+}
+
+impl NowPlaying {
+    /// Check if another snapshot describes the same track (title, artist and album)
+    pub fn is_same_track(&self, other: &NowPlaying) -> bool {
+        self.title == other.title && self.artist == other.artist && self.album == other.album
+    }
+
+    /// Track position at `now` in microseconds, extrapolated from the reported progress
+    ///
+    /// The report is valid at the server `timestamp`, which `sync` maps to local
+    /// time; without a synchronized clock the time of arrival stands in for it.
+    /// The position advances at `playback_speed` (0.0 holds it still) and stays
+    /// within the track duration when that is known.
+    pub fn position_at(&self, now: Instant, sync: Option<&ClockSync>) -> Option<i64> {
+        let progress = self.progress.as_ref()?;
+        let anchor = sync
+            .and_then(|sync| sync.server_to_local_instant(self.timestamp))
+            .unwrap_or(self.received_at);
+        let elapsed = if now >= anchor {
+            now.duration_since(anchor).as_micros() as f64
+        } else {
+            -(anchor.duration_since(now).as_micros() as f64)
+        };
+        let position = (progress.position + (elapsed * progress.speed()) as i64).max(0);
+        Some(if progress.duration > 0 {
+            position.min(progress.duration)
+        } else {
+            position
+        })
+    }
+
+    /// The reported progress with the position moved on to `now`
+    pub fn progress_at(&self, now: Instant, sync: Option<&ClockSync>) -> Option<TrackProgress> {
+        let position = self.position_at(now, sync)?;
+        self.progress.as_ref().map(|progress| TrackProgress {
+            position,
+            ..progress.clone()
+        })
+    }
+}
+
+/// Track change notification passed to hooks
+#[derive(Debug, Clone)]
+pub struct TrackChange {
+    /// Previously playing track (None for the first track)
+    pub previous: Option<NowPlaying>,
+    /// Newly playing track
+    pub current: NowPlaying,
+}
+
+type TrackChangeFn = dyn Fn(TrackChange) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+#[derive(Clone, Default)]
+struct TrackChangeHooks(Arc<Mutex<Vec<Arc<TrackChangeFn>>>>);
+
+impl fmt::Debug for TrackChangeHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TrackChangeHooks({})", self.0.lock().len())
+    }
+}
+
+/// Shared store of the latest now-playing metadata
+///
+/// Each `server/state` metadata object describes the complete current state, so an
+/// update replaces the previous snapshot. Cloning is cheap; all clones share state.
+///
+/// [`position`](Self::position) extrapolates the reported progress to the present,
+/// for progress bars that move between updates. Give the tracker the client's
+/// clock with [`with_clock`](Self::with_clock) so the server timestamps of the
+/// reports are honored.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataTracker {
+    current: Arc<Mutex<Option<NowPlaying>>>,
+    hooks: TrackChangeHooks,
+    clock: Option<Arc<tokio::sync::Mutex<ClockSync>>>,
+}
+
+impl MetadataTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the server timestamps of progress reports with `clock`
+    ///
+    /// Usually [`ProtocolClient::clock_sync`](crate::protocol::client::ProtocolClient::clock_sync).
+    pub fn with_clock(mut self, clock: Arc<tokio::sync::Mutex<ClockSync>>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Track position right now in microseconds (None without progress)
+    ///
+    /// See [`NowPlaying::position_at`].
+    pub fn position(&self) -> Option<i64> {
+        self.progress().map(|progress| progress.position)
+    }
+
+    /// Track progress with the position moved on to right now
+    pub fn progress(&self) -> Option<TrackProgress> {
+        let current = self.current.lock();
+        let now_playing = current.as_ref()?;
+        // A clock busy with a server/time reply is skipped rather than waited for
+        let sync = self.clock.as_ref().and_then(|clock| clock.try_lock().ok());
+        now_playing.progress_at(Instant::now(), sync.as_deref())
+    }
+
+    /// Whether the current track is paused, per its reported playback speed
+    pub fn is_paused(&self) -> bool {
+        self.current
+            .lock()
+            .as_ref()
+            .is_some_and(NowPlaying::is_paused)
+    }
+
+    /// Update from a protocol message (only `server/state` with metadata is used)
+    pub fn apply(&self, msg: &Message) {
+        if let Message::ServerState(state) = msg {
+            if let Some(ref metadata) = state.metadata {
+                self.update(metadata);
+            }
+        }
+    }
+
+    /// Replace the current snapshot with a server metadata update
+    ///
+    /// Returns the track change if the update switched to a different track. Registered
+    /// track change hooks are spawned on the current tokio runtime.
+    pub fn update(&self, state: &MetadataState) -> Option<TrackChange> {
+        let next = NowPlaying::from_state(state);
+        let previous = self.current.lock().replace(next.clone());
+
+        if previous.as_ref().is_some_and(|p| p.is_same_track(&next)) {
+            return None;
+        }
+
+        let change = TrackChange {
+            previous,
+            current: next,
+        };
+        self.notify(&change);
+        Some(change)
+    }
+
+    /// Register an async callback invoked whenever the track changes
+    pub fn on_track_change<F, Fut>(&self, hook: F)
+    where
+        F: Fn(TrackChange) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.hooks
+            .0
+            .lock()
+            .push(Arc::new(move |change| Box::pin(hook(change))));
+    }
+
+    fn notify(&self, change: &TrackChange) {
+        let hooks = self.hooks.0.lock().clone();
+        if hooks.is_empty() {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            log::warn!("Track change hooks skipped: no tokio runtime");
+            return;
+        };
+        for hook in hooks {
+            runtime.spawn(hook(change.clone()));
+        }
+    }
+
+    /// Get the current snapshot (None until metadata has been received)
+    pub fn current(&self) -> Option<NowPlaying> {
+        self.current.lock().clone()
+    }
+
+    /// Forget the current snapshot
+    pub fn clear(&self) {
+        *self.current.lock() = None;
+    }
+}
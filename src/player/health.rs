@@ -0,0 +1,90 @@
+// ABOUTME: Sync health policy of the player
+// ABOUTME: Decides when to report the error state on underruns or lost clock sync, and when to recover
+
+use crate::protocol::messages::PlayerSyncState;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// When the player tells the server it cannot stay in sync
+///
+/// The player reports [`PlayerSyncState::Error`] in client/state while the
+/// policy is violated, and [`PlayerSyncState::Synchronized`] again once it has
+/// been healthy for [`recovery`](Self::recovery).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// Underruns within [`underrun_window`](Self::underrun_window) that count as
+    /// out of sync; 0 ignores underruns
+    pub max_underruns: u32,
+    /// How far back underruns are counted
+    pub underrun_window: Duration,
+    /// Whether clock sync quality dropping to lost counts as out of sync
+    pub sync_lost: bool,
+    /// How long the player must stay healthy before reporting synchronized again
+    pub recovery: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            max_underruns: 3,
+            underrun_window: Duration::from_secs(10),
+            sync_lost: true,
+            recovery: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Applies a [`HealthPolicy`] to what the output thread observes
+pub(super) struct Health {
+    policy: HealthPolicy,
+    underruns: VecDeque<Instant>,
+    state: PlayerSyncState,
+    healthy_since: Option<Instant>,
+}
+
+impl Health {
+    pub(super) fn new(policy: HealthPolicy) -> Self {
+        Self {
+            policy,
+            underruns: VecDeque::new(),
+            state: PlayerSyncState::Synchronized,
+            healthy_since: None,
+        }
+    }
+
+    /// The output ran dry in the middle of a stream
+    pub(super) fn record_underrun(&mut self, now: Instant) {
+        self.underruns.push_back(now);
+    }
+
+    /// Re-evaluate the policy; returns the new state when it changes
+    pub(super) fn update(&mut self, now: Instant, sync_lost: bool) -> Option<PlayerSyncState> {
+        while let Some(&first) = self.underruns.front() {
+            if now.saturating_duration_since(first) <= self.policy.underrun_window {
+                break;
+            }
+            self.underruns.pop_front();
+        }
+        let failing = (self.policy.max_underruns > 0
+            && self.underruns.len() >= self.policy.max_underruns as usize)
+            || (self.policy.sync_lost && sync_lost);
+
+        if failing {
+            self.healthy_since = None;
+            if self.state == PlayerSyncState::Synchronized {
+                self.state = PlayerSyncState::Error;
+                return Some(PlayerSyncState::Error);
+            }
+            return None;
+        }
+        if self.state == PlayerSyncState::Error {
+            let since = *self.healthy_since.get_or_insert(now);
+            if now.saturating_duration_since(since) >= self.policy.recovery {
+                self.state = PlayerSyncState::Synchronized;
+                self.healthy_since = None;
+                return Some(PlayerSyncState::Synchronized);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,621 @@
+// ABOUTME: High-level player that turns a client connection into sound
+// ABOUTME: Owns decoding, prebuffering, scheduling, and the audio output thread
+
+/// Sync health policy
+mod health;
+/// Decoding and timing of received chunks
+mod pipeline;
+/// Output thread
+mod playback;
+/// Output gain handle
+mod volume;
+
+use crate::audio::decode::{DecodeErrorEvent, DecodeErrorPolicy, PcmEndian, SUPPORTED_CODECS};
+use crate::audio::{
+    AudioFormat, AudioOutput, Balance, CpalOutput, Fade, FadeCurve, Fader, Gain, MultiOutput,
+    ResampleQuality, VolumeTaper,
+};
+use crate::audit::replay::MAX_LATE_HEADER;
+use crate::audit::{AuditEvent, AuditLog, Direction};
+use crate::error::Error;
+use crate::protocol::client::{AudioChunk, ClientParts, ProtocolClient, WsSender};
+use crate::protocol::messages::{
+    AudioFormatSpec, Message, PlayerCommandKind, PlayerSyncState, TrackProgress,
+};
+use crate::protocol::reporter::StateReporter;
+use crate::protocol::streams::StreamRole;
+use crate::protocol::Role;
+use crate::scheduler::{AudioScheduler, LatencyProfile, LatencyReport, Scheduler};
+use crate::sync::ClockSync;
+pub use health::HealthPolicy;
+use parking_lot::Mutex;
+use pipeline::Pipeline;
+use playback::Playback;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::oneshot;
+pub use volume::VolumeControl;
+
+/// Opens the audio output for a stream format, on the player's output thread
+pub type OutputFactory = Box<dyn FnMut(&AudioFormat) -> Result<Box<dyn AudioOutput>, Error> + Send>;
+
+/// Called with the playback speed the server reports for the track
+pub type SpeedHook = Box<dyn FnMut(f64) + Send>;
+
+/// Settings for a [`Player`]
+#[derive(Debug, Clone)]
+pub struct PlayerConfig {
+    /// Prebuffer, minimum lead, and late-chunk dropping
+    pub profile: LatencyProfile,
+    /// Output device name; `None` uses the system default
+    pub device: Option<String>,
+    /// What to do with chunks that fail to decode
+    pub decode_errors: DecodeErrorPolicy,
+    /// How often to emit [`PlayerEvent::Latency`]; `None` turns the reports off
+    pub latency_report_interval: Option<Duration>,
+    /// When to report the error state to the server
+    pub health: HealthPolicy,
+    /// Ramp applied when playback starts, pauses, stops, or the stream ends
+    pub fade: Fade,
+    /// How the 0-100 protocol volume maps onto output gain
+    pub volume_taper: VolumeTaper,
+    /// Ramp to silence and back when muting and unmuting
+    pub mute_fade: Fade,
+    /// Initial balance and channel trim; change them at runtime through [`Player::volume`]
+    pub balance: Balance,
+    /// Resample every stream to this rate, for devices that only run at one;
+    /// `None` opens the output at the rate of each stream
+    pub output_rate: Option<u32>,
+    /// Resampler used for [`output_rate`](Self::output_rate)
+    pub resample_quality: ResampleQuality,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            profile: LatencyProfile::standard(),
+            device: None,
+            decode_errors: DecodeErrorPolicy::default(),
+            latency_report_interval: Some(Duration::from_secs(10)),
+            health: HealthPolicy::default(),
+            fade: Fade::default(),
+            volume_taper: VolumeTaper::default(),
+            mute_fade: Fade {
+                duration: Duration::from_millis(20),
+                curve: FadeCurve::Linear,
+            },
+            balance: Balance::default(),
+            output_rate: None,
+            resample_quality: ResampleQuality::default(),
+        }
+    }
+}
+
+/// Something the player did or ran into, from [`Player::events`]
+#[derive(Debug, Clone)]
+pub enum PlayerEvent {
+    /// A stream/start the player can play; chunks are decoded in this format
+    StreamStarted(AudioFormat),
+    /// A stream/start in a format the player cannot play; its chunks are ignored
+    UnsupportedFormat(String),
+    /// Enough audio is buffered to start playback
+    PlaybackStarted {
+        /// Audio buffered when playback started
+        buffered: Duration,
+    },
+    /// The server ended the player stream; queued audio fades out and is dropped
+    StreamEnded,
+    /// The server cleared the player buffers (e.g., after a seek); playback
+    /// prebuffers again from the chunks that follow
+    StreamCleared,
+    /// The audio output was opened for this format
+    OutputOpened(AudioFormat),
+    /// A chunk failed to decode
+    DecodeError(DecodeErrorEvent),
+    /// End-to-end latency over the last report interval
+    Latency(LatencyReport),
+    /// The player reported a new sync state to the server
+    SyncStateChanged(PlayerSyncState),
+    /// A protocol message, after the player handled it
+    ///
+    /// Lets the application track metadata or controller state while the player
+    /// owns the connection.
+    Message(Box<Message>),
+}
+
+/// Event subscribers of a player
+#[derive(Clone, Default)]
+struct Events(Vec<UnboundedSender<PlayerEvent>>);
+
+impl Events {
+    fn emit(&self, event: PlayerEvent) {
+        for tx in &self.0 {
+            let _ = tx.send(event.clone());
+        }
+    }
+}
+
+/// Plays the audio of a client connection
+///
+/// Handles what every player needs: the stream format from stream/start, chunk
+/// integrity checks, decoding with error recovery, prebuffering, scheduling on
+/// the synchronized clock, and an output thread that opens the device when audio
+/// arrives. It also keeps the server informed through client/state, switching
+/// to the error state as [`PlayerConfig::health`] prescribes.
+///
+/// ```no_run
+/// # async fn example(client: sendspin::ProtocolClient) -> Result<(), sendspin::Error> {
+/// use sendspin::player::{Player, PlayerConfig, PlayerEvent};
+///
+/// let mut player = Player::new(PlayerConfig::default());
+/// let mut events = player.events();
+/// tokio::spawn(async move {
+///     while let Some(event) = events.recv().await {
+///         if let PlayerEvent::StreamStarted(format) = event {
+///             println!("Playing {:?}", format);
+///         }
+///     }
+/// });
+/// player.run(client).await
+/// # }
+/// ```
+pub struct Player {
+    config: PlayerConfig,
+    events: Events,
+    audit: Option<AuditLog>,
+    output: Option<OutputFactory>,
+    speed_hook: Option<SpeedHook>,
+    reporter: StateReporter,
+    volume: VolumeControl,
+}
+
+impl Player {
+    /// Create a player that plays to the configured device
+    pub fn new(config: PlayerConfig) -> Self {
+        Self {
+            volume: VolumeControl::new(
+                Gain::new(config.volume_taper),
+                config.balance,
+                config.mute_fade,
+            ),
+            config,
+            events: Events::default(),
+            audit: None,
+            output: None,
+            speed_hook: None,
+            reporter: StateReporter::new(),
+        }
+    }
+
+    /// Formats this build can play, for `PlayerV1Support::supported_formats`
+    ///
+    /// Lists stereo at 48kHz and 44.1kHz for every codec with a decoder compiled
+    /// in, so enabling a codec feature advertises it without further changes.
+    /// Multi-byte PCM is listed in both byte orders.
+    pub fn supported_formats() -> Vec<AudioFormatSpec> {
+        let mut formats = Vec::new();
+        for &codec in SUPPORTED_CODECS {
+            // Opus always decodes at 48kHz; lossless codecs carry 24-bit audio
+            let rates: &[u32] = if codec == "opus" {
+                &[48_000]
+            } else {
+                &[48_000, 44_100]
+            };
+            let depths: &[u8] = match codec {
+                "pcm" => &[24, 16, 32, 8],
+                "alac" | "flac" => &[24, 16],
+                "pcm_float" => &[32],
+                _ => &[16],
+            };
+            for &sample_rate in rates {
+                for &bit_depth in depths {
+                    let endians: &[Option<PcmEndian>] = if codec.starts_with("pcm") && bit_depth > 8
+                    {
+                        &[Some(PcmEndian::Little), Some(PcmEndian::Big)]
+                    } else {
+                        &[None]
+                    };
+                    for &endian in endians {
+                        formats.push(AudioFormatSpec {
+                            codec: codec.to_string(),
+                            channels: 2,
+                            sample_rate,
+                            bit_depth,
+                            endian,
+                        });
+                    }
+                }
+            }
+        }
+        formats
+    }
+
+    /// Record protocol, sync, scheduling, and output events into `audit`
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Play through outputs opened by `open` instead of a `CpalOutput`
+    ///
+    /// `open` runs on the output thread whenever the stream format changes, so
+    /// the outputs it returns do not have to be `Send`.
+    pub fn with_output<F>(mut self, open: F) -> Self
+    where
+        F: FnMut(&AudioFormat) -> Result<Box<dyn AudioOutput>, Error> + Send + 'static,
+    {
+        self.output = Some(Box::new(open));
+        self
+    }
+
+    /// Play through several outputs at once, e.g. DACs in different rooms
+    ///
+    /// Each output is named for the logs and opened like in
+    /// [`with_output`](Self::with_output). They play from one schedule through a
+    /// [`MultiOutput`], which delays lower-latency outputs to keep them aligned.
+    /// An output that fails to open or to play is dropped and the others keep
+    /// going; the player fails only when none can be opened.
+    pub fn with_outputs(self, mut outputs: Vec<(String, OutputFactory)>) -> Self {
+        self.with_output(move |format| {
+            let mut multi = MultiOutput::new(format.clone());
+            for (name, open) in &mut outputs {
+                if let Err(e) = open(format).and_then(|out| multi.add(name.clone(), out)) {
+                    log::error!("Cannot open output {}: {}", name, e);
+                }
+            }
+            if multi.is_empty() {
+                return Err(Error::Output("No audio output could be opened".to_string()));
+            }
+            Ok(Box::new(multi) as Box<dyn AudioOutput>)
+        })
+    }
+
+    /// Call `hook` when the server reports a playback speed other than 1.0
+    ///
+    /// The player always plays at normal speed; the hook lets the application
+    /// react to varispeed playback, e.g. by resampling or telling the user. It
+    /// is called whenever the reported speed changes, including the return to
+    /// 1.0, and sees 0.0 when the server pauses the track.
+    pub fn on_playback_speed<F>(mut self, hook: F) -> Self
+    where
+        F: FnMut(f64) + Send + 'static,
+    {
+        self.speed_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Report client/state through `reporter` instead of a default one
+    ///
+    /// Useful to start from a stored volume or to report at another interval.
+    /// The output starts at the reporter's volume and mute state.
+    pub fn with_reporter(mut self, reporter: StateReporter) -> Self {
+        let state = reporter.state();
+        if let Some(volume) = state.volume {
+            self.volume.set_volume(volume);
+        }
+        if let Some(muted) = state.muted {
+            self.volume.set_muted(muted);
+        }
+        self.reporter = reporter;
+        self
+    }
+
+    /// Handle on the reported client/state, e.g. to report a local volume change
+    pub fn reporter(&self) -> StateReporter {
+        self.reporter.clone()
+    }
+
+    /// Handle on the output gain and mute, e.g. for a local volume knob
+    pub fn volume(&self) -> VolumeControl {
+        self.volume.clone()
+    }
+
+    /// Subscribe to player events
+    ///
+    /// Subscribe before [`run`](Self::run); every subscriber gets every event.
+    pub fn events(&mut self) -> UnboundedReceiver<PlayerEvent> {
+        let (tx, rx) = unbounded_channel();
+        self.events.0.push(tx);
+        rx
+    }
+
+    /// Play until the connection ends
+    ///
+    /// Fails if the audio output cannot be opened. To stop early, close the
+    /// connection through a sender from [`ProtocolClient::sender`].
+    pub async fn run(self, client: ProtocolClient) -> Result<(), Error> {
+        let Player {
+            config,
+            events,
+            audit,
+            output,
+            speed_hook,
+            reporter,
+            volume,
+        } = self;
+        let ClientParts {
+            mut messages,
+            mut audio,
+            clock,
+            sender,
+            ..
+        } = client.into_parts();
+
+        let profile = config.profile;
+        let scheduler: Arc<dyn Scheduler> = Arc::new(match profile.max_late {
+            Some(max_late) => AudioScheduler::new().with_max_late(max_late),
+            None => AudioScheduler::new(),
+        });
+        if let (Some(audit), Some(max_late)) = (&audit, profile.max_late) {
+            audit.set_header(MAX_LATE_HEADER, max_late.as_micros());
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        // Silent until the first stream starts, so it fades in
+        let fader = Arc::new(Mutex::new(Fader::silent(config.fade)));
+        let streaming = Arc::new(AtomicBool::new(false));
+        let flush = Arc::new(AtomicBool::new(false));
+        let (failed_tx, mut failed) = oneshot::channel();
+        let device = config.device.clone();
+        let playback = Playback {
+            scheduler: Arc::clone(&scheduler),
+            open: output.unwrap_or_else(|| {
+                Box::new(move |format: &AudioFormat| {
+                    CpalOutput::with_device(format.clone(), device.as_deref())
+                        .map(|out| Box::new(out) as Box<dyn AudioOutput>)
+                })
+            }),
+            profile,
+            report_interval: config.latency_report_interval,
+            events: events.clone(),
+            audit: audit.clone(),
+            stop: Arc::clone(&stop),
+            failed: failed_tx,
+            streaming: Arc::clone(&streaming),
+            flush: Arc::clone(&flush),
+            clock: Arc::clone(&clock),
+            health: config.health,
+            reporter: reporter.clone(),
+            fader: Arc::clone(&fader),
+            volume: volume.clone(),
+        };
+        let thread = std::thread::Builder::new()
+            .name("sendspin-playback".to_string())
+            .spawn(move || playback.run())
+            .map_err(|e| Error::Output(format!("Cannot start the output thread: {}", e)))?;
+        let reporting = tokio::spawn(reporter.clone().run(sender.clone()));
+
+        let mut driver = Driver {
+            pipeline: Pipeline::new(
+                profile,
+                config.decode_errors,
+                config
+                    .output_rate
+                    .map(|rate| (rate, config.resample_quality)),
+                events.clone(),
+            ),
+            events,
+            audit,
+            clock,
+            scheduler,
+            sender,
+            reporter,
+            streaming,
+            flush,
+            cleared_at: None,
+            speed: 1.0,
+            speed_hook,
+            fader,
+            volume,
+        };
+        let (mut messages_open, mut audio_open) = (true, true);
+        let result = loop {
+            if !messages_open && !audio_open {
+                break Ok(());
+            }
+            tokio::select! {
+                // Messages first: a stream/start must take effect before the
+                // chunks that follow it, which arrive on another channel
+                biased;
+                msg = messages.recv_stamped(), if messages_open => match msg {
+                    Some(received) => driver.on_message(received.message, received.received_at).await,
+                    None => messages_open = false,
+                },
+                chunk = audio.recv(), if audio_open => match chunk {
+                    Some(chunk) => driver.on_chunk(chunk).await,
+                    None => audio_open = false,
+                },
+                Ok(e) = &mut failed => break Err(e),
+            }
+        };
+
+        reporting.abort();
+        stop.store(true, Ordering::Relaxed);
+        let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+        result
+    }
+}
+
+/// The connection side of a running player
+struct Driver {
+    pipeline: Pipeline,
+    events: Events,
+    audit: Option<AuditLog>,
+    clock: Arc<tokio::sync::Mutex<ClockSync>>,
+    scheduler: Arc<dyn Scheduler>,
+    sender: WsSender,
+    reporter: StateReporter,
+    /// Tells the output thread whether running dry is an underrun
+    streaming: Arc<AtomicBool>,
+    /// Asks the output thread to drop audio the output has queued
+    flush: Arc<AtomicBool>,
+    /// Arrival of the last stream/clear; chunks that arrived earlier are stale
+    cleared_at: Option<Instant>,
+    /// Playback speed last reported in the track progress
+    speed: f64,
+    speed_hook: Option<SpeedHook>,
+    /// Fades the output thread applies
+    fader: Arc<Mutex<Fader>>,
+    volume: VolumeControl,
+}
+
+impl Driver {
+    async fn on_message(&mut self, msg: Message, received_at: Instant) {
+        if let Some(ref audit) = self.audit {
+            audit.record(AuditEvent::Protocol {
+                direction: Direction::Inbound,
+                message_type: msg.message_type().to_string(),
+            });
+        }
+        match &msg {
+            Message::StreamStart(start) => {
+                if let Some(ref player) = start.player {
+                    match self.pipeline.start(player) {
+                        Ok(format) => {
+                            self.fader.lock().fade_in();
+                            self.events.emit(PlayerEvent::StreamStarted(format));
+                        }
+                        Err(reason) => {
+                            log::error!("Cannot play stream: {}", reason);
+                            self.events.emit(PlayerEvent::UnsupportedFormat(reason));
+                        }
+                    }
+                }
+            }
+            Message::StreamEnd(end) if targets_player(end.roles.as_deref()) => {
+                // The output thread drops what is left once the fade is done
+                self.fader.lock().fade_out();
+                self.pipeline.end();
+                self.events.emit(PlayerEvent::StreamEnded);
+            }
+            Message::StreamClear(clear) if targets_player(clear.roles.as_deref()) => {
+                self.cleared_at = Some(received_at);
+                self.pipeline.clear();
+                self.scheduler.clear();
+                self.flush.store(true, Ordering::Relaxed);
+                self.fader.lock().restart();
+                self.events.emit(PlayerEvent::StreamCleared);
+            }
+            Message::ServerCommand(command) => {
+                if let Some(ref player) = command.player {
+                    if let Some(volume) = player.volume {
+                        self.volume.set_volume(volume);
+                    }
+                    if let Some(mute) = player.mute {
+                        self.volume.set_muted(mute);
+                    }
+                    match player.command {
+                        PlayerCommandKind::Play => self.fader.lock().fade_in(),
+                        PlayerCommandKind::Pause | PlayerCommandKind::Stop => {
+                            self.fader.lock().fade_out()
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Message::ServerState(state) => {
+                let progress = state.metadata.as_ref().and_then(|m| m.progress.as_ref());
+                if let Some(speed) = progress.map(TrackProgress::speed) {
+                    self.on_speed(speed);
+                }
+            }
+            Message::ServerTime(_) => {
+                // The client already applied the reply
+                if let Some(ref audit) = self.audit {
+                    let sync = self.clock.lock().await;
+                    if let Some(rtt) = sync.rtt_micros() {
+                        audit.record(AuditEvent::Sync {
+                            rtt_micros: rtt,
+                            quality: sync.quality(),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.reporter.apply(&msg);
+        self.streaming
+            .store(self.pipeline.is_active(), Ordering::Relaxed);
+        self.events.emit(PlayerEvent::Message(Box::new(msg)));
+    }
+
+    fn on_speed(&mut self, speed: f64) {
+        if speed == self.speed {
+            return;
+        }
+        log::debug!("Server playback speed {} -> {}", self.speed, speed);
+        self.speed = speed;
+        if let Some(ref mut hook) = self.speed_hook {
+            hook(speed);
+        }
+    }
+
+    async fn on_chunk(&mut self, chunk: AudioChunk) {
+        // Chunks and messages arrive on separate channels, so a chunk sent
+        // before a stream/clear can be handled after it
+        if self
+            .cleared_at
+            .is_some_and(|cleared| chunk.received_at < cleared)
+        {
+            log::debug!(
+                "Dropping chunk ts={} sent before stream/clear",
+                chunk.timestamp
+            );
+            return;
+        }
+        let fed = {
+            let sync = self.clock.lock().await;
+            self.pipeline.feed(&chunk, &sync)
+        };
+        if let Some(buffer) = fed.buffer {
+            if let Some(ref audit) = self.audit {
+                // Signed lead so replays can see chunks that arrived late
+                let now = Instant::now();
+                let lead_micros = if buffer.play_at >= now {
+                    buffer.play_at.duration_since(now).as_micros() as i64
+                } else {
+                    -(now.duration_since(buffer.play_at).as_micros() as i64)
+                };
+                audit.record(AuditEvent::Schedule {
+                    timestamp: buffer.timestamp,
+                    lead_micros,
+                });
+            }
+            let frames = buffer.samples.len() / buffer.format.channels.max(1) as usize;
+            let end = buffer.play_at
+                + Duration::from_micros(
+                    frames as u64 * 1_000_000 / buffer.format.sample_rate.max(1) as u64,
+                );
+            self.reporter
+                .set_buffered(end.saturating_duration_since(Instant::now()));
+            self.scheduler.schedule(buffer);
+        }
+        self.streaming
+            .store(self.pipeline.is_active(), Ordering::Relaxed);
+        if let Some(request) = fed.fallback {
+            if let Err(e) = self.sender.send_message(request).await {
+                log::error!("Failed to request fallback format: {}", e);
+            }
+        }
+    }
+}
+
+/// Whether a stream/end or stream/clear with these roles covers the player
+fn targets_player(roles: Option<&[Role]>) -> bool {
+    roles.is_none_or(|roles| {
+        roles
+            .iter()
+            .any(|role| StreamRole::from_role(role) == Some(StreamRole::Player))
+    })
+}
+
+impl std::fmt::Debug for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Player")
+            .field("config", &self.config)
+            .field("subscribers", &self.events.0.len())
+            .finish_non_exhaustive()
+    }
+}
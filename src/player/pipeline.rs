@@ -0,0 +1,348 @@
+// ABOUTME: Decoding and timing of received audio chunks for the player
+// ABOUTME: Tracks the negotiated format, checks chunk integrity, resamples, and computes play times
+
+use super::{Events, PlayerEvent};
+#[cfg(feature = "opus")]
+use crate::audio::decode::OpusDecoder;
+#[cfg(feature = "vorbis")]
+use crate::audio::decode::VorbisDecoder;
+use crate::audio::decode::{
+    AutoEndianPcmDecoder, DecodeErrorPolicy, DecodeErrorTracker, Decoder, PcmDecoder, PcmEndian,
+    RecoveryAction, SUPPORTED_CODECS,
+};
+#[cfg(feature = "symphonia")]
+use crate::audio::decode::{SymphoniaDecoder, SYMPHONIA_CODECS};
+use crate::audio::{
+    AudioBuffer, AudioFormat, Codec, FrameLayout, IntegrityChecker, ResampleQuality, Resampler,
+    Sample,
+};
+use crate::protocol::client::AudioChunk;
+use crate::protocol::messages::{Message, StreamPlayerConfig};
+use crate::scheduler::LatencyProfile;
+use crate::sync::ClockSync;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// What became of one audio chunk
+#[derive(Default)]
+pub(super) struct Fed {
+    /// Decoded audio to schedule
+    pub(super) buffer: Option<AudioBuffer>,
+    /// Format request to send after the stream was abandoned
+    pub(super) fallback: Option<Message>,
+}
+
+/// Turns the chunks of the current stream into scheduled buffers
+pub(super) struct Pipeline {
+    profile: LatencyProfile,
+    policy: DecodeErrorPolicy,
+    /// Output rate and quality to resample streams to
+    resample: Option<(u32, ResampleQuality)>,
+    events: Events,
+    stream: Option<Stream>,
+}
+
+/// Decode state of one stream, from stream/start to stream/end
+struct Stream {
+    format: AudioFormat,
+    /// The format of scheduled buffers, differing in rate when resampling
+    output_format: AudioFormat,
+    decoder: Box<dyn Decoder + Send>,
+    resampler: Option<Resampler>,
+    integrity: Option<IntegrityChecker>,
+    errors: DecodeErrorTracker,
+    buffered: Duration,
+    started: bool,
+    next_play_time: Option<Instant>,
+    /// Frames in the last decoded chunk, the length of concealment for codecs
+    /// whose chunk size says nothing about duration
+    last_frames: usize,
+}
+
+impl Stream {
+    fn new(
+        format: AudioFormat,
+        policy: &DecodeErrorPolicy,
+        resample: Option<(u32, ResampleQuality)>,
+    ) -> Result<Self, String> {
+        // Only PCM chunks have a fixed frame layout to check; the PCM decoders
+        // carry a split frame over to the next chunk
+        let integrity = if format.codec.is_pcm() {
+            match FrameLayout::for_format(&format) {
+                Ok(layout) => Some(IntegrityChecker::new(layout).with_split_frames()),
+                Err(e) => {
+                    log::warn!("Cannot verify chunks: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let resampler = match resample {
+            Some((rate, quality)) if rate != format.sample_rate => Some(
+                Resampler::new(format.sample_rate, rate, format.channels, quality)
+                    .map_err(|e| e.to_string())?,
+            ),
+            _ => None,
+        };
+        let output_format = AudioFormat {
+            sample_rate: resampler
+                .as_ref()
+                .map_or(format.sample_rate, Resampler::output_rate),
+            ..format.clone()
+        };
+        Ok(Self {
+            decoder: decoder_for(&format)?,
+            resampler,
+            output_format,
+            format,
+            integrity,
+            errors: DecodeErrorTracker::new(policy.clone()),
+            buffered: Duration::ZERO,
+            started: false,
+            next_play_time: None,
+            last_frames: 0,
+        })
+    }
+}
+
+/// A decoder for a stream format, if the player can play it
+fn decoder_for(format: &AudioFormat) -> Result<Box<dyn Decoder + Send>, String> {
+    match format.codec {
+        Codec::Pcm => {
+            if !matches!(format.bit_depth, 8 | 16 | 24 | 32) {
+                return Err(format!(
+                    "unsupported bit depth {}, only 8, 16, 24 or 32-bit PCM is supported",
+                    format.bit_depth
+                ));
+            }
+            // Without a byte order from the server, tell from the audio
+            match format.endian {
+                Some(endian) => Ok(Box::new(
+                    PcmDecoder::with_endian(format.bit_depth, endian)
+                        .with_channels(format.channels),
+                )),
+                None => Ok(Box::new(AutoEndianPcmDecoder::new(
+                    format.bit_depth,
+                    format.channels,
+                ))),
+            }
+        }
+        Codec::PcmFloat => {
+            if format.bit_depth != 32 {
+                return Err(format!(
+                    "unsupported bit depth {}, float PCM must be 32-bit",
+                    format.bit_depth
+                ));
+            }
+            Ok(Box::new(
+                PcmDecoder::float(format.endian.unwrap_or(PcmEndian::Little))
+                    .with_channels(format.channels),
+            ))
+        }
+        #[cfg(feature = "opus")]
+        Codec::Opus => OpusDecoder::new(format)
+            .map(|decoder| Box::new(decoder) as Box<dyn Decoder + Send>)
+            .map_err(|e| e.to_string()),
+        #[cfg(feature = "vorbis")]
+        Codec::Vorbis => VorbisDecoder::new(format)
+            .map(|decoder| Box::new(decoder) as Box<dyn Decoder + Send>)
+            .map_err(|e| e.to_string()),
+        // Codecs without a dedicated decoder in this build
+        #[cfg(feature = "symphonia")]
+        codec if SYMPHONIA_CODECS.contains(&codec) => SymphoniaDecoder::new(format)
+            .map(|decoder| Box::new(decoder) as Box<dyn Decoder + Send>)
+            .map_err(|e| e.to_string()),
+        codec => Err(format!(
+            "unsupported codec '{}', this build plays {}",
+            codec.name(),
+            SUPPORTED_CODECS.join(", ")
+        )),
+    }
+}
+
+impl Pipeline {
+    pub(super) fn new(
+        profile: LatencyProfile,
+        policy: DecodeErrorPolicy,
+        resample: Option<(u32, ResampleQuality)>,
+        events: Events,
+    ) -> Self {
+        Self {
+            profile,
+            policy,
+            resample,
+            events,
+            stream: None,
+        }
+    }
+
+    /// Set up decoding for a new stream; fails for formats the player cannot play
+    pub(super) fn start(&mut self, config: &StreamPlayerConfig) -> Result<AudioFormat, String> {
+        self.stream = None;
+        if Codec::from_name(&config.codec).is_none() {
+            return Err(format!(
+                "unsupported codec '{}', this build plays {}",
+                config.codec,
+                SUPPORTED_CODECS.join(", ")
+            ));
+        }
+        let format = config.audio_format().map_err(|e| e.to_string())?;
+        self.stream = Some(Stream::new(format.clone(), &self.policy, self.resample)?);
+        Ok(format)
+    }
+
+    /// Drop decode and timing state for a stream/clear, keeping the format
+    ///
+    /// The chunks that follow start a new timeline, so playback prebuffers again.
+    pub(super) fn clear(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            // The format was accepted by stream/start, so this only fails if the
+            // decoder cannot be created again
+            match Stream::new(stream.format, &self.policy, self.resample) {
+                Ok(stream) => self.stream = Some(stream),
+                Err(e) => log::error!("Cannot restart decoding after stream/clear: {}", e),
+            }
+        }
+    }
+
+    /// Forget the current stream; chunks are ignored until the next stream/start
+    pub(super) fn end(&mut self) {
+        self.stream = None;
+    }
+
+    /// Whether a stream is being decoded
+    pub(super) fn is_active(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Decode a chunk and work out when it plays
+    pub(super) fn feed(&mut self, chunk: &AudioChunk, sync: &ClockSync) -> Fed {
+        let Some(stream) = self.stream.as_mut() else {
+            return Fed::default();
+        };
+        if let Some(ref mut checker) = stream.integrity {
+            match checker.check(chunk.timestamp, &chunk.data) {
+                Ok(report) => {
+                    if let Some(delta) = report.discontinuity_micros.filter(|d| d.abs() > 1_000) {
+                        log::warn!(
+                            "Timestamp discontinuity at ts={}: {}µs",
+                            chunk.timestamp,
+                            delta
+                        );
+                    }
+                }
+                Err(mismatch) => {
+                    // Don't decode garbage
+                    log::error!("Bad frame at ts={}: {}", chunk.timestamp, mismatch);
+                    return Fed::default();
+                }
+            }
+        }
+
+        let mut samples: Arc<[Sample]> = match stream.decoder.feed(&chunk.data) {
+            Ok(samples) => {
+                stream.errors.record_success();
+                samples.into()
+            }
+            Err(e) => {
+                let event = stream
+                    .errors
+                    .record_failure(stream.format.codec, &e, chunk.timestamp);
+                log::error!(
+                    "Decode error: codec={:?} ts={} cause={} action={:?} consecutive={}",
+                    event.codec,
+                    event.timestamp,
+                    event.cause,
+                    event.action,
+                    event.consecutive
+                );
+                self.events.emit(PlayerEvent::DecodeError(event.clone()));
+                match event.action {
+                    RecoveryAction::Skipped => return Fed::default(),
+                    RecoveryAction::Concealed => {
+                        silence(&stream.format, chunk.data.len(), stream.last_frames)
+                    }
+                    RecoveryAction::StreamAborted => {
+                        // Wait for the server to restart the stream in the fallback format
+                        let fallback = stream.errors.fallback_request(&event);
+                        self.stream = None;
+                        return Fed {
+                            buffer: None,
+                            fallback,
+                        };
+                    }
+                }
+            }
+        };
+
+        // samples.len() includes all channels
+        stream.last_frames = samples.len() / stream.format.channels.max(1) as usize;
+        let mut timestamp = chunk.timestamp;
+        if let Some(ref mut resampler) = stream.resampler {
+            // Resampled output starts a little before this chunk
+            timestamp += resampler.offset_micros();
+            match resampler.process(&samples) {
+                Ok(resampled) if resampled.is_empty() => return Fed::default(),
+                Ok(resampled) => samples = resampled.into(),
+                Err(e) => {
+                    log::error!("Cannot resample chunk at ts={}: {}", chunk.timestamp, e);
+                    return Fed::default();
+                }
+            }
+        }
+        let format = &stream.output_format;
+        let frames = samples.len() / format.channels.max(1) as usize;
+        let duration = Duration::from_micros(frames as u64 * 1_000_000 / format.sample_rate as u64);
+        let play_at = match sync.server_to_local_instant(timestamp) {
+            Some(instant) => instant,
+            None => {
+                // No clock sync yet: play back to back after the initial buffer
+                let play_at = *stream
+                    .next_play_time
+                    .get_or_insert_with(|| Instant::now() + self.profile.start_buffer);
+                stream.next_play_time = Some(play_at + duration);
+                play_at
+            }
+        };
+        // Never schedule closer than the minimum lead, so chunks are not dropped as late
+        let play_at = play_at.max(Instant::now() + self.profile.min_lead);
+
+        stream.buffered += duration;
+        if !stream.started && stream.buffered >= self.profile.start_buffer {
+            stream.started = true;
+            self.events.emit(PlayerEvent::PlaybackStarted {
+                buffered: stream.buffered,
+            });
+        }
+        log::trace!(
+            "Scheduled chunk ts={} lead={:?} buffered={:?}",
+            chunk.timestamp,
+            play_at.saturating_duration_since(Instant::now()),
+            stream.buffered
+        );
+
+        Fed {
+            buffer: Some(AudioBuffer {
+                timestamp,
+                play_at,
+                samples,
+                format: stream.output_format.clone(),
+            }),
+            fallback: None,
+        }
+    }
+}
+
+/// Silence standing in for a chunk of `bytes` that failed to decode
+///
+/// Compressed chunks are assumed to last as long as the previous one.
+fn silence(format: &AudioFormat, bytes: usize, last_frames: usize) -> Arc<[Sample]> {
+    let frames = if format.codec.is_pcm() {
+        let frame = (format.bit_depth as usize / 8) * format.channels.max(1) as usize;
+        bytes / frame.max(1)
+    } else {
+        last_frames
+    };
+    vec![Sample::ZERO; frames * format.channels as usize].into()
+}
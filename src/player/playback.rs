@@ -0,0 +1,161 @@
+// ABOUTME: Output thread of the player
+// ABOUTME: Plays due buffers from the scheduler and reports achieved latency
+
+use super::health::{Health, HealthPolicy};
+use super::{Events, OutputFactory, PlayerEvent, VolumeControl};
+use crate::audio::{AudioOutput, Fader};
+use crate::audit::{AuditEvent, AuditLog};
+use crate::error::Error;
+use crate::protocol::messages::PlayerSyncState;
+use crate::protocol::reporter::StateReporter;
+use crate::scheduler::{LatencyMonitor, LatencyProfile, Scheduler};
+use crate::sync::{ClockSync, SyncQuality};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Everything the output thread owns
+pub(super) struct Playback {
+    pub(super) scheduler: Arc<dyn Scheduler>,
+    pub(super) open: OutputFactory,
+    pub(super) profile: LatencyProfile,
+    pub(super) report_interval: Option<Duration>,
+    pub(super) events: Events,
+    pub(super) audit: Option<AuditLog>,
+    pub(super) stop: Arc<AtomicBool>,
+    /// Reports an output that could not be opened
+    pub(super) failed: oneshot::Sender<Error>,
+    /// Whether a stream is playing, so running dry is an underrun
+    pub(super) streaming: Arc<AtomicBool>,
+    /// Set on stream/clear to drop what the output has queued
+    pub(super) flush: Arc<AtomicBool>,
+    pub(super) clock: Arc<tokio::sync::Mutex<ClockSync>>,
+    pub(super) health: HealthPolicy,
+    pub(super) reporter: StateReporter,
+    /// Fades requested by the connection side
+    pub(super) fader: Arc<Mutex<Fader>>,
+    pub(super) volume: VolumeControl,
+}
+
+/// How long the output may run dry mid-stream before it counts as an underrun
+const UNDERRUN_TOLERANCE: Duration = Duration::from_millis(20);
+
+impl Playback {
+    /// Run until stopped or the output cannot be opened
+    ///
+    /// A plain thread rather than a task: outputs such as `CpalOutput` are not `Send`.
+    pub(super) fn run(mut self) {
+        let mut output: Option<Box<dyn AudioOutput>> = None;
+        let mut latency = LatencyMonitor::new(self.profile);
+        let mut last_report = Instant::now();
+        let mut dropped = self.scheduler.stats().dropped;
+        let mut health = Health::new(self.health);
+        let mut sync_lost = false;
+        // End of the audio handed to the output so far
+        let mut playing_until: Option<Instant> = None;
+
+        while !self.stop.load(Ordering::Relaxed) {
+            if let Some(buffer) = self.scheduler.next_ready() {
+                // Opened on the first buffer, and again whenever the format changes
+                if output.as_ref().map(|out| out.format()) != Some(&buffer.format) {
+                    // Release the device before opening it again
+                    drop(output.take());
+                    match (self.open)(&buffer.format) {
+                        Ok(out) => {
+                            log::info!("Audio output opened for {:?}", buffer.format);
+                            self.events
+                                .emit(PlayerEvent::OutputOpened(buffer.format.clone()));
+                            output = Some(out);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to open audio output: {}", e);
+                            let _ = self.failed.send(e);
+                            return;
+                        }
+                    }
+                }
+                if let Some(ref mut out) = output {
+                    let frames = buffer.samples.len() / buffer.format.channels.max(1) as usize;
+                    let duration = Duration::from_micros(
+                        frames as u64 * 1_000_000 / buffer.format.sample_rate.max(1) as u64,
+                    );
+                    playing_until = Some(buffer.play_at.max(Instant::now()) + duration);
+                    let playout_delay = Instant::now().saturating_duration_since(buffer.play_at);
+                    // Volume and mute, then fades, before the output converts the samples
+                    let samples = self.volume.process(
+                        &buffer.samples,
+                        buffer.format.channels,
+                        buffer.format.sample_rate,
+                    );
+                    let samples = self.fader.lock().process(
+                        &samples,
+                        buffer.format.channels,
+                        buffer.format.sample_rate,
+                    );
+                    if let Err(e) = out.write_timed(buffer.timestamp, &samples) {
+                        log::error!("Output error: {}", e);
+                    }
+                    latency.record(playout_delay, Duration::from_micros(out.latency_micros()));
+                    if let Some(ref audit) = self.audit {
+                        audit.record(AuditEvent::Output {
+                            timestamp: buffer.timestamp,
+                            samples: buffer.samples.len(),
+                        });
+                    }
+                }
+            }
+            if self.flush.swap(false, Ordering::Relaxed) {
+                if let Some(ref mut out) = output {
+                    out.flush();
+                }
+                playing_until = None;
+            }
+            // After stream/end, drop the rest of the stream once it has faded out
+            if !self.streaming.load(Ordering::Relaxed)
+                && !self.scheduler.is_empty()
+                && self.fader.lock().is_silent()
+            {
+                self.scheduler.clear();
+            }
+            let now = Instant::now();
+            if let Some(until) = playing_until {
+                if now > until + UNDERRUN_TOLERANCE && self.scheduler.is_empty() {
+                    playing_until = None;
+                    if self.streaming.load(Ordering::Relaxed) {
+                        log::warn!("Audio output ran dry");
+                        health.record_underrun(now);
+                    }
+                }
+            }
+            // Keep the last known quality while the connection updates the clock
+            if let Ok(sync) = self.clock.try_lock() {
+                sync_lost = sync.rtt_micros().is_some() && sync.quality() == SyncQuality::Lost;
+            }
+            if let Some(state) = health.update(now, sync_lost) {
+                match state {
+                    PlayerSyncState::Error => log::warn!("Reporting sync error to the server"),
+                    PlayerSyncState::Synchronized => log::info!("Back in sync"),
+                }
+                self.reporter.set_sync_state(state.clone());
+                self.events.emit(PlayerEvent::SyncStateChanged(state));
+            }
+            if let Some(interval) = self.report_interval {
+                if last_report.elapsed() >= interval {
+                    let total = self.scheduler.stats().dropped;
+                    latency.record_dropped(total - dropped);
+                    dropped = total;
+                    let report = latency.report();
+                    if report.samples > 0 {
+                        self.events.emit(PlayerEvent::Latency(report));
+                    }
+                    latency.reset();
+                    last_report = Instant::now();
+                }
+            }
+            // Per spec: 1ms polling to reduce enqueue jitter
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
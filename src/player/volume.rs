@@ -0,0 +1,121 @@
+// ABOUTME: Volume handle of the player
+// ABOUTME: Lets applications and server commands set gain, mute, and balance while the player runs
+
+use crate::audio::{Balance, Fade, Fader, Gain, Sample};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Sets the gain, mute state, and balance the player applies to its output
+///
+/// Server volume and mute commands go through the same controls, volumes
+/// mapped onto the configured [`VolumeTaper`](crate::audio::VolumeTaper).
+/// Muting ramps to silence over [`PlayerConfig::mute_fade`](super::PlayerConfig::mute_fade)
+/// rather than cutting off, and unmuting ramps back. Changes take effect with
+/// the next buffer handed to the output. Report local changes to the server
+/// through [`Player::reporter`](super::Player::reporter). Cloning is cheap; all
+/// clones control the same player.
+#[derive(Debug, Clone)]
+pub struct VolumeControl {
+    level: Arc<Mutex<Level>>,
+}
+
+#[derive(Debug)]
+struct Level {
+    gain: Gain,
+    /// Temporary attenuation on top of the gain, e.g. under an announcement
+    duck: Gain,
+    balance: Balance,
+    mute: Fader,
+    muted: bool,
+}
+
+impl VolumeControl {
+    pub(super) fn new(gain: Gain, balance: Balance, mute_fade: Fade) -> Self {
+        Self {
+            level: Arc::new(Mutex::new(Level {
+                gain,
+                duck: Gain::default(),
+                balance,
+                mute: Fader::new(mute_fade),
+                muted: false,
+            })),
+        }
+    }
+
+    /// Set the gain for a 0-100 protocol volume
+    pub fn set_volume(&self, volume: u8) {
+        self.level.lock().gain.set_volume(volume);
+    }
+
+    /// Set the gain in dB, up to [`MAX_GAIN_DB`](crate::audio::gain::MAX_GAIN_DB)
+    pub fn set_gain_db(&self, db: f32) {
+        self.level.lock().gain.set_gain_db(db);
+    }
+
+    /// Current gain in dB
+    pub fn gain_db(&self) -> f32 {
+        self.level.lock().gain.gain_db()
+    }
+
+    /// Duck the output `db` below the set gain; 0 restores it
+    ///
+    /// Kept apart from the volume, so volume changes while ducked are not lost.
+    pub fn set_duck_db(&self, db: f32) {
+        self.level.lock().duck.set_gain_db(-db.abs());
+    }
+
+    /// Current ducking in dB below the set gain
+    pub fn duck_db(&self) -> f32 {
+        -self.level.lock().duck.gain_db()
+    }
+
+    /// Set the balance, from -1.0 (left only) to 1.0 (right only)
+    pub fn set_balance(&self, balance: f32) {
+        self.level.lock().balance.set_balance(balance);
+    }
+
+    /// Current balance
+    pub fn balance(&self) -> f32 {
+        self.level.lock().balance.balance()
+    }
+
+    /// Set the trim of one channel in dB
+    pub fn set_trim_db(&self, channel: usize, db: f32) {
+        self.level.lock().balance.set_trim_db(channel, db);
+    }
+
+    /// Trim of one channel in dB
+    pub fn trim_db(&self, channel: usize) -> f32 {
+        self.level.lock().balance.trim_db(channel)
+    }
+
+    /// Mute or unmute with a short ramp
+    pub fn set_muted(&self, muted: bool) {
+        let mut level = self.level.lock();
+        level.muted = muted;
+        if muted {
+            level.mute.fade_out();
+        } else {
+            level.mute.fade_in();
+        }
+    }
+
+    /// Whether the output is muted, or fading to silence
+    pub fn is_muted(&self) -> bool {
+        self.level.lock().muted
+    }
+
+    /// Apply gain, balance, and mute to a buffer on its way to the output
+    pub(super) fn process(
+        &self,
+        samples: &Arc<[Sample]>,
+        channels: u8,
+        sample_rate: u32,
+    ) -> Arc<[Sample]> {
+        let mut level = self.level.lock();
+        let samples = level.gain.process(samples);
+        let samples = level.duck.process(&samples);
+        let samples = level.balance.process(&samples, channels);
+        level.mute.process(&samples, channels, sample_rate)
+    }
+}
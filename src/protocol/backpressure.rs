@@ -0,0 +1,310 @@
+// ABOUTME: Bounded channels for binary frames with a configurable backpressure policy
+// ABOUTME: Waits for the consumer or drops the oldest/newest frame when a stream falls behind
+
+use futures_util::Stream;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// What the router does when a frame channel is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait until the consumer makes room
+    ///
+    /// Nothing is lost, but the whole connection stalls meanwhile, including
+    /// control messages and the other streams.
+    Block,
+    /// Drop the oldest queued frame to make room for the new one
+    #[default]
+    DropOldest,
+    /// Drop the incoming frame and keep the queue as it is
+    DropNewest,
+}
+
+/// Capacity and full-queue policy of one frame channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// Maximum number of queued frames
+    pub capacity: usize,
+    /// What happens when the queue is full
+    pub policy: BackpressurePolicy,
+}
+
+impl ChannelConfig {
+    /// A channel holding up to `capacity` frames (at least one)
+    pub fn new(capacity: usize, policy: BackpressurePolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+        }
+    }
+}
+
+/// Channel settings per binary stream type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamChannels {
+    /// Audio chunks, for the primary stream and each extra slot
+    pub audio: ChannelConfig,
+    /// Artwork images
+    pub artwork: ChannelConfig,
+    /// Visualizer frames
+    pub visualizer: ChannelConfig,
+}
+
+impl Default for StreamChannels {
+    /// About 20 seconds of 20ms audio chunks, a few images, and two seconds of visualizer frames
+    fn default() -> Self {
+        Self {
+            audio: ChannelConfig::new(1024, BackpressurePolicy::DropOldest),
+            artwork: ChannelConfig::new(8, BackpressurePolicy::DropOldest),
+            visualizer: ChannelConfig::new(64, BackpressurePolicy::DropOldest),
+        }
+    }
+}
+
+/// Frames dropped so far by each stream's channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DroppedFrames {
+    /// Primary stream audio chunks
+    pub audio: u64,
+    /// Artwork images
+    pub artwork: u64,
+    /// Visualizer frames
+    pub visualizer: u64,
+}
+
+#[derive(Debug)]
+struct Channel<T> {
+    queue: Mutex<VecDeque<T>>,
+    config: ChannelConfig,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    readable: Notify,
+    writable: Notify,
+}
+
+/// Create a bounded frame channel
+pub(crate) fn channel<T>(config: ChannelConfig) -> (FrameSender<T>, FrameReceiver<T>) {
+    let channel = Arc::new(Channel {
+        queue: Mutex::new(VecDeque::with_capacity(config.capacity.min(64))),
+        config: ChannelConfig::new(config.capacity, config.policy),
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        readable: Notify::new(),
+        writable: Notify::new(),
+    });
+    (
+        FrameSender {
+            channel: Arc::clone(&channel),
+        },
+        FrameReceiver {
+            channel,
+            waiting: None,
+        },
+    )
+}
+
+/// Sending half of a frame channel, owned by the message router
+#[derive(Debug)]
+pub(crate) struct FrameSender<T> {
+    channel: Arc<Channel<T>>,
+}
+
+impl<T> FrameSender<T> {
+    /// Queue a frame according to the channel's policy
+    ///
+    /// Returns false once the receiver is gone.
+    pub(crate) async fn send(&self, frame: T) -> bool {
+        let channel = &self.channel;
+        let mut frame = Some(frame);
+        loop {
+            let space = channel.writable.notified();
+            tokio::pin!(space);
+            space.as_mut().enable();
+            {
+                let mut queue = channel.queue.lock();
+                if !channel.receiver_alive.load(Ordering::Acquire) {
+                    return false;
+                }
+                let full = queue.len() >= channel.config.capacity;
+                match channel.config.policy {
+                    _ if !full => {}
+                    BackpressurePolicy::Block => {}
+                    BackpressurePolicy::DropOldest => {
+                        channel.dropped.fetch_add(1, Ordering::Relaxed);
+                        queue.pop_front();
+                    }
+                    BackpressurePolicy::DropNewest => {
+                        channel.dropped.fetch_add(1, Ordering::Relaxed);
+                        return true;
+                    }
+                }
+                if queue.len() < channel.config.capacity {
+                    queue.extend(frame.take());
+                    drop(queue);
+                    channel.readable.notify_one();
+                    return true;
+                }
+            }
+            space.await;
+        }
+    }
+}
+
+impl<T> Clone for FrameSender<T> {
+    fn clone(&self) -> Self {
+        self.channel.senders.fetch_add(1, Ordering::AcqRel);
+        Self {
+            channel: Arc::clone(&self.channel),
+        }
+    }
+}
+
+impl<T> Drop for FrameSender<T> {
+    fn drop(&mut self) {
+        if self.channel.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Take the lock so a receiver between its check and its wait sees the close
+            drop(self.channel.queue.lock());
+            self.channel.readable.notify_waiters();
+        }
+    }
+}
+
+/// Receiving half of a bounded frame channel
+///
+/// Frames that do not fit are handled by the channel's [`BackpressurePolicy`];
+/// [`dropped`](Self::dropped) counts the ones that were discarded.
+///
+/// Also a [`Stream`], for use with `StreamExt` combinators.
+pub struct FrameReceiver<T> {
+    channel: Arc<Channel<T>>,
+    /// Wakeup registration of a [`Stream::poll_next`] that found the queue empty
+    waiting: Option<Pin<Box<dyn Future<Output = ()> + Send + Sync>>>,
+}
+
+impl<T> FrameReceiver<T> {
+    /// Receive the next frame, or `None` once the connection is closed and the queue is empty
+    pub async fn recv(&mut self) -> Option<T> {
+        let channel = Arc::clone(&self.channel);
+        loop {
+            let ready = channel.readable.notified();
+            tokio::pin!(ready);
+            ready.as_mut().enable();
+            if let Some(frame) = self.try_recv() {
+                return Some(frame);
+            }
+            if channel.senders.load(Ordering::Acquire) == 0 {
+                return self.try_recv();
+            }
+            ready.await;
+        }
+    }
+
+    /// Receive the next frame, waiting at most `timeout`
+    ///
+    /// Fails with [`RecvTimeoutError::Disconnected`] once the connection is closed
+    /// and the queue is empty.
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match tokio::time::timeout(timeout, self.recv()).await {
+            Ok(Some(frame)) => Ok(frame),
+            Ok(None) => Err(RecvTimeoutError::Disconnected),
+            Err(_) => Err(RecvTimeoutError::Timeout),
+        }
+    }
+
+    /// Take the next frame if one is queued, without waiting
+    ///
+    /// `None` means nothing is queued right now; see [`is_closed`](Self::is_closed)
+    /// to tell whether more can arrive.
+    pub fn try_recv(&mut self) -> Option<T> {
+        let frame = self.channel.queue.lock().pop_front();
+        if frame.is_some() {
+            self.channel.writable.notify_one();
+        }
+        frame
+    }
+
+    /// Whether the connection is closed, so no more frames will be queued
+    pub fn is_closed(&self) -> bool {
+        self.channel.senders.load(Ordering::Acquire) == 0
+    }
+
+    /// Number of frames waiting
+    pub fn len(&self) -> usize {
+        self.channel.queue.lock().len()
+    }
+
+    /// Whether no frames are waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Capacity and policy of this channel
+    pub fn config(&self) -> ChannelConfig {
+        self.channel.config
+    }
+
+    /// Frames discarded so far because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.channel.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<T: Send + 'static> Stream for FrameReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(frame) = this.try_recv() {
+                this.waiting = None;
+                return Poll::Ready(Some(frame));
+            }
+            if this.channel.senders.load(Ordering::Acquire) == 0 {
+                this.waiting = None;
+                return Poll::Ready(this.try_recv());
+            }
+            let channel = Arc::clone(&this.channel);
+            let waiting = this
+                .waiting
+                .get_or_insert_with(|| Box::pin(async move { channel.readable.notified().await }));
+            match waiting.as_mut().poll(cx) {
+                Poll::Ready(()) => this.waiting = None,
+                // Registered now; look again in case a frame or the close came first
+                Poll::Pending
+                    if this.is_empty() && this.channel.senders.load(Ordering::Acquire) > 0 =>
+                {
+                    return Poll::Pending
+                }
+                Poll::Pending => {}
+            }
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for FrameReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameReceiver")
+            .field("len", &self.len())
+            .field("config", &self.channel.config)
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+impl<T> Drop for FrameReceiver<T> {
+    fn drop(&mut self) {
+        self.channel.receiver_alive.store(false, Ordering::Release);
+        drop(self.channel.queue.lock());
+        self.channel.writable.notify_waiters();
+    }
+}
@@ -0,0 +1,140 @@
+// ABOUTME: Protocol client over the browser WebSocket API, for wasm32 builds
+// ABOUTME: Lets web dashboards run controller and metadata roles with the crate's message types
+
+use crate::error::Error;
+use crate::protocol::messages::{ClientHello, Message};
+use crate::protocol::session::Session;
+use crate::protocol::version;
+use futures_util::{SinkExt, StreamExt};
+use gloo_net::websocket::futures::WebSocket;
+use gloo_net::websocket::{Message as WsMessage, WebSocketError};
+
+/// A connection to a Sendspin server from a web page
+///
+/// The browser answers pings itself, and messages are always JSON. Binary
+/// frames (audio, artwork, visualizer) are skipped, so request only roles
+/// that are carried in text messages.
+pub struct BrowserClient {
+    ws: WebSocket,
+    session: Session,
+}
+
+impl BrowserClient {
+    /// Open `url` and complete the hello exchange
+    pub async fn connect(url: &str, hello: ClientHello) -> Result<Self, Error> {
+        let hello_msg = Message::ClientHello(hello.clone());
+        hello_msg.validate()?;
+
+        let mut ws =
+            WebSocket::open(url).map_err(|e| Error::Connection(format!("{}: {}", url, e)))?;
+        ws.send(WsMessage::Text(encode(&hello_msg)?))
+            .await
+            .map_err(ws_error)?;
+
+        let server_hello = match next_message(&mut ws).await? {
+            Some(Message::ServerHello(server_hello)) => server_hello,
+            Some(Message::ServerGoodbye(goodbye)) => {
+                return Err(Error::Connection(format!(
+                    "Server said goodbye: {:?}",
+                    goodbye.reason
+                )))
+            }
+            Some(msg) => {
+                return Err(Error::Protocol(format!(
+                    "Expected server/hello, got {}",
+                    msg.message_type()
+                )))
+            }
+            None => return Err(Error::Connection("No server hello received".to_string())),
+        };
+
+        let version = version::negotiate(hello.version, server_hello.version)?;
+        // Neither is offered from the browser
+        if let Some(params) = &server_hello.payload_encryption {
+            return Err(Error::Protocol(format!(
+                "Server enabled payload encryption ({}) without an offer",
+                params.cipher
+            )));
+        }
+        if let Some(name) = server_hello.encoding.as_deref().filter(|e| *e != "json") {
+            return Err(Error::Protocol(format!(
+                "Server chose message encoding '{}' that was not offered",
+                name
+            )));
+        }
+        log::info!("Connected to server: {}", server_hello.name);
+
+        Ok(Self {
+            ws,
+            session: Session::new(&hello, &server_hello, version),
+        })
+    }
+
+    /// What the hello exchange agreed on
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Send a message to the server
+    pub async fn send(&mut self, msg: &Message) -> Result<(), Error> {
+        self.ws
+            .send(WsMessage::Text(encode(msg)?))
+            .await
+            .map_err(ws_error)
+    }
+
+    /// Wait for the next message; `Ok(None)` once the server has closed
+    pub async fn recv(&mut self) -> Result<Option<Message>, Error> {
+        next_message(&mut self.ws).await
+    }
+
+    /// Close the connection
+    pub fn close(self) -> Result<(), Error> {
+        self.ws
+            .close(None, None)
+            .map_err(|e| Error::WebSocket(e.to_string()))
+    }
+}
+
+impl std::fmt::Debug for BrowserClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BrowserClient")
+            .field("session", &self.session)
+            .finish_non_exhaustive()
+    }
+}
+
+fn encode(msg: &Message) -> Result<String, Error> {
+    serde_json::to_string(msg).map_err(|e| Error::Protocol(e.to_string()))
+}
+
+/// The next text message, skipping binary frames
+async fn next_message(ws: &mut WebSocket) -> Result<Option<Message>, Error> {
+    loop {
+        match ws.next().await {
+            Some(Ok(WsMessage::Text(text))) => {
+                return serde_json::from_str(&text)
+                    .map(Some)
+                    .map_err(|e| Error::Protocol(e.to_string()))
+            }
+            Some(Ok(WsMessage::Bytes(bytes))) => {
+                log::trace!("Skipping {}-byte binary frame", bytes.len());
+            }
+            Some(Err(WebSocketError::ConnectionClose(event))) if event.was_clean => {
+                return Ok(None)
+            }
+            Some(Err(e)) => return Err(ws_error(e)),
+            None => return Ok(None),
+        }
+    }
+}
+
+fn ws_error(e: WebSocketError) -> Error {
+    match e {
+        WebSocketError::ConnectionClose(event) => Error::Connection(format!(
+            "Server closed connection: {} {}",
+            event.code, event.reason
+        )),
+        e => Error::WebSocket(e.to_string()),
+    }
+}
@@ -0,0 +1,281 @@
+// ABOUTME: Builder for configuring and opening a ProtocolClient connection
+// ABOUTME: Hello, initial state, timeouts, keepalive, TLS, proxy, headers, channels, clock sync, coalescing, retries, hooks
+
+use crate::audit::Direction;
+use crate::error::Error;
+use crate::protocol::backpressure::StreamChannels;
+use crate::protocol::client::{ClientConfig, MessageHook, ProtocolClient};
+use crate::protocol::coalesce::Coalescing;
+use crate::protocol::keepalive::Keepalive;
+use crate::protocol::messages::{ClientHello, ClientState, Message};
+use crate::protocol::proxy::ProxyConfig;
+use crate::protocol::tap::{FrameTap, RawFrame};
+use std::time::Duration;
+
+/// How often to retry a failed connection attempt
+///
+/// Only connection-level failures (unreachable server, WebSocket or I/O errors,
+/// timeouts) are retried; a server that rejects the handshake is not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    /// Total connection attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound for the delay, which doubles after every retry
+    pub max_delay: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Try once and fail immediately
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    /// Up to `max_attempts` attempts with exponential backoff from 500ms to 30s
+    pub fn exponential(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+/// Builder returned by [`ProtocolClient::builder`]
+///
+/// ```no_run
+/// # async fn example(hello: sendspin::protocol::messages::ClientHello) -> Result<(), sendspin::Error> {
+/// use sendspin::protocol::client::ProtocolClient;
+/// use sendspin::protocol::ReconnectPolicy;
+/// use std::time::Duration;
+///
+/// let client = ProtocolClient::builder("ws://localhost:8927/sendspin")
+///     .hello(hello)
+///     .connect_timeout(Duration::from_secs(5))
+///     .reconnect(ReconnectPolicy::exponential(5))
+///     .on_message(|direction, msg| log::trace!("{:?} {}", direction, msg.message_type()))
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    url: String,
+    hello: Option<ClientHello>,
+    config: ClientConfig,
+    connect_timeout: Option<Duration>,
+    reconnect: ReconnectPolicy,
+    runtime: Option<tokio::runtime::Handle>,
+}
+
+impl ProtocolClient {
+    /// Configure a connection to `url`; see [`ClientBuilder`]
+    pub fn builder(url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            url: url.into(),
+            hello: None,
+            config: ClientConfig::default(),
+            connect_timeout: None,
+            reconnect: ReconnectPolicy::default(),
+            runtime: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// The client/hello to send (required)
+    pub fn hello(mut self, hello: ClientHello) -> Self {
+        self.hello = Some(hello);
+        self
+    }
+
+    /// Replace all [`ClientConfig`] options at once
+    ///
+    /// Options set before this call are overwritten; set individual options after it.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Give up on an attempt that has not completed the hello exchange within `timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// TLS settings for `wss://` URLs
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: crate::protocol::tls::TlsConfig) -> Self {
+        self.config.tls = Some(tls);
+        self
+    }
+
+    /// Tunnel the connection through an HTTP CONNECT or SOCKS5 proxy
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Send an extra HTTP header with the WebSocket upgrade request
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.request = self.config.request.header(name, value);
+        self
+    }
+
+    /// Authenticate the upgrade request with `Authorization: Bearer <token>`
+    pub fn bearer_token(mut self, token: impl AsRef<str>) -> Self {
+        self.config.request = self.config.request.bearer_token(token);
+        self
+    }
+
+    /// Append a query parameter to the server URL
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.request = self.config.request.query(key, value);
+        self
+    }
+
+    /// Capacity and backpressure policy of the frame receivers
+    pub fn channels(mut self, channels: StreamChannels) -> Self {
+        self.config.channels = channels;
+        self
+    }
+
+    /// Keep the clock in sync by sending `client/time` at `interval`
+    pub fn clock_sync(mut self, interval: Duration) -> Self {
+        self.config.clock_sync_interval = Some(interval);
+        self
+    }
+
+    /// Send this client/state as the last handshake step, before `connect` returns
+    pub fn initial_state(mut self, state: ClientState) -> Self {
+        self.config.initial_state = Some(state);
+        self
+    }
+
+    /// Ping the server and drop the connection when it goes silent
+    pub fn keepalive(mut self, keepalive: Keepalive) -> Self {
+        self.config.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Hold back rapid state updates and volume, mute, or seek commands
+    pub fn coalesce(mut self, coalescing: Coalescing) -> Self {
+        self.config.coalescing = Some(coalescing);
+        self
+    }
+
+    /// Retry failed connection attempts
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Run the connection's background tasks on this tokio runtime
+    ///
+    /// For applications built on another executor (async-std, smol, a GUI event
+    /// loop): keep a multi-threaded tokio runtime alive on the side and pass its
+    /// handle here. `connect` and everything it returns can then be awaited from
+    /// any executor; the socket, timers, and message router stay on the tokio
+    /// runtime. Without this, `connect` must be called inside a tokio runtime.
+    pub fn runtime(mut self, handle: tokio::runtime::Handle) -> Self {
+        self.runtime = Some(handle);
+        self
+    }
+
+    /// Call `hook` with every protocol message sent or received
+    pub fn on_message(
+        mut self,
+        hook: impl Fn(Direction, &Message) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.message_hook = Some(MessageHook::new(hook));
+        self
+    }
+
+    /// Call `tap` with every raw text and binary frame sent or received
+    pub fn on_frame(
+        mut self,
+        tap: impl Fn(Direction, RawFrame<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.frame_tap = Some(FrameTap::new(tap));
+        self
+    }
+
+    /// Connect and complete the hello exchange
+    pub async fn connect(mut self) -> Result<ProtocolClient, Error> {
+        match self.runtime.take() {
+            Some(runtime) => runtime
+                .spawn(self.connect_here())
+                .await
+                .map_err(|e| Error::Connection(format!("Connect task failed: {}", e)))?,
+            None => self.connect_here().await,
+        }
+    }
+
+    /// Connect on the current tokio runtime
+    async fn connect_here(self) -> Result<ProtocolClient, Error> {
+        let hello = self
+            .hello
+            .ok_or_else(|| Error::Protocol("ClientBuilder needs a client/hello".to_string()))?;
+        let attempts = self.reconnect.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let connect =
+                ProtocolClient::connect_with_config(&self.url, hello.clone(), self.config.clone());
+            let result = match self.connect_timeout {
+                Some(limit) => tokio::time::timeout(limit, connect)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(Error::Connection(format!(
+                            "Timed out after {:?} connecting to {}",
+                            limit, self.url
+                        )))
+                    }),
+                None => connect.await,
+            };
+            match result {
+                Err(e) if attempt < attempts && is_retryable(&e) => {
+                    let delay = self.reconnect.delay(attempt);
+                    log::warn!(
+                        "Connection attempt {}/{} failed ({}), retrying in {:?}",
+                        attempt,
+                        attempts,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn is_retryable(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Connection(_) | Error::WebSocket(_) | Error::Io(_)
+    )
+}
@@ -1,41 +1,264 @@
 // ABOUTME: WebSocket client implementation for Sendspin protocol
 // ABOUTME: Handles connection, message routing, and protocol state machine
 
+use crate::audit::Direction;
 use crate::error::Error;
-use crate::protocol::messages::{ClientHello, Message};
-use crate::sync::ClockSync;
-use futures_util::{
-    stream::{SplitSink, SplitStream},
-    SinkExt, StreamExt,
+use crate::events::{ClientEvent, ConnectionStatus, EventBus};
+use crate::protocol::backpressure::{
+    self, DroppedFrames, FrameReceiver, FrameSender, StreamChannels,
 };
-use std::sync::Arc;
-use tokio::net::TcpStream;
+use crate::protocol::coalesce::{Admission, Coalescer, Coalescing, UpdateKind};
+use crate::protocol::compliance::{SpecCompliance, StreamChecks};
+use crate::protocol::encoding::MessageEncoding;
+use crate::protocol::encryption::{PayloadCipher, PayloadKey, PAYLOAD_CIPHER};
+use crate::protocol::keepalive::{self, Keepalive, Liveness};
+use crate::protocol::messages::{
+    ClientCommand, ClientGoodbye, ClientHello, ClientState, ClientTime, ControllerCommand,
+    GoodbyeReason, Message, ServerHello, StreamEnd, StreamVisualizerConfig,
+};
+use crate::protocol::proxy::ProxyConfig;
+use crate::protocol::redact;
+use crate::protocol::request::HandshakeRequest;
+use crate::protocol::role::{Role, RoleList};
+use crate::protocol::session::Session;
+use crate::protocol::stats::{Counter, FrameCounters, FrameStats};
+use crate::protocol::streams::{CurrentStream, StreamTracker};
+use crate::protocol::strict;
+use crate::protocol::tap::{FrameTap, TappedSink};
+use crate::protocol::transport::{FrameStream, Transport, WebSocketTransport};
+use crate::protocol::version::{self, Feature};
+use crate::sync::{unix_micros, ClockSync};
+use futures_util::{Stream, StreamExt};
+use parking_lot::Mutex;
+use std::pin::Pin;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tokio::sync::{oneshot, watch, Notify};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+
+/// Write half of the WebSocket, shared by every sender
+pub(crate) type WsSink = tokio::sync::Mutex<TappedSink>;
 
 /// WebSocket sender wrapper for sending messages
+#[derive(Clone)]
 pub struct WsSender {
-    tx: Arc<tokio::sync::Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, WsMessage>>>,
+    tx: Arc<WsSink>,
+    validate_outgoing: bool,
+    encoding: MessageEncoding,
+    version: u32,
+    hook: Option<MessageHook>,
+    status: watch::Receiver<ConnectionStatus>,
+    coalescer: Option<Arc<Coalescer>>,
 }
 
 impl WsSender {
     /// Send a message to the server
+    ///
+    /// With [`ClientConfig::validate_outgoing`], invalid messages are rejected here.
+    /// With [`ClientConfig::coalescing`], rapid updates may be held back briefly.
     pub async fn send_message(&self, msg: Message) -> Result<(), Error> {
-        let json = serde_json::to_string(&msg).map_err(|e| Error::Protocol(e.to_string()))?;
-        log::debug!("Sending message: {}", json);
+        if self.validate_outgoing {
+            msg.validate()?;
+        }
+        send_outgoing(
+            &self.tx,
+            &msg,
+            self.encoding,
+            self.hook.as_ref(),
+            self.coalescer.as_ref(),
+        )
+        .await
+    }
+
+    /// Ask the server to seek the current track to `position_us` microseconds
+    pub async fn seek(&self, position_us: i64) -> Result<(), Error> {
+        self.send_message(controller_command(ControllerCommand::seek(position_us)))
+            .await
+    }
 
-        let mut tx = self.tx.lock().await;
-        tx.send(WsMessage::Text(json))
+    /// Ask the server to seek the current track by `offset_us` microseconds
+    pub async fn seek_by(&self, offset_us: i64) -> Result<(), Error> {
+        self.send_message(controller_command(ControllerCommand::seek_by(offset_us)))
             .await
-            .map_err(|e| Error::WebSocket(e.to_string()))
+    }
+
+    /// Ask the server to move this client into another group
+    pub async fn join_group(&self, group_id: &str) -> Result<(), Error> {
+        Feature::GroupMembership.require(self.version)?;
+        self.send_message(controller_command(ControllerCommand::join_group(group_id)))
+            .await
+    }
+
+    /// Ask the server to move this client back to its default group
+    pub async fn leave_group(&self) -> Result<(), Error> {
+        Feature::GroupMembership.require(self.version)?;
+        self.send_message(controller_command(ControllerCommand::leave_group()))
+            .await
+    }
+
+    /// Protocol version negotiated with the server
+    pub fn protocol_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Watch whether the connection is still up; see [`ProtocolClient::connection_status`]
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.clone()
+    }
+
+    /// Send client/goodbye and close the connection
+    ///
+    /// Closes the socket for every clone of this sender; see [`ProtocolClient::close`].
+    pub async fn close(&self, goodbye: impl Into<ClientGoodbye>) -> Result<(), Error> {
+        send_goodbye(&self.tx, goodbye.into(), self.encoding, self.hook.as_ref()).await
+    }
+}
+
+/// Send `msg` now, or hold it back under [`ClientConfig::coalescing`]
+async fn send_outgoing(
+    tx: &Arc<WsSink>,
+    msg: &Message,
+    encoding: MessageEncoding,
+    hook: Option<&MessageHook>,
+    coalescer: Option<&Arc<Coalescer>>,
+) -> Result<(), Error> {
+    let Some(coalescer) = coalescer else {
+        return send_now(tx, msg, encoding, hook).await;
+    };
+    match coalescer.admit(msg) {
+        Admission::Send => send_now(tx, msg, encoding, hook).await,
+        Admission::Held => Ok(()),
+        Admission::Flush(kind, deadline) => {
+            tokio::spawn(flush_later(
+                Arc::clone(coalescer),
+                kind,
+                deadline,
+                Arc::downgrade(tx),
+                encoding,
+                hook.cloned(),
+            ));
+            Ok(())
+        }
     }
 }
 
+/// Encode and send one message
+async fn send_now(
+    tx: &WsSink,
+    msg: &Message,
+    encoding: MessageEncoding,
+    hook: Option<&MessageHook>,
+) -> Result<(), Error> {
+    let frame = encode_outgoing(msg, encoding)?;
+    if let Some(hook) = hook {
+        hook.call(Direction::Outbound, msg);
+    }
+
+    let mut tx = tx.lock().await;
+    tx.send(frame)
+        .await
+        .map_err(|e| Error::WebSocket(e.to_string()))
+}
+
+/// Send the latest held update of `kind` once its window ends
+async fn flush_later(
+    coalescer: Arc<Coalescer>,
+    kind: UpdateKind,
+    deadline: tokio::time::Instant,
+    tx: Weak<WsSink>,
+    encoding: MessageEncoding,
+    hook: Option<MessageHook>,
+) {
+    tokio::time::sleep_until(deadline).await;
+    let (Some(msg), Some(tx)) = (coalescer.take(kind), tx.upgrade()) else {
+        return;
+    };
+    if let Err(e) = send_now(&tx, &msg, encoding, hook.as_ref()).await {
+        log::debug!("Could not send held {}: {}", msg.message_type(), e);
+    }
+}
+
+/// Send client/goodbye, then flush and close the write half
+///
+/// A peer that already closed the connection is not an error.
+async fn send_goodbye(
+    tx: &WsSink,
+    goodbye: ClientGoodbye,
+    encoding: MessageEncoding,
+    hook: Option<&MessageHook>,
+) -> Result<(), Error> {
+    say_goodbye(&mut *tx.lock().await, goodbye, encoding, hook).await
+}
+
+/// Send client/goodbye on a write half that is already locked, then close it
+async fn say_goodbye(
+    tx: &mut TappedSink,
+    goodbye: ClientGoodbye,
+    encoding: MessageEncoding,
+    hook: Option<&MessageHook>,
+) -> Result<(), Error> {
+    let msg = Message::ClientGoodbye(goodbye);
+    let frame = encode_outgoing(&msg, encoding)?;
+    if let Some(hook) = hook {
+        hook.call(Direction::Outbound, &msg);
+    }
+
+    let result = match tx.send(frame).await {
+        Ok(()) => tx.close().await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) | Err(WsError::ConnectionClosed) | Err(WsError::AlreadyClosed) => Ok(()),
+        Err(e) => Err(Error::WebSocket(e.to_string())),
+    }
+}
+
+/// The part of the configured initial state that applies to the activated roles
+fn initial_state(state: Option<&ClientState>, session: &Session) -> Option<ClientState> {
+    let player = state?.player.clone().filter(|_| {
+        session
+            .active_roles()
+            .version_of(&Role::Player(1))
+            .is_some()
+    });
+    player.map(|player| ClientState {
+        player: Some(player),
+    })
+}
+
+fn controller_command(command: ControllerCommand) -> Message {
+    Message::ClientCommand(ClientCommand {
+        controller: Some(command),
+    })
+}
+
+/// Encode an outgoing message in the negotiated encoding
+fn encode_outgoing(msg: &Message, encoding: MessageEncoding) -> Result<WsMessage, Error> {
+    if encoding == MessageEncoding::Json {
+        let json = serde_json::to_string(msg).map_err(|e| Error::Protocol(e.to_string()))?;
+        log::debug!("Sending message: {}", redact::for_log(&json));
+        return Ok(WsMessage::Text(json));
+    }
+    let frame = encoding.encode(msg)?;
+    log::debug!(
+        "Sending {} as {} ({} bytes)",
+        msg.message_type(),
+        encoding,
+        frame.len()
+    );
+    Ok(WsMessage::Binary(frame))
+}
+
 /// Binary message type IDs per Sendspin spec
 pub mod binary_types {
-    /// Player audio chunk (types 4-7, we use 4)
+    /// Player audio chunk, stream slot 0 (type 4)
     pub const PLAYER_AUDIO: u8 = 0x04;
+    /// Player audio chunk, last stream slot (type 7)
+    pub const PLAYER_AUDIO_LAST: u8 = 0x07;
     /// Artwork channel 0 (type 8)
     pub const ARTWORK_CHANNEL_0: u8 = 0x08;
     /// Artwork channel 1 (type 9)
@@ -46,6 +269,22 @@ pub mod binary_types {
     pub const ARTWORK_CHANNEL_3: u8 = 0x0B;
     /// Visualizer data (type 16)
     pub const VISUALIZER: u8 = 0x10;
+    /// Protocol message in a negotiated binary encoding (extension, not in the spec)
+    pub const ENCODED_MESSAGE: u8 = 0xF0;
+
+    /// Check if a binary type ID is for player audio (4-7)
+    pub fn is_player_audio(type_id: u8) -> bool {
+        (PLAYER_AUDIO..=PLAYER_AUDIO_LAST).contains(&type_id)
+    }
+
+    /// Get the audio stream slot from type ID (0-3)
+    pub fn audio_slot(type_id: u8) -> Option<u8> {
+        if is_player_audio(type_id) {
+            Some(type_id - PLAYER_AUDIO)
+        } else {
+            None
+        }
+    }
 
     /// Check if a binary type ID is for artwork (8-11)
     pub fn is_artwork(type_id: u8) -> bool {
@@ -60,19 +299,32 @@ pub mod binary_types {
             None
         }
     }
+
+    /// Encode a binary frame: type byte, big-endian timestamp, payload
+    pub fn encode_frame(type_id: u8, timestamp: i64, data: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(9 + data.len());
+        frame.push(type_id);
+        frame.extend_from_slice(&timestamp.to_be_bytes());
+        frame.extend_from_slice(data);
+        frame
+    }
 }
 
-/// Audio chunk from server (binary type 4)
+/// Audio chunk from server (binary types 4-7)
 #[derive(Debug, Clone)]
 pub struct AudioChunk {
+    /// Stream slot (0-3) from the binary type; 0 for ordinary playback
+    pub slot: u8,
     /// Server timestamp in microseconds
     pub timestamp: i64,
     /// Raw audio data bytes
     pub data: Arc<[u8]>,
+    /// When the frame carrying this chunk arrived
+    pub received_at: Instant,
 }
 
 impl AudioChunk {
-    /// Parse from WebSocket binary frame (type 4 = player audio)
+    /// Parse from WebSocket binary frame (types 4-7 = player audio slots 0-3)
     pub fn from_bytes(frame: &[u8]) -> Result<Self, Error> {
         if frame.len() < 9 {
             return Err(Error::Protocol(format!(
@@ -81,14 +333,9 @@ impl AudioChunk {
             )));
         }
 
-        // Per spec: player audio uses binary type 4
-        if frame[0] != binary_types::PLAYER_AUDIO {
-            return Err(Error::Protocol(format!(
-                "Invalid audio chunk type: expected {}, got {}",
-                binary_types::PLAYER_AUDIO,
-                frame[0]
-            )));
-        }
+        let type_id = frame[0];
+        let slot = binary_types::audio_slot(type_id)
+            .ok_or_else(|| Error::Protocol(format!("Invalid audio chunk type: {}", type_id)))?;
 
         let timestamp = i64::from_be_bytes([
             frame[1], frame[2], frame[3], frame[4], frame[5], frame[6], frame[7], frame[8],
@@ -96,7 +343,36 @@ impl AudioChunk {
 
         let data = Arc::from(&frame[9..]);
 
-        Ok(Self { timestamp, data })
+        Ok(Self {
+            slot,
+            timestamp,
+            data,
+            received_at: Instant::now(),
+        })
+    }
+
+    /// Encode a player audio frame for slot 0 without building a chunk first
+    pub fn encode(timestamp: i64, data: &[u8]) -> Vec<u8> {
+        binary_types::encode_frame(binary_types::PLAYER_AUDIO, timestamp, data)
+    }
+
+    /// Encode a player audio frame for stream `slot` (0-3)
+    pub fn encode_slot(slot: u8, timestamp: i64, data: &[u8]) -> Result<Vec<u8>, Error> {
+        if slot > binary_types::PLAYER_AUDIO_LAST - binary_types::PLAYER_AUDIO {
+            return Err(Error::Protocol(format!("Invalid audio slot: {}", slot)));
+        }
+        Ok(binary_types::encode_frame(
+            binary_types::PLAYER_AUDIO + slot,
+            timestamp,
+            data,
+        ))
+    }
+
+    /// Encode as a WebSocket binary frame
+    ///
+    /// Fails only for a slot outside 0-3.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Self::encode_slot(self.slot, self.timestamp, &self.data)
     }
 }
 
@@ -110,6 +386,8 @@ pub struct ArtworkChunk {
     /// Image data bytes (JPEG, PNG, or BMP)
     /// Empty payload means clear the artwork
     pub data: Arc<[u8]>,
+    /// When the frame carrying this chunk arrived
+    pub received_at: Instant,
 }
 
 impl ArtworkChunk {
@@ -123,9 +401,8 @@ impl ArtworkChunk {
         }
 
         let type_id = frame[0];
-        let channel = binary_types::artwork_channel(type_id).ok_or_else(|| {
-            Error::Protocol(format!("Invalid artwork chunk type: {}", type_id))
-        })?;
+        let channel = binary_types::artwork_channel(type_id)
+            .ok_or_else(|| Error::Protocol(format!("Invalid artwork chunk type: {}", type_id)))?;
 
         let timestamp = i64::from_be_bytes([
             frame[1], frame[2], frame[3], frame[4], frame[5], frame[6], frame[7], frame[8],
@@ -137,6 +414,7 @@ impl ArtworkChunk {
             channel,
             timestamp,
             data,
+            received_at: Instant::now(),
         })
     }
 
@@ -144,6 +422,26 @@ impl ArtworkChunk {
     pub fn is_clear(&self) -> bool {
         self.data.is_empty()
     }
+
+    /// Encode an artwork frame for `channel` (0-3) without building a chunk first
+    pub fn encode(channel: u8, timestamp: i64, data: &[u8]) -> Result<Vec<u8>, Error> {
+        if channel > binary_types::ARTWORK_CHANNEL_3 - binary_types::ARTWORK_CHANNEL_0 {
+            return Err(Error::Protocol(format!(
+                "Invalid artwork channel: {}",
+                channel
+            )));
+        }
+        Ok(binary_types::encode_frame(
+            binary_types::ARTWORK_CHANNEL_0 + channel,
+            timestamp,
+            data,
+        ))
+    }
+
+    /// Encode as a WebSocket binary frame
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        Self::encode(self.channel, self.timestamp, &self.data)
+    }
 }
 
 /// Visualizer chunk from server (binary type 16)
@@ -153,6 +451,8 @@ pub struct VisualizerChunk {
     pub timestamp: i64,
     /// FFT/visualization data bytes
     pub data: Arc<[u8]>,
+    /// When the frame carrying this chunk arrived
+    pub received_at: Instant,
 }
 
 impl VisualizerChunk {
@@ -179,14 +479,28 @@ impl VisualizerChunk {
 
         let data = Arc::from(&frame[9..]);
 
-        Ok(Self { timestamp, data })
+        Ok(Self {
+            timestamp,
+            data,
+            received_at: Instant::now(),
+        })
+    }
+
+    /// Encode a visualizer frame without building a chunk first
+    pub fn encode(timestamp: i64, data: &[u8]) -> Vec<u8> {
+        binary_types::encode_frame(binary_types::VISUALIZER, timestamp, data)
+    }
+
+    /// Encode as a WebSocket binary frame
+    pub fn to_bytes(&self) -> Vec<u8> {
+        Self::encode(self.timestamp, &self.data)
     }
 }
 
 /// Binary frame from server (any type)
 #[derive(Debug, Clone)]
 pub enum BinaryFrame {
-    /// Player audio (type 4)
+    /// Player audio (types 4-7)
     Audio(AudioChunk),
     /// Artwork image (types 8-11)
     Artwork(ArtworkChunk),
@@ -211,7 +525,9 @@ impl BinaryFrame {
         let type_id = frame[0];
 
         match type_id {
-            binary_types::PLAYER_AUDIO => Ok(BinaryFrame::Audio(AudioChunk::from_bytes(frame)?)),
+            t if binary_types::is_player_audio(t) => {
+                Ok(BinaryFrame::Audio(AudioChunk::from_bytes(frame)?))
+            }
             t if binary_types::is_artwork(t) => {
                 Ok(BinaryFrame::Artwork(ArtworkChunk::from_bytes(frame)?))
             }
@@ -227,35 +543,323 @@ impl BinaryFrame {
             }
         }
     }
+
+    /// Encode as a WebSocket binary frame
+    ///
+    /// Fails only for audio slots or artwork channels outside 0-3.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        match self {
+            BinaryFrame::Audio(chunk) => chunk.to_bytes(),
+            BinaryFrame::Artwork(chunk) => chunk.to_bytes(),
+            BinaryFrame::Visualizer(chunk) => Ok(chunk.to_bytes()),
+            BinaryFrame::Unknown { type_id, data } => {
+                let mut frame = Vec::with_capacity(1 + data.len());
+                frame.push(*type_id);
+                frame.extend_from_slice(data);
+                Ok(frame)
+            }
+        }
+    }
+}
+
+/// Senders for audio slots 1-3, installed by [`ProtocolClient::audio_slot`]
+type AudioSlotSenders = Arc<Mutex<[Option<FrameSender<AudioChunk>>; 3]>>;
+
+/// Callback that sees every protocol message sent or received, see [`ClientConfig::message_hook`]
+#[derive(Clone)]
+pub struct MessageHook(Arc<HookFn>);
+
+type HookFn = dyn Fn(Direction, &Message) + Send + Sync;
+
+impl MessageHook {
+    /// Wrap a callback
+    pub fn new(hook: impl Fn(Direction, &Message) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    fn call(&self, direction: Direction, msg: &Message) {
+        (self.0)(direction, msg)
+    }
+}
+
+impl std::fmt::Debug for MessageHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MessageHook")
+    }
+}
+
+/// Connection options for [`ProtocolClient`]
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// How strictly the server is held to the spec
+    pub compliance: SpecCompliance,
+    /// Check outgoing messages with [`Message::validate`] and refuse to send invalid ones
+    pub validate_outgoing: bool,
+    /// Offer payload encryption with this shared key
+    ///
+    /// Binary frames are decrypted transparently if the server accepts; otherwise
+    /// the connection continues unencrypted.
+    pub payload_key: Option<PayloadKey>,
+    /// Offer these binary message encodings instead of JSON, most preferred first
+    ///
+    /// Only encodings compiled in (`msgpack`, `cbor` features) are offered. JSON
+    /// stays in use if the server picks none.
+    pub encodings: Vec<MessageEncoding>,
+    /// Report JSON messages with fields this crate does not know
+    ///
+    /// Unknown fields in server/hello fail the connection with [`Error::SpecDrift`].
+    /// Later messages are treated as spec violations under [`compliance`](Self::compliance).
+    /// Off by default, since unknown fields are normally ignored.
+    pub strict_fields: bool,
+    /// TLS settings for `wss://` URLs (system defaults if unset)
+    #[cfg(feature = "tls")]
+    pub tls: Option<crate::protocol::tls::TlsConfig>,
+    /// Reach the server through an HTTP CONNECT or SOCKS5 proxy
+    ///
+    /// The WebSocket and TLS handshakes run end to end through the tunnel.
+    pub proxy: Option<ProxyConfig>,
+    /// Extra headers and query parameters for the WebSocket upgrade request
+    ///
+    /// For servers behind an authenticating reverse proxy. A 4xx answer to the
+    /// upgrade fails with [`Error::Protocol`] rather than a retryable connection error.
+    pub request: HandshakeRequest,
+    /// Capacity and backpressure policy of the audio, artwork, and visualizer receivers
+    ///
+    /// Control messages are never dropped and are not affected.
+    pub channels: StreamChannels,
+    /// Called with every JSON or encoded protocol message, in both directions
+    ///
+    /// Runs on the connection's tasks, so it should return quickly. Binary audio,
+    /// artwork, and visualizer frames are not passed to it.
+    pub message_hook: Option<MessageHook>,
+    /// Called with every text and binary WebSocket frame, in both directions
+    ///
+    /// Sees the frames as they are on the wire: before parsing and decryption
+    /// on the way in, after encoding and encryption on the way out. Runs on the
+    /// connection's tasks, so it should return quickly.
+    pub frame_tap: Option<FrameTap>,
+    /// Send `client/time` right after connecting and then at this interval
+    ///
+    /// Replies are applied to [`ProtocolClient::clock_sync`] by the connection
+    /// itself, whether or not this is set.
+    pub clock_sync_interval: Option<Duration>,
+    /// Complete the handshake by sending this client/state before `connect` returns
+    ///
+    /// The player state is only sent if the server activated a player role; see
+    /// [`Session::state_sent`].
+    pub initial_state: Option<ClientState>,
+    /// Ping the server and drop the connection when it goes silent
+    ///
+    /// The connection then reports [`ConnectionStatus::Lost`] and its receivers
+    /// close; reconnect with [`ProtocolClient::builder`], or let a
+    /// [`ServerPool`](crate::protocol::ServerPool) fail over. Off by default.
+    pub keepalive: Option<Keepalive>,
+    /// Hold back rapid client/state updates and volume, mute, or seek commands
+    ///
+    /// Off by default: every message is sent as soon as it is passed in.
+    pub coalescing: Option<Coalescing>,
+}
+
+/// A protocol message and when the frame carrying it arrived
+#[derive(Debug, Clone)]
+pub struct ReceivedMessage {
+    /// The parsed message
+    pub message: Message,
+    /// Local arrival time of the frame, before any queueing in the client
+    pub received_at: Instant,
+}
+
+impl ReceivedMessage {
+    fn now(message: Message) -> Self {
+        Self {
+            message,
+            received_at: Instant::now(),
+        }
+    }
+}
+
+/// Protocol messages from the server, in order
+///
+/// Also a [`Stream`], for use with `StreamExt` combinators.
+#[derive(Debug)]
+pub struct MessageReceiver(UnboundedReceiver<ReceivedMessage>);
+
+impl MessageReceiver {
+    /// Receive the next message, or `None` once the connection is closed
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.0.recv().await.map(|received| received.message)
+    }
+
+    /// Receive the next message with its arrival time
+    pub async fn recv_stamped(&mut self) -> Option<ReceivedMessage> {
+        self.0.recv().await
+    }
+
+    /// Take the next message if one is queued, without waiting
+    pub fn try_recv(&mut self) -> Option<Message> {
+        self.0.try_recv().ok().map(|received| received.message)
+    }
+
+    /// Receive the next message, waiting at most `timeout`
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<Message, RecvTimeoutError> {
+        recv_message_timeout(&mut self.0, timeout)
+            .await
+            .map(|received| received.message)
+    }
+
+    /// Whether the connection is closed, so no more messages will be queued
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Plain message channel for the deprecated split APIs
+    fn into_unstamped(mut self) -> UnboundedReceiver<Message> {
+        let (tx, rx) = unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(received) = self.0.recv().await {
+                if tx.send(received.message).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+async fn recv_message_timeout(
+    rx: &mut UnboundedReceiver<ReceivedMessage>,
+    timeout: Duration,
+) -> Result<ReceivedMessage, RecvTimeoutError> {
+    match tokio::time::timeout(timeout, rx.recv()).await {
+        Ok(Some(msg)) => Ok(msg),
+        Ok(None) => Err(RecvTimeoutError::Disconnected),
+        Err(_) => Err(RecvTimeoutError::Timeout),
+    }
+}
+
+impl Stream for MessageReceiver {
+    type Item = Message;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Message>> {
+        self.get_mut()
+            .0
+            .poll_recv(cx)
+            .map(|received| received.map(|received| received.message))
+    }
+}
+
+/// The parts of a [`ProtocolClient`], from [`ProtocolClient::into_parts`]
+pub struct ClientParts {
+    /// Protocol messages from the server, in order
+    pub messages: MessageReceiver,
+    /// Audio chunks of the primary stream (slot 0)
+    pub audio: FrameReceiver<AudioChunk>,
+    /// Artwork images
+    pub artwork: FrameReceiver<ArtworkChunk>,
+    /// Visualizer frames
+    pub visualizer: FrameReceiver<VisualizerChunk>,
+    /// Clock synchronization, kept up to date by the connection
+    pub clock: Arc<tokio::sync::Mutex<ClockSync>>,
+    /// Handle for sending messages and closing the connection
+    pub sender: WsSender,
 }
 
 /// WebSocket client for Sendspin protocol
 pub struct ProtocolClient {
-    ws_tx:
-        Arc<tokio::sync::Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, WsMessage>>>,
-    audio_rx: UnboundedReceiver<AudioChunk>,
-    artwork_rx: UnboundedReceiver<ArtworkChunk>,
-    visualizer_rx: UnboundedReceiver<VisualizerChunk>,
-    message_rx: UnboundedReceiver<Message>,
+    ws_tx: Arc<WsSink>,
+    audio_rx: FrameReceiver<AudioChunk>,
+    audio_slots: AudioSlotSenders,
+    artwork_rx: FrameReceiver<ArtworkChunk>,
+    visualizer_rx: FrameReceiver<VisualizerChunk>,
+    message_rx: UnboundedReceiver<ReceivedMessage>,
+    channels: StreamChannels,
     clock_sync: Arc<tokio::sync::Mutex<ClockSync>>,
+    streams: StreamTracker,
+    counters: Arc<FrameCounters>,
+    events: EventBus,
+    server_hello: ServerHello,
+    validate_outgoing: bool,
+    encoding: MessageEncoding,
+    version: u32,
+    hook: Option<MessageHook>,
+    session: Session,
+    sessions: watch::Receiver<Session>,
+    status: watch::Receiver<ConnectionStatus>,
+    switch: UnboundedSender<SwitchRequest>,
+    coalescer: Option<Arc<Coalescer>>,
 }
 
 impl ProtocolClient {
     /// Connect to Sendspin server
     pub async fn connect(url: &str, hello: ClientHello) -> Result<Self, Error> {
-        // Connect WebSocket
-        let (ws_stream, _) = connect_async(url)
-            .await
-            .map_err(|e| Error::Connection(e.to_string()))?;
+        Self::connect_with_config(url, hello, ClientConfig::default()).await
+    }
+
+    /// Connect to Sendspin server with explicit options
+    ///
+    /// Fails with [`Error::UnsupportedVersion`] when the server's protocol version
+    /// cannot be reconciled with `hello.version`.
+    pub async fn connect_with_config(
+        url: &str,
+        hello: ClientHello,
+        config: ClientConfig,
+    ) -> Result<Self, Error> {
+        Self::connect_with_transport(WebSocketTransport::new(url), hello, config).await
+    }
+
+    /// Connect over another [`Transport`], such as a Unix socket or an in-memory pipe
+    ///
+    /// [`ClientConfig::proxy`] and `ClientConfig::tls` only apply to URL transports.
+    pub async fn connect_with_transport(
+        transport: impl Transport,
+        hello: ClientHello,
+        config: ClientConfig,
+    ) -> Result<Self, Error> {
+        let handshake = Self::handshake(transport, hello, &config).await?;
+        let (client, link) = Self::start(handshake, config);
+        tokio::spawn(link.close_when_ended());
+        Ok(client)
+    }
 
-        let (mut write, read) = ws_stream.split();
+    /// Open a connection over `transport`, complete the hello exchange, and
+    /// send the configured initial state
+    pub(crate) async fn handshake(
+        transport: impl Transport,
+        mut hello: ClientHello,
+        config: &ClientConfig,
+    ) -> Result<Handshake, Error> {
+        let compliance = config.compliance;
+        if config.payload_key.is_some()
+            && !hello.payload_encryption.iter().any(|c| c == PAYLOAD_CIPHER)
+        {
+            hello.payload_encryption.push(PAYLOAD_CIPHER.to_string());
+        }
+        for encoding in config.encodings.iter().filter(|e| e.is_available()) {
+            if *encoding != MessageEncoding::Json
+                && !hello.encodings.contains(&encoding.name().to_string())
+            {
+                hello.encodings.push(encoding.name().to_string());
+            }
+        }
+        let checks = StreamChecks::new(compliance, &hello);
+
+        // Refuse an invalid client hello before connecting
+        let hello_msg = Message::ClientHello(hello.clone());
+        if config.validate_outgoing {
+            hello_msg.validate()?;
+        }
+
+        let (write, read) = transport.open(config).await?.into_split();
+        let mut write = TappedSink::new(write, config.frame_tap.clone());
 
         // Send client hello
-        let hello_msg = Message::ClientHello(hello);
         let hello_json =
             serde_json::to_string(&hello_msg).map_err(|e| Error::Protocol(e.to_string()))?;
 
-        log::debug!("Sending client/hello: {}", hello_json);
+        log::debug!("Sending client/hello: {}", redact::for_log(&hello_json));
+        if let Some(hook) = &config.message_hook {
+            hook.call(Direction::Outbound, &hello_msg);
+        }
 
         write
             .send(WsMessage::Text(hello_json))
@@ -266,27 +870,46 @@ impl ProtocolClient {
         let mut read_temp = read;
         log::debug!("Waiting for server/hello...");
 
-        loop {
+        let server_hello = loop {
             if let Some(result) = read_temp.next().await {
+                if let (Some(tap), Ok(frame)) = (&config.frame_tap, &result) {
+                    tap.call(Direction::Inbound, frame);
+                }
                 match result {
                     Ok(WsMessage::Text(text)) => {
-                        log::debug!("Received text message: {}", text);
+                        log::debug!("Received text message: {}", redact::for_log(&text));
                         let msg: Message = serde_json::from_str(&text).map_err(|e| {
                             log::error!("Failed to parse server message: {}", e);
                             Error::Protocol(e.to_string())
                         })?;
 
+                        if config.strict_fields {
+                            strict::check(&text, &msg)?;
+                        }
+                        if let Some(hook) = &config.message_hook {
+                            hook.call(Direction::Inbound, &msg);
+                        }
                         match msg {
                             Message::ServerHello(server_hello) => {
                                 log::info!(
                                     "Connected to server: {} ({})",
-                                    server_hello.name,
-                                    server_hello.server_id
+                                    redact::field_for_log("name", &server_hello.name),
+                                    redact::field_for_log("server_id", &server_hello.server_id)
                                 );
-                                break; // Exit loop, we got the server/hello
+                                break server_hello; // Exit loop, we got the server/hello
+                            }
+                            Message::ServerGoodbye(goodbye) => {
+                                log::info!(
+                                    "Server said goodbye during handshake: {:?}",
+                                    goodbye.reason
+                                );
+                                return Err(Error::Connection(format!(
+                                    "Server said goodbye: {:?}",
+                                    goodbye.reason
+                                )));
                             }
                             _ => {
-                                log::error!("Expected server/hello, got: {:?}", msg);
+                                log::error!("Expected server/hello, got: {}", msg.message_type());
                                 return Err(Error::Protocol("Expected server/hello".to_string()));
                             }
                         }
@@ -296,12 +919,21 @@ impl ProtocolClient {
                         log::debug!("Received Ping/Pong, continuing to wait for server/hello");
                         continue;
                     }
-                    Ok(WsMessage::Close(_)) => {
-                        log::error!("Server closed connection");
-                        return Err(Error::Connection("Server closed connection".to_string()));
+                    Ok(WsMessage::Close(frame)) => {
+                        let reason = match frame {
+                            Some(frame) if !frame.reason.is_empty() => {
+                                format!("Server closed connection: {}", frame.reason)
+                            }
+                            _ => "Server closed connection".to_string(),
+                        };
+                        log::error!("{}", reason);
+                        return Err(Error::Connection(reason));
                     }
                     Ok(other) => {
-                        log::warn!("Unexpected message type while waiting for hello: {:?}", other);
+                        log::warn!(
+                            "Unexpected message type while waiting for hello: {:?}",
+                            other
+                        );
                         continue;
                     }
                     Err(e) => {
@@ -313,60 +945,441 @@ impl ProtocolClient {
                 log::error!("Connection closed before receiving server/hello");
                 return Err(Error::Connection("No server hello received".to_string()));
             }
+        };
+
+        let version = version::negotiate(hello.version, server_hello.version)?;
+        if version != hello.version {
+            log::info!(
+                "Using protocol version {} (offered {}, server speaks {})",
+                version,
+                hello.version,
+                server_hello.version
+            );
+        }
+        compliance.check_roles(&hello, &server_hello)?;
+        if server_hello.payload_encryption.is_some() {
+            Feature::PayloadEncryption.require(version)?;
+        }
+        if server_hello.encoding.is_some() {
+            Feature::MessageEncodings.require(version)?;
         }
 
+        let cipher = match (&config.payload_key, &server_hello.payload_encryption) {
+            (Some(key), Some(params)) => Some(PayloadCipher::accept(key, params)?),
+            (None, Some(params)) => {
+                return Err(Error::Protocol(format!(
+                    "Server enabled payload encryption ({}) without an offer",
+                    params.cipher
+                )))
+            }
+            (Some(_), None) => {
+                log::warn!("Server declined payload encryption; binary frames are unencrypted");
+                None
+            }
+            (None, None) => None,
+        };
+
+        let encoding = match server_hello.encoding.as_deref() {
+            None => MessageEncoding::Json,
+            Some(name) => MessageEncoding::from_name(name)
+                .filter(|e| {
+                    *e == MessageEncoding::Json || hello.encodings.iter().any(|o| o == name)
+                })
+                .ok_or_else(|| {
+                    Error::Protocol(format!(
+                        "Server chose message encoding '{}' that was not offered",
+                        name
+                    ))
+                })?,
+        };
+        if encoding != MessageEncoding::Json {
+            log::info!("Using {} message encoding", encoding);
+        }
+
+        let mut session = Session::new(&hello, &server_hello, version);
+        if let Some(state) = initial_state(config.initial_state.as_ref(), &session) {
+            let msg = Message::ClientState(state);
+            if config.validate_outgoing {
+                msg.validate()?;
+            }
+            if let Some(hook) = &config.message_hook {
+                hook.call(Direction::Outbound, &msg);
+            }
+            write
+                .send(encode_outgoing(&msg, encoding)?)
+                .await
+                .map_err(|e| Error::WebSocket(e.to_string()))?;
+            session.mark_state_sent();
+        }
+
+        Ok(Handshake {
+            hello,
+            write,
+            route: Route {
+                read: read_temp,
+                checks,
+                cipher,
+                encoding,
+            },
+            server_hello,
+            session,
+            version,
+        })
+    }
+
+    /// Start routing a completed handshake into a new set of receivers
+    pub(crate) fn start(handshake: Handshake, config: ClientConfig) -> (Self, Link) {
+        let Handshake {
+            hello,
+            write,
+            route,
+            server_hello,
+            session,
+            version,
+        } = handshake;
+        let encoding = route.encoding;
+
         // Create channels for message routing
-        let (audio_tx, audio_rx) = unbounded_channel();
-        let (artwork_tx, artwork_rx) = unbounded_channel();
-        let (visualizer_tx, visualizer_rx) = unbounded_channel();
+        let channels = config.channels;
+        let (audio_tx, audio_rx) = backpressure::channel(channels.audio);
+        let audio_slots = AudioSlotSenders::default();
+        let (artwork_tx, artwork_rx) = backpressure::channel(channels.artwork);
+        let (visualizer_tx, visualizer_rx) = backpressure::channel(channels.visualizer);
         let (message_tx, message_rx) = unbounded_channel();
 
         let clock_sync = Arc::new(tokio::sync::Mutex::new(ClockSync::new()));
+        let streams = StreamTracker::new();
+        let counters = Arc::new(FrameCounters::default());
+        let events = EventBus::new();
+        let outputs = Outputs {
+            audio_tx,
+            audio_slots: Arc::clone(&audio_slots),
+            artwork_tx,
+            visualizer_tx,
+            message_tx,
+            clock_sync: Arc::clone(&clock_sync),
+            streams: streams.clone(),
+            counters: Arc::clone(&counters),
+            events: events.clone(),
+        };
 
-        // Spawn message router task
-        let clock_sync_clone = Arc::clone(&clock_sync);
-        tokio::spawn(async move {
-            Self::message_router(
-                read_temp,
-                audio_tx,
-                artwork_tx,
-                visualizer_tx,
-                message_tx,
-                clock_sync_clone,
-            )
-            .await;
-        });
+        let (status_tx, status) = watch::channel(ConnectionStatus::Connected);
+        let status_tx = Arc::new(status_tx);
+        tokio::spawn(Self::publish_status(status.clone(), events.clone()));
+        let (session_tx, sessions) = watch::channel(session.clone());
+        let ws_tx = Arc::new(tokio::sync::Mutex::new(write));
+        let router = Self::route(route, outputs, &ws_tx, &config, &status_tx);
+        let (switch, requests) = unbounded_channel();
 
-        Ok(Self {
-            ws_tx: Arc::new(tokio::sync::Mutex::new(write)),
+        let client = Self {
+            ws_tx: Arc::clone(&ws_tx),
             audio_rx,
+            audio_slots,
             artwork_rx,
             visualizer_rx,
             message_rx,
+            channels,
             clock_sync,
-        })
+            streams,
+            counters,
+            events,
+            server_hello,
+            validate_outgoing: config.validate_outgoing,
+            encoding,
+            version,
+            hook: config.message_hook.clone(),
+            session,
+            sessions,
+            status,
+            switch,
+            coalescer: config.coalescing.map(|c| Arc::new(Coalescer::new(c))),
+        };
+        let link = Link {
+            router,
+            ws_tx: Arc::downgrade(&ws_tx),
+            status: status_tx,
+            session: session_tx,
+            requests,
+            hello,
+            config,
+            encoding,
+            version,
+        };
+        (client, link)
     }
 
-    async fn message_router(
-        mut read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
-        audio_tx: UnboundedSender<AudioChunk>,
-        artwork_tx: UnboundedSender<ArtworkChunk>,
-        visualizer_tx: UnboundedSender<VisualizerChunk>,
-        message_tx: UnboundedSender<Message>,
-        _clock_sync: Arc<tokio::sync::Mutex<ClockSync>>,
+    /// Publish every connection status change on `events`, until the status is dropped
+    async fn publish_status(mut status: watch::Receiver<ConnectionStatus>, events: EventBus) {
+        while status.changed().await.is_ok() {
+            let current = *status.borrow_and_update();
+            events.publish(ClientEvent::ConnectionChanged(current));
+        }
+    }
+
+    /// Spawn the message router for a connection, with its clock sync and keepalive tasks
+    fn route(
+        route: Route,
+        outputs: Outputs,
+        ws_tx: &Arc<WsSink>,
+        config: &ClientConfig,
+        status: &Arc<watch::Sender<ConnectionStatus>>,
+    ) -> Router {
+        let encoding = route.encoding;
+        let stop = Arc::new(Notify::new());
+        let liveness = Arc::new(Liveness::new());
+        let router = tokio::spawn(Self::message_router(
+            route,
+            outputs,
+            config.compliance,
+            config.strict_fields,
+            config.message_hook.clone(),
+            Arc::clone(&liveness),
+            Arc::clone(&stop),
+            config.frame_tap.clone(),
+        ));
+        if let Some(interval) = config.clock_sync_interval {
+            tokio::spawn(Self::clock_sync_task(
+                Arc::downgrade(ws_tx),
+                router.abort_handle(),
+                interval,
+                encoding,
+                config.message_hook.clone(),
+            ));
+        }
+        if let Some(settings) = config.keepalive {
+            tokio::spawn(keepalive::watchdog(
+                settings,
+                Arc::downgrade(ws_tx),
+                liveness,
+                router.abort_handle(),
+                Arc::clone(&stop),
+                Arc::clone(status),
+            ));
+        }
+        Router { task: router, stop }
+    }
+
+    /// Send `client/time` every `interval` until the connection ends or every sender is gone
+    async fn clock_sync_task(
+        ws_tx: Weak<WsSink>,
+        router: AbortHandle,
+        interval: Duration,
+        encoding: MessageEncoding,
+        hook: Option<MessageHook>,
     ) {
-        while let Some(msg) = read.next().await {
-            match msg {
-                Ok(WsMessage::Binary(data)) => {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            if router.is_finished() {
+                break;
+            }
+            let Some(ws_tx) = ws_tx.upgrade() else {
+                break;
+            };
+            let msg = Message::ClientTime(ClientTime {
+                client_transmitted: unix_micros(),
+            });
+            if let Some(hook) = &hook {
+                hook.call(Direction::Outbound, &msg);
+            }
+            let sent = match encode_outgoing(&msg, encoding) {
+                Ok(frame) => ws_tx.lock().await.send(frame).await.is_ok(),
+                Err(_) => false,
+            };
+            if !sent {
+                log::debug!("Stopping clock sync: connection closed");
+                break;
+            }
+        }
+    }
+
+    /// Deliver the connection's frames into `outputs` until it ends or `stop` is notified
+    ///
+    /// Hands `outputs` back, so a replacement connection can feed the same receivers.
+    #[allow(clippy::too_many_arguments)]
+    async fn message_router(
+        route: Route,
+        outputs: Outputs,
+        compliance: SpecCompliance,
+        strict_fields: bool,
+        hook: Option<MessageHook>,
+        liveness: Arc<Liveness>,
+        stop: Arc<Notify>,
+        tap: Option<FrameTap>,
+    ) -> Outputs {
+        let Route {
+            mut read,
+            mut checks,
+            mut cipher,
+            encoding,
+        } = route;
+        let Outputs {
+            audio_tx,
+            audio_slots,
+            artwork_tx,
+            visualizer_tx,
+            message_tx,
+            clock_sync,
+            streams,
+            counters,
+            events,
+        } = &outputs;
+        // Published when it changes; a new connection starts without sync
+        let mut quality = None;
+        loop {
+            let msg = tokio::select! {
+                msg = read.next() => msg,
+                _ = stop.notified() => None,
+            };
+            let Some(msg) = msg else {
+                break;
+            };
+            // t4 of clock sync: as close to the arrival of the frame as possible
+            let received = unix_micros();
+            let arrived = Instant::now();
+            liveness.touch();
+            if let (Some(tap), Ok(frame)) = (&tap, &msg) {
+                tap.call(Direction::Inbound, frame);
+            }
+            // Messages in a negotiated binary encoding take the same path as JSON text
+            let parsed = match &msg {
+                Ok(WsMessage::Text(text)) => {
+                    log::debug!("Received text message: {}", redact::for_log(text));
+                    Some(serde_json::from_str::<Message>(text).map_err(|e| e.to_string()))
+                }
+                Ok(WsMessage::Binary(data))
+                    if encoding != MessageEncoding::Json
+                        && data.first() == Some(&binary_types::ENCODED_MESSAGE) =>
+                {
+                    Some(encoding.decode(data).map_err(|e| e.to_string()))
+                }
+                _ => None,
+            };
+            if let (Some(hook), Some(Ok(msg))) = (&hook, &parsed) {
+                hook.call(Direction::Inbound, msg);
+            }
+            // Spec violations are fatal in strict mode
+            let checked = match (parsed, msg) {
+                (Some(parsed), raw) => match parsed {
+                    Ok(Message::ServerGoodbye(goodbye)) => {
+                        counters.count(Counter::Message);
+                        // Deliver the goodbye, then end the connection cleanly
+                        log::info!("Server said goodbye: {:?}", goodbye.reason);
+                        let _ = message_tx.send(ReceivedMessage {
+                            message: Message::ServerGoodbye(goodbye),
+                            received_at: arrived,
+                        });
+                        break;
+                    }
+                    Ok(msg) => {
+                        log::debug!("Parsed message: {}", msg.message_type());
+                        counters.count(Counter::Message);
+                        let drift = match &raw {
+                            Ok(WsMessage::Text(text)) if strict_fields => strict::check(text, &msg)
+                                .or_else(|e| compliance.violation(e.to_string())),
+                            _ => Ok(()),
+                        };
+                        if let (Ok(()), Message::ServerTime(time)) = (&drift, &msg) {
+                            let mut sync = clock_sync.lock().await;
+                            sync.update(
+                                time.client_transmitted,
+                                time.server_received,
+                                time.server_transmitted,
+                                received,
+                            );
+                            if quality != Some(sync.quality()) {
+                                quality = Some(sync.quality());
+                                events.publish(ClientEvent::SyncQualityChanged(sync.quality()));
+                            }
+                        }
+                        if drift.is_ok() {
+                            streams.apply(&msg);
+                            checks.observe_message(&msg);
+                            for event in ClientEvent::from_message(&msg) {
+                                events.publish(event);
+                            }
+                            let _ = message_tx.send(ReceivedMessage {
+                                message: msg,
+                                received_at: arrived,
+                            });
+                        }
+                        drift
+                    }
+                    Err(e) => {
+                        counters.count(Counter::ParseFailure);
+                        compliance.violation(format!("failed to parse message: {}", e))
+                    }
+                },
+                (None, Ok(WsMessage::Binary(data))) => {
                     log::debug!("Received binary frame ({} bytes)", data.len());
-                    match BinaryFrame::from_bytes(&data) {
+                    // A frame that fails to authenticate also desyncs the nonce counter
+                    let frame = match cipher.as_mut().map(|c| c.open(&data)).transpose() {
+                        Ok(plain) => plain.unwrap_or(data),
+                        Err(e) => {
+                            counters.count(Counter::ParseFailure);
+                            log::error!("Closing connection: {}", e);
+                            break;
+                        }
+                    };
+                    let mut parsed = BinaryFrame::from_bytes(&frame);
+                    match &mut parsed {
+                        Ok(BinaryFrame::Audio(chunk)) => {
+                            counters.count(Counter::Audio);
+                            chunk.received_at = arrived;
+                        }
+                        Ok(BinaryFrame::Artwork(chunk)) => {
+                            counters.count(Counter::Artwork);
+                            chunk.received_at = arrived;
+                        }
+                        Ok(BinaryFrame::Visualizer(chunk)) => {
+                            counters.count(Counter::Visualizer);
+                            chunk.received_at = arrived;
+                        }
+                        Ok(BinaryFrame::Unknown { .. }) => counters.count(Counter::UnknownBinary),
+                        Err(_) => counters.count(Counter::ParseFailure),
+                    }
+                    match parsed {
+                        Ok(BinaryFrame::Audio(chunk)) if chunk.slot > 0 => {
+                            log::debug!(
+                                "Parsed audio chunk: slot={}, timestamp={}, data_len={}",
+                                chunk.slot,
+                                chunk.timestamp,
+                                chunk.data.len()
+                            );
+                            // Extra streams have their own timelines and are not
+                            // counted against the primary buffer
+                            let tx = audio_slots.lock()[chunk.slot as usize - 1].clone();
+                            match tx {
+                                Some(tx) => {
+                                    tx.send(chunk).await;
+                                }
+                                None => {
+                                    counters.count(Counter::UnroutedAudio);
+                                    log::debug!(
+                                        "Dropping audio chunk for unsubscribed slot {}",
+                                        chunk.slot
+                                    )
+                                }
+                            }
+                            Ok(())
+                        }
                         Ok(BinaryFrame::Audio(chunk)) => {
                             log::debug!(
                                 "Parsed audio chunk: timestamp={}, data_len={}",
                                 chunk.timestamp,
                                 chunk.data.len()
                             );
-                            let _ = audio_tx.send(chunk);
+                            let play_at = if checks.tracks_buffer() {
+                                clock_sync
+                                    .lock()
+                                    .await
+                                    .server_to_local_instant(chunk.timestamp)
+                            } else {
+                                None
+                            };
+                            let checked = checks.check_audio(&chunk, play_at);
+                            audio_tx.send(chunk).await;
+                            checked
                         }
                         Ok(BinaryFrame::Artwork(chunk)) => {
                             log::debug!(
@@ -375,7 +1388,8 @@ impl ProtocolClient {
                                 chunk.timestamp,
                                 chunk.data.len()
                             );
-                            let _ = artwork_tx.send(chunk);
+                            artwork_tx.send(chunk).await;
+                            Ok(())
                         }
                         Ok(BinaryFrame::Visualizer(chunk)) => {
                             log::debug!(
@@ -383,120 +1397,665 @@ impl ProtocolClient {
                                 chunk.timestamp,
                                 chunk.data.len()
                             );
-                            let _ = visualizer_tx.send(chunk);
+                            visualizer_tx.send(chunk).await;
+                            Ok(())
                         }
                         Ok(BinaryFrame::Unknown { type_id, .. }) => {
-                            log::warn!("Received unknown binary type: {}", type_id);
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to parse binary frame: {}", e);
+                            compliance.violation(format!("unknown binary type {}", type_id))
                         }
+                        Err(e) => compliance.violation(format!("malformed binary frame: {}", e)),
                     }
                 }
-                Ok(WsMessage::Text(text)) => {
-                    log::debug!("Received text message: {}", text);
-                    match serde_json::from_str::<Message>(&text) {
-                        Ok(msg) => {
-                            log::debug!("Parsed message: {:?}", msg);
-                            let _ = message_tx.send(msg);
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to parse message: {}", e);
-                        }
-                    }
-                }
-                Ok(WsMessage::Ping(_)) | Ok(WsMessage::Pong(_)) => {
+                (None, Ok(WsMessage::Ping(_))) | (None, Ok(WsMessage::Pong(_))) => {
                     // Handled automatically by tokio-tungstenite
+                    counters.count(Counter::Control);
+                    Ok(())
                 }
-                Ok(WsMessage::Close(_)) => {
+                (None, Ok(WsMessage::Close(_))) => {
+                    counters.count(Counter::Control);
                     log::info!("Server closed connection");
                     break;
                 }
-                Err(e) => {
+                (None, Err(e)) => {
                     log::error!("WebSocket error: {}", e);
                     break;
                 }
-                _ => {}
+                _ => Ok(()),
+            };
+            if let Err(e) = checked {
+                log::error!("Closing connection: {}", e);
+                break;
             }
+            // Time spent waiting on full frame channels is not server silence
+            liveness.touch();
         }
+        outputs
     }
 
-    /// Receive next audio chunk
+    /// Receive next audio chunk from the primary stream (slot 0, binary type 4)
     pub async fn recv_audio_chunk(&mut self) -> Option<AudioChunk> {
         self.audio_rx.recv().await
     }
 
+    /// Take the next audio chunk of the primary stream if one is queued, without waiting
+    pub fn try_recv_audio_chunk(&mut self) -> Option<AudioChunk> {
+        self.audio_rx.try_recv()
+    }
+
+    /// Receive the next audio chunk of the primary stream, waiting at most `timeout`
+    pub async fn recv_audio_chunk_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<AudioChunk, RecvTimeoutError> {
+        self.audio_rx.recv_timeout(timeout).await
+    }
+
+    /// Get a separate receiver for a concurrent audio stream in `slot` (1-3)
+    ///
+    /// Chunks for a slot are dropped until it has a receiver, so subscribe before
+    /// the server starts the stream. Subscribing again replaces the previous
+    /// receiver. Slot 0 is always delivered through [`recv_audio_chunk`](Self::recv_audio_chunk)
+    /// and the split receivers. The subscription survives [`into_parts`](Self::into_parts).
+    pub fn audio_slot(&self, slot: u8) -> Result<FrameReceiver<AudioChunk>, Error> {
+        let mut slots = self.audio_slots.lock();
+        let sender = slot
+            .checked_sub(1)
+            .and_then(|i| slots.get_mut(i as usize))
+            .ok_or_else(|| Error::Protocol(format!("Invalid extra audio slot: {}", slot)))?;
+        let (tx, rx) = backpressure::channel(self.channels.audio);
+        *sender = Some(tx);
+        Ok(rx)
+    }
+
     /// Receive next artwork chunk
     pub async fn recv_artwork_chunk(&mut self) -> Option<ArtworkChunk> {
         self.artwork_rx.recv().await
     }
 
+    /// Take the next artwork chunk if one is queued, without waiting
+    pub fn try_recv_artwork_chunk(&mut self) -> Option<ArtworkChunk> {
+        self.artwork_rx.try_recv()
+    }
+
+    /// Receive the next artwork chunk, waiting at most `timeout`
+    pub async fn recv_artwork_chunk_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<ArtworkChunk, RecvTimeoutError> {
+        self.artwork_rx.recv_timeout(timeout).await
+    }
+
     /// Receive next visualizer chunk
     pub async fn recv_visualizer_chunk(&mut self) -> Option<VisualizerChunk> {
         self.visualizer_rx.recv().await
     }
 
+    /// Take the next visualizer chunk if one is queued, without waiting
+    pub fn try_recv_visualizer_chunk(&mut self) -> Option<VisualizerChunk> {
+        self.visualizer_rx.try_recv()
+    }
+
+    /// Receive the next visualizer chunk, waiting at most `timeout`
+    pub async fn recv_visualizer_chunk_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<VisualizerChunk, RecvTimeoutError> {
+        self.visualizer_rx.recv_timeout(timeout).await
+    }
+
+    /// Frames dropped so far because a receiver fell behind
+    ///
+    /// Extra audio slots count their drops on their own receivers, see
+    /// [`FrameReceiver::dropped`].
+    pub fn dropped_frames(&self) -> DroppedFrames {
+        DroppedFrames {
+            audio: self.audio_rx.dropped(),
+            artwork: self.artwork_rx.dropped(),
+            visualizer: self.visualizer_rx.dropped(),
+        }
+    }
+
+    /// Counts of received frames, parse failures, and drops since connecting
+    ///
+    /// Extra audio slots count their channel drops on their own receivers; their
+    /// chunks are included in [`FrameStats::audio`].
+    pub fn stats(&self) -> FrameStats {
+        self.counters.snapshot(self.dropped_frames())
+    }
+
     /// Receive next protocol message
     pub async fn recv_message(&mut self) -> Option<Message> {
+        self.message_rx
+            .recv()
+            .await
+            .map(|received| received.message)
+    }
+
+    /// Receive the next protocol message with the time its frame arrived
+    ///
+    /// The arrival time is taken before the message is queued, so it is not
+    /// skewed by how long the application took to get to it.
+    pub async fn recv_message_stamped(&mut self) -> Option<ReceivedMessage> {
         self.message_rx.recv().await
     }
 
+    /// Take the next protocol message if one is queued, without waiting
+    ///
+    /// For game loops and GUI frameworks that poll once per frame; `None` means
+    /// nothing is queued right now.
+    pub fn try_recv_message(&mut self) -> Option<Message> {
+        self.message_rx
+            .try_recv()
+            .ok()
+            .map(|received| received.message)
+    }
+
+    /// Receive the next protocol message, waiting at most `timeout`
+    ///
+    /// Fails with [`RecvTimeoutError::Disconnected`] once the connection is closed
+    /// and every message was received.
+    pub async fn recv_message_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Message, RecvTimeoutError> {
+        recv_message_timeout(&mut self.message_rx, timeout)
+            .await
+            .map(|received| received.message)
+    }
+
     /// Send a message to the server
+    ///
+    /// With [`ClientConfig::validate_outgoing`], invalid messages are rejected here.
+    /// With [`ClientConfig::coalescing`], rapid updates may be held back briefly.
     pub async fn send_message(&self, msg: &Message) -> Result<(), Error> {
-        let json = serde_json::to_string(msg).map_err(|e| Error::Protocol(e.to_string()))?;
-        log::debug!("Sending message: {}", json);
+        if self.validate_outgoing {
+            msg.validate()?;
+        }
+        send_outgoing(
+            &self.ws_tx,
+            msg,
+            self.encoding,
+            self.hook.as_ref(),
+            self.coalescer.as_ref(),
+        )
+        .await
+    }
+
+    /// Tell the server why this client is leaving, then close the connection
+    ///
+    /// Sends client/goodbye, flushes it, and starts the WebSocket close handshake.
+    /// A dropped client sends neither, so the server only learns that it left
+    /// once the socket fails. After [`into_parts`](Self::into_parts), use [`WsSender::close`].
+    ///
+    /// Pass a [`GoodbyeReason`](crate::protocol::messages::GoodbyeReason), or a
+    /// [`ClientGoodbye`] to include telemetry.
+    pub async fn close(self, goodbye: impl Into<ClientGoodbye>) -> Result<(), Error> {
+        send_goodbye(
+            &self.ws_tx,
+            goodbye.into(),
+            self.encoding,
+            self.hook.as_ref(),
+        )
+        .await
+    }
+
+    /// Move this client to the server at `url`, keeping its receivers
+    ///
+    /// Connects and completes the handshake with the new server first, so the
+    /// current connection stays up if that fails. Then sends client/goodbye with
+    /// [`AnotherServer`](crate::protocol::messages::GoodbyeReason::AnotherServer)
+    /// to the current server and routes the new connection into the existing
+    /// receivers: they get a stream/end for every role, and clock sync starts over.
+    /// Audio slot subscriptions and the [`ClientConfig`] carry over.
+    ///
+    /// The new server has to negotiate the same protocol version and message
+    /// encoding, since senders already handed out keep using them.
+    pub async fn switch_server(&mut self, url: &str) -> Result<(), Error> {
+        let closed = || Error::Connection("The connection is closed".to_string());
+        let (reply, result) = oneshot::channel();
+        self.switch
+            .send(SwitchRequest {
+                url: url.to_string(),
+                reply,
+            })
+            .map_err(|_| closed())?;
+        let (server_hello, session) = result.await.map_err(|_| closed())??;
+        self.server_hello = server_hello;
+        self.session = session;
+        Ok(())
+    }
+
+    /// Ask the server to seek the current track to `position_us` microseconds
+    ///
+    /// Chunks for the old position may still arrive; see
+    /// [`Scheduler::discard_before`](crate::scheduler::Scheduler::discard_before).
+    pub async fn seek(&self, position_us: i64) -> Result<(), Error> {
+        self.send_message(&controller_command(ControllerCommand::seek(position_us)))
+            .await
+    }
+
+    /// Ask the server to seek the current track by `offset_us` microseconds
+    pub async fn seek_by(&self, offset_us: i64) -> Result<(), Error> {
+        self.send_message(&controller_command(ControllerCommand::seek_by(offset_us)))
+            .await
+    }
+
+    /// Ask the server to move this client into another group
+    ///
+    /// The server answers with stream/end for the old group's stream, then
+    /// group/update and the new group's stream/start.
+    pub async fn join_group(&self, group_id: &str) -> Result<(), Error> {
+        Feature::GroupMembership.require(self.version)?;
+        self.send_message(&controller_command(ControllerCommand::join_group(group_id)))
+            .await
+    }
 
-        let mut tx = self.ws_tx.lock().await;
-        tx.send(WsMessage::Text(json))
+    /// Ask the server to move this client back to its default group
+    pub async fn leave_group(&self) -> Result<(), Error> {
+        Feature::GroupMembership.require(self.version)?;
+        self.send_message(&controller_command(ControllerCommand::leave_group()))
             .await
-            .map_err(|e| Error::WebSocket(e.to_string()))
     }
 
     /// Get reference to clock sync
+    ///
+    /// The connection applies every `server/time` reply to it; see
+    /// [`ClientConfig::clock_sync_interval`] for sending the requests.
     pub fn clock_sync(&self) -> Arc<tokio::sync::Mutex<ClockSync>> {
         Arc::clone(&self.clock_sync)
     }
 
-    /// Split into separate receivers for concurrent processing
+    /// Protocol version negotiated with the server
+    ///
+    /// The lower of the client/hello and server/hello versions.
+    pub fn protocol_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether the negotiated protocol version has an optional feature
+    pub fn supports(&self, feature: Feature) -> bool {
+        feature.is_supported(self.version)
+    }
+
+    /// Watch whether the connection is still up
+    ///
+    /// Starts as [`ConnectionStatus::Connected`] and changes once, to
+    /// [`Disconnected`](ConnectionStatus::Disconnected) when the connection ends or
+    /// to [`Lost`](ConnectionStatus::Lost) when the [`ClientConfig::keepalive`]
+    /// watchdog gives up on a silent server. A client from a
+    /// [`ServerPool`](crate::protocol::ServerPool) goes through
+    /// [`Reconnecting`](ConnectionStatus::Reconnecting) and back to `Connected`
+    /// on every failover.
+    pub fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status.clone()
+    }
+
+    /// Server identity and activated roles from the handshake
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Watch the session, which changes when a [`ServerPool`](crate::protocol::ServerPool)
+    /// fails over to another server
+    pub fn watch_session(&self) -> watch::Receiver<Session> {
+        self.sessions.clone()
+    }
+
+    /// Get the server/hello received during the handshake
+    pub fn server_hello(&self) -> &ServerHello {
+        &self.server_hello
+    }
+
+    /// Get a snapshot of the currently active stream descriptors per role
+    pub fn current_stream(&self) -> CurrentStream {
+        self.streams.current()
+    }
+
+    /// Get the configuration of the active visualizer stream, if any
+    ///
+    /// Describes how to read the chunks from [`recv_visualizer_chunk`](Self::recv_visualizer_chunk):
+    /// bin count, frame rate, window, and frequency range.
+    pub fn visualizer_config(&self) -> Option<StreamVisualizerConfig> {
+        self.streams.current().visualizer
+    }
+
+    /// Get a handle to the event bus of this connection
+    ///
+    /// Any number of subscribers can follow metadata, volume, stream, group,
+    /// sync quality, and connection changes, independently of the message
+    /// receiver. The handle keeps working after [`into_parts`](Self::into_parts)
+    /// and across server switches.
+    pub fn event_bus(&self) -> EventBus {
+        self.events.clone()
+    }
+
+    /// Get a handle to the stream tracker
+    ///
+    /// The handle stays up to date after [`into_parts`](Self::into_parts), and also exposes
+    /// the per-role format history.
+    pub fn stream_tracker(&self) -> StreamTracker {
+        self.streams.clone()
+    }
+
+    /// A sender for this connection that outlives the client
+    ///
+    /// Useful to send commands or close the connection while the client itself
+    /// has been handed to something that consumes it, such as a
+    /// [`Player`](crate::player::Player).
+    pub fn sender(&self) -> WsSender {
+        WsSender {
+            tx: Arc::clone(&self.ws_tx),
+            validate_outgoing: self.validate_outgoing,
+            encoding: self.encoding,
+            version: self.version,
+            hook: self.hook.clone(),
+            status: self.status.clone(),
+            coalescer: self.coalescer.clone(),
+        }
+    }
+
+    /// Split into separately owned receivers and a sender
     ///
     /// This allows using tokio::select! to process messages and binary data concurrently
     /// without borrow checker issues
+    pub fn into_parts(self) -> ClientParts {
+        ClientParts {
+            messages: MessageReceiver(self.message_rx),
+            audio: self.audio_rx,
+            artwork: self.artwork_rx,
+            visualizer: self.visualizer_rx,
+            clock: self.clock_sync,
+            sender: WsSender {
+                tx: self.ws_tx,
+                validate_outgoing: self.validate_outgoing,
+                encoding: self.encoding,
+                version: self.version,
+                hook: self.hook,
+                status: self.status,
+                coalescer: self.coalescer,
+            },
+        }
+    }
+
+    /// Split into separate receivers for concurrent processing
+    #[deprecated(note = "Use into_parts, which names each part")]
     pub fn split(
         self,
     ) -> (
         UnboundedReceiver<Message>,
-        UnboundedReceiver<AudioChunk>,
+        FrameReceiver<AudioChunk>,
         Arc<tokio::sync::Mutex<ClockSync>>,
         WsSender,
     ) {
+        let parts = self.into_parts();
         (
-            self.message_rx,
-            self.audio_rx,
-            self.clock_sync,
-            WsSender { tx: self.ws_tx },
+            parts.messages.into_unstamped(),
+            parts.audio,
+            parts.clock,
+            parts.sender,
         )
     }
 
     /// Split into all receivers including artwork and visualizer
-    ///
-    /// Use this when you need to handle all binary frame types
+    #[deprecated(note = "Use into_parts, which names each part")]
+    #[allow(clippy::type_complexity)]
     pub fn split_full(
         self,
     ) -> (
         UnboundedReceiver<Message>,
-        UnboundedReceiver<AudioChunk>,
-        UnboundedReceiver<ArtworkChunk>,
-        UnboundedReceiver<VisualizerChunk>,
+        FrameReceiver<AudioChunk>,
+        FrameReceiver<ArtworkChunk>,
+        FrameReceiver<VisualizerChunk>,
         Arc<tokio::sync::Mutex<ClockSync>>,
         WsSender,
     ) {
+        let parts = self.into_parts();
         (
-            self.message_rx,
-            self.audio_rx,
-            self.artwork_rx,
-            self.visualizer_rx,
-            self.clock_sync,
-            WsSender { tx: self.ws_tx },
+            parts.messages.into_unstamped(),
+            parts.audio,
+            parts.artwork,
+            parts.visualizer,
+            parts.clock,
+            parts.sender,
+        )
+    }
+}
+
+/// Sending ends of a client's receivers
+///
+/// The message router owns them while a connection is up and hands them back
+/// when it ends, so a replacement connection keeps feeding the same receivers.
+pub(crate) struct Outputs {
+    audio_tx: FrameSender<AudioChunk>,
+    audio_slots: AudioSlotSenders,
+    artwork_tx: FrameSender<ArtworkChunk>,
+    visualizer_tx: FrameSender<VisualizerChunk>,
+    message_tx: UnboundedSender<ReceivedMessage>,
+    clock_sync: Arc<tokio::sync::Mutex<ClockSync>>,
+    streams: StreamTracker,
+    counters: Arc<FrameCounters>,
+    events: EventBus,
+}
+
+impl Outputs {
+    /// Forget the previous server's clock offset and end its streams
+    ///
+    /// Receivers get a stream/end for every role, as if the server had sent it.
+    pub(crate) async fn reset(&self) {
+        *self.clock_sync.lock().await = ClockSync::new();
+        let end = Message::StreamEnd(StreamEnd { roles: None });
+        self.streams.apply(&end);
+        for event in ClientEvent::from_message(&end) {
+            self.events.publish(event);
+        }
+        let _ = self.message_tx.send(ReceivedMessage::now(end));
+    }
+}
+
+/// A running message router and the signal that stops it
+struct Router {
+    task: JoinHandle<Outputs>,
+    stop: Arc<Notify>,
+}
+
+/// Request from [`ProtocolClient::switch_server`] to the task that owns the [`Link`]
+pub(crate) struct SwitchRequest {
+    url: String,
+    reply: oneshot::Sender<Result<(ServerHello, Session), Error>>,
+}
+
+/// Something the owner of a [`Link`] has to act on
+pub(crate) enum LinkEvent {
+    /// The connection ended; `None` if its router panicked
+    Ended(Option<Outputs>),
+    /// The client asked to move to another server
+    Switch(SwitchRequest),
+}
+
+/// What the message router needs from a completed handshake
+pub(crate) struct Route {
+    read: FrameStream,
+    checks: StreamChecks,
+    cipher: Option<PayloadCipher>,
+    encoding: MessageEncoding,
+}
+
+/// A connection that completed the hello exchange but is not routed yet
+pub(crate) struct Handshake {
+    hello: ClientHello,
+    write: TappedSink,
+    route: Route,
+    server_hello: ServerHello,
+    session: Session,
+    version: u32,
+}
+
+impl Handshake {
+    /// Server identity and activated roles
+    pub(crate) fn session(&self) -> &Session {
+        &self.session
+    }
+}
+
+/// The connection behind a [`ProtocolClient`], which can be swapped for another
+pub(crate) struct Link {
+    router: Router,
+    ws_tx: Weak<WsSink>,
+    status: Arc<watch::Sender<ConnectionStatus>>,
+    session: watch::Sender<Session>,
+    requests: UnboundedReceiver<SwitchRequest>,
+    hello: ClientHello,
+    config: ClientConfig,
+    encoding: MessageEncoding,
+    version: u32,
+}
+
+impl Link {
+    /// Wait for the current connection to end; `None` if its router panicked
+    pub(crate) async fn ended(&mut self) -> Option<Outputs> {
+        (&mut self.router.task).await.ok()
+    }
+
+    /// Wait for the current connection to end or the client to ask for another server
+    pub(crate) async fn next_event(&mut self) -> LinkEvent {
+        tokio::select! {
+            outputs = &mut self.router.task => LinkEvent::Ended(outputs.ok()),
+            Some(request) = self.requests.recv() => LinkEvent::Switch(request),
+        }
+    }
+
+    /// Carry out a [`ProtocolClient::switch_server`] request
+    pub(crate) async fn switch(&mut self, request: SwitchRequest) {
+        let result = self.switch_to(&request.url).await;
+        if let Err(e) = &result {
+            log::warn!("Could not switch to {}: {}", request.url, e);
+        }
+        let _ = request.reply.send(result);
+    }
+
+    async fn switch_to(&mut self, url: &str) -> Result<(ServerHello, Session), Error> {
+        let handshake = ProtocolClient::handshake(
+            WebSocketTransport::new(url),
+            self.hello.clone(),
+            &self.config,
         )
+        .await?;
+        self.accepts(&handshake)?;
+        let closed = || Error::Connection("The connection is closed".to_string());
+        let ws_tx = self.ws_tx.upgrade().ok_or_else(closed)?;
+        // Senders wait until the new connection is in place
+        let mut sink = ws_tx.lock().await;
+        if sink.is_closed() {
+            return Err(closed());
+        }
+        let goodbye = GoodbyeReason::AnotherServer.into();
+        let hook = self.config.message_hook.as_ref();
+        if let Err(e) = say_goodbye(&mut sink, goodbye, self.encoding, hook).await {
+            log::debug!("Could not say goodbye to the previous server: {}", e);
+        }
+        self.router.stop.notify_one();
+        let outputs = self
+            .ended()
+            .await
+            .ok_or_else(|| Error::Connection("Message router failed".to_string()))?;
+        *sink = handshake.write;
+        drop(sink);
+
+        outputs.reset().await;
+        self.router =
+            ProtocolClient::route(handshake.route, outputs, &ws_tx, &self.config, &self.status);
+        log::info!("Switched to {}", url);
+        self.session.send_replace(handshake.session.clone());
+        Ok((handshake.server_hello, handshake.session))
+    }
+
+    /// Whether the client closed the connection or dropped every handle to it
+    pub(crate) async fn closed_by_client(&self) -> bool {
+        match self.ws_tx.upgrade() {
+            Some(ws_tx) => ws_tx.lock().await.is_closed(),
+            None => true,
+        }
+    }
+
+    /// Publish a connection status
+    pub(crate) fn set_status(&self, status: ConnectionStatus) {
+        self.status.send_replace(status);
+    }
+
+    /// Check that senders handed out for this link can be used with `handshake`
+    ///
+    /// They keep the message encoding and protocol version of the first
+    /// connection, so a replacement has to negotiate the same ones.
+    pub(crate) fn accepts(&self, handshake: &Handshake) -> Result<(), Error> {
+        if handshake.version != self.version {
+            return Err(Error::Protocol(format!(
+                "Negotiated protocol version {}, but the client uses {}",
+                handshake.version, self.version
+            )));
+        }
+        if handshake.route.encoding != self.encoding {
+            return Err(Error::Protocol(format!(
+                "Negotiated {} message encoding, but the client uses {}",
+                handshake.route.encoding, self.encoding
+            )));
+        }
+        Ok(())
+    }
+
+    /// Route `handshake` into `outputs` in place of the connection that ended
+    ///
+    /// Returns `false`, and closes the receivers, if the client was closed in the meantime.
+    pub(crate) async fn replace(&mut self, handshake: Handshake, outputs: Outputs) -> bool {
+        let Some(ws_tx) = self.ws_tx.upgrade() else {
+            self.finish(outputs);
+            return false;
+        };
+        {
+            let mut sink = ws_tx.lock().await;
+            if sink.is_closed() {
+                drop(sink);
+                self.finish(outputs);
+                return false;
+            }
+            *sink = handshake.write;
+        }
+        self.router =
+            ProtocolClient::route(handshake.route, outputs, &ws_tx, &self.config, &self.status);
+        self.set_status(ConnectionStatus::Connected);
+        self.session.send_replace(handshake.session);
+        true
+    }
+
+    /// Publish that the connection is closed, then close the receivers
+    ///
+    /// The status changes first, so receivers that see their channel end can tell
+    /// why; a lost connection keeps its status.
+    pub(crate) fn finish(&self, outputs: Outputs) {
+        self.status.send_if_modified(|status| {
+            let closed = matches!(
+                status,
+                ConnectionStatus::Connected | ConnectionStatus::Reconnecting
+            );
+            if closed {
+                *status = ConnectionStatus::Disconnected;
+            }
+            closed
+        });
+        drop(outputs);
+    }
+
+    /// Serve switch requests until the connection ends, then close the receivers
+    pub(crate) async fn close_when_ended(mut self) {
+        loop {
+            match self.next_event().await {
+                LinkEvent::Ended(outputs) => {
+                    if let Some(outputs) = outputs {
+                        self.finish(outputs);
+                    }
+                    return;
+                }
+                LinkEvent::Switch(request) => self.switch(request).await,
+            }
+        }
     }
 }
@@ -0,0 +1,140 @@
+// ABOUTME: Outgoing coalescing of rapid client/state updates and controller commands
+// ABOUTME: Sends the first update of a burst at once and the latest when the window ends
+
+use crate::protocol::messages::{ControllerCommandKind, Message};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Minimum spacing of outgoing updates that supersede each other
+///
+/// A volume slider dragged across its range produces dozens of updates per
+/// second, and only the last one matters. With coalescing, the first update of a
+/// burst goes out at once; later ones within the window are held, each replacing
+/// the previous, and the latest is sent when the window ends.
+///
+/// Applies to client/state and to volume, mute, and absolute seek commands.
+/// Other messages are sent immediately, so they can overtake a held update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coalescing {
+    /// Minimum time between two client/state messages
+    pub state_window: Duration,
+    /// Minimum time between two commands of the same kind
+    pub command_window: Duration,
+}
+
+impl Coalescing {
+    /// The same window for states and commands
+    pub fn new(window: Duration) -> Self {
+        Self {
+            state_window: window,
+            command_window: window,
+        }
+    }
+}
+
+/// Kinds of update where a newer one makes an older one pointless
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum UpdateKind {
+    State,
+    Volume,
+    Mute,
+    Seek,
+}
+
+impl UpdateKind {
+    fn of(msg: &Message) -> Option<Self> {
+        match msg {
+            Message::ClientState(_) => Some(Self::State),
+            Message::ClientCommand(command) => {
+                let command = command.controller.as_ref()?;
+                match command.command {
+                    ControllerCommandKind::Volume => Some(Self::Volume),
+                    ControllerCommandKind::Mute => Some(Self::Mute),
+                    // Relative seeks add up, so none of them can be dropped
+                    ControllerCommandKind::Seek if command.offset.is_none() => Some(Self::Seek),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What to do with an outgoing message
+pub(crate) enum Admission {
+    /// Send it now
+    Send,
+    /// Held; a flush is already scheduled
+    Held,
+    /// Held; schedule a flush of this kind at the deadline
+    Flush(UpdateKind, Instant),
+}
+
+#[derive(Debug)]
+struct Slot {
+    last_sent: Instant,
+    pending: Option<Message>,
+}
+
+/// Shared outgoing queue of the senders of one client
+#[derive(Debug)]
+pub(crate) struct Coalescer {
+    settings: Coalescing,
+    slots: Mutex<HashMap<UpdateKind, Slot>>,
+}
+
+impl Coalescer {
+    pub(crate) fn new(settings: Coalescing) -> Self {
+        Self {
+            settings,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn window(&self, kind: UpdateKind) -> Duration {
+        match kind {
+            UpdateKind::State => self.settings.state_window,
+            _ => self.settings.command_window,
+        }
+    }
+
+    /// Decide whether `msg` goes out now or replaces the held update of its kind
+    pub(crate) fn admit(&self, msg: &Message) -> Admission {
+        let Some(kind) = UpdateKind::of(msg) else {
+            return Admission::Send;
+        };
+        let now = Instant::now();
+        let window = self.window(kind);
+        let mut slots = self.slots.lock().unwrap();
+        let Some(slot) = slots.get_mut(&kind) else {
+            slots.insert(
+                kind,
+                Slot {
+                    last_sent: now,
+                    pending: None,
+                },
+            );
+            return Admission::Send;
+        };
+        if slot.pending.is_some() {
+            slot.pending = Some(msg.clone());
+            Admission::Held
+        } else if now >= slot.last_sent + window {
+            slot.last_sent = now;
+            Admission::Send
+        } else {
+            slot.pending = Some(msg.clone());
+            Admission::Flush(kind, slot.last_sent + window)
+        }
+    }
+
+    /// Take the held update of `kind` for sending, starting a new window
+    pub(crate) fn take(&self, kind: UpdateKind) -> Option<Message> {
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.get_mut(&kind)?;
+        slot.last_sent = Instant::now();
+        slot.pending.take()
+    }
+}
@@ -0,0 +1,137 @@
+// ABOUTME: Spec-compliance mode for the protocol client
+// ABOUTME: One switch for strict parsing, role validation, buffer capacity, and timestamp checks
+
+use crate::error::Error;
+use crate::protocol::client::AudioChunk;
+use crate::protocol::messages::{ClientHello, Message, ServerHello};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// How strictly the client holds the server to the Sendspin spec
+///
+/// `Strict` turns every check below into a hard error that ends the connection, for
+/// certification testing. `Lenient` logs the same problems and carries on, for field
+/// deployments talking to imperfect servers.
+///
+/// The checks are:
+/// - messages that fail to parse and unknown binary frame types
+/// - server/hello activating roles the client did not offer
+/// - audio buffered ahead of playback beyond the advertised `buffer_capacity`
+/// - negative or backwards-running chunk timestamps within a stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpecCompliance {
+    /// Reject spec violations
+    Strict,
+    /// Log spec violations and continue
+    #[default]
+    Lenient,
+}
+
+impl SpecCompliance {
+    /// Whether violations are fatal
+    pub fn is_strict(self) -> bool {
+        self == Self::Strict
+    }
+
+    /// Report a violation: an error in strict mode, a warning otherwise
+    pub fn violation(self, description: String) -> Result<(), Error> {
+        match self {
+            Self::Strict => Err(Error::Protocol(format!("Spec violation: {}", description))),
+            Self::Lenient => {
+                log::warn!("Spec violation (ignored): {}", description);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that the server only activated roles the client offered
+    pub fn check_roles(self, hello: &ClientHello, server_hello: &ServerHello) -> Result<(), Error> {
+        for role in &server_hello.active_roles {
+            if !hello.supported_roles.contains(role) {
+                self.violation(format!("server activated unsupported role '{}'", role))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Per-connection state for the audio stream checks
+#[derive(Debug)]
+pub(crate) struct StreamChecks {
+    mode: SpecCompliance,
+    buffer_capacity: Option<usize>,
+    last_timestamp: Option<i64>,
+    /// Chunks not yet played: local play time and size in bytes
+    outstanding: VecDeque<(Instant, usize)>,
+    outstanding_bytes: usize,
+}
+
+impl StreamChecks {
+    pub(crate) fn new(mode: SpecCompliance, hello: &ClientHello) -> Self {
+        Self {
+            mode,
+            buffer_capacity: hello
+                .player_v1_support
+                .as_ref()
+                .map(|s| s.buffer_capacity as usize)
+                .filter(|c| *c > 0),
+            last_timestamp: None,
+            outstanding: VecDeque::new(),
+            outstanding_bytes: 0,
+        }
+    }
+
+    /// Whether buffer capacity is enforced (and play times are needed)
+    pub(crate) fn tracks_buffer(&self) -> bool {
+        self.buffer_capacity.is_some()
+    }
+
+    /// Reset stream state on stream boundaries
+    pub(crate) fn observe_message(&mut self, msg: &Message) {
+        if matches!(
+            msg,
+            Message::StreamStart(_) | Message::StreamClear(_) | Message::StreamEnd(_)
+        ) {
+            self.last_timestamp = None;
+            self.outstanding.clear();
+            self.outstanding_bytes = 0;
+        }
+    }
+
+    /// Check an audio chunk; `play_at` is its local play time once clocks are synced
+    pub(crate) fn check_audio(
+        &mut self,
+        chunk: &AudioChunk,
+        play_at: Option<Instant>,
+    ) -> Result<(), Error> {
+        if chunk.timestamp < 0 {
+            self.mode
+                .violation(format!("negative audio timestamp {}", chunk.timestamp))?;
+        }
+        if let Some(last) = self.last_timestamp.filter(|last| chunk.timestamp < *last) {
+            self.mode.violation(format!(
+                "audio timestamp went backwards from {} to {}",
+                last, chunk.timestamp
+            ))?;
+        }
+        self.last_timestamp = Some(chunk.timestamp);
+
+        let (Some(capacity), Some(play_at)) = (self.buffer_capacity, play_at) else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        while let Some((_, bytes)) = self.outstanding.front().filter(|(at, _)| *at <= now) {
+            self.outstanding_bytes -= bytes;
+            self.outstanding.pop_front();
+        }
+        self.outstanding.push_back((play_at, chunk.data.len()));
+        self.outstanding_bytes += chunk.data.len();
+        if self.outstanding_bytes > capacity {
+            self.mode.violation(format!(
+                "{} bytes buffered ahead of playback exceeds buffer_capacity {}",
+                self.outstanding_bytes, capacity
+            ))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+// ABOUTME: Negotiable wire encodings for protocol messages
+// ABOUTME: JSON text by default, MessagePack or CBOR binary frames when both ends support them
+
+use crate::error::Error;
+use crate::protocol::client::binary_types;
+use crate::protocol::messages::Message;
+use std::fmt;
+
+/// How protocol messages are encoded after the hello exchange
+///
+/// The hello messages themselves are always JSON. Binary encodings travel in
+/// binary frames tagged with [`binary_types::ENCODED_MESSAGE`], so they never
+/// collide with audio, artwork, or visualizer frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageEncoding {
+    /// JSON text frames (the spec default)
+    #[default]
+    Json,
+    /// MessagePack with named fields (`msgpack` feature)
+    MessagePack,
+    /// CBOR (`cbor` feature)
+    Cbor,
+}
+
+impl MessageEncoding {
+    /// Name used in the hello exchange
+    pub fn name(self) -> &'static str {
+        match self {
+            MessageEncoding::Json => "json",
+            MessageEncoding::MessagePack => "msgpack",
+            MessageEncoding::Cbor => "cbor",
+        }
+    }
+
+    /// Look up an encoding by its hello name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(MessageEncoding::Json),
+            "msgpack" => Some(MessageEncoding::MessagePack),
+            "cbor" => Some(MessageEncoding::Cbor),
+            _ => None,
+        }
+    }
+
+    /// Whether support for this encoding is compiled in
+    pub fn is_available(self) -> bool {
+        match self {
+            MessageEncoding::Json => true,
+            MessageEncoding::MessagePack => cfg!(feature = "msgpack"),
+            MessageEncoding::Cbor => cfg!(feature = "cbor"),
+        }
+    }
+
+    /// Compiled-in binary encodings, most compact first
+    pub fn available() -> Vec<Self> {
+        [MessageEncoding::Cbor, MessageEncoding::MessagePack]
+            .into_iter()
+            .filter(|e| e.is_available())
+            .collect()
+    }
+
+    /// Pick the first of `preferred` that the peer offered by name
+    ///
+    /// Falls back to JSON when nothing matches.
+    pub fn negotiate(preferred: &[Self], offered: &[String]) -> Self {
+        preferred
+            .iter()
+            .copied()
+            .find(|e| e.is_available() && offered.iter().any(|o| o == e.name()))
+            .unwrap_or_default()
+    }
+
+    /// Encode a binary message frame (type byte and encoded message)
+    ///
+    /// Fails for JSON, which is sent as text, and for encodings not compiled in.
+    #[cfg_attr(
+        not(any(feature = "msgpack", feature = "cbor")),
+        allow(unused_variables)
+    )]
+    pub fn encode(self, msg: &Message) -> Result<Vec<u8>, Error> {
+        match self {
+            #[cfg(feature = "msgpack")]
+            MessageEncoding::MessagePack => {
+                let mut frame = vec![binary_types::ENCODED_MESSAGE];
+                rmp_serde::encode::write_named(&mut frame, msg)
+                    .map_err(|e| Error::Protocol(e.to_string()))?;
+                Ok(frame)
+            }
+            #[cfg(feature = "cbor")]
+            MessageEncoding::Cbor => {
+                let mut frame = vec![binary_types::ENCODED_MESSAGE];
+                ciborium::into_writer(msg, &mut frame)
+                    .map_err(|e| Error::Protocol(e.to_string()))?;
+                Ok(frame)
+            }
+            other => Err(unsupported(other)),
+        }
+    }
+
+    /// Decode a frame produced by [`encode`](Self::encode)
+    #[cfg_attr(
+        not(any(feature = "msgpack", feature = "cbor")),
+        allow(unused_variables)
+    )]
+    pub fn decode(self, frame: &[u8]) -> Result<Message, Error> {
+        let payload = match frame.split_first() {
+            Some((&binary_types::ENCODED_MESSAGE, payload)) => payload,
+            _ => return Err(Error::Protocol("Not an encoded message frame".to_string())),
+        };
+        match self {
+            #[cfg(feature = "msgpack")]
+            MessageEncoding::MessagePack => {
+                rmp_serde::from_slice(payload).map_err(|e| Error::Protocol(e.to_string()))
+            }
+            #[cfg(feature = "cbor")]
+            MessageEncoding::Cbor => {
+                ciborium::from_reader(payload).map_err(|e| Error::Protocol(e.to_string()))
+            }
+            other => Err(unsupported(other)),
+        }
+    }
+}
+
+impl fmt::Display for MessageEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn unsupported(encoding: MessageEncoding) -> Error {
+    Error::Protocol(format!(
+        "Message encoding '{}' is not a binary encoding in this build",
+        encoding
+    ))
+}
@@ -0,0 +1,169 @@
+// ABOUTME: Optional payload encryption for binary frames
+// ABOUTME: Shared-key XChaCha20-Poly1305 sealing negotiated through the hello messages
+
+use crate::error::Error;
+use crate::protocol::messages::PayloadEncryptionParams;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use std::fmt;
+
+/// Cipher name offered in client/hello and confirmed in server/hello
+pub const PAYLOAD_CIPHER: &str = "xchacha20poly1305";
+
+/// Bytes of the binary frame header left in the clear (type byte and timestamp)
+const HEADER_LEN: usize = 9;
+
+/// Bytes of per-connection salt in each nonce
+const SALT_LEN: usize = 16;
+
+/// Shared 256-bit key for payload encryption
+///
+/// Both ends need the same key out of band. `Debug` never prints it.
+#[derive(Clone, PartialEq, Eq)]
+pub struct PayloadKey([u8; 32]);
+
+impl PayloadKey {
+    /// Use raw key bytes
+    pub fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Parse a key from 64 hex characters
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let bytes = decode_hex(hex.trim())?;
+        let key = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            Error::Protocol(format!("Payload key must be 32 bytes, got {}", bytes.len()))
+        })?;
+        Ok(Self(key))
+    }
+}
+
+impl fmt::Debug for PayloadKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PayloadKey(<redacted>)")
+    }
+}
+
+/// One direction of an encrypted binary frame stream
+///
+/// Frames keep their type byte and timestamp in the clear, authenticated as
+/// associated data, so routing and scheduling work unchanged. The payload is
+/// sealed with a nonce made of the connection salt and a frame counter, so both
+/// ends must see every binary frame in order, which WebSocket guarantees.
+pub struct PayloadCipher {
+    aead: XChaCha20Poly1305,
+    salt: [u8; SALT_LEN],
+    sequence: u64,
+}
+
+impl PayloadCipher {
+    /// Create a cipher for a connection using `salt` from server/hello
+    pub fn new(key: &PayloadKey, salt: [u8; SALT_LEN]) -> Self {
+        Self {
+            aead: XChaCha20Poly1305::new((&key.0).into()),
+            salt,
+            sequence: 0,
+        }
+    }
+
+    /// Server side: pick a fresh salt and describe it for server/hello
+    pub fn negotiate(key: &PayloadKey) -> (Self, PayloadEncryptionParams) {
+        let salt = *uuid::Uuid::new_v4().as_bytes();
+        let params = PayloadEncryptionParams {
+            cipher: PAYLOAD_CIPHER.to_string(),
+            salt: encode_hex(&salt),
+        };
+        (Self::new(key, salt), params)
+    }
+
+    /// Client side: set up decryption from the server's parameters
+    pub fn accept(key: &PayloadKey, params: &PayloadEncryptionParams) -> Result<Self, Error> {
+        if params.cipher != PAYLOAD_CIPHER {
+            return Err(Error::Protocol(format!(
+                "Unsupported payload cipher '{}'",
+                params.cipher
+            )));
+        }
+        let salt = decode_hex(&params.salt)?
+            .try_into()
+            .map_err(|_| Error::Protocol(format!("Payload salt must be {} bytes", SALT_LEN)))?;
+        Ok(Self::new(key, salt))
+    }
+
+    fn next_nonce(&mut self) -> XNonce {
+        let mut nonce = [0u8; 24];
+        nonce[..SALT_LEN].copy_from_slice(&self.salt);
+        nonce[SALT_LEN..].copy_from_slice(&self.sequence.to_be_bytes());
+        self.sequence += 1;
+        nonce.into()
+    }
+
+    /// Encrypt the payload of an encoded binary frame
+    pub fn seal(&mut self, frame: &[u8]) -> Result<Vec<u8>, Error> {
+        let (header, payload) = split_frame(frame)?;
+        let nonce = self.next_nonce();
+        let sealed = self
+            .aead
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: payload,
+                    aad: header,
+                },
+            )
+            .map_err(|_| Error::Protocol("Payload encryption failed".to_string()))?;
+        Ok([header, &sealed].concat())
+    }
+
+    /// Decrypt and authenticate a frame produced by [`seal`](Self::seal)
+    pub fn open(&mut self, frame: &[u8]) -> Result<Vec<u8>, Error> {
+        let (header, sealed) = split_frame(frame)?;
+        let nonce = self.next_nonce();
+        let payload = self
+            .aead
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: sealed,
+                    aad: header,
+                },
+            )
+            .map_err(|_| Error::Protocol("Payload authentication failed".to_string()))?;
+        Ok([header, &payload].concat())
+    }
+}
+
+impl fmt::Debug for PayloadCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayloadCipher")
+            .field("sequence", &self.sequence)
+            .finish_non_exhaustive()
+    }
+}
+
+fn split_frame(frame: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    if frame.len() < HEADER_LEN {
+        return Err(Error::Protocol(format!(
+            "Binary frame too short to encrypt: {} bytes",
+            frame.len()
+        )));
+    }
+    Ok(frame.split_at(HEADER_LEN))
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(Error::Protocol("Invalid hex string".to_string()));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| Error::Protocol("Invalid hex string".to_string()))
+        })
+        .collect()
+}
@@ -0,0 +1,219 @@
+// ABOUTME: Event-driven client API: a handler trait with one callback per event
+// ABOUTME: ProtocolClient::run drives the callbacks from the message and frame receivers
+
+use crate::error::Error;
+use crate::protocol::client::{
+    ArtworkChunk, AudioChunk, ClientParts, ProtocolClient, VisualizerChunk, WsSender,
+};
+use crate::protocol::messages::{
+    GroupUpdate, Message, ServerCommand, ServerGoodbye, ServerState, StreamClear, StreamEnd,
+    StreamStart,
+};
+use crate::sync::ClockSync;
+use std::future::Future;
+use std::sync::Arc;
+
+/// What a [`ClientHandler`] can reach while handling an event
+pub struct ClientContext {
+    sender: WsSender,
+    clock_sync: Arc<tokio::sync::Mutex<ClockSync>>,
+}
+
+impl ClientContext {
+    /// Sender for replies such as client/state
+    pub fn sender(&self) -> &WsSender {
+        &self.sender
+    }
+
+    /// Clock synchronization shared with the connection
+    pub fn clock_sync(&self) -> &Arc<tokio::sync::Mutex<ClockSync>> {
+        &self.clock_sync
+    }
+}
+
+/// Callbacks for [`ProtocolClient::run`]
+///
+/// Every method has an empty default, so a handler only implements the events it
+/// cares about. Callbacks run one at a time on the task calling `run`; an error
+/// from any of them stops the loop and is returned from `run`.
+///
+/// ```no_run
+/// use sendspin::prelude::*;
+/// use sendspin::protocol::ClientContext;
+///
+/// struct Printer;
+///
+/// impl ClientHandler for Printer {
+///     async fn on_audio_chunk(&mut self, chunk: AudioChunk, _ctx: &ClientContext) -> Result<()> {
+///         println!("audio at {}", chunk.timestamp);
+///         Ok(())
+///     }
+/// }
+///
+/// # async fn example(client: ProtocolClient) -> Result<()> {
+/// client.run(Printer).await
+/// # }
+/// ```
+pub trait ClientHandler: Send {
+    /// server/state: metadata, controller state, and other group-wide state
+    fn on_server_state(
+        &mut self,
+        _state: ServerState,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// server/command: volume, mute, and other player commands
+    fn on_server_command(
+        &mut self,
+        _command: ServerCommand,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// group/update: playback state and group membership
+    fn on_group_update(
+        &mut self,
+        _update: GroupUpdate,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// stream/start: a new stream format; chunks that follow use it
+    fn on_stream_start(
+        &mut self,
+        _start: StreamStart,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// stream/clear: drop buffered audio, typically after a seek
+    fn on_stream_clear(
+        &mut self,
+        _clear: StreamClear,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// stream/end: the stream stopped
+    fn on_stream_end(
+        &mut self,
+        _end: StreamEnd,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Any other message, such as server/time
+    fn on_message(
+        &mut self,
+        _message: Message,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Audio chunk of the primary stream
+    fn on_audio_chunk(
+        &mut self,
+        _chunk: AudioChunk,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Artwork image
+    fn on_artwork(
+        &mut self,
+        _chunk: ArtworkChunk,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Visualizer frame
+    fn on_visualizer(
+        &mut self,
+        _chunk: VisualizerChunk,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The connection ended, with the server's goodbye if it sent one
+    ///
+    /// Called once, after every queued message and frame was handled.
+    fn on_disconnect(
+        &mut self,
+        _goodbye: Option<ServerGoodbye>,
+        _ctx: &ClientContext,
+    ) -> impl Future<Output = Result<(), Error>> + Send {
+        async { Ok(()) }
+    }
+}
+
+impl ProtocolClient {
+    /// Handle every event of the connection with `handler` until it ends
+    ///
+    /// An alternative to [`into_parts`](Self::into_parts) and a hand-written
+    /// `tokio::select!` loop. Returns after [`ClientHandler::on_disconnect`], or with
+    /// the first error a callback returns.
+    pub async fn run<H: ClientHandler>(self, mut handler: H) -> Result<(), Error> {
+        let ClientParts {
+            mut messages,
+            mut audio,
+            mut artwork,
+            mut visualizer,
+            clock: clock_sync,
+            sender,
+        } = self.into_parts();
+        let ctx = ClientContext { sender, clock_sync };
+        let mut goodbye = None;
+
+        loop {
+            tokio::select! {
+                message = messages.recv() => match message {
+                    Some(Message::ServerGoodbye(bye)) => goodbye = Some(bye),
+                    Some(message) => dispatch(&mut handler, message, &ctx).await?,
+                    None => break,
+                },
+                Some(chunk) = audio.recv() => handler.on_audio_chunk(chunk, &ctx).await?,
+                Some(chunk) = artwork.recv() => handler.on_artwork(chunk, &ctx).await?,
+                Some(chunk) = visualizer.recv() => handler.on_visualizer(chunk, &ctx).await?,
+            }
+        }
+
+        // The connection is gone, but frames queued before the close still count
+        while let Some(chunk) = audio.try_recv() {
+            handler.on_audio_chunk(chunk, &ctx).await?;
+        }
+        while let Some(chunk) = artwork.try_recv() {
+            handler.on_artwork(chunk, &ctx).await?;
+        }
+        while let Some(chunk) = visualizer.try_recv() {
+            handler.on_visualizer(chunk, &ctx).await?;
+        }
+        handler.on_disconnect(goodbye, &ctx).await
+    }
+}
+
+async fn dispatch<H: ClientHandler>(
+    handler: &mut H,
+    message: Message,
+    ctx: &ClientContext,
+) -> Result<(), Error> {
+    match message {
+        Message::ServerState(state) => handler.on_server_state(state, ctx).await,
+        Message::ServerCommand(command) => handler.on_server_command(command, ctx).await,
+        Message::GroupUpdate(update) => handler.on_group_update(update, ctx).await,
+        Message::StreamStart(start) => handler.on_stream_start(start, ctx).await,
+        Message::StreamClear(clear) => handler.on_stream_clear(clear, ctx).await,
+        Message::StreamEnd(end) => handler.on_stream_end(end, ctx).await,
+        other => handler.on_message(other, ctx).await,
+    }
+}
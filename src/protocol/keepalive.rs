@@ -0,0 +1,108 @@
+// ABOUTME: WebSocket pings and a liveness watchdog for the client connection
+// ABOUTME: Declares the connection lost when the server goes silent for too long
+
+use crate::events::ConnectionStatus;
+use crate::protocol::client::WsSink;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Notify};
+use tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+/// Ping interval and silence limit for detecting a dead server
+///
+/// A connection through a NAT that silently dropped its mapping never reports
+/// an error; the only symptom is that nothing arrives any more. Pongs count as
+/// traffic, so an idle but healthy server keeps the connection alive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// Send a WebSocket ping this often
+    pub ping_interval: Duration,
+    /// Declare the connection lost after this long without any frame
+    pub timeout: Duration,
+}
+
+impl Keepalive {
+    /// Ping every `ping_interval` and give up after three missed intervals
+    pub fn new(ping_interval: Duration) -> Self {
+        Self {
+            ping_interval,
+            timeout: ping_interval.saturating_mul(3),
+        }
+    }
+}
+
+/// Time of the last sign of life from the server
+#[derive(Debug)]
+pub(crate) struct Liveness {
+    start: Instant,
+    last_ms: AtomicU64,
+}
+
+impl Liveness {
+    pub(crate) fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record activity now
+    pub(crate) fn touch(&self) {
+        let now = self.start.elapsed().as_millis() as u64;
+        self.last_ms.store(now, Ordering::Relaxed);
+    }
+
+    /// Time since the last recorded activity
+    fn idle(&self) -> Duration {
+        let last = Duration::from_millis(self.last_ms.load(Ordering::Relaxed));
+        self.start.elapsed().saturating_sub(last)
+    }
+}
+
+/// Ping the server and stop the router once it has been silent for `keepalive.timeout`
+///
+/// Ends on its own when the router finishes or every sender is dropped.
+pub(crate) async fn watchdog(
+    keepalive: Keepalive,
+    ws_tx: Weak<WsSink>,
+    liveness: Arc<Liveness>,
+    router: AbortHandle,
+    stop: Arc<Notify>,
+    status: Arc<watch::Sender<ConnectionStatus>>,
+) {
+    let mut ticks = tokio::time::interval(keepalive.ping_interval);
+    // The first tick completes immediately, right after the handshake
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        if router.is_finished() {
+            break;
+        }
+        let idle = liveness.idle();
+        if idle >= keepalive.timeout {
+            log::warn!(
+                "No frames from the server for {:?}, dropping the connection",
+                idle
+            );
+            // Publish first, so whoever sees the channels close can tell why
+            status.send_replace(ConnectionStatus::Lost);
+            stop.notify_one();
+            break;
+        }
+        let Some(ws_tx) = ws_tx.upgrade() else {
+            break;
+        };
+        // A stalled socket must not keep the watchdog from firing
+        let ping = async { ws_tx.lock().await.send(WsMessage::Ping(Vec::new())).await };
+        match tokio::time::timeout(keepalive.ping_interval, ping).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                log::debug!("Stopping keepalive: {}", e);
+                break;
+            }
+            Err(_) => log::debug!("Ping did not go out within {:?}", keepalive.ping_interval),
+        }
+    }
+}
@@ -1,10 +1,103 @@
 // ABOUTME: Protocol message type definitions and serialization
 // ABOUTME: Supports all Sendspin protocol messages per spec
 
+use crate::audio::decode::PcmEndian;
+use crate::audio::{AudioFormat, Codec};
+use crate::error::Error;
+use crate::protocol::base64;
+use crate::protocol::role::Role;
 use serde::{Deserialize, Serialize};
 
+/// Define a command-name enum that serializes as its wire string
+///
+/// Unknown names are kept in an `Other` variant so they round-trip unchanged.
+macro_rules! command_kind {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident => $wire:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// Any other command
+            Other(String),
+        }
+
+        #[cfg(feature = "schema")]
+        impl schemars::JsonSchema for $name {
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                stringify!($name).into()
+            }
+
+            fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                schemars::json_schema!({
+                    "type": "string",
+                    "examples": [$($wire),*]
+                })
+            }
+        }
+
+        impl $name {
+            /// Wire name of the command
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $wire,)*
+                    Self::Other(name) => name,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(name: &str) -> Self {
+                match name {
+                    $($wire => Self::$variant,)*
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(name: String) -> Self {
+                Self::from(name.as_str())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(kind: $name) -> Self {
+                match kind {
+                    $name::Other(name) => name,
+                    known => known.as_str().to_string(),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
+            }
+        }
+    };
+}
+
 /// Top-level protocol message envelope
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "payload")]
 pub enum Message {
     // === Handshake messages ===
@@ -69,6 +162,33 @@ pub enum Message {
     /// Client goodbye message
     #[serde(rename = "client/goodbye")]
     ClientGoodbye(ClientGoodbye),
+
+    /// Server goodbye message (server is going away)
+    #[serde(rename = "server/goodbye")]
+    ServerGoodbye(ServerGoodbye),
+}
+
+impl Message {
+    /// Get the wire type name of this message (e.g., "stream/start")
+    pub fn message_type(&self) -> &'static str {
+        match self {
+            Self::ClientHello(_) => "client/hello",
+            Self::ServerHello(_) => "server/hello",
+            Self::ClientTime(_) => "client/time",
+            Self::ServerTime(_) => "server/time",
+            Self::ClientState(_) => "client/state",
+            Self::ServerState(_) => "server/state",
+            Self::ServerCommand(_) => "server/command",
+            Self::ClientCommand(_) => "client/command",
+            Self::StreamStart(_) => "stream/start",
+            Self::StreamEnd(_) => "stream/end",
+            Self::StreamClear(_) => "stream/clear",
+            Self::StreamRequestFormat(_) => "stream/request-format",
+            Self::GroupUpdate(_) => "group/update",
+            Self::ClientGoodbye(_) => "client/goodbye",
+            Self::ServerGoodbye(_) => "server/goodbye",
+        }
+    }
 }
 
 // =============================================================================
@@ -77,6 +197,7 @@ pub enum Message {
 
 /// Client hello message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientHello {
     /// Unique client identifier
     pub client_id: String,
@@ -85,23 +206,82 @@ pub struct ClientHello {
     /// Protocol version number
     pub version: u32,
     /// List of supported roles with versions (e.g., "player@v1", "controller@v1")
-    pub supported_roles: Vec<String>,
+    pub supported_roles: Vec<Role>,
     /// Device information (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub device_info: Option<DeviceInfo>,
     /// Player capabilities (if client supports player@v1 role)
     #[serde(rename = "player@v1_support", skip_serializing_if = "Option::is_none")]
     pub player_v1_support: Option<PlayerV1Support>,
+    /// Controller capabilities (if client supports controller@v1 role)
+    #[serde(
+        rename = "controller@v1_support",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub controller_v1_support: Option<ControllerV1Support>,
     /// Artwork capabilities (if client supports artwork@v1 role)
     #[serde(rename = "artwork@v1_support", skip_serializing_if = "Option::is_none")]
     pub artwork_v1_support: Option<ArtworkV1Support>,
     /// Visualizer capabilities (if client supports visualizer@v1 role)
-    #[serde(rename = "visualizer@v1_support", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "visualizer@v1_support",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub visualizer_v1_support: Option<VisualizerV1Support>,
+    /// Payload ciphers the client can decrypt
+    ///
+    /// Extension field: servers that do not know it ignore it.
+    #[serde(
+        rename = "x_payload_encryption",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub payload_encryption: Vec<String>,
+    /// Binary message encodings the client can use instead of JSON
+    ///
+    /// Extension field: servers that do not know it ignore it.
+    #[serde(rename = "x_encodings", default, skip_serializing_if = "Vec::is_empty")]
+    pub encodings: Vec<String>,
+}
+
+impl ClientHello {
+    /// A protocol version 1 hello with no capability blocks or extensions
+    pub fn new(
+        client_id: impl Into<String>,
+        name: impl Into<String>,
+        supported_roles: Vec<Role>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            name: name.into(),
+            version: 1,
+            supported_roles,
+            device_info: None,
+            player_v1_support: None,
+            controller_v1_support: None,
+            artwork_v1_support: None,
+            visualizer_v1_support: None,
+            payload_encryption: Vec::new(),
+            encodings: Vec::new(),
+        }
+    }
+
+    /// Describe the device
+    pub fn with_device_info(mut self, device_info: DeviceInfo) -> Self {
+        self.device_info = Some(device_info);
+        self
+    }
+
+    /// Declare player@v1 capabilities
+    pub fn with_player_support(mut self, support: PlayerV1Support) -> Self {
+        self.player_v1_support = Some(support);
+        self
+    }
 }
 
 /// Device information (all fields optional per spec)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeviceInfo {
     /// Product name (e.g., "Sendspin-RS Player")
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -116,6 +296,7 @@ pub struct DeviceInfo {
 
 /// Player@v1 capabilities
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlayerV1Support {
     /// List of supported audio formats
     pub supported_formats: Vec<AudioFormatSpec>,
@@ -125,8 +306,20 @@ pub struct PlayerV1Support {
     pub supported_commands: Vec<String>,
 }
 
+/// Controller@v1 capabilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ControllerV1Support {
+    /// Commands the controller can send
+    pub supported_commands: Vec<ControllerCommandKind>,
+    /// Whether the controller shows now-playing metadata
+    #[serde(default)]
+    pub displays_metadata: bool,
+}
+
 /// Audio format specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AudioFormatSpec {
     /// Codec name (e.g., "pcm", "opus", "flac")
     pub codec: String,
@@ -136,10 +329,14 @@ pub struct AudioFormatSpec {
     pub sample_rate: u32,
     /// Bit depth per sample
     pub bit_depth: u8,
+    /// Byte order of PCM samples; unstated means little-endian
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endian: Option<PcmEndian>,
 }
 
 /// Artwork@v1 capabilities
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ArtworkV1Support {
     /// Supported artwork channels (0-3)
     pub channels: Vec<u8>,
@@ -147,6 +344,7 @@ pub struct ArtworkV1Support {
 
 /// Visualizer@v1 capabilities
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct VisualizerV1Support {
     /// Buffer capacity for visualization data
     pub buffer_capacity: u32,
@@ -154,6 +352,7 @@ pub struct VisualizerV1Support {
 
 /// Server hello message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ServerHello {
     /// Unique server identifier
     pub server_id: String,
@@ -162,13 +361,61 @@ pub struct ServerHello {
     /// Protocol version number
     pub version: u32,
     /// List of roles activated by server for this client
-    pub active_roles: Vec<String>,
+    pub active_roles: Vec<Role>,
     /// Reason for connection: 'discovery' or 'playback'
     pub connection_reason: ConnectionReason,
+    /// Payload encryption chosen for this connection's binary frames
+    ///
+    /// Extension field, only sent when the client offered a cipher.
+    #[serde(
+        rename = "x_payload_encryption",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub payload_encryption: Option<PayloadEncryptionParams>,
+    /// Message encoding both ends use after the hello exchange (JSON if absent)
+    ///
+    /// Extension field, only sent when the server picked one of the client's encodings.
+    #[serde(
+        rename = "x_encoding",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub encoding: Option<String>,
+}
+
+impl ServerHello {
+    /// A protocol version 1 playback hello with no extensions
+    pub fn new(
+        server_id: impl Into<String>,
+        name: impl Into<String>,
+        active_roles: Vec<Role>,
+    ) -> Self {
+        Self {
+            server_id: server_id.into(),
+            name: name.into(),
+            version: 1,
+            active_roles,
+            connection_reason: ConnectionReason::Playback,
+            payload_encryption: None,
+            encoding: None,
+        }
+    }
+}
+
+/// Payload encryption parameters confirmed by the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PayloadEncryptionParams {
+    /// Cipher name (e.g., "xchacha20poly1305")
+    pub cipher: String,
+    /// Per-connection nonce salt, hex encoded
+    pub salt: String,
 }
 
 /// Connection reason enum
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ConnectionReason {
     /// Server connected for discovery/announcement
@@ -183,6 +430,7 @@ pub enum ConnectionReason {
 
 /// Client time sync message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientTime {
     /// Client transmission timestamp (Unix microseconds)
     pub client_transmitted: i64,
@@ -190,6 +438,7 @@ pub struct ClientTime {
 
 /// Server time sync response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ServerTime {
     /// Original client transmission timestamp
     pub client_transmitted: i64,
@@ -205,6 +454,7 @@ pub struct ServerTime {
 
 /// Client state update message (wraps role-specific state)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientState {
     /// Player state (if player role active)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -213,6 +463,7 @@ pub struct ClientState {
 
 /// Player state
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlayerState {
     /// Sync state: "synchronized" or "error"
     pub state: PlayerSyncState,
@@ -222,10 +473,20 @@ pub struct PlayerState {
     /// Whether audio is muted
     #[serde(skip_serializing_if = "Option::is_none")]
     pub muted: Option<bool>,
+    /// Audio buffered ahead of playback, in milliseconds
+    ///
+    /// Extension field; servers that do not know it ignore it.
+    #[serde(
+        rename = "x_buffered_ms",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub buffered_ms: Option<u32>,
 }
 
 /// Player synchronization state
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum PlayerSyncState {
     /// Player is synchronized with server clock
@@ -236,6 +497,7 @@ pub enum PlayerSyncState {
 
 /// Server state update message (metadata and controller info)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ServerState {
     /// Metadata state (track info, progress, etc.)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -247,6 +509,7 @@ pub struct ServerState {
 
 /// Metadata state from server
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MetadataState {
     /// Server timestamp for progress calculation (microseconds)
     pub timestamp: i64,
@@ -277,10 +540,80 @@ pub struct MetadataState {
     /// Shuffle state
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shuffle: Option<bool>,
+    /// Genre
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    /// Composer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub composer: Option<String>,
+    /// Disc number within a multi-disc release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disc_number: Option<u32>,
+    /// Kind of content playing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<ContentType>,
+    /// Where the content comes from (e.g., a streaming service or station name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Lyrics for the current track
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lyrics: Option<Lyrics>,
+}
+
+/// Kind of content described by metadata
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    /// Music track
+    Music,
+    /// Podcast episode
+    Podcast,
+    /// Audiobook chapter
+    Audiobook,
+    /// Live radio
+    Radio,
+    /// Content type not known to this version
+    #[serde(other)]
+    Other,
+}
+
+/// Lyrics attached to track metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Lyrics {
+    /// Language tag (e.g., "en")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Unsynchronized lyrics text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Timestamped lines, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<LyricLine>,
+}
+
+impl Lyrics {
+    /// The line being sung at a track position (microseconds), if any has started
+    pub fn line_at(&self, position: i64) -> Option<&LyricLine> {
+        let started = self.lines.partition_point(|line| line.time <= position);
+        started.checked_sub(1).map(|i| &self.lines[i])
+    }
+}
+
+/// A single timestamped lyric line
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LyricLine {
+    /// Track position at which the line starts (microseconds)
+    pub time: i64,
+    /// Line text
+    pub text: String,
 }
 
 /// Track progress information
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TrackProgress {
     /// Current position in microseconds
     pub position: i64,
@@ -293,6 +626,7 @@ pub struct TrackProgress {
 
 /// Repeat mode
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum RepeatMode {
     /// No repeat
@@ -305,6 +639,7 @@ pub enum RepeatMode {
 
 /// Controller state from server
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ControllerState {
     /// List of supported commands
     pub supported_commands: Vec<String>,
@@ -320,6 +655,7 @@ pub struct ControllerState {
 
 /// Server command message (wraps role-specific commands)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ServerCommand {
     /// Player command (if targeting player role)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -328,19 +664,93 @@ pub struct ServerCommand {
 
 /// Player-specific command from server
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlayerCommand {
-    /// Command name (e.g., "play", "pause", "stop")
-    pub command: String,
+    /// Command to execute
+    pub command: PlayerCommandKind,
     /// Optional volume level (0-100)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<u8>,
     /// Optional mute state
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mute: Option<bool>,
+    /// Target position in microseconds for seek command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    /// Relative offset in microseconds for seek command (negative seeks back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+}
+
+impl PlayerCommand {
+    /// A command without arguments (play, pause, stop)
+    pub fn new(command: PlayerCommandKind) -> Self {
+        Self {
+            command,
+            volume: None,
+            mute: None,
+            position: None,
+            offset: None,
+        }
+    }
+
+    /// Set the player volume (0-100)
+    pub fn volume(volume: u8) -> Self {
+        Self {
+            volume: Some(volume),
+            ..Self::new(PlayerCommandKind::Volume)
+        }
+    }
+
+    /// Set the player mute state
+    pub fn mute(mute: bool) -> Self {
+        Self {
+            mute: Some(mute),
+            ..Self::new(PlayerCommandKind::Mute)
+        }
+    }
+
+    /// Seek to a position in microseconds
+    pub fn seek(position_us: i64) -> Self {
+        Self {
+            position: Some(position_us),
+            ..Self::new(PlayerCommandKind::Seek)
+        }
+    }
+
+    /// Seek relative to the current position by `offset_us` microseconds
+    pub fn seek_by(offset_us: i64) -> Self {
+        Self {
+            offset: Some(offset_us),
+            ..Self::new(PlayerCommandKind::Seek)
+        }
+    }
+}
+
+command_kind! {
+    /// Player command name
+    ///
+    /// Serialized as the plain command string. Names this crate does not know yet are
+    /// kept in `Other` so they round-trip unchanged.
+    PlayerCommandKind {
+        /// Start playback
+        Play => "play",
+        /// Pause playback
+        Pause => "pause",
+        /// Stop playback
+        Stop => "stop",
+        /// Set the volume (see `PlayerCommand::volume`)
+        Volume => "volume",
+        /// Set the mute state (see `PlayerCommand::mute`)
+        Mute => "mute",
+        /// Seek (see `PlayerCommand::position` and `PlayerCommand::offset`)
+        Seek => "seek",
+    }
 }
 
 /// Client command message (controller commands to server)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientCommand {
     /// Controller command
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -349,15 +759,129 @@ pub struct ClientCommand {
 
 /// Controller command from client
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ControllerCommand {
-    /// Command name (play, pause, stop, next, previous, volume, mute, etc.)
-    pub command: String,
+    /// Command to execute
+    pub command: ControllerCommandKind,
     /// Optional volume level (0-100) for volume command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<u8>,
     /// Optional mute state for mute command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mute: Option<bool>,
+    /// Target position in microseconds for seek command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<i64>,
+    /// Relative offset in microseconds for seek command (negative seeks back)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Target group for the join group command
+    #[serde(
+        rename = "x_group_id",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub group_id: Option<String>,
+}
+
+impl ControllerCommand {
+    /// A command without arguments (play, pause, next, ...)
+    pub fn new(command: ControllerCommandKind) -> Self {
+        Self {
+            command,
+            volume: None,
+            mute: None,
+            position: None,
+            offset: None,
+            group_id: None,
+        }
+    }
+
+    /// Set the group volume (0-100)
+    pub fn volume(volume: u8) -> Self {
+        Self {
+            volume: Some(volume),
+            ..Self::new(ControllerCommandKind::Volume)
+        }
+    }
+
+    /// Set the group mute state
+    pub fn mute(mute: bool) -> Self {
+        Self {
+            mute: Some(mute),
+            ..Self::new(ControllerCommandKind::Mute)
+        }
+    }
+
+    /// Seek the current track to a position in microseconds
+    pub fn seek(position_us: i64) -> Self {
+        Self {
+            position: Some(position_us),
+            ..Self::new(ControllerCommandKind::Seek)
+        }
+    }
+
+    /// Seek the current track relative to its position by `offset_us` microseconds
+    pub fn seek_by(offset_us: i64) -> Self {
+        Self {
+            offset: Some(offset_us),
+            ..Self::new(ControllerCommandKind::Seek)
+        }
+    }
+
+    /// Move the sending client into another group
+    pub fn join_group(group_id: impl Into<String>) -> Self {
+        Self {
+            group_id: Some(group_id.into()),
+            ..Self::new(ControllerCommandKind::JoinGroup)
+        }
+    }
+
+    /// Move the sending client back to the server's default group
+    pub fn leave_group() -> Self {
+        Self::new(ControllerCommandKind::LeaveGroup)
+    }
+}
+
+command_kind! {
+    /// Controller command name
+    ///
+    /// Serialized as the plain command string. Names this crate does not know yet are
+    /// kept in `Other` so they round-trip unchanged.
+    ControllerCommandKind {
+        /// Start playback
+        Play => "play",
+        /// Pause playback
+        Pause => "pause",
+        /// Stop playback
+        Stop => "stop",
+        /// Skip to the next track
+        Next => "next",
+        /// Go back to the previous track
+        Previous => "previous",
+        /// Set the group volume (see `ControllerCommand::volume`)
+        Volume => "volume",
+        /// Set the group mute state (see `ControllerCommand::mute`)
+        Mute => "mute",
+        /// Seek within the current track (see `ControllerCommand::seek`)
+        Seek => "seek",
+        /// Turn repeat off
+        RepeatOff => "repeat_off",
+        /// Repeat the current track
+        RepeatOne => "repeat_one",
+        /// Repeat all tracks
+        RepeatAll => "repeat_all",
+        /// Enable shuffle
+        Shuffle => "shuffle",
+        /// Disable shuffle
+        Unshuffle => "unshuffle",
+        /// Switch to another group
+        Switch => "switch",
+        /// Join a specific group (see `ControllerCommand::join_group`)
+        JoinGroup => "x_join_group",
+        /// Leave the current group for the default group
+        LeaveGroup => "x_leave_group",
+    }
 }
 
 // =============================================================================
@@ -366,6 +890,7 @@ pub struct ControllerCommand {
 
 /// Stream start message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamStart {
     /// Player stream configuration (optional - only if player role active)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -380,6 +905,7 @@ pub struct StreamStart {
 
 /// Stream player configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamPlayerConfig {
     /// Audio codec name
     pub codec: String,
@@ -392,39 +918,126 @@ pub struct StreamPlayerConfig {
     /// Optional codec-specific header (base64 encoded)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codec_header: Option<String>,
+    /// Byte order of PCM samples ("little" or "big"), if the server states it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endian: Option<PcmEndian>,
+}
+
+impl StreamPlayerConfig {
+    /// Attach a codec header, base64 encoding it
+    pub fn with_codec_header(mut self, header: &[u8]) -> Self {
+        self.codec_header = Some(base64::encode(header));
+        self
+    }
+
+    /// The decoded codec header, if the server sent one
+    pub fn codec_header_bytes(&self) -> Result<Option<Vec<u8>>, Error> {
+        self.codec_header
+            .as_deref()
+            .map(|encoded| {
+                base64::decode(encoded)
+                    .ok_or_else(|| Error::Protocol("codec_header is not valid base64".to_string()))
+            })
+            .transpose()
+    }
+
+    /// The audio format this stream is sent in, with the codec header decoded
+    ///
+    /// Fails for codec names the crate does not know and for malformed headers;
+    /// whether a decoder for the codec is compiled in is up to the caller.
+    pub fn audio_format(&self) -> Result<AudioFormat, Error> {
+        let codec = Codec::from_name(&self.codec)
+            .ok_or_else(|| Error::Protocol(format!("unknown codec '{}'", self.codec)))?;
+        Ok(AudioFormat {
+            codec,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            bit_depth: self.bit_depth,
+            codec_header: self.codec_header_bytes()?,
+            endian: self.endian,
+        })
+    }
 }
 
 /// Stream artwork configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamArtworkConfig {
     /// Active artwork channels
     pub channels: Vec<u8>,
 }
 
 /// Stream visualizer configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Every field defaults when absent, so a partial or differently shaped block
+/// never rejects the stream/start carrying it; frames are then skipped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamVisualizerConfig {
-    // FFT details TBD per spec
+    /// Number of FFT bins in each visualizer frame (0 if not stated)
+    #[serde(default)]
+    pub bins: u16,
+    /// Visualizer frames per second (0 if not stated)
+    #[serde(default)]
+    pub rate_hz: u16,
+    /// Window function applied before the FFT
+    #[serde(default)]
+    pub window: FftWindow,
+    /// Lower edge of the first bin in Hz
+    #[serde(default)]
+    pub min_frequency_hz: f32,
+    /// Upper edge of the last bin in Hz (0 if not stated)
+    #[serde(default)]
+    pub max_frequency_hz: f32,
+}
+
+impl StreamVisualizerConfig {
+    /// Time between visualizer frames, if the rate is non-zero
+    pub fn frame_interval(&self) -> Option<std::time::Duration> {
+        (self.rate_hz > 0).then(|| std::time::Duration::from_secs_f64(1.0 / self.rate_hz as f64))
+    }
+}
+
+/// FFT window function used for visualizer data
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FftWindow {
+    /// Hann window
+    #[default]
+    Hann,
+    /// Hamming window
+    Hamming,
+    /// Blackman window
+    Blackman,
+    /// No window
+    Rectangular,
+    /// Window not known to this version
+    #[serde(other)]
+    Other,
 }
 
 /// Stream end message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamEnd {
     /// Roles for which streaming has ended (optional, all if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub roles: Option<Vec<String>>,
+    pub roles: Option<Vec<Role>>,
 }
 
 /// Stream clear message (clear buffers)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamClear {
     /// Roles for which buffers should be cleared (optional, all if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub roles: Option<Vec<String>>,
+    pub roles: Option<Vec<Role>>,
 }
 
 /// Stream format request from client
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StreamRequestFormat {
     /// Requested player format
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -436,6 +1049,7 @@ pub struct StreamRequestFormat {
 
 /// Player format request
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlayerFormatRequest {
     /// Preferred codec
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -449,10 +1063,14 @@ pub struct PlayerFormatRequest {
     /// Preferred bit depth
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bit_depth: Option<u8>,
+    /// Preferred byte order of PCM samples
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endian: Option<PcmEndian>,
 }
 
 /// Artwork format request
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ArtworkFormatRequest {
     /// Artwork channel to request
     pub channel: u8,
@@ -476,6 +1094,7 @@ pub struct ArtworkFormatRequest {
 
 /// Group update notification
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GroupUpdate {
     /// Current playback state of the group
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -486,10 +1105,30 @@ pub struct GroupUpdate {
     /// Human-readable group name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub group_name: Option<String>,
+    /// Clients currently in the group
+    #[serde(rename = "x_members", default, skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<GroupMember>>,
+}
+
+/// A client in a group, as listed in group/update
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GroupMember {
+    /// Client identifier
+    pub client_id: String,
+    /// Human-readable client name
+    pub name: String,
+    /// Volume last reported by the client (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u8>,
+    /// Mute state last reported by the client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub muted: Option<bool>,
 }
 
 /// Group playback state
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum PlaybackState {
     /// Audio is playing
@@ -506,13 +1145,91 @@ pub enum PlaybackState {
 
 /// Client goodbye message
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientGoodbye {
     /// Reason for disconnection
     pub reason: GoodbyeReason,
+    /// Opt-in session quality summary
+    ///
+    /// Extension field: servers that do not know it ignore it.
+    #[serde(
+        rename = "x_telemetry",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub telemetry: Option<SessionTelemetry>,
+}
+
+impl ClientGoodbye {
+    /// A goodbye without telemetry
+    pub fn new(reason: GoodbyeReason) -> Self {
+        Self {
+            reason,
+            telemetry: None,
+        }
+    }
+
+    /// Attach a session quality summary
+    pub fn with_telemetry(mut self, telemetry: SessionTelemetry) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+}
+
+impl From<GoodbyeReason> for ClientGoodbye {
+    fn from(reason: GoodbyeReason) -> Self {
+        Self::new(reason)
+    }
+}
+
+/// Client-side playback quality over one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionTelemetry {
+    /// Session length in seconds
+    #[serde(default)]
+    pub duration_secs: u64,
+    /// Times the output ran out of audio
+    #[serde(default)]
+    pub underruns: u64,
+    /// Chunks dropped for arriving too late
+    #[serde(default)]
+    pub dropped_chunks: u64,
+    /// Reconnects to the server during the session
+    #[serde(default)]
+    pub reconnects: u32,
+    /// Mean clock sync round-trip time in microseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avg_rtt_micros: Option<i64>,
+}
+
+/// Server goodbye message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ServerGoodbye {
+    /// Reason the server is disconnecting
+    pub reason: ServerGoodbyeReason,
+}
+
+/// Server goodbye reason
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ServerGoodbyeReason {
+    /// Server is shutting down
+    Shutdown,
+    /// Server is restarting and will be back shortly
+    Restart,
+    /// Server dropped this client (e.g., replaced by a newer connection)
+    ClientRemoved,
+    /// Reason not known to this version
+    #[serde(other)]
+    Other,
 }
 
 /// Goodbye reason
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum GoodbyeReason {
     /// Switching to another server
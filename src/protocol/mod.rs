@@ -1,10 +1,100 @@
 // ABOUTME: Protocol implementation for Sendspin WebSocket protocol
 // ABOUTME: Message types, serialization, and WebSocket client
 
+/// Bounded frame channels with a backpressure policy
+pub mod backpressure;
+/// Base64 for codec headers and proxy credentials
+pub(crate) mod base64;
+/// Protocol client over the browser WebSocket API
+#[cfg(all(target_arch = "wasm32", feature = "browser"))]
+pub mod browser;
+/// Builder for client connections
+pub mod builder;
 /// WebSocket client implementation
 pub mod client;
+/// Coalescing of rapid outgoing updates
+pub mod coalesce;
+/// Spec-compliance mode (strict or lenient)
+pub mod compliance;
+/// Negotiable message encodings (JSON, MessagePack, CBOR)
+pub mod encoding;
+/// Optional payload encryption for binary frames
+pub mod encryption;
+/// Event-driven client handler trait
+pub mod handler;
+/// WebSocket pings and dead-server detection
+pub mod keepalive;
 /// Protocol message type definitions and serialization
 pub mod messages;
+/// Server list with automatic failover
+pub mod pool;
+/// HTTP CONNECT and SOCKS5 proxy tunnels
+pub mod proxy;
+/// Redaction of sensitive fields in protocol logs
+pub mod redact;
+/// Automatic client/state reporting
+pub mod reporter;
+/// Extra headers and query parameters for the WebSocket handshake
+pub mod request;
+/// Typed versioned roles
+pub mod role;
+/// JSON Schema and TypeScript export of the message types
+#[cfg(feature = "schema")]
+pub mod schema;
+/// Handshake outcome and activated roles
+pub mod session;
+/// Counters of received frames
+pub mod stats;
+/// Negotiated stream format tracking
+pub mod streams;
+/// Opt-in strict parsing that reports unknown fields
+pub mod strict;
+/// Raw WebSocket frame tap
+pub mod tap;
+/// Opt-in session quality telemetry
+pub mod telemetry;
+/// TLS options for wss:// connections
+#[cfg(feature = "tls")]
+pub mod tls;
+/// Transports the client connection runs over
+pub mod transport;
+/// Spec invariant checks for protocol messages
+pub mod validate;
+/// Protocol version negotiation and feature gating
+pub mod version;
+/// Typed visualizer frames
+pub mod visualizer;
 
-pub use client::WsSender;
+pub use backpressure::{
+    BackpressurePolicy, ChannelConfig, DroppedFrames, FrameReceiver, StreamChannels,
+};
+#[cfg(all(target_arch = "wasm32", feature = "browser"))]
+pub use browser::BrowserClient;
+pub use builder::{ClientBuilder, ReconnectPolicy};
+pub use client::{
+    ClientConfig, ClientParts, MessageHook, MessageReceiver, ReceivedMessage, WsSender,
+};
+pub use coalesce::Coalescing;
+pub use compliance::SpecCompliance;
+pub use encoding::MessageEncoding;
+pub use encryption::{PayloadCipher, PayloadKey};
+pub use handler::{ClientContext, ClientHandler};
+pub use keepalive::Keepalive;
 pub use messages::Message;
+pub use pool::ServerPool;
+pub use proxy::{ProxyConfig, ProxyKind};
+pub use reporter::StateReporter;
+pub use request::HandshakeRequest;
+pub use role::{Role, RoleList};
+pub use session::Session;
+pub use stats::FrameStats;
+pub use streams::{CurrentStream, StreamTracker};
+pub use tap::{FrameTap, RawFrame};
+pub use telemetry::TelemetryRecorder;
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
+#[cfg(unix)]
+pub use transport::UnixTransport;
+pub use transport::{Connection, StreamTransport, Transport, WebSocketTransport};
+pub use version::{Feature, PROTOCOL_VERSION};
+pub use visualizer::{VisualizerFrame, VisualizerStream};
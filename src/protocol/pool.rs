@@ -0,0 +1,204 @@
+// ABOUTME: Ordered list of servers with automatic failover for the client connection
+// ABOUTME: Connects to the first reachable server and moves on when the connection dies
+
+use crate::error::Error;
+use crate::events::ConnectionStatus;
+use crate::protocol::builder::ReconnectPolicy;
+use crate::protocol::client::{ClientConfig, Handshake, Link, LinkEvent, ProtocolClient};
+use crate::protocol::messages::ClientHello;
+use crate::protocol::transport::WebSocketTransport;
+use std::time::Duration;
+
+/// Servers in order of preference, with failover to the next one
+///
+/// [`connect`](Self::connect) returns a client for the first server that completes
+/// the handshake. When that connection ends without the client closing it (the
+/// server went away, said goodbye, or the [`ClientConfig::keepalive`] watchdog gave
+/// up), the pool connects to the next server in the list, wrapping around, and
+/// repeats the handshake including [`ClientConfig::initial_state`].
+///
+/// The client's receivers and senders keep working across a failover. While it
+/// runs, [`ProtocolClient::connection_status`] reports
+/// [`Reconnecting`](ConnectionStatus::Reconnecting); then the receivers get a
+/// stream/end for every role, the clock sync starts over, and
+/// [`ProtocolClient::watch_session`] reports the new server. A standby server has
+/// to negotiate the same protocol version and message encoding as the first one.
+///
+/// ```no_run
+/// # async fn example(hello: sendspin::protocol::messages::ClientHello) -> Result<(), sendspin::Error> {
+/// use sendspin::protocol::ServerPool;
+///
+/// let client = ServerPool::new(["ws://main.local:8927/sendspin", "ws://standby.local:8927/sendspin"])
+///     .hello(hello)
+///     .connect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServerPool {
+    urls: Vec<String>,
+    hello: Option<ClientHello>,
+    config: ClientConfig,
+    connect_timeout: Option<Duration>,
+    retry: ReconnectPolicy,
+}
+
+impl ServerPool {
+    /// Connect to these server URLs, most preferred first
+    pub fn new<I>(urls: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self {
+            urls: urls.into_iter().map(Into::into).collect(),
+            hello: None,
+            config: ClientConfig::default(),
+            connect_timeout: None,
+            retry: ReconnectPolicy::exponential(3),
+        }
+    }
+
+    /// The client/hello to send to every server (required)
+    pub fn hello(mut self, hello: ClientHello) -> Self {
+        self.hello = Some(hello);
+        self
+    }
+
+    /// Connection options used for every server
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Move on from a server that has not completed the handshake within `timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// How often to go through the whole list before giving up
+    ///
+    /// Each attempt of the policy tries every server once; the delays apply
+    /// between passes. Three passes by default.
+    pub fn retry(mut self, policy: ReconnectPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// The server URLs, most preferred first
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Connect to the first reachable server and keep failing over until the
+    /// client is closed or no server is reachable
+    pub async fn connect(self) -> Result<ProtocolClient, Error> {
+        let hello = self
+            .hello
+            .clone()
+            .ok_or_else(|| Error::Protocol("ServerPool needs a client/hello".to_string()))?;
+        let (active, handshake) = self.connect_any(&hello, 0, |_| Ok(())).await?;
+        let (client, link) = ProtocolClient::start(handshake, self.config.clone());
+        tokio::spawn(self.supervise(hello, active, link));
+        Ok(client)
+    }
+
+    /// Fail over whenever the connection ends, until the client closes it
+    async fn supervise(self, hello: ClientHello, mut active: usize, mut link: Link) {
+        loop {
+            let outputs = match link.next_event().await {
+                LinkEvent::Ended(Some(outputs)) => outputs,
+                LinkEvent::Ended(None) => return,
+                LinkEvent::Switch(request) => {
+                    link.switch(request).await;
+                    continue;
+                }
+            };
+            if link.closed_by_client().await {
+                link.finish(outputs);
+                return;
+            }
+            log::warn!("Connection to {} ended, failing over", self.urls[active]);
+            link.set_status(ConnectionStatus::Reconnecting);
+            outputs.reset().await;
+            match self
+                .connect_any(&hello, active + 1, |handshake| link.accepts(handshake))
+                .await
+            {
+                Ok((index, handshake)) => {
+                    active = index;
+                    if !link.replace(handshake, outputs).await {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    log::error!("No server reachable, giving up: {}", e);
+                    link.finish(outputs);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Try every server, starting at index `first`, until one completes the
+    /// handshake and passes `check`; returns its index and the handshake
+    async fn connect_any(
+        &self,
+        hello: &ClientHello,
+        first: usize,
+        check: impl Fn(&Handshake) -> Result<(), Error>,
+    ) -> Result<(usize, Handshake), Error> {
+        let mut last_error = Error::Connection("ServerPool has no servers".to_string());
+        if self.urls.is_empty() {
+            return Err(last_error);
+        }
+        let passes = self.retry.max_attempts.max(1);
+        for pass in 1..=passes {
+            if pass > 1 {
+                tokio::time::sleep(self.retry.delay(pass - 1)).await;
+            }
+            for offset in 0..self.urls.len() {
+                let index = (first + offset) % self.urls.len();
+                let url = &self.urls[index];
+                match self
+                    .attempt(url, hello)
+                    .await
+                    .and_then(|handshake| check(&handshake).map(|()| handshake))
+                {
+                    Ok(handshake) => {
+                        log::info!(
+                            "Connected to {} ({})",
+                            url,
+                            handshake.session().server_name()
+                        );
+                        return Ok((index, handshake));
+                    }
+                    Err(e) => {
+                        log::warn!("Could not use {}: {}", url, e);
+                        last_error = e;
+                    }
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Connect to one server and complete the handshake
+    async fn attempt(&self, url: &str, hello: &ClientHello) -> Result<Handshake, Error> {
+        let handshake =
+            ProtocolClient::handshake(WebSocketTransport::new(url), hello.clone(), &self.config);
+        match self.connect_timeout {
+            Some(limit) => tokio::time::timeout(limit, handshake)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(Error::Connection(format!(
+                        "Timed out after {:?} connecting to {}",
+                        limit, url
+                    )))
+                }),
+            None => handshake.await,
+        }
+    }
+}
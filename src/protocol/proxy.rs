@@ -0,0 +1,270 @@
+// ABOUTME: HTTP CONNECT and SOCKS5 proxies for reaching the server through a jump host
+// ABOUTME: Opens the tunnel; the WebSocket (and TLS) handshake then runs through it
+
+use super::base64;
+use crate::error::Error;
+use std::net::IpAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Proxy protocol spoken to the jump host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// HTTP proxy with the CONNECT method
+    HttpConnect,
+    /// SOCKS5 proxy; the server host name is resolved by the proxy
+    Socks5,
+}
+
+/// Proxy to tunnel the server connection through
+///
+/// ```
+/// use sendspin::protocol::ProxyConfig;
+///
+/// let proxy = ProxyConfig::socks5("jump.example.net:1080").with_credentials("me", "secret");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    kind: ProxyKind,
+    addr: String,
+    credentials: Option<(String, String)>,
+}
+
+impl ProxyConfig {
+    /// An HTTP proxy at `addr` (`host:port`), using CONNECT
+    pub fn http(addr: impl Into<String>) -> Self {
+        Self {
+            kind: ProxyKind::HttpConnect,
+            addr: addr.into(),
+            credentials: None,
+        }
+    }
+
+    /// A SOCKS5 proxy at `addr` (`host:port`)
+    pub fn socks5(addr: impl Into<String>) -> Self {
+        Self {
+            kind: ProxyKind::Socks5,
+            addr: addr.into(),
+            credentials: None,
+        }
+    }
+
+    /// Authenticate to the proxy (HTTP Basic or SOCKS5 username/password)
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Proxy protocol
+    pub fn kind(&self) -> ProxyKind {
+        self.kind
+    }
+
+    /// Proxy address
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Open a TCP tunnel to `host:port` through the proxy
+    pub(crate) async fn tunnel(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        let mut stream = TcpStream::connect(&self.addr)
+            .await
+            .map_err(|e| self.error(e.to_string()))?;
+        let result = match self.kind {
+            ProxyKind::HttpConnect => self.http_connect(&mut stream, host, port).await,
+            ProxyKind::Socks5 => self.socks5_connect(&mut stream, host, port).await,
+        };
+        match result {
+            Ok(()) => Ok(stream),
+            Err(ProxyError::Io(e)) => Err(self.error(e.to_string())),
+            Err(ProxyError::Refused(reason)) => Err(self.error(reason)),
+        }
+    }
+
+    async fn http_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), ProxyError> {
+        // IPv6 literals need brackets in the authority
+        let authority = match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, port),
+            _ => format!("{}:{}", host, port),
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+        if let Some((user, password)) = &self.credentials {
+            let token = base64::encode(format!("{}:{}", user, password).as_bytes());
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read the response head byte by byte, so nothing after it is consumed
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            if head.len() >= MAX_RESPONSE_HEAD {
+                return Err(ProxyError::Refused("response head too long".to_string()));
+            }
+            head.push(stream.read_u8().await?);
+        }
+        let head = String::from_utf8_lossy(&head);
+        let status = head.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            _ => Err(ProxyError::Refused(format!("CONNECT failed: {}", status))),
+        }
+    }
+
+    async fn socks5_connect(
+        &self,
+        stream: &mut TcpStream,
+        host: &str,
+        port: u16,
+    ) -> Result<(), ProxyError> {
+        let methods: &[u8] = match self.credentials {
+            Some(_) => &[SOCKS_NO_AUTH, SOCKS_USER_PASS],
+            None => &[SOCKS_NO_AUTH],
+        };
+        let mut greeting = vec![SOCKS_VERSION, methods.len() as u8];
+        greeting.extend_from_slice(methods);
+        stream.write_all(&greeting).await?;
+
+        let mut choice = [0u8; 2];
+        stream.read_exact(&mut choice).await?;
+        match (choice, &self.credentials) {
+            ([SOCKS_VERSION, SOCKS_NO_AUTH], _) => {}
+            ([SOCKS_VERSION, SOCKS_USER_PASS], Some((user, password))) => {
+                let (user, password) = (user.as_bytes(), password.as_bytes());
+                if user.len() > 255 || password.len() > 255 {
+                    return Err(ProxyError::Refused(
+                        "SOCKS5 credentials longer than 255 bytes".to_string(),
+                    ));
+                }
+                let mut auth = vec![1, user.len() as u8];
+                auth.extend_from_slice(user);
+                auth.push(password.len() as u8);
+                auth.extend_from_slice(password);
+                stream.write_all(&auth).await?;
+                let mut status = [0u8; 2];
+                stream.read_exact(&mut status).await?;
+                if status[1] != 0 {
+                    return Err(ProxyError::Refused(
+                        "SOCKS5 authentication failed".to_string(),
+                    ));
+                }
+            }
+            _ => {
+                return Err(ProxyError::Refused(
+                    "SOCKS5 proxy accepts none of the offered authentication methods".to_string(),
+                ))
+            }
+        }
+
+        let mut request = vec![SOCKS_VERSION, SOCKS_CONNECT, 0];
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(SOCKS_ADDR_IPV4);
+                request.extend_from_slice(&ip.octets());
+            }
+            Ok(IpAddr::V6(ip)) => {
+                request.push(SOCKS_ADDR_IPV6);
+                request.extend_from_slice(&ip.octets());
+            }
+            Err(_) => {
+                if host.len() > 255 {
+                    return Err(ProxyError::Refused(format!("host name too long: {}", host)));
+                }
+                request.push(SOCKS_ADDR_DOMAIN);
+                request.push(host.len() as u8);
+                request.extend_from_slice(host.as_bytes());
+            }
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0 {
+            return Err(ProxyError::Refused(format!(
+                "SOCKS5 connect failed: {}",
+                socks_reply(reply[1])
+            )));
+        }
+        // Skip the bound address and port
+        let addr_len = match reply[3] {
+            SOCKS_ADDR_IPV4 => 4,
+            SOCKS_ADDR_IPV6 => 16,
+            SOCKS_ADDR_DOMAIN => stream.read_u8().await? as usize,
+            other => {
+                return Err(ProxyError::Refused(format!(
+                    "SOCKS5 reply with unknown address type {}",
+                    other
+                )))
+            }
+        };
+        let mut bound = vec![0u8; addr_len + 2];
+        stream.read_exact(&mut bound).await?;
+        Ok(())
+    }
+
+    fn error(&self, reason: String) -> Error {
+        Error::Connection(format!("Proxy {}: {}", self.addr, reason))
+    }
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("kind", &self.kind)
+            .field("addr", &self.addr)
+            .field(
+                "credentials",
+                &self
+                    .credentials
+                    .as_ref()
+                    .map(|(user, _)| (user, "<redacted>")),
+            )
+            .finish()
+    }
+}
+
+/// Longest HTTP CONNECT response head accepted
+const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+
+const SOCKS_VERSION: u8 = 5;
+const SOCKS_NO_AUTH: u8 = 0;
+const SOCKS_USER_PASS: u8 = 2;
+const SOCKS_CONNECT: u8 = 1;
+const SOCKS_ADDR_IPV4: u8 = 1;
+const SOCKS_ADDR_DOMAIN: u8 = 3;
+const SOCKS_ADDR_IPV6: u8 = 4;
+
+enum ProxyError {
+    Io(std::io::Error),
+    Refused(String),
+}
+
+impl From<std::io::Error> for ProxyError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+fn socks_reply(code: u8) -> &'static str {
+    match code {
+        1 => "general failure",
+        2 => "connection not allowed by ruleset",
+        3 => "network unreachable",
+        4 => "host unreachable",
+        5 => "connection refused",
+        6 => "TTL expired",
+        7 => "command not supported",
+        8 => "address type not supported",
+        _ => "unknown error",
+    }
+}
@@ -0,0 +1,146 @@
+// ABOUTME: Redaction of sensitive fields in protocol logs
+// ABOUTME: Masks or hashes configured JSON fields before messages are logged
+
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// Fields redacted by [`Redactor::default`]
+pub const DEFAULT_FIELDS: &[&str] = &[
+    "client_id",
+    "server_id",
+    "name",
+    "group_name",
+    "product_name",
+    "manufacturer",
+    "token",
+    "authorization",
+];
+
+/// How redacted values are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactionMode {
+    /// Replace the value with "<redacted>"
+    Mask,
+    /// Replace the value with a short stable hash, so equal values stay correlatable
+    Hash,
+}
+
+/// Redacts configured fields from JSON log output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redactor {
+    fields: Vec<String>,
+    mode: RedactionMode,
+}
+
+impl Redactor {
+    /// Create a redactor for the given field names (masking values)
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+            mode: RedactionMode::Mask,
+        }
+    }
+
+    /// Set how redacted values are rendered
+    pub fn with_mode(mut self, mode: RedactionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Check if a field name is redacted
+    pub fn is_redacted(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f == field)
+    }
+
+    /// Render a single value as it should appear in logs
+    pub fn redact_value_str(&self, value: &str) -> String {
+        match self.mode {
+            RedactionMode::Mask => "<redacted>".to_string(),
+            RedactionMode::Hash => format!("#{:08x}", fnv1a(value.as_bytes()) as u32),
+        }
+    }
+
+    /// Redact matching fields anywhere in a JSON value
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    if self.is_redacted(key) && !v.is_object() && !v.is_array() {
+                        let original = match &*v {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        };
+                        *v = Value::String(self.redact_value_str(&original));
+                    } else {
+                        self.redact_value(v);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.redact_value(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact a JSON document; text that is not valid JSON is returned unchanged
+    pub fn redact_json<'a>(&self, json: &'a str) -> Cow<'a, str> {
+        match serde_json::from_str::<Value>(json) {
+            Ok(mut value) => {
+                self.redact_value(&mut value);
+                Cow::Owned(value.to_string())
+            }
+            Err(_) => Cow::Borrowed(json),
+        }
+    }
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_FIELDS.iter().copied())
+    }
+}
+
+static GLOBAL: RwLock<Option<Redactor>> = RwLock::new(None);
+
+/// Install (or remove, with None) the redactor used by the protocol client's logging
+pub fn set_global(redactor: Option<Redactor>) {
+    *GLOBAL.write() = redactor;
+}
+
+/// Get the currently installed redactor
+pub fn global() -> Option<Redactor> {
+    GLOBAL.read().clone()
+}
+
+/// Apply the installed redactor to JSON about to be logged
+pub fn for_log(json: &str) -> Cow<'_, str> {
+    match *GLOBAL.read() {
+        Some(ref redactor) => Cow::Owned(redactor.redact_json(json).into_owned()),
+        None => Cow::Borrowed(json),
+    }
+}
+
+/// Apply the installed redactor to a single named field about to be logged
+pub fn field_for_log<'a>(field: &str, value: &'a str) -> Cow<'a, str> {
+    match *GLOBAL.read() {
+        Some(ref redactor) if redactor.is_redacted(field) => {
+            Cow::Owned(redactor.redact_value_str(value))
+        }
+        _ => Cow::Borrowed(value),
+    }
+}
+
+/// 64-bit FNV-1a hash (stable across runs and platforms)
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
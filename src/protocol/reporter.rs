@@ -0,0 +1,153 @@
+// ABOUTME: Automatic client/state reporting for players
+// ABOUTME: Sends the player state when it changes and again at a fixed interval
+
+use crate::error::Error;
+use crate::protocol::client::WsSender;
+use crate::protocol::messages::{ClientState, Message, PlayerState, PlayerSyncState};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Default time between periodic client/state reports
+pub const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Keeps the server informed of this player's state
+///
+/// Holds the state the spec expects players to report: sync status, volume,
+/// mute, and (as an extension) how much audio is buffered. [`run`](Self::run)
+/// sends it right away, whenever the sync status, volume, or mute changes, and
+/// every [`interval`](Self::with_interval) in between. Buffer fill changes
+/// constantly, so it only goes out with the next report. Cloning is cheap; all
+/// clones share the same state.
+///
+/// ```no_run
+/// # async fn example(client: sendspin::ProtocolClient) {
+/// use sendspin::protocol::StateReporter;
+///
+/// let reporter = StateReporter::new();
+/// tokio::spawn(reporter.clone().run(client.sender()));
+/// let mut messages = client.into_parts().messages;
+/// while let Some(msg) = messages.recv().await {
+///     // Volume and mute commands from the server are reported back
+///     reporter.apply(&msg);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StateReporter {
+    state: Arc<watch::Sender<PlayerState>>,
+    interval: Duration,
+}
+
+impl StateReporter {
+    /// Report a synchronized player at full volume, unmuted
+    pub fn new() -> Self {
+        Self::with_state(PlayerState {
+            state: PlayerSyncState::Synchronized,
+            volume: Some(100),
+            muted: Some(false),
+            buffered_ms: None,
+        })
+    }
+
+    /// Start from `state` instead of the defaults
+    pub fn with_state(state: PlayerState) -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(state)),
+            interval: DEFAULT_REPORT_INTERVAL,
+        }
+    }
+
+    /// Repeat the report at this interval while nothing changes
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the sync status; a change is reported right away
+    pub fn set_sync_state(&self, sync: PlayerSyncState) {
+        self.state.send_if_modified(|state| {
+            let changed = state.state != sync;
+            state.state = sync;
+            changed
+        });
+    }
+
+    /// Set the volume (0-100); a change is reported right away
+    pub fn set_volume(&self, volume: u8) {
+        self.state.send_if_modified(|state| {
+            let changed = state.volume != Some(volume);
+            state.volume = Some(volume);
+            changed
+        });
+    }
+
+    /// Set the mute state; a change is reported right away
+    pub fn set_muted(&self, muted: bool) {
+        self.state.send_if_modified(|state| {
+            let changed = state.muted != Some(muted);
+            state.muted = Some(muted);
+            changed
+        });
+    }
+
+    /// Set how much audio is buffered; sent with the next report
+    pub fn set_buffered(&self, buffered: Duration) {
+        let ms = u32::try_from(buffered.as_millis()).unwrap_or(u32::MAX);
+        self.state.send_if_modified(|state| {
+            state.buffered_ms = Some(ms);
+            false
+        });
+    }
+
+    /// Take in a message from the server; volume and mute commands update the state
+    pub fn apply(&self, msg: &Message) {
+        let Message::ServerCommand(command) = msg else {
+            return;
+        };
+        let Some(ref player) = command.player else {
+            return;
+        };
+        if let Some(volume) = player.volume {
+            self.set_volume(volume);
+        }
+        if let Some(mute) = player.mute {
+            self.set_muted(mute);
+        }
+    }
+
+    /// The state the next report will carry
+    pub fn state(&self) -> PlayerState {
+        self.state.borrow().clone()
+    }
+
+    /// Report through `sender` until the connection closes
+    ///
+    /// Ends with the error of the first report that could not be sent.
+    pub async fn run(self, sender: WsSender) -> Result<(), Error> {
+        let mut changes = self.state.subscribe();
+        let mut ticks = tokio::time::interval(self.interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            // The first tick completes immediately, so the state goes out at once
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = changes.changed() => {}
+            }
+            let state = changes.borrow_and_update().clone();
+            sender
+                .send_message(Message::ClientState(ClientState {
+                    player: Some(state),
+                }))
+                .await?;
+            // A full interval of quiet before the next periodic report
+            ticks.reset();
+        }
+    }
+}
+
+impl Default for StateReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,108 @@
+// ABOUTME: Extra HTTP headers and query parameters for the WebSocket handshake
+// ABOUTME: For servers behind an authenticating reverse proxy (bearer tokens, cookies)
+
+use crate::error::Error;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+/// Additions to the HTTP upgrade request that opens the WebSocket
+///
+/// Header and query values usually carry credentials, so `Debug` only shows
+/// their names.
+///
+/// ```
+/// use sendspin::protocol::HandshakeRequest;
+///
+/// let request = HandshakeRequest::new()
+///     .bearer_token("eyJhbGciOi...")
+///     .header("Cookie", "session=abc")
+///     .query("room", "kitchen");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct HandshakeRequest {
+    headers: Vec<(String, String)>,
+    query: Vec<(String, String)>,
+}
+
+impl HandshakeRequest {
+    /// No extra headers or parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send an extra header; repeated names are all sent
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>`
+    pub fn bearer_token(self, token: impl AsRef<str>) -> Self {
+        self.header("Authorization", format!("Bearer {}", token.as_ref()))
+    }
+
+    /// Append a query parameter to the URL (percent-encoded)
+    pub fn query(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.query.push((key.into(), value.into()));
+        self
+    }
+
+    /// Whether nothing is added to the request
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.query.is_empty()
+    }
+
+    /// Build the upgrade request for `url`
+    pub(crate) fn build(&self, url: &str) -> Result<Request, Error> {
+        let mut url = url.to_string();
+        for (i, (key, value)) in self.query.iter().enumerate() {
+            let separator = if i == 0 && !url.contains('?') {
+                '?'
+            } else {
+                '&'
+            };
+            url.push(separator);
+            url.push_str(&percent_encode(key));
+            url.push('=');
+            url.push_str(&percent_encode(value));
+        }
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| Error::Connection(e.to_string()))?;
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| Error::Protocol(format!("Invalid header name: {}", name)))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| Error::Protocol(format!("Invalid value for header {}", name)))?;
+            request.headers_mut().append(name, value);
+        }
+        Ok(request)
+    }
+}
+
+impl std::fmt::Debug for HandshakeRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |pairs: &[(String, String)]| -> Vec<String> {
+            pairs.iter().map(|(name, _)| name.clone()).collect()
+        };
+        f.debug_struct("HandshakeRequest")
+            .field("headers", &names(&self.headers))
+            .field("query", &names(&self.query))
+            .finish()
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
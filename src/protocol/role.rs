@@ -0,0 +1,183 @@
+// ABOUTME: Typed Sendspin roles
+// ABOUTME: Parses and formats versioned role names such as "player@v1"
+
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+/// A versioned protocol role
+///
+/// Serializes as its wire name (`"player@v1"`). Names that are not a known role
+/// with a numeric version are kept in `Unknown` so they round-trip unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Role {
+    /// Plays synchronized audio
+    Player(u32),
+    /// Receives album artwork
+    Artwork(u32),
+    /// Controls group playback
+    Controller(u32),
+    /// Receives now-playing metadata
+    Metadata(u32),
+    /// Receives visualization data
+    Visualizer(u32),
+    /// Any other role name
+    Unknown(String),
+}
+
+impl Role {
+    /// Role name without the version (e.g., "player")
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Player(_) => "player",
+            Self::Artwork(_) => "artwork",
+            Self::Controller(_) => "controller",
+            Self::Metadata(_) => "metadata",
+            Self::Visualizer(_) => "visualizer",
+            Self::Unknown(name) => name.split('@').next().unwrap_or(name),
+        }
+    }
+
+    /// Role version, if known
+    pub fn version(&self) -> Option<u32> {
+        match self {
+            Self::Player(v)
+            | Self::Artwork(v)
+            | Self::Controller(v)
+            | Self::Metadata(v)
+            | Self::Visualizer(v) => Some(*v),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Whether the role name has the `name@vN` form required by the spec
+    ///
+    /// Known roles are always well formed; unknown names must be lowercase
+    /// letters, digits, `_` or `-`, followed by a numeric version.
+    pub fn is_well_formed(&self) -> bool {
+        let Self::Unknown(name) = self else {
+            return true;
+        };
+        name.split_once("@v").is_some_and(|(name, version)| {
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+                && !version.is_empty()
+                && version.chars().all(|c| c.is_ascii_digit())
+        })
+    }
+
+    /// Whether both roles have the same name, regardless of version
+    pub fn same_family(&self, other: &Role) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl FromStr for Role {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parsed = s.split_once("@v").and_then(|(name, version)| {
+            let version = version.parse().ok()?;
+            match name {
+                "player" => Some(Self::Player(version)),
+                "artwork" => Some(Self::Artwork(version)),
+                "controller" => Some(Self::Controller(version)),
+                "metadata" => Some(Self::Metadata(version)),
+                "visualizer" => Some(Self::Visualizer(version)),
+                _ => None,
+            }
+        });
+        Ok(parsed.unwrap_or_else(|| Self::Unknown(s.to_string())))
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.version()) {
+            (Self::Unknown(name), _) => f.write_str(name),
+            (_, Some(version)) => write!(f, "{}@v{}", self.name(), version),
+            (_, None) => f.write_str(self.name()),
+        }
+    }
+}
+
+impl From<&str> for Role {
+    fn from(name: &str) -> Self {
+        match name.parse() {
+            Ok(role) => role,
+            Err(never) => match never {},
+        }
+    }
+}
+
+impl From<String> for Role {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<Role> for String {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Unknown(name) => name,
+            known => known.to_string(),
+        }
+    }
+}
+
+impl PartialEq<str> for Role {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Self::Unknown(name) => name == other,
+            known => other.split_once("@v").is_some_and(|(name, version)| {
+                name == known.name() && version.parse().ok() == known.version()
+            }),
+        }
+    }
+}
+
+impl PartialEq<&str> for Role {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Role {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Role".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "pattern": "^[a-z0-9_-]+@v[0-9]+$",
+            "examples": ["player@v1", "artwork@v1", "controller@v1", "metadata@v1", "visualizer@v1"]
+        })
+    }
+}
+
+/// Lookups over a list of roles
+pub trait RoleList {
+    /// Whether the list contains exactly this role and version
+    fn contains_role(&self, role: Role) -> bool;
+
+    /// Version of the listed role in the same family as `role`, if any
+    fn version_of(&self, role: &Role) -> Option<u32>;
+}
+
+impl RoleList for [Role] {
+    fn contains_role(&self, role: Role) -> bool {
+        self.contains(&role)
+    }
+
+    fn version_of(&self, role: &Role) -> Option<u32> {
+        self.iter()
+            .filter(|r| r.same_family(role))
+            .find_map(Role::version)
+    }
+}
@@ -0,0 +1,163 @@
+// ABOUTME: Machine-readable export of the protocol message definitions
+// ABOUTME: Generates JSON Schema and TypeScript declarations from the Rust message types
+
+use crate::protocol::messages::Message;
+use serde_json::{Map, Value};
+use std::fmt::Write as _;
+
+/// JSON Schema (draft 2020-12) describing every protocol message
+///
+/// The root schema matches the `{"type": ..., "payload": ...}` envelope; each
+/// payload type is listed under `$defs`.
+pub fn json_schema() -> Value {
+    schemars::schema_for!(Message).to_value()
+}
+
+/// TypeScript declarations for every protocol message
+///
+/// Emits one exported type per schema definition plus a `Message` union of the
+/// envelope variants.
+pub fn typescript() -> String {
+    let schema = json_schema();
+    let mut out = String::from("// Generated from the sendspin Rust message types. Do not edit.\n");
+
+    let empty = Map::new();
+    let defs = schema
+        .get("$defs")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let mut names: Vec<&String> = defs.keys().collect();
+    names.sort();
+
+    emit_type(&mut out, "Message", &schema);
+    for name in names {
+        emit_type(&mut out, name, &defs[name]);
+    }
+    out
+}
+
+fn emit_type(out: &mut String, name: &str, schema: &Value) {
+    out.push('\n');
+    if let Some(doc) = schema.get("description").and_then(Value::as_str) {
+        write_doc(out, doc, "");
+    }
+    let _ = writeln!(out, "export type {} = {};", name, ts_type(schema, ""));
+}
+
+fn write_doc(out: &mut String, doc: &str, indent: &str) {
+    let _ = writeln!(out, "{}/**", indent);
+    for line in doc.lines() {
+        let _ = writeln!(out, "{} * {}", indent, line);
+    }
+    let _ = writeln!(out, "{} */", indent);
+}
+
+/// TypeScript type expression for a schema node
+fn ts_type(schema: &Value, indent: &str) -> String {
+    let Some(obj) = schema.as_object() else {
+        // `true` accepts anything
+        return "unknown".to_string();
+    };
+    if let Some(reference) = obj.get("$ref").and_then(Value::as_str) {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(value) = obj.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = obj.get("enum").and_then(Value::as_array) {
+        return union(values.iter().map(Value::to_string));
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(variants) = obj.get(key).and_then(Value::as_array) {
+            return union(variants.iter().map(|v| ts_type(v, indent)));
+        }
+    }
+    match obj.get("type") {
+        Some(Value::Array(types)) => union(types.iter().map(|t| {
+            let mut single = obj.clone();
+            single.insert("type".to_string(), t.clone());
+            ts_type(&Value::Object(single), indent)
+        })),
+        Some(Value::String(kind)) => match kind.as_str() {
+            // Open string sets list their known values but accept any string
+            "string" => match obj.get("examples").and_then(Value::as_array) {
+                Some(known) => union(
+                    known
+                        .iter()
+                        .map(Value::to_string)
+                        .chain(["(string & {})".to_string()]),
+                ),
+                None => "string".to_string(),
+            },
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => {
+                let item = obj
+                    .get("items")
+                    .map_or("unknown".to_string(), |items| ts_type(items, indent));
+                format!("Array<{}>", item)
+            }
+            "object" => ts_object(obj, indent),
+            _ => "unknown".to_string(),
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+fn ts_object(obj: &Map<String, Value>, indent: &str) -> String {
+    let Some(properties) = obj.get("properties").and_then(Value::as_object) else {
+        return "Record<string, unknown>".to_string();
+    };
+    if properties.is_empty() {
+        return "Record<string, never>".to_string();
+    }
+    let required: Vec<&str> = obj
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let inner = format!("{}  ", indent);
+    let mut out = String::from("{\n");
+    for (name, property) in properties {
+        if let Some(doc) = property.get("description").and_then(Value::as_str) {
+            write_doc(&mut out, doc, &inner);
+        }
+        let key = if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            name.clone()
+        } else {
+            format!("{:?}", name)
+        };
+        let optional = if required.contains(&name.as_str()) {
+            ""
+        } else {
+            "?"
+        };
+        let _ = writeln!(
+            out,
+            "{}{}{}: {};",
+            inner,
+            key,
+            optional,
+            ts_type(property, &inner)
+        );
+    }
+    out.push_str(indent);
+    out.push('}');
+    out
+}
+
+fn union(parts: impl Iterator<Item = String>) -> String {
+    let mut seen: Vec<String> = Vec::new();
+    for part in parts {
+        if !seen.contains(&part) {
+            seen.push(part);
+        }
+    }
+    seen.join(" | ")
+}
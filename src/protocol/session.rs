@@ -0,0 +1,82 @@
+// ABOUTME: Outcome of the hello exchange: server identity, version, and activated roles
+// ABOUTME: Lets applications check which requested roles the server refused
+
+use crate::protocol::messages::{ClientHello, ConnectionReason, ServerHello};
+use crate::protocol::role::{Role, RoleList};
+
+/// What the client and server agreed on during the handshake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    server_id: String,
+    server_name: String,
+    version: u32,
+    connection_reason: ConnectionReason,
+    active_roles: Vec<Role>,
+    requested_roles: Vec<Role>,
+    state_sent: bool,
+}
+
+impl Session {
+    /// Describe a completed hello exchange at the negotiated protocol `version`
+    pub fn new(hello: &ClientHello, server_hello: &ServerHello, version: u32) -> Self {
+        Self {
+            server_id: server_hello.server_id.clone(),
+            server_name: server_hello.name.clone(),
+            version,
+            connection_reason: server_hello.connection_reason.clone(),
+            active_roles: server_hello.active_roles.clone(),
+            requested_roles: hello.supported_roles.clone(),
+            state_sent: false,
+        }
+    }
+
+    /// Server identifier
+    pub fn server_id(&self) -> &str {
+        &self.server_id
+    }
+
+    /// Human-readable server name
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    /// Negotiated protocol version
+    pub fn protocol_version(&self) -> u32 {
+        self.version
+    }
+
+    /// Why the server accepted this connection
+    pub fn connection_reason(&self) -> &ConnectionReason {
+        &self.connection_reason
+    }
+
+    /// Roles the server activated
+    pub fn active_roles(&self) -> &[Role] {
+        &self.active_roles
+    }
+
+    /// Whether the server activated exactly this role and version
+    pub fn has_role(&self, role: Role) -> bool {
+        self.active_roles.contains_role(role)
+    }
+
+    /// Requested roles that the server did not activate in any version
+    pub fn refused_roles(&self) -> Vec<Role> {
+        self.requested_roles
+            .iter()
+            .filter(|role| !self.active_roles.iter().any(|r| r.same_family(role)))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether the initial client/state was sent as part of the handshake
+    ///
+    /// See [`ClientConfig::initial_state`](crate::protocol::ClientConfig::initial_state).
+    pub fn state_sent(&self) -> bool {
+        self.state_sent
+    }
+
+    pub(crate) fn mark_state_sent(&mut self) {
+        self.state_sent = true;
+    }
+}
@@ -0,0 +1,90 @@
+// ABOUTME: Counters of the frames the client received and what became of them
+// ABOUTME: Kept by the message router, read with ProtocolClient::stats
+
+use crate::protocol::backpressure::DroppedFrames;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// What the client has received since it connected, from [`ProtocolClient::stats`]
+///
+/// Counts accumulate across a [`ServerPool`](crate::protocol::ServerPool)
+/// failover or a server switch.
+///
+/// [`ProtocolClient::stats`]: crate::protocol::client::ProtocolClient::stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Protocol messages parsed, JSON or in the negotiated binary encoding
+    pub messages: u64,
+    /// Audio chunks, all slots
+    pub audio: u64,
+    /// Artwork images
+    pub artwork: u64,
+    /// Visualizer frames
+    pub visualizer: u64,
+    /// Ping, pong and close frames
+    pub control: u64,
+    /// Messages and binary frames that could not be parsed or decrypted
+    pub parse_failures: u64,
+    /// Binary frames of a type this client does not know
+    pub unknown_binary: u64,
+    /// Audio chunks for a slot without a receiver
+    pub unrouted_audio: u64,
+    /// Frames dropped because a receiver fell behind
+    pub dropped: DroppedFrames,
+}
+
+/// Live counters behind [`FrameStats`]
+#[derive(Debug, Default)]
+pub(crate) struct FrameCounters {
+    messages: AtomicU64,
+    audio: AtomicU64,
+    artwork: AtomicU64,
+    visualizer: AtomicU64,
+    control: AtomicU64,
+    parse_failures: AtomicU64,
+    unknown_binary: AtomicU64,
+    unrouted_audio: AtomicU64,
+}
+
+/// Counter to bump in [`FrameCounters::count`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Counter {
+    Message,
+    Audio,
+    Artwork,
+    Visualizer,
+    Control,
+    ParseFailure,
+    UnknownBinary,
+    UnroutedAudio,
+}
+
+impl FrameCounters {
+    pub(crate) fn count(&self, counter: Counter) {
+        let counter = match counter {
+            Counter::Message => &self.messages,
+            Counter::Audio => &self.audio,
+            Counter::Artwork => &self.artwork,
+            Counter::Visualizer => &self.visualizer,
+            Counter::Control => &self.control,
+            Counter::ParseFailure => &self.parse_failures,
+            Counter::UnknownBinary => &self.unknown_binary,
+            Counter::UnroutedAudio => &self.unrouted_audio,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current counts, with the receivers' drops filled in by the caller
+    pub(crate) fn snapshot(&self, dropped: DroppedFrames) -> FrameStats {
+        FrameStats {
+            messages: self.messages.load(Ordering::Relaxed),
+            audio: self.audio.load(Ordering::Relaxed),
+            artwork: self.artwork.load(Ordering::Relaxed),
+            visualizer: self.visualizer.load(Ordering::Relaxed),
+            control: self.control.load(Ordering::Relaxed),
+            parse_failures: self.parse_failures.load(Ordering::Relaxed),
+            unknown_binary: self.unknown_binary.load(Ordering::Relaxed),
+            unrouted_audio: self.unrouted_audio.load(Ordering::Relaxed),
+            dropped,
+        }
+    }
+}
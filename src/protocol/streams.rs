@@ -0,0 +1,239 @@
+// ABOUTME: Tracking of negotiated stream formats per role
+// ABOUTME: Retains the active stream/start descriptors and a bounded per-role history
+
+use crate::protocol::messages::{
+    Message, StreamArtworkConfig, StreamPlayerConfig, StreamVisualizerConfig,
+};
+use crate::protocol::role::Role;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Default number of history entries retained per role
+pub const DEFAULT_HISTORY_LIMIT: usize = 16;
+
+/// Role a stream descriptor applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamRole {
+    /// Player audio stream
+    Player,
+    /// Artwork image stream
+    Artwork,
+    /// Visualizer data stream
+    Visualizer,
+}
+
+impl StreamRole {
+    /// Parse a role name as used in `stream/end` (e.g., "player@v1" or "player")
+    pub fn from_role_name(name: &str) -> Option<Self> {
+        Self::from_role(&Role::from(name))
+    }
+
+    /// Stream role for a protocol role, ignoring its version
+    pub fn from_role(role: &Role) -> Option<Self> {
+        match role.name() {
+            "player" => Some(Self::Player),
+            "artwork" => Some(Self::Artwork),
+            "visualizer" => Some(Self::Visualizer),
+            _ => None,
+        }
+    }
+}
+
+/// Stream descriptor announced by the server for a single role
+#[derive(Debug, Clone)]
+pub enum StreamDescriptor {
+    /// Player stream format
+    Player(StreamPlayerConfig),
+    /// Artwork stream configuration
+    Artwork(StreamArtworkConfig),
+    /// Visualizer stream configuration
+    Visualizer(StreamVisualizerConfig),
+}
+
+impl StreamDescriptor {
+    /// Get the role this descriptor applies to
+    pub fn role(&self) -> StreamRole {
+        match self {
+            Self::Player(_) => StreamRole::Player,
+            Self::Artwork(_) => StreamRole::Artwork,
+            Self::Visualizer(_) => StreamRole::Visualizer,
+        }
+    }
+}
+
+/// A negotiated format change, recorded when `stream/start` arrives
+#[derive(Debug, Clone)]
+pub struct FormatHistoryEntry {
+    /// Local time the descriptor was received
+    pub received_at: Instant,
+    /// The descriptor that became active
+    pub descriptor: StreamDescriptor,
+}
+
+/// Currently active stream descriptors for each role
+#[derive(Debug, Clone, Default)]
+pub struct CurrentStream {
+    /// Active player stream format (if streaming)
+    pub player: Option<StreamPlayerConfig>,
+    /// Active artwork stream configuration (if streaming)
+    pub artwork: Option<StreamArtworkConfig>,
+    /// Active visualizer stream configuration (if streaming)
+    pub visualizer: Option<StreamVisualizerConfig>,
+}
+
+impl CurrentStream {
+    /// Check if no role currently has an active stream
+    pub fn is_idle(&self) -> bool {
+        self.player.is_none() && self.artwork.is_none() && self.visualizer.is_none()
+    }
+}
+
+impl fmt::Display for CurrentStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.player {
+            Some(p) => write!(
+                f,
+                "player={} {}Hz {}ch {}bit",
+                p.codec, p.sample_rate, p.channels, p.bit_depth
+            )?,
+            None => write!(f, "player=none")?,
+        }
+        match &self.artwork {
+            Some(a) => write!(f, ", artwork=channels {:?}", a.channels)?,
+            None => write!(f, ", artwork=none")?,
+        }
+        match &self.visualizer {
+            Some(v) => write!(f, ", visualizer={}bins@{}Hz", v.bins, v.rate_hz),
+            None => write!(f, ", visualizer=none"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TrackerState {
+    current: CurrentStream,
+    history: VecDeque<FormatHistoryEntry>,
+    history_limit: usize,
+}
+
+/// Shared tracker of negotiated stream formats
+///
+/// Cloning is cheap; all clones observe the same state. The protocol client feeds
+/// it every incoming message, so it stays valid after the client has been split.
+#[derive(Debug, Clone)]
+pub struct StreamTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl StreamTracker {
+    /// Create a tracker retaining [`DEFAULT_HISTORY_LIMIT`] entries per role
+    pub fn new() -> Self {
+        Self::with_history_limit(DEFAULT_HISTORY_LIMIT)
+    }
+
+    /// Create a tracker retaining at most `limit` history entries per role
+    pub fn with_history_limit(limit: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TrackerState {
+                current: CurrentStream::default(),
+                history: VecDeque::new(),
+                history_limit: limit,
+            })),
+        }
+    }
+
+    /// Update tracked state from a protocol message
+    ///
+    /// Only `stream/start` and `stream/end` affect the tracker; other messages are ignored.
+    pub fn apply(&self, msg: &Message) {
+        match msg {
+            Message::StreamStart(start) => {
+                let now = Instant::now();
+                let mut state = self.state.lock();
+                if let Some(ref player) = start.player {
+                    state.current.player = Some(player.clone());
+                    state.record(now, StreamDescriptor::Player(player.clone()));
+                }
+                if let Some(ref artwork) = start.artwork {
+                    state.current.artwork = Some(artwork.clone());
+                    state.record(now, StreamDescriptor::Artwork(artwork.clone()));
+                }
+                if let Some(ref visualizer) = start.visualizer {
+                    state.current.visualizer = Some(visualizer.clone());
+                    state.record(now, StreamDescriptor::Visualizer(visualizer.clone()));
+                }
+            }
+            Message::StreamEnd(end) => {
+                let mut state = self.state.lock();
+                match end.roles {
+                    Some(ref roles) => {
+                        for role in roles.iter().filter_map(StreamRole::from_role) {
+                            state.end(role);
+                        }
+                    }
+                    None => state.current = CurrentStream::default(),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Get a snapshot of the currently active stream descriptors
+    pub fn current(&self) -> CurrentStream {
+        self.state.lock().current.clone()
+    }
+
+    /// Get the negotiated format history for a role, oldest first
+    pub fn history(&self, role: StreamRole) -> Vec<FormatHistoryEntry> {
+        self.state
+            .lock()
+            .history
+            .iter()
+            .filter(|e| e.descriptor.role() == role)
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for StreamTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrackerState {
+    fn record(&mut self, received_at: Instant, descriptor: StreamDescriptor) {
+        let role = descriptor.role();
+        let count = self
+            .history
+            .iter()
+            .filter(|e| e.descriptor.role() == role)
+            .count();
+        if count >= self.history_limit {
+            if let Some(pos) = self
+                .history
+                .iter()
+                .position(|e| e.descriptor.role() == role)
+            {
+                self.history.remove(pos);
+            }
+        }
+        if self.history_limit > 0 {
+            self.history.push_back(FormatHistoryEntry {
+                received_at,
+                descriptor,
+            });
+        }
+    }
+
+    fn end(&mut self, role: StreamRole) {
+        match role {
+            StreamRole::Player => self.current.player = None,
+            StreamRole::Artwork => self.current.artwork = None,
+            StreamRole::Visualizer => self.current.visualizer = None,
+        }
+    }
+}
@@ -0,0 +1,76 @@
+// ABOUTME: Opt-in strict parsing of incoming JSON messages
+// ABOUTME: Reports fields this crate does not know as spec drift instead of silently dropping them
+
+use crate::error::Error;
+use crate::protocol::messages::Message;
+use serde_json::Value;
+
+/// Parse a JSON message, failing on fields this crate does not know
+///
+/// Normal parsing ignores unknown fields so newer servers keep working. This variant
+/// returns [`Error::SpecDrift`] listing them instead, which helps when developing
+/// against new server builds.
+pub fn parse(text: &str) -> Result<Message, Error> {
+    let msg: Message = serde_json::from_str(text).map_err(|e| Error::Protocol(e.to_string()))?;
+    check(text, &msg)?;
+    Ok(msg)
+}
+
+/// Check an already parsed message against its JSON text
+pub fn check(text: &str, msg: &Message) -> Result<(), Error> {
+    let raw: Value = serde_json::from_str(text).map_err(|e| Error::Protocol(e.to_string()))?;
+    let fields = unknown_fields(&raw, msg);
+    if fields.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::SpecDrift {
+            message_type: msg.message_type().to_string(),
+            fields,
+        })
+    }
+}
+
+/// Fields of `raw` that are lost when it is parsed as `msg`, as dotted paths
+///
+/// Paths start at the envelope, e.g. `payload.player.x_new_field`. Null values and
+/// empty arrays or objects are not reported, since they carry nothing that was lost.
+pub fn unknown_fields(raw: &Value, msg: &Message) -> Vec<String> {
+    let known = serde_json::to_value(msg).unwrap_or(Value::Null);
+    let mut fields = Vec::new();
+    collect(raw, &known, "", &mut fields);
+    fields
+}
+
+fn collect(raw: &Value, known: &Value, path: &str, fields: &mut Vec<String>) {
+    match (raw, known) {
+        (Value::Object(raw), Value::Object(known)) => {
+            for (key, value) in raw {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match known.get(key) {
+                    Some(known) => collect(value, known, &path, fields),
+                    None if is_empty(value) => {}
+                    None => fields.push(path),
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(known)) => {
+            for (i, (raw, known)) in raw.iter().zip(known).enumerate() {
+                collect(raw, known, &format!("{}[{}]", path, i), fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,105 @@
+// ABOUTME: Raw WebSocket frame tap for protocol debugging tools and bridges
+// ABOUTME: Sees text and binary frames exactly as sent or received, before parsing
+
+use crate::audit::Direction;
+use crate::protocol::transport::FrameSink;
+use futures_util::SinkExt;
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+
+/// A text or binary WebSocket frame as it appears on the wire
+///
+/// Binary frames are seen before decryption and unknown types are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFrame<'a> {
+    /// JSON protocol message
+    Text(&'a str),
+    /// Binary frame: audio, artwork, visualizer, or an encoded protocol message
+    Binary(&'a [u8]),
+}
+
+impl<'a> RawFrame<'a> {
+    /// The text or binary payload of a WebSocket message; control frames have none
+    pub(crate) fn of(msg: &'a WsMessage) -> Option<Self> {
+        match msg {
+            WsMessage::Text(text) => Some(Self::Text(text)),
+            WsMessage::Binary(data) => Some(Self::Binary(data)),
+            _ => None,
+        }
+    }
+
+    /// Payload length in bytes
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Text(text) => text.len(),
+            Self::Binary(data) => data.len(),
+        }
+    }
+
+    /// Whether the payload is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Callback that sees every raw frame, see [`ClientConfig::frame_tap`](crate::protocol::ClientConfig::frame_tap)
+#[derive(Clone)]
+pub struct FrameTap(Arc<TapFn>);
+
+type TapFn = dyn Fn(Direction, RawFrame<'_>) + Send + Sync;
+
+impl FrameTap {
+    /// Wrap a callback
+    pub fn new(tap: impl Fn(Direction, RawFrame<'_>) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(tap))
+    }
+
+    /// Pass `msg` to the callback if it is a text or binary frame
+    pub(crate) fn call(&self, direction: Direction, msg: &WsMessage) {
+        if let Some(frame) = RawFrame::of(msg) {
+            (self.0)(direction, frame)
+        }
+    }
+}
+
+impl std::fmt::Debug for FrameTap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FrameTap")
+    }
+}
+
+/// Write half of the WebSocket that shows every outgoing frame to the tap
+pub(crate) struct TappedSink {
+    sink: FrameSink,
+    tap: Option<FrameTap>,
+    closed: bool,
+}
+
+impl TappedSink {
+    pub(crate) fn new(sink: FrameSink, tap: Option<FrameTap>) -> Self {
+        Self {
+            sink,
+            tap,
+            closed: false,
+        }
+    }
+
+    /// Whether [`close`](Self::close) was called
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Send and flush one frame
+    pub(crate) async fn send(&mut self, frame: WsMessage) -> Result<(), WsError> {
+        if let Some(tap) = &self.tap {
+            tap.call(Direction::Outbound, &frame);
+        }
+        self.sink.send(frame).await
+    }
+
+    /// Flush and start the close handshake
+    pub(crate) async fn close(&mut self) -> Result<(), WsError> {
+        self.closed = true;
+        self.sink.close().await
+    }
+}
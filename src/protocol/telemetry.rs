@@ -0,0 +1,83 @@
+// ABOUTME: Opt-in session quality telemetry
+// ABOUTME: Counts underruns, late drops, reconnects, and RTT for the client/goodbye summary
+
+use crate::protocol::messages::SessionTelemetry;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Instant;
+
+#[derive(Debug)]
+struct TelemetryState {
+    started: Instant,
+    underruns: u64,
+    dropped_chunks: u64,
+    reconnects: u32,
+    rtt_total: i64,
+    rtt_samples: i64,
+}
+
+/// Collects playback quality counters over a session
+///
+/// Cloning is cheap; all clones update the same counters. Attach the result of
+/// [`summary`](Self::summary) to a `client/goodbye` with
+/// [`ClientGoodbye::with_telemetry`](crate::protocol::messages::ClientGoodbye::with_telemetry).
+#[derive(Debug, Clone)]
+pub struct TelemetryRecorder {
+    state: Arc<Mutex<TelemetryState>>,
+}
+
+impl TelemetryRecorder {
+    /// Start a session now
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TelemetryState {
+                started: Instant::now(),
+                underruns: 0,
+                dropped_chunks: 0,
+                reconnects: 0,
+                rtt_total: 0,
+                rtt_samples: 0,
+            })),
+        }
+    }
+
+    /// Record the output running out of audio
+    pub fn record_underrun(&self) {
+        self.state.lock().underruns += 1;
+    }
+
+    /// Record chunks dropped for arriving too late
+    pub fn record_dropped(&self, chunks: u64) {
+        self.state.lock().dropped_chunks += chunks;
+    }
+
+    /// Record a reconnect to the server
+    pub fn record_reconnect(&self) {
+        self.state.lock().reconnects += 1;
+    }
+
+    /// Record a clock sync round-trip time in microseconds
+    pub fn record_rtt(&self, rtt_micros: i64) {
+        let mut state = self.state.lock();
+        state.rtt_total += rtt_micros;
+        state.rtt_samples += 1;
+    }
+
+    /// Summarize the session so far
+    pub fn summary(&self) -> SessionTelemetry {
+        let state = self.state.lock();
+        SessionTelemetry {
+            duration_secs: state.started.elapsed().as_secs(),
+            underruns: state.underruns,
+            dropped_chunks: state.dropped_chunks,
+            reconnects: state.reconnects,
+            avg_rtt_micros: (state.rtt_samples > 0).then(|| state.rtt_total / state.rtt_samples),
+        }
+    }
+}
+
+impl Default for TelemetryRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
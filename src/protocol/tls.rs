@@ -0,0 +1,221 @@
+// ABOUTME: TLS options for wss:// connections
+// ABOUTME: Custom root CAs, client certificates, and opt-in acceptance of self-signed servers
+
+use crate::error::Error;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{self, CryptoProvider};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_tungstenite::Connector;
+
+/// TLS settings for connecting to `wss://` servers (`tls` feature)
+///
+/// By default the built-in web PKI roots are trusted and no client certificate is
+/// sent, the same as a plain `wss://` connect.
+///
+/// ```no_run
+/// # fn main() -> Result<(), sendspin::Error> {
+/// use sendspin::protocol::TlsConfig;
+///
+/// let tls = TlsConfig::new()
+///     .add_root_pem_file("/etc/sendspin/home-ca.pem")?
+///     .with_client_identity_pem_files("/etc/sendspin/player.pem", "/etc/sendspin/player.key")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    roots: Vec<CertificateDer<'static>>,
+    builtin_roots: bool,
+    identity: Option<ClientIdentity>,
+    accept_invalid_certs: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ClientIdentity {
+    chain: Vec<CertificateDer<'static>>,
+    key: Arc<PrivateKeyDer<'static>>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TlsConfig {
+    /// Trust the built-in web PKI roots only
+    pub fn new() -> Self {
+        Self {
+            roots: Vec::new(),
+            builtin_roots: true,
+            identity: None,
+            accept_invalid_certs: false,
+        }
+    }
+
+    /// Also trust every certificate in a PEM bundle
+    pub fn add_root_pem(mut self, pem: &[u8]) -> Result<Self, Error> {
+        let certs = certificates(pem)?;
+        if certs.is_empty() {
+            return Err(Error::Protocol(
+                "No certificates found in root CA PEM".to_string(),
+            ));
+        }
+        self.roots.extend(certs);
+        Ok(self)
+    }
+
+    /// Also trust every certificate in a PEM file
+    pub fn add_root_pem_file(self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let pem = read(path.as_ref())?;
+        self.add_root_pem(&pem)
+    }
+
+    /// Only trust the roots added with [`add_root_pem`](Self::add_root_pem)
+    pub fn without_builtin_roots(mut self) -> Self {
+        self.builtin_roots = false;
+        self
+    }
+
+    /// Present a client certificate chain and its private key (both PEM)
+    pub fn with_client_identity_pem(mut self, chain: &[u8], key: &[u8]) -> Result<Self, Error> {
+        let chain = certificates(chain)?;
+        if chain.is_empty() {
+            return Err(Error::Protocol(
+                "No certificates found in client certificate PEM".to_string(),
+            ));
+        }
+        let key = PrivateKeyDer::from_pem_slice(key)
+            .map_err(|e| Error::Protocol(format!("Invalid client private key: {}", e)))?;
+        self.identity = Some(ClientIdentity {
+            chain,
+            key: Arc::new(key),
+        });
+        Ok(self)
+    }
+
+    /// Present a client certificate chain and private key read from PEM files
+    pub fn with_client_identity_pem_files(
+        self,
+        chain: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let chain = read(chain.as_ref())?;
+        let key = read(key.as_ref())?;
+        self.with_client_identity_pem(&chain, &key)
+    }
+
+    /// Accept any server certificate, including self-signed and expired ones
+    ///
+    /// Only meant for LAN servers with self-signed certificates: the connection is
+    /// still encrypted, but anyone on the network path can impersonate the server.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Whether server certificates are checked
+    pub fn verifies_certs(&self) -> bool {
+        !self.accept_invalid_certs
+    }
+
+    /// Build the connector used for the WebSocket handshake
+    pub(crate) fn connector(&self) -> Result<Connector, Error> {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(tls_error)?;
+
+        let builder = if self.accept_invalid_certs {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        } else {
+            let mut roots = RootCertStore::empty();
+            if self.builtin_roots {
+                roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+            }
+            for cert in &self.roots {
+                roots.add(cert.clone()).map_err(tls_error)?;
+            }
+            builder.with_root_certificates(roots)
+        };
+
+        let config = match &self.identity {
+            Some(identity) => builder
+                .with_client_auth_cert(identity.chain.clone(), identity.key.clone_key())
+                .map_err(tls_error)?,
+            None => builder.with_no_client_auth(),
+        };
+        Ok(Connector::Rustls(Arc::new(config)))
+    }
+}
+
+fn certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, Error> {
+    CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::Protocol(format!("Invalid certificate PEM: {}", e)))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    std::fs::read(path).map_err(|e| Error::Io(format!("{}: {}", path.display(), e)))
+}
+
+fn tls_error(e: rustls::Error) -> Error {
+    Error::Connection(format!("TLS configuration error: {}", e))
+}
+
+/// Verifier for [`TlsConfig::danger_accept_invalid_certs`]
+///
+/// Skips the certificate checks but still verifies handshake signatures.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
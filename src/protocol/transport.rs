@@ -0,0 +1,226 @@
+// ABOUTME: Pluggable transports the client's WebSocket connection runs over
+// ABOUTME: ws:// and wss:// URLs, any connected byte stream, Unix sockets, and in-memory pipes
+
+use crate::error::Error;
+use crate::protocol::client::ClientConfig;
+use futures_util::{Sink, Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message as WsMessage};
+use tokio_tungstenite::WebSocketStream;
+
+/// Write half of an open connection
+pub(crate) type FrameSink = Pin<Box<dyn Sink<WsMessage, Error = WsError> + Send>>;
+/// Read half of an open connection
+pub(crate) type FrameStream = Pin<Box<dyn Stream<Item = Result<WsMessage, WsError>> + Send>>;
+
+/// URL used for the handshake when a stream transport is not given one
+const DEFAULT_STREAM_URL: &str = "ws://localhost/sendspin";
+
+/// A WebSocket connection whose handshake has completed, over any transport
+pub struct Connection {
+    sink: FrameSink,
+    stream: FrameStream,
+}
+
+impl Connection {
+    /// Wrap an established WebSocket
+    pub fn new<S>(ws: WebSocketStream<S>) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (sink, stream) = ws.split();
+        Self {
+            sink: Box::pin(sink),
+            stream: Box::pin(stream),
+        }
+    }
+
+    pub(crate) fn into_split(self) -> (FrameSink, FrameStream) {
+        (self.sink, self.stream)
+    }
+}
+
+impl std::fmt::Debug for Connection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Connection")
+    }
+}
+
+/// Opens the WebSocket connection for [`ProtocolClient::connect_with_transport`](crate::protocol::client::ProtocolClient::connect_with_transport)
+///
+/// The hello exchange and everything after it are the same for every transport.
+/// Implementations should apply [`ClientConfig::request`] to the handshake.
+pub trait Transport: Send {
+    /// Connect and complete the WebSocket handshake
+    fn open(self, config: &ClientConfig) -> impl Future<Output = Result<Connection, Error>> + Send;
+}
+
+/// A `ws://` or `wss://` URL, the default transport
+///
+/// Honors [`ClientConfig::proxy`] and, with the `tls` feature, `ClientConfig::tls`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketTransport {
+    url: String,
+}
+
+impl WebSocketTransport {
+    /// Connect to `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+}
+
+impl Transport for WebSocketTransport {
+    async fn open(self, config: &ClientConfig) -> Result<Connection, Error> {
+        let url = self.url.as_str();
+        #[cfg(feature = "tls")]
+        let connector = config.tls.as_ref().map(|tls| tls.connector()).transpose()?;
+        let request = config.request.build(url)?;
+        let connected = match &config.proxy {
+            Some(proxy) => {
+                let uri = request.uri();
+                let secure = uri.scheme_str() == Some("wss");
+                let host = uri
+                    .host()
+                    .ok_or_else(|| Error::Connection(format!("No host in {}", url)))?
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string();
+                let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+                let stream = proxy.tunnel(&host, port).await?;
+                #[cfg(feature = "tls")]
+                let connected = tokio_tungstenite::client_async_tls_with_config(
+                    request, stream, None, connector,
+                )
+                .await;
+                #[cfg(not(feature = "tls"))]
+                let connected = if secure {
+                    return Err(Error::Connection(
+                        "wss:// through a proxy needs the tls feature".to_string(),
+                    ));
+                } else {
+                    tokio_tungstenite::client_async(
+                        request,
+                        tokio_tungstenite::MaybeTlsStream::Plain(stream),
+                    )
+                    .await
+                };
+                connected
+            }
+            #[cfg(feature = "tls")]
+            None => {
+                tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector)
+                    .await
+            }
+            #[cfg(not(feature = "tls"))]
+            None => tokio_tungstenite::connect_async(request).await,
+        };
+        connected
+            .map(|(ws, _)| Connection::new(ws))
+            .map_err(handshake_error)
+    }
+}
+
+/// WebSocket over a byte stream that is already connected
+///
+/// Works with a `TcpStream` set up by the application, a TLS stream, or one end
+/// of an in-memory pipe (see [`memory`](Self::memory)). The handshake uses
+/// `ws://localhost/sendspin` unless another URL is given.
+#[derive(Debug)]
+pub struct StreamTransport<S> {
+    stream: S,
+    url: String,
+}
+
+impl<S> StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    /// Run the WebSocket over `stream`
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            url: DEFAULT_STREAM_URL.to_string(),
+        }
+    }
+
+    /// Use `url` for the Host header and request path of the handshake
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+}
+
+impl StreamTransport<DuplexStream> {
+    /// An in-memory connection: the transport and the server's end of the pipe
+    ///
+    /// Serve the other end with `tokio_tungstenite::accept_async`. `max_buf_size`
+    /// bounds the bytes in flight in each direction.
+    pub fn memory(max_buf_size: usize) -> (Self, DuplexStream) {
+        let (client, server) = tokio::io::duplex(max_buf_size);
+        (Self::new(client), server)
+    }
+}
+
+impl<S> Transport for StreamTransport<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    async fn open(self, config: &ClientConfig) -> Result<Connection, Error> {
+        let request = config.request.build(&self.url)?;
+        tokio_tungstenite::client_async(request, self.stream)
+            .await
+            .map(|(ws, _)| Connection::new(ws))
+            .map_err(handshake_error)
+    }
+}
+
+/// WebSocket over a Unix domain socket, for servers on the same machine
+#[cfg(unix)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnixTransport {
+    path: std::path::PathBuf,
+    url: String,
+}
+
+#[cfg(unix)]
+impl UnixTransport {
+    /// Connect to the socket at `path`
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            url: DEFAULT_STREAM_URL.to_string(),
+        }
+    }
+
+    /// Use `url` for the Host header and request path of the handshake
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = url.into();
+        self
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixTransport {
+    async fn open(self, config: &ClientConfig) -> Result<Connection, Error> {
+        let stream = tokio::net::UnixStream::connect(&self.path)
+            .await
+            .map_err(|e| Error::Connection(format!("{}: {}", self.path.display(), e)))?;
+        StreamTransport::new(stream)
+            .with_url(self.url)
+            .open(config)
+            .await
+    }
+}
+
+fn handshake_error(e: WsError) -> Error {
+    match e {
+        // Rejected credentials will not get better by retrying
+        WsError::Http(response) if response.status().is_client_error() => {
+            Error::Protocol(format!("Server refused the upgrade: {}", response.status()))
+        }
+        e => Error::Connection(e.to_string()),
+    }
+}
@@ -0,0 +1,307 @@
+// ABOUTME: Validation of protocol messages against spec invariants
+// ABOUTME: Checks value ranges, channel counts, timestamps, and role name syntax
+
+use crate::error::Error;
+use crate::protocol::messages::{
+    AudioFormatSpec, ClientHello, ControllerCommand, ControllerCommandKind, Message, MetadataState,
+    PlayerCommand, PlayerCommandKind, PlayerFormatRequest, PlayerState, StreamPlayerConfig,
+    StreamVisualizerConfig,
+};
+use crate::protocol::role::Role;
+
+/// Highest volume level allowed by the spec
+pub const MAX_VOLUME: u8 = 100;
+
+/// Highest artwork channel number
+pub const MAX_ARTWORK_CHANNEL: u8 = 3;
+
+/// Collects the first violation found in a message
+struct Checker {
+    message_type: &'static str,
+}
+
+impl Checker {
+    fn ensure(&self, ok: bool, problem: impl FnOnce() -> String) -> Result<(), Error> {
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::Protocol(format!(
+                "Invalid {}: {}",
+                self.message_type,
+                problem()
+            )))
+        }
+    }
+
+    fn volume(&self, field: &str, volume: Option<u8>) -> Result<(), Error> {
+        let volume = volume.unwrap_or(0);
+        self.ensure(volume <= MAX_VOLUME, || {
+            format!("{} {} is above {}", field, volume, MAX_VOLUME)
+        })
+    }
+
+    fn non_negative(&self, field: &str, value: i64) -> Result<(), Error> {
+        self.ensure(value >= 0, || format!("{} {} is negative", field, value))
+    }
+
+    fn non_empty(&self, field: &str, value: &str) -> Result<(), Error> {
+        self.ensure(!value.trim().is_empty(), || format!("{} is empty", field))
+    }
+
+    fn channels(&self, field: &str, channels: u8) -> Result<(), Error> {
+        self.ensure(channels > 0, || format!("{} must be at least 1", field))
+    }
+
+    fn sample_rate(&self, field: &str, sample_rate: u32) -> Result<(), Error> {
+        self.ensure(sample_rate > 0, || format!("{} must be non-zero", field))
+    }
+
+    fn bit_depth(&self, field: &str, bit_depth: u8) -> Result<(), Error> {
+        self.ensure(bit_depth > 0 && bit_depth.is_multiple_of(8), || {
+            format!("{} {} is not a whole number of bytes", field, bit_depth)
+        })
+    }
+
+    fn artwork_channel(&self, channel: u8) -> Result<(), Error> {
+        self.ensure(channel <= MAX_ARTWORK_CHANNEL, || {
+            format!(
+                "artwork channel {} is above {}",
+                channel, MAX_ARTWORK_CHANNEL
+            )
+        })
+    }
+
+    fn roles(&self, field: &str, roles: &[Role]) -> Result<(), Error> {
+        for role in roles {
+            self.ensure(role.is_well_formed(), || {
+                format!("{} contains malformed role '{}'", field, role)
+            })?;
+        }
+        Ok(())
+    }
+
+    fn format_spec(&self, format: &AudioFormatSpec) -> Result<(), Error> {
+        self.non_empty("codec", &format.codec)?;
+        self.channels("channels", format.channels)?;
+        self.sample_rate("sample_rate", format.sample_rate)?;
+        self.bit_depth("bit_depth", format.bit_depth)
+    }
+
+    fn hello(&self, hello: &ClientHello) -> Result<(), Error> {
+        self.non_empty("client_id", &hello.client_id)?;
+        self.ensure(!hello.supported_roles.is_empty(), || {
+            "supported_roles is empty".to_string()
+        })?;
+        self.roles("supported_roles", &hello.supported_roles)?;
+        if let Some(ref player) = hello.player_v1_support {
+            self.ensure(!player.supported_formats.is_empty(), || {
+                "player@v1_support lists no formats".to_string()
+            })?;
+            for format in &player.supported_formats {
+                self.format_spec(format)?;
+            }
+            self.ensure(player.buffer_capacity > 0, || {
+                "buffer_capacity must be non-zero".to_string()
+            })?;
+        }
+        if let Some(ref artwork) = hello.artwork_v1_support {
+            for channel in &artwork.channels {
+                self.artwork_channel(*channel)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn player_state(&self, state: &PlayerState) -> Result<(), Error> {
+        self.volume("volume", state.volume)
+    }
+
+    fn metadata(&self, metadata: &MetadataState) -> Result<(), Error> {
+        self.non_negative("metadata timestamp", metadata.timestamp)?;
+        if let Some(ref progress) = metadata.progress {
+            self.non_negative("progress position", progress.position)?;
+            self.non_negative("progress duration", progress.duration)?;
+            if let Some(speed) = progress.playback_speed {
+                self.ensure(speed.is_finite() && speed >= 0.0, || {
+                    format!("playback_speed {} is not a non-negative number", speed)
+                })?;
+            }
+        }
+        if let Some(ref lyrics) = metadata.lyrics {
+            for line in &lyrics.lines {
+                self.non_negative("lyric line time", line.time)?;
+            }
+            self.ensure(
+                lyrics.lines.windows(2).all(|w| w[0].time <= w[1].time),
+                || "lyric lines are not in time order".to_string(),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn player_command(&self, command: &PlayerCommand) -> Result<(), Error> {
+        self.volume("volume", command.volume)?;
+        self.ensure(
+            command.command != PlayerCommandKind::Volume || command.volume.is_some(),
+            || "volume command without a volume".to_string(),
+        )?;
+        self.ensure(
+            command.command != PlayerCommandKind::Mute || command.mute.is_some(),
+            || "mute command without a mute state".to_string(),
+        )?;
+        self.seek(
+            command.command == PlayerCommandKind::Seek,
+            command.position,
+            command.offset,
+        )
+    }
+
+    /// Seek commands carry exactly one of a non-negative position or an offset
+    fn seek(&self, is_seek: bool, position: Option<i64>, offset: Option<i64>) -> Result<(), Error> {
+        if let Some(position) = position {
+            self.non_negative("seek position", position)?;
+        }
+        self.ensure(!is_seek || position.is_some() || offset.is_some(), || {
+            "seek command needs a position or an offset".to_string()
+        })?;
+        self.ensure(position.is_none() || offset.is_none(), || {
+            "seek command has both a position and an offset".to_string()
+        })
+    }
+
+    fn controller_command(&self, command: &ControllerCommand) -> Result<(), Error> {
+        self.volume("volume", command.volume)?;
+        self.seek(
+            command.command == ControllerCommandKind::Seek,
+            command.position,
+            command.offset,
+        )?;
+        let required = match command.command {
+            ControllerCommandKind::Volume => command.volume.is_some(),
+            ControllerCommandKind::Mute => command.mute.is_some(),
+            ControllerCommandKind::JoinGroup => command
+                .group_id
+                .as_ref()
+                .is_some_and(|id| !id.trim().is_empty()),
+            _ => true,
+        };
+        self.ensure(required, || {
+            format!("{} command is missing its value", command.command)
+        })
+    }
+
+    fn stream_player(&self, config: &StreamPlayerConfig) -> Result<(), Error> {
+        self.non_empty("codec", &config.codec)?;
+        self.channels("channels", config.channels)?;
+        self.sample_rate("sample_rate", config.sample_rate)?;
+        self.bit_depth("bit_depth", config.bit_depth)
+    }
+
+    fn stream_visualizer(&self, config: &StreamVisualizerConfig) -> Result<(), Error> {
+        self.ensure(config.bins > 0, || "bins must be at least 1".to_string())?;
+        self.ensure(config.rate_hz > 0, || {
+            "rate_hz must be non-zero".to_string()
+        })?;
+        self.ensure(
+            config.min_frequency_hz >= 0.0 && config.min_frequency_hz < config.max_frequency_hz,
+            || {
+                format!(
+                    "frequency range {}-{}Hz is empty or negative",
+                    config.min_frequency_hz, config.max_frequency_hz
+                )
+            },
+        )
+    }
+
+    fn format_request(&self, request: &PlayerFormatRequest) -> Result<(), Error> {
+        if let Some(channels) = request.channels {
+            self.channels("channels", channels)?;
+        }
+        if let Some(sample_rate) = request.sample_rate {
+            self.sample_rate("sample_rate", sample_rate)?;
+        }
+        if let Some(bit_depth) = request.bit_depth {
+            self.bit_depth("bit_depth", bit_depth)?;
+        }
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Check the message against the spec's invariants
+    ///
+    /// Catches values that serialize fine but that a conforming peer would reject:
+    /// volumes above 100, zero channel counts or sample rates, negative timestamps,
+    /// durations, and positions, artwork channels above 3, commands missing their
+    /// value, and role names that are not of the form `name@vN`.
+    pub fn validate(&self) -> Result<(), Error> {
+        let check = Checker {
+            message_type: self.message_type(),
+        };
+        match self {
+            Self::ClientHello(hello) => check.hello(hello),
+            Self::ServerHello(hello) => {
+                check.non_empty("server_id", &hello.server_id)?;
+                check.roles("active_roles", &hello.active_roles)
+            }
+            Self::ClientTime(time) => {
+                check.non_negative("client_transmitted", time.client_transmitted)
+            }
+            Self::ServerTime(time) => {
+                check.non_negative("client_transmitted", time.client_transmitted)?;
+                check.non_negative("server_received", time.server_received)?;
+                check.non_negative("server_transmitted", time.server_transmitted)?;
+                check.ensure(time.server_transmitted >= time.server_received, || {
+                    "server_transmitted is before server_received".to_string()
+                })
+            }
+            Self::ClientState(state) => match state.player {
+                Some(ref player) => check.player_state(player),
+                None => Ok(()),
+            },
+            Self::ServerState(state) => {
+                if let Some(ref metadata) = state.metadata {
+                    check.metadata(metadata)?;
+                }
+                if let Some(ref controller) = state.controller {
+                    check.volume("volume", Some(controller.volume))?;
+                }
+                Ok(())
+            }
+            Self::ServerCommand(command) => match command.player {
+                Some(ref player) => check.player_command(player),
+                None => Ok(()),
+            },
+            Self::ClientCommand(command) => match command.controller {
+                Some(ref controller) => check.controller_command(controller),
+                None => Ok(()),
+            },
+            Self::StreamStart(start) => {
+                if let Some(ref player) = start.player {
+                    check.stream_player(player)?;
+                }
+                if let Some(ref artwork) = start.artwork {
+                    for channel in &artwork.channels {
+                        check.artwork_channel(*channel)?;
+                    }
+                }
+                if let Some(ref visualizer) = start.visualizer {
+                    check.stream_visualizer(visualizer)?;
+                }
+                Ok(())
+            }
+            Self::StreamEnd(end) => check.roles("roles", end.roles.as_deref().unwrap_or(&[])),
+            Self::StreamClear(clear) => check.roles("roles", clear.roles.as_deref().unwrap_or(&[])),
+            Self::StreamRequestFormat(request) => {
+                if let Some(ref player) = request.player {
+                    check.format_request(player)?;
+                }
+                if let Some(ref artwork) = request.artwork {
+                    check.artwork_channel(artwork.channel)?;
+                }
+                Ok(())
+            }
+            Self::GroupUpdate(_) | Self::ClientGoodbye(_) | Self::ServerGoodbye(_) => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+// ABOUTME: Protocol version negotiation
+// ABOUTME: Picks the version both ends speak and gates optional features on it
+
+use crate::error::Error;
+use std::fmt;
+
+/// Highest protocol version this crate speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Lowest protocol version this crate still accepts from a peer
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Pick the protocol version to use with a peer
+///
+/// `offered` is our side's version and `peer` the version the other end announced.
+/// A newer end downgrades to the older one, so both use the lower of the two.
+/// Fails with [`Error::UnsupportedVersion`] when that version is outside
+/// [`MIN_PROTOCOL_VERSION`]..=[`PROTOCOL_VERSION`].
+pub fn negotiate(offered: u32, peer: u32) -> Result<u32, Error> {
+    let version = offered.min(peer);
+    if (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(Error::UnsupportedVersion {
+            version,
+            min: MIN_PROTOCOL_VERSION,
+            max: PROTOCOL_VERSION,
+        })
+    }
+}
+
+/// Optional protocol features that depend on the negotiated version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Binary message encodings (`x_encoding`)
+    MessageEncodings,
+    /// Encrypted binary payloads (`x_payload_encryption`)
+    PayloadEncryption,
+    /// Group member lists and join/leave group commands
+    GroupMembership,
+}
+
+impl Feature {
+    /// Name used in error messages
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::MessageEncodings => "message encodings",
+            Feature::PayloadEncryption => "payload encryption",
+            Feature::GroupMembership => "group membership",
+        }
+    }
+
+    /// First protocol version that has this feature
+    pub fn min_version(self) -> u32 {
+        match self {
+            Feature::MessageEncodings | Feature::PayloadEncryption | Feature::GroupMembership => 1,
+        }
+    }
+
+    /// Whether a connection on `version` can use this feature
+    pub fn is_supported(self, version: u32) -> bool {
+        version >= self.min_version()
+    }
+
+    /// Fail unless a connection on `version` can use this feature
+    pub fn require(self, version: u32) -> Result<(), Error> {
+        if self.is_supported(version) {
+            Ok(())
+        } else {
+            Err(Error::Protocol(format!(
+                "{} needs protocol version {}, but version {} was negotiated",
+                self,
+                self.min_version(),
+                version
+            )))
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,191 @@
+// ABOUTME: Typed visualizer frames
+// ABOUTME: Parses raw visualizer chunks into per-channel frequency bins using the stream config
+
+use crate::error::Error;
+use crate::protocol::backpressure::FrameReceiver;
+use crate::protocol::client::VisualizerChunk;
+use crate::protocol::messages::StreamVisualizerConfig;
+use crate::protocol::streams::{StreamDescriptor, StreamRole, StreamTracker};
+use futures_util::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// One visualizer frame: the spectrum of each audio channel at a moment in time
+#[derive(Debug, Clone)]
+pub struct VisualizerFrame {
+    /// Server timestamp in microseconds
+    pub timestamp: i64,
+    /// When the frame carrying this data arrived
+    pub received_at: Instant,
+    /// Magnitude of each frequency bin (0.0-1.0), one vector per channel
+    pub channels: Vec<Vec<f32>>,
+    /// The stream/start configuration the frame was read with
+    pub config: StreamVisualizerConfig,
+}
+
+impl VisualizerFrame {
+    /// Read a chunk laid out as described by `config`
+    ///
+    /// The payload holds one byte per bin, channel after channel, so its length
+    /// must be a multiple of `config.bins`. Byte values scale to 0.0-1.0.
+    pub fn parse(chunk: &VisualizerChunk, config: &StreamVisualizerConfig) -> Result<Self, Error> {
+        let bins = config.bins as usize;
+        if bins == 0 || chunk.data.is_empty() || !chunk.data.len().is_multiple_of(bins) {
+            return Err(Error::Protocol(format!(
+                "Visualizer payload of {} bytes does not fit {} bins per channel",
+                chunk.data.len(),
+                bins
+            )));
+        }
+        let channels = chunk
+            .data
+            .chunks(bins)
+            .map(|channel| channel.iter().map(|&b| b as f32 / 255.0).collect())
+            .collect();
+        Ok(Self {
+            timestamp: chunk.timestamp,
+            received_at: chunk.received_at,
+            channels,
+            config: config.clone(),
+        })
+    }
+
+    /// Average of all channels, bin by bin
+    pub fn mixed(&self) -> Vec<f32> {
+        let count = self.channels.len().max(1) as f32;
+        let mut mixed = vec![0.0; self.config.bins as usize];
+        for channel in &self.channels {
+            for (sum, value) in mixed.iter_mut().zip(channel) {
+                *sum += value / count;
+            }
+        }
+        mixed
+    }
+}
+
+/// Visualizer frames of a connection, parsed with the active stream config
+///
+/// Wraps the raw chunk receiver from [`ClientParts`](crate::protocol::client::ClientParts).
+/// Each chunk is read with the config of the last stream/start received before
+/// it, so frames queued across a format change keep their own layout. Chunks
+/// without an active visualizer stream, or that do not fit its config, are
+/// skipped and counted in [`skipped`](Self::skipped).
+///
+/// ```no_run
+/// # async fn example(client: sendspin::ProtocolClient) {
+/// use sendspin::protocol::VisualizerStream;
+///
+/// let streams = client.stream_tracker();
+/// let mut frames = VisualizerStream::new(client.into_parts().visualizer, streams);
+/// while let Some(frame) = frames.recv().await {
+///     println!("{:?}", frame.mixed());
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct VisualizerStream {
+    chunks: FrameReceiver<VisualizerChunk>,
+    streams: StreamTracker,
+    skipped: u64,
+}
+
+impl VisualizerStream {
+    /// Parse `chunks` with the visualizer config that `streams` reports
+    ///
+    /// Use the tracker of the same client, from
+    /// [`ProtocolClient::stream_tracker`](crate::protocol::client::ProtocolClient::stream_tracker).
+    pub fn new(chunks: FrameReceiver<VisualizerChunk>, streams: StreamTracker) -> Self {
+        Self {
+            chunks,
+            streams,
+            skipped: 0,
+        }
+    }
+
+    /// Receive the next frame, or `None` once the connection is closed
+    pub async fn recv(&mut self) -> Option<VisualizerFrame> {
+        loop {
+            let chunk = self.chunks.recv().await?;
+            if let Some(frame) = self.parse(&chunk) {
+                return Some(frame);
+            }
+        }
+    }
+
+    /// Take the next frame if one is queued, without waiting
+    pub fn try_recv(&mut self) -> Option<VisualizerFrame> {
+        loop {
+            let chunk = self.chunks.try_recv()?;
+            if let Some(frame) = self.parse(&chunk) {
+                return Some(frame);
+            }
+        }
+    }
+
+    /// Chunks skipped so far because they could not be parsed
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// The raw chunk receiver back
+    pub fn into_inner(self) -> FrameReceiver<VisualizerChunk> {
+        self.chunks
+    }
+
+    fn parse(&mut self, chunk: &VisualizerChunk) -> Option<VisualizerFrame> {
+        let Some(config) = self.config_for(chunk) else {
+            log::debug!(
+                "Visualizer chunk ts={} without an active stream",
+                chunk.timestamp
+            );
+            self.skipped += 1;
+            return None;
+        };
+        match VisualizerFrame::parse(chunk, &config) {
+            Ok(frame) => Some(frame),
+            Err(e) => {
+                log::warn!("Skipping visualizer chunk ts={}: {}", chunk.timestamp, e);
+                self.skipped += 1;
+                None
+            }
+        }
+    }
+
+    /// Config of the visualizer stream that `chunk` belongs to
+    fn config_for(&self, chunk: &VisualizerChunk) -> Option<StreamVisualizerConfig> {
+        // Nothing to read with once the stream has ended
+        let current = self.streams.current().visualizer?;
+        let history = self.streams.history(StreamRole::Visualizer);
+        if history.is_empty() {
+            return Some(current);
+        }
+        history
+            .into_iter()
+            .rev()
+            .find(|entry| entry.received_at <= chunk.received_at)
+            .and_then(|entry| match entry.descriptor {
+                StreamDescriptor::Visualizer(config) => Some(config),
+                _ => None,
+            })
+    }
+}
+
+impl Stream for VisualizerStream {
+    type Item = VisualizerFrame;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<VisualizerFrame>> {
+        let this = self.get_mut();
+        loop {
+            match Pin::new(&mut this.chunks).poll_next(cx) {
+                Poll::Ready(Some(chunk)) => {
+                    if let Some(frame) = this.parse(&chunk) {
+                        return Poll::Ready(Some(frame));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -2,7 +2,9 @@
 // ABOUTME: Uses crossbeam queues for thread-safe scheduling without locks
 
 use crate::audio::AudioBuffer;
+use crate::scheduler::{Scheduler, SchedulerStats};
 use crossbeam::queue::SegQueue;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -13,6 +15,24 @@ pub struct AudioScheduler {
 
     /// Sorted buffers ready for playback
     sorted: Arc<parking_lot::Mutex<Vec<AudioBuffer>>>,
+
+    /// Buffers later than this are dropped instead of played
+    max_late: Option<Duration>,
+
+    /// Count of buffers dropped for lateness
+    dropped: AtomicU64,
+
+    /// Count of buffers scheduled
+    scheduled: AtomicU64,
+
+    /// Count of buffers handed out for playback
+    played: AtomicU64,
+
+    /// Count of buffers discarded by `clear` or `discard_before`
+    cleared: AtomicU64,
+
+    /// Buffers timestamped before this are discarded (see `discard_before`)
+    cutoff: AtomicI64,
 }
 
 impl AudioScheduler {
@@ -21,42 +41,106 @@ impl AudioScheduler {
         Self {
             incoming: Arc::new(SegQueue::new()),
             sorted: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            max_late: None,
+            dropped: AtomicU64::new(0),
+            scheduled: AtomicU64::new(0),
+            played: AtomicU64::new(0),
+            cleared: AtomicU64::new(0),
+            cutoff: AtomicI64::new(i64::MIN),
         }
     }
 
+    /// Drop buffers that are more than `max_late` past their play time
+    ///
+    /// Useful for low-latency playback, where catching up matters more than playing
+    /// every chunk.
+    pub fn with_max_late(mut self, max_late: Duration) -> Self {
+        self.max_late = Some(max_late);
+        self
+    }
+
+    /// Number of buffers dropped for arriving too late
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
     /// Schedule an audio buffer for future playback
     pub fn schedule(&self, buffer: AudioBuffer) {
+        self.scheduled.fetch_add(1, Ordering::Relaxed);
+        if buffer.timestamp < self.cutoff.load(Ordering::Acquire) {
+            self.cleared.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
         self.incoming.push(buffer);
     }
 
+    /// Discard buffers timestamped before `timestamp`, now and when scheduled later
+    ///
+    /// See [`Scheduler::discard_before`].
+    pub fn discard_before(&self, timestamp: i64) {
+        let mut sorted = self.sorted.lock();
+        self.cutoff.store(timestamp, Ordering::Release);
+        self.drain_incoming(&mut sorted);
+        // Sorted by timestamp, so stale buffers are a prefix
+        let stale = sorted.partition_point(|b| b.timestamp < timestamp);
+        sorted.drain(..stale);
+        self.cleared.fetch_add(stale as u64, Ordering::Relaxed);
+    }
+
     /// Check if scheduler is empty
     pub fn is_empty(&self) -> bool {
         self.incoming.is_empty() && self.sorted.lock().is_empty()
     }
 
-    /// Get next buffer that's ready to play (within 50ms window)
-    pub fn next_ready(&self) -> Option<AudioBuffer> {
-        // Take the lock once and do all operations under it
-        let mut sorted = self.sorted.lock();
-
-        // Drain incoming queue into sorted vec
+    /// Move incoming buffers into the sorted queue
+    fn drain_incoming(&self, sorted: &mut Vec<AudioBuffer>) {
+        let cutoff = self.cutoff.load(Ordering::Acquire);
         while let Some(buf) = self.incoming.pop() {
+            // Raced with `discard_before`
+            if buf.timestamp < cutoff {
+                self.cleared.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
             let pos = sorted
                 .binary_search_by_key(&buf.timestamp, |b| b.timestamp)
                 .unwrap_or_else(|e| e);
             sorted.insert(pos, buf);
         }
+    }
 
-        let now = Instant::now();
+    /// Get next buffer that's ready to play (within 50ms window)
+    pub fn next_ready(&self) -> Option<AudioBuffer> {
+        self.next_ready_at(Instant::now())
+    }
+
+    /// Get next buffer that's ready to play as of `now`
+    ///
+    /// Same decision as [`next_ready`](Self::next_ready) against a caller-supplied
+    /// clock, so recorded sessions can be replayed deterministically.
+    pub fn next_ready_at(&self, now: Instant) -> Option<AudioBuffer> {
+        // Take the lock once and do all operations under it
+        let mut sorted = self.sorted.lock();
+
+        // Drain incoming queue into sorted vec
+        self.drain_incoming(&mut sorted);
 
         // Per spec: 1ms early window to tolerate micro jitter
         let early_ok = Duration::from_micros(1000);
 
+        // Skip buffers that are too late to be worth playing
+        if let Some(max_late) = self.max_late {
+            while sorted.first().is_some_and(|b| b.play_at + max_late < now) {
+                sorted.remove(0);
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
         // Check if first buffer is ready
         if let Some(buf) = sorted.first() {
             // Check if play_at time has passed or is within early window
             if buf.play_at <= now + early_ok {
                 // Ready to play, late, or within 1ms early (tolerate jitter)
+                self.played.fetch_add(1, Ordering::Relaxed);
                 return Some(sorted.remove(0));
             }
         }
@@ -65,6 +149,48 @@ impl AudioScheduler {
     }
 }
 
+impl Scheduler for AudioScheduler {
+    fn schedule(&self, buffer: AudioBuffer) {
+        AudioScheduler::schedule(self, buffer)
+    }
+
+    fn next_ready(&self) -> Option<AudioBuffer> {
+        AudioScheduler::next_ready(self)
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let mut sorted = self.sorted.lock();
+        self.drain_incoming(&mut sorted);
+        sorted.first().map(|b| b.play_at)
+    }
+
+    fn clear(&self) {
+        let mut sorted = self.sorted.lock();
+        self.drain_incoming(&mut sorted);
+        self.cleared
+            .fetch_add(sorted.len() as u64, Ordering::Relaxed);
+        sorted.clear();
+    }
+
+    fn discard_before(&self, timestamp: i64) {
+        AudioScheduler::discard_before(self, timestamp)
+    }
+
+    fn stats(&self) -> SchedulerStats {
+        let queued = self.sorted.lock().len() + self.incoming.len();
+        SchedulerStats {
+            scheduled: self.scheduled.load(Ordering::Relaxed),
+            played: self.played.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed) + self.cleared.load(Ordering::Relaxed),
+            queued,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        AudioScheduler::is_empty(self)
+    }
+}
+
 impl Default for AudioScheduler {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,163 @@
+// ABOUTME: Latency profiles shared by sender and player
+// ABOUTME: Standard and low-latency (TV) timing settings plus end-to-end latency reporting
+
+use std::time::Duration;
+
+/// Timing settings for the whole pipeline, from capture to speaker
+///
+/// The same profile should be used on the sender (chunk size and lead) and the
+/// player (prebuffer, minimum lead, and late-chunk dropping) so the latency target
+/// is reachable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyProfile {
+    /// Duration of audio in each chunk sent by the server
+    pub chunk_duration: Duration,
+    /// How far ahead of playback the server timestamps chunks
+    pub buffer_lead: Duration,
+    /// Audio the player buffers before starting playback
+    pub start_buffer: Duration,
+    /// Minimum scheduling lead the player enforces for each chunk
+    pub min_lead: Duration,
+    /// Chunks later than this are dropped instead of played (`None` plays everything)
+    pub max_late: Option<Duration>,
+    /// End-to-end latency the profile aims for
+    pub target_latency: Duration,
+}
+
+impl LatencyProfile {
+    /// Default profile favouring robustness over latency
+    pub fn standard() -> Self {
+        Self {
+            chunk_duration: Duration::from_millis(20),
+            buffer_lead: Duration::from_millis(500),
+            start_buffer: Duration::from_millis(500),
+            min_lead: Duration::from_millis(200),
+            max_late: None,
+            target_latency: Duration::from_millis(750),
+        }
+    }
+
+    /// Low-latency profile for lip-synced TV audio from a capture source
+    ///
+    /// Uses 10ms chunks, a 50ms lead, and drops any chunk more than 5ms late to stay
+    /// under 80ms end to end on a wired network.
+    pub fn low_latency() -> Self {
+        Self {
+            chunk_duration: Duration::from_millis(10),
+            buffer_lead: Duration::from_millis(50),
+            start_buffer: Duration::from_millis(20),
+            min_lead: Duration::from_millis(5),
+            max_late: Some(Duration::from_millis(5)),
+            target_latency: Duration::from_millis(80),
+        }
+    }
+
+    /// Look up a profile by name (`standard`, or `tv` / `low-latency`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "standard" | "default" => Some(Self::standard()),
+            "tv" | "low-latency" | "low_latency" => Some(Self::low_latency()),
+            _ => None,
+        }
+    }
+
+    /// Estimated end-to-end latency of a chunk
+    ///
+    /// Adds the sender lead, how late the chunk reached the output, and the output
+    /// device latency.
+    pub fn pipeline_latency(&self, playout_delay: Duration, output_latency: Duration) -> Duration {
+        self.buffer_lead + playout_delay + output_latency
+    }
+}
+
+impl Default for LatencyProfile {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Summary of achieved end-to-end latency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// Number of chunks measured
+    pub samples: u64,
+    /// Mean latency
+    pub mean: Duration,
+    /// Worst latency seen
+    pub max: Duration,
+    /// Chunks that exceeded the target
+    pub over_target: u64,
+    /// Target from the profile
+    pub target: Duration,
+    /// Chunks dropped for arriving too late
+    pub dropped: u64,
+}
+
+impl LatencyReport {
+    /// Whether the mean latency is within the target
+    pub fn meets_target(&self) -> bool {
+        self.samples > 0 && self.mean <= self.target
+    }
+}
+
+/// Accumulates per-chunk latency measurements against a profile target
+#[derive(Debug, Clone)]
+pub struct LatencyMonitor {
+    profile: LatencyProfile,
+    samples: u64,
+    total_micros: u128,
+    max: Duration,
+    over_target: u64,
+    dropped: u64,
+}
+
+impl LatencyMonitor {
+    /// Create a monitor for a profile
+    pub fn new(profile: LatencyProfile) -> Self {
+        Self {
+            profile,
+            samples: 0,
+            total_micros: 0,
+            max: Duration::ZERO,
+            over_target: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Record a played chunk
+    pub fn record(&mut self, playout_delay: Duration, output_latency: Duration) {
+        let latency = self.profile.pipeline_latency(playout_delay, output_latency);
+        self.samples += 1;
+        self.total_micros += latency.as_micros();
+        self.max = self.max.max(latency);
+        if latency > self.profile.target_latency {
+            self.over_target += 1;
+        }
+    }
+
+    /// Record chunks dropped for lateness
+    pub fn record_dropped(&mut self, count: u64) {
+        self.dropped += count;
+    }
+
+    /// Summarize the measurements so far
+    pub fn report(&self) -> LatencyReport {
+        let mean = match self.samples {
+            0 => Duration::ZERO,
+            n => Duration::from_micros((self.total_micros / n as u128) as u64),
+        };
+        LatencyReport {
+            samples: self.samples,
+            mean,
+            max: self.max,
+            over_target: self.over_target,
+            target: self.profile.target_latency,
+            dropped: self.dropped,
+        }
+    }
+
+    /// Clear all measurements
+    pub fn reset(&mut self) {
+        *self = Self::new(self.profile);
+    }
+}
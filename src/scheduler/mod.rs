@@ -3,5 +3,65 @@
 
 /// Audio scheduler implementation
 pub mod audio_scheduler;
+/// Latency profiles and end-to-end latency reporting
+pub mod latency;
 
 pub use audio_scheduler::AudioScheduler;
+pub use latency::{LatencyMonitor, LatencyProfile, LatencyReport};
+
+use crate::audio::AudioBuffer;
+use std::time::Instant;
+
+/// Counters describing a scheduler's activity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchedulerStats {
+    /// Buffers handed to `schedule`
+    pub scheduled: u64,
+    /// Buffers returned by `next_ready`
+    pub played: u64,
+    /// Buffers discarded (late or cleared)
+    pub dropped: u64,
+    /// Buffers waiting to play
+    pub queued: usize,
+}
+
+/// Scheduling policy for decoded audio buffers
+///
+/// [`AudioScheduler`] is the default implementation. Embedders with their own
+/// real-time executors (e.g., an interrupt-driven scheduler on an RTOS) can supply
+/// another implementation and keep using the decode and sync layers unchanged.
+pub trait Scheduler: Send + Sync {
+    /// Queue a buffer for playback at its `play_at` time
+    fn schedule(&self, buffer: AudioBuffer);
+
+    /// Take the next buffer that is due, if any
+    fn next_ready(&self) -> Option<AudioBuffer>;
+
+    /// When the earliest queued buffer is due
+    ///
+    /// Lets callers sleep until the next deadline instead of polling.
+    fn next_deadline(&self) -> Option<Instant>;
+
+    /// Discard every queued buffer (e.g., on stream/clear)
+    fn clear(&self);
+
+    /// Discard buffers timestamped before `timestamp` (server time), including any
+    /// scheduled later
+    ///
+    /// Used after a seek once the server timestamp where the new position starts is
+    /// known, so chunks for the old position still in flight are not played. A later
+    /// call replaces the cutoff; `i64::MIN` lifts it. The default implementation
+    /// discards everything queued.
+    fn discard_before(&self, timestamp: i64) {
+        let _ = timestamp;
+        self.clear();
+    }
+
+    /// Activity counters
+    fn stats(&self) -> SchedulerStats;
+
+    /// Whether no buffers are queued
+    fn is_empty(&self) -> bool {
+        self.stats().queued == 0
+    }
+}
@@ -0,0 +1,182 @@
+// ABOUTME: Access control for connecting clients
+// ABOUTME: Static allow/deny rules on client_id, name, or IP plus an optional async policy callback
+
+use crate::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A connecting client, as seen by access rules and the policy callback
+#[derive(Debug, Clone)]
+pub struct AccessRequest {
+    /// Client identifier from client/hello
+    pub client_id: String,
+    /// Client name from client/hello
+    pub name: String,
+    /// Remote address
+    pub addr: SocketAddr,
+}
+
+/// Outcome of an access check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessDecision {
+    /// Let the client in
+    Allow,
+    /// Close the connection with this reason
+    Deny(String),
+}
+
+/// A static allow/deny rule
+///
+/// Parses from `client:<id>`, `name:<name>`, or `ip:<addr>[/<prefix>]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessRule {
+    /// Exact client_id
+    ClientId(String),
+    /// Exact client name
+    Name(String),
+    /// Address within a network (prefix length in bits)
+    Network(IpAddr, u8),
+}
+
+impl AccessRule {
+    /// Whether the rule matches a connecting client
+    pub fn matches(&self, request: &AccessRequest) -> bool {
+        match self {
+            AccessRule::ClientId(id) => *id == request.client_id,
+            AccessRule::Name(name) => *name == request.name,
+            AccessRule::Network(network, prefix) => {
+                in_network(request.addr.ip().to_canonical(), *network, *prefix)
+            }
+        }
+    }
+}
+
+impl FromStr for AccessRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let invalid = || {
+            Error::Protocol(format!(
+                "Invalid access rule '{}': expected client:<id>, name:<name>, or ip:<addr>[/<prefix>]",
+                s
+            ))
+        };
+        match s.split_once(':').ok_or_else(invalid)? {
+            ("client", id) if !id.is_empty() => Ok(AccessRule::ClientId(id.to_string())),
+            ("name", name) if !name.is_empty() => Ok(AccessRule::Name(name.to_string())),
+            ("ip", net) => {
+                let (addr, prefix) = match net.split_once('/') {
+                    Some((addr, prefix)) => (addr, Some(prefix)),
+                    None => (net, None),
+                };
+                let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+                let max = if addr.is_ipv4() { 32 } else { 128 };
+                let prefix = match prefix {
+                    Some(p) => p.parse().ok().filter(|p| *p <= max).ok_or_else(invalid)?,
+                    None => max,
+                };
+                Ok(AccessRule::Network(addr, prefix))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for AccessRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessRule::ClientId(id) => write!(f, "client:{}", id),
+            AccessRule::Name(name) => write!(f, "name:{}", name),
+            AccessRule::Network(addr, prefix) => write!(f, "ip:{}/{}", addr, prefix),
+        }
+    }
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+type PolicyFn =
+    dyn Fn(AccessRequest) -> Pin<Box<dyn Future<Output = AccessDecision> + Send>> + Send + Sync;
+
+/// Who may connect to the server
+///
+/// The denylist is checked first, then the allowlist (when non-empty, only matching
+/// clients get in), then the policy callback. Rejected clients receive a WebSocket
+/// close frame (policy violation) carrying the reason instead of server/hello.
+#[derive(Clone, Default)]
+pub struct AccessControl {
+    /// Clients matching any of these rules are rejected
+    pub deny: Vec<AccessRule>,
+    /// If non-empty, only clients matching one of these rules are accepted
+    pub allow: Vec<AccessRule>,
+    policy: Option<Arc<PolicyFn>>,
+}
+
+impl AccessControl {
+    /// Accept every client
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule to the allowlist
+    pub fn allow(mut self, rule: AccessRule) -> Self {
+        self.allow.push(rule);
+        self
+    }
+
+    /// Add a rule to the denylist
+    pub fn deny(mut self, rule: AccessRule) -> Self {
+        self.deny.push(rule);
+        self
+    }
+
+    /// Decide clients that pass the static lists with an async callback
+    pub fn with_policy<F, Fut>(mut self, policy: F) -> Self
+    where
+        F: Fn(AccessRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = AccessDecision> + Send + 'static,
+    {
+        self.policy = Some(Arc::new(move |request| Box::pin(policy(request))));
+        self
+    }
+
+    /// Check a connecting client against the lists and the policy
+    pub async fn check(&self, request: &AccessRequest) -> AccessDecision {
+        if let Some(rule) = self.deny.iter().find(|r| r.matches(request)) {
+            log::debug!("Client {} matched deny rule {}", request.client_id, rule);
+            return AccessDecision::Deny("Client is not allowed to connect".to_string());
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|r| r.matches(request)) {
+            return AccessDecision::Deny("Client is not on the allowlist".to_string());
+        }
+        match &self.policy {
+            Some(policy) => policy(request.clone()).await,
+            None => AccessDecision::Allow,
+        }
+    }
+}
+
+impl fmt::Debug for AccessControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccessControl")
+            .field("deny", &self.deny)
+            .field("allow", &self.allow)
+            .field("policy", &self.policy.is_some())
+            .finish()
+    }
+}
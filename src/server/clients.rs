@@ -0,0 +1,56 @@
+// ABOUTME: Per-client configuration for the server
+// ABOUTME: Static delay offsets, volume trims, and per-client mute
+
+use crate::protocol::messages::{Message, PlayerCommand, ServerCommand};
+use std::time::Duration;
+
+/// Per-client adjustments applied by the server
+///
+/// Settings are keyed by client ID and survive reconnects, so they can be configured
+/// before a client first connects.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientSettings {
+    /// Extra output latency of the client (e.g., a Bluetooth speaker)
+    ///
+    /// Chunk timestamps sent to this client are moved earlier by this amount so its
+    /// audio leaves the speaker in sync with the rest of the group.
+    pub delay: Duration,
+    /// Volume offset in percentage points applied on top of the server volume
+    pub volume_trim: i8,
+    /// Mute this client regardless of the server mute state
+    pub muted: bool,
+}
+
+impl ClientSettings {
+    /// Delay in microseconds, as subtracted from chunk timestamps
+    pub fn delay_micros(&self) -> i64 {
+        self.delay.as_micros() as i64
+    }
+
+    /// Volume this client should play at for a given server volume (0-100)
+    pub fn effective_volume(&self, server_volume: u8) -> u8 {
+        (server_volume as i16 + self.volume_trim as i16).clamp(0, 100) as u8
+    }
+}
+
+/// Build the player commands that bring a client to the given volume and mute state
+///
+/// Commands the client did not advertise in client/hello are left out.
+pub(crate) fn volume_commands(supported: &[String], volume: u8, muted: bool) -> Vec<Message> {
+    let supports = |name: &str| supported.iter().any(|c| c == name);
+
+    let mut commands = Vec::new();
+    if supports("volume") {
+        commands.push(player_command(PlayerCommand::volume(volume)));
+    }
+    if supports("mute") {
+        commands.push(player_command(PlayerCommand::mute(muted)));
+    }
+    commands
+}
+
+fn player_command(command: PlayerCommand) -> Message {
+    Message::ServerCommand(ServerCommand {
+        player: Some(command),
+    })
+}
@@ -0,0 +1,88 @@
+// ABOUTME: Server loop clock
+// ABOUTME: Monotonic microsecond timestamps used for server/time replies and chunk timestamps
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Monotonic clock counting microseconds since the server started
+///
+/// A [`manual`](Self::manual) clock only moves when advanced, so simulations
+/// can run the server through hours of stream time in seconds.
+#[derive(Debug, Clone)]
+pub struct ServerClock {
+    start: Instant,
+    manual: Option<Arc<watch::Sender<i64>>>,
+}
+
+impl ServerClock {
+    /// Start a new clock at zero
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            manual: None,
+        }
+    }
+
+    /// A clock stopped at zero that moves only through [`advance`](Self::advance)
+    pub fn manual() -> Self {
+        Self {
+            start: Instant::now(),
+            manual: Some(Arc::new(watch::Sender::new(0))),
+        }
+    }
+
+    /// Move a manual clock forward, waking anything sleeping on it
+    ///
+    /// Does nothing to a real-time clock.
+    pub fn advance(&self, by: Duration) {
+        if let Some(ref manual) = self.manual {
+            manual.send_modify(|now| *now += by.as_micros() as i64);
+        }
+    }
+
+    /// Current server loop time in microseconds
+    pub fn now_micros(&self) -> i64 {
+        match self.manual {
+            Some(ref manual) => *manual.borrow(),
+            None => self.start.elapsed().as_micros() as i64,
+        }
+    }
+
+    /// Convert server loop microseconds to a local Instant
+    ///
+    /// For a manual clock this is where the time would fall if the clock ran
+    /// in real time from now.
+    pub fn to_instant(&self, micros: i64) -> Instant {
+        match self.manual {
+            Some(_) => {
+                let ahead = micros - self.now_micros();
+                let now = Instant::now();
+                if ahead >= 0 {
+                    now + Duration::from_micros(ahead as u64)
+                } else {
+                    now.checked_sub(Duration::from_micros(ahead.unsigned_abs()))
+                        .unwrap_or(now)
+                }
+            }
+            None => self.start + Duration::from_micros(micros.max(0) as u64),
+        }
+    }
+
+    /// Wait until the clock reads at least `micros`
+    pub async fn sleep_until(&self, micros: i64) {
+        match self.manual {
+            Some(ref manual) => {
+                // The sender lives as long as self, so this cannot fail
+                let _ = manual.subscribe().wait_for(|&now| now >= micros).await;
+            }
+            None => tokio::time::sleep_until(self.to_instant(micros).into()).await,
+        }
+    }
+}
+
+impl Default for ServerClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
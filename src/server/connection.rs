@@ -0,0 +1,436 @@
+// ABOUTME: Per-connection handling for the Sendspin server
+// ABOUTME: Performs the hello handshake, answers time sync, and forwards group stream events
+
+use crate::audio::decode::PcmEndian;
+use crate::audio::AudioFormat;
+use crate::error::Error;
+use crate::protocol::client::{binary_types, AudioChunk};
+use crate::protocol::encoding::MessageEncoding;
+use crate::protocol::encryption::{PayloadCipher, PAYLOAD_CIPHER};
+use crate::protocol::messages::{
+    ClientHello, ConnectionReason, ControllerCommand, ControllerCommandKind, Message, PlayerState,
+    ServerHello, ServerTime, StreamEnd, StreamPlayerConfig, StreamStart,
+};
+use crate::protocol::role::{Role, RoleList};
+use crate::protocol::version::{self, PROTOCOL_VERSION};
+use crate::server::access::{AccessDecision, AccessRequest};
+use crate::server::group::{Group, StreamEvent, DEFAULT_GROUP};
+use crate::server::listener::{ConnectedClient, Outbound, Shared};
+use futures_util::{SinkExt, StreamExt};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+/// Time allowed for the client to send client/hello
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Roles this server can activate
+const SERVED_ROLES: &[Role] = &[Role::Player(1)];
+
+/// Serve a single client connection until it closes
+pub(crate) async fn handle(
+    stream: TcpStream,
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+) -> Result<(), Error> {
+    let mut ws = tokio_tungstenite::accept_async(stream)
+        .await
+        .map_err(|e| Error::WebSocket(e.to_string()))?;
+
+    let hello = tokio::time::timeout(HELLO_TIMEOUT, read_hello(&mut ws))
+        .await
+        .map_err(|_| Error::Protocol("Timed out waiting for client/hello".to_string()))??;
+
+    let request = AccessRequest {
+        client_id: hello.client_id.clone(),
+        name: hello.name.clone(),
+        addr,
+    };
+    if let AccessDecision::Deny(reason) = shared.config.access.check(&request).await {
+        log::info!(
+            "Rejected client {} ({}) from {}: {}",
+            hello.name,
+            hello.client_id,
+            addr,
+            reason
+        );
+        let frame = CloseFrame {
+            code: CloseCode::Policy,
+            reason: reason.into(),
+        };
+        // The client may already be gone; nothing else to do either way
+        let _ = ws.close(Some(frame)).await;
+        return Ok(());
+    }
+
+    // Newer clients are answered with our version and downgrade to it
+    let version = match version::negotiate(hello.version, PROTOCOL_VERSION) {
+        Ok(version) => version,
+        Err(e) => {
+            log::info!(
+                "Rejected client {} ({}): {}",
+                hello.name,
+                hello.client_id,
+                e
+            );
+            let frame = CloseFrame {
+                code: CloseCode::Protocol,
+                reason: e.to_string().into(),
+            };
+            let _ = ws.close(Some(frame)).await;
+            return Ok(());
+        }
+    };
+
+    let active_roles: Vec<Role> = hello
+        .supported_roles
+        .iter()
+        .filter(|r| SERVED_ROLES.contains(r))
+        .cloned()
+        .collect();
+    let is_player = active_roles.contains_role(Role::Player(1));
+
+    // Encrypt binary frames when both sides share a key
+    let (mut cipher, payload_encryption) = match &shared.config.payload_key {
+        Some(key) if hello.payload_encryption.iter().any(|c| c == PAYLOAD_CIPHER) => {
+            let (cipher, params) = PayloadCipher::negotiate(key);
+            (Some(cipher), Some(params))
+        }
+        _ => (None, None),
+    };
+    let encoding = MessageEncoding::negotiate(&shared.config.message_encodings, &hello.encodings);
+
+    // The hello exchange is always JSON
+    send(
+        &mut ws,
+        MessageEncoding::Json,
+        &Message::ServerHello(ServerHello {
+            server_id: shared.config.server_id.clone(),
+            name: shared.config.name.clone(),
+            version,
+            active_roles: active_roles.clone(),
+            connection_reason: ConnectionReason::Playback,
+            payload_encryption,
+            encoding: (encoding != MessageEncoding::Json).then(|| encoding.name().to_string()),
+        }),
+    )
+    .await?;
+
+    log::info!(
+        "Client connected: {} ({}) from {}, roles {:?}",
+        hello.name,
+        hello.client_id,
+        addr,
+        active_roles
+    );
+
+    let client_id = hello.client_id.clone();
+    let client = ConnectedClient {
+        client_id: client_id.clone(),
+        name: hello.name.clone(),
+        addr,
+        active_roles,
+        supported_commands: hello
+            .player_v1_support
+            .as_ref()
+            .map(|s| s.supported_commands.clone())
+            .unwrap_or_default(),
+        player_state: None,
+    };
+    let volume_commands = shared.volume_commands_for(&client);
+    shared.clients.lock().insert(client_id.clone(), client);
+    let (outbox_tx, mut outbox) = mpsc::unbounded_channel();
+    shared.outboxes.lock().insert(client_id.clone(), outbox_tx);
+
+    let mut group = shared.group_of(&client_id);
+    // Subscribe before checking the current format so no chunk is missed in between
+    let mut events = group.events.subscribe();
+    shared.announce_members(&group);
+    let result = async {
+        if is_player {
+            for msg in &volume_commands {
+                send(&mut ws, encoding, msg).await?;
+            }
+        }
+        enter_group(&mut ws, encoding, &shared, &group, &hello, is_player).await?;
+        run(
+            &mut ws,
+            &mut group,
+            &mut events,
+            &mut outbox,
+            &shared,
+            &hello,
+            is_player,
+            &mut cipher,
+            encoding,
+        )
+        .await
+    }
+    .await;
+
+    shared.outboxes.lock().remove(&client_id);
+    shared.clients.lock().remove(&client_id);
+    shared.announce_members(&group);
+    log::info!("Client disconnected: {} ({})", hello.name, client_id);
+    result
+}
+
+async fn read_hello(ws: &mut WebSocketStream<TcpStream>) -> Result<ClientHello, Error> {
+    while let Some(msg) = ws.next().await {
+        match msg.map_err(|e| Error::WebSocket(e.to_string()))? {
+            WsMessage::Text(text) => {
+                return match serde_json::from_str::<Message>(&text) {
+                    Ok(Message::ClientHello(hello)) => Ok(hello),
+                    Ok(other) => Err(Error::Protocol(format!(
+                        "Expected client/hello, got {}",
+                        other.message_type()
+                    ))),
+                    Err(e) => Err(Error::Protocol(e.to_string())),
+                };
+            }
+            WsMessage::Close(_) => break,
+            _ => continue,
+        }
+    }
+    Err(Error::Connection(
+        "Connection closed before client/hello".to_string(),
+    ))
+}
+
+/// Send the group/update, state, and active stream of a group the client just joined
+async fn enter_group(
+    ws: &mut WebSocketStream<TcpStream>,
+    encoding: MessageEncoding,
+    shared: &Shared,
+    group: &Group,
+    hello: &ClientHello,
+    is_player: bool,
+) -> Result<(), Error> {
+    let current = group.current_format.lock().clone();
+    let state = group.state.lock().clone();
+    let update = shared.with_members(group.update());
+    send(ws, encoding, &Message::GroupUpdate(update)).await?;
+    if let Some(state) = state {
+        send(ws, encoding, &Message::ServerState(state)).await?;
+    }
+    if let (true, Some(format)) = (is_player, current) {
+        check_supported(hello, &format);
+        send(ws, encoding, &stream_start(&format)).await?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run(
+    ws: &mut WebSocketStream<TcpStream>,
+    group: &mut Arc<Group>,
+    events: &mut broadcast::Receiver<StreamEvent>,
+    outbox: &mut mpsc::UnboundedReceiver<Outbound>,
+    shared: &Shared,
+    hello: &ClientHello,
+    is_player: bool,
+    cipher: &mut Option<PayloadCipher>,
+    encoding: MessageEncoding,
+) -> Result<(), Error> {
+    loop {
+        tokio::select! {
+            incoming = ws.next() => {
+                let Some(incoming) = incoming else {
+                    return Ok(());
+                };
+                let received = shared.clock.now_micros();
+                let msg = match incoming.map_err(|e| Error::WebSocket(e.to_string()))? {
+                    WsMessage::Text(text) => {
+                        serde_json::from_str(&text).map_err(|e| Error::Protocol(e.to_string()))?
+                    }
+                    WsMessage::Binary(data)
+                        if encoding != MessageEncoding::Json
+                            && data.first() == Some(&binary_types::ENCODED_MESSAGE) =>
+                    {
+                        encoding.decode(&data)?
+                    }
+                    WsMessage::Close(_) => return Ok(()),
+                    _ => continue,
+                };
+                match msg {
+                    Message::ClientTime(time) => {
+                        let reply = Message::ServerTime(ServerTime {
+                            client_transmitted: time.client_transmitted,
+                            server_received: received,
+                            server_transmitted: shared.clock.now_micros(),
+                        });
+                        send(ws, encoding, &reply).await?;
+                    }
+                    Message::ClientState(state) => {
+                        let changed = match shared.clients.lock().get_mut(&hello.client_id) {
+                            Some(client) => {
+                                let level = |s: &Option<PlayerState>| {
+                                    s.as_ref().map(|s| (s.volume, s.muted))
+                                };
+                                let changed = level(&client.player_state) != level(&state.player);
+                                client.player_state = state.player;
+                                changed
+                            }
+                            None => false,
+                        };
+                        if changed {
+                            shared.announce_members(group);
+                        }
+                    }
+                    Message::ClientCommand(command) => {
+                        if let Some(controller) = command.controller {
+                            change_group(shared, hello, &controller);
+                        }
+                    }
+                    Message::ClientGoodbye(goodbye) => {
+                        log::info!("Client {} said goodbye: {:?}", hello.name, goodbye.reason);
+                        if let Some(t) = goodbye.telemetry {
+                            log::info!(
+                                "Session telemetry client_id={} duration_s={} underruns={} dropped_chunks={} reconnects={} avg_rtt_us={:?}",
+                                hello.client_id, t.duration_secs, t.underruns, t.dropped_chunks, t.reconnects, t.avg_rtt_micros
+                            );
+                        }
+                        let _ = ws.close(None).await;
+                        return Ok(());
+                    }
+                    other => log::debug!("Ignoring {} from {}", other.message_type(), hello.name),
+                }
+            }
+            Some(item) = outbox.recv() => match item {
+                Outbound::Message(msg) => send(ws, encoding, &msg).await?,
+                Outbound::Regroup => {
+                    let next = shared.group_of(&hello.client_id);
+                    if next.id == group.id {
+                        continue;
+                    }
+                    log::info!("Client {} moved to group {}", hello.name, next.id);
+                    if is_player && group.current_format.lock().is_some() {
+                        send(ws, encoding, &Message::StreamEnd(StreamEnd { roles: None })).await?;
+                    }
+                    *events = next.events.subscribe();
+                    shared.announce_members(group);
+                    *group = next;
+                    enter_group(ws, encoding, shared, group, hello, is_player).await?;
+                    shared.announce_members(group);
+                }
+            },
+            event = events.recv() => {
+                match event {
+                    Ok(StreamEvent::State(state)) => {
+                        send(ws, encoding, &Message::ServerState(*state)).await?;
+                    }
+                    Ok(StreamEvent::Group(update)) => {
+                        let update = shared.with_members(update);
+                        send(ws, encoding, &Message::GroupUpdate(update)).await?;
+                    }
+                    // Audio events only concern players
+                    Ok(_) if !is_player => {}
+                    Ok(StreamEvent::Start(format)) => {
+                        check_supported(hello, &format);
+                        send(ws, encoding, &stream_start(&format)).await?;
+                    }
+                    Ok(StreamEvent::Chunk { timestamp, data }) => {
+                        // Slow endpoints get their audio earlier to compensate for output latency
+                        let timestamp = timestamp - shared.settings_for(&hello.client_id).delay_micros();
+                        let mut frame = AudioChunk::encode(timestamp, &data);
+                        if let Some(cipher) = cipher.as_mut() {
+                            frame = cipher.seal(&frame)?;
+                        }
+                        ws.send(WsMessage::Binary(frame))
+                            .await
+                            .map_err(|e| Error::WebSocket(e.to_string()))?;
+                    }
+                    Ok(StreamEvent::End) => {
+                        send(ws, encoding, &Message::StreamEnd(StreamEnd { roles: None })).await?;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Client {} lagging, dropped {} chunks", hello.name, skipped);
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// Apply a join or leave group command sent by a client
+fn change_group(shared: &Shared, hello: &ClientHello, command: &ControllerCommand) {
+    let target = match command.command {
+        ControllerCommandKind::JoinGroup => command.group_id.as_deref().unwrap_or_default(),
+        ControllerCommandKind::LeaveGroup => DEFAULT_GROUP,
+        _ => {
+            log::debug!("Ignoring {} command from {}", command.command, hello.name);
+            return;
+        }
+    };
+    if let Err(e) = shared.assign(&hello.client_id, target) {
+        log::warn!("Client {} cannot join group: {}", hello.name, e);
+    }
+}
+
+async fn send(
+    ws: &mut WebSocketStream<TcpStream>,
+    encoding: MessageEncoding,
+    msg: &Message,
+) -> Result<(), Error> {
+    let frame = match encoding {
+        MessageEncoding::Json => {
+            WsMessage::Text(serde_json::to_string(msg).map_err(|e| Error::Protocol(e.to_string()))?)
+        }
+        binary => WsMessage::Binary(binary.encode(msg)?),
+    };
+    ws.send(frame)
+        .await
+        .map_err(|e| Error::WebSocket(e.to_string()))
+}
+
+fn stream_start(format: &AudioFormat) -> Message {
+    Message::StreamStart(StreamStart {
+        player: Some(StreamPlayerConfig {
+            codec: format.codec.name().to_string(),
+            sample_rate: format.sample_rate,
+            channels: format.channels,
+            bit_depth: format.bit_depth,
+            codec_header: None,
+            // Sources produce little-endian PCM unless their format says otherwise
+            endian: format
+                .codec
+                .is_pcm()
+                .then(|| format.endian.unwrap_or(PcmEndian::Little)),
+        }),
+        artwork: None,
+        visualizer: None,
+    })
+}
+
+/// Warn when the stream format is not in the client's advertised list
+fn check_supported(hello: &ClientHello, format: &AudioFormat) {
+    let supported = hello.player_v1_support.as_ref().is_some_and(|support| {
+        support.supported_formats.iter().any(|f| {
+            f.codec == format.codec.name()
+                && f.sample_rate == format.sample_rate
+                && f.channels == format.channels
+                && f.bit_depth == format.bit_depth
+                && (!format.codec.is_pcm()
+                    || f.endian.unwrap_or(PcmEndian::Little)
+                        == format.endian.unwrap_or(PcmEndian::Little))
+        })
+    });
+    if !supported {
+        log::warn!(
+            "Client {} did not advertise {} {}Hz {}ch {}bit; streaming anyway",
+            hello.name,
+            format.codec.name(),
+            format.sample_rate,
+            format.channels,
+            format.bit_depth
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// ABOUTME: Server-side crossfade between audio sources
+// ABOUTME: Blends the tail of the outgoing source into the start of its replacement
+
+use crate::audio::convert;
+use crate::audio::decode::PcmEndian;
+use crate::audio::{AudioFormat, Codec, Sample};
+use crate::error::Error;
+use crate::server::source::AudioSource;
+use std::time::Duration;
+
+/// Linearly blend two chunks of little-endian interleaved PCM
+///
+/// `start` is the frame offset of these chunks within a fade lasting `total` frames.
+/// The result has the length of `incoming`; missing outgoing frames count as silence.
+pub fn mix(
+    outgoing: &[u8],
+    incoming: &[u8],
+    bit_depth: u8,
+    channels: u8,
+    start: usize,
+    total: usize,
+) -> Result<Vec<u8>, Error> {
+    let old = convert::to_samples(outgoing, bit_depth, PcmEndian::Little)?;
+    let mut new = convert::to_samples(incoming, bit_depth, PcmEndian::Little)?;
+    blend(&old, &mut new, channels, start, total);
+    convert::to_bytes(&new, bit_depth, PcmEndian::Little)
+}
+
+/// [`mix`] for little-endian 32-bit float PCM
+pub fn mix_float(
+    outgoing: &[u8],
+    incoming: &[u8],
+    channels: u8,
+    start: usize,
+    total: usize,
+) -> Vec<u8> {
+    let old = convert::float_to_samples(outgoing, PcmEndian::Little);
+    let mut new = convert::float_to_samples(incoming, PcmEndian::Little);
+    blend(&old, &mut new, channels, start, total);
+    convert::float_to_bytes(&new, PcmEndian::Little)
+}
+
+fn blend(old: &[Sample], new: &mut [Sample], channels: u8, start: usize, total: usize) {
+    let channels = channels.max(1) as usize;
+    for (i, sample) in new.iter_mut().enumerate() {
+        let frame = start + i / channels;
+        let gain = if total == 0 {
+            1.0
+        } else {
+            (frame as f32 / total as f32).min(1.0)
+        };
+        let old = old.get(i).map_or(0.0, |s| s.0 as f32);
+        *sample = Sample((old * (1.0 - gain) + sample.0 as f32 * gain).round() as i32);
+    }
+}
+
+/// A fade from a replaced source into the active one
+pub(crate) struct Crossfade {
+    outgoing: Box<dyn AudioSource>,
+    total: usize,
+    done: usize,
+}
+
+impl Crossfade {
+    /// Fade out `outgoing` over `duration`
+    pub(crate) fn new(outgoing: Box<dyn AudioSource>, duration: Duration) -> Self {
+        let rate = outgoing.format().sample_rate as u128;
+        let total = (duration.as_micros() * rate / 1_000_000) as usize;
+        Self {
+            outgoing,
+            total,
+            done: 0,
+        }
+    }
+
+    /// Whether the outgoing source has faded out completely
+    pub(crate) fn is_done(&self) -> bool {
+        self.done >= self.total
+    }
+
+    /// Blend a chunk of the incoming source with the same span of the outgoing one
+    ///
+    /// An outgoing source that ends or fails early is treated as silence.
+    pub(crate) fn apply(&mut self, incoming: Vec<u8>, format: &AudioFormat) -> Vec<u8> {
+        let frame_size = (format.bit_depth as usize / 8) * format.channels as usize;
+        let frames = incoming.len() / frame_size.max(1);
+        let outgoing = match self.outgoing.next_chunk(frames) {
+            Ok(Some(data)) => data,
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                log::warn!("Outgoing source failed during crossfade: {}", e);
+                Vec::new()
+            }
+        };
+        let mixed = match format.codec {
+            Codec::PcmFloat => Ok(mix_float(
+                &outgoing,
+                &incoming,
+                format.channels,
+                self.done,
+                self.total,
+            )),
+            _ => mix(
+                &outgoing,
+                &incoming,
+                format.bit_depth,
+                format.channels,
+                self.done,
+                self.total,
+            ),
+        };
+        self.done += frames;
+        mixed.unwrap_or(incoming)
+    }
+}
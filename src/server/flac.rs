@@ -0,0 +1,137 @@
+// ABOUTME: FLAC file and stream source for the server (feature "symphonia")
+// ABOUTME: Demuxes and decodes FLAC through symphonia into little-endian PCM chunks
+
+use crate::audio::decode::PcmEndian;
+use crate::audio::{convert, AudioFormat, Codec, Sample};
+use crate::error::Error;
+use crate::server::source::AudioSource;
+use ::symphonia::core::audio::SampleBuffer;
+use ::symphonia::core::codecs::{Decoder as CodecDecoder, DecoderOptions};
+use ::symphonia::core::errors::Error as SymphoniaError;
+use ::symphonia::core::formats::{FormatOptions, FormatReader};
+use ::symphonia::core::io::{MediaSourceStream, ReadOnlySource};
+use ::symphonia::default::formats::FlacReader;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+
+/// Gives a `Send` reader the `Sync` bound symphonia asks of its inputs
+struct SyncReader<R>(Mutex<R>);
+
+impl<R: Read> Read for SyncReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.get_mut().read(buf)
+    }
+}
+
+/// Source decoding a FLAC stream (a file, stdin, or an HTTP body)
+///
+/// Produces 16-bit PCM for 16-bit (and narrower) FLAC and 24-bit PCM otherwise.
+pub struct FlacSource {
+    reader: FlacReader,
+    decoder: Box<dyn CodecDecoder>,
+    track_id: u32,
+    format: AudioFormat,
+    pending: VecDeque<Sample>,
+    ended: bool,
+}
+
+impl FlacSource {
+    /// Read the FLAC stream header from `reader` and set up decoding
+    pub fn new(reader: impl Read + Send + 'static) -> Result<Self, Error> {
+        let source = ReadOnlySource::new(SyncReader(Mutex::new(reader)));
+        let stream = MediaSourceStream::new(Box::new(source), Default::default());
+        let reader = FlacReader::try_new(stream, &FormatOptions::default())
+            .map_err(|e| Error::Protocol(format!("Not a FLAC stream: {}", e)))?;
+        let track = reader
+            .default_track()
+            .ok_or_else(|| Error::Protocol("FLAC stream has no audio track".to_string()))?;
+        let params = &track.codec_params;
+        let sample_rate = params
+            .sample_rate
+            .ok_or_else(|| Error::Protocol("FLAC stream without a sample rate".to_string()))?;
+        let channels = params
+            .channels
+            .map(|c| c.count() as u8)
+            .ok_or_else(|| Error::Protocol("FLAC stream without a channel count".to_string()))?;
+        let bit_depth = match params.bits_per_sample {
+            Some(bits) if bits <= 16 => 16,
+            _ => 24,
+        };
+        let decoder = ::symphonia::default::get_codecs()
+            .make(params, &DecoderOptions::default())
+            .map_err(|e| Error::Protocol(format!("Cannot create FLAC decoder: {}", e)))?;
+        let track_id = track.id;
+        Ok(Self {
+            reader,
+            decoder,
+            track_id,
+            format: AudioFormat {
+                codec: Codec::Pcm,
+                sample_rate,
+                channels,
+                bit_depth,
+                codec_header: None,
+                endian: Some(PcmEndian::Little),
+            },
+            pending: VecDeque::new(),
+            ended: false,
+        })
+    }
+
+    /// Decode the next packet into `pending`; false at end of stream
+    fn decode_next(&mut self) -> Result<bool, Error> {
+        let packet = match self.reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Ok(false)
+            }
+            Err(e) => return Err(Error::Io(format!("FLAC read failed: {}", e))),
+        };
+        if packet.track_id() != self.track_id {
+            return Ok(true);
+        }
+        match self.decoder.decode(&packet) {
+            Ok(decoded) => {
+                let mut buffer =
+                    SampleBuffer::<i32>::new(decoded.capacity() as u64, *decoded.spec());
+                buffer.copy_interleaved_ref(decoded);
+                self.pending
+                    .extend(buffer.samples().iter().map(|&s| Sample::from_i32(s)));
+            }
+            // A corrupt frame is skipped; FLAC frames resync on their own
+            Err(SymphoniaError::DecodeError(e)) => log::warn!("Skipping bad FLAC frame: {}", e),
+            Err(e) => return Err(Error::Protocol(format!("FLAC decode failed: {}", e))),
+        }
+        Ok(true)
+    }
+}
+
+impl AudioSource for FlacSource {
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        let channels = self.format.channels as usize;
+        let wanted = max_frames * channels;
+        while self.pending.len() < wanted && !self.ended {
+            self.ended = !self.decode_next()?;
+        }
+        let take = self.pending.len().min(wanted);
+        let take = take - take % channels;
+        if take == 0 {
+            return Ok(None);
+        }
+        let samples: Vec<Sample> = self.pending.drain(..take).collect();
+        convert::to_bytes(&samples, self.format.bit_depth, PcmEndian::Little).map(Some)
+    }
+}
+
+impl std::fmt::Debug for FlacSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlacSource")
+            .field("format", &self.format)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,154 @@
+// ABOUTME: Server-side playback groups
+// ABOUTME: Each group has its own stream, metadata, and playback state
+
+use crate::audio::AudioFormat;
+use crate::protocol::messages::{GroupUpdate, PlaybackState, ServerState};
+use crate::server::source::AudioSource;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+/// ID of the group every client joins unless assigned elsewhere
+pub const DEFAULT_GROUP: &str = "default";
+
+/// Capacity of each group's stream event channel (in chunks)
+const EVENT_CAPACITY: usize = 512;
+
+/// Stream event fanned out to every connection in a group
+#[derive(Debug, Clone)]
+pub(crate) enum StreamEvent {
+    /// A new stream started with the given format
+    Start(AudioFormat),
+    /// Encoded audio for the active stream
+    Chunk {
+        /// Server loop timestamp at which the chunk should play
+        timestamp: i64,
+        /// PCM payload
+        data: Arc<[u8]>,
+    },
+    /// The active stream ended
+    End,
+    /// Server state (e.g., metadata) changed
+    State(Box<ServerState>),
+    /// Group playback state changed
+    Group(GroupUpdate),
+}
+
+/// Replacement source handed to a running stream pump
+pub(crate) struct SourceSwitch {
+    /// The new source
+    pub(crate) source: Box<dyn AudioSource>,
+    /// Fade duration when the formats match
+    pub(crate) crossfade: Option<Duration>,
+}
+
+/// Snapshot of a group
+#[derive(Debug, Clone)]
+pub struct GroupInfo {
+    /// Group identifier
+    pub id: String,
+    /// Human-readable group name
+    pub name: String,
+    /// Current playback state
+    pub playback_state: PlaybackState,
+    /// Format of the active stream, if any
+    pub format: Option<AudioFormat>,
+    /// IDs of connected clients in this group
+    pub members: Vec<String>,
+}
+
+/// A group of clients sharing one audio source
+#[derive(Debug)]
+pub(crate) struct Group {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) events: broadcast::Sender<StreamEvent>,
+    pub(crate) current_format: Mutex<Option<AudioFormat>>,
+    pub(crate) state: Mutex<Option<ServerState>>,
+    pub(crate) switch: Mutex<Option<mpsc::UnboundedSender<SourceSwitch>>>,
+    playback: Mutex<PlaybackState>,
+    serving: AtomicBool,
+}
+
+impl Group {
+    pub(crate) fn new(id: &str, name: &str) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            events,
+            current_format: Mutex::new(None),
+            state: Mutex::new(None),
+            switch: Mutex::new(None),
+            playback: Mutex::new(PlaybackState::Stopped),
+            serving: AtomicBool::new(false),
+        }
+    }
+
+    /// The group/update describing this group
+    ///
+    /// Members are filled in per connection at send time, see `Shared::group_update`.
+    pub(crate) fn update(&self) -> GroupUpdate {
+        GroupUpdate {
+            playback_state: Some(self.playback.lock().clone()),
+            group_id: Some(self.id.clone()),
+            group_name: Some(self.name.clone()),
+            members: None,
+        }
+    }
+
+    /// Publish an event to the group's connections
+    pub(crate) fn publish(&self, event: StreamEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Change the playback state and notify members
+    pub(crate) fn set_playback(&self, state: PlaybackState) {
+        *self.playback.lock() = state;
+        self.publish(StreamEvent::Group(self.update()));
+    }
+
+    /// Claim the group for a source and start its stream
+    ///
+    /// Fails if another source is already serving the group.
+    pub(crate) fn begin_stream(&self, format: AudioFormat) -> bool {
+        if self.serving.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.resume(format);
+        true
+    }
+
+    /// End the stream and release the group
+    pub(crate) fn end_stream(&self) {
+        self.suspend();
+        self.serving.store(false, Ordering::Release);
+    }
+
+    /// Start a new stream without releasing the group
+    pub(crate) fn resume(&self, format: AudioFormat) {
+        *self.current_format.lock() = Some(format.clone());
+        self.publish(StreamEvent::Start(format));
+        self.set_playback(PlaybackState::Playing);
+    }
+
+    /// End the active stream (if any) without releasing the group
+    pub(crate) fn suspend(&self) {
+        if self.current_format.lock().take().is_some() {
+            self.publish(StreamEvent::End);
+            self.set_playback(PlaybackState::Stopped);
+        }
+    }
+
+    pub(crate) fn info(&self, members: Vec<String>) -> GroupInfo {
+        GroupInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            playback_state: self.playback.lock().clone(),
+            format: self.current_format.lock().clone(),
+            members,
+        }
+    }
+}
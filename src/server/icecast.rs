@@ -0,0 +1,404 @@
+// ABOUTME: Icecast/HTTP audio source adapter for the server
+// ABOUTME: Fetches an HTTP stream, strips ICY metadata, and decodes it via registered decoders
+
+use crate::audio::decode::PcmEndian;
+use crate::audio::{AudioFormat, Codec};
+use crate::error::Error;
+use crate::protocol::messages::{ContentType, MetadataState};
+use crate::server::source::{AudioSource, ReaderSource};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Maximum number of HTTP redirects followed
+const MAX_REDIRECTS: usize = 5;
+/// Timeout for connecting and for each read from the stream
+const IO_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Metadata carried in an ICY metadata block
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IcyMetadata {
+    /// `StreamTitle`, usually "Artist - Title"
+    pub stream_title: Option<String>,
+    /// `StreamUrl`, if the station sends one
+    pub stream_url: Option<String>,
+}
+
+impl IcyMetadata {
+    /// Parse a metadata block such as `StreamTitle='Artist - Title';StreamUrl='';`
+    pub fn parse(block: &str) -> Self {
+        let mut meta = Self::default();
+        let mut rest = block.trim_end_matches('\0');
+        while let Some(eq) = rest.find("='") {
+            let key = rest[..eq].trim();
+            let after = &rest[eq + 2..];
+            // Values end at "';" (titles may contain bare quotes)
+            let end = after.find("';").unwrap_or(after.len());
+            let value = after[..end].trim_end_matches('\'').to_string();
+            let value = if value.is_empty() { None } else { Some(value) };
+            match key {
+                "StreamTitle" => meta.stream_title = value,
+                "StreamUrl" => meta.stream_url = value,
+                _ => {}
+            }
+            rest = after.get(end + 2..).unwrap_or("");
+        }
+        meta
+    }
+
+    /// Convert to `server/state` radio metadata, splitting "Artist - Title" when present
+    pub fn to_metadata_state(&self, timestamp: i64) -> MetadataState {
+        let (artist, title) = match self.stream_title.as_deref() {
+            Some(t) => match t.split_once(" - ") {
+                Some((artist, title)) => (Some(artist.to_string()), Some(title.to_string())),
+                None => (None, Some(t.to_string())),
+            },
+            None => (None, None),
+        };
+        MetadataState {
+            timestamp,
+            title,
+            artist,
+            album: None,
+            artwork_url: None,
+            year: None,
+            track: None,
+            progress: None,
+            repeat: None,
+            shuffle: None,
+            genre: None,
+            composer: None,
+            disc_number: None,
+            content_type: Some(ContentType::Radio),
+            source: None,
+            lyrics: None,
+        }
+    }
+}
+
+/// Reader that removes interleaved ICY metadata blocks from a stream
+pub struct IcyReader<R> {
+    inner: R,
+    metaint: Option<usize>,
+    until_meta: usize,
+    on_metadata: Box<dyn FnMut(IcyMetadata) + Send>,
+}
+
+impl<R: Read> IcyReader<R> {
+    /// Wrap a stream with metadata every `metaint` bytes (None = no metadata)
+    pub fn new(
+        inner: R,
+        metaint: Option<usize>,
+        on_metadata: impl FnMut(IcyMetadata) + Send + 'static,
+    ) -> Self {
+        Self {
+            inner,
+            metaint: metaint.filter(|m| *m > 0),
+            until_meta: metaint.unwrap_or(0),
+            on_metadata: Box::new(on_metadata),
+        }
+    }
+
+    fn read_metadata(&mut self) -> std::io::Result<()> {
+        let mut len = [0u8; 1];
+        self.inner.read_exact(&mut len)?;
+        let len = len[0] as usize * 16;
+        if len > 0 {
+            let mut block = vec![0u8; len];
+            self.inner.read_exact(&mut block)?;
+            let meta = IcyMetadata::parse(&String::from_utf8_lossy(&block));
+            log::debug!("ICY metadata: {:?}", meta);
+            (self.on_metadata)(meta);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for IcyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some(metaint) = self.metaint else {
+            return self.inner.read(buf);
+        };
+        if self.until_meta == 0 {
+            self.read_metadata()?;
+            self.until_meta = metaint;
+        }
+        let max = buf.len().min(self.until_meta);
+        let n = self.inner.read(&mut buf[..max])?;
+        self.until_meta -= n;
+        Ok(n)
+    }
+}
+
+/// Factory building a PCM source from a response body
+pub type DecoderFactory =
+    fn(content_type: &str, body: Box<dyn Read + Send>) -> Result<Box<dyn AudioSource>, Error>;
+
+/// Content-type to decoder mapping used by [`IcecastSource`]
+///
+/// The default registry handles uncompressed streams (`audio/L16` and WAV), and
+/// FLAC with the "symphonia" feature.
+/// Register a factory for compressed types such as `audio/mpeg` or `audio/aac`.
+#[derive(Clone)]
+pub struct SourceDecoders {
+    entries: Vec<(String, DecoderFactory)>,
+}
+
+impl SourceDecoders {
+    /// Create an empty registry
+    pub fn empty() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Register a factory for a MIME type (matched case-insensitively, parameters ignored)
+    pub fn register(&mut self, mime: &str, factory: DecoderFactory) {
+        let mime = mime.to_ascii_lowercase();
+        self.entries.retain(|(m, _)| *m != mime);
+        self.entries.push((mime, factory));
+    }
+
+    /// Build a source for a response body with the given content type
+    pub fn open(
+        &self,
+        content_type: &str,
+        body: Box<dyn Read + Send>,
+    ) -> Result<Box<dyn AudioSource>, Error> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        let factory = self
+            .entries
+            .iter()
+            .find(|(m, _)| *m == mime)
+            .map(|(_, f)| *f)
+            .ok_or_else(|| {
+                Error::Protocol(format!("No decoder registered for '{}'", content_type))
+            })?;
+        factory(content_type, body)
+    }
+}
+
+impl Default for SourceDecoders {
+    fn default() -> Self {
+        let mut decoders = Self::empty();
+        decoders.register("audio/l16", l16_source);
+        for mime in ["audio/wav", "audio/wave", "audio/x-wav"] {
+            decoders.register(mime, |_, body| Ok(Box::new(ReaderSource::from_wav(body)?)));
+        }
+        #[cfg(feature = "symphonia")]
+        for mime in ["audio/flac", "audio/x-flac"] {
+            decoders.register(mime, |_, body| {
+                Ok(Box::new(crate::server::flac::FlacSource::new(body)?))
+            });
+        }
+        decoders
+    }
+}
+
+impl std::fmt::Debug for SourceDecoders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|(m, _)| m))
+            .finish()
+    }
+}
+
+/// `audio/L16;rate=44100;channels=2` (RFC 2586: big-endian, mono unless stated)
+fn l16_source(
+    content_type: &str,
+    body: Box<dyn Read + Send>,
+) -> Result<Box<dyn AudioSource>, Error> {
+    let mut sample_rate = None;
+    let mut channels = 1;
+    for param in content_type.split(';').skip(1) {
+        match param.trim().split_once('=') {
+            Some(("rate", v)) => sample_rate = v.trim().parse().ok(),
+            Some(("channels", v)) => channels = v.trim().parse().unwrap_or(1),
+            _ => {}
+        }
+    }
+    let sample_rate = sample_rate
+        .ok_or_else(|| Error::Protocol("audio/L16 stream without a rate".to_string()))?;
+    let format = AudioFormat {
+        codec: Codec::Pcm,
+        sample_rate,
+        channels,
+        bit_depth: 16,
+        codec_header: None,
+        endian: Some(PcmEndian::Little),
+    };
+    Ok(Box::new(ReaderSource::with_endian(
+        body,
+        format,
+        PcmEndian::Big,
+    )?))
+}
+
+/// Internet radio source pulling an Icecast/SHOUTcast or plain HTTP stream
+///
+/// ICY metadata (when the server sends it) is stripped from the audio and passed to
+/// the metadata callback, e.g. to forward titles with
+/// [`ServerHandle::set_metadata`](crate::server::ServerHandle::set_metadata).
+pub struct IcecastSource {
+    inner: Box<dyn AudioSource>,
+    station: Option<String>,
+    content_type: String,
+}
+
+impl IcecastSource {
+    /// Connect to an `http://` stream URL and pick a decoder from its content type
+    pub fn connect(
+        url: &str,
+        decoders: &SourceDecoders,
+        on_metadata: impl FnMut(IcyMetadata) + Send + 'static,
+    ) -> Result<Self, Error> {
+        let (headers, body) = open_stream(url)?;
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone())
+        };
+
+        let content_type = header("content-type").unwrap_or_default();
+        let metaint = header("icy-metaint").and_then(|v| v.trim().parse().ok());
+        let station = header("icy-name");
+
+        let reader = IcyReader::new(body, metaint, on_metadata);
+        let inner = decoders.open(&content_type, Box::new(reader))?;
+
+        log::info!(
+            "Opened stream {} ({}, station {:?})",
+            url,
+            content_type,
+            station
+        );
+
+        Ok(Self {
+            inner,
+            station,
+            content_type,
+        })
+    }
+
+    /// Station name from the `icy-name` header
+    pub fn station_name(&self) -> Option<&str> {
+        self.station.as_deref()
+    }
+
+    /// Content type reported by the server
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+}
+
+impl AudioSource for IcecastSource {
+    fn format(&self) -> &AudioFormat {
+        self.inner.format()
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        self.inner.next_chunk(max_frames)
+    }
+}
+
+type Headers = Vec<(String, String)>;
+
+/// Issue an HTTP/1.0 GET (following redirects) and return headers and body
+fn open_stream(url: &str) -> Result<(Headers, BufReader<TcpStream>), Error> {
+    let mut url = url.to_string();
+    for _ in 0..=MAX_REDIRECTS {
+        let (host, port, path) = split_url(&url)?;
+        let addr = std::net::ToSocketAddrs::to_socket_addrs(&(host.as_str(), port))
+            .map_err(|e| Error::Connection(e.to_string()))?
+            .next()
+            .ok_or_else(|| Error::Connection(format!("Could not resolve {}", host)))?;
+        let mut stream = TcpStream::connect_timeout(&addr, IO_TIMEOUT)
+            .map_err(|e| Error::Connection(e.to_string()))?;
+        stream
+            .set_read_timeout(Some(IO_TIMEOUT))
+            .map_err(|e| Error::Io(e.to_string()))?;
+
+        let request = format!(
+            "GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: sendspin-rs/{}\r\nIcy-MetaData: 1\r\nAccept: */*\r\n\r\n",
+            path,
+            host,
+            env!("CARGO_PKG_VERSION")
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| Error::Io(e.to_string()))?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status_line = String::new();
+        reader
+            .read_line(&mut status_line)
+            .map_err(|e| Error::Io(e.to_string()))?;
+        // "HTTP/1.1 200 OK" or SHOUTcast's "ICY 200 OK"
+        let status: u16 = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| {
+                Error::Protocol(format!("Invalid HTTP status line: {}", status_line.trim()))
+            })?;
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader
+                .read_line(&mut line)
+                .map_err(|e| Error::Io(e.to_string()))?;
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.push((k.trim().to_string(), v.trim().to_string()));
+            }
+        }
+
+        match status {
+            200 => return Ok((headers, reader)),
+            301 | 302 | 303 | 307 | 308 => {
+                url = headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("location"))
+                    .map(|(_, v)| v.clone())
+                    .ok_or_else(|| Error::Protocol("Redirect without Location".to_string()))?;
+                log::debug!("Following redirect to {}", url);
+            }
+            _ => {
+                return Err(Error::Connection(format!(
+                    "Unexpected HTTP status: {}",
+                    status_line.trim()
+                )))
+            }
+        }
+    }
+    Err(Error::Connection("Too many redirects".to_string()))
+}
+
+fn split_url(url: &str) -> Result<(String, u16, String), Error> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        Error::Protocol(format!("Unsupported stream URL '{}' (only http://)", url))
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => (
+            h,
+            p.parse()
+                .map_err(|_| Error::Protocol(format!("Invalid port in '{}'", url)))?,
+        ),
+        None => (authority, 80),
+    };
+    Ok((host.to_string(), port, path.to_string()))
+}
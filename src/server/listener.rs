@@ -0,0 +1,679 @@
+// ABOUTME: Sendspin server listener and stream pump
+// ABOUTME: Accepts player connections and streams timestamped PCM from an AudioSource
+
+use crate::audio::integrity::FrameLayout;
+use crate::audio::AudioFormat;
+use crate::error::Error;
+use crate::protocol::encoding::MessageEncoding;
+use crate::protocol::encryption::PayloadKey;
+use crate::protocol::messages::{
+    GroupMember, GroupUpdate, Message, MetadataState, PlayerState, ServerState,
+};
+use crate::protocol::role::Role;
+use crate::scheduler::LatencyProfile;
+use crate::server::access::AccessControl;
+use crate::server::clients::{self, ClientSettings};
+use crate::server::clock::ServerClock;
+use crate::server::connection;
+use crate::server::crossfade::Crossfade;
+use crate::server::group::{Group, GroupInfo, SourceSwitch, StreamEvent, DEFAULT_GROUP};
+use crate::server::silence::{SilenceAction, SilenceConfig, SilenceDetector};
+use crate::server::source::AudioSource;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Server configuration
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Human-readable server name sent in server/hello
+    pub name: String,
+    /// Unique server identifier sent in server/hello
+    pub server_id: String,
+    /// Duration of audio in each chunk
+    pub chunk_duration: Duration,
+    /// How far ahead of playback chunks are sent
+    pub buffer_lead: Duration,
+    /// End the stream while the source is silent (disabled when `None`)
+    pub silence: Option<SilenceConfig>,
+    /// Encrypt binary frames for clients that offer payload encryption
+    pub payload_key: Option<PayloadKey>,
+    /// Which clients may connect
+    pub access: AccessControl,
+    /// Binary message encodings accepted from clients that offer them, most preferred first
+    pub message_encodings: Vec<MessageEncoding>,
+    /// Clock for timestamps and pacing (a real-time clock started at bind when `None`)
+    pub clock: Option<ServerClock>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            name: "Sendspin-RS Server".to_string(),
+            server_id: uuid::Uuid::new_v4().to_string(),
+            chunk_duration: Duration::from_millis(20),
+            buffer_lead: Duration::from_millis(500),
+            silence: None,
+            payload_key: None,
+            access: AccessControl::default(),
+            message_encodings: MessageEncoding::available(),
+            clock: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Apply the sender side (chunk size and lead) of a latency profile
+    pub fn with_profile(mut self, profile: &LatencyProfile) -> Self {
+        self.chunk_duration = profile.chunk_duration;
+        self.buffer_lead = profile.buffer_lead;
+        self
+    }
+
+    /// Run the server on the given clock, e.g. a [`ServerClock::manual`] one
+    pub fn with_clock(mut self, clock: ServerClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+}
+
+/// A player currently connected to the server
+#[derive(Debug, Clone)]
+pub struct ConnectedClient {
+    /// Client identifier from client/hello
+    pub client_id: String,
+    /// Client name from client/hello
+    pub name: String,
+    /// Remote address
+    pub addr: SocketAddr,
+    /// Roles activated for this client
+    pub active_roles: Vec<Role>,
+    /// Player commands the client advertised in client/hello
+    pub supported_commands: Vec<String>,
+    /// Last player state reported by the client
+    pub player_state: Option<PlayerState>,
+}
+
+/// Message queued for a single connection
+#[derive(Debug)]
+pub(crate) enum Outbound {
+    /// Send a protocol message
+    Message(Box<Message>),
+    /// The client's group assignment changed
+    Regroup,
+}
+
+/// State shared between the listener, the stream pump and connections
+#[derive(Debug)]
+pub(crate) struct Shared {
+    pub(crate) config: ServerConfig,
+    pub(crate) clock: ServerClock,
+    pub(crate) clients: Mutex<HashMap<String, ConnectedClient>>,
+    pub(crate) groups: Mutex<HashMap<String, Arc<Group>>>,
+    pub(crate) membership: Mutex<HashMap<String, String>>,
+    pub(crate) settings: Mutex<HashMap<String, ClientSettings>>,
+    pub(crate) volume: Mutex<Option<u8>>,
+    pub(crate) outboxes: Mutex<HashMap<String, mpsc::UnboundedSender<Outbound>>>,
+}
+
+impl Shared {
+    /// Look up a group by ID
+    pub(crate) fn group(&self, group_id: &str) -> Result<Arc<Group>, Error> {
+        self.groups
+            .lock()
+            .get(group_id)
+            .cloned()
+            .ok_or_else(|| Error::Protocol(format!("Unknown group '{}'", group_id)))
+    }
+
+    /// The group a client belongs to (the default group unless assigned)
+    pub(crate) fn group_of(&self, client_id: &str) -> Arc<Group> {
+        let assigned = self.membership.lock().get(client_id).cloned();
+        let groups = self.groups.lock();
+        assigned
+            .and_then(|id| groups.get(&id).cloned())
+            .unwrap_or_else(|| Arc::clone(&groups[DEFAULT_GROUP]))
+    }
+
+    /// Move a client to another group and tell its connection
+    pub(crate) fn assign(&self, client_id: &str, group_id: &str) -> Result<(), Error> {
+        self.group(group_id)?;
+        self.membership
+            .lock()
+            .insert(client_id.to_string(), group_id.to_string());
+        self.notify(client_id, Outbound::Regroup);
+        Ok(())
+    }
+
+    /// Connected clients in a group with their last reported volume and mute state
+    pub(crate) fn group_members(&self, group_id: &str) -> Vec<GroupMember> {
+        let clients: Vec<ConnectedClient> = self.clients.lock().values().cloned().collect();
+        let mut members: Vec<GroupMember> = clients
+            .into_iter()
+            .filter(|client| self.group_of(&client.client_id).id == group_id)
+            .map(|client| GroupMember {
+                volume: client.player_state.as_ref().and_then(|s| s.volume),
+                muted: client.player_state.as_ref().and_then(|s| s.muted),
+                client_id: client.client_id,
+                name: client.name,
+            })
+            .collect();
+        members.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        members
+    }
+
+    /// Fill in the member list of a group/update
+    pub(crate) fn with_members(&self, mut update: GroupUpdate) -> GroupUpdate {
+        if let Some(group_id) = &update.group_id {
+            update.members = Some(self.group_members(group_id));
+        }
+        update
+    }
+
+    /// Tell a group's connections that its members changed
+    pub(crate) fn announce_members(&self, group: &Group) {
+        group.publish(StreamEvent::Group(group.update()));
+    }
+
+    /// Settings for a client (defaults if none were configured)
+    pub(crate) fn settings_for(&self, client_id: &str) -> ClientSettings {
+        self.settings
+            .lock()
+            .get(client_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Volume and mute commands for a connected client, if its volume is managed
+    ///
+    /// Volume is managed once a server volume is set or the client has settings.
+    pub(crate) fn volume_commands_for(&self, client: &ConnectedClient) -> Vec<Message> {
+        let configured = self.settings.lock().get(&client.client_id).cloned();
+        let server_volume = *self.volume.lock();
+        if configured.is_none() && server_volume.is_none() {
+            return Vec::new();
+        }
+        let settings = configured.unwrap_or_default();
+        clients::volume_commands(
+            &client.supported_commands,
+            settings.effective_volume(server_volume.unwrap_or(100)),
+            settings.muted,
+        )
+    }
+
+    /// Queue a message for a single connected client
+    pub(crate) fn send_to(&self, client_id: &str, msg: Message) {
+        self.notify(client_id, Outbound::Message(Box::new(msg)));
+    }
+
+    fn notify(&self, client_id: &str, item: Outbound) {
+        if let Some(outbox) = self.outboxes.lock().get(client_id) {
+            let _ = outbox.send(item);
+        }
+    }
+
+    /// Re-send volume and mute commands to a connected client
+    fn push_volume(&self, client_id: &str) {
+        let client = self.clients.lock().get(client_id).cloned();
+        if let Some(client) = client {
+            for msg in self.volume_commands_for(&client) {
+                self.send_to(client_id, msg);
+            }
+        }
+    }
+}
+
+/// Cloneable handle for inspecting a running server
+#[derive(Debug, Clone)]
+pub struct ServerHandle {
+    shared: Arc<Shared>,
+}
+
+impl ServerHandle {
+    /// Get the players currently connected
+    pub fn clients(&self) -> Vec<ConnectedClient> {
+        self.shared.clients.lock().values().cloned().collect()
+    }
+
+    /// Get the server loop clock
+    pub fn clock(&self) -> ServerClock {
+        self.shared.clock.clone()
+    }
+
+    /// Get the format of the default group's active stream, if any
+    pub fn current_format(&self) -> Option<AudioFormat> {
+        self.shared.groups.lock()[DEFAULT_GROUP]
+            .current_format
+            .lock()
+            .clone()
+    }
+
+    /// Publish now-playing metadata to the default group
+    ///
+    /// The latest state is also sent to clients that join the group later.
+    pub fn set_metadata(&self, metadata: MetadataState) {
+        // The default group always exists
+        let _ = self.set_group_metadata(DEFAULT_GROUP, metadata);
+    }
+
+    /// Publish now-playing metadata to every client in a group
+    pub fn set_group_metadata(&self, group_id: &str, metadata: MetadataState) -> Result<(), Error> {
+        let group = self.shared.group(group_id)?;
+        let state = ServerState {
+            metadata: Some(metadata),
+            controller: None,
+        };
+        *group.state.lock() = Some(state.clone());
+        group.publish(StreamEvent::State(Box::new(state)));
+        Ok(())
+    }
+
+    /// Replace the default group's source without dropping clients
+    ///
+    /// See [`switch_group_source`](Self::switch_group_source).
+    pub fn switch_source<S: AudioSource + 'static>(
+        &self,
+        source: S,
+        crossfade: Option<Duration>,
+    ) -> Result<(), Error> {
+        self.switch_group_source(DEFAULT_GROUP, source, crossfade)
+    }
+
+    /// Replace the source a group is being served from without dropping clients
+    ///
+    /// The switch happens at the next chunk boundary and the timeline continues, so
+    /// connections and clock sync are untouched. If the new source has a different
+    /// format, the group's clients receive stream/end followed by stream/start with
+    /// the new format; otherwise playback continues seamlessly, fading from the old
+    /// source to the new one over `crossfade` if given. Fails if the group is not
+    /// being served.
+    pub fn switch_group_source<S: AudioSource + 'static>(
+        &self,
+        group_id: &str,
+        source: S,
+        crossfade: Option<Duration>,
+    ) -> Result<(), Error> {
+        let group = self.shared.group(group_id)?;
+        FrameLayout::for_format(source.format())?;
+        let switch = SourceSwitch {
+            source: Box::new(source),
+            crossfade,
+        };
+        let sent = match group.switch.lock().as_ref() {
+            Some(pump) => pump.send(switch).is_ok(),
+            None => false,
+        };
+        if !sent {
+            return Err(Error::Protocol(format!(
+                "Group '{}' is not streaming",
+                group_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Create an empty group
+    pub fn create_group(&self, group_id: &str, name: &str) -> Result<(), Error> {
+        let mut groups = self.shared.groups.lock();
+        if groups.contains_key(group_id) {
+            return Err(Error::Protocol(format!(
+                "Group '{}' already exists",
+                group_id
+            )));
+        }
+        groups.insert(group_id.to_string(), Arc::new(Group::new(group_id, name)));
+        Ok(())
+    }
+
+    /// Remove a group, moving its clients to the default group
+    pub fn remove_group(&self, group_id: &str) -> Result<(), Error> {
+        if group_id == DEFAULT_GROUP {
+            return Err(Error::Protocol(
+                "The default group cannot be removed".to_string(),
+            ));
+        }
+        self.shared
+            .groups
+            .lock()
+            .remove(group_id)
+            .ok_or_else(|| Error::Protocol(format!("Unknown group '{}'", group_id)))?;
+
+        let moved: Vec<String> = {
+            let mut membership = self.shared.membership.lock();
+            let moved = membership
+                .iter()
+                .filter(|(_, g)| *g == group_id)
+                .map(|(c, _)| c.clone())
+                .collect();
+            membership.retain(|_, g| g != group_id);
+            moved
+        };
+        for client_id in moved {
+            self.shared.notify(&client_id, Outbound::Regroup);
+        }
+        Ok(())
+    }
+
+    /// Move a client to another group
+    ///
+    /// The assignment is remembered for clients that are not connected yet. A connected
+    /// client receives stream/end for its old stream, then group/update and the new
+    /// group's stream/start.
+    pub fn assign_client(&self, client_id: &str, group_id: &str) -> Result<(), Error> {
+        self.shared.assign(client_id, group_id)
+    }
+
+    /// Get the ID of the group a client belongs to
+    pub fn client_group(&self, client_id: &str) -> String {
+        self.shared.group_of(client_id).id.clone()
+    }
+
+    /// Get a snapshot of every group and its connected members
+    pub fn groups(&self) -> Vec<GroupInfo> {
+        let client_ids: Vec<String> = self.shared.clients.lock().keys().cloned().collect();
+        let groups: Vec<Arc<Group>> = self.shared.groups.lock().values().cloned().collect();
+        groups
+            .iter()
+            .map(|group| {
+                let members = client_ids
+                    .iter()
+                    .filter(|id| self.shared.group_of(id).id == group.id)
+                    .cloned()
+                    .collect();
+                group.info(members)
+            })
+            .collect()
+    }
+
+    /// Get the settings for a client (defaults if none were configured)
+    pub fn client_settings(&self, client_id: &str) -> ClientSettings {
+        self.shared.settings_for(client_id)
+    }
+
+    /// Replace the settings for a client
+    ///
+    /// Delay changes apply from the next chunk; volume and mute are pushed to the
+    /// client immediately if it is connected.
+    pub fn set_client_settings(&self, client_id: &str, settings: ClientSettings) {
+        self.shared
+            .settings
+            .lock()
+            .insert(client_id.to_string(), settings);
+        self.shared.push_volume(client_id);
+    }
+
+    /// Set the static delay offset for a client
+    pub fn set_client_delay(&self, client_id: &str, delay: Duration) {
+        let mut settings = self.client_settings(client_id);
+        settings.delay = delay;
+        self.set_client_settings(client_id, settings);
+    }
+
+    /// Set the volume trim (percentage points) for a client
+    pub fn set_client_volume_trim(&self, client_id: &str, trim: i8) {
+        let mut settings = self.client_settings(client_id);
+        settings.volume_trim = trim;
+        self.set_client_settings(client_id, settings);
+    }
+
+    /// Mute or unmute a single client
+    pub fn set_client_muted(&self, client_id: &str, muted: bool) {
+        let mut settings = self.client_settings(client_id);
+        settings.muted = muted;
+        self.set_client_settings(client_id, settings);
+    }
+
+    /// Set the server volume (0-100); each client receives it adjusted by its trim
+    pub fn set_volume(&self, volume: u8) {
+        *self.shared.volume.lock() = Some(volume.min(100));
+        let ids: Vec<String> = self.shared.clients.lock().keys().cloned().collect();
+        for id in ids {
+            self.shared.push_volume(&id);
+        }
+    }
+}
+
+/// Sendspin server accepting player connections
+///
+/// The listener starts accepting as soon as the server is bound; call [`Server::serve`]
+/// to stream a source to every connected player.
+pub struct Server {
+    shared: Arc<Shared>,
+    local_addr: SocketAddr,
+    accept_task: JoinHandle<()>,
+}
+
+impl Server {
+    /// Bind to an address and start accepting connections
+    pub async fn bind(addr: impl ToSocketAddrs, config: ServerConfig) -> Result<Self, Error> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| Error::Connection(e.to_string()))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| Error::Connection(e.to_string()))?;
+
+        let mut groups = HashMap::new();
+        groups.insert(
+            DEFAULT_GROUP.to_string(),
+            Arc::new(Group::new(DEFAULT_GROUP, &config.name)),
+        );
+        let clock = config.clock.clone().unwrap_or_default();
+        let shared = Arc::new(Shared {
+            config,
+            clock,
+            clients: Mutex::new(HashMap::new()),
+            groups: Mutex::new(groups),
+            membership: Mutex::new(HashMap::new()),
+            settings: Mutex::new(HashMap::new()),
+            volume: Mutex::new(None),
+            outboxes: Mutex::new(HashMap::new()),
+        });
+
+        let accept_shared = Arc::clone(&shared);
+        let accept_task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, addr)) => {
+                        let shared = Arc::clone(&accept_shared);
+                        tokio::spawn(async move {
+                            if let Err(e) = connection::handle(stream, addr, shared).await {
+                                log::warn!("Connection from {} ended: {}", addr, e);
+                            }
+                        });
+                    }
+                    Err(e) => log::error!("Accept failed: {}", e),
+                }
+            }
+        });
+
+        log::info!("Sendspin server listening on {}", local_addr);
+
+        Ok(Self {
+            shared,
+            local_addr,
+            accept_task,
+        })
+    }
+
+    /// Get the bound address
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Get a handle for inspecting the server
+    pub fn handle(&self) -> ServerHandle {
+        ServerHandle {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Stream a source to the players in the default group until it ends
+    pub async fn serve<S: AudioSource + 'static>(&self, source: S) -> Result<(), Error> {
+        self.serve_group(DEFAULT_GROUP, source).await
+    }
+
+    /// Stream a source to the players in a group until it ends
+    ///
+    /// Chunks are timestamped `buffer_lead` ahead of the server clock and paced in
+    /// real time. If the source falls behind (e.g., a stalled pipe) the timeline is
+    /// restarted with a fresh lead. With silence suppression configured, the stream
+    /// is ended while the source is silent and restarted when signal returns. Each
+    /// group plays one source at a time; groups can be served concurrently. The
+    /// source can be replaced while streaming with
+    /// [`ServerHandle::switch_group_source`]; this returns when the active source ends.
+    pub async fn serve_group<S: AudioSource + 'static>(
+        &self,
+        group_id: &str,
+        source: S,
+    ) -> Result<(), Error> {
+        let group = self.shared.group(group_id)?;
+        let mut format = source.format().clone();
+        let mut layout = FrameLayout::for_format(&format)?;
+        let chunk_duration = self.shared.config.chunk_duration;
+        let lead = self.shared.config.buffer_lead.as_micros() as i64;
+        let clock = self.shared.clock.clone();
+
+        // Sources block, so read them on their own thread
+        let (tx, mut rx) = mpsc::channel::<Result<Pumped, Error>>(8);
+        let (switch_tx, mut switches) = mpsc::unbounded_channel::<SourceSwitch>();
+        std::thread::spawn(move || {
+            let mut source: Box<dyn AudioSource> = Box::new(source);
+            let mut fade: Option<Crossfade> = None;
+            loop {
+                while let Ok(switch) = switches.try_recv() {
+                    let outgoing = std::mem::replace(&mut source, switch.source);
+                    fade = None;
+                    if outgoing.format() == source.format() {
+                        fade = switch.crossfade.map(|d| Crossfade::new(outgoing, d));
+                    } else {
+                        let format = source.format().clone();
+                        if tx.blocking_send(Ok(Pumped::Format(format))).is_err() {
+                            return;
+                        }
+                    }
+                }
+
+                let next = source.next_chunk(frames_per_chunk(source.format(), chunk_duration));
+                let done = !matches!(next, Ok(Some(_)));
+                let item = match next {
+                    Ok(Some(data)) => match fade.as_mut() {
+                        Some(f) => Ok(Pumped::Chunk(f.apply(data, source.format()))),
+                        None => Ok(Pumped::Chunk(data)),
+                    },
+                    Ok(None) => break,
+                    Err(e) => Err(e),
+                };
+                if fade.as_ref().is_some_and(Crossfade::is_done) {
+                    fade = None;
+                }
+                if tx.blocking_send(item).is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        let mut silence = self.shared.config.silence.map(SilenceDetector::new);
+        if !group.begin_stream(format.clone()) {
+            return Err(Error::Protocol(format!(
+                "Group '{}' is already streaming",
+                group_id
+            )));
+        }
+        *group.switch.lock() = Some(switch_tx);
+
+        let mut base = clock.now_micros() + lead;
+        let mut frames_sent: usize = 0;
+        let result = loop {
+            let data = match rx.recv().await {
+                Some(Ok(Pumped::Chunk(data))) => data,
+                Some(Ok(Pumped::Format(next))) => {
+                    // Continue the timeline where the previous source left off
+                    base += layout.frames_to_micros(frames_sent);
+                    frames_sent = 0;
+                    layout = match FrameLayout::for_format(&next) {
+                        Ok(layout) => layout,
+                        Err(e) => break Err(e),
+                    };
+                    format = next;
+                    log::info!("Source switched to {:?} for group {}", format, group.id);
+                    // A stream suspended for silence restarts with the new format later
+                    if group.current_format.lock().is_some() {
+                        group.suspend();
+                        group.resume(format.clone());
+                    }
+                    continue;
+                }
+                Some(Err(e)) => break Err(e),
+                None => break Ok(()),
+            };
+
+            let mut timestamp = base + layout.frames_to_micros(frames_sent);
+            let now = clock.now_micros();
+            if timestamp - lead > now {
+                clock.sleep_until(timestamp - lead).await;
+            } else if timestamp < now + lead / 2 {
+                log::warn!(
+                    "Source underrun: chunk would play in {}µs, restarting timeline",
+                    timestamp - now
+                );
+                base = now + lead;
+                frames_sent = 0;
+                timestamp = base;
+            }
+
+            let frames = data.len() / layout.frame_size();
+            frames_sent += frames;
+
+            let action = match silence.as_mut() {
+                Some(detector) => detector.observe(&data, &format, layout.frames_to_micros(frames)),
+                None => SilenceAction::Send,
+            };
+            match action {
+                SilenceAction::Send => {}
+                SilenceAction::Suspend => {
+                    log::info!("Source silent, ending stream for group {}", group.id);
+                    group.suspend();
+                    continue;
+                }
+                SilenceAction::Drop => continue,
+                SilenceAction::Resume => {
+                    log::info!("Signal detected, restarting stream for group {}", group.id);
+                    group.resume(format.clone());
+                }
+            }
+            group.publish(StreamEvent::Chunk {
+                timestamp,
+                data: Arc::from(data),
+            });
+        };
+
+        group.switch.lock().take();
+        group.end_stream();
+        result
+    }
+}
+
+/// Output of a group's source thread
+enum Pumped {
+    /// PCM from the active source
+    Chunk(Vec<u8>),
+    /// The source was replaced by one with a different format
+    Format(AudioFormat),
+}
+
+/// Frames of `format` in one chunk of `chunk_duration`
+fn frames_per_chunk(format: &AudioFormat, chunk_duration: Duration) -> usize {
+    ((format.sample_rate as u128 * chunk_duration.as_micros() / 1_000_000) as usize).max(1)
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+    }
+}
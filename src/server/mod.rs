@@ -0,0 +1,40 @@
+// ABOUTME: Server-side Sendspin implementation
+// ABOUTME: Streams audio from pluggable sources to connected players
+
+/// Allow/deny rules and policy callbacks for connecting clients
+pub mod access;
+/// Per-client delay, volume trim, and mute settings
+pub mod clients;
+/// Server loop clock
+pub mod clock;
+/// Per-connection handshake, time sync, and stream forwarding
+mod connection;
+/// Crossfading between sources on a switch
+pub mod crossfade;
+/// FLAC file and stream source, with the "symphonia" feature
+#[cfg(feature = "symphonia")]
+pub mod flac;
+/// Playback groups with independent sources
+pub mod group;
+/// Icecast/HTTP stream source with ICY metadata
+pub mod icecast;
+/// Listener, configuration, and stream pump
+pub mod listener;
+/// Silence suppression
+pub mod silence;
+/// Audio sources feeding the server
+pub mod source;
+/// Reference tone source
+pub mod tone;
+
+pub use access::{AccessControl, AccessDecision, AccessRequest, AccessRule};
+pub use clients::ClientSettings;
+pub use clock::ServerClock;
+#[cfg(feature = "symphonia")]
+pub use flac::FlacSource;
+pub use group::{GroupInfo, DEFAULT_GROUP};
+pub use icecast::{IcecastSource, IcyMetadata, SourceDecoders};
+pub use listener::{ConnectedClient, Server, ServerConfig, ServerHandle};
+pub use silence::SilenceConfig;
+pub use source::{parse_pcm_format, AudioSource, ReaderSource};
+pub use tone::ToneSource;
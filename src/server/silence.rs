@@ -0,0 +1,142 @@
+// ABOUTME: Silence suppression for the server
+// ABOUTME: Ends the stream while the source is silent and restarts it when signal returns
+
+use crate::audio::{AudioFormat, Codec};
+use std::time::Duration;
+
+/// Silence suppression settings
+///
+/// When the source stays below `threshold_db` for `hold`, the server sends stream/end
+/// and stops sending chunks, letting players idle. The first chunk above the threshold
+/// starts a fresh stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceConfig {
+    /// Peak level below which audio counts as silent (dBFS)
+    pub threshold_db: f32,
+    /// How long the source must stay silent before the stream is ended
+    pub hold: Duration,
+}
+
+impl Default for SilenceConfig {
+    fn default() -> Self {
+        Self {
+            threshold_db: -60.0,
+            hold: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SilenceConfig {
+    /// Threshold as a linear amplitude relative to full scale
+    pub fn threshold_amplitude(&self) -> f32 {
+        10f32.powf(self.threshold_db / 20.0)
+    }
+}
+
+/// Peak level of little-endian PCM relative to full scale (0.0 to 1.0)
+///
+/// Supports 16-, 24-, and 32-bit samples; other bit depths report full scale so
+/// they are never treated as silent.
+pub fn peak_level(data: &[u8], bit_depth: u8) -> f32 {
+    match bit_depth {
+        16 => {
+            let peak = data
+                .chunks_exact(2)
+                .map(|b| (i16::from_le_bytes([b[0], b[1]]) as i32).abs())
+                .max()
+                .unwrap_or(0);
+            peak as f32 / 32768.0
+        }
+        24 => {
+            let peak = data
+                .chunks_exact(3)
+                .map(|b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8).abs())
+                .max()
+                .unwrap_or(0);
+            peak as f32 / 8_388_608.0
+        }
+        32 => {
+            let peak = data
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]).unsigned_abs())
+                .max()
+                .unwrap_or(0);
+            peak as f32 / 2_147_483_648.0
+        }
+        _ => 1.0,
+    }
+}
+
+/// Peak level of little-endian 32-bit float PCM (0.0 to 1.0, NaN counts as silence)
+pub fn float_peak_level(data: &[u8]) -> f32 {
+    data.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).abs())
+        .filter(|v| !v.is_nan())
+        .fold(0.0, f32::max)
+        .min(1.0)
+}
+
+/// What the stream pump should do with a chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SilenceAction {
+    /// Send the chunk as usual
+    Send,
+    /// The hold time elapsed: end the stream and drop the chunk
+    Suspend,
+    /// Still silent while suspended: drop the chunk
+    Drop,
+    /// Signal returned: start a new stream, then send the chunk
+    Resume,
+}
+
+/// Tracks how long the source has been silent
+#[derive(Debug)]
+pub(crate) struct SilenceDetector {
+    threshold: f32,
+    hold_micros: i64,
+    silent_micros: i64,
+    suspended: bool,
+}
+
+impl SilenceDetector {
+    pub(crate) fn new(config: SilenceConfig) -> Self {
+        Self {
+            threshold: config.threshold_amplitude(),
+            hold_micros: config.hold.as_micros() as i64,
+            silent_micros: 0,
+            suspended: false,
+        }
+    }
+
+    /// Classify the next chunk of `duration_micros` of audio
+    pub(crate) fn observe(
+        &mut self,
+        data: &[u8],
+        format: &AudioFormat,
+        duration_micros: i64,
+    ) -> SilenceAction {
+        let peak = match format.codec {
+            Codec::PcmFloat => float_peak_level(data),
+            _ => peak_level(data, format.bit_depth),
+        };
+        if peak >= self.threshold {
+            self.silent_micros = 0;
+            if self.suspended {
+                self.suspended = false;
+                return SilenceAction::Resume;
+            }
+            return SilenceAction::Send;
+        }
+
+        if self.suspended {
+            return SilenceAction::Drop;
+        }
+        self.silent_micros += duration_micros;
+        if self.silent_micros >= self.hold_micros {
+            self.suspended = true;
+            SilenceAction::Suspend
+        } else {
+            SilenceAction::Send
+        }
+    }
+}
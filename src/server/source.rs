@@ -0,0 +1,218 @@
+// ABOUTME: Audio sources feeding the server
+// ABOUTME: AudioSource trait, raw PCM reader source, and PCM format spec parsing
+
+use crate::audio::convert;
+use crate::audio::decode::PcmEndian;
+use crate::audio::{AudioFormat, Codec};
+use crate::error::Error;
+use std::io::{ErrorKind, Read};
+
+/// Producer of interleaved PCM for the server to stream
+///
+/// Sources are blocking; the server drives them from a dedicated thread.
+pub trait AudioSource: Send {
+    /// Format of the PCM produced by this source
+    fn format(&self) -> &AudioFormat;
+
+    /// Read up to `max_frames` frames of little-endian interleaved PCM
+    ///
+    /// Returns `Ok(None)` at end of stream. Returned chunks always hold whole frames.
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error>;
+}
+
+impl<S: AudioSource + ?Sized> AudioSource for Box<S> {
+    fn format(&self) -> &AudioFormat {
+        (**self).format()
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        (**self).next_chunk(max_frames)
+    }
+}
+
+/// Parse a raw PCM format spec such as `s16le,48000,2`
+///
+/// Supported sample formats are `s16le`, `s24le`, `s32le`, and `f32le` (float).
+pub fn parse_pcm_format(spec: &str) -> Result<AudioFormat, Error> {
+    let parts: Vec<&str> = spec.split(',').map(str::trim).collect();
+    let [sample, rate, channels] = parts.as_slice() else {
+        return Err(Error::Protocol(format!(
+            "Invalid PCM format '{}': expected <sample>,<rate>,<channels>",
+            spec
+        )));
+    };
+
+    let (codec, bit_depth) = match *sample {
+        "s16le" => (Codec::Pcm, 16),
+        "s24le" => (Codec::Pcm, 24),
+        "s32le" => (Codec::Pcm, 32),
+        "f32le" => (Codec::PcmFloat, 32),
+        other => {
+            return Err(Error::Protocol(format!(
+                "Unsupported sample format '{}' (expected s16le, s24le, s32le, or f32le)",
+                other
+            )))
+        }
+    };
+    let sample_rate: u32 = rate
+        .parse()
+        .ok()
+        .filter(|r| *r > 0)
+        .ok_or_else(|| Error::Protocol(format!("Invalid sample rate '{}'", rate)))?;
+    let channels: u8 = channels
+        .parse()
+        .ok()
+        .filter(|c| *c > 0)
+        .ok_or_else(|| Error::Protocol(format!("Invalid channel count '{}'", channels)))?;
+
+    Ok(AudioFormat {
+        codec,
+        sample_rate,
+        channels,
+        bit_depth,
+        codec_header: None,
+        endian: Some(PcmEndian::Little),
+    })
+}
+
+/// Source reading raw PCM from any reader (stdin, a file, a pipe)
+pub struct ReaderSource<R> {
+    reader: R,
+    format: AudioFormat,
+    frame_size: usize,
+    endian: PcmEndian,
+}
+
+impl<R: Read + Send> ReaderSource<R> {
+    /// Create a source producing PCM in `format` from `reader`
+    pub fn new(reader: R, format: AudioFormat) -> Result<Self, Error> {
+        if !format.codec.is_pcm() {
+            return Err(Error::Protocol(format!(
+                "Reader source only supports PCM, got {:?}",
+                format.codec
+            )));
+        }
+        if format.codec == Codec::PcmFloat && format.bit_depth != 32 {
+            return Err(Error::Protocol(format!(
+                "Float PCM must be 32-bit, got {} bits",
+                format.bit_depth
+            )));
+        }
+        let frame_size = (format.bit_depth as usize / 8) * format.channels as usize;
+        if frame_size == 0 {
+            return Err(Error::Protocol("Invalid PCM frame size".to_string()));
+        }
+        Ok(Self {
+            reader,
+            // Output is always little-endian, whatever the input
+            format: AudioFormat {
+                endian: Some(PcmEndian::Little),
+                ..format
+            },
+            frame_size,
+            endian: PcmEndian::Little,
+        })
+    }
+
+    /// Create a source for input in the given byte order (output is always little-endian)
+    pub fn with_endian(reader: R, format: AudioFormat, endian: PcmEndian) -> Result<Self, Error> {
+        let mut source = Self::new(reader, format)?;
+        source.endian = endian;
+        Ok(source)
+    }
+
+    /// Create a source from a WAV stream, reading the format from its header
+    ///
+    /// Integer PCM of 16, 24, or 32 bits and 32-bit float are supported. The data chunk size is
+    /// ignored so live streams with a placeholder size play until the input ends.
+    pub fn from_wav(mut reader: R) -> Result<Self, Error> {
+        let mut riff = [0u8; 12];
+        read_exact(&mut reader, &mut riff)?;
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(Error::Protocol("Not a RIFF/WAVE stream".to_string()));
+        }
+
+        let mut format = None;
+        loop {
+            let mut header = [0u8; 8];
+            read_exact(&mut reader, &mut header)?;
+            let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+            match &header[0..4] {
+                b"fmt " => {
+                    let mut fmt = vec![0u8; len + len % 2];
+                    read_exact(&mut reader, &mut fmt)?;
+                    if fmt.len() < 16 {
+                        return Err(Error::Protocol("WAV fmt chunk too short".to_string()));
+                    }
+                    let tag = u16::from_le_bytes([fmt[0], fmt[1]]);
+                    let channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+                    let sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+                    let bits = u16::from_le_bytes([fmt[14], fmt[15]]);
+                    // 1 = PCM, 3 = IEEE float, 0xFFFE = WAVE_FORMAT_EXTENSIBLE
+                    let codec = match (tag, bits) {
+                        (1 | 0xFFFE, 16 | 24 | 32) => Codec::Pcm,
+                        (3, 32) => Codec::PcmFloat,
+                        _ => {
+                            return Err(Error::Protocol(format!(
+                                "Unsupported WAV encoding: format tag {:#x}, {} bits",
+                                tag, bits
+                            )))
+                        }
+                    };
+                    format = Some(AudioFormat {
+                        codec,
+                        sample_rate,
+                        channels: channels as u8,
+                        bit_depth: bits as u8,
+                        codec_header: None,
+                        endian: Some(PcmEndian::Little),
+                    });
+                }
+                b"data" => {
+                    let format = format.ok_or_else(|| {
+                        Error::Protocol("WAV data chunk before fmt chunk".to_string())
+                    })?;
+                    return Self::new(reader, format);
+                }
+                _ => {
+                    // Skip unknown chunks (LIST, fact, ...)
+                    let mut skip = vec![0u8; len + len % 2];
+                    read_exact(&mut reader, &mut skip)?;
+                }
+            }
+        }
+    }
+}
+
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), Error> {
+    reader.read_exact(buf).map_err(|e| Error::Io(e.to_string()))
+}
+
+impl<R: Read + Send> AudioSource for ReaderSource<R> {
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        let mut buf = vec![0u8; max_frames * self.frame_size];
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::Io(e.to_string())),
+            }
+        }
+
+        // Drop a trailing partial frame at end of input
+        buf.truncate(filled - filled % self.frame_size);
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        if self.endian == PcmEndian::Big {
+            convert::swap_endian(&mut buf, self.format.bit_depth)?;
+        }
+        Ok(Some(buf))
+    }
+}
@@ -0,0 +1,97 @@
+// ABOUTME: Reference tone audio source
+// ABOUTME: Streams a phase-continuous sine for end-to-end sample accuracy checks
+
+use crate::audio::decode::PcmEndian;
+use crate::audio::tone::{check_tone, tone_value, DEFAULT_TONE_HZ};
+use crate::audio::{convert, AudioFormat, Codec, Sample};
+use crate::error::Error;
+use crate::server::source::AudioSource;
+use std::time::Duration;
+
+/// Source producing a sine tone on every channel
+///
+/// Pair with [`ToneVerifier`](crate::audio::tone::ToneVerifier) on the player to
+/// check the whole pipeline for dropped or inserted samples.
+pub struct ToneSource {
+    format: AudioFormat,
+    frequency: f64,
+    amplitude: f64,
+    frame: u64,
+    end: Option<u64>,
+}
+
+impl ToneSource {
+    /// Create a [`DEFAULT_TONE_HZ`] tone at half scale in a PCM `format`
+    pub fn new(format: AudioFormat) -> Result<Self, Error> {
+        if format.codec != Codec::Pcm || !(format.bit_depth == 16 || format.bit_depth == 24) {
+            return Err(Error::Protocol(format!(
+                "Tone source needs 16- or 24-bit PCM, got {:?} {}bit",
+                format.codec, format.bit_depth
+            )));
+        }
+        if format.channels == 0 || format.sample_rate == 0 {
+            return Err(Error::Protocol(format!(
+                "Invalid PCM format: {}Hz {}ch",
+                format.sample_rate, format.channels
+            )));
+        }
+        Ok(Self {
+            format,
+            frequency: DEFAULT_TONE_HZ,
+            amplitude: 0.5,
+            frame: 0,
+            end: None,
+        })
+    }
+
+    /// Use a different tone frequency in Hz
+    pub fn with_frequency(mut self, frequency: f64) -> Result<Self, Error> {
+        check_tone(frequency, self.format.sample_rate)?;
+        self.frequency = frequency;
+        Ok(self)
+    }
+
+    /// Use a different level (0.0 to 1.0 of full scale)
+    pub fn with_amplitude(mut self, amplitude: f64) -> Self {
+        self.amplitude = amplitude.clamp(0.0, 1.0);
+        self
+    }
+
+    /// End the stream after `duration` instead of running forever
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.end = Some((duration.as_secs_f64() * self.format.sample_rate as f64).round() as u64);
+        self
+    }
+
+    /// Tone frequency in Hz
+    pub fn frequency(&self) -> f64 {
+        self.frequency
+    }
+}
+
+impl AudioSource for ToneSource {
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        let frames = match self.end {
+            Some(end) => (end.saturating_sub(self.frame)).min(max_frames as u64),
+            None => max_frames as u64,
+        };
+        if frames == 0 {
+            return Ok(None);
+        }
+
+        let channels = self.format.channels as usize;
+        let scale = self.amplitude * Sample::MAX.0 as f64;
+        let mut samples = Vec::with_capacity(frames as usize * channels);
+        for frame in self.frame..self.frame + frames {
+            let value = tone_value(self.frequency, self.format.sample_rate, frame);
+            let sample = Sample((value * scale).round() as i32);
+            samples.extend(std::iter::repeat_n(sample, channels));
+        }
+        self.frame += frames;
+        convert::to_bytes(&samples, self.format.bit_depth, PcmEndian::Little).map(Some)
+    }
+}
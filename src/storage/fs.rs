@@ -0,0 +1,84 @@
+// ABOUTME: Filesystem storage backend
+// ABOUTME: Stores each key as a file under <root>/<namespace>/<key>
+
+use crate::error::Error;
+use crate::storage::{validate_name, Storage};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Storage backed by a directory tree
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    /// Create storage rooted at `root` (created lazily on first write)
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Get the root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, namespace: &str, key: &str) -> Result<PathBuf, Error> {
+        validate_name(namespace)?;
+        validate_name(key)?;
+        Ok(self.root.join(namespace).join(key))
+    }
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Storage(e.to_string())
+}
+
+impl Storage for FsStorage {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        match std::fs::read(self.path(namespace, key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        let path = self.path(namespace, key)?;
+        std::fs::create_dir_all(self.root.join(namespace)).map_err(io_error)?;
+
+        // Write to a temporary file first so readers never see partial values
+        let tmp = self.root.join(namespace).join(format!(".{}.tmp", key));
+        std::fs::write(&tmp, value).map_err(io_error)?;
+        std::fs::rename(&tmp, &path).map_err(io_error)
+    }
+
+    fn remove(&self, namespace: &str, key: &str) -> Result<(), Error> {
+        match std::fs::remove_file(self.path(namespace, key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, Error> {
+        validate_name(namespace)?;
+        let entries = match std::fs::read_dir(self.root.join(namespace)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(e)),
+        };
+
+        let mut keys = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(io_error)?;
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_name(name).is_ok() {
+                    keys.push(name.to_string());
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
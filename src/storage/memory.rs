@@ -0,0 +1,61 @@
+// ABOUTME: In-memory storage backend
+// ABOUTME: Non-persistent storage for tests and ephemeral clients
+
+use crate::error::Error;
+use crate::storage::{validate_name, Storage};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+
+/// Storage held in memory (lost when dropped)
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<BTreeMap<(String, String), Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// Create empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        validate_name(namespace)?;
+        validate_name(key)?;
+        Ok(self
+            .entries
+            .lock()
+            .get(&(namespace.to_string(), key.to_string()))
+            .cloned())
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error> {
+        validate_name(namespace)?;
+        validate_name(key)?;
+        self.entries
+            .lock()
+            .insert((namespace.to_string(), key.to_string()), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, namespace: &str, key: &str) -> Result<(), Error> {
+        validate_name(namespace)?;
+        validate_name(key)?;
+        self.entries
+            .lock()
+            .remove(&(namespace.to_string(), key.to_string()));
+        Ok(())
+    }
+
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, Error> {
+        validate_name(namespace)?;
+        Ok(self
+            .entries
+            .lock()
+            .keys()
+            .filter(|(ns, _)| ns == namespace)
+            .map(|(_, key)| key.clone())
+            .collect())
+    }
+}
@@ -0,0 +1,62 @@
+// ABOUTME: Pluggable persistence for caches and client state
+// ABOUTME: Storage trait with filesystem and in-memory implementations
+
+/// Filesystem storage backend
+pub mod fs;
+/// In-memory storage backend
+pub mod memory;
+
+pub use fs::FsStorage;
+pub use memory::MemoryStorage;
+
+use crate::error::Error;
+
+/// Namespace used for client identity
+pub const IDENTITY_NAMESPACE: &str = "identity";
+
+/// Namespaced key-value storage
+///
+/// Namespaces and keys must be non-empty and may only contain ASCII letters, digits,
+/// `-`, `_` and `.` (and must not start with `.`), so every backend can map them to
+/// file names safely.
+pub trait Storage: Send + Sync {
+    /// Get the value stored under a key (None if missing)
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Store a value under a key, replacing any previous value
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<(), Error>;
+
+    /// Remove a key (removing a missing key is not an error)
+    fn remove(&self, namespace: &str, key: &str) -> Result<(), Error>;
+
+    /// List the keys in a namespace
+    fn keys(&self, namespace: &str) -> Result<Vec<String>, Error>;
+}
+
+/// Validate a namespace or key name
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Storage(format!("Invalid storage name: {:?}", name)))
+    }
+}
+
+/// Load the persisted client id, generating and storing a new one on first use
+pub fn client_id(storage: &dyn Storage) -> Result<String, Error> {
+    if let Some(bytes) = storage.get(IDENTITY_NAMESPACE, "client_id")? {
+        if let Ok(id) = String::from_utf8(bytes) {
+            if !id.is_empty() {
+                return Ok(id);
+            }
+        }
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    storage.put(IDENTITY_NAMESPACE, "client_id", id.as_bytes())?;
+    Ok(id)
+}
@@ -3,6 +3,14 @@
 
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+/// Current Unix time in microseconds, the client clock for `client/time` (t1 and t4)
+pub fn unix_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or(0)
+}
+
 /// Clock synchronization quality
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncQuality {
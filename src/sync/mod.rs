@@ -4,4 +4,4 @@
 /// Clock synchronization implementation
 pub mod clock;
 
-pub use clock::{ClockSync, SyncQuality};
+pub use clock::{unix_micros, ClockSync, SyncQuality};
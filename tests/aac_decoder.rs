@@ -0,0 +1,51 @@
+// ABOUTME: Tests for the AAC codec header and AAC through the symphonia backend
+// ABOUTME: Decoder tests run only with the "symphonia" feature
+
+use sendspin::audio::decode::AacConfig;
+
+#[test]
+fn test_parse_audio_specific_config() {
+    // AAC-LC, 44.1kHz, stereo
+    let config = AacConfig::parse(&[0x12, 0x10]).unwrap();
+    assert_eq!(config.object_type, 2);
+    assert_eq!(config.sample_rate, 44_100);
+    assert_eq!(config.channel_config, 2);
+
+    // HE-AAC, 24kHz core, mono
+    let config = AacConfig::parse(&[0x2B, 0x08]).unwrap();
+    assert_eq!(config.object_type, 5);
+    assert_eq!(config.sample_rate, 24_000);
+    assert_eq!(config.channel_config, 1);
+
+    // Explicit 24-bit sample rate
+    let config = AacConfig::parse(&[0x17, 0x80, 0x5D, 0xC0, 0x10]).unwrap();
+    assert_eq!(config.sample_rate, 48_000);
+    assert_eq!(config.channel_config, 2);
+}
+
+#[test]
+fn test_reject_bad_audio_specific_config() {
+    assert!(AacConfig::parse(&[]).is_err());
+    assert!(AacConfig::parse(&[0x12]).is_err());
+    // Reserved sample rate index 13
+    assert!(AacConfig::parse(&[0x16, 0x90]).is_err());
+}
+
+#[cfg(feature = "symphonia")]
+mod symphonia {
+    use sendspin::audio::decode::SymphoniaDecoder;
+    use sendspin::audio::{AudioFormat, Codec};
+
+    fn format(codec_header: Option<Vec<u8>>) -> AudioFormat {
+        let mut format = AudioFormat::new(Codec::Aac, 44_100, 2, 16);
+        format.codec_header = codec_header;
+        format
+    }
+
+    #[test]
+    fn test_decoder_accepts_adts_and_raw_streams() {
+        assert!(SymphoniaDecoder::new(&format(None)).is_ok());
+        assert!(SymphoniaDecoder::new(&format(Some(vec![0x12, 0x10]))).is_ok());
+        assert!(SymphoniaDecoder::new(&format(Some(vec![0x12]))).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+// ABOUTME: Tests for server access control
+// ABOUTME: Covers rule parsing and matching, check order, and rejected connections
+
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::ClientHello;
+use sendspin::protocol::Role;
+use sendspin::server::{
+    AccessControl, AccessDecision, AccessRequest, AccessRule, Server, ServerConfig,
+};
+use sendspin::Error;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn request(client_id: &str, name: &str, addr: &str) -> AccessRequest {
+    AccessRequest {
+        client_id: client_id.to_string(),
+        name: name.to_string(),
+        addr: addr.parse().unwrap(),
+    }
+}
+
+fn hello(client_id: &str, name: &str) -> ClientHello {
+    ClientHello::new(client_id, name, vec![Role::Player(1)])
+}
+
+#[test]
+fn test_access_rule_parsing() {
+    let rule: AccessRule = "client:kitchen".parse().unwrap();
+    assert_eq!(rule, AccessRule::ClientId("kitchen".to_string()));
+    let rule: AccessRule = "name:Living Room".parse().unwrap();
+    assert_eq!(rule, AccessRule::Name("Living Room".to_string()));
+    let rule: AccessRule = "ip:10.0.0.0/8".parse().unwrap();
+    assert_eq!(rule.to_string(), "ip:10.0.0.0/8");
+    let rule: AccessRule = "ip:::1".parse().unwrap();
+    assert_eq!(rule.to_string(), "ip:::1/128");
+
+    for bad in [
+        "kitchen",
+        "client:",
+        "ip:10.0.0.0/33",
+        "ip:nope",
+        "mac:00:11",
+    ] {
+        assert!(bad.parse::<AccessRule>().is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_access_rule_matching() {
+    let lan: AccessRule = "ip:192.168.1.0/24".parse().unwrap();
+    assert!(lan.matches(&request("a", "A", "192.168.1.20:5000")));
+    assert!(!lan.matches(&request("a", "A", "192.168.2.20:5000")));
+    // IPv4-mapped IPv6 peers match IPv4 rules
+    assert!(lan.matches(&request("a", "A", "[::ffff:192.168.1.20]:5000")));
+
+    let everyone: AccessRule = "ip:0.0.0.0/0".parse().unwrap();
+    assert!(everyone.matches(&request("a", "A", "8.8.8.8:1")));
+    assert!(!everyone.matches(&request("a", "A", "[2001:db8::1]:1")));
+
+    let v6: AccessRule = "ip:2001:db8::/32".parse().unwrap();
+    assert!(v6.matches(&request("a", "A", "[2001:db8::1]:1")));
+
+    assert!(AccessRule::ClientId("a".to_string()).matches(&request("a", "A", "1.2.3.4:1")));
+    assert!(!AccessRule::Name("B".to_string()).matches(&request("a", "A", "1.2.3.4:1")));
+}
+
+#[tokio::test]
+async fn test_access_check_order() {
+    let access = AccessControl::new()
+        .allow("ip:127.0.0.0/8".parse().unwrap())
+        .deny("client:blocked".parse().unwrap())
+        .with_policy(|request: AccessRequest| async move {
+            if request.name.starts_with("Guest") {
+                AccessDecision::Deny("Guests are not allowed".to_string())
+            } else {
+                AccessDecision::Allow
+            }
+        });
+
+    assert_eq!(
+        access.check(&request("ok", "Kitchen", "127.0.0.1:1")).await,
+        AccessDecision::Allow
+    );
+    // Denylist wins over the allowlist
+    assert!(matches!(
+        access
+            .check(&request("blocked", "Kitchen", "127.0.0.1:1"))
+            .await,
+        AccessDecision::Deny(_)
+    ));
+    assert!(matches!(
+        access.check(&request("ok", "Kitchen", "10.0.0.1:1")).await,
+        AccessDecision::Deny(_)
+    ));
+    assert_eq!(
+        access.check(&request("ok", "Guest 1", "127.0.0.1:1")).await,
+        AccessDecision::Deny("Guests are not allowed".to_string())
+    );
+
+    assert_eq!(
+        AccessControl::default()
+            .check(&request("any", "Any", "8.8.8.8:1"))
+            .await,
+        AccessDecision::Allow
+    );
+}
+
+async fn server(access: AccessControl) -> (Server, String) {
+    let config = ServerConfig {
+        access,
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    (server, url)
+}
+
+#[tokio::test]
+async fn test_rejected_client_gets_close_reason() {
+    let access = AccessControl::new()
+        .deny("client:intruder".parse().unwrap())
+        .with_policy(|request: AccessRequest| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if request.addr.ip().is_loopback() && request.name != "Nobody" {
+                AccessDecision::Allow
+            } else {
+                AccessDecision::Deny("Unknown device".to_string())
+            }
+        });
+    let (server, url) = server(access).await;
+
+    match ProtocolClient::connect(&url, hello("intruder", "Intruder")).await {
+        Err(Error::Connection(reason)) => assert!(reason.contains("not allowed"), "{}", reason),
+        Err(other) => panic!("Expected connection error, got {}", other),
+        Ok(_) => panic!("Expected the client to be rejected"),
+    }
+    match ProtocolClient::connect(&url, hello("someone", "Nobody")).await {
+        Err(Error::Connection(reason)) => assert!(reason.contains("Unknown device"), "{}", reason),
+        Err(other) => panic!("Expected connection error, got {}", other),
+        Ok(_) => panic!("Expected the client to be rejected"),
+    }
+
+    let client = timeout(
+        Duration::from_secs(2),
+        ProtocolClient::connect(&url, hello("kitchen", "Kitchen")),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(client.server_hello().active_roles, vec![Role::Player(1)]);
+    // Registration follows server/hello, so give it a moment
+    let handle = server.handle();
+    timeout(Duration::from_secs(2), async {
+        while handle.clients().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let ids: Vec<String> = handle.clients().into_iter().map(|c| c.client_id).collect();
+    assert_eq!(ids, vec!["kitchen".to_string()]);
+}
+
+#[tokio::test]
+async fn test_allowlist_rejects_unlisted_clients() {
+    let access = AccessControl::new().allow(AccessRule::ClientId("kitchen".to_string()));
+    let (_server, url) = server(access).await;
+
+    assert!(ProtocolClient::connect(&url, hello("kitchen", "Kitchen"))
+        .await
+        .is_ok());
+    let rejected = ProtocolClient::connect(&url, hello("garage", "Garage")).await;
+    assert!(matches!(rejected, Err(Error::Connection(reason)) if reason.contains("allowlist")));
+}
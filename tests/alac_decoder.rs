@@ -0,0 +1,259 @@
+// ABOUTME: Tests for the ALAC codec header and ALAC through the symphonia backend
+// ABOUTME: Frames are built by a minimal encoder; decoding tests need the "symphonia" feature
+
+use sendspin::audio::decode::AlacConfig;
+
+/// ALACSpecificConfig with the encoder's default Rice parameters
+fn config(bit_depth: u8, channels: u8) -> Vec<u8> {
+    let mut config = 4096u32.to_be_bytes().to_vec();
+    config.extend_from_slice(&[0, bit_depth, 40, 10, 14, channels]);
+    config.extend_from_slice(&255u16.to_be_bytes());
+    config.extend_from_slice(&[0; 8]);
+    config.extend_from_slice(&44_100u32.to_be_bytes());
+    config
+}
+
+#[test]
+fn test_parse_config() {
+    let parsed = AlacConfig::parse(&config(24, 2)).unwrap();
+    assert_eq!(parsed.frame_length, 4096);
+    assert_eq!(parsed.bit_depth, 24);
+    assert_eq!((parsed.pb, parsed.mb, parsed.kb), (40, 10, 14));
+    assert_eq!(parsed.channels, 2);
+    assert_eq!(parsed.max_run, 255);
+    assert_eq!(parsed.sample_rate, 44_100);
+
+    // Wrapped in the alac atom of an MP4 sample description
+    let mut atom = 36u32.to_be_bytes().to_vec();
+    atom.extend_from_slice(b"alac");
+    atom.extend_from_slice(&[0; 4]);
+    atom.extend_from_slice(&config(24, 2));
+    assert_eq!(AlacConfig::parse(&atom).unwrap(), parsed);
+}
+
+#[test]
+fn test_reject_bad_config() {
+    assert!(AlacConfig::parse(&config(16, 2)[..20]).is_err());
+    assert!(AlacConfig::parse(&config(12, 2)).is_err());
+    assert!(AlacConfig::parse(&config(16, 0)).is_err());
+    let mut version = config(16, 2);
+    version[4] = 1;
+    assert!(AlacConfig::parse(&version).is_err());
+}
+
+#[cfg(feature = "symphonia")]
+mod decoder {
+    use super::config;
+    use sendspin::audio::decode::{Decoder, SymphoniaDecoder};
+    use sendspin::audio::{AudioFormat, Codec, Sample};
+
+    fn decoder(bit_depth: u8, channels: u8) -> SymphoniaDecoder {
+        let format = AudioFormat::new(Codec::Alac, 44_100, channels, bit_depth)
+            .with_codec_header(config(bit_depth, channels));
+        SymphoniaDecoder::new(&format).unwrap()
+    }
+
+    #[derive(Default)]
+    struct BitWriter {
+        bytes: Vec<u8>,
+        bits: usize,
+    }
+
+    impl BitWriter {
+        fn write(&mut self, value: u32, bits: u32) {
+            for i in (0..bits).rev() {
+                if self.bits.is_multiple_of(8) {
+                    self.bytes.push(0);
+                }
+                let bit = (value >> i & 1) as u8;
+                *self.bytes.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+                self.bits += 1;
+            }
+        }
+
+        fn rice(&mut self, n: u32, m: u32, k: u32, max_bits: u32) {
+            let prefix = n / m;
+            if prefix >= 9 {
+                self.write(0x1ff, 9);
+                self.write(n, max_bits);
+                return;
+            }
+            self.write((1 << prefix) - 1, prefix);
+            self.write(0, 1);
+            if k > 1 {
+                match n % m {
+                    0 => self.write(0, k - 1),
+                    rem => self.write(rem + 1, k),
+                }
+            }
+        }
+
+        /// Adaptive Rice coding as the decoder expects it (pb 40, mb 10, kb 14)
+        fn residuals(&mut self, values: &[i32], max_bits: u32) {
+            let (pb, kb) = (40u32, 14u32);
+            let mut mb = 10u32;
+            let mut zmode = 0;
+            let mut c = 0;
+            while c < values.len() {
+                let k = (31 - ((mb >> 9) + 3).leading_zeros()).min(kb);
+                let x = values[c];
+                let ndecode = if x >= 0 {
+                    2 * x as u32
+                } else {
+                    (-2 * x - 1) as u32
+                };
+                let n = ndecode - zmode;
+                self.rice(n, (1 << k) - 1, k, max_bits);
+                c += 1;
+                mb = pb * (n + zmode) + mb - ((pb * mb) >> 9);
+                if n > 0xffff {
+                    mb = 0xffff;
+                }
+                zmode = 0;
+                if (mb << 2) < 512 && c < values.len() {
+                    zmode = 1;
+                    let k = mb.leading_zeros() - 24 + ((mb + 16) >> 6);
+                    let run = values[c..].iter().take_while(|&&v| v == 0).count() as u32;
+                    self.rice(run, ((1 << k) - 1) & ((1 << kb) - 1), k, 16);
+                    c += run as usize;
+                    mb = 0;
+                }
+            }
+        }
+
+        /// Element header of a partial frame with `frames` samples
+        fn element(&mut self, tag: u32, escape: bool, frames: u32) {
+            self.write(tag, 3);
+            self.write(0, 4 + 12);
+            self.write(0b1000 | escape as u32, 4);
+            self.write(frames, 32);
+        }
+
+        fn finish(mut self) -> Vec<u8> {
+            self.write(7, 3);
+            self.bytes
+        }
+    }
+
+    fn tone(len: usize, amplitude: f64) -> Vec<i32> {
+        (0..len)
+            .map(|i| ((i as f64 * 0.05).sin() * amplitude) as i32)
+            .collect()
+    }
+
+    #[test]
+    fn test_uncompressed_stereo_frame() {
+        let left = tone(64, 30_000.0);
+        let right: Vec<i32> = left.iter().map(|s| -s / 2).collect();
+        let mut w = BitWriter::default();
+        w.element(1, true, 64);
+        for (l, r) in left.iter().zip(&right) {
+            w.write(*l as u32 & 0xffff, 16);
+            w.write(*r as u32 & 0xffff, 16);
+        }
+        let samples = decoder(16, 2).decode(&w.finish()).unwrap();
+        let expected: Vec<Sample> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(&l, &r)| [l, r])
+            .map(|s| Sample::from_i16(s as i16))
+            .collect();
+        assert_eq!(&samples[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_uncompressed_24_bit_frame() {
+        let values = tone(32, 8_000_000.0);
+        let mut w = BitWriter::default();
+        w.element(0, true, 32);
+        for v in &values {
+            w.write(*v as u32 & 0xff_ffff, 24);
+        }
+        let samples = decoder(24, 1).decode(&w.finish()).unwrap();
+        let expected: Vec<Sample> = values.into_iter().map(Sample).collect();
+        assert_eq!(&samples[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_compressed_mono_frame_with_silence() {
+        let mut values = tone(100, 20_000.0);
+        values.extend(std::iter::repeat_n(0, 200));
+        values.extend(tone(100, 500.0));
+        let mut w = BitWriter::default();
+        w.element(0, false, values.len() as u32);
+        // No mixing; mode 0 without coefficients passes residuals through
+        w.write(0, 16);
+        w.write(0, 8);
+        w.write(4 << 5, 8);
+        w.residuals(&values, 16);
+        let samples = decoder(16, 1).decode(&w.finish()).unwrap();
+        let expected: Vec<Sample> = values
+            .into_iter()
+            .map(|s| Sample::from_i16(s as i16))
+            .collect();
+        assert_eq!(&samples[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_compressed_stereo_frame_with_mixing() {
+        let left = tone(256, 20_000.0);
+        let right: Vec<i32> = left.iter().map(|s| s / 3 + 100).collect();
+        let (mix_bits, mix_res) = (2, 2);
+        // Inverse of the decoder's unmixing
+        let v: Vec<i32> = left.iter().zip(&right).map(|(l, r)| l - r).collect();
+        let u: Vec<i32> = left
+            .iter()
+            .zip(&v)
+            .map(|(l, v)| l - v + ((mix_res * v) >> mix_bits))
+            .collect();
+
+        let mut w = BitWriter::default();
+        w.element(1, false, 256);
+        w.write(mix_bits as u32, 8);
+        w.write(mix_res as u32, 8);
+        w.write(0, 8);
+        w.write(4 << 5, 8);
+        w.write(0, 8);
+        w.write(4 << 5, 8);
+        // Stereo channels carry one extra bit
+        w.residuals(&u, 17);
+        w.residuals(&v, 17);
+        let samples = decoder(16, 2).decode(&w.finish()).unwrap();
+        let expected: Vec<Sample> = left
+            .iter()
+            .zip(&right)
+            .flat_map(|(&l, &r)| [l, r])
+            .map(|s| Sample::from_i16(s as i16))
+            .collect();
+        assert_eq!(&samples[..], &expected[..]);
+    }
+
+    #[test]
+    fn test_reject_bad_frames() {
+        let decoder = decoder(16, 2);
+        assert!(decoder.decode(&[]).is_err());
+        // A mono element in a stereo stream leaves the second channel silent
+        let mut w = BitWriter::default();
+        w.element(0, true, 1);
+        w.write(1000, 16);
+        let samples = decoder.decode(&w.finish()).unwrap();
+        assert_eq!(&samples[..], &[Sample::from_i16(1000), Sample::from_i16(0)]);
+        // Truncated verbatim data
+        let mut w = BitWriter::default();
+        w.element(1, true, 64);
+        w.write(0, 32);
+        assert!(decoder.decode(&w.finish()).is_err());
+    }
+
+    #[test]
+    fn test_needs_matching_codec_header() {
+        let format = |codec_header, channels| {
+            let mut format = AudioFormat::new(Codec::Alac, 44_100, channels, 16);
+            format.codec_header = codec_header;
+            format
+        };
+        assert!(SymphoniaDecoder::new(&format(None, 2)).is_err());
+        assert!(SymphoniaDecoder::new(&format(Some(config(16, 1)), 2)).is_err());
+        assert!(SymphoniaDecoder::new(&format(Some(config(16, 2)), 2)).is_ok());
+    }
+}
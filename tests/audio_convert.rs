@@ -0,0 +1,219 @@
+// ABOUTME: Tests for the public PCM conversion API
+// ABOUTME: Golden vectors and a matrix of bit depth, endianness, channel, and rate conversions
+
+use sendspin::audio::convert::{
+    convert, remix_channels, resample_linear, swap_endian, to_bytes, to_samples, PcmSpec,
+};
+use sendspin::audio::decode::PcmEndian;
+use sendspin::audio::Sample;
+
+const DEPTHS: [u8; 2] = [16, 24];
+const ENDIANS: [PcmEndian; 2] = [PcmEndian::Little, PcmEndian::Big];
+
+/// Samples that survive 16-bit truncation exactly
+fn golden_samples() -> Vec<Sample> {
+    vec![
+        Sample(0),
+        Sample(0x123400),
+        Sample(-256),
+        Sample::MIN,
+        Sample(0x7FFF00),
+    ]
+}
+
+/// Golden encodings of `golden_samples()` per bit depth and endianness
+fn golden_bytes(bit_depth: u8, endian: PcmEndian) -> Vec<u8> {
+    match (bit_depth, endian) {
+        (16, PcmEndian::Little) => vec![0, 0, 0x34, 0x12, 0xFF, 0xFF, 0x00, 0x80, 0xFF, 0x7F],
+        (16, PcmEndian::Big) => vec![0, 0, 0x12, 0x34, 0xFF, 0xFF, 0x80, 0x00, 0x7F, 0xFF],
+        (24, PcmEndian::Little) => vec![
+            0, 0, 0, 0x00, 0x34, 0x12, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x80, 0x00, 0xFF, 0x7F,
+        ],
+        (24, PcmEndian::Big) => vec![
+            0, 0, 0, 0x12, 0x34, 0x00, 0xFF, 0xFF, 0x00, 0x80, 0x00, 0x00, 0x7F, 0xFF, 0x00,
+        ],
+        _ => unreachable!(),
+    }
+}
+
+fn spec(sample_rate: u32, channels: u8, bit_depth: u8, endian: PcmEndian) -> PcmSpec {
+    PcmSpec {
+        sample_rate,
+        channels,
+        bit_depth,
+        endian,
+    }
+}
+
+#[test]
+fn test_golden_vectors_decode_and_encode() {
+    for bit_depth in DEPTHS {
+        for endian in ENDIANS {
+            let bytes = golden_bytes(bit_depth, endian);
+            assert_eq!(
+                to_samples(&bytes, bit_depth, endian).unwrap(),
+                golden_samples(),
+                "decode {}-bit {:?}",
+                bit_depth,
+                endian
+            );
+            assert_eq!(
+                to_bytes(&golden_samples(), bit_depth, endian).unwrap(),
+                bytes,
+                "encode {}-bit {:?}",
+                bit_depth,
+                endian
+            );
+        }
+    }
+}
+
+#[test]
+fn test_bit_depth_and_endian_matrix() {
+    for from_depth in DEPTHS {
+        for from_endian in ENDIANS {
+            for to_depth in DEPTHS {
+                for to_endian in ENDIANS {
+                    let from = spec(48000, 1, from_depth, from_endian);
+                    let to = spec(48000, 1, to_depth, to_endian);
+                    let out = convert(&golden_bytes(from_depth, from_endian), &from, &to).unwrap();
+                    assert_eq!(
+                        out,
+                        golden_bytes(to_depth, to_endian),
+                        "{}-bit {:?} -> {}-bit {:?}",
+                        from_depth,
+                        from_endian,
+                        to_depth,
+                        to_endian
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_swap_endian_matches_golden() {
+    for bit_depth in DEPTHS {
+        let mut bytes = golden_bytes(bit_depth, PcmEndian::Little);
+        swap_endian(&mut bytes, bit_depth).unwrap();
+        assert_eq!(bytes, golden_bytes(bit_depth, PcmEndian::Big));
+    }
+    let mut bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+    swap_endian(&mut bytes, 32).unwrap();
+    assert_eq!(bytes, [4, 3, 2, 1, 8, 7, 6, 5]);
+    assert!(swap_endian(&mut [0u8; 4], 12).is_err());
+}
+
+#[test]
+fn test_8_and_32_bit_scaling() {
+    // 8-bit is unsigned around 128; 32-bit keeps the top 24 bits
+    let bytes = [0x80, 0x00, 0xFF, 0x81];
+    let samples = to_samples(&bytes, 8, PcmEndian::Little).unwrap();
+    assert_eq!(
+        samples,
+        vec![Sample(0), Sample::MIN, Sample(0x7F0000), Sample(0x010000)]
+    );
+    assert_eq!(to_bytes(&samples, 8, PcmEndian::Big).unwrap(), bytes);
+
+    let wide = [i32::MAX, i32::MIN, 0x1234_5678, -256];
+    for endian in ENDIANS {
+        let bytes: Vec<u8> = wide
+            .iter()
+            .flat_map(|s| match endian {
+                PcmEndian::Little => s.to_le_bytes(),
+                PcmEndian::Big => s.to_be_bytes(),
+            })
+            .collect();
+        let samples = to_samples(&bytes, 32, endian).unwrap();
+        assert_eq!(
+            samples,
+            vec![Sample::MAX, Sample::MIN, Sample(0x123456), Sample(-1)]
+        );
+        let round_trip = to_samples(&to_bytes(&samples, 32, endian).unwrap(), 32, endian);
+        assert_eq!(round_trip.unwrap(), samples);
+    }
+}
+
+#[test]
+fn test_downconversion_truncates_low_byte_and_clamps() {
+    let samples = [Sample(0x1234FF), Sample(i32::MAX), Sample(i32::MIN)];
+    assert_eq!(
+        to_bytes(&samples, 16, PcmEndian::Little).unwrap(),
+        vec![0x34, 0x12, 0xFF, 0x7F, 0x00, 0x80]
+    );
+}
+
+#[test]
+fn test_channel_matrix() {
+    let s = Sample;
+    let mono = vec![s(100), s(-200)];
+    let stereo = vec![s(100), s(300), s(-200), s(-400)];
+
+    assert_eq!(remix_channels(&mono, 1, 1).unwrap(), mono);
+    assert_eq!(
+        remix_channels(&mono, 1, 2).unwrap(),
+        vec![s(100), s(100), s(-200), s(-200)]
+    );
+    assert_eq!(
+        remix_channels(&stereo, 2, 1).unwrap(),
+        vec![s(200), s(-300)]
+    );
+    assert_eq!(remix_channels(&mono, 1, 6).unwrap().len(), 12);
+
+    // 5.1 down to stereo keeps front left/right
+    let surround: Vec<Sample> = (1..=6).map(s).collect();
+    assert_eq!(remix_channels(&surround, 6, 2).unwrap(), vec![s(1), s(2)]);
+    // Stereo up to quad leaves the rear silent
+    assert_eq!(
+        remix_channels(&stereo[..2], 2, 4).unwrap(),
+        vec![s(100), s(300), s(0), s(0)]
+    );
+
+    assert!(remix_channels(&mono, 0, 2).is_err());
+    assert!(remix_channels(&mono, 1, 0).is_err());
+}
+
+#[test]
+fn test_sample_rate_matrix() {
+    let rates = [8000, 22050, 44100, 48000, 96000];
+    for from in rates {
+        for to in rates {
+            // One second of stereo ramp
+            let input: Vec<Sample> = (0..from as i32)
+                .flat_map(|i| [Sample(i), Sample(-i)])
+                .collect();
+            let out = resample_linear(&input, 2, from, to).unwrap();
+            assert_eq!(out.len(), to as usize * 2, "{} -> {}", from, to);
+            // Channels stay separated and the ramp stays monotonic
+            for pair in out.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+                assert!(pair[1][0].0 >= pair[0][0].0);
+                assert!(pair[1][1].0 <= pair[0][1].0);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_resample_interpolates_linearly() {
+    let input = [Sample(0), Sample(1000)];
+    assert_eq!(
+        resample_linear(&input, 1, 1, 2).unwrap(),
+        vec![Sample(0), Sample(500), Sample(1000), Sample(1000)]
+    );
+    assert!(resample_linear(&input, 1, 0, 48000).is_err());
+    assert!(resample_linear(&[], 1, 44100, 48000).unwrap().is_empty());
+}
+
+#[test]
+fn test_full_conversion() {
+    // 16-bit BE mono at 24kHz to 24-bit LE stereo at 48kHz
+    let from = spec(24000, 1, 16, PcmEndian::Big);
+    let to = spec(48000, 2, 24, PcmEndian::Little);
+    let out = convert(&[0x12, 0x34, 0x12, 0x34], &from, &to).unwrap();
+    assert_eq!(out.len(), 4 * 2 * 3);
+    assert_eq!(&out[..6], &[0x00, 0x34, 0x12, 0x00, 0x34, 0x12]);
+
+    assert!(convert(&[0; 4], &from, &spec(48000, 2, 20, PcmEndian::Little)).is_err());
+    assert!(convert(&[0; 4], &spec(48000, 1, 12, PcmEndian::Little), &to).is_err());
+}
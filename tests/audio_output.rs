@@ -4,13 +4,7 @@ use std::sync::Arc;
 
 #[test]
 fn test_audio_output_creation() {
-    let format = AudioFormat {
-        codec: Codec::Pcm,
-        sample_rate: 48000,
-        channels: 2,
-        bit_depth: 24,
-        codec_header: None,
-    };
+    let format = AudioFormat::new(Codec::Pcm, 48000, 2, 24);
 
     // CpalOutput::new() should succeed
     let output = CpalOutput::new(format);
@@ -19,13 +13,7 @@ fn test_audio_output_creation() {
 
 #[test]
 fn test_audio_output_write() {
-    let format = AudioFormat {
-        codec: Codec::Pcm,
-        sample_rate: 48000,
-        channels: 2,
-        bit_depth: 24,
-        codec_header: None,
-    };
+    let format = AudioFormat::new(Codec::Pcm, 48000, 2, 24);
 
     let mut output = CpalOutput::new(format).unwrap();
 
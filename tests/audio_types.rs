@@ -1,3 +1,4 @@
+use sendspin::audio::decode::PcmEndian;
 use sendspin::audio::{AudioFormat, Codec, Sample};
 
 #[test]
@@ -24,14 +25,33 @@ fn test_sample_clamp() {
 
 #[test]
 fn test_audio_format_creation() {
-    let format = AudioFormat {
-        codec: Codec::Pcm,
-        sample_rate: 48000,
-        channels: 2,
-        bit_depth: 24,
-        codec_header: None,
-    };
+    let format = AudioFormat::new(Codec::Pcm, 48000, 2, 24);
 
     assert_eq!(format.sample_rate, 48000);
     assert_eq!(format.channels, 2);
+    assert_eq!(format.codec_header, None);
+    assert_eq!(format.endian, None);
+
+    let format = format
+        .with_codec_header(vec![1, 2])
+        .with_endian(PcmEndian::Big);
+    assert_eq!(format.codec_header, Some(vec![1, 2]));
+    assert_eq!(format.endian, Some(PcmEndian::Big));
+}
+
+#[test]
+fn test_sample_from_f32() {
+    assert_eq!(Sample::from_f32(1.0), Sample::MAX);
+    assert_eq!(Sample::from_f32(-2.0).0, -Sample::MAX.0);
+    assert_eq!(Sample::from_f32(0.5).0, Sample::MAX.0 / 2);
+    assert_eq!(Sample::from_f32(f32::NAN), Sample::ZERO);
+}
+
+#[test]
+fn test_codec_names() {
+    assert_eq!(Codec::from_name("opus"), Some(Codec::Opus));
+    assert_eq!(Codec::Pcm.name(), "pcm");
+    assert_eq!(Codec::from_name("pcm_float"), Some(Codec::PcmFloat));
+    assert!(Codec::PcmFloat.is_pcm() && !Codec::Flac.is_pcm());
+    assert_eq!(Codec::from_name("mystery"), None);
 }
@@ -0,0 +1,63 @@
+// ABOUTME: Tests for the rolling audit log
+// ABOUTME: Validates window pruning and the rendered report format
+
+use sendspin::audit::{AuditEvent, AuditLog, Direction};
+use sendspin::sync::SyncQuality;
+use std::time::Duration;
+
+#[test]
+fn test_audit_log_records_events() {
+    let audit = AuditLog::new();
+    audit.record(AuditEvent::Protocol {
+        direction: Direction::Inbound,
+        message_type: "stream/start".to_string(),
+    });
+    audit.record(AuditEvent::Sync {
+        rtt_micros: 1500,
+        quality: SyncQuality::Good,
+    });
+
+    assert_eq!(audit.entries().len(), 2);
+}
+
+#[test]
+fn test_audit_log_prunes_old_entries() {
+    let audit = AuditLog::with_window(Duration::from_millis(20));
+    audit.record(AuditEvent::Note("old".to_string()));
+    std::thread::sleep(Duration::from_millis(30));
+    audit.record(AuditEvent::Note("new".to_string()));
+
+    let entries = audit.entries();
+    assert_eq!(entries.len(), 1);
+    match &entries[0].event {
+        AuditEvent::Note(note) => assert_eq!(note, "new"),
+        other => panic!("Unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn test_audit_log_render_and_dump() {
+    let audit = AuditLog::new();
+    audit.record(AuditEvent::Schedule {
+        timestamp: 1_000_000,
+        lead_micros: 250_000,
+    });
+    audit.record(AuditEvent::Output {
+        timestamp: 1_000_000,
+        samples: 960,
+    });
+
+    let report = audit.render();
+    assert!(report.starts_with("# sendspin audit log"));
+    assert!(report.contains("# entries: 2"));
+    assert!(report.contains("# codecs: pcm"));
+    assert!(report.contains("# platform: "));
+    assert!(report.contains("[schedule] ts=1000000 lead=250000µs"));
+    assert!(report.contains("[output] ts=1000000 samples=960"));
+
+    let path = std::env::temp_dir().join(format!("sendspin-audit-{}.log", std::process::id()));
+    audit.dump_to(&path).unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("[output]"));
+    let _ = std::fs::remove_file(&path);
+}
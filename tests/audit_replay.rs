@@ -0,0 +1,123 @@
+// ABOUTME: Tests for deterministic replay of captured audit logs
+// ABOUTME: Validates report parsing, scheduler decisions, and decision inputs
+
+use sendspin::audit::{AuditEvent, AuditLog, CapturedSession, Outcome, Replay, ReplayConfig};
+use sendspin::sync::SyncQuality;
+use std::time::Duration;
+
+const CAPTURE: &str = "\
+# sendspin audit log
+# window_s: 60
+# max_late_us: 20000
+-1.000000s [sync] rtt=1500µs quality=Good
+-1.000000s [schedule] ts=0 lead=10000µs
+-0.990000s [schedule] ts=20000 lead=-30000µs
+-0.980000s [schedule] ts=40000 lead=-5000µs
+-0.900000s [output] ts=0 samples=960
+-0.500000s [protocol] Inbound stream/end
+";
+
+#[test]
+fn test_parse_captured_session() {
+    let session = CapturedSession::parse(CAPTURE).unwrap();
+    assert_eq!(session.header("window_s"), Some("60"));
+    assert_eq!(session.max_late(), Some(Duration::from_millis(20)));
+    assert_eq!(session.entries.len(), 6);
+    assert_eq!(session.entries[0].at, 0);
+    assert_eq!(session.entries[2].at, 10_000);
+    assert_eq!(session.entries[5].at, 500_000);
+    assert!(matches!(
+        session.entries[2].event,
+        AuditEvent::Schedule {
+            timestamp: 20_000,
+            lead_micros: -30_000
+        }
+    ));
+
+    assert!(CapturedSession::parse("-0.1s [bogus] entry").is_err());
+}
+
+#[test]
+fn test_replay_explains_drops_and_delays() {
+    let session = CapturedSession::parse(CAPTURE).unwrap();
+    let replay = Replay::run(&session, ReplayConfig::from_session(&session));
+
+    let first = replay.explain(0).unwrap();
+    assert_eq!(first.outcome, Outcome::Played { late_micros: -1000 });
+    assert_eq!(first.recorded_output_at, Some(100_000));
+
+    let dropped = replay.explain(20_000).unwrap();
+    assert_eq!(
+        dropped.outcome,
+        Outcome::Dropped {
+            late_micros: 30_000
+        }
+    );
+    assert_eq!(dropped.decided_at, Some(10_000));
+    assert_eq!(replay.dropped().count(), 1);
+
+    let delayed = replay.explain(40_000).unwrap();
+    assert_eq!(delayed.outcome, Outcome::Played { late_micros: 5000 });
+    assert_eq!(replay.delayed(Duration::from_millis(1)).count(), 1);
+    assert!(replay.explain(60_000).is_none());
+
+    // The same session always replays the same way
+    let again = Replay::run(&session, ReplayConfig::from_session(&session));
+    assert_eq!(again.chunks(), replay.chunks());
+}
+
+#[test]
+fn test_replay_with_different_policy() {
+    let session = CapturedSession::parse(CAPTURE).unwrap();
+    let replay = Replay::run(&session, ReplayConfig::default());
+
+    assert_eq!(replay.dropped().count(), 0);
+    assert_eq!(
+        replay.explain(20_000).unwrap().outcome,
+        Outcome::Played {
+            late_micros: 30_000
+        }
+    );
+}
+
+#[test]
+fn test_replay_decision_inputs() {
+    let session = CapturedSession::parse(CAPTURE).unwrap();
+    let replay = Replay::run(&session, ReplayConfig::from_session(&session));
+
+    let inputs = replay.inputs_at(10_000);
+    let sync = inputs.sync.unwrap();
+    assert_eq!(sync.rtt_micros, 1500);
+    assert_eq!(sync.quality, SyncQuality::Good);
+    assert_eq!(inputs.sync_offset_micros, Some(-40_000));
+    assert_eq!(inputs.buffer.queued, 1);
+    assert_eq!(inputs.buffer.next_timestamp, Some(20_000));
+    assert_eq!(inputs.max_late, Some(Duration::from_millis(20)));
+
+    let rendered = inputs.to_string();
+    assert!(rendered.contains("rtt=1500µs"));
+    assert!(rendered.contains("drop after 20000µs"));
+
+    assert_eq!(replay.inputs_at(400_000).buffer.queued, 0);
+}
+
+#[test]
+fn test_replay_from_live_log() {
+    let audit = AuditLog::new();
+    audit.set_header("max_late_us", 1000);
+    audit.set_header("max_late_us", 2000);
+    audit.record(AuditEvent::Schedule {
+        timestamp: 0,
+        lead_micros: 50_000,
+    });
+
+    assert_eq!(audit.render().matches("# max_late_us").count(), 1);
+    let session = CapturedSession::from_log(&audit).unwrap();
+    assert_eq!(session.max_late(), Some(Duration::from_millis(2)));
+
+    let replay = Replay::run(&session, ReplayConfig::from_session(&session));
+    assert!(matches!(
+        replay.explain(0).unwrap().outcome,
+        Outcome::Played { late_micros } if late_micros <= 0
+    ));
+}
@@ -12,6 +12,7 @@ use sendspin::protocol::client::{
 #[test]
 fn test_binary_type_constants() {
     assert_eq!(binary_types::PLAYER_AUDIO, 0x04);
+    assert_eq!(binary_types::PLAYER_AUDIO_LAST, 0x07);
     assert_eq!(binary_types::ARTWORK_CHANNEL_0, 0x08);
     assert_eq!(binary_types::ARTWORK_CHANNEL_1, 0x09);
     assert_eq!(binary_types::ARTWORK_CHANNEL_2, 0x0A);
@@ -31,6 +32,16 @@ fn test_is_artwork() {
     assert!(!binary_types::is_artwork(0x10)); // Visualizer
 }
 
+#[test]
+fn test_audio_slot() {
+    assert_eq!(binary_types::audio_slot(0x03), None);
+    assert_eq!(binary_types::audio_slot(0x04), Some(0));
+    assert_eq!(binary_types::audio_slot(0x05), Some(1));
+    assert_eq!(binary_types::audio_slot(0x06), Some(2));
+    assert_eq!(binary_types::audio_slot(0x07), Some(3));
+    assert_eq!(binary_types::audio_slot(0x08), None); // Artwork
+}
+
 #[test]
 fn test_artwork_channel() {
     assert_eq!(binary_types::artwork_channel(0x08), Some(0));
@@ -60,6 +71,23 @@ fn test_audio_chunk_parsing() {
     assert_eq!(&*chunk.data, &[0xDE, 0xAD, 0xBE, 0xEF]);
 }
 
+#[test]
+fn test_audio_chunk_all_slots() {
+    for type_id in 0x04..=0x07u8 {
+        let mut frame = vec![type_id];
+        frame.extend_from_slice(&500i64.to_be_bytes());
+        frame.push(0x01);
+
+        match BinaryFrame::from_bytes(&frame).unwrap() {
+            BinaryFrame::Audio(chunk) => {
+                assert_eq!(chunk.slot, type_id - 0x04);
+                assert_eq!(chunk.timestamp, 500);
+            }
+            other => panic!("Expected audio frame, got {:?}", other),
+        }
+    }
+}
+
 #[test]
 fn test_audio_chunk_wrong_type() {
     let frame: Vec<u8> = vec![
@@ -273,3 +301,72 @@ fn test_binary_frame_empty() {
     let result = BinaryFrame::from_bytes(&frame);
     assert!(result.is_err());
 }
+
+// =============================================================================
+// Encoding Tests
+// =============================================================================
+
+#[test]
+fn test_audio_chunk_to_bytes_round_trip() {
+    let frame = AudioChunk::encode(-2, &[0xAA, 0xBB]);
+    assert_eq!(
+        frame,
+        vec![0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE, 0xAA, 0xBB]
+    );
+
+    let chunk = AudioChunk::from_bytes(&frame).unwrap();
+    assert_eq!(chunk.timestamp, -2);
+    assert_eq!(chunk.slot, 0);
+    assert_eq!(chunk.to_bytes().unwrap(), frame);
+}
+
+#[test]
+fn test_audio_chunk_slot_encoding() {
+    let frame = AudioChunk::encode_slot(3, 7, &[1]).unwrap();
+    assert_eq!(frame[0], binary_types::PLAYER_AUDIO_LAST);
+    assert_eq!(
+        AudioChunk::encode_slot(0, 7, &[1]).unwrap(),
+        AudioChunk::encode(7, &[1])
+    );
+    assert!(AudioChunk::encode_slot(4, 7, &[1]).is_err());
+
+    let chunk = AudioChunk::from_bytes(&frame).unwrap();
+    assert_eq!(chunk.slot, 3);
+    assert_eq!(chunk.to_bytes().unwrap(), frame);
+}
+
+#[test]
+fn test_artwork_chunk_to_bytes() {
+    let frame = ArtworkChunk::encode(2, 1_000, b"png").unwrap();
+    assert_eq!(frame[0], binary_types::ARTWORK_CHANNEL_2);
+
+    let chunk = ArtworkChunk::from_bytes(&frame).unwrap();
+    assert_eq!(chunk.channel, 2);
+    assert_eq!(chunk.timestamp, 1_000);
+    assert_eq!(chunk.to_bytes().unwrap(), frame);
+
+    // Empty payload clears the channel
+    assert!(
+        ArtworkChunk::from_bytes(&ArtworkChunk::encode(0, 0, &[]).unwrap())
+            .unwrap()
+            .is_clear()
+    );
+    assert!(ArtworkChunk::encode(4, 0, &[]).is_err());
+}
+
+#[test]
+fn test_binary_frame_to_bytes_round_trip() {
+    let frames = [
+        AudioChunk::encode(1, &[1, 2, 3]),
+        AudioChunk::encode_slot(2, 1, &[1, 2, 3]).unwrap(),
+        ArtworkChunk::encode(3, 2, &[4]).unwrap(),
+        VisualizerChunk::encode(3, &[5, 6]),
+        vec![0xFF, 0x00, 0x01],
+    ];
+    for frame in frames {
+        assert_eq!(
+            BinaryFrame::from_bytes(&frame).unwrap().to_bytes().unwrap(),
+            frame
+        );
+    }
+}
@@ -0,0 +1,281 @@
+// ABOUTME: Tests for ProtocolClient::builder
+// ABOUTME: Covers the hello, message hook, timeouts, retries, handshake headers, and foreign executors
+
+mod common;
+
+use common::{answer_hello, idle};
+use sendspin::audit::Direction;
+use sendspin::protocol::client::{ClientParts, ProtocolClient};
+use sendspin::protocol::messages::{ClientHello, ClientTime, GoodbyeReason, Message, ServerHello};
+use sendspin::protocol::{HandshakeRequest, ReconnectPolicy, Role};
+use sendspin::Error;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+
+fn hello() -> ClientHello {
+    ClientHello::new("builder-test", "Builder Test", vec![Role::Player(1)])
+}
+
+/// Serve connections after dropping the first `refuse` TCP connections unanswered
+async fn flaky_server(refuse: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/sendspin", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        for _ in 0..refuse {
+            let _dropped = listener.accept().await.unwrap();
+        }
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        if answer_hello(
+            &mut ws,
+            ServerHello::new("mock", "Mock", vec![Role::Player(1)]),
+        )
+        .await
+        {
+            idle(ws).await;
+        }
+    });
+    url
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_limit() {
+    let policy = ReconnectPolicy::exponential(5);
+    assert_eq!(policy.delay(1), Duration::from_millis(500));
+    assert_eq!(policy.delay(2), Duration::from_secs(1));
+    assert_eq!(policy.delay(4), Duration::from_secs(4));
+    assert_eq!(policy.delay(40), Duration::from_secs(30));
+    assert_eq!(ReconnectPolicy::default(), ReconnectPolicy::never());
+}
+
+#[tokio::test]
+async fn test_builder_requires_hello() {
+    let result = ProtocolClient::builder("ws://127.0.0.1:1/sendspin")
+        .connect()
+        .await;
+    assert!(matches!(result, Err(Error::Protocol(_))));
+}
+
+#[tokio::test]
+async fn test_message_hook_sees_both_directions() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    let client = ProtocolClient::builder(flaky_server(0).await)
+        .hello(hello())
+        .on_message(move |direction, msg| {
+            log.lock()
+                .unwrap()
+                .push((direction, msg.message_type().to_string()))
+        })
+        .connect()
+        .await
+        .unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(
+        *seen,
+        [
+            (Direction::Outbound, "client/hello".to_string()),
+            (Direction::Inbound, "server/hello".to_string()),
+            (Direction::Outbound, "client/time".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_connect_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/sendspin", listener.local_addr().unwrap());
+    // Accept the TCP connection but never answer the WebSocket handshake
+    tokio::spawn(async move {
+        let (_stream, _) = listener.accept().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+    });
+
+    let result = ProtocolClient::builder(url)
+        .hello(hello())
+        .connect_timeout(Duration::from_millis(100))
+        .connect()
+        .await;
+    match result {
+        Err(Error::Connection(reason)) => assert!(reason.contains("Timed out"), "{}", reason),
+        Err(other) => panic!("Expected timeout, got {}", other),
+        Ok(_) => panic!("Expected timeout"),
+    }
+}
+
+#[tokio::test]
+async fn test_failed_attempts_are_retried() {
+    let policy = ReconnectPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+    };
+    let url = flaky_server(2).await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .reconnect(policy)
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.server_hello().name, "Mock");
+
+    let url = flaky_server(2).await;
+    let result = ProtocolClient::builder(url)
+        .hello(hello())
+        .reconnect(ReconnectPolicy {
+            max_attempts: 2,
+            ..policy
+        })
+        .connect()
+        .await;
+    assert!(result.is_err());
+}
+
+/// Server that only upgrades requests carrying `Authorization: Bearer secret`;
+/// reports the path and query of each request
+async fn auth_server() -> (String, Arc<Mutex<Option<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/sendspin", listener.local_addr().unwrap());
+    let seen = Arc::new(Mutex::new(None));
+    let log = Arc::clone(&seen);
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        // The callback signature is fixed by tungstenite
+        #[allow(clippy::result_large_err)]
+        let check = |request: &Request, response: Response| {
+            *log.lock().unwrap() = request.uri().path_and_query().map(|p| p.to_string());
+            let authorized = request
+                .headers()
+                .get("Authorization")
+                .is_some_and(|v| v == "Bearer secret");
+            if authorized
+                && request
+                    .headers()
+                    .get("X-Room")
+                    .is_some_and(|v| v == "kitchen")
+            {
+                Ok(response)
+            } else {
+                let mut refusal = ErrorResponse::new(None);
+                *refusal.status_mut() = StatusCode::UNAUTHORIZED;
+                Err(refusal)
+            }
+        };
+        let Ok(mut ws) = tokio_tungstenite::accept_hdr_async(stream, check).await else {
+            return;
+        };
+        if answer_hello(
+            &mut ws,
+            ServerHello::new("mock", "Mock", vec![Role::Player(1)]),
+        )
+        .await
+        {
+            idle(ws).await;
+        }
+    });
+    (url, seen)
+}
+
+#[tokio::test]
+async fn test_headers_and_query_reach_the_server() {
+    let (url, seen) = auth_server().await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .bearer_token("secret")
+        .header("X-Room", "kitchen")
+        .query("token", "a b&c")
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.server_hello().name, "Mock");
+    assert_eq!(
+        seen.lock().unwrap().as_deref(),
+        Some("/sendspin?token=a%20b%26c")
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_upgrade_is_not_retried() {
+    let (url, _seen) = auth_server().await;
+    let result = ProtocolClient::builder(url)
+        .hello(hello())
+        .bearer_token("wrong")
+        .reconnect(ReconnectPolicy::exponential(3))
+        .connect_timeout(Duration::from_secs(2))
+        .connect()
+        .await;
+    match result {
+        Err(Error::Protocol(reason)) => assert!(reason.contains("401"), "{}", reason),
+        Err(other) => panic!("Expected a refused upgrade, got {}", other),
+        Ok(_) => panic!("Expected a refused upgrade"),
+    }
+}
+
+#[test]
+fn test_handshake_request_debug_hides_values() {
+    let request = HandshakeRequest::new()
+        .bearer_token("secret")
+        .query("token", "hunter2");
+    let debug = format!("{:?}", request);
+    assert!(debug.contains("Authorization"));
+    assert!(!debug.contains("secret") && !debug.contains("hunter2"));
+}
+
+/// Minimal executor that is not tokio: polls on this thread, parking between wakeups
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(std::thread::Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark()
+        }
+    }
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_client_usable_outside_tokio() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let url = runtime.block_on(flaky_server(0));
+
+    let client = block_on(
+        ProtocolClient::builder(url)
+            .hello(hello())
+            .runtime(runtime.handle().clone())
+            .connect(),
+    )
+    .unwrap();
+    assert_eq!(client.server_hello().name, "Mock");
+
+    let ClientParts {
+        mut messages,
+        sender,
+        ..
+    } = client.into_parts();
+    block_on(sender.send_message(Message::ClientTime(ClientTime {
+        client_transmitted: 0,
+    })))
+    .unwrap();
+    block_on(sender.close(GoodbyeReason::Shutdown)).unwrap();
+    assert!(block_on(messages.recv()).is_none());
+}
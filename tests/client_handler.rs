@@ -0,0 +1,135 @@
+// ABOUTME: Tests for the event-driven ClientHandler API
+// ABOUTME: Drives ProtocolClient::run against a mock server and records the callbacks
+
+mod common;
+
+use common::{idle, mock_server, text};
+use futures_util::{SinkExt, StreamExt};
+use sendspin::prelude::*;
+use sendspin::protocol::messages::{
+    ClientTime, ServerGoodbye, ServerGoodbyeReason, ServerState, StreamEnd,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+/// Serve one connection: answer client/hello, wait for one more message, then
+/// send server/state, stream/end, one audio chunk, and server/goodbye
+async fn scripted_server() -> String {
+    mock_server(vec![Role::Player(1)], |mut ws| async move {
+        let _ready = ws.next().await;
+        ws.send(text(&Message::ServerState(ServerState {
+            metadata: None,
+            controller: None,
+        })))
+        .await
+        .unwrap();
+        ws.send(text(&Message::StreamEnd(StreamEnd { roles: None })))
+            .await
+            .unwrap();
+        ws.send(WsMessage::Binary(AudioChunk::encode(5, &[1, 2])))
+            .await
+            .unwrap();
+        ws.send(text(&Message::ServerGoodbye(ServerGoodbye {
+            reason: ServerGoodbyeReason::Shutdown,
+        })))
+        .await
+        .unwrap();
+        idle(ws).await;
+    })
+    .await
+}
+
+fn hello() -> ClientHello {
+    ClientHello::new("handler-test", "Handler Test", vec![Role::Player(1)])
+}
+
+async fn connect() -> ProtocolClient {
+    let client = ProtocolClient::connect(&scripted_server().await, hello())
+        .await
+        .unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    client
+}
+
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+    fail_on_stream_end: bool,
+}
+
+impl Recorder {
+    fn record(&self, event: impl Into<String>) {
+        self.events.lock().unwrap().push(event.into());
+    }
+}
+
+impl ClientHandler for Recorder {
+    async fn on_server_state(&mut self, _state: ServerState, _ctx: &ClientContext) -> Result<()> {
+        self.record("state");
+        Ok(())
+    }
+
+    async fn on_stream_end(&mut self, _end: StreamEnd, _ctx: &ClientContext) -> Result<()> {
+        self.record("end");
+        if self.fail_on_stream_end {
+            return Err(Error::Protocol("stop".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn on_audio_chunk(&mut self, chunk: AudioChunk, _ctx: &ClientContext) -> Result<()> {
+        self.record(format!("audio:{}", chunk.timestamp));
+        Ok(())
+    }
+
+    async fn on_disconnect(
+        &mut self,
+        goodbye: Option<ServerGoodbye>,
+        _ctx: &ClientContext,
+    ) -> Result<()> {
+        self.record(format!("disconnect:{:?}", goodbye.map(|g| g.reason)));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_run_dispatches_every_event() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handler = Recorder {
+        events: Arc::clone(&events),
+        fail_on_stream_end: false,
+    };
+    timeout(Duration::from_secs(2), connect().await.run(handler))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let events = events.lock().unwrap().clone();
+    assert_eq!(events.len(), 4, "{:?}", events);
+    // Messages keep their order; frames come from their own queue
+    let messages: Vec<_> = events.iter().filter(|e| !e.starts_with("audio")).collect();
+    assert_eq!(messages, ["state", "end", "disconnect:Some(Shutdown)"]);
+    assert!(events.contains(&"audio:5".to_string()));
+}
+
+#[tokio::test]
+async fn test_handler_error_stops_run() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handler = Recorder {
+        events: Arc::clone(&events),
+        fail_on_stream_end: true,
+    };
+    let result = timeout(Duration::from_secs(2), connect().await.run(handler))
+        .await
+        .unwrap();
+
+    assert!(matches!(result, Err(Error::Protocol(reason)) if reason == "stop"));
+    let events = events.lock().unwrap();
+    assert!(!events.iter().any(|e| e.starts_with("disconnect")));
+}
@@ -0,0 +1,95 @@
+// ABOUTME: Mock Sendspin server shared by the client integration tests
+// ABOUTME: Answers client/hello on a local port, then hands the socket to a per-test script
+
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use sendspin::protocol::messages::{Message, ServerHello};
+use sendspin::protocol::Role;
+use std::future::Future;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::WebSocketStream;
+
+/// Server side of a mock connection
+pub type MockSocket = WebSocketStream<TcpStream>;
+
+/// Serve one WebSocket connection on a free local port; returns its URL
+///
+/// `handler` gets the socket before the client's hello has been read.
+pub async fn serve<F, Fut>(handler: F) -> String
+where
+    F: FnOnce(MockSocket) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}/sendspin", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        handler(ws).await;
+    });
+    url
+}
+
+/// Serve one connection: answer client/hello with `roles`, then run `script`
+pub async fn mock_server<F, Fut>(roles: Vec<Role>, script: F) -> String
+where
+    F: FnOnce(MockSocket) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    mock_server_with_hello(ServerHello::new("mock", "Mock", roles), script).await
+}
+
+/// Like [`mock_server`], answering with a custom server/hello
+pub async fn mock_server_with_hello<F, Fut>(hello: ServerHello, script: F) -> String
+where
+    F: FnOnce(MockSocket) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    serve(|mut ws| async move {
+        if answer_hello(&mut ws, hello).await {
+            script(ws).await;
+        }
+    })
+    .await
+}
+
+/// Read client/hello and reply with `hello`; false once the client has gone
+pub async fn answer_hello<S>(ws: &mut WebSocketStream<S>, hello: ServerHello) -> bool
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let _hello = ws.next().await;
+    send(ws, &Message::ServerHello(hello)).await
+}
+
+/// Send a message as a JSON text frame; false once the client has gone
+pub async fn send<S>(ws: &mut WebSocketStream<S>, msg: &Message) -> bool
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    ws.send(text(msg)).await.is_ok()
+}
+
+/// A message as a JSON text frame
+pub fn text(msg: &Message) -> WsMessage {
+    WsMessage::Text(serde_json::to_string(msg).unwrap())
+}
+
+/// Keep the connection open until the client goes away
+pub async fn idle<S>(mut ws: WebSocketStream<S>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    while ws.next().await.is_some() {}
+}
+
+/// Forward every text frame the client sends until it goes away
+pub async fn record(mut ws: MockSocket, tx: mpsc::UnboundedSender<Message>) {
+    while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+        let _ = tx.send(serde_json::from_str(&text).unwrap());
+    }
+}
@@ -0,0 +1,89 @@
+// ABOUTME: Tests for settings files and environment overrides
+// ABOUTME: Covers TOML parsing, SS_* overrides, and conversion into player and client configs
+
+use sendspin::audio::ResampleQuality;
+use sendspin::config::Config;
+use sendspin::scheduler::LatencyProfile;
+use sendspin::Error;
+use std::time::Duration;
+
+#[test]
+fn test_toml_settings_reach_player_and_client_configs() {
+    let config = Config::from_toml(
+        r#"
+        [client]
+        server = "ws://10.0.0.2:8927/sendspin"
+        clock_sync_interval_ms = 2000
+        keepalive_interval_ms = 4000
+
+        [player]
+        profile = "tv"
+        start_buffer_ms = 80
+        latency_report_interval_ms = 0
+        output_rate = 48000
+        resample_quality = "linear"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.client.server.as_deref(),
+        Some("ws://10.0.0.2:8927/sendspin")
+    );
+
+    let player = config.player_config().unwrap();
+    let tv = LatencyProfile::from_name("tv").unwrap();
+    assert_eq!(player.profile.start_buffer, Duration::from_millis(80));
+    assert_eq!(player.profile.min_lead, tv.min_lead);
+    assert_eq!(player.latency_report_interval, None);
+    assert_eq!(player.output_rate, Some(48_000));
+    assert_eq!(player.resample_quality, ResampleQuality::Linear);
+
+    let client = config.client_config().unwrap();
+    assert_eq!(client.clock_sync_interval, Some(Duration::from_secs(2)));
+    assert!(client.keepalive.is_some());
+    assert!(client.payload_key.is_none());
+}
+
+#[test]
+fn test_environment_overrides_file_settings() {
+    let config = Config::from_toml("[player]\nmin_lead_ms = 100\nstart_buffer_ms = 500\n")
+        .unwrap()
+        .with_vars(|key| match key {
+            "SS_PLAY_MIN_LEAD_MS" => Some("25".to_string()),
+            "SS_NAME" => Some("Kitchen".to_string()),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(config.player.min_lead_ms, Some(25));
+    assert_eq!(config.player.start_buffer_ms, Some(500));
+    assert_eq!(config.client.name.as_deref(), Some("Kitchen"));
+
+    let profile = config.profile().unwrap();
+    assert_eq!(profile.min_lead, Duration::from_millis(25));
+    assert_eq!(profile.start_buffer, Duration::from_millis(500));
+}
+
+#[test]
+fn test_invalid_settings_are_config_errors() {
+    assert!(matches!(
+        Config::from_toml("[player]\nbuffer = 3\n"),
+        Err(Error::Config(_))
+    ));
+    assert!(matches!(
+        Config::default()
+            .with_vars(|key| (key == "SS_PLAY_START_BUFFER_MS").then(|| "soon".to_string())),
+        Err(Error::Config(_))
+    ));
+    let unknown = Config::from_toml("[player]\nprofile = \"studio\"\n").unwrap();
+    assert!(matches!(unknown.player_config(), Err(Error::Config(_))));
+    let unknown = Config::from_toml("[player]\nresample_quality = \"perfect\"\n").unwrap();
+    assert!(matches!(unknown.player_config(), Err(Error::Config(_))));
+    assert!(matches!(
+        Config::load("/nonexistent/sendspin.toml"),
+        Err(Error::Io(_))
+    ));
+
+    // Defaults survive a round trip through TOML
+    let config = Config::default().with_vars(|_| None).unwrap();
+    assert_eq!(Config::from_toml(&config.to_toml()).unwrap(), config);
+}
@@ -0,0 +1,191 @@
+// ABOUTME: Tests for the high-level Controller against a mock server
+// ABOUTME: Covers state caching and checking commands against supported_commands
+
+mod common;
+
+use common::{mock_server, record};
+use sendspin::controller::{Controller, MediaDisplay, MediaKey, MediaKeys, MediaSession};
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{
+    ClientHello, ControllerCommandKind, ControllerState, Message, ServerState,
+};
+use sendspin::protocol::Role;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+fn hello() -> ClientHello {
+    ClientHello::new(
+        "controller-test",
+        "Controller Test",
+        vec![Role::Controller(1)],
+    )
+}
+
+fn controller_state(volume: u8, commands: &[&str]) -> Message {
+    Message::ServerState(ServerState {
+        metadata: None,
+        controller: Some(ControllerState {
+            supported_commands: commands.iter().map(|c| c.to_string()).collect(),
+            volume,
+            muted: false,
+        }),
+    })
+}
+
+/// Serve one connection and forward every text frame after client/hello
+async fn recording_server() -> (String, mpsc::UnboundedReceiver<Message>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let url = mock_server(vec![Role::Controller(1)], |ws| record(ws, tx)).await;
+    (url, rx)
+}
+
+#[tokio::test]
+async fn test_controller_sends_only_supported_commands() {
+    let (url, mut sent) = recording_server().await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let controller = Controller::new(client.sender());
+
+    assert!(controller.play().await.is_err(), "No state yet");
+    controller.apply(&controller_state(30, &["play", "volume"]));
+    assert_eq!(controller.volume(), Some(30));
+    assert!(controller.supports(&ControllerCommandKind::Play));
+    assert!(!controller.supports(&ControllerCommandKind::Next));
+
+    assert!(matches!(
+        controller.next().await,
+        Err(sendspin::Error::Protocol(_))
+    ));
+    assert!(controller.set_volume(101).await.is_err());
+    controller.play().await.unwrap();
+    controller.set_volume(40).await.unwrap();
+
+    let mut commands = Vec::new();
+    for _ in 0..2 {
+        let msg = timeout(Duration::from_secs(2), sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let Message::ClientCommand(command) = msg else {
+            panic!("Expected client/command, got {:?}", msg);
+        };
+        let command = command.controller.unwrap();
+        commands.push((command.command, command.volume));
+    }
+    assert_eq!(
+        commands,
+        vec![
+            (ControllerCommandKind::Play, None),
+            (ControllerCommandKind::Volume, Some(40))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_controller_keeps_parts_missing_from_update() {
+    let (url, _sent) = recording_server().await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let controller = Controller::new(client.sender());
+    let mut changes = controller.watch();
+
+    controller.apply(&controller_state(55, &["pause"]));
+    controller.apply(&Message::ServerState(ServerState {
+        metadata: None,
+        controller: None,
+    }));
+    assert!(changes.has_changed().unwrap());
+    let state = changes.borrow_and_update().clone();
+    assert_eq!(state.controller.unwrap().volume, 55);
+    assert!(controller.supports(&ControllerCommandKind::Pause));
+}
+
+#[tokio::test]
+async fn test_media_keys_translate_to_commands() {
+    let (url, _sent) = recording_server().await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let controller = Controller::new(client.sender());
+    let keys = MediaKeys::new(controller.clone()).with_volume_step(10);
+
+    assert!(keys.command(MediaKey::VolumeUp).is_none(), "Volume unknown");
+    controller.apply(&controller_state(95, &["play", "pause", "volume"]));
+
+    let command = |key| keys.command(key).unwrap();
+    assert_eq!(command(MediaKey::VolumeUp).volume, Some(100));
+    assert_eq!(command(MediaKey::VolumeDown).volume, Some(85));
+    assert_eq!(command(MediaKey::Mute).mute, Some(true));
+    assert_eq!(
+        command(MediaKey::SeekBackward).offset,
+        Some(-10_000_000),
+        "Default seek step is 10s"
+    );
+    // Nothing reported as playing yet
+    assert_eq!(
+        command(MediaKey::PlayPause).command,
+        ControllerCommandKind::Play
+    );
+    assert!(matches!(
+        keys.handle(MediaKey::Next).await,
+        Err(sendspin::Error::Protocol(_))
+    ));
+}
+
+async fn next_display(shown: &mut mpsc::UnboundedReceiver<MediaDisplay>) -> MediaDisplay {
+    timeout(Duration::from_secs(2), shown.recv())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// Media session that forwards what it is asked to show
+struct Session(mpsc::UnboundedSender<MediaDisplay>);
+
+impl MediaSession for Session {
+    fn set_now_playing(&mut self, display: &MediaDisplay) -> Result<(), sendspin::Error> {
+        let _ = self.0.send(display.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_media_keys_update_session_and_send_commands() {
+    let (url, mut sent) = recording_server().await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let controller = Controller::new(client.sender());
+    controller.apply(&controller_state(50, &["play", "pause"]));
+
+    let (shown_tx, mut shown) = mpsc::unbounded_channel();
+    let (keys_tx, keys) = mpsc::unbounded_channel();
+    let running = tokio::spawn(MediaKeys::new(controller.clone()).run(Session(shown_tx), keys));
+    let mut shown_display = next_display(&mut shown).await;
+    assert_eq!(shown_display, MediaDisplay::default());
+
+    let playing: Message = serde_json::from_str(
+        r#"{"type":"server/state","payload":{"metadata":{"timestamp":1,"title":"Song",
+        "progress":{"position":2000000,"duration":60000000,"playback_speed":1.0}}}}"#,
+    )
+    .unwrap();
+    controller.apply(&playing);
+    shown_display = next_display(&mut shown).await;
+    assert_eq!(shown_display.title.as_deref(), Some("Song"));
+    assert_eq!(shown_display.duration, Some(Duration::from_secs(60)));
+    assert!(shown_display.playing);
+
+    keys_tx.send(MediaKey::PlayPause).unwrap();
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    let Message::ClientCommand(command) = msg else {
+        panic!("Expected client/command, got {:?}", msg);
+    };
+    assert_eq!(
+        command.controller.unwrap().command,
+        ControllerCommandKind::Pause
+    );
+
+    drop(keys_tx);
+    timeout(Duration::from_secs(2), running)
+        .await
+        .unwrap()
+        .unwrap();
+}
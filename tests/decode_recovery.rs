@@ -0,0 +1,70 @@
+// ABOUTME: Tests for decode error events and recovery policy
+// ABOUTME: Validates consecutive-error thresholds and PCM fallback requests
+
+use sendspin::audio::decode::{DecodeErrorPolicy, DecodeErrorTracker, RecoveryAction};
+use sendspin::audio::Codec;
+use sendspin::error::Error;
+use sendspin::protocol::messages::Message;
+
+fn failure() -> Error {
+    Error::Protocol("bad frame".to_string())
+}
+
+#[test]
+fn test_failures_are_skipped_below_threshold() {
+    let mut tracker = DecodeErrorTracker::default();
+
+    let event = tracker.record_failure(Codec::Opus, &failure(), 1000);
+    assert_eq!(event.codec, Codec::Opus);
+    assert_eq!(event.timestamp, 1000);
+    assert_eq!(event.action, RecoveryAction::Skipped);
+    assert_eq!(event.consecutive, 1);
+    assert!(event.cause.contains("bad frame"));
+    assert!(tracker.fallback_request(&event).is_none());
+}
+
+#[test]
+fn test_conceal_policy() {
+    let mut tracker = DecodeErrorTracker::new(DecodeErrorPolicy {
+        conceal: true,
+        ..DecodeErrorPolicy::default()
+    });
+
+    let event = tracker.record_failure(Codec::Flac, &failure(), 0);
+    assert_eq!(event.action, RecoveryAction::Concealed);
+}
+
+#[test]
+fn test_threshold_aborts_and_requests_pcm() {
+    let mut tracker = DecodeErrorTracker::new(DecodeErrorPolicy {
+        max_consecutive: 3,
+        ..DecodeErrorPolicy::default()
+    });
+
+    tracker.record_failure(Codec::Opus, &failure(), 0);
+    tracker.record_failure(Codec::Opus, &failure(), 1);
+    let event = tracker.record_failure(Codec::Opus, &failure(), 2);
+    assert_eq!(event.action, RecoveryAction::StreamAborted);
+
+    match tracker.fallback_request(&event) {
+        Some(Message::StreamRequestFormat(request)) => {
+            let player = request.player.expect("Expected player request");
+            assert_eq!(player.codec, Some("pcm".to_string()));
+        }
+        other => panic!("Expected StreamRequestFormat, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_success_resets_consecutive_count() {
+    let mut tracker = DecodeErrorTracker::new(DecodeErrorPolicy {
+        max_consecutive: 2,
+        ..DecodeErrorPolicy::default()
+    });
+
+    tracker.record_failure(Codec::Opus, &failure(), 0);
+    tracker.record_success();
+    let event = tracker.record_failure(Codec::Opus, &failure(), 1);
+    assert_eq!(event.consecutive, 1);
+    assert_eq!(event.action, RecoveryAction::Skipped);
+}
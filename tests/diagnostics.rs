@@ -0,0 +1,37 @@
+// ABOUTME: Tests for the startup diagnostics report
+// ABOUTME: Validates build info contents and the rendered report
+
+use sendspin::diagnostics;
+
+#[test]
+fn test_build_info_describes_this_build() {
+    let info = diagnostics::build_info();
+    assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(info.protocol_version, 1);
+    assert!(info.codecs.contains(&"pcm"));
+    assert!(info.outputs.contains(&"cpal"));
+    assert_eq!(info.target_os, std::env::consts::OS);
+    assert_eq!(info.target_arch, std::env::consts::ARCH);
+}
+
+#[test]
+fn test_build_info_display() {
+    let text = diagnostics::build_info().to_string();
+    assert!(text.starts_with(&format!("sendspin: {}", env!("CARGO_PKG_VERSION"))));
+    assert!(text.contains("codecs: pcm"));
+    assert!(text.contains("features: "));
+}
+
+#[tokio::test]
+async fn test_report_includes_runtime_and_backends() {
+    let report = diagnostics::report();
+    assert_eq!(report.build, diagnostics::build_info());
+    assert!(report.tokio_runtime.is_some());
+
+    let text = report.to_string();
+    assert!(text.contains("tokio: "));
+    assert!(text.contains("audio backends"));
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["build"]["codecs"][0], "pcm");
+}
@@ -0,0 +1,190 @@
+// ABOUTME: Tests for optional binary payload encryption
+// ABOUTME: Covers key parsing, seal/open round trips, tampering, negotiation, and end-to-end streaming
+
+use sendspin::protocol::client::{AudioChunk, ClientConfig, ProtocolClient};
+use sendspin::protocol::encryption::PAYLOAD_CIPHER;
+use sendspin::protocol::messages::{
+    AudioFormatSpec, ClientHello, Message, PayloadEncryptionParams, PlayerV1Support,
+};
+use sendspin::protocol::{PayloadCipher, PayloadKey, Role};
+use sendspin::server::{parse_pcm_format, ReaderSource, Server, ServerConfig};
+use std::io::Cursor;
+use std::time::Duration;
+use tokio::time::timeout;
+
+const KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+fn key() -> PayloadKey {
+    PayloadKey::from_hex(KEY_HEX).unwrap()
+}
+
+fn hello() -> ClientHello {
+    ClientHello::new("secure-player", "Secure Player", vec![Role::Player(1)]).with_player_support(
+        PlayerV1Support {
+            supported_formats: vec![AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 16,
+                endian: None,
+            }],
+            buffer_capacity: 100,
+            supported_commands: vec![],
+        },
+    )
+}
+
+#[test]
+fn test_payload_key_parsing() {
+    assert_eq!(key(), PayloadKey::new(std::array::from_fn(|i| i as u8)));
+    assert!(PayloadKey::from_hex("0011").is_err());
+    assert!(PayloadKey::from_hex(&"zz".repeat(32)).is_err());
+    assert_eq!(format!("{:?}", key()), "PayloadKey(<redacted>)");
+}
+
+#[test]
+fn test_seal_open_round_trip() {
+    let (mut sender, params) = PayloadCipher::negotiate(&key());
+    assert_eq!(params.cipher, PAYLOAD_CIPHER);
+    let mut receiver = PayloadCipher::accept(&key(), &params).unwrap();
+
+    let frame = AudioChunk::encode(123_456, &[1, 2, 3, 4]);
+    let sealed = sender.seal(&frame).unwrap();
+    // Header stays in the clear, payload does not
+    assert_eq!(sealed[..9], frame[..9]);
+    assert_ne!(sealed[9..13], frame[9..]);
+    assert_eq!(receiver.open(&sealed).unwrap(), frame);
+
+    // Identical frames get different ciphertext
+    let again = sender.seal(&frame).unwrap();
+    assert_ne!(again, sealed);
+    assert_eq!(receiver.open(&again).unwrap(), frame);
+}
+
+#[test]
+fn test_tampering_is_rejected() {
+    let (mut sender, params) = PayloadCipher::negotiate(&key());
+    let frame = AudioChunk::encode(1_000, &[9; 16]);
+    let sealed = sender.seal(&frame).unwrap();
+
+    // Timestamp is authenticated
+    let mut header = sealed.clone();
+    header[8] ^= 1;
+    assert!(PayloadCipher::accept(&key(), &params)
+        .unwrap()
+        .open(&header)
+        .is_err());
+
+    let mut payload = sealed.clone();
+    payload[12] ^= 1;
+    assert!(PayloadCipher::accept(&key(), &params)
+        .unwrap()
+        .open(&payload)
+        .is_err());
+
+    let wrong_key = PayloadKey::new([7; 32]);
+    assert!(PayloadCipher::accept(&wrong_key, &params)
+        .unwrap()
+        .open(&sealed)
+        .is_err());
+
+    // Skipping a frame breaks the sequence
+    let second = sender.seal(&frame).unwrap();
+    assert!(PayloadCipher::accept(&key(), &params)
+        .unwrap()
+        .open(&second)
+        .is_err());
+
+    assert!(PayloadCipher::accept(&key(), &params)
+        .unwrap()
+        .open(&[4, 0, 0])
+        .is_err());
+}
+
+#[test]
+fn test_accept_rejects_bad_params() {
+    let unknown = PayloadEncryptionParams {
+        cipher: "rot13".to_string(),
+        salt: "00".repeat(16),
+    };
+    assert!(PayloadCipher::accept(&key(), &unknown).is_err());
+
+    let short_salt = PayloadEncryptionParams {
+        cipher: PAYLOAD_CIPHER.to_string(),
+        salt: "00".repeat(8),
+    };
+    assert!(PayloadCipher::accept(&key(), &short_salt).is_err());
+}
+
+#[test]
+fn test_hello_wire_fields() {
+    let plain = serde_json::to_value(Message::ClientHello(hello())).unwrap();
+    assert!(plain["payload"].get("x_payload_encryption").is_none());
+
+    let mut offer = hello();
+    offer.payload_encryption = vec![PAYLOAD_CIPHER.to_string()];
+    let json = serde_json::to_value(Message::ClientHello(offer)).unwrap();
+    assert_eq!(
+        json["payload"]["x_payload_encryption"],
+        serde_json::json!([PAYLOAD_CIPHER])
+    );
+
+    let server_hello = r#"{"type":"server/hello","payload":{
+        "server_id":"s","name":"S","version":1,"active_roles":["player@v1"],
+        "connection_reason":"playback",
+        "x_payload_encryption":{"cipher":"xchacha20poly1305","salt":"00112233445566778899aabbccddeeff"}}}"#;
+    match serde_json::from_str::<Message>(server_hello).unwrap() {
+        Message::ServerHello(hello) => {
+            let params = hello.payload_encryption.unwrap();
+            assert_eq!(params.cipher, PAYLOAD_CIPHER);
+            assert!(PayloadCipher::accept(&key(), &params).is_ok());
+        }
+        other => panic!("Expected server/hello, got {}", other.message_type()),
+    }
+}
+
+async fn stream_one_chunk(server_key: Option<PayloadKey>, client_key: Option<PayloadKey>) {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        payload_key: server_key.clone(),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+
+    let client_config = ClientConfig {
+        payload_key: client_key.clone(),
+        ..ClientConfig::default()
+    };
+    let mut client = ProtocolClient::connect_with_config(&url, hello(), client_config)
+        .await
+        .unwrap();
+    let negotiated = server_key.is_some() && client_key.is_some();
+    assert_eq!(
+        client.server_hello().payload_encryption.is_some(),
+        negotiated
+    );
+
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let pcm: Vec<u8> = (0..4800 * 4).map(|i| i as u8).collect();
+    let source = ReaderSource::new(Cursor::new(pcm), format).unwrap();
+    tokio::spawn(async move { server.serve(source).await });
+
+    let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    let expected: Vec<u8> = (0..960 * 4).map(|i| i as u8).collect();
+    assert_eq!(&chunk.data[..], &expected[..]);
+}
+
+#[tokio::test]
+async fn test_encrypted_stream_is_transparent() {
+    stream_one_chunk(Some(key()), Some(key())).await;
+}
+
+#[tokio::test]
+async fn test_encryption_falls_back_without_both_keys() {
+    stream_one_chunk(Some(key()), None).await;
+    stream_one_chunk(None, Some(key())).await;
+}
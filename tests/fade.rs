@@ -0,0 +1,72 @@
+// ABOUTME: Tests for click-free fades
+// ABOUTME: Covers ramp length, curves, reversing mid-fade, and staying silent after a fade out
+
+use sendspin::audio::{Fade, FadeCurve, Fader, Sample};
+use std::sync::Arc;
+use std::time::Duration;
+
+const FULL: i32 = 1 << 20;
+
+fn ramp(curve: FadeCurve) -> Fade {
+    // 10 frames at 1kHz
+    Fade {
+        duration: Duration::from_millis(10),
+        curve,
+    }
+}
+
+#[test]
+fn test_fade_in_ramps_to_full_over_its_duration() {
+    let mut fader = Fader::silent(ramp(FadeCurve::Linear));
+    let mut samples = vec![Sample(FULL); 2 * 20];
+    fader.fade_in();
+    fader.apply(&mut samples, 2, 1_000);
+
+    // Both channels of a frame get the same gain
+    let left: Vec<i32> = samples.iter().step_by(2).map(|s| s.0).collect();
+    let right: Vec<i32> = samples.iter().skip(1).step_by(2).map(|s| s.0).collect();
+    assert_eq!(left, right);
+    assert!(left.windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(left[0], (FULL as f32 / 10.0).round() as i32);
+    assert_eq!(left[9], FULL);
+    assert!(fader.is_unity());
+
+    // At unity buffers pass through without a copy
+    let shared: Arc<[Sample]> = vec![Sample(5); 4].into();
+    assert!(Arc::ptr_eq(&fader.process(&shared, 2, 1_000), &shared));
+}
+
+#[test]
+fn test_fade_out_stays_silent_until_faded_in() {
+    let mut fader = Fader::new(ramp(FadeCurve::Exponential));
+    fader.fade_out();
+    let faded = fader.process(&vec![Sample(FULL); 10].into(), 1, 1_000);
+    // Exponential fades drop faster than linear ones at first
+    assert!(faded[4].0 < FULL / 2);
+    assert_eq!(faded[9].0, 0);
+    assert!(fader.is_silent());
+
+    let later = fader.process(&vec![Sample(FULL); 10].into(), 1, 1_000);
+    assert!(later.iter().all(|s| s.0 == 0));
+}
+
+#[test]
+fn test_reversed_fade_continues_from_current_gain() {
+    let mut fader = Fader::new(ramp(FadeCurve::Linear));
+    fader.fade_out();
+    fader.apply(&mut [Sample(FULL); 4], 1, 1_000);
+    let gain = fader.gain();
+    assert!((gain - 0.6).abs() < 1e-4);
+
+    fader.fade_in();
+    let mut samples = [Sample(FULL); 1];
+    fader.apply(&mut samples, 1, 1_000);
+    assert!(samples[0].0 > (gain * FULL as f32) as i32);
+
+    // Without a duration fades switch at once
+    let mut instant = Fader::new(Fade::NONE);
+    instant.fade_out();
+    let mut samples = [Sample(FULL); 2];
+    instant.apply(&mut samples, 1, 48_000);
+    assert_eq!(samples, [Sample(0); 2]);
+}
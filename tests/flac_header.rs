@@ -0,0 +1,51 @@
+// ABOUTME: Tests for the FLAC STREAMINFO codec header
+// ABOUTME: Covers the bare block, the block with its header, and the fLaC stream start
+
+use sendspin::audio::decode::FlacStreamInfo;
+
+/// STREAMINFO for 44.1kHz stereo 16-bit, 4096-sample blocks, 1000 samples
+fn stream_info() -> Vec<u8> {
+    let mut block = Vec::new();
+    block.extend_from_slice(&4096u16.to_be_bytes());
+    block.extend_from_slice(&4096u16.to_be_bytes());
+    block.extend_from_slice(&[0, 0, 14, 0, 0x30, 0]);
+    let packed = 44_100u64 << 44 | 1 << 41 | 15 << 36 | 1000;
+    block.extend_from_slice(&packed.to_be_bytes());
+    block.extend_from_slice(&[0xAB; 16]);
+    block
+}
+
+#[test]
+fn test_parse_stream_info() {
+    let info = FlacStreamInfo::parse(&stream_info()).unwrap();
+    assert_eq!((info.min_block_size, info.max_block_size), (4096, 4096));
+    assert_eq!((info.min_frame_size, info.max_frame_size), (14, 0x3000));
+    assert_eq!(info.sample_rate, 44_100);
+    assert_eq!(info.channels, 2);
+    assert_eq!(info.bits_per_sample, 16);
+    assert_eq!(info.total_samples, 1000);
+    assert_eq!(info.md5, [0xAB; 16]);
+
+    // Last-block flag set, as when STREAMINFO is the only metadata block
+    let mut with_header = vec![0x80, 0, 0, 34];
+    with_header.extend_from_slice(&stream_info());
+    assert_eq!(FlacStreamInfo::parse(&with_header).unwrap(), info);
+    let mut stream_start = b"fLaC".to_vec();
+    stream_start.extend_from_slice(&with_header);
+    assert_eq!(FlacStreamInfo::parse(&stream_start).unwrap(), info);
+}
+
+#[test]
+fn test_reject_bad_stream_info() {
+    assert!(FlacStreamInfo::parse(&[]).is_err());
+    assert!(FlacStreamInfo::parse(&stream_info()[..30]).is_err());
+    // Another block type in front
+    let mut padding = vec![1, 0, 0, 34];
+    padding.extend_from_slice(&stream_info());
+    assert!(FlacStreamInfo::parse(&padding).is_err());
+    // Zero sample rate
+    let mut no_rate = stream_info();
+    no_rate[10..13].fill(0);
+    no_rate[13] &= 0x0F;
+    assert!(FlacStreamInfo::parse(&no_rate).is_err());
+}
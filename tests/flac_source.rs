@@ -0,0 +1,142 @@
+// ABOUTME: Tests for the FLAC server source
+// ABOUTME: Runs only with the "symphonia" feature; streams are built by a minimal encoder
+#![cfg(feature = "symphonia")]
+
+use sendspin::audio::Codec;
+use sendspin::server::{AudioSource, FlacSource, SourceDecoders};
+use std::io::Cursor;
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            *self.bytes.last_mut().unwrap() |= ((value >> i & 1) as u8) << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 != 0 {
+                crc << 1 ^ 0x07
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0, |crc, &byte| {
+        (0..8).fold(crc ^ (byte as u16) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x8005
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+/// A 44.1kHz 16-bit stereo FLAC stream of verbatim frames of 64 samples
+fn flac_stream(left: &[i16], right: &[i16]) -> Vec<u8> {
+    let mut out = b"fLaC".to_vec();
+    // Last metadata block, STREAMINFO, 34 bytes
+    out.extend_from_slice(&[0x80, 0, 0, 34]);
+    let mut info = BitWriter::default();
+    info.write(64, 16);
+    info.write(64, 16);
+    info.write(0, 24);
+    info.write(0, 24);
+    info.write(44_100, 20);
+    info.write(1, 3);
+    info.write(15, 5);
+    info.write(left.len() as u64, 36);
+    info.write(0, 64);
+    info.write(0, 64);
+    out.extend(info.bytes);
+
+    for (number, (left, right)) in left.chunks(64).zip(right.chunks(64)).enumerate() {
+        let mut frame = BitWriter::default();
+        frame.write(0xFFF8, 16);
+        // 8-bit block size at the end of the header, rate from STREAMINFO
+        frame.write(0b0110, 4);
+        frame.write(0, 4);
+        // Independent stereo, 16-bit
+        frame.write(0b0001, 4);
+        frame.write(0b100, 3);
+        frame.write(0, 1);
+        frame.write(number as u64, 8);
+        frame.write(left.len() as u64 - 1, 8);
+        let crc = crc8(&frame.bytes);
+        frame.write(crc as u64, 8);
+        for channel in [left, right] {
+            // Verbatim subframe
+            frame.write(0b0000_0010, 8);
+            for &s in channel {
+                frame.write(s as u16 as u64, 16);
+            }
+        }
+        let crc = crc16(&frame.bytes);
+        frame.write(crc as u64, 16);
+        out.extend(frame.bytes);
+    }
+    out
+}
+
+fn ramp(len: usize, step: i16) -> Vec<i16> {
+    (0..len as i16).map(|i| i * step).collect()
+}
+
+#[test]
+fn test_flac_source_decodes_frames_into_chunks() {
+    let (left, right) = (ramp(128, 100), ramp(128, -50));
+    let mut source = FlacSource::new(Cursor::new(flac_stream(&left, &right))).unwrap();
+    let format = source.format().clone();
+    assert_eq!(format.codec, Codec::Pcm);
+    assert_eq!(
+        (format.sample_rate, format.channels, format.bit_depth),
+        (44_100, 2, 16)
+    );
+
+    let mut samples = Vec::new();
+    let first = source.next_chunk(100).unwrap().unwrap();
+    assert_eq!(first.len(), 100 * 4);
+    samples.extend(first);
+    let rest = source.next_chunk(100).unwrap().unwrap();
+    assert_eq!(rest.len(), 28 * 4);
+    samples.extend(rest);
+    assert!(source.next_chunk(100).unwrap().is_none());
+
+    let expected: Vec<u8> = left
+        .iter()
+        .zip(&right)
+        .flat_map(|(l, r)| [*l, *r])
+        .flat_map(i16::to_le_bytes)
+        .collect();
+    assert_eq!(samples, expected);
+}
+
+#[test]
+fn test_flac_source_from_http_content_type() {
+    let stream = flac_stream(&ramp(64, 1), &ramp(64, 1));
+    let mut source = SourceDecoders::default()
+        .open("audio/flac", Box::new(Cursor::new(stream)))
+        .unwrap();
+    assert_eq!(source.next_chunk(64).unwrap().unwrap().len(), 64 * 4);
+}
+
+#[test]
+fn test_flac_source_rejects_other_streams() {
+    assert!(FlacSource::new(Cursor::new(b"RIFF....WAVE".to_vec())).is_err());
+}
@@ -0,0 +1,66 @@
+// ABOUTME: Tests for the volume gain stage
+// ABOUTME: Covers the logarithmic and linear tapers, dB gain, clamping, balance, and trim
+
+use sendspin::audio::gain::{db_to_gain, MAX_GAIN_DB};
+use sendspin::audio::{Balance, Gain, Sample, VolumeTaper};
+use std::sync::Arc;
+
+#[test]
+fn test_logarithmic_taper_spaces_volume_evenly_in_db() {
+    let taper = VolumeTaper::Logarithmic { range_db: 60.0 };
+    assert_eq!(taper.gain_db(100), 0.0);
+    assert!((taper.gain_db(1) + 60.0).abs() < 1e-4);
+    assert_eq!(taper.gain_db(0), f32::NEG_INFINITY);
+    let step = taper.gain_db(100) - taper.gain_db(99);
+    assert!((taper.gain_db(51) - taper.gain_db(50) - step).abs() < 1e-4);
+    // Half volume is far quieter than half amplitude
+    assert!(db_to_gain(taper.gain_db(50)) < 0.05);
+
+    let linear = VolumeTaper::Linear;
+    assert!((db_to_gain(linear.gain_db(50)) - 0.5).abs() < 1e-6);
+    assert_eq!(linear.gain_db(200), 0.0);
+}
+
+#[test]
+fn test_gain_scales_and_clamps_samples() {
+    let mut gain = Gain::default();
+    let samples: Arc<[Sample]> = vec![Sample(1_000_000), Sample(-1_000_000)].into();
+    assert!(Arc::ptr_eq(&gain.process(&samples), &samples));
+
+    gain.set_gain_db(-6.0206);
+    let half = gain.process(&samples);
+    assert_eq!(half[0], Sample(500_000));
+    assert_eq!(half[1], Sample(-500_000));
+
+    gain.set_gain_db(40.0);
+    assert_eq!(gain.gain_db(), MAX_GAIN_DB);
+    let loud = gain.process(&vec![Sample(Sample::MAX.0 / 2), Sample::MIN].into());
+    assert_eq!(&*loud, &[Sample::MAX, Sample::MIN]);
+
+    gain.set_volume(0);
+    assert!(gain.process(&samples).iter().all(|s| *s == Sample::ZERO));
+}
+
+#[test]
+fn test_balance_and_trim_adjust_single_channels() {
+    let frame: Arc<[Sample]> = vec![Sample(1_000_000); 4].into();
+    let mut balance = Balance::new();
+    assert!(Arc::ptr_eq(&balance.process(&frame, 2), &frame));
+
+    // Halfway right halves the left channel only
+    balance.set_balance(0.5);
+    let out = balance.process(&frame, 2);
+    assert_eq!(&*out, &[Sample(500_000), Sample(1_000_000)].repeat(2));
+    balance.set_balance(-3.0);
+    assert_eq!(balance.balance(), -1.0);
+    assert_eq!(balance.factor(1, 2), 0.0);
+    // Mono streams have no sides
+    assert_eq!(balance.factor(0, 1), 1.0);
+
+    balance.set_balance(0.0);
+    balance.set_trim_db(1, -6.0206);
+    let out = balance.process(&frame, 2);
+    assert_eq!(&*out, &[Sample(1_000_000), Sample(500_000)].repeat(2));
+    balance.set_trim_db(100, -6.0);
+    assert_eq!(balance.trim_db(100), 0.0);
+}
@@ -0,0 +1,87 @@
+// ABOUTME: Tests for the GroupManager
+// ABOUTME: Covers merging partial group/update messages, group changes, and reconnects
+
+use sendspin::events::ConnectionStatus;
+use sendspin::group::{GroupManager, GroupState};
+use sendspin::protocol::messages::{GroupMember, GroupUpdate, Message, PlaybackState};
+
+fn update(
+    group_id: Option<&str>,
+    group_name: Option<&str>,
+    playback_state: Option<PlaybackState>,
+) -> Message {
+    Message::GroupUpdate(GroupUpdate {
+        playback_state,
+        group_id: group_id.map(str::to_string),
+        group_name: group_name.map(str::to_string),
+        members: None,
+    })
+}
+
+#[test]
+fn test_partial_updates_are_merged() {
+    let groups = GroupManager::new();
+    let mut changes = groups.watch();
+
+    groups.apply(&update(
+        Some("kitchen"),
+        Some("Kitchen"),
+        Some(PlaybackState::Paused),
+    ));
+    assert!(changes.has_changed().unwrap());
+    changes.mark_unchanged();
+
+    groups.apply(&update(None, None, Some(PlaybackState::Playing)));
+    let state = groups.state();
+    assert_eq!(state.group_id.as_deref(), Some("kitchen"));
+    assert_eq!(state.group_name.as_deref(), Some("Kitchen"));
+    assert!(state.is_playing());
+    changes.mark_unchanged();
+
+    // Repeating the same state wakes no one
+    groups.apply(&update(Some("kitchen"), None, Some(PlaybackState::Playing)));
+    assert!(!changes.has_changed().unwrap());
+}
+
+#[test]
+fn test_new_group_replaces_old_state() {
+    let mut state = GroupState::default();
+    state.apply(&GroupUpdate {
+        playback_state: Some(PlaybackState::Playing),
+        group_id: Some("kitchen".to_string()),
+        group_name: Some("Kitchen".to_string()),
+        members: Some(vec![GroupMember {
+            client_id: "a".to_string(),
+            name: "A".to_string(),
+            volume: None,
+            muted: None,
+        }]),
+    });
+
+    assert!(state.apply(&GroupUpdate {
+        playback_state: None,
+        group_id: Some("office".to_string()),
+        group_name: None,
+        members: None,
+    }));
+    assert_eq!(
+        state,
+        GroupState {
+            group_id: Some("office".to_string()),
+            ..GroupState::default()
+        }
+    );
+}
+
+#[test]
+fn test_disconnect_forgets_group() {
+    let groups = GroupManager::new();
+    groups.apply(&update(Some("kitchen"), None, Some(PlaybackState::Playing)));
+
+    groups.connection_changed(ConnectionStatus::Connected);
+    assert_eq!(groups.group_id().as_deref(), Some("kitchen"));
+
+    groups.connection_changed(ConnectionStatus::Reconnecting);
+    assert_eq!(groups.state(), GroupState::default());
+    assert!(groups.playback_state().is_none());
+}
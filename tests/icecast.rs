@@ -0,0 +1,162 @@
+// ABOUTME: Tests for the Icecast/HTTP source adapter
+// ABOUTME: Validates ICY metadata parsing, metadata stripping, WAV/L16 decoding, and HTTP fetching
+
+use sendspin::audio::Codec;
+use sendspin::protocol::messages::ContentType;
+use sendspin::server::icecast::IcyReader;
+use sendspin::server::{AudioSource, IcecastSource, IcyMetadata, ReaderSource, SourceDecoders};
+use std::io::{Cursor, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+/// Build an ICY metadata block (length byte + padded text)
+fn icy_block(text: &str) -> Vec<u8> {
+    let blocks = text.len().div_ceil(16);
+    let mut out = vec![blocks as u8];
+    out.extend_from_slice(text.as_bytes());
+    out.resize(1 + blocks * 16, 0);
+    out
+}
+
+fn wav_header(sample_rate: u32, channels: u16, bits: u16) -> Vec<u8> {
+    wav_header_tagged(1, sample_rate, channels, bits)
+}
+
+fn wav_header_tagged(tag: u16, sample_rate: u32, channels: u16, bits: u16) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&u32::MAX.to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&tag.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    let block_align = channels * bits / 8;
+    out.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&u32::MAX.to_le_bytes());
+    out
+}
+
+#[test]
+fn test_parse_icy_metadata() {
+    let meta = IcyMetadata::parse("StreamTitle='Daft Punk - Get Lucky';StreamUrl='';\0\0");
+    assert_eq!(meta.stream_title.as_deref(), Some("Daft Punk - Get Lucky"));
+    assert_eq!(meta.stream_url, None);
+
+    let state = meta.to_metadata_state(5);
+    assert_eq!(state.artist.as_deref(), Some("Daft Punk"));
+    assert_eq!(state.title.as_deref(), Some("Get Lucky"));
+    assert_eq!(state.timestamp, 5);
+    assert_eq!(state.content_type, Some(ContentType::Radio));
+
+    // Quotes inside the title and no artist separator
+    let meta = IcyMetadata::parse("StreamTitle='Rock 'n' Roll';");
+    assert_eq!(meta.stream_title.as_deref(), Some("Rock 'n' Roll"));
+    assert_eq!(meta.to_metadata_state(0).artist, None);
+}
+
+#[test]
+fn test_icy_reader_strips_metadata() {
+    let mut stream = vec![1u8; 8];
+    stream.extend(icy_block("StreamTitle='A - B';"));
+    stream.extend(vec![2u8; 8]);
+    stream.extend([0u8]); // empty metadata block
+    stream.extend(vec![3u8; 4]);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    let mut reader = IcyReader::new(Cursor::new(stream), Some(8), move |m| {
+        sink.lock().unwrap().push(m)
+    });
+    let mut audio = Vec::new();
+    reader.read_to_end(&mut audio).unwrap();
+
+    let mut expected = vec![1u8; 8];
+    expected.extend(vec![2u8; 8]);
+    expected.extend(vec![3u8; 4]);
+    assert_eq!(audio, expected);
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].stream_title.as_deref(), Some("A - B"));
+}
+
+#[test]
+fn test_wav_source_reads_header() {
+    let mut wav = wav_header(44100, 2, 16);
+    wav.extend(vec![7u8; 40]);
+    let mut source = ReaderSource::from_wav(Cursor::new(wav)).unwrap();
+    assert_eq!(source.format().sample_rate, 44100);
+    assert_eq!(source.format().channels, 2);
+    assert_eq!(source.format().bit_depth, 16);
+    assert_eq!(source.next_chunk(100).unwrap().unwrap().len(), 40);
+
+    assert!(ReaderSource::from_wav(Cursor::new(b"not a wav file!!".to_vec())).is_err());
+
+    // IEEE float (format tag 3)
+    let source = ReaderSource::from_wav(Cursor::new(wav_header_tagged(3, 48000, 2, 32))).unwrap();
+    assert_eq!(source.format().codec, Codec::PcmFloat);
+    assert!(ReaderSource::from_wav(Cursor::new(wav_header_tagged(3, 48000, 2, 64))).is_err());
+}
+
+#[test]
+fn test_unknown_content_type_rejected() {
+    let decoders = SourceDecoders::default();
+    let err = decoders
+        .open("audio/mpeg", Box::new(Cursor::new(Vec::new())))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("audio/mpeg"));
+}
+
+#[test]
+fn test_icecast_source_over_http() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // Big-endian L16 mono, metadata every 4 bytes
+    std::thread::spawn(move || {
+        let (mut conn, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let n = conn.read(&mut request).unwrap();
+        let request = String::from_utf8_lossy(&request[..n]);
+        assert!(request.contains("Icy-MetaData: 1"));
+
+        let mut body = Vec::new();
+        body.extend_from_slice(b"ICY 200 OK\r\n");
+        body.extend_from_slice(b"Content-Type: audio/L16;rate=8000;channels=1\r\n");
+        body.extend_from_slice(b"icy-name: Test FM\r\n");
+        body.extend_from_slice(b"icy-metaint: 4\r\n\r\n");
+        body.extend_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+        body.extend(icy_block("StreamTitle='Artist - Song';"));
+        body.extend_from_slice(&[0x05, 0x06]);
+        conn.write_all(&body).unwrap();
+    });
+
+    let titles = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&titles);
+    let mut source = IcecastSource::connect(
+        &format!("http://{}/stream", addr),
+        &SourceDecoders::default(),
+        move |meta| sink.lock().unwrap().push(meta.stream_title),
+    )
+    .unwrap();
+
+    assert_eq!(source.station_name(), Some("Test FM"));
+    assert_eq!(source.format().sample_rate, 8000);
+    assert_eq!(source.format().channels, 1);
+
+    // Samples are converted to little-endian
+    let pcm = source.next_chunk(16).unwrap().unwrap();
+    assert_eq!(pcm, vec![0x02, 0x01, 0x04, 0x03, 0x06, 0x05]);
+    assert!(source.next_chunk(16).unwrap().is_none());
+
+    assert_eq!(
+        *titles.lock().unwrap(),
+        vec![Some("Artist - Song".to_string())]
+    );
+}
@@ -0,0 +1,83 @@
+// ABOUTME: Tests for audio chunk integrity verification
+// ABOUTME: Validates frame alignment diagnostics and timestamp continuity tracking
+
+use sendspin::audio::integrity::{FrameLayout, IntegrityChecker};
+use sendspin::audio::{AudioFormat, Codec};
+
+fn pcm_format(bit_depth: u8, channels: u8) -> AudioFormat {
+    AudioFormat::new(Codec::Pcm, 48000, channels, bit_depth)
+}
+
+#[test]
+fn test_frame_layout_sizes() {
+    let layout = FrameLayout::for_format(&pcm_format(24, 2)).unwrap();
+    assert_eq!(layout.bytes_per_sample(), 3);
+    assert_eq!(layout.frame_size(), 6);
+    assert_eq!(layout.verify(&[0u8; 12]), Ok(2));
+}
+
+#[test]
+fn test_frame_layout_rejects_compressed() {
+    let mut format = pcm_format(16, 2);
+    format.codec = Codec::Opus;
+    assert!(FrameLayout::for_format(&format).is_err());
+}
+
+#[test]
+fn test_misaligned_payload_diagnostics() {
+    let layout = FrameLayout::for_format(&pcm_format(24, 2)).unwrap();
+
+    let mismatch = layout.verify(&[0u8; 13]).unwrap_err();
+    assert_eq!(mismatch.whole_frames, 2);
+    assert_eq!(mismatch.remainder, 1);
+    let message = mismatch.to_string();
+    assert!(message.contains("13 bytes"));
+    assert!(message.contains("truncation"));
+
+    // Remainder of exactly one sample suggests a channel count mismatch
+    let mismatch = layout.verify(&[0u8; 9]).unwrap_err();
+    assert!(mismatch.to_string().contains("channel count"));
+}
+
+#[test]
+fn test_checker_tracks_continuity() {
+    let layout = FrameLayout::for_format(&pcm_format(16, 2)).unwrap();
+    let mut checker = IntegrityChecker::new(layout);
+
+    // 480 frames = 10ms at 48kHz
+    let chunk = vec![0u8; 480 * 4];
+    let first = checker.check(0, &chunk).unwrap();
+    assert_eq!(first.frames, 480);
+    assert_eq!(first.duration_micros, 10_000);
+    assert_eq!(first.discontinuity_micros, None);
+
+    let second = checker.check(10_000, &chunk).unwrap();
+    assert_eq!(second.discontinuity_micros, Some(0));
+
+    // 5ms gap
+    let third = checker.check(25_000, &chunk).unwrap();
+    assert_eq!(third.discontinuity_micros, Some(5_000));
+
+    assert!(checker.check(35_000, &chunk[..7]).is_err());
+
+    let stats = checker.stats();
+    assert_eq!(stats.verified, 3);
+    assert_eq!(stats.discontinuities, 1);
+    assert_eq!(stats.misaligned, 1);
+}
+
+#[test]
+fn test_checker_counts_split_frames() {
+    let layout = FrameLayout::for_format(&pcm_format(16, 2)).unwrap();
+    let mut checker = IntegrityChecker::new(layout).with_split_frames();
+
+    // One frame split between the left and right samples
+    assert_eq!(checker.check(0, &[0u8; 479 * 4 + 2]).unwrap().frames, 479);
+    assert_eq!(checker.carried_bytes(), 2);
+    assert_eq!(
+        checker.check(9_979, &[0u8; 2 + 480 * 4]).unwrap().frames,
+        481
+    );
+    assert_eq!(checker.carried_bytes(), 0);
+    assert_eq!(checker.stats().misaligned, 0);
+}
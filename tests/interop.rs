@@ -0,0 +1,360 @@
+// ABOUTME: Interoperability scenario against an external Sendspin server implementation
+// ABOUTME: Set SS_INTEROP_URL and run `cargo test --test interop -- --ignored --nocapture`
+
+use sendspin::audio::integrity::{FrameLayout, IntegrityChecker};
+use sendspin::audio::{AudioFormat, Codec};
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{
+    AudioFormatSpec, ClientGoodbye, ClientHello, ClientState, ClientTime, DeviceInfo,
+    GoodbyeReason, Message, PlayerState, PlayerSyncState, PlayerV1Support,
+};
+use sendspin::protocol::{Role, RoleList};
+use sendspin::sync::{ClockSync, SyncQuality};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{timeout, Instant};
+
+/// Number of client/time exchanges performed
+const TIME_SAMPLES: usize = 5;
+/// Audio chunks to verify once a stream has started
+const AUDIO_CHUNKS: usize = 20;
+/// Per-reply timeout for request/response steps
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// Compatibility report
+// ============================================================================
+
+enum Outcome {
+    Pass(String),
+    Warn(String),
+    Fail(String),
+    Skip(String),
+}
+
+struct Report {
+    server: String,
+    steps: Vec<(&'static str, Outcome)>,
+}
+
+impl Report {
+    fn record(&mut self, step: &'static str, outcome: Outcome) {
+        self.steps.push((step, outcome));
+    }
+
+    fn failures(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|(_, o)| matches!(o, Outcome::Fail(_)))
+            .count()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# sendspin-rs interop report")?;
+        writeln!(f, "server: {}", self.server)?;
+        writeln!(f, "client: sendspin-rs {}", env!("CARGO_PKG_VERSION"))?;
+        for (step, outcome) in &self.steps {
+            let (tag, detail) = match outcome {
+                Outcome::Pass(d) => ("PASS", d),
+                Outcome::Warn(d) => ("WARN", d),
+                Outcome::Fail(d) => ("FAIL", d),
+                Outcome::Skip(d) => ("SKIP", d),
+            };
+            writeln!(f, "{:<4} {:<12} {}", tag, step, detail)?;
+        }
+        write!(f, "{} step(s) failed", self.failures())
+    }
+}
+
+// ============================================================================
+// Scenario
+// ============================================================================
+
+fn unix_micros() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64
+}
+
+fn hello() -> ClientHello {
+    ClientHello::new(
+        uuid::Uuid::new_v4().to_string(),
+        "sendspin-rs interop",
+        vec![Role::Player(1)],
+    )
+    .with_device_info(DeviceInfo {
+        product_name: Some("sendspin-rs interop".to_string()),
+        manufacturer: Some("Sendspin".to_string()),
+        software_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+    })
+    .with_player_support(PlayerV1Support {
+        supported_formats: vec![
+            AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 24,
+                endian: None,
+            },
+            AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 16,
+                endian: None,
+            },
+        ],
+        buffer_capacity: 100,
+        supported_commands: vec!["volume".to_string(), "mute".to_string()],
+    })
+}
+
+async fn handshake(url: &str, report: &mut Report) -> Option<ProtocolClient> {
+    let client = match timeout(REPLY_TIMEOUT, ProtocolClient::connect(url, hello())).await {
+        Ok(Ok(client)) => client,
+        Ok(Err(e)) => {
+            report.record("handshake", Outcome::Fail(e.to_string()));
+            return None;
+        }
+        Err(_) => {
+            report.record("handshake", Outcome::Fail("no server/hello".to_string()));
+            return None;
+        }
+    };
+
+    let server = client.server_hello();
+    let detail = format!(
+        "version={} roles={:?} reason={:?}",
+        server.version, server.active_roles, server.connection_reason
+    );
+    let outcome = if server.version != 1 {
+        Outcome::Fail(format!("unsupported protocol version: {}", detail))
+    } else if !server.active_roles.contains_role(Role::Player(1)) {
+        Outcome::Warn(format!("player@v1 not activated: {}", detail))
+    } else {
+        Outcome::Pass(detail)
+    };
+    report.record("handshake", outcome);
+    Some(client)
+}
+
+async fn initial_state(client: &ProtocolClient, report: &mut Report) {
+    let state = Message::ClientState(ClientState {
+        player: Some(PlayerState {
+            state: PlayerSyncState::Synchronized,
+            volume: Some(100),
+            muted: Some(false),
+            buffered_ms: None,
+        }),
+    });
+    let outcome = match client.send_message(&state).await {
+        Ok(()) => Outcome::Pass("client/state accepted".to_string()),
+        Err(e) => Outcome::Fail(e.to_string()),
+    };
+    report.record("state", outcome);
+}
+
+async fn time_sync(client: &mut ProtocolClient, report: &mut Report) {
+    let mut clock = ClockSync::new();
+    let mut answered = 0;
+    let mut unmatched = 0;
+
+    for _ in 0..TIME_SAMPLES {
+        let t1 = unix_micros();
+        let request = Message::ClientTime(ClientTime {
+            client_transmitted: t1,
+        });
+        if let Err(e) = client.send_message(&request).await {
+            report.record("time", Outcome::Fail(e.to_string()));
+            return;
+        }
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        while let Ok(Some(msg)) = timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            client.recv_message(),
+        )
+        .await
+        {
+            if let Message::ServerTime(reply) = msg {
+                if reply.client_transmitted != t1 {
+                    unmatched += 1;
+                    continue;
+                }
+                let t4 = unix_micros();
+                clock.update(t1, reply.server_received, reply.server_transmitted, t4);
+                answered += 1;
+                break;
+            }
+        }
+    }
+
+    let detail = format!(
+        "{}/{} answered, rtt={:?}µs, quality={:?}",
+        answered,
+        TIME_SAMPLES,
+        clock.rtt_micros(),
+        clock.quality()
+    );
+    let outcome = if answered == 0 {
+        Outcome::Fail(detail)
+    } else if unmatched > 0 {
+        Outcome::Fail(format!(
+            "{} replies echoed the wrong timestamp; {}",
+            unmatched, detail
+        ))
+    } else if answered < TIME_SAMPLES || clock.quality() != SyncQuality::Good {
+        Outcome::Warn(detail)
+    } else {
+        Outcome::Pass(detail)
+    };
+    report.record("time", outcome);
+}
+
+async fn short_stream(client: &mut ProtocolClient, wait: Duration, report: &mut Report) {
+    let deadline = Instant::now() + wait;
+    let mut commands = Vec::new();
+
+    // Wait for stream/start, noting any commands that arrive in the meantime
+    while client.current_stream().player.is_none() {
+        match timeout(
+            deadline.saturating_duration_since(Instant::now()),
+            client.recv_message(),
+        )
+        .await
+        {
+            Ok(Some(Message::ServerCommand(cmd))) => commands.push(cmd),
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                report.record("stream", Outcome::Fail("connection closed".to_string()));
+                return;
+            }
+            Err(_) => break,
+        }
+    }
+
+    match client.current_stream().player {
+        None => report.record(
+            "stream",
+            Outcome::Skip(format!("no stream/start within {:?}", wait)),
+        ),
+        Some(player) => {
+            let format = AudioFormat::new(
+                Codec::Pcm,
+                player.sample_rate,
+                player.channels,
+                player.bit_depth,
+            );
+            let mut checker = match (player.codec.as_str(), FrameLayout::for_format(&format)) {
+                ("pcm", Ok(layout)) => Some(IntegrityChecker::new(layout)),
+                _ => None,
+            };
+
+            let mut received = 0;
+            let mut problems = Vec::new();
+            while received < AUDIO_CHUNKS {
+                match timeout(REPLY_TIMEOUT, client.recv_audio_chunk()).await {
+                    Ok(Some(chunk)) => {
+                        received += 1;
+                        if let Some(ref mut checker) = checker {
+                            if let Err(mismatch) = checker.check(chunk.timestamp, &chunk.data) {
+                                problems.push(mismatch.to_string());
+                            }
+                        }
+                    }
+                    _ => break,
+                }
+            }
+
+            let detail = format!(
+                "{} {}Hz {}ch {}bit, {} chunks, {} discontinuities",
+                player.codec,
+                player.sample_rate,
+                player.channels,
+                player.bit_depth,
+                received,
+                checker.map_or(0, |c| c.stats().discontinuities)
+            );
+            let outcome = if !problems.is_empty() {
+                Outcome::Fail(format!("{}; {}", detail, problems.join("; ")))
+            } else if received < AUDIO_CHUNKS {
+                Outcome::Warn(format!("stream stalled: {}", detail))
+            } else {
+                Outcome::Pass(detail)
+            };
+            report.record("stream", outcome);
+        }
+    }
+
+    let outcome = if commands.is_empty() {
+        Outcome::Skip("no server/command observed".to_string())
+    } else {
+        Outcome::Pass(format!("{} server/command parsed", commands.len()))
+    };
+    report.record("commands", outcome);
+}
+
+async fn goodbye(client: &mut ProtocolClient, report: &mut Report) {
+    let msg = Message::ClientGoodbye(ClientGoodbye {
+        reason: GoodbyeReason::Shutdown,
+        telemetry: None,
+    });
+    if let Err(e) = client.send_message(&msg).await {
+        report.record("goodbye", Outcome::Fail(e.to_string()));
+        return;
+    }
+
+    // Drain until the server closes the connection
+    let closed = timeout(REPLY_TIMEOUT, async {
+        while client.recv_message().await.is_some() {}
+    })
+    .await
+    .is_ok();
+    let outcome = if closed {
+        Outcome::Pass("server closed connection".to_string())
+    } else {
+        Outcome::Warn("server kept connection open after client/goodbye".to_string())
+    };
+    report.record("goodbye", outcome);
+}
+
+#[tokio::test]
+#[ignore] // Requires an external server
+async fn interop_scenario() {
+    let Ok(url) = std::env::var("SS_INTEROP_URL") else {
+        eprintln!("SS_INTEROP_URL not set, skipping interop scenario");
+        return;
+    };
+    let stream_wait = std::env::var("SS_INTEROP_STREAM_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(10));
+
+    let mut report = Report {
+        server: url.clone(),
+        steps: Vec::new(),
+    };
+
+    if let Some(mut client) = handshake(&url, &mut report).await {
+        initial_state(&client, &mut report).await;
+        time_sync(&mut client, &mut report).await;
+        short_stream(&mut client, stream_wait, &mut report).await;
+        goodbye(&mut client, &mut report).await;
+    }
+
+    let rendered = report.to_string();
+    println!("{}", rendered);
+    if let Ok(path) = std::env::var("SS_INTEROP_REPORT") {
+        std::fs::write(&path, format!("{}\n", rendered)).expect("write interop report");
+    }
+    assert_eq!(
+        report.failures(),
+        0,
+        "interop scenario failed:\n{}",
+        rendered
+    );
+}
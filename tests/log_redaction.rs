@@ -0,0 +1,49 @@
+// ABOUTME: Tests for protocol log redaction
+// ABOUTME: Validates masking, hashing, and nested field handling
+
+use sendspin::protocol::redact::{self, RedactionMode, Redactor};
+
+const HELLO: &str = r#"{"type":"client/hello","payload":{"client_id":"abc-123","name":"Kitchen","version":1,"device_info":{"manufacturer":"Acme"}}}"#;
+
+#[test]
+fn test_mask_mode_redacts_nested_fields() {
+    let redactor = Redactor::default();
+    let redacted = redactor.redact_json(HELLO);
+
+    assert!(!redacted.contains("abc-123"));
+    assert!(!redacted.contains("Kitchen"));
+    assert!(!redacted.contains("Acme"));
+    assert!(redacted.contains("\"client_id\":\"<redacted>\""));
+    assert!(redacted.contains("\"version\":1"));
+    assert!(redacted.contains("client/hello"));
+}
+
+#[test]
+fn test_hash_mode_is_stable() {
+    let redactor = Redactor::new(["client_id"]).with_mode(RedactionMode::Hash);
+    let first = redactor.redact_json(HELLO).into_owned();
+    let second = redactor.redact_json(HELLO).into_owned();
+
+    assert_eq!(first, second);
+    assert!(!first.contains("abc-123"));
+    assert!(first.contains("\"client_id\":\"#"));
+    // Fields outside the list are left alone
+    assert!(first.contains("Kitchen"));
+}
+
+#[test]
+fn test_invalid_json_passes_through() {
+    let redactor = Redactor::default();
+    assert_eq!(redactor.redact_json("not json"), "not json");
+}
+
+#[test]
+fn test_global_redactor() {
+    redact::set_global(Some(Redactor::new(["name"])));
+    assert!(!redact::for_log(HELLO).contains("Kitchen"));
+    assert_eq!(redact::field_for_log("name", "Kitchen"), "<redacted>");
+    assert_eq!(redact::field_for_log("server_id", "srv"), "srv");
+
+    redact::set_global(None);
+    assert_eq!(redact::for_log(HELLO), HELLO);
+}
@@ -0,0 +1,183 @@
+// ABOUTME: Tests for negotiated MessagePack/CBOR message encodings
+// ABOUTME: Covers negotiation, round trips of every encoding, and encoded sessions with the server
+
+use sendspin::protocol::client::{binary_types, ClientConfig, ProtocolClient};
+use sendspin::protocol::messages::{
+    ClientHello, ClientTime, Message, MetadataState, ServerState, StreamPlayerConfig, StreamStart,
+    StreamVisualizerConfig,
+};
+use sendspin::protocol::{MessageEncoding, Role};
+use sendspin::server::{Server, ServerConfig};
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn hello() -> ClientHello {
+    ClientHello::new("embedded", "Embedded", vec![Role::Player(1)])
+}
+
+fn samples() -> Vec<Message> {
+    vec![
+        Message::ClientHello(hello()),
+        Message::ClientTime(ClientTime {
+            client_transmitted: 1_234_567,
+        }),
+        Message::ServerState(ServerState {
+            metadata: Some(MetadataState {
+                timestamp: 10,
+                title: Some("Song".to_string()),
+                artist: None,
+                album: None,
+                artwork_url: None,
+                year: Some(1999),
+                track: None,
+                progress: None,
+                repeat: None,
+                shuffle: Some(false),
+                genre: None,
+                composer: None,
+                disc_number: None,
+                content_type: None,
+                source: None,
+                lyrics: None,
+            }),
+            controller: None,
+        }),
+        Message::StreamStart(StreamStart {
+            player: Some(StreamPlayerConfig {
+                codec: "pcm".to_string(),
+                sample_rate: 48000,
+                channels: 2,
+                bit_depth: 16,
+                codec_header: None,
+                endian: None,
+            }),
+            artwork: None,
+            visualizer: Some(StreamVisualizerConfig {
+                bins: 32,
+                rate_hz: 30,
+                window: Default::default(),
+                min_frequency_hz: 20.0,
+                max_frequency_hz: 20_000.0,
+            }),
+        }),
+    ]
+}
+
+#[test]
+fn test_encoding_names() {
+    for encoding in [
+        MessageEncoding::Json,
+        MessageEncoding::MessagePack,
+        MessageEncoding::Cbor,
+    ] {
+        assert_eq!(MessageEncoding::from_name(encoding.name()), Some(encoding));
+        assert_eq!(encoding.to_string(), encoding.name());
+    }
+    assert_eq!(MessageEncoding::from_name("bson"), None);
+    assert!(MessageEncoding::Json.is_available());
+    assert!(!MessageEncoding::available().contains(&MessageEncoding::Json));
+}
+
+#[test]
+fn test_negotiation_falls_back_to_json() {
+    let offered = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let available = MessageEncoding::available();
+    assert_eq!(
+        MessageEncoding::negotiate(&available, &offered(&[])),
+        MessageEncoding::Json
+    );
+    assert_eq!(
+        MessageEncoding::negotiate(&available, &offered(&["bson"])),
+        MessageEncoding::Json
+    );
+    assert_eq!(
+        MessageEncoding::negotiate(&[], &offered(&["cbor", "msgpack"])),
+        MessageEncoding::Json
+    );
+    // The server's preference order wins among compiled-in encodings
+    let expected = available.first().copied().unwrap_or_default();
+    assert_eq!(
+        MessageEncoding::negotiate(&available, &offered(&["msgpack", "cbor"])),
+        expected
+    );
+}
+
+#[test]
+fn test_json_is_not_a_binary_encoding() {
+    let msg = &samples()[1];
+    assert!(MessageEncoding::Json.encode(msg).is_err());
+    for encoding in [MessageEncoding::MessagePack, MessageEncoding::Cbor] {
+        assert_eq!(encoding.encode(msg).is_ok(), encoding.is_available());
+    }
+}
+
+#[test]
+fn test_round_trips() {
+    for encoding in MessageEncoding::available() {
+        for msg in samples() {
+            let frame = encoding.encode(&msg).unwrap();
+            assert_eq!(frame[0], binary_types::ENCODED_MESSAGE);
+            let decoded = encoding.decode(&frame).unwrap();
+            assert_eq!(
+                serde_json::to_value(&decoded).unwrap(),
+                serde_json::to_value(&msg).unwrap(),
+                "{} {}",
+                encoding,
+                msg.message_type()
+            );
+        }
+
+        // Frequent small messages get smaller than JSON
+        let time = &samples()[1];
+        let json = serde_json::to_string(time).unwrap();
+        assert!(encoding.encode(time).unwrap().len() < json.len());
+
+        assert!(encoding
+            .decode(&[binary_types::ENCODED_MESSAGE, 0xFF])
+            .is_err());
+        assert!(encoding.decode(&[0x04, 0x00]).is_err());
+    }
+}
+
+async fn time_round_trip(encodings: Vec<MessageEncoding>) -> Option<String> {
+    let server = Server::bind("127.0.0.1:0", ServerConfig::default())
+        .await
+        .unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let config = ClientConfig {
+        encodings,
+        ..ClientConfig::default()
+    };
+    let mut client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 42,
+        }))
+        .await
+        .unwrap();
+    loop {
+        let msg = timeout(Duration::from_secs(2), client.recv_message())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::ServerTime(time) = msg {
+            assert_eq!(time.client_transmitted, 42);
+            break;
+        }
+    }
+    client.server_hello().encoding.clone()
+}
+
+#[tokio::test]
+async fn test_encoded_session() {
+    assert_eq!(time_round_trip(Vec::new()).await, None);
+    for encoding in MessageEncoding::available() {
+        assert_eq!(
+            time_round_trip(vec![encoding]).await.as_deref(),
+            Some(encoding.name())
+        );
+    }
+}
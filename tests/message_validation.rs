@@ -0,0 +1,209 @@
+// ABOUTME: Tests for protocol message validation
+// ABOUTME: Validates range, channel, timestamp, and role name checks and outgoing rejection
+
+use sendspin::protocol::client::{ClientConfig, ProtocolClient};
+use sendspin::protocol::messages::{
+    AudioFormatSpec, ClientCommand, ClientHello, ClientState, ClientTime, ControllerCommand,
+    ControllerCommandKind, Message, PlayerCommand, PlayerCommandKind, PlayerState, PlayerSyncState,
+    PlayerV1Support, ServerCommand, StreamEnd, StreamPlayerConfig, StreamStart,
+    StreamVisualizerConfig,
+};
+use sendspin::protocol::Role;
+use sendspin::Error;
+
+fn hello() -> ClientHello {
+    ClientHello::new("client-1", "Test", vec![Role::Player(1)]).with_player_support(
+        PlayerV1Support {
+            supported_formats: vec![AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 16,
+                endian: None,
+            }],
+            buffer_capacity: 1_000_000,
+            supported_commands: vec![],
+        },
+    )
+}
+
+fn player_state(volume: u8) -> Message {
+    Message::ClientState(ClientState {
+        player: Some(PlayerState {
+            state: PlayerSyncState::Synchronized,
+            volume: Some(volume),
+            muted: None,
+            buffered_ms: None,
+        }),
+    })
+}
+
+fn stream_start(channels: u8, sample_rate: u32) -> Message {
+    Message::StreamStart(StreamStart {
+        player: Some(StreamPlayerConfig {
+            codec: "pcm".to_string(),
+            sample_rate,
+            channels,
+            bit_depth: 16,
+            codec_header: None,
+            endian: None,
+        }),
+        artwork: None,
+        visualizer: None,
+    })
+}
+
+#[test]
+fn test_valid_messages_pass() {
+    assert!(Message::ClientHello(hello()).validate().is_ok());
+    assert!(player_state(100).validate().is_ok());
+    assert!(stream_start(2, 48000).validate().is_ok());
+    let seek = Message::ClientCommand(ClientCommand {
+        controller: Some(ControllerCommand::seek(5_000_000)),
+    });
+    assert!(seek.validate().is_ok());
+}
+
+#[test]
+fn test_volume_range() {
+    let err = player_state(200).validate().unwrap_err();
+    assert!(err.to_string().contains("volume 200"), "{}", err);
+
+    let cmd = Message::ClientCommand(ClientCommand {
+        controller: Some(ControllerCommand::volume(101)),
+    });
+    assert!(cmd.validate().is_err());
+}
+
+#[test]
+fn test_channels_and_sample_rate() {
+    assert!(stream_start(0, 48000).validate().is_err());
+    assert!(stream_start(2, 0).validate().is_err());
+
+    let mut bad = hello();
+    bad.player_v1_support.as_mut().unwrap().supported_formats[0].channels = 0;
+    assert!(Message::ClientHello(bad).validate().is_err());
+}
+
+#[test]
+fn test_visualizer_config() {
+    let start = |bins, rate_hz, min_frequency_hz, max_frequency_hz| {
+        Message::StreamStart(StreamStart {
+            player: None,
+            artwork: None,
+            visualizer: Some(StreamVisualizerConfig {
+                bins,
+                rate_hz,
+                window: Default::default(),
+                min_frequency_hz,
+                max_frequency_hz,
+            }),
+        })
+    };
+    assert!(start(32, 30, 20.0, 20_000.0).validate().is_ok());
+    assert!(start(0, 30, 20.0, 20_000.0).validate().is_err());
+    assert!(start(32, 0, 20.0, 20_000.0).validate().is_err());
+    assert!(start(32, 30, 20_000.0, 20.0).validate().is_err());
+    assert!(start(32, 30, -1.0, 20.0).validate().is_err());
+}
+
+#[test]
+fn test_timestamps_and_positions() {
+    let time = Message::ClientTime(ClientTime {
+        client_transmitted: -1,
+    });
+    assert!(time.validate().is_err());
+
+    let state: Message = serde_json::from_str(
+        r#"{"type":"server/state","payload":{"metadata":{"timestamp":1,
+            "progress":{"position":10,"duration":-5}}}}"#,
+    )
+    .unwrap();
+    let err = state.validate().unwrap_err();
+    assert!(err.to_string().contains("duration"), "{}", err);
+
+    let lyrics: Message = serde_json::from_str(
+        r#"{"type":"server/state","payload":{"metadata":{"timestamp":1,
+            "lyrics":{"lines":[{"time":2000,"text":"b"},{"time":1000,"text":"a"}]}}}}"#,
+    )
+    .unwrap();
+    let err = lyrics.validate().unwrap_err();
+    assert!(err.to_string().contains("lyric"), "{}", err);
+
+    let seek = Message::ClientCommand(ClientCommand {
+        controller: Some(ControllerCommand::seek(-1)),
+    });
+    assert!(seek.validate().is_err());
+}
+
+#[test]
+fn test_commands_need_their_values() {
+    let cmd = Message::ClientCommand(ClientCommand {
+        controller: Some(ControllerCommand::new(ControllerCommandKind::Volume)),
+    });
+    assert!(cmd.validate().is_err());
+
+    let controller = |command: ControllerCommand| {
+        Message::ClientCommand(ClientCommand {
+            controller: Some(command),
+        })
+        .validate()
+    };
+    assert!(controller(ControllerCommand::seek_by(-5_000_000)).is_ok());
+    assert!(controller(ControllerCommand::new(ControllerCommandKind::Seek)).is_err());
+    let both = ControllerCommand {
+        offset: Some(1),
+        ..ControllerCommand::seek(0)
+    };
+    assert!(controller(both).is_err());
+    assert!(controller(ControllerCommand::join_group("kitchen")).is_ok());
+    assert!(controller(ControllerCommand::join_group(" ")).is_err());
+    assert!(controller(ControllerCommand::new(ControllerCommandKind::JoinGroup)).is_err());
+    assert!(controller(ControllerCommand::leave_group()).is_ok());
+
+    let player = |command: PlayerCommand| {
+        Message::ServerCommand(ServerCommand {
+            player: Some(command),
+        })
+        .validate()
+    };
+    assert!(player(PlayerCommand::seek(0)).is_ok());
+    assert!(player(PlayerCommand::seek_by(-1)).is_ok());
+    assert!(player(PlayerCommand::seek(-1)).is_err());
+    assert!(player(PlayerCommand::new(PlayerCommandKind::Seek)).is_err());
+}
+
+#[test]
+fn test_role_name_syntax() {
+    assert!(Role::Unknown("jukebox@v2".to_string()).is_well_formed());
+    assert!(!Role::Unknown("Player".to_string()).is_well_formed());
+    assert!(!Role::Unknown("player@v".to_string()).is_well_formed());
+
+    let mut bad = hello();
+    bad.supported_roles.push(Role::from("not a role"));
+    assert!(Message::ClientHello(bad).validate().is_err());
+
+    let end = Message::StreamEnd(StreamEnd {
+        roles: Some(vec![Role::from("player")]),
+    });
+    assert!(end.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_client_rejects_invalid_hello_before_connecting() {
+    let mut bad = hello();
+    bad.client_id = String::new();
+    let config = ClientConfig {
+        validate_outgoing: true,
+        ..Default::default()
+    };
+
+    // Nothing listens on this port; validation must fail first
+    let result =
+        ProtocolClient::connect_with_config("ws://127.0.0.1:9/sendspin", bad, config).await;
+    match result {
+        Err(Error::Protocol(msg)) => assert!(msg.contains("client_id"), "{}", msg),
+        Err(e) => panic!("expected validation error, got {}", e),
+        Ok(_) => panic!("expected validation error"),
+    }
+}
@@ -0,0 +1,211 @@
+// ABOUTME: Tests for now-playing metadata tracking and export
+// ABOUTME: Validates template rendering and file/artwork output
+
+use sendspin::metadata::{ArtworkManager, MetadataExporter, MetadataTracker};
+use sendspin::protocol::client::ArtworkChunk;
+use sendspin::protocol::messages::{
+    Message, MetadataState, RepeatMode, ServerState, TrackProgress,
+};
+use sendspin::sync::ClockSync;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn metadata_message() -> Message {
+    Message::ServerState(ServerState {
+        metadata: Some(MetadataState {
+            timestamp: 1_000,
+            title: Some("Song".to_string()),
+            artist: Some("Artist".to_string()),
+            album: None,
+            artwork_url: None,
+            year: Some(2024),
+            track: None,
+            progress: Some(TrackProgress {
+                position: 83_000_000,
+                duration: 4_000_000_000,
+                playback_speed: Some(1.0),
+            }),
+            repeat: Some(RepeatMode::All),
+            shuffle: Some(false),
+            genre: Some("Rock".to_string()),
+            composer: None,
+            disc_number: Some(2),
+            content_type: None,
+            source: None,
+            lyrics: None,
+        }),
+        controller: None,
+    })
+}
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("sendspin-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn test_tracker_ingests_server_state() {
+    let tracker = MetadataTracker::new();
+    assert!(tracker.current().is_none());
+
+    tracker.apply(&metadata_message());
+
+    let now_playing = tracker.current().expect("Expected metadata");
+    assert_eq!(now_playing.title, Some("Song".to_string()));
+    assert_eq!(now_playing.timestamp, 1_000);
+}
+
+#[test]
+fn test_position_interpolates_from_server_timestamp() {
+    let tracker = MetadataTracker::new();
+    assert!(tracker.position().is_none());
+    tracker.apply(&metadata_message());
+    let now_playing = tracker.current().unwrap();
+
+    // Without a clock the report counts from its arrival
+    let later = now_playing.received_at + Duration::from_secs(2);
+    assert_eq!(now_playing.position_at(later, None), Some(85_000_000));
+
+    // Server loop time is 5s now, so the report at 1ms is about 5s old
+    let mut sync = ClockSync::new();
+    let t1 = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_micros() as i64;
+    sync.update(t1, 5_000_000, 5_000_000, t1);
+    let position = now_playing
+        .position_at(Instant::now(), Some(&sync))
+        .unwrap();
+    assert!(
+        (position - 87_999_000).abs() < 100_000,
+        "Position {} should be about 88s",
+        position
+    );
+
+    let tracker = tracker.with_clock(Arc::new(tokio::sync::Mutex::new(sync)));
+    let position = tracker.position().unwrap();
+    assert!((position - 87_999_000).abs() < 100_000);
+}
+
+#[test]
+fn test_position_follows_playback_speed_and_duration() {
+    let mut now_playing = {
+        let tracker = MetadataTracker::new();
+        tracker.apply(&metadata_message());
+        tracker.current().unwrap()
+    };
+    let start = now_playing.received_at;
+    let progress = now_playing.progress.as_mut().unwrap();
+
+    progress.playback_speed = Some(0.5);
+    let position = now_playing.position_at(start + Duration::from_secs(2), None);
+    assert_eq!(position, Some(84_000_000));
+
+    // Paused
+    assert!(!now_playing.is_paused());
+    now_playing.progress.as_mut().unwrap().playback_speed = Some(0.0);
+    assert!(now_playing.is_paused());
+    let position = now_playing.position_at(start + Duration::from_secs(2), None);
+    assert_eq!(position, Some(83_000_000));
+
+    // Never past the end of the track
+    now_playing.progress.as_mut().unwrap().playback_speed = None;
+    let position = now_playing.position_at(start + Duration::from_secs(10_000), None);
+    assert_eq!(position, Some(4_000_000_000));
+    let progress = now_playing.progress_at(start + Duration::from_secs(1), None);
+    assert_eq!(progress.unwrap().position, 84_000_000);
+}
+
+#[test]
+fn test_template_rendering() {
+    let tracker = MetadataTracker::new();
+    tracker.apply(&metadata_message());
+    let exporter = MetadataExporter::new(
+        "{artist} - {title} [{album}] {progress} repeat={repeat} {unknown}",
+        temp_path("unused.txt"),
+    );
+
+    let rendered = exporter.render(tracker.current().as_ref());
+    assert_eq!(
+        rendered,
+        "Artist - Song [] 1:23 / 1:06:40 repeat=all {unknown}"
+    );
+
+    assert_eq!(exporter.render(None), " -  []  repeat= {unknown}");
+
+    let exporter = MetadataExporter::new("{genre}|{composer}|disc {disc}", temp_path("x.txt"));
+    assert_eq!(exporter.render(tracker.current().as_ref()), "Rock||disc 2");
+}
+
+#[test]
+fn test_write_and_artwork_export() {
+    let text_path = temp_path("now-playing.txt");
+    let art_path = temp_path("cover.jpg");
+    let exporter = MetadataExporter::new("{title}", &text_path).with_artwork_path(&art_path);
+
+    let tracker = MetadataTracker::new();
+    tracker.apply(&metadata_message());
+    exporter.write(tracker.current().as_ref()).unwrap();
+    assert_eq!(std::fs::read_to_string(&text_path).unwrap(), "Song");
+
+    let image = ArtworkChunk {
+        channel: 0,
+        timestamp: 0,
+        data: Arc::from(&[0xFFu8, 0xD8, 0xFF][..]),
+        received_at: Instant::now(),
+    };
+    exporter.export_artwork(&image).unwrap();
+    assert_eq!(std::fs::read(&art_path).unwrap(), vec![0xFF, 0xD8, 0xFF]);
+
+    let clear = ArtworkChunk {
+        channel: 0,
+        timestamp: 0,
+        data: Arc::from(&[][..]),
+        received_at: Instant::now(),
+    };
+    exporter.export_artwork(&clear).unwrap();
+    assert!(!art_path.exists());
+
+    let _ = std::fs::remove_file(&text_path);
+}
+
+fn artwork(channel: u8, data: &[u8]) -> ArtworkChunk {
+    ArtworkChunk {
+        channel,
+        timestamp: 0,
+        data: Arc::from(data),
+        received_at: Instant::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_artwork_manager_tracks_channels() {
+    let manager = ArtworkManager::new();
+    let mut changes = manager.subscribe();
+
+    manager.handle(artwork(0, b"cover"));
+    manager.handle(artwork(2, b"artist"));
+    assert_eq!(&*manager.current(0).unwrap().data, b"cover");
+    assert!(manager.current(1).is_none());
+    assert_eq!(&*manager.current(2).unwrap().data, b"artist");
+
+    // An empty payload clears only its channel
+    let change = manager.handle(artwork(0, b"")).unwrap();
+    assert!(change.image.is_none());
+    assert!(manager.current(0).is_none());
+    assert!(manager.current(2).is_some());
+    // Clearing an empty channel or an invalid one is not a change
+    assert!(manager.handle(artwork(1, b"")).is_none());
+    assert!(manager.handle(artwork(7, b"x")).is_none());
+
+    let seen: Vec<_> = (0..3)
+        .map(|_| changes.try_recv().unwrap())
+        .map(|change| (change.channel, change.image.is_some()))
+        .collect();
+    assert_eq!(seen, vec![(0, true), (2, true), (0, false)]);
+    assert!(changes.try_recv().is_err());
+
+    manager.clear();
+    assert!(manager.current(2).is_none());
+    let change = changes.try_recv().unwrap();
+    assert_eq!((change.channel, change.image.is_some()), (2, false));
+}
@@ -0,0 +1,75 @@
+// ABOUTME: Tests for progress and duration formatting helpers
+// ABOUTME: Validates duration, percent, relative-time, and locale rendering
+
+use sendspin::metadata::format::{
+    format_clock_time, format_duration, format_percent, format_relative, Locale,
+};
+use sendspin::protocol::messages::TrackProgress;
+
+fn progress(position: i64, duration: i64) -> TrackProgress {
+    TrackProgress {
+        position,
+        duration,
+        playback_speed: Some(1.0),
+    }
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(0), "0:00");
+    assert_eq!(format_duration(225_000_000), "3:45");
+    assert_eq!(format_duration(3_725_000_000), "1:02:05");
+    assert_eq!(format_duration(-207_000_000), "-3:27");
+}
+
+#[test]
+fn test_track_progress_helpers() {
+    let p = progress(225_000_000, 432_000_000);
+    assert_eq!(p.display(), "3:45 / 7:12");
+    assert_eq!(p.display_remaining(), "-3:27");
+    assert_eq!(p.remaining_micros(), 207_000_000);
+
+    let percent = p.percent_complete().unwrap();
+    assert!((percent - 52.083).abs() < 0.01);
+
+    assert_eq!(progress(10, 0).percent_complete(), None);
+    assert_eq!(progress(500, 100).remaining_micros(), 0);
+}
+
+#[test]
+fn test_track_progress_speed() {
+    let mut p = progress(0, 100);
+    assert_eq!(p.speed(), 1.0);
+    assert!(!p.is_paused());
+
+    p.playback_speed = None;
+    assert_eq!(p.speed(), 1.0);
+    p.playback_speed = Some(0.0);
+    assert!(p.is_paused());
+    p.playback_speed = Some(-1.0);
+    assert!(p.is_paused());
+}
+
+#[test]
+fn test_locale_aware_percent() {
+    assert_eq!(format_percent(52.08, Locale::EN), "52.1%");
+    assert_eq!(
+        format_percent(52.08, Locale::from_tag("de-DE")),
+        "52,1\u{a0}%"
+    );
+    assert_eq!(format_percent(52.08, Locale::from_tag("es_ES")), "52,1%");
+    assert_eq!(Locale::from_tag("ja-JP"), Locale::EN);
+}
+
+#[test]
+fn test_relative_and_clock_time() {
+    assert_eq!(format_relative(2_000_000), "just now");
+    assert_eq!(format_relative(120_000_000), "in 2 min");
+    assert_eq!(format_relative(-3 * 3_600_000_000), "3 h ago");
+
+    // 1970-01-01 12:30 UTC rendered in UTC and UTC+2
+    let unix_micros = (12 * 3600 + 30 * 60) * 1_000_000;
+    assert_eq!(format_clock_time(unix_micros, 0), "12:30");
+    assert_eq!(format_clock_time(unix_micros, 2 * 3600), "14:30");
+    assert_eq!(format_clock_time(unix_micros, -13 * 3600), "23:30");
+}
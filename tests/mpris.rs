@@ -0,0 +1,74 @@
+// ABOUTME: Tests for the MPRIS media session against a real D-Bus session bus
+// ABOUTME: Runs only with the "mpris" feature, and skips when no session bus is running
+#![cfg(feature = "mpris")]
+
+use dbus::arg::{PropMap, RefArg};
+use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
+use dbus::nonblock::{Proxy, SyncConnection};
+use sendspin::controller::{MediaDisplay, MediaKey, MediaSession, MprisSession};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+const PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+
+/// A second connection to call the player with, or None without a session bus
+fn client() -> Option<Arc<SyncConnection>> {
+    std::env::var_os("DBUS_SESSION_BUS_ADDRESS")?;
+    let (resource, connection) = dbus_tokio::connection::new_session_sync().ok()?;
+    tokio::spawn(resource);
+    Some(connection)
+}
+
+#[tokio::test]
+async fn test_mpris_calls_become_media_keys() {
+    let Some(client) = client() else {
+        eprintln!("No D-Bus session bus, skipping");
+        return;
+    };
+    let name = format!("sendspin_test_{}", std::process::id());
+    let (mut session, mut keys) = MprisSession::connect(&name, "Sendspin Test").await.unwrap();
+    let proxy = Proxy::new(
+        format!("org.mpris.MediaPlayer2.{}", name),
+        "/org/mpris/MediaPlayer2",
+        Duration::from_secs(5),
+        client,
+    );
+
+    let identity: String = proxy
+        .get("org.mpris.MediaPlayer2", "Identity")
+        .await
+        .unwrap();
+    assert_eq!(identity, "Sendspin Test");
+
+    proxy
+        .method_call::<(), _, _, _>(PLAYER, "PlayPause", ())
+        .await
+        .unwrap();
+    let key = timeout(Duration::from_secs(5), keys.recv()).await.unwrap();
+    assert_eq!(key, Some(MediaKey::PlayPause));
+
+    session
+        .set_now_playing(&MediaDisplay {
+            title: Some("Song".to_string()),
+            artist: Some("Band".to_string()),
+            duration: Some(Duration::from_secs(200)),
+            position: Some(Duration::from_secs(30)),
+            playing: true,
+            ..Default::default()
+        })
+        .unwrap();
+    let status: String = proxy.get(PLAYER, "PlaybackStatus").await.unwrap();
+    assert_eq!(status, "Playing");
+    let metadata: PropMap = proxy.get(PLAYER, "Metadata").await.unwrap();
+    assert_eq!(metadata["xesam:title"].0.as_str(), Some("Song"));
+    assert_eq!(metadata["mpris:length"].0.as_i64(), Some(200_000_000));
+
+    // Seek offsets are relative to the reported position
+    proxy
+        .method_call::<(), _, _, _>(PLAYER, "Seek", (10_000_000i64,))
+        .await
+        .unwrap();
+    let key = timeout(Duration::from_secs(5), keys.recv()).await.unwrap();
+    assert_eq!(key, Some(MediaKey::SetPosition(Duration::from_secs(40))));
+}
@@ -0,0 +1,117 @@
+// ABOUTME: Tests for fanning audio out to several outputs
+// ABOUTME: Validates latency alignment, timestamps, and dropping failed outputs
+
+use sendspin::audio::{AudioFormat, AudioOutput, Codec, MultiOutput, Sample};
+use sendspin::Error;
+use std::sync::{Arc, Mutex};
+
+fn format() -> AudioFormat {
+    AudioFormat::new(Codec::Pcm, 48_000, 2, 16)
+}
+
+/// Writes seen by a mock output: server timestamp and sample count
+type Writes = Arc<Mutex<Vec<(Option<i64>, usize)>>>;
+
+struct Mock {
+    format: AudioFormat,
+    latency_micros: u64,
+    writes: Writes,
+    fail: bool,
+}
+
+fn mock(latency_micros: u64, fail: bool) -> (Box<dyn AudioOutput>, Writes) {
+    let writes = Writes::default();
+    let out = Mock {
+        format: format(),
+        latency_micros,
+        writes: Arc::clone(&writes),
+        fail,
+    };
+    (Box::new(out), writes)
+}
+
+impl AudioOutput for Mock {
+    fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        self.write_timed_inner(None, samples)
+    }
+
+    fn write_timed(&mut self, timestamp: i64, samples: &Arc<[Sample]>) -> Result<(), Error> {
+        self.write_timed_inner(Some(timestamp), samples)
+    }
+
+    fn latency_micros(&self) -> u64 {
+        self.latency_micros
+    }
+
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+}
+
+impl Mock {
+    fn write_timed_inner(
+        &mut self,
+        timestamp: Option<i64>,
+        samples: &Arc<[Sample]>,
+    ) -> Result<(), Error> {
+        if self.fail {
+            return Err(Error::Output("Device unplugged".to_string()));
+        }
+        self.writes.lock().unwrap().push((timestamp, samples.len()));
+        Ok(())
+    }
+}
+
+/// 10ms of 48kHz stereo
+fn chunk() -> Arc<[Sample]> {
+    vec![Sample(1); 960].into()
+}
+
+#[test]
+fn test_faster_output_is_delayed_to_the_slowest() {
+    let mut multi = MultiOutput::new(format());
+    let (slow, slow_writes) = mock(30_000, false);
+    let (fast, fast_writes) = mock(10_000, false);
+    multi.add("slow", slow).unwrap();
+    multi.add("fast", fast).unwrap();
+    assert_eq!(multi.latency_micros(), 30_000);
+
+    multi.write_timed(1_000_000, &chunk()).unwrap();
+    multi.write_timed(1_010_000, &chunk()).unwrap();
+
+    assert_eq!(
+        *slow_writes.lock().unwrap(),
+        vec![(Some(1_000_000), 960), (Some(1_010_000), 960)]
+    );
+    // 20ms of silence (960 frames) ahead of the first chunk, once
+    assert_eq!(
+        *fast_writes.lock().unwrap(),
+        vec![(Some(980_000), 960 + 1920), (Some(1_010_000), 960)]
+    );
+}
+
+#[test]
+fn test_failed_output_is_dropped() {
+    let mut multi = MultiOutput::new(format());
+    let (good, good_writes) = mock(0, false);
+    let (bad, _) = mock(0, true);
+    multi.add("good", good).unwrap();
+    multi.add("bad", bad).unwrap();
+
+    multi.write(&chunk()).unwrap();
+    assert_eq!(multi.names(), vec!["good"]);
+    multi.write(&chunk()).unwrap();
+    assert_eq!(good_writes.lock().unwrap().len(), 2);
+
+    let mut only_bad = MultiOutput::new(format());
+    only_bad.add("bad", mock(0, true).0).unwrap();
+    assert!(only_bad.write(&chunk()).is_err());
+    assert!(only_bad.is_empty());
+}
+
+#[test]
+fn test_rejects_mismatched_format() {
+    let mut multi = MultiOutput::new(AudioFormat::new(Codec::Pcm, 44_100, 2, 16));
+    assert!(multi.add("dac", mock(0, false).0).is_err());
+    assert_eq!(multi.len(), 0);
+}
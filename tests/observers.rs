@@ -0,0 +1,93 @@
+// ABOUTME: Tests for the weak observer registry and client events
+// ABOUTME: Validates automatic cleanup and event extraction from protocol messages
+
+use parking_lot::Mutex;
+use sendspin::events::{ClientEvent, ConnectionStatus, ObserverRegistry};
+use sendspin::protocol::messages::Message;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_observer_unregisters_when_handle_dropped() {
+    let registry = ObserverRegistry::<u32>::new();
+    let seen = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&seen);
+    let handle = registry.observe(move |v| {
+        counter.fetch_add(*v as usize, Ordering::SeqCst);
+    });
+    registry.notify(&2);
+    assert_eq!(seen.load(Ordering::SeqCst), 2);
+
+    drop(handle);
+    registry.notify(&5);
+    assert_eq!(seen.load(Ordering::SeqCst), 2);
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_observer_target_is_not_kept_alive() {
+    let registry = ObserverRegistry::<u32>::new();
+    let state = Arc::new(Mutex::new(Vec::new()));
+    registry.observe_target(&state, |state, v| state.lock().push(*v));
+
+    registry.notify(&1);
+    registry.notify(&2);
+    assert_eq!(*state.lock(), vec![1, 2]);
+    assert_eq!(Arc::strong_count(&state), 1);
+
+    drop(state);
+    registry.notify(&3);
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn test_observer_may_register_during_notify() {
+    let registry = ObserverRegistry::<u32>::new();
+    let inner = registry.clone();
+    let added = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&added);
+    let _handle = registry.observe(move |_| {
+        sink.lock().push(inner.observe(|_| {}));
+    });
+
+    registry.notify(&1);
+    assert_eq!(registry.len(), 2);
+}
+
+#[test]
+fn test_dispatch_extracts_client_events() {
+    let registry = ObserverRegistry::<ClientEvent>::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    registry.observe_target(&events, |events, event| events.lock().push(event.clone()));
+
+    let state: Message = serde_json::from_str(
+        r#"{"type":"server/state","payload":{
+            "metadata":{"timestamp":1,"title":"Song"},
+            "controller":{"supported_commands":["volume"],"volume":40,"muted":true}
+        }}"#,
+    )
+    .unwrap();
+    registry.dispatch(&state);
+    let goodbye: Message =
+        serde_json::from_str(r#"{"type":"server/goodbye","payload":{"reason":"shutdown"}}"#)
+            .unwrap();
+    registry.dispatch(&goodbye);
+
+    let events = events.lock();
+    assert_eq!(events.len(), 3);
+    assert!(
+        matches!(&events[0], ClientEvent::MetadataChanged(now) if now.title.as_deref() == Some("Song"))
+    );
+    assert!(matches!(
+        events[1],
+        ClientEvent::VolumeChanged {
+            volume: 40,
+            muted: true
+        }
+    ));
+    assert!(matches!(
+        events[2],
+        ClientEvent::ConnectionChanged(ConnectionStatus::Disconnected)
+    ));
+}
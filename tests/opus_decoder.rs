@@ -0,0 +1,80 @@
+// ABOUTME: Tests for the Opus codec header and the libopus decoder
+// ABOUTME: Decoding tests run only with the "opus" feature and libopus installed
+
+use sendspin::audio::decode::OpusHead;
+
+/// OpusHead for stereo, 312 samples pre-skip, 44.1kHz input, -1dB gain
+fn opus_head() -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.push(1);
+    head.push(2);
+    head.extend_from_slice(&312u16.to_le_bytes());
+    head.extend_from_slice(&44_100u32.to_le_bytes());
+    head.extend_from_slice(&(-256i16).to_le_bytes());
+    head.push(0);
+    head
+}
+
+#[test]
+fn test_parse_opus_head() {
+    let head = OpusHead::parse(&opus_head()).unwrap();
+    assert_eq!(head.channels, 2);
+    assert_eq!(head.pre_skip, 312);
+    assert_eq!(head.input_sample_rate, 44_100);
+    assert_eq!(head.output_gain, -256);
+    assert_eq!(head.mapping_family, 0);
+}
+
+#[test]
+fn test_reject_bad_opus_head() {
+    assert!(OpusHead::parse(b"OpusTags").is_err());
+    assert!(OpusHead::parse(&opus_head()[..18]).is_err());
+
+    let mut future = opus_head();
+    future[8] = 0x10;
+    assert!(OpusHead::parse(&future).is_err());
+}
+
+#[cfg(feature = "opus")]
+mod libopus {
+    use super::opus_head;
+    use sendspin::audio::decode::{Decoder, OpusDecoder};
+    use sendspin::audio::{AudioFormat, Codec, Sample};
+
+    fn format(codec_header: Option<Vec<u8>>) -> AudioFormat {
+        let mut format = AudioFormat::new(Codec::Opus, 48_000, 2, 16);
+        format.codec_header = codec_header;
+        format
+    }
+
+    /// 20ms CELT packet of digital silence (TOC 0xFC: fullband, stereo)
+    const SILENCE: [u8; 3] = [0xFC, 0xFF, 0xFE];
+
+    #[test]
+    fn test_decode_silence_and_pre_skip() {
+        let plain = OpusDecoder::new(&format(None)).unwrap();
+        // Loss before the first packet is concealed as 20ms
+        assert_eq!(plain.decode(&[]).unwrap().len(), 960 * 2);
+        let samples = plain.decode(&SILENCE).unwrap();
+        assert_eq!(samples.len(), 960 * 2);
+        assert!(samples.iter().all(|&s| s == Sample::ZERO));
+
+        // The first 312 frames are dropped
+        let skipping = OpusDecoder::new(&format(Some(opus_head()))).unwrap();
+        assert_eq!(skipping.decode(&SILENCE).unwrap().len(), (960 - 312) * 2);
+        assert_eq!(skipping.decode(&SILENCE).unwrap().len(), 960 * 2);
+        // A lost packet is concealed
+        assert_eq!(skipping.decode(&[]).unwrap().len(), 960 * 2);
+    }
+
+    #[test]
+    fn test_reject_unsupported_formats() {
+        let mut odd_rate = format(None);
+        odd_rate.sample_rate = 44_100;
+        assert!(OpusDecoder::new(&odd_rate).is_err());
+
+        let mut mono = format(Some(opus_head()));
+        mono.channels = 1;
+        assert!(OpusDecoder::new(&mono).is_err(), "Header says stereo");
+    }
+}
@@ -0,0 +1,66 @@
+// ABOUTME: Tests for output callback timing
+// ABOUTME: Validates server-timeline tracking across consumed frames and sync error reporting
+
+use sendspin::audio::output::{FrameTimeline, OutputTiming};
+use sendspin::sync::ClockSync;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_timeline_tracks_consumed_frames() {
+    // 48kHz stereo: 20ms chunks are 960 frames / 1920 samples
+    let mut timeline = FrameTimeline::new(48000, 2);
+    assert_eq!(timeline.timestamp(), None);
+
+    timeline.push(Some(1_000_000), 1920);
+    timeline.push(Some(1_020_000), 1920);
+    assert_eq!(timeline.timestamp(), Some(1_000_000));
+
+    // Half a chunk in
+    timeline.consume(960);
+    assert_eq!(timeline.timestamp(), Some(1_010_000));
+
+    // Crossing into the second chunk
+    timeline.consume(960 + 480);
+    assert_eq!(timeline.timestamp(), Some(1_025_000));
+
+    timeline.consume(10_000);
+    assert_eq!(timeline.timestamp(), None);
+}
+
+#[test]
+fn test_timeline_untimed_buffers() {
+    let mut timeline = FrameTimeline::new(48000, 2);
+    timeline.push(None, 100);
+    timeline.push(Some(5_000), 100);
+    assert_eq!(timeline.timestamp(), None);
+    timeline.consume(100);
+    assert_eq!(timeline.timestamp(), Some(5_000));
+    timeline.clear();
+    assert_eq!(timeline.timestamp(), None);
+}
+
+#[test]
+fn test_sync_error_against_clock() {
+    let mut sync = ClockSync::new();
+    let callback_at = Instant::now();
+    let timing = OutputTiming {
+        server_timestamp: Some(50_000),
+        frames: 480,
+        callback_at,
+        playback_delay: Some(Duration::from_millis(10)),
+    };
+    assert_eq!(timing.heard_at(), callback_at + Duration::from_millis(10));
+    // No clock sync yet
+    assert!(timing.sync_error_micros(&sync).is_none());
+
+    // Server loop time zero is now, so timestamp 50ms is due in 50ms but heard in 10ms
+    sync.update(0, 0, 0, 0);
+    let error = timing.sync_error_micros(&sync).unwrap();
+    assert!((-41_000..=-39_000).contains(&error), "error {}", error);
+
+    let untimed = OutputTiming {
+        server_timestamp: None,
+        ..timing
+    };
+    assert!(untimed.sync_error_micros(&sync).is_none());
+}
@@ -1,4 +1,7 @@
-use sendspin::audio::decode::{Decoder, PcmDecoder};
+use sendspin::audio::decode::{
+    detect_endian, AutoEndianPcmDecoder, Decoder, PcmDecoder, PcmEndian,
+};
+use sendspin::audio::Sample;
 
 #[test]
 fn test_decode_pcm_16bit() {
@@ -37,3 +40,180 @@ fn test_decode_pcm_24bit() {
     assert_eq!(samples[0].0, 4096);
     assert_eq!(samples[1].0, -1);
 }
+
+#[test]
+fn test_decode_pcm_8bit_and_32bit() {
+    // Unsigned 8-bit: 128 is silence
+    let samples = PcmDecoder::new(8).decode(&[0x80, 0x00, 0xC0]).unwrap();
+    assert_eq!(&samples[..], &[Sample::ZERO, Sample::MIN, Sample(0x400000)]);
+
+    let data: Vec<u8> = [0x0100_0000i32, -0x100]
+        .iter()
+        .flat_map(|s| s.to_be_bytes())
+        .collect();
+    let samples = PcmDecoder::with_endian(32, PcmEndian::Big)
+        .decode(&data)
+        .unwrap();
+    assert_eq!(&samples[..], &[Sample(0x010000), Sample(-1)]);
+}
+
+#[test]
+fn test_decode_pcm_float() {
+    let decoder = PcmDecoder::float(PcmEndian::Little);
+    let data: Vec<u8> = [0.5f32, -1.0, 2.0, f32::NAN]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+
+    let samples = decoder.decode(&data).unwrap();
+
+    assert_eq!(samples.len(), 4);
+    assert_eq!(samples[0], Sample::from_f32(0.5));
+    assert_eq!(samples[1].0, -Sample::MAX.0);
+    // Out of range clips, NaN is silence
+    assert_eq!(samples[2], Sample::MAX);
+    assert_eq!(samples[3], Sample::ZERO);
+
+    let big = PcmDecoder::float(PcmEndian::Big);
+    let samples = big.decode(&0.25f32.to_be_bytes()).unwrap();
+    assert_eq!(samples[0], Sample::from_f32(0.25));
+}
+
+/// A stereo 16-bit sine in the given byte order
+fn tone_16bit(frames: usize, endian: PcmEndian) -> Vec<u8> {
+    (0..frames)
+        .flat_map(|i| {
+            let s = ((i as f64 * 0.03).sin() * 12_000.0) as i16;
+            [s, s / 2]
+        })
+        .flat_map(|s| match endian {
+            PcmEndian::Little => s.to_le_bytes(),
+            PcmEndian::Big => s.to_be_bytes(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_detect_endian() {
+    for endian in [PcmEndian::Little, PcmEndian::Big] {
+        assert_eq!(detect_endian(&tone_16bit(256, endian), 16, 2), Some(endian));
+        let samples = PcmDecoder::new(16)
+            .decode(&tone_16bit(256, PcmEndian::Little))
+            .unwrap();
+        let bytes = sendspin::audio::convert::to_bytes(&samples, 24, endian).unwrap();
+        assert_eq!(detect_endian(&bytes, 24, 2), Some(endian));
+    }
+    // Nothing to go on
+    assert_eq!(detect_endian(&[0; 1024], 16, 2), None);
+    assert_eq!(detect_endian(&tone_16bit(8, PcmEndian::Big), 16, 2), None);
+    assert_eq!(detect_endian(&[0x80; 1024], 8, 1), None);
+    // Unsupported depths are not guessed
+    assert_eq!(
+        detect_endian(&tone_16bit(256, PcmEndian::Little), 4, 2),
+        None
+    );
+    assert_eq!(
+        detect_endian(&tone_16bit(256, PcmEndian::Little), 12, 2),
+        None
+    );
+}
+
+#[test]
+fn test_auto_endian_decoder() {
+    let decoder = AutoEndianPcmDecoder::new(16, 2);
+    // Silence decodes as little-endian without settling
+    assert!(decoder
+        .decode(&[0; 512])
+        .unwrap()
+        .iter()
+        .all(|s| *s == Sample::ZERO));
+    assert_eq!(decoder.endian(), None);
+
+    let big = tone_16bit(256, PcmEndian::Big);
+    let expected = PcmDecoder::with_endian(16, PcmEndian::Big)
+        .decode(&big)
+        .unwrap();
+    assert_eq!(decoder.decode(&big).unwrap(), expected);
+    assert_eq!(decoder.endian(), Some(PcmEndian::Big));
+
+    // Settled: later ambiguous chunks keep the detected order
+    let samples = decoder.decode(&[0x00, 0x01, 0x00, 0x02]).unwrap();
+    assert_eq!(&samples[..], &[Sample::from_i16(1), Sample::from_i16(2)]);
+
+    // Streams that never give a clear answer fall back to little-endian
+    let decoder = AutoEndianPcmDecoder::new(16, 2);
+    for _ in 0..8 {
+        decoder.decode(&[0; 512]).unwrap();
+    }
+    assert_eq!(decoder.endian(), Some(PcmEndian::Little));
+}
+
+/// Feed `data` in uneven pieces and collect what comes out
+fn feed_split(decoder: &dyn Decoder, data: &[u8]) -> Vec<Sample> {
+    let mut out = Vec::new();
+    let mut rest = data;
+    for size in [1, 4, 2, 7, 5].iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+        let (piece, tail) = rest.split_at((*size).min(rest.len()));
+        out.extend(decoder.feed(piece).unwrap());
+        rest = tail;
+    }
+    out
+}
+
+#[test]
+fn test_feed_holds_partial_samples() {
+    let tone = tone_16bit(128, PcmEndian::Little);
+    let samples = PcmDecoder::new(16).decode(&tone).unwrap();
+
+    for bit_depth in [8, 16, 24, 32] {
+        let bytes =
+            sendspin::audio::convert::to_bytes(&samples, bit_depth, PcmEndian::Little).unwrap();
+        let whole = PcmDecoder::new(bit_depth).decode(&bytes).unwrap();
+        let fed = feed_split(&PcmDecoder::new(bit_depth), &bytes);
+        assert_eq!(&fed[..], &whole[..], "{}-bit", bit_depth);
+    }
+
+    let float: Vec<u8> = [0.5f32, -0.25, 1.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let whole = PcmDecoder::float(PcmEndian::Little).decode(&float).unwrap();
+    let fed = feed_split(&PcmDecoder::float(PcmEndian::Little), &float);
+    assert_eq!(&fed[..], &whole[..]);
+
+    // A partial sample waits for the rest
+    let decoder = PcmDecoder::new(24);
+    assert!(decoder.feed(&[0x01, 0x02]).unwrap().is_empty());
+    assert_eq!(decoder.feed(&[0x00]).unwrap(), vec![Sample(0x000201)]);
+}
+
+#[test]
+fn test_feed_holds_partial_frames() {
+    // Two stereo frames, the second split between its left and right samples
+    let decoder = PcmDecoder::new(16).with_channels(2);
+    let samples = decoder.feed(&[0x01, 0x00, 0x02, 0x00, 0x03, 0x00]).unwrap();
+    assert_eq!(samples, vec![Sample::from_i16(1), Sample::from_i16(2)]);
+    let samples = decoder.feed(&[0x04, 0x00]).unwrap();
+    assert_eq!(samples, vec![Sample::from_i16(3), Sample::from_i16(4)]);
+
+    let decoder = AutoEndianPcmDecoder::new(16, 2);
+    assert!(decoder.feed(&[0x01, 0x00]).unwrap().is_empty());
+    assert_eq!(decoder.feed(&[0x02, 0x00]).unwrap().len(), 2);
+}
+
+#[test]
+fn test_auto_endian_feed_holds_partial_samples() {
+    let big = tone_16bit(256, PcmEndian::Big);
+    let expected = PcmDecoder::with_endian(16, PcmEndian::Big)
+        .decode(&big)
+        .unwrap();
+    let decoder = AutoEndianPcmDecoder::new(16, 2);
+    // Odd pieces are still detected once whole samples add up
+    let mut fed = decoder.feed(&big[..1023]).unwrap();
+    fed.extend(decoder.feed(&big[1023..]).unwrap());
+    assert_eq!(&fed[..], &expected[..]);
+    assert_eq!(decoder.endian(), Some(PcmEndian::Big));
+}
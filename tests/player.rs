@@ -0,0 +1,543 @@
+// ABOUTME: Tests for the high-level Player against a mock server
+// ABOUTME: Uses a recording output instead of a sound card
+
+mod common;
+
+use common::{mock_server, text};
+use futures_util::{SinkExt, StreamExt};
+use sendspin::audio::{AudioFormat, AudioOutput, Codec, ResampleQuality, Sample};
+use sendspin::player::{HealthPolicy, OutputFactory, Player, PlayerConfig, PlayerEvent};
+use sendspin::protocol::client::{AudioChunk, ProtocolClient};
+use sendspin::protocol::messages::{
+    ClientHello, GroupUpdate, Message, PlayerCommand, PlayerSyncState, ServerCommand, StreamClear,
+    StreamEnd, StreamPlayerConfig, StreamStart,
+};
+use sendspin::protocol::Role;
+use sendspin::scheduler::LatencyProfile;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+fn hello() -> ClientHello {
+    ClientHello::new("player-test", "Player Test", vec![Role::Player(1)])
+}
+
+fn stream_start(codec: &str) -> Message {
+    Message::StreamStart(StreamStart {
+        player: Some(StreamPlayerConfig {
+            codec: codec.to_string(),
+            sample_rate: 48_000,
+            channels: 2,
+            bit_depth: 16,
+            codec_header: None,
+            endian: None,
+        }),
+        artwork: None,
+        visualizer: None,
+    })
+}
+
+/// 10ms of 16-bit stereo PCM
+fn pcm_chunk(timestamp: i64) -> Vec<u8> {
+    AudioChunk::encode(timestamp, &[0x10; 480 * 4])
+}
+
+/// Serve one connection: send `script` after the hello exchange, forward what
+/// the client sends, then close once `hang_up` fires
+async fn server(
+    script: Vec<WsMessage>,
+) -> (
+    String,
+    oneshot::Sender<()>,
+    mpsc::UnboundedReceiver<Message>,
+) {
+    let (hang_up, mut hung_up) = oneshot::channel::<()>();
+    let (inbound_tx, inbound) = mpsc::unbounded_channel();
+    let url = mock_server(vec![Role::Player(1)], |ws| async move {
+        let (mut write, mut read) = ws.split();
+        tokio::spawn(async move {
+            while let Some(Ok(WsMessage::Text(text))) = read.next().await {
+                let _ = inbound_tx.send(serde_json::from_str(&text).unwrap());
+            }
+        });
+        for frame in script {
+            write.send(frame).await.unwrap();
+            // Messages and chunks reach the player on separate channels
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let _ = (&mut hung_up).await;
+        let _ = write.close().await;
+    })
+    .await;
+    (url, hang_up, inbound)
+}
+
+/// Output that records what it is asked to play
+struct Recorder {
+    format: AudioFormat,
+    played: mpsc::UnboundedSender<usize>,
+}
+
+impl AudioOutput for Recorder {
+    fn write(&mut self, samples: &Arc<[Sample]>) -> Result<(), sendspin::Error> {
+        let _ = self.played.send(samples.len());
+        Ok(())
+    }
+
+    fn latency_micros(&self) -> u64 {
+        0
+    }
+
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+}
+
+fn config() -> PlayerConfig {
+    let mut profile = LatencyProfile::standard();
+    profile.start_buffer = Duration::from_millis(10);
+    profile.min_lead = Duration::from_millis(5);
+    PlayerConfig {
+        profile,
+        ..PlayerConfig::default()
+    }
+}
+
+async fn next_event(events: &mut mpsc::UnboundedReceiver<PlayerEvent>) -> PlayerEvent {
+    loop {
+        match timeout(Duration::from_secs(5), events.recv()).await {
+            Ok(Some(PlayerEvent::Message(_))) => continue,
+            Ok(Some(event)) => return event,
+            other => panic!("Expected a player event, got {:?}", other),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_player_plays_stream_through_output() {
+    let script = vec![
+        text(&stream_start("pcm")),
+        WsMessage::Binary(pcm_chunk(0)),
+        WsMessage::Binary(pcm_chunk(10_000)),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let (played_tx, mut played) = mpsc::unbounded_channel();
+    let opened = Arc::new(Mutex::new(Vec::new()));
+    let opened_by_player = Arc::clone(&opened);
+    let mut player = Player::new(config()).with_output(move |format| {
+        opened_by_player.lock().unwrap().push(format.clone());
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played_tx.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    let PlayerEvent::StreamStarted(format) = next_event(&mut events).await else {
+        panic!("Expected the stream to start");
+    };
+    assert_eq!(format.codec, Codec::Pcm);
+    assert_eq!(format.sample_rate, 48_000);
+    for _ in 0..2 {
+        let samples = timeout(Duration::from_secs(5), played.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(samples, 960);
+    }
+    assert_eq!(opened.lock().unwrap().as_slice(), &[format]);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_plays_frames_split_across_chunks() {
+    // The first chunk ends after the left sample of a frame, the second
+    // starts with its right sample
+    let script = vec![
+        text(&stream_start("pcm")),
+        WsMessage::Binary(AudioChunk::encode(0, &[0x10; 479 * 4 + 2])),
+        WsMessage::Binary(AudioChunk::encode(9_979, &[0x10; 2 + 480 * 4])),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let (played_tx, mut played) = mpsc::unbounded_channel();
+    let mut player = Player::new(config()).with_output(move |format| {
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played_tx.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    assert!(matches!(
+        next_event(&mut events).await,
+        PlayerEvent::StreamStarted(_)
+    ));
+    let mut lengths = Vec::new();
+    for _ in 0..2 {
+        lengths.push(
+            timeout(Duration::from_secs(5), played.recv())
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+    }
+    // Whole frames only, with the split one in the second chunk
+    assert_eq!(lengths, vec![479 * 2, 481 * 2]);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_resamples_to_output_rate() {
+    let script = vec![
+        text(&stream_start("pcm")),
+        WsMessage::Binary(pcm_chunk(0)),
+        WsMessage::Binary(pcm_chunk(10_000)),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let (played_tx, mut played) = mpsc::unbounded_channel();
+    let opened = Arc::new(Mutex::new(Vec::new()));
+    let opened_by_player = Arc::clone(&opened);
+    let config = PlayerConfig {
+        output_rate: Some(44_100),
+        resample_quality: ResampleQuality::Linear,
+        ..config()
+    };
+    let mut player = Player::new(config).with_output(move |format| {
+        opened_by_player.lock().unwrap().push(format.clone());
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played_tx.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    let PlayerEvent::StreamStarted(format) = next_event(&mut events).await else {
+        panic!("Expected the stream to start");
+    };
+    assert_eq!(format.sample_rate, 48_000);
+    // 10ms at 44.1kHz, stereo
+    let samples = timeout(Duration::from_secs(5), played.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(samples, 441 * 2);
+    let opened = opened.lock().unwrap().clone();
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].sample_rate, 44_100);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_reports_unsupported_format_and_stream_end() {
+    let script = vec![
+        text(&stream_start("mystery")),
+        WsMessage::Binary(pcm_chunk(0)),
+        text(&Message::StreamEnd(StreamEnd { roles: None })),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let mut player = Player::new(config()).with_output(|_| panic!("Nothing should play"));
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    assert!(matches!(
+        next_event(&mut events).await,
+        PlayerEvent::UnsupportedFormat(_)
+    ));
+    assert!(matches!(
+        next_event(&mut events).await,
+        PlayerEvent::StreamEnded
+    ));
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_fails_when_output_cannot_open() {
+    let script = vec![text(&stream_start("pcm")), WsMessage::Binary(pcm_chunk(0))];
+    let (url, _hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let player = Player::new(config())
+        .with_output(|_| Err(sendspin::Error::Output("no device".to_string())));
+    let result = timeout(Duration::from_secs(5), player.run(client))
+        .await
+        .unwrap();
+    assert!(matches!(result, Err(sendspin::Error::Output(_))));
+}
+
+#[tokio::test]
+async fn test_player_reports_error_state_on_underruns() {
+    // Each frame takes 20ms to arrive; filler between chunks starves the output
+    let filler = text(&Message::GroupUpdate(GroupUpdate {
+        playback_state: None,
+        group_id: None,
+        group_name: None,
+        members: None,
+    }));
+    let mut script = vec![text(&stream_start("pcm"))];
+    for timestamp in [0, 10_000] {
+        script.push(WsMessage::Binary(pcm_chunk(timestamp)));
+        script.extend([filler.clone(), filler.clone(), filler.clone()]);
+    }
+    script.push(text(&Message::StreamEnd(StreamEnd { roles: None })));
+    let (url, hang_up, mut inbound) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let (played, _played) = mpsc::unbounded_channel();
+    let mut config = config();
+    config.health = HealthPolicy {
+        max_underruns: 1,
+        underrun_window: Duration::from_millis(100),
+        sync_lost: false,
+        recovery: Duration::from_millis(50),
+    };
+    let mut player = Player::new(config).with_output(move |format| {
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    let mut changes = Vec::new();
+    while changes.len() < 2 {
+        if let PlayerEvent::SyncStateChanged(state) = next_event(&mut events).await {
+            changes.push(state);
+        }
+    }
+    assert_eq!(
+        changes,
+        vec![PlayerSyncState::Error, PlayerSyncState::Synchronized]
+    );
+
+    // The server heard about both, after the initial report
+    let mut reported = Vec::new();
+    while !reported.ends_with(&[PlayerSyncState::Error, PlayerSyncState::Synchronized]) {
+        let msg = timeout(Duration::from_secs(5), inbound.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::ClientState(state) = msg {
+            reported.push(state.player.unwrap().state);
+        }
+    }
+    assert_eq!(reported[0], PlayerSyncState::Synchronized);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_applies_server_volume_and_mute() {
+    let command = |player| {
+        text(&Message::ServerCommand(ServerCommand {
+            player: Some(player),
+        }))
+    };
+    let script = vec![
+        command(PlayerCommand::volume(50)),
+        command(PlayerCommand::mute(true)),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let mut player = Player::new(config()).with_output(|_| panic!("Nothing should play"));
+    let volume = player.volume();
+    let reporter = player.reporter();
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    let mut commands = 0;
+    while commands < 2 {
+        match timeout(Duration::from_secs(5), events.recv()).await {
+            Ok(Some(PlayerEvent::Message(msg))) => {
+                commands += matches!(*msg, Message::ServerCommand(_)) as usize;
+            }
+            Ok(Some(_)) => {}
+            other => panic!("Expected a player event, got {:?}", other),
+        }
+    }
+    assert!(volume.is_muted());
+    assert!(volume.gain_db() < -20.0);
+    let state = reporter.state();
+    assert_eq!((state.volume, state.muted), (Some(50), Some(true)));
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_prebuffers_again_after_stream_clear() {
+    let script = vec![
+        text(&stream_start("pcm")),
+        WsMessage::Binary(pcm_chunk(0)),
+        text(&Message::StreamClear(StreamClear { roles: None })),
+        WsMessage::Binary(pcm_chunk(500_000)),
+        text(&Message::StreamEnd(StreamEnd { roles: None })),
+    ];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let (played, _played) = mpsc::unbounded_channel();
+    let mut player = Player::new(config()).with_output(move |format| {
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let mut events = player.events();
+    let running = tokio::spawn(player.run(client));
+
+    let mut seen = Vec::new();
+    loop {
+        match next_event(&mut events).await {
+            PlayerEvent::StreamStarted(_) => seen.push("started"),
+            PlayerEvent::PlaybackStarted { .. } => seen.push("playing"),
+            PlayerEvent::StreamCleared => seen.push("cleared"),
+            PlayerEvent::StreamEnded => break,
+            _ => {}
+        }
+    }
+    // Whether the first chunk plays before the clear depends on delivery timing
+    assert_eq!(seen[0], "started");
+    assert!(seen.ends_with(&["cleared", "playing"]), "{:?}", seen);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_player_reports_playback_speed_changes() {
+    let progress = |speed: f64| {
+        WsMessage::Text(format!(
+            r#"{{"type":"server/state","payload":{{"metadata":{{"timestamp":1,
+            "progress":{{"position":0,"duration":1000000,"playback_speed":{}}}}}}}}}"#,
+            speed
+        ))
+    };
+    let script = vec![progress(1.0), progress(1.5), progress(1.5), progress(1.0)];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let (speeds_tx, mut speeds) = mpsc::unbounded_channel();
+    let player = Player::new(config())
+        .with_output(|_| panic!("Nothing should play"))
+        .on_playback_speed(move |speed| {
+            let _ = speeds_tx.send(speed);
+        });
+    let running = tokio::spawn(player.run(client));
+
+    // Only changes from the normal speed and back are reported
+    for expected in [1.5, 1.0] {
+        let speed = timeout(Duration::from_secs(5), speeds.recv())
+            .await
+            .unwrap();
+        assert_eq!(speed, Some(expected));
+    }
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(speeds.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_player_plays_to_outputs_that_open() {
+    let script = vec![text(&stream_start("pcm")), WsMessage::Binary(pcm_chunk(0))];
+    let (url, hang_up, _) = server(script).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let (played_tx, mut played) = mpsc::unbounded_channel();
+    let recorder: OutputFactory = Box::new(move |format| {
+        Ok(Box::new(Recorder {
+            format: format.clone(),
+            played: played_tx.clone(),
+        }) as Box<dyn AudioOutput>)
+    });
+    let unplugged: OutputFactory =
+        Box::new(|_| Err(sendspin::Error::Output("No such device".to_string())));
+    let player = Player::new(config()).with_outputs(vec![
+        ("kitchen".to_string(), unplugged),
+        ("den".to_string(), recorder),
+    ]);
+    let running = tokio::spawn(player.run(client));
+
+    let samples = timeout(Duration::from_secs(5), played.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(samples, 960);
+
+    hang_up.send(()).unwrap();
+    timeout(Duration::from_secs(5), running)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
+
+#[test]
+fn test_supported_formats_follow_compiled_decoders() {
+    let formats = Player::supported_formats();
+    let has = |codec: &str| formats.iter().any(|f| f.codec == codec);
+    assert!(formats
+        .iter()
+        .any(|f| f.codec == "pcm" && f.sample_rate == 48_000 && f.bit_depth == 24));
+    assert!(formats
+        .iter()
+        .any(|f| f.codec == "pcm_float" && f.bit_depth == 32));
+    assert_eq!(has("opus"), cfg!(feature = "opus"));
+    assert_eq!(
+        has("vorbis"),
+        cfg!(any(feature = "vorbis", feature = "symphonia"))
+    );
+    assert_eq!(has("aac"), cfg!(feature = "symphonia"));
+    assert_eq!(has("alac"), cfg!(feature = "symphonia"));
+    assert_eq!(has("flac"), cfg!(feature = "symphonia"));
+}
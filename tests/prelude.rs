@@ -0,0 +1,48 @@
+// ABOUTME: Tests for the prelude and top-level re-exports
+// ABOUTME: Guards the stable high-level API against accidental removal
+
+use sendspin::prelude::*;
+
+#[test]
+fn test_prelude_covers_receive_pipeline() {
+    let format = AudioFormat::new(Codec::Pcm, 48000, 2, 16);
+    let decoder = PcmDecoder::new(format.bit_depth);
+    let samples = decoder.decode(&[0, 0, 0, 0]).unwrap();
+    assert_eq!(samples.len(), 2);
+
+    let scheduler = AudioScheduler::new();
+    scheduler.schedule(AudioBuffer {
+        timestamp: 0,
+        play_at: std::time::Instant::now(),
+        samples,
+        format,
+    });
+    assert!(scheduler.next_ready().is_some());
+
+    assert!(StreamTracker::new().current().is_idle());
+    assert!(MetadataTracker::new().current().is_none());
+    assert_eq!(ClockSync::new().quality(), SyncQuality::Lost);
+}
+
+#[test]
+fn test_root_reexports_match_prelude() {
+    fn same<T>(_: Option<T>, _: Option<T>) {}
+
+    same::<sendspin::Message>(None, None::<Message>);
+    same::<sendspin::Error>(None, None::<Error>);
+    same::<sendspin::AudioFormat>(None, None::<AudioFormat>);
+    same::<sendspin::ClockSync>(None, None::<ClockSync>);
+    same::<sendspin::NowPlaying>(None, None::<NowPlaying>);
+    same::<sendspin::Player>(None, None::<Player>);
+    same::<sendspin::PlayerConfig>(None, None::<PlayerConfig>);
+    same::<sendspin::PlayerEvent>(None, None::<PlayerEvent>);
+    same::<sendspin::Controller>(None, None::<Controller>);
+    same::<sendspin::EventBus>(None, None::<EventBus>);
+    same::<sendspin::ClientEvent>(None, None::<ClientEvent>);
+    same::<sendspin::Config>(None, None::<Config>);
+    same::<sendspin::ClientSettings>(None, None::<ClientSettings>);
+    same::<sendspin::PlayerSettings>(None, None::<PlayerSettings>);
+
+    let result: Result<()> = Ok(());
+    assert!(result.is_ok());
+}
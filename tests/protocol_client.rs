@@ -1,6 +1,31 @@
 // Note: These are integration tests that require a running server
 // For now, we'll create the structure and skip them
 
+mod common;
+
+use common::{answer_hello, idle, mock_server, send, serve};
+use futures_util::{SinkExt, StreamExt};
+use sendspin::audit::Direction;
+use sendspin::events::{ClientEvent, ConnectionStatus};
+use sendspin::protocol::client::{AudioChunk, ClientConfig, ClientParts, ProtocolClient};
+use sendspin::protocol::messages::{
+    ClientCommand, ClientHello, ClientState, ClientTime, ControllerCommand, ControllerCommandKind,
+    GoodbyeReason, Message, PlayerState, PlayerSyncState, ServerGoodbye, ServerGoodbyeReason,
+    ServerHello, ServerTime,
+};
+use sendspin::protocol::{
+    BackpressurePolicy, ChannelConfig, Coalescing, DroppedFrames, FrameStats, FrameTap, Keepalive,
+    RawFrame, Role, StateReporter, StreamChannels,
+};
+use sendspin::Error;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
 #[test]
 #[ignore] // Requires running server
 fn test_client_receives_stream_start() {
@@ -14,3 +39,864 @@ fn test_client_handles_audio_chunks() {
     // Test that client can receive binary audio chunks
     // Will implement when we have full client
 }
+
+/// Serve one connection: answer client/hello, then send server/goodbye
+async fn goodbye_server(before_hello: bool) -> String {
+    serve(move |mut ws| async move {
+        let goodbye = Message::ServerGoodbye(ServerGoodbye {
+            reason: ServerGoodbyeReason::Shutdown,
+        });
+        if before_hello {
+            let _hello = ws.next().await;
+            send(&mut ws, &goodbye).await;
+        } else {
+            let hello = ServerHello::new("mock", "Mock", vec![Role::Player(1)]);
+            if answer_hello(&mut ws, hello).await {
+                send(&mut ws, &goodbye).await;
+            }
+        }
+        // Keep the socket open: the client must end the connection itself
+        idle(ws).await;
+    })
+    .await
+}
+
+fn hello() -> ClientHello {
+    ClientHello::new("goodbye-test", "Goodbye Test", vec![Role::Player(1)])
+}
+
+#[tokio::test]
+async fn test_server_goodbye_ends_connection_cleanly() {
+    let url = goodbye_server(false).await;
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let msg = timeout(Duration::from_secs(2), client.recv_message())
+        .await
+        .unwrap();
+    match msg {
+        Some(Message::ServerGoodbye(goodbye)) => {
+            assert_eq!(goodbye.reason, ServerGoodbyeReason::Shutdown)
+        }
+        other => panic!("Expected server/goodbye, got {:?}", other),
+    }
+    // The goodbye is followed by a clean end of all channels
+    let next = timeout(Duration::from_secs(2), client.recv_message())
+        .await
+        .unwrap();
+    assert!(next.is_none());
+    let audio = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+        .await
+        .unwrap();
+    assert!(audio.is_none());
+}
+
+#[tokio::test]
+async fn test_server_goodbye_during_handshake() {
+    let url = goodbye_server(true).await;
+    match ProtocolClient::connect(&url, hello()).await {
+        Err(Error::Connection(reason)) => assert!(reason.contains("goodbye")),
+        Err(other) => panic!("Expected connection error, got {}", other),
+        Ok(_) => panic!("Expected connection error"),
+    }
+}
+
+/// Serve one connection: answer client/hello, wait for one more message, then send `frames`
+async fn binary_server(frames: Vec<Vec<u8>>) -> String {
+    mock_server(vec![Role::Player(1)], |mut ws| async move {
+        // The client is ready once it sends anything else
+        let _ready = ws.next().await;
+        for frame in frames {
+            ws.send(WsMessage::Binary(frame)).await.unwrap();
+        }
+        idle(ws).await;
+    })
+    .await
+}
+
+/// Serve one connection: answer client/hello and client/time, and forward every later text frame
+async fn recording_server(active_roles: Vec<Role>) -> (String, mpsc::UnboundedReceiver<Message>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let url = mock_server(active_roles, |mut ws| async move {
+        while let Some(Ok(frame)) = ws.next().await {
+            if let WsMessage::Text(text) = frame {
+                let msg: Message = serde_json::from_str(&text).unwrap();
+                if let Message::ClientTime(time) = &msg {
+                    let reply = Message::ServerTime(ServerTime {
+                        client_transmitted: time.client_transmitted,
+                        server_received: 1_000,
+                        server_transmitted: 1_100,
+                    });
+                    send(&mut ws, &reply).await;
+                }
+                let _ = tx.send(msg);
+            }
+        }
+    })
+    .await;
+    (url, rx)
+}
+
+#[tokio::test]
+async fn test_seek_helpers_send_controller_commands() {
+    let (url, mut sent) = recording_server(vec![Role::Controller(1)]).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    client.seek(83_000_000).await.unwrap();
+    client.seek_by(-10_000_000).await.unwrap();
+
+    let mut seeks = Vec::new();
+    while seeks.len() < 2 {
+        let msg = timeout(Duration::from_secs(2), sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::ClientCommand(ClientCommand {
+            controller: Some(command),
+        }) = msg
+        {
+            assert_eq!(command.command, ControllerCommandKind::Seek);
+            seeks.push((command.position, command.offset));
+        }
+    }
+    assert_eq!(seeks, [(Some(83_000_000), None), (None, Some(-10_000_000))]);
+}
+
+#[tokio::test]
+async fn test_audio_slots_are_demultiplexed() {
+    let url = binary_server(vec![
+        AudioChunk::encode_slot(2, 10, &[2]).unwrap(),
+        AudioChunk::encode_slot(3, 11, &[3]).unwrap(),
+        AudioChunk::encode(12, &[0]),
+        AudioChunk::encode_slot(2, 13, &[2]).unwrap(),
+    ])
+    .await;
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    assert!(client.audio_slot(0).is_err());
+    assert!(client.audio_slot(4).is_err());
+    let mut slot2 = client.audio_slot(2).unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+
+    let primary = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((primary.slot, primary.timestamp), (0, 12));
+
+    let mut extra = Vec::new();
+    for _ in 0..2 {
+        let chunk = timeout(Duration::from_secs(2), slot2.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        extra.push((chunk.slot, chunk.timestamp));
+    }
+    assert_eq!(extra, vec![(2, 10), (2, 13)]);
+}
+
+/// Connect to a server that sends five primary audio chunks, with a small audio channel
+async fn slow_consumer(policy: BackpressurePolicy) -> ProtocolClient {
+    let frames = (0..5).map(|ts| AudioChunk::encode(ts, &[0])).collect();
+    let url = binary_server(frames).await;
+    let config = ClientConfig {
+        channels: StreamChannels {
+            audio: ChannelConfig::new(2, policy),
+            ..StreamChannels::default()
+        },
+        ..ClientConfig::default()
+    };
+    let client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    client
+}
+
+async fn drain_audio(client: &mut ProtocolClient, count: usize) -> Vec<i64> {
+    let mut timestamps = Vec::new();
+    for _ in 0..count {
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        timestamps.push(chunk.timestamp);
+    }
+    timestamps
+}
+
+async fn wait_for_drops(client: &ProtocolClient, audio: u64) {
+    timeout(Duration::from_secs(2), async {
+        while client.dropped_frames().audio < audio {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_full_audio_channel_drops_oldest() {
+    let mut client = slow_consumer(BackpressurePolicy::DropOldest).await;
+    wait_for_drops(&client, 3).await;
+    assert_eq!(drain_audio(&mut client, 2).await, vec![3, 4]);
+    assert_eq!(
+        client.dropped_frames(),
+        DroppedFrames {
+            audio: 3,
+            ..DroppedFrames::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_full_audio_channel_drops_newest() {
+    let mut client = slow_consumer(BackpressurePolicy::DropNewest).await;
+    wait_for_drops(&client, 3).await;
+    assert_eq!(drain_audio(&mut client, 2).await, vec![0, 1]);
+}
+
+#[tokio::test]
+async fn test_full_audio_channel_blocks() {
+    let mut client = slow_consumer(BackpressurePolicy::Block).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(drain_audio(&mut client, 5).await, vec![0, 1, 2, 3, 4]);
+    assert_eq!(client.dropped_frames(), DroppedFrames::default());
+}
+
+#[tokio::test]
+async fn test_clock_sync_runs_automatically() {
+    let (url, mut sent) = recording_server(vec![Role::Controller(1)]).await;
+    let config = ClientConfig {
+        clock_sync_interval: Some(Duration::from_millis(50)),
+        ..ClientConfig::default()
+    };
+    let mut client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+
+    // The first request goes out right away, the next after one interval
+    for _ in 0..2 {
+        let msg = timeout(Duration::from_secs(2), sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(msg, Message::ClientTime(_)), "{:?}", msg);
+    }
+    // Replies are applied by the client and still delivered
+    let reply = timeout(Duration::from_secs(2), client.recv_message())
+        .await
+        .unwrap();
+    assert!(matches!(reply, Some(Message::ServerTime(_))));
+    let sync = client.clock_sync();
+    let sync = sync.lock().await;
+    assert!(sync.rtt_micros().is_some());
+    assert!(sync.server_to_local_instant(0).is_some());
+}
+
+fn player_state() -> ClientState {
+    ClientState {
+        player: Some(PlayerState {
+            state: PlayerSyncState::Synchronized,
+            volume: Some(40),
+            muted: Some(false),
+            buffered_ms: None,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_initial_state_completes_handshake() {
+    let (url, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .initial_state(player_state())
+        .connect()
+        .await
+        .unwrap();
+
+    let session = client.session();
+    assert!(session.state_sent());
+    assert!(session.has_role(Role::Player(1)));
+    assert!(session.refused_roles().is_empty());
+    assert_eq!(session.server_name(), "Mock");
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match msg {
+        Message::ClientState(state) => assert_eq!(state.player.unwrap().volume, Some(40)),
+        other => panic!("Expected client/state, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_initial_state_skipped_for_refused_player_role() {
+    let (url, mut sent) = recording_server(vec![Role::Controller(1)]).await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .initial_state(player_state())
+        .connect()
+        .await
+        .unwrap();
+
+    let session = client.session();
+    assert!(!session.state_sent());
+    assert!(!session.has_role(Role::Player(1)));
+    assert_eq!(session.refused_roles(), vec![Role::Player(1)]);
+    // Nothing was sent after the hello
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::ClientTime(_)), "{:?}", msg);
+}
+
+#[tokio::test]
+async fn test_close_sends_goodbye_and_closes_socket() {
+    let (url, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    client.close(GoodbyeReason::UserRequest).await.unwrap();
+
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match msg {
+        Message::ClientGoodbye(goodbye) => assert_eq!(goodbye.reason, GoodbyeReason::UserRequest),
+        other => panic!("Expected client/goodbye, got {:?}", other),
+    }
+    // The server saw the close handshake and stopped reading
+    let after = timeout(Duration::from_secs(2), sent.recv()).await.unwrap();
+    assert!(after.is_none(), "{:?}", after);
+}
+
+#[tokio::test]
+async fn test_split_sender_close_ends_message_stream() {
+    let (url, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let ClientParts {
+        mut messages,
+        sender,
+        ..
+    } = client.into_parts();
+
+    sender.close(GoodbyeReason::Shutdown).await.unwrap();
+
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::ClientGoodbye(_)), "{:?}", msg);
+    let next = timeout(Duration::from_secs(2), messages.recv())
+        .await
+        .unwrap();
+    assert!(next.is_none(), "{:?}", next);
+    assert!(sender
+        .send_message(Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .is_err());
+}
+
+/// Complete the handshake, then never read or answer again, like a dead NAT mapping
+async fn silent_server() -> String {
+    mock_server(vec![Role::Player(1)], |ws| async move {
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        drop(ws);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn test_keepalive_detects_silent_server() {
+    let client = ProtocolClient::builder(silent_server().await)
+        .hello(hello())
+        .keepalive(Keepalive {
+            ping_interval: Duration::from_millis(50),
+            timeout: Duration::from_millis(150),
+        })
+        .connect()
+        .await
+        .unwrap();
+    let status = client.connection_status();
+    // Keep the sender alive: the watchdog stops once every sender is gone
+    let ClientParts {
+        mut messages,
+        sender: _sender,
+        ..
+    } = client.into_parts();
+
+    let next = timeout(Duration::from_secs(2), messages.recv())
+        .await
+        .expect("watchdog should end the connection");
+    assert!(next.is_none(), "{:?}", next);
+    assert_eq!(*status.borrow(), ConnectionStatus::Lost);
+}
+
+#[tokio::test]
+async fn test_keepalive_pongs_keep_connection_up() {
+    let (url, _sent) = recording_server(vec![Role::Player(1)]).await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .keepalive(Keepalive {
+            ping_interval: Duration::from_millis(50),
+            timeout: Duration::from_millis(150),
+        })
+        .connect()
+        .await
+        .unwrap();
+    let status = client.connection_status();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(*status.borrow(), ConnectionStatus::Connected);
+
+    let ClientParts {
+        mut messages,
+        sender,
+        ..
+    } = client.into_parts();
+    sender.close(GoodbyeReason::Shutdown).await.unwrap();
+    let next = timeout(Duration::from_secs(2), messages.recv())
+        .await
+        .unwrap();
+    assert!(next.is_none(), "{:?}", next);
+    assert_eq!(*status.borrow(), ConnectionStatus::Disconnected);
+}
+
+#[tokio::test]
+async fn test_receivers_are_streams() {
+    let client = slow_consumer(BackpressurePolicy::Block).await;
+    let ClientParts { audio, .. } = client.into_parts();
+    // Blocking capacity 2: the stream must keep waking up as the router refills it
+    let timestamps: Vec<i64> = timeout(
+        Duration::from_secs(2),
+        audio.map(|chunk| chunk.timestamp).take(5).collect(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(timestamps, vec![0, 1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_streams_end_with_the_connection() {
+    let url = goodbye_server(false).await;
+    let client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let ClientParts {
+        messages, audio, ..
+    } = client.into_parts();
+
+    let reasons: Vec<_> = timeout(
+        Duration::from_secs(2),
+        messages
+            .filter_map(|msg| async move {
+                match msg {
+                    Message::ServerGoodbye(goodbye) => Some(goodbye.reason),
+                    _ => None,
+                }
+            })
+            .collect(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(reasons, vec![ServerGoodbyeReason::Shutdown]);
+    let chunks: Vec<_> = timeout(Duration::from_secs(2), audio.collect())
+        .await
+        .unwrap();
+    assert!(chunks.is_empty());
+}
+
+#[tokio::test]
+async fn test_frame_tap_sees_raw_frames() {
+    let url = binary_server(vec![AudioChunk::encode(7, &[1, 2])]).await;
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&frames);
+    let config = ClientConfig {
+        frame_tap: Some(FrameTap::new(move |direction, frame| {
+            let summary = match frame {
+                RawFrame::Text(text) => {
+                    let msg: Message = serde_json::from_str(text).unwrap();
+                    msg.message_type().to_string()
+                }
+                RawFrame::Binary(data) => format!("binary type {}", data[0]),
+            };
+            log.lock().unwrap().push((direction, summary));
+        })),
+        ..ClientConfig::default()
+    };
+    let mut client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    timeout(Duration::from_secs(2), client.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        *frames.lock().unwrap(),
+        [
+            (Direction::Outbound, "client/hello".to_string()),
+            (Direction::Inbound, "server/hello".to_string()),
+            (Direction::Outbound, "client/time".to_string()),
+            (Direction::Inbound, "binary type 4".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_switch_server_keeps_receivers() {
+    let (old, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let new = binary_server(vec![AudioChunk::encode(9, &[1])]).await;
+    let mut client = ProtocolClient::connect(&old, hello()).await.unwrap();
+    let mut status = client.connection_status();
+
+    client.switch_server(&new).await.unwrap();
+
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    match msg {
+        Message::ClientGoodbye(goodbye) => {
+            assert_eq!(goodbye.reason, GoodbyeReason::AnotherServer)
+        }
+        other => panic!("Expected client/goodbye, got {:?}", other),
+    }
+    let msg = timeout(Duration::from_secs(2), client.recv_message())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::StreamEnd(_)), "{:?}", msg);
+    // Sending reaches the new server, which then starts streaming
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(chunk.timestamp, 9);
+    assert_eq!(*status.borrow_and_update(), ConnectionStatus::Connected);
+}
+
+#[tokio::test]
+async fn test_failed_switch_keeps_current_server() {
+    let (url, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let unused = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = format!("ws://{}/sendspin", unused.local_addr().unwrap());
+    drop(unused);
+
+    let result = client.switch_server(&unreachable).await;
+    assert!(
+        matches!(result, Err(Error::Connection(_))),
+        "{:?}",
+        result.err()
+    );
+
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    let msg = timeout(Duration::from_secs(2), sent.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::ClientTime(_)), "{:?}", msg);
+}
+
+fn command(command: ControllerCommand) -> Message {
+    Message::ClientCommand(ClientCommand {
+        controller: Some(command),
+    })
+}
+
+#[tokio::test]
+async fn test_coalescing_sends_first_and_latest_update() {
+    let (url, mut sent) = recording_server(vec![Role::Controller(1)]).await;
+    let client = ProtocolClient::builder(url)
+        .hello(hello())
+        .coalesce(Coalescing::new(Duration::from_millis(100)))
+        .connect()
+        .await
+        .unwrap();
+
+    for volume in 1..=5 {
+        client
+            .send_message(&command(ControllerCommand::volume(volume)))
+            .await
+            .unwrap();
+        if volume == 2 {
+            // Other commands are never held back
+            client
+                .send_message(&command(ControllerCommand::new(
+                    ControllerCommandKind::Next,
+                )))
+                .await
+                .unwrap();
+        }
+    }
+
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        let msg = timeout(Duration::from_secs(2), sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::ClientCommand(ClientCommand {
+            controller: Some(command),
+        }) = msg
+        {
+            received.push((command.command, command.volume));
+        }
+    }
+    assert_eq!(
+        received,
+        [
+            (ControllerCommandKind::Volume, Some(1)),
+            (ControllerCommandKind::Next, None),
+            (ControllerCommandKind::Volume, Some(5)),
+        ]
+    );
+    let more = timeout(Duration::from_millis(300), sent.recv()).await;
+    assert!(more.is_err(), "Unexpected {:?}", more);
+}
+
+#[tokio::test]
+async fn test_polling_receive_apis() {
+    let url = binary_server(vec![AudioChunk::encode(3, &[1])]).await;
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    assert!(client.try_recv_message().is_none());
+    assert!(client.try_recv_audio_chunk().is_none());
+    assert_eq!(
+        client
+            .recv_message_timeout(Duration::from_millis(20))
+            .await
+            .err(),
+        Some(RecvTimeoutError::Timeout)
+    );
+
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    let chunk = client
+        .recv_audio_chunk_timeout(Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(chunk.timestamp, 3);
+    assert!(client.try_recv_artwork_chunk().is_none());
+    assert!(client.try_recv_visualizer_chunk().is_none());
+}
+
+#[tokio::test]
+async fn test_timeout_receive_reports_closed_connection() {
+    let url = goodbye_server(false).await;
+    let parts = ProtocolClient::connect(&url, hello())
+        .await
+        .unwrap()
+        .into_parts();
+    let (mut messages, mut audio) = (parts.messages, parts.audio);
+    let msg = messages.recv_timeout(Duration::from_secs(2)).await.unwrap();
+    assert!(matches!(msg, Message::ServerGoodbye(_)));
+    assert_eq!(
+        messages.recv_timeout(Duration::from_secs(2)).await.err(),
+        Some(RecvTimeoutError::Disconnected)
+    );
+    assert!(messages.is_closed());
+    assert_eq!(
+        audio.recv_timeout(Duration::from_secs(2)).await.err(),
+        Some(RecvTimeoutError::Disconnected)
+    );
+    assert!(audio.is_closed());
+}
+
+#[tokio::test]
+async fn test_received_at_is_arrival_time() {
+    let url = goodbye_server(false).await;
+    let connected = Instant::now();
+    let mut messages = ProtocolClient::connect(&url, hello())
+        .await
+        .unwrap()
+        .into_parts()
+        .messages;
+    // The message waits in the queue; its stamp must not include that wait
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let read = Instant::now();
+    let received = timeout(Duration::from_secs(2), messages.recv_stamped())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(received.message, Message::ServerGoodbye(_)));
+    assert!(received.received_at >= connected);
+    assert!(read - received.received_at >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_audio_chunks_carry_arrival_time() {
+    let url = binary_server(vec![AudioChunk::encode(7, &[1])]).await;
+    let connected = Instant::now();
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    let chunk = client
+        .recv_audio_chunk_timeout(Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert!(chunk.received_at >= connected);
+    assert!(chunk.received_at <= Instant::now());
+}
+
+#[tokio::test]
+async fn test_stats_count_frames_and_failures() {
+    let url = binary_server(vec![
+        AudioChunk::encode_slot(2, 1, &[1]).unwrap(),
+        vec![200, 0, 0, 0, 0, 0, 0, 0, 0],
+        vec![4, 0, 0],
+        AudioChunk::encode(2, &[1]),
+    ])
+    .await;
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 0,
+        }))
+        .await
+        .unwrap();
+    // The router handles frames in order, so the last one settles the counts
+    let chunk = client
+        .recv_audio_chunk_timeout(Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(chunk.timestamp, 2);
+    assert_eq!(
+        client.stats(),
+        FrameStats {
+            audio: 2,
+            unknown_binary: 1,
+            parse_failures: 1,
+            unrouted_audio: 1,
+            ..FrameStats::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_event_bus_reaches_every_subscriber() {
+    let (url, _sent) = recording_server(vec![Role::Player(1)]).await;
+    let config = ClientConfig {
+        clock_sync_interval: None,
+        ..ClientConfig::default()
+    };
+    let client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+    let bus = client.event_bus();
+    let mut first = bus.subscribe();
+    let mut second = bus.subscribe();
+
+    for _ in 0..2 {
+        client
+            .send_message(&Message::ClientTime(ClientTime {
+                client_transmitted: sendspin::sync::unix_micros(),
+            }))
+            .await
+            .unwrap();
+    }
+    // Two replies of the same quality make one change
+    for events in [&mut first, &mut second] {
+        let event = timeout(Duration::from_secs(2), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(event, ClientEvent::SyncQualityChanged(_)),
+            "{:?}",
+            event
+        );
+    }
+    client.close(GoodbyeReason::UserRequest).await.unwrap();
+    for events in [&mut first, &mut second] {
+        let event = timeout(Duration::from_secs(2), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(
+                event,
+                ClientEvent::ConnectionChanged(ConnectionStatus::Disconnected)
+            ),
+            "{:?}",
+            event
+        );
+    }
+}
+
+async fn next_state(sent: &mut mpsc::UnboundedReceiver<Message>) -> PlayerState {
+    match timeout(Duration::from_secs(2), sent.recv()).await.unwrap() {
+        Some(Message::ClientState(state)) => state.player.unwrap(),
+        other => panic!("Expected client/state, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_state_reporter_sends_state_on_change() {
+    let (url, mut sent) = recording_server(vec![Role::Player(1)]).await;
+    let config = ClientConfig {
+        clock_sync_interval: None,
+        ..ClientConfig::default()
+    };
+    let client = ProtocolClient::connect_with_config(&url, hello(), config)
+        .await
+        .unwrap();
+    let reporter = StateReporter::new().with_interval(Duration::from_secs(60));
+    tokio::spawn(reporter.clone().run(client.sender()));
+
+    // Reported right away
+    let state = next_state(&mut sent).await;
+    assert_eq!(state.state, PlayerSyncState::Synchronized);
+    assert_eq!(state.volume, Some(100));
+
+    // Buffer fill alone waits for the next report
+    reporter.set_buffered(Duration::from_millis(250));
+    reporter.set_muted(true);
+    let state = next_state(&mut sent).await;
+    assert_eq!(state.muted, Some(true));
+    assert_eq!(state.buffered_ms, Some(250));
+
+    reporter.set_sync_state(PlayerSyncState::Error);
+    assert_eq!(next_state(&mut sent).await.state, PlayerSyncState::Error);
+}
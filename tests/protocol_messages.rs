@@ -1,8 +1,14 @@
+use sendspin::audio::decode::PcmEndian;
+use sendspin::audio::Codec;
 use sendspin::protocol::messages::{
     AudioFormatSpec, ClientCommand, ClientGoodbye, ClientHello, ClientState, ConnectionReason,
-    ControllerCommand, DeviceInfo, GoodbyeReason, Message, PlaybackState, PlayerState,
-    PlayerSyncState, PlayerV1Support, RepeatMode,
+    ContentType, ControllerCommand, ControllerCommandKind, ControllerV1Support, DeviceInfo,
+    FftWindow, GoodbyeReason, GroupMember, LyricLine, Message, PlaybackState, PlayerCommand,
+    PlayerCommandKind, PlayerFormatRequest, PlayerState, PlayerSyncState, PlayerV1Support,
+    RepeatMode, ServerGoodbyeReason, StreamPlayerConfig, StreamVisualizerConfig,
 };
+use sendspin::protocol::streams::StreamTracker;
+use sendspin::protocol::{Role, RoleList};
 
 // =============================================================================
 // Handshake Tests
@@ -10,29 +16,23 @@ use sendspin::protocol::messages::{
 
 #[test]
 fn test_client_hello_serialization() {
-    let hello = ClientHello {
-        client_id: "test-client-123".to_string(),
-        name: "Test Player".to_string(),
-        version: 1,
-        supported_roles: vec!["player@v1".to_string()],
-        device_info: Some(DeviceInfo {
+    let hello = ClientHello::new("test-client-123", "Test Player", vec![Role::Player(1)])
+        .with_device_info(DeviceInfo {
             product_name: Some("Sendspin-RS Player".to_string()),
             manufacturer: Some("Sendspin".to_string()),
             software_version: Some("0.1.0".to_string()),
-        }),
-        player_v1_support: Some(PlayerV1Support {
+        })
+        .with_player_support(PlayerV1Support {
             supported_formats: vec![AudioFormatSpec {
                 codec: "pcm".to_string(),
                 channels: 2,
                 sample_rate: 48000,
                 bit_depth: 24,
+                endian: None,
             }],
             buffer_capacity: 100,
             supported_commands: vec!["play".to_string(), "pause".to_string()],
-        }),
-        artwork_v1_support: None,
-        visualizer_v1_support: None,
-    };
+        });
 
     let message = Message::ClientHello(hello);
     let json = serde_json::to_string(&message).unwrap();
@@ -43,6 +43,66 @@ fn test_client_hello_serialization() {
     assert!(json.contains("\"player@v1\""));
 }
 
+#[test]
+fn test_client_hello_new_is_minimal() {
+    let hello = ClientHello::new("bare", "Bare", vec![Role::Player(1)]);
+    let value = serde_json::to_value(Message::ClientHello(hello)).unwrap();
+    let payload = value["payload"].as_object().unwrap();
+    let mut keys: Vec<&str> = payload.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["client_id", "name", "supported_roles", "version"]);
+    assert_eq!(payload["version"], 1);
+}
+
+#[test]
+fn test_client_hello_controller_support() {
+    let hello = ClientHello {
+        controller_v1_support: Some(ControllerV1Support {
+            supported_commands: vec![ControllerCommandKind::Play, ControllerCommandKind::Volume],
+            displays_metadata: true,
+        }),
+        ..ClientHello::new(
+            "remote-1",
+            "Remote",
+            vec![Role::Controller(1), Role::Metadata(1)],
+        )
+    };
+
+    let value = serde_json::to_value(Message::ClientHello(hello)).unwrap();
+    assert_eq!(
+        value["payload"]["controller@v1_support"],
+        serde_json::json!({
+            "supported_commands": ["play", "volume"],
+            "displays_metadata": true
+        })
+    );
+    assert!(value["payload"].get("player@v1_support").is_none());
+
+    // displays_metadata defaults to false when omitted
+    let json = r#"{
+        "type": "client/hello",
+        "payload": {
+            "client_id": "remote-2",
+            "name": "Remote",
+            "version": 1,
+            "supported_roles": ["controller@v1"],
+            "controller@v1_support": {"supported_commands": ["next", "fast_forward"]}
+        }
+    }"#;
+    match serde_json::from_str::<Message>(json).unwrap() {
+        Message::ClientHello(hello) => {
+            let support = hello.controller_v1_support.unwrap();
+            assert!(!support.displays_metadata);
+            assert_eq!(support.supported_commands[0], ControllerCommandKind::Next);
+            assert_eq!(
+                support.supported_commands[1],
+                ControllerCommandKind::Other("fast_forward".to_string())
+            );
+        }
+        other => panic!("Expected client/hello, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_server_hello_deserialization() {
     let json = r#"{
@@ -63,7 +123,7 @@ fn test_server_hello_deserialization() {
             assert_eq!(hello.server_id, "server-456");
             assert_eq!(hello.name, "Test Server");
             assert_eq!(hello.version, 1);
-            assert_eq!(hello.active_roles, vec!["player@v1"]);
+            assert_eq!(hello.active_roles, vec![Role::Player(1)]);
             assert_eq!(hello.connection_reason, ConnectionReason::Playback);
         }
         _ => panic!("Expected ServerHello"),
@@ -81,6 +141,7 @@ fn test_client_state_serialization() {
             state: PlayerSyncState::Synchronized,
             volume: Some(100),
             muted: Some(false),
+            buffered_ms: None,
         }),
     };
 
@@ -99,6 +160,7 @@ fn test_player_sync_state_error() {
             state: PlayerSyncState::Error,
             volume: None,
             muted: None,
+            buffered_ms: None,
         }),
     };
 
@@ -153,6 +215,67 @@ fn test_server_state_metadata_deserialization() {
     }
 }
 
+#[test]
+fn test_server_state_extended_metadata() {
+    let json = r#"{
+        "type": "server/state",
+        "payload": {
+            "metadata": {
+                "timestamp": 1,
+                "title": "Prelude",
+                "genre": "Classical",
+                "composer": "J.S. Bach",
+                "disc_number": 2,
+                "content_type": "music",
+                "source": "Library",
+                "lyrics": {
+                    "language": "en",
+                    "lines": [
+                        {"time": 0, "text": "First"},
+                        {"time": 5000000, "text": "Second"}
+                    ]
+                }
+            }
+        }
+    }"#;
+
+    let message: Message = serde_json::from_str(json).unwrap();
+    let Message::ServerState(state) = message else {
+        panic!("Expected ServerState");
+    };
+    let metadata = state.metadata.expect("Expected metadata");
+    assert_eq!(metadata.genre.as_deref(), Some("Classical"));
+    assert_eq!(metadata.composer.as_deref(), Some("J.S. Bach"));
+    assert_eq!(metadata.disc_number, Some(2));
+    assert_eq!(metadata.content_type, Some(ContentType::Music));
+    assert_eq!(metadata.source.as_deref(), Some("Library"));
+
+    let lyrics = metadata.lyrics.clone().expect("Expected lyrics");
+    assert_eq!(lyrics.language.as_deref(), Some("en"));
+    assert_eq!(lyrics.text, None);
+    assert_eq!(lyrics.line_at(-1), None);
+    assert_eq!(
+        lyrics.line_at(4_999_999).map(|l| l.text.as_str()),
+        Some("First")
+    );
+    assert_eq!(
+        lyrics.line_at(60_000_000),
+        Some(&LyricLine {
+            time: 5_000_000,
+            text: "Second".to_string(),
+        })
+    );
+
+    // Absent fields stay off the wire; unknown content types are tolerated
+    let json = serde_json::to_value(&metadata).unwrap();
+    assert!(json.get("year").is_none());
+    assert_eq!(json["lyrics"]["lines"][1]["time"], 5_000_000);
+    let radio: ContentType = serde_json::from_str(r#""radio""#).unwrap();
+    assert_eq!(radio, ContentType::Radio);
+    let other: ContentType = serde_json::from_str(r#""audiodrama""#).unwrap();
+    assert_eq!(other, ContentType::Other);
+}
+
 #[test]
 fn test_server_state_controller_deserialization() {
     let json = r#"{
@@ -174,7 +297,9 @@ fn test_server_state_controller_deserialization() {
             assert_eq!(controller.volume, 75);
             assert!(!controller.muted);
             assert!(controller.supported_commands.contains(&"play".to_string()));
-            assert!(controller.supported_commands.contains(&"volume".to_string()));
+            assert!(controller
+                .supported_commands
+                .contains(&"volume".to_string()));
         }
         _ => panic!("Expected ServerState"),
     }
@@ -187,11 +312,7 @@ fn test_server_state_controller_deserialization() {
 #[test]
 fn test_client_command_serialization() {
     let command = ClientCommand {
-        controller: Some(ControllerCommand {
-            command: "play".to_string(),
-            volume: None,
-            mute: None,
-        }),
+        controller: Some(ControllerCommand::new(ControllerCommandKind::Play)),
     };
 
     let message = Message::ClientCommand(command);
@@ -226,14 +347,59 @@ fn test_server_command_deserialization() {
     }
 }
 
+#[test]
+fn test_player_command_kind_round_trip() {
+    for (name, kind) in [
+        ("play", PlayerCommandKind::Play),
+        ("pause", PlayerCommandKind::Pause),
+        ("stop", PlayerCommandKind::Stop),
+        ("volume", PlayerCommandKind::Volume),
+        ("mute", PlayerCommandKind::Mute),
+        ("seek", PlayerCommandKind::Seek),
+        ("shuffle", PlayerCommandKind::Other("shuffle".to_string())),
+    ] {
+        let json = format!(
+            r#"{{"type":"server/command","payload":{{"player":{{"command":"{}"}}}}}}"#,
+            name
+        );
+        let message: Message = serde_json::from_str(&json).unwrap();
+        let Message::ServerCommand(cmd) = &message else {
+            panic!("Expected ServerCommand");
+        };
+        let player = cmd.player.as_ref().unwrap();
+        assert_eq!(player.command, kind);
+        assert_eq!(player.command.as_str(), name);
+
+        // Unknown commands serialize back unchanged
+        let reserialized = serde_json::to_string(&message).unwrap();
+        assert!(reserialized.contains(&format!("\"command\":\"{}\"", name)));
+    }
+}
+
+#[test]
+fn test_player_command_seek() {
+    let json =
+        r#"{"type":"server/command","payload":{"player":{"command":"seek","offset":-10000000}}}"#;
+    let message: Message = serde_json::from_str(json).unwrap();
+    let Message::ServerCommand(cmd) = &message else {
+        panic!("Expected ServerCommand");
+    };
+    let player = cmd.player.as_ref().unwrap();
+    assert_eq!(player.command, PlayerCommandKind::Seek);
+    assert_eq!(player.offset, Some(-10_000_000));
+    assert_eq!(player.position, None);
+
+    let seek = serde_json::to_value(PlayerCommand::seek(83_000_000)).unwrap();
+    assert_eq!(
+        seek,
+        serde_json::json!({"command": "seek", "position": 83_000_000})
+    );
+}
+
 #[test]
 fn test_client_command_volume() {
     let command = ClientCommand {
-        controller: Some(ControllerCommand {
-            command: "volume".to_string(),
-            volume: Some(50),
-            mute: None,
-        }),
+        controller: Some(ControllerCommand::volume(50)),
     };
 
     let message = Message::ClientCommand(command);
@@ -242,6 +408,42 @@ fn test_client_command_volume() {
     assert!(json.contains("\"volume\":50"));
 }
 
+#[test]
+fn test_client_command_seek() {
+    let message = Message::ClientCommand(ClientCommand {
+        controller: Some(ControllerCommand::seek(90_000_000)),
+    });
+    let json = serde_json::to_string(&message).unwrap();
+    assert!(json.contains("\"command\":\"seek\""));
+    assert!(json.contains("\"position\":90000000"));
+    assert!(!json.contains("volume"));
+
+    let parsed: Message = serde_json::from_str(&json).unwrap();
+    let Message::ClientCommand(cmd) = parsed else {
+        panic!("Expected ClientCommand");
+    };
+    let controller = cmd.controller.unwrap();
+    assert_eq!(controller.command, ControllerCommandKind::Seek);
+    assert_eq!(controller.position, Some(90_000_000));
+}
+
+#[test]
+fn test_controller_command_kinds() {
+    let json = r#"{"type":"client/command","payload":{"controller":{"command":"repeat_one"}}}"#;
+    let Message::ClientCommand(cmd) = serde_json::from_str(json).unwrap() else {
+        panic!("Expected ClientCommand");
+    };
+    let controller = cmd.controller.unwrap();
+    assert_eq!(controller.command, ControllerCommandKind::RepeatOne);
+    assert!(controller.position.is_none());
+
+    assert_eq!(
+        ControllerCommandKind::from("crossfade"),
+        ControllerCommandKind::Other("crossfade".to_string())
+    );
+    assert_eq!(String::from(ControllerCommandKind::Previous), "previous");
+}
+
 // =============================================================================
 // Stream Control Tests
 // =============================================================================
@@ -276,6 +478,140 @@ fn test_stream_start_deserialization() {
     }
 }
 
+#[test]
+fn test_stream_start_with_empty_visualizer_starts_player() {
+    let json = r#"{
+        "type": "stream/start",
+        "payload": {
+            "player": {"codec": "pcm", "sample_rate": 48000, "channels": 2, "bit_depth": 16},
+            "visualizer": {}
+        }
+    }"#;
+
+    let message: Message = serde_json::from_str(json).unwrap();
+    let streams = StreamTracker::new();
+    streams.apply(&message);
+    let current = streams.current();
+    let player = current.player.expect("player stream should start");
+    assert_eq!(player.audio_format().unwrap().sample_rate, 48000);
+    let visualizer = current.visualizer.expect("visualizer block is kept");
+    assert_eq!(visualizer.bins, 0);
+    assert_eq!(visualizer.frame_interval(), None);
+}
+
+#[test]
+fn test_stream_start_visualizer_config() {
+    let json = r#"{
+        "type": "stream/start",
+        "payload": {
+            "visualizer": {
+                "bins": 64,
+                "rate_hz": 30,
+                "window": "blackman",
+                "min_frequency_hz": 20.0,
+                "max_frequency_hz": 20000.0
+            }
+        }
+    }"#;
+
+    let message: Message = serde_json::from_str(json).unwrap();
+    let Message::StreamStart(start) = message else {
+        panic!("Expected StreamStart");
+    };
+    let visualizer = start.visualizer.expect("Expected visualizer config");
+    assert_eq!(visualizer.bins, 64);
+    assert_eq!(visualizer.rate_hz, 30);
+    assert_eq!(visualizer.window, FftWindow::Blackman);
+    assert_eq!(visualizer.min_frequency_hz, 20.0);
+    assert_eq!(visualizer.max_frequency_hz, 20000.0);
+    assert_eq!(
+        visualizer.frame_interval(),
+        Some(std::time::Duration::from_secs_f64(1.0 / 30.0))
+    );
+
+    // Window defaults to Hann; unknown windows still parse
+    let json = r#"{"bins":8,"rate_hz":10,"min_frequency_hz":0,"max_frequency_hz":24000}"#;
+    let config: StreamVisualizerConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(config.window, FftWindow::Hann);
+    let json = r#"{"bins":8,"rate_hz":10,"window":"kaiser","min_frequency_hz":0,"max_frequency_hz":24000}"#;
+    let config: StreamVisualizerConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(config.window, FftWindow::Other);
+}
+
+#[test]
+fn test_stream_start_codec_header() {
+    let json = r#"{
+        "codec": "opus",
+        "sample_rate": 48000,
+        "channels": 2,
+        "bit_depth": 16,
+        "codec_header": "T3B1c0hlYWQBAjgBgLsAAAAAAA=="
+    }"#;
+    let config: StreamPlayerConfig = serde_json::from_str(json).unwrap();
+    let format = config.audio_format().unwrap();
+    assert_eq!(format.codec, Codec::Opus);
+    let header = format.codec_header.unwrap();
+    assert_eq!(&header[..8], b"OpusHead");
+    assert_eq!(header.len(), 19);
+
+    // Encoding for a server round-trips, including lengths that need padding
+    for len in 0..5 {
+        let bytes: Vec<u8> = (0..len).map(|i| 0xF0 | i).collect();
+        let config = StreamPlayerConfig {
+            codec_header: None,
+            ..config.clone()
+        }
+        .with_codec_header(&bytes);
+        assert_eq!(config.codec_header_bytes().unwrap(), Some(bytes));
+    }
+
+    let bad = StreamPlayerConfig {
+        codec_header: Some("not base64!".to_string()),
+        ..config.clone()
+    };
+    assert!(bad.audio_format().is_err());
+    let unknown = StreamPlayerConfig {
+        codec: "wma".to_string(),
+        codec_header: None,
+        ..config
+    };
+    assert!(unknown.audio_format().is_err());
+}
+
+#[test]
+fn test_stream_start_endian_hint() {
+    let json = r#"{"codec":"pcm","sample_rate":48000,"channels":2,"bit_depth":24,"endian":"big"}"#;
+    let config: StreamPlayerConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(config.audio_format().unwrap().endian, Some(PcmEndian::Big));
+    assert!(serde_json::to_string(&config)
+        .unwrap()
+        .contains(r#""endian":"big""#));
+
+    // Without the hint the player detects the byte order
+    let json = r#"{"codec":"pcm","sample_rate":48000,"channels":2,"bit_depth":24}"#;
+    let config: StreamPlayerConfig = serde_json::from_str(json).unwrap();
+    assert_eq!(config.audio_format().unwrap().endian, None);
+    assert!(!serde_json::to_string(&config).unwrap().contains("endian"));
+}
+
+#[test]
+fn test_format_negotiation_endian() {
+    let json = r#"{"codec":"pcm","channels":2,"sample_rate":48000,"bit_depth":24,"endian":"big"}"#;
+    let spec: AudioFormatSpec = serde_json::from_str(json).unwrap();
+    assert_eq!(spec.endian, Some(PcmEndian::Big));
+    let json = r#"{"codec":"pcm","channels":2,"sample_rate":48000,"bit_depth":24}"#;
+    let spec: AudioFormatSpec = serde_json::from_str(json).unwrap();
+    assert_eq!(spec.endian, None);
+    assert!(!serde_json::to_string(&spec).unwrap().contains("endian"));
+
+    let request: PlayerFormatRequest =
+        serde_json::from_str(r#"{"codec":"pcm","endian":"little"}"#).unwrap();
+    assert_eq!(request.endian, Some(PcmEndian::Little));
+    assert!(serde_json::to_string(&request)
+        .unwrap()
+        .contains(r#""endian":"little""#));
+}
+
 #[test]
 fn test_stream_end_deserialization() {
     let json = r#"{
@@ -289,7 +625,7 @@ fn test_stream_end_deserialization() {
 
     match message {
         Message::StreamEnd(end) => {
-            assert_eq!(end.roles, Some(vec!["player@v1".to_string()]));
+            assert_eq!(end.roles, Some(vec![Role::Player(1)]));
         }
         _ => panic!("Expected StreamEnd"),
     }
@@ -334,11 +670,59 @@ fn test_group_update_deserialization() {
             assert_eq!(update.playback_state, Some(PlaybackState::Playing));
             assert_eq!(update.group_id, Some("living-room".to_string()));
             assert_eq!(update.group_name, Some("Living Room".to_string()));
+            assert!(update.members.is_none());
         }
         _ => panic!("Expected GroupUpdate"),
     }
 }
 
+#[test]
+fn test_group_update_members() {
+    let json = r#"{
+        "type": "group/update",
+        "payload": {
+            "group_id": "living-room",
+            "x_members": [
+                {"client_id": "sofa", "name": "Sofa", "volume": 40, "muted": false},
+                {"client_id": "tv", "name": "TV"}
+            ]
+        }
+    }"#;
+
+    let Message::GroupUpdate(update) = serde_json::from_str(json).unwrap() else {
+        panic!("Expected GroupUpdate");
+    };
+    let members = update.members.clone().unwrap();
+    assert_eq!(
+        members[0],
+        GroupMember {
+            client_id: "sofa".to_string(),
+            name: "Sofa".to_string(),
+            volume: Some(40),
+            muted: Some(false),
+        }
+    );
+    assert_eq!(members[1].volume, None);
+
+    let value = serde_json::to_value(Message::GroupUpdate(update)).unwrap();
+    assert_eq!(
+        value["payload"]["x_members"][1],
+        serde_json::json!({"client_id": "tv", "name": "TV"})
+    );
+}
+
+#[test]
+fn test_client_command_join_and_leave_group() {
+    let join = serde_json::to_value(ControllerCommand::join_group("kitchen")).unwrap();
+    assert_eq!(
+        join,
+        serde_json::json!({"command": "x_join_group", "x_group_id": "kitchen"})
+    );
+    let leave: ControllerCommand = serde_json::from_str(r#"{"command":"x_leave_group"}"#).unwrap();
+    assert_eq!(leave.command, ControllerCommandKind::LeaveGroup);
+    assert_eq!(leave.group_id, None);
+}
+
 #[test]
 fn test_playback_state_variants() {
     // Test all playback state variants
@@ -362,6 +746,7 @@ fn test_playback_state_variants() {
 fn test_client_goodbye_serialization() {
     let goodbye = ClientGoodbye {
         reason: GoodbyeReason::AnotherServer,
+        telemetry: None,
     };
 
     let message = Message::ClientGoodbye(goodbye);
@@ -369,6 +754,25 @@ fn test_client_goodbye_serialization() {
 
     assert!(json.contains("\"type\":\"client/goodbye\""));
     assert!(json.contains("\"reason\":\"another_server\""));
+    assert!(!json.contains("x_telemetry"));
+}
+
+#[test]
+fn test_server_goodbye_deserialization() {
+    let json = r#"{"type":"server/goodbye","payload":{"reason":"restart"}}"#;
+    let message: Message = serde_json::from_str(json).unwrap();
+    assert_eq!(message.message_type(), "server/goodbye");
+    match message {
+        Message::ServerGoodbye(goodbye) => assert_eq!(goodbye.reason, ServerGoodbyeReason::Restart),
+        _ => panic!("Expected ServerGoodbye"),
+    }
+
+    // Unknown reasons still parse
+    let json = r#"{"type":"server/goodbye","payload":{"reason":"meteor_strike"}}"#;
+    let Message::ServerGoodbye(goodbye) = serde_json::from_str(json).unwrap() else {
+        panic!("Expected ServerGoodbye");
+    };
+    assert_eq!(goodbye.reason, ServerGoodbyeReason::Other);
 }
 
 #[test]
@@ -403,3 +807,50 @@ fn test_repeat_mode_variants() {
         assert_eq!(parsed, expected);
     }
 }
+
+// =============================================================================
+// Role Tests
+// =============================================================================
+
+#[test]
+fn test_role_parse_and_display() {
+    let cases = [
+        ("player@v1", Role::Player(1)),
+        ("artwork@v1", Role::Artwork(1)),
+        ("controller@v2", Role::Controller(2)),
+        ("metadata@v1", Role::Metadata(1)),
+        ("visualizer@v1", Role::Visualizer(1)),
+    ];
+    for (name, role) in cases {
+        assert_eq!(name.parse::<Role>().unwrap(), role);
+        assert_eq!(role.to_string(), name);
+    }
+
+    // Unknown names and malformed versions are kept verbatim
+    for name in ["jukebox@v1", "player", "player@vx"] {
+        let role: Role = name.parse().unwrap();
+        assert_eq!(role, Role::Unknown(name.to_string()));
+        assert_eq!(role.to_string(), name);
+    }
+    assert_eq!(Role::from("player").name(), "player");
+}
+
+#[test]
+fn test_role_serialization_round_trip() {
+    let roles = vec![Role::Player(1), Role::Unknown("jukebox@v1".to_string())];
+    let json = serde_json::to_string(&roles).unwrap();
+    assert_eq!(json, r#"["player@v1","jukebox@v1"]"#);
+    let parsed: Vec<Role> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, roles);
+}
+
+#[test]
+fn test_role_list_helpers() {
+    let active = [Role::Player(1), Role::Controller(2)];
+    assert!(active.contains_role(Role::Player(1)));
+    assert!(!active.contains_role(Role::Player(2)));
+    assert!(!active.contains_role(Role::Artwork(1)));
+    assert_eq!(active.version_of(&Role::Controller(1)), Some(2));
+    assert_eq!(active.version_of(&Role::Metadata(1)), None);
+    assert!(Role::Player(1).same_family(&Role::Player(3)));
+}
@@ -0,0 +1,117 @@
+// ABOUTME: Tests for protocol version negotiation
+// ABOUTME: Covers downgrades, incompatible versions on both ends, and feature gating
+
+mod common;
+
+use common::{idle, mock_server_with_hello};
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{ClientHello, ServerHello};
+use sendspin::protocol::version::{negotiate, MIN_PROTOCOL_VERSION};
+use sendspin::protocol::{Feature, Role, PROTOCOL_VERSION};
+use sendspin::server::{Server, ServerConfig};
+use sendspin::Error;
+
+fn hello(version: u32) -> ClientHello {
+    ClientHello {
+        version,
+        ..ClientHello::new("versioned", "Versioned", vec![Role::Player(1)])
+    }
+}
+
+/// Server that answers client/hello with the given version and then idles
+async fn versioned_server(version: u32) -> String {
+    let hello = ServerHello {
+        version,
+        ..ServerHello::new("mock", "Mock", vec![Role::Player(1)])
+    };
+    mock_server_with_hello(hello, idle).await
+}
+
+#[test]
+fn test_negotiate_picks_lower_version() {
+    assert_eq!(
+        negotiate(PROTOCOL_VERSION, PROTOCOL_VERSION).unwrap(),
+        PROTOCOL_VERSION
+    );
+    assert_eq!(
+        negotiate(PROTOCOL_VERSION + 1, PROTOCOL_VERSION).unwrap(),
+        PROTOCOL_VERSION
+    );
+    assert_eq!(
+        negotiate(PROTOCOL_VERSION, PROTOCOL_VERSION + 3).unwrap(),
+        PROTOCOL_VERSION
+    );
+
+    match negotiate(PROTOCOL_VERSION, MIN_PROTOCOL_VERSION - 1) {
+        Err(Error::UnsupportedVersion { version, min, max }) => {
+            assert_eq!(version, MIN_PROTOCOL_VERSION - 1);
+            assert_eq!((min, max), (MIN_PROTOCOL_VERSION, PROTOCOL_VERSION));
+        }
+        other => panic!("Expected unsupported version, got {:?}", other),
+    }
+    // Both ends newer than this crate
+    assert!(negotiate(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 1).is_err());
+}
+
+#[test]
+fn test_feature_gating() {
+    for feature in [
+        Feature::MessageEncodings,
+        Feature::PayloadEncryption,
+        Feature::GroupMembership,
+    ] {
+        assert!(feature.is_supported(PROTOCOL_VERSION));
+        assert!(feature.require(feature.min_version()).is_ok());
+        let err = feature.require(0).unwrap_err();
+        assert!(err.to_string().contains(feature.name()), "{}", err);
+    }
+}
+
+#[tokio::test]
+async fn test_newer_client_is_downgraded() {
+    let server = Server::bind("127.0.0.1:0", ServerConfig::default())
+        .await
+        .unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+
+    let client = ProtocolClient::connect(&url, hello(PROTOCOL_VERSION + 1))
+        .await
+        .unwrap();
+    assert_eq!(client.server_hello().version, PROTOCOL_VERSION);
+    assert_eq!(client.protocol_version(), PROTOCOL_VERSION);
+    assert!(client.supports(Feature::GroupMembership));
+}
+
+#[tokio::test]
+async fn test_server_rejects_unsupported_client() {
+    let server = Server::bind("127.0.0.1:0", ServerConfig::default())
+        .await
+        .unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+
+    match ProtocolClient::connect(&url, hello(MIN_PROTOCOL_VERSION - 1)).await {
+        Err(Error::Connection(reason)) => {
+            assert!(
+                reason.contains("Unsupported protocol version"),
+                "{}",
+                reason
+            )
+        }
+        Err(other) => panic!("Expected connection error, got {}", other),
+        Ok(_) => panic!("Expected the client to be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_client_rejects_unsupported_server() {
+    let url = versioned_server(MIN_PROTOCOL_VERSION - 1).await;
+    let result = ProtocolClient::connect(&url, hello(PROTOCOL_VERSION)).await;
+    assert!(matches!(result, Err(Error::UnsupportedVersion { .. })));
+
+    // A server newer than this crate is fine as long as the client offered our version
+    let url = versioned_server(PROTOCOL_VERSION + 1).await;
+    let client = ProtocolClient::connect(&url, hello(PROTOCOL_VERSION))
+        .await
+        .unwrap();
+    assert_eq!(client.protocol_version(), PROTOCOL_VERSION);
+}
@@ -0,0 +1,186 @@
+// ABOUTME: Tests for connecting through HTTP CONNECT and SOCKS5 proxies
+// ABOUTME: Mock proxies tunnel to a mock server and record what the client asked for
+
+mod common;
+
+use common::{idle, mock_server_with_hello};
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{ClientHello, ServerHello};
+use sendspin::protocol::{ProxyConfig, Role};
+use sendspin::Error;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+fn hello() -> ClientHello {
+    ClientHello::new("proxy-test", "Proxy Test", vec![Role::Player(1)])
+}
+
+/// Mock server that completes the hello exchange; returns its `host:port`
+async fn server() -> String {
+    let hello = ServerHello::new("mock", "Behind Proxy", vec![Role::Player(1)]);
+    let url = mock_server_with_hello(hello, idle).await;
+    url.trim_start_matches("ws://")
+        .trim_end_matches("/sendspin")
+        .to_string()
+}
+
+/// HTTP proxy that reports the request head and tunnels to the requested target
+async fn http_proxy(status: &'static str) -> (String, oneshot::Receiver<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut client = BufReader::new(stream);
+        let mut head = Vec::new();
+        loop {
+            let mut line = String::new();
+            client.read_line(&mut line).await.unwrap();
+            let line = line.trim_end().to_string();
+            if line.is_empty() {
+                break;
+            }
+            head.push(line);
+        }
+        let target = head[0].split_whitespace().nth(1).unwrap().to_string();
+        let _ = tx.send(head);
+        let mut client = client.into_inner();
+        client
+            .write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes())
+            .await
+            .unwrap();
+        if status.starts_with('2') {
+            let mut upstream = TcpStream::connect(target).await.unwrap();
+            let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+        }
+    });
+    (addr, rx)
+}
+
+/// SOCKS5 proxy requiring `user`/`pass`; reports the requested target and tunnels to it
+async fn socks5_proxy() -> (String, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut greeting = [0u8; 2];
+        client.read_exact(&mut greeting).await.unwrap();
+        let mut methods = vec![0u8; greeting[1] as usize];
+        client.read_exact(&mut methods).await.unwrap();
+        assert!(methods.contains(&2));
+        client.write_all(&[5, 2]).await.unwrap();
+
+        let mut version = [0u8; 2];
+        client.read_exact(&mut version).await.unwrap();
+        let mut user = vec![0u8; version[1] as usize];
+        client.read_exact(&mut user).await.unwrap();
+        let mut pass = vec![0u8; client.read_u8().await.unwrap() as usize];
+        client.read_exact(&mut pass).await.unwrap();
+        let ok = user == b"user" && pass == b"pass";
+        client
+            .write_all(&[1, if ok { 0 } else { 1 }])
+            .await
+            .unwrap();
+        if !ok {
+            return;
+        }
+
+        let mut request = [0u8; 4];
+        client.read_exact(&mut request).await.unwrap();
+        assert_eq!(request[..3], [5, 1, 0]);
+        let host = match request[3] {
+            1 => {
+                let mut ip = [0u8; 4];
+                client.read_exact(&mut ip).await.unwrap();
+                std::net::Ipv4Addr::from(ip).to_string()
+            }
+            3 => {
+                let mut name = vec![0u8; client.read_u8().await.unwrap() as usize];
+                client.read_exact(&mut name).await.unwrap();
+                String::from_utf8(name).unwrap()
+            }
+            other => panic!("Unexpected address type {}", other),
+        };
+        let port = client.read_u16().await.unwrap();
+        let target = format!("{}:{}", host, port);
+        let _ = tx.send(target.clone());
+
+        let mut upstream = TcpStream::connect(target).await.unwrap();
+        client
+            .write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0, 0])
+            .await
+            .unwrap();
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+    });
+    (addr, rx)
+}
+
+#[tokio::test]
+async fn test_connect_through_http_proxy() {
+    let target = server().await;
+    let (proxy, head) = http_proxy("200 Connection established").await;
+    let client = ProtocolClient::builder(format!("ws://{}/sendspin", target))
+        .hello(hello())
+        .proxy(ProxyConfig::http(proxy).with_credentials("user", "pass"))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.server_hello().name, "Behind Proxy");
+
+    let head = head.await.unwrap();
+    assert_eq!(head[0], format!("CONNECT {} HTTP/1.1", target));
+    // base64("user:pass")
+    assert!(head.contains(&"Proxy-Authorization: Basic dXNlcjpwYXNz".to_string()));
+}
+
+#[tokio::test]
+async fn test_http_proxy_refusal_is_a_connection_error() {
+    let target = server().await;
+    let (proxy, _head) = http_proxy("407 Proxy Authentication Required").await;
+    let result = ProtocolClient::builder(format!("ws://{}/sendspin", target))
+        .hello(hello())
+        .proxy(ProxyConfig::http(proxy))
+        .connect()
+        .await;
+    match result {
+        Err(Error::Connection(reason)) => assert!(reason.contains("407"), "{}", reason),
+        Err(other) => panic!("Expected connection error, got {}", other),
+        Ok(_) => panic!("Expected the proxy to refuse"),
+    }
+}
+
+#[tokio::test]
+async fn test_connect_through_socks5_proxy() {
+    let target = server().await;
+    let (proxy, requested) = socks5_proxy().await;
+    let client = ProtocolClient::builder(format!("ws://{}/sendspin", target))
+        .hello(hello())
+        .proxy(ProxyConfig::socks5(proxy).with_credentials("user", "pass"))
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.server_hello().name, "Behind Proxy");
+    assert_eq!(requested.await.unwrap(), target);
+}
+
+#[tokio::test]
+async fn test_socks5_bad_credentials() {
+    let target = server().await;
+    let (proxy, _requested) = socks5_proxy().await;
+    let result = ProtocolClient::builder(format!("ws://{}/sendspin", target))
+        .hello(hello())
+        .proxy(ProxyConfig::socks5(proxy).with_credentials("user", "wrong"))
+        .connect()
+        .await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+}
+
+#[test]
+fn test_debug_hides_proxy_password() {
+    let proxy = ProxyConfig::socks5("jump:1080").with_credentials("user", "hunter2");
+    let debug = format!("{:?}", proxy);
+    assert!(debug.contains("user"));
+    assert!(!debug.contains("hunter2"));
+}
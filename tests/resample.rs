@@ -0,0 +1,127 @@
+// ABOUTME: Tests for the streaming resampler of the player
+// ABOUTME: Linear conversion always; rubato qualities with the "resample" feature
+
+use sendspin::audio::{ResampleQuality, Resampler, Sample};
+
+/// A stereo ramp, rising on the left and falling on the right
+fn ramp(frames: i32) -> Vec<Sample> {
+    (0..frames)
+        .flat_map(|i| [Sample(i * 100), Sample(-i * 100)])
+        .collect()
+}
+
+#[test]
+fn test_quality_names() {
+    assert_eq!(
+        ResampleQuality::from_name("linear"),
+        Some(ResampleQuality::Linear)
+    );
+    assert_eq!(
+        ResampleQuality::from_name("best"),
+        Some(ResampleQuality::Best)
+    );
+    assert_eq!(ResampleQuality::from_name("perfect"), None);
+    assert!(Resampler::new(0, 48_000, 2, ResampleQuality::Linear).is_err());
+    assert!(Resampler::new(44_100, 48_000, 0, ResampleQuality::Linear).is_err());
+}
+
+#[test]
+fn test_linear_chunks_join_seamlessly() {
+    let input = ramp(4410);
+    let mut whole = Resampler::new(44_100, 48_000, 2, ResampleQuality::Linear).unwrap();
+    let expected = whole.process(&input).unwrap();
+
+    let mut chunked = Resampler::new(44_100, 48_000, 2, ResampleQuality::Linear).unwrap();
+    let mut out = Vec::new();
+    for chunk in input.chunks(2 * 441) {
+        out.extend(chunked.process(chunk).unwrap());
+    }
+    assert_eq!(out, expected);
+    // 100ms in; the last frame out waits for input after the last frame in
+    assert_eq!(out.len() / 2, 4799);
+    // The ramp stays a ramp across chunk boundaries
+    for pair in out.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+        assert!(pair[1][0].0 > pair[0][0].0);
+        assert!(pair[1][1].0 < pair[0][1].0);
+    }
+}
+
+#[test]
+fn test_linear_offset() {
+    // Halving the rate keeps every other frame
+    let mut resampler = Resampler::new(48_000, 24_000, 1, ResampleQuality::Linear).unwrap();
+    assert_eq!(resampler.offset_micros(), 0);
+    let input: Vec<Sample> = (0..8).map(|i| Sample(i * 100)).collect();
+    let out = resampler.process(&input).unwrap();
+    assert_eq!(out, vec![Sample(0), Sample(200), Sample(400), Sample(600)]);
+    // Frame 8 comes next, and is the first frame of the next chunk
+    assert_eq!(resampler.offset_micros(), 0);
+    assert!(resampler.process(&[Sample(800)]).unwrap().is_empty());
+    // Frame 8 now waits for frame 9, and begins one frame before it
+    assert_eq!(resampler.offset_micros(), -1_000_000 / 48_000);
+
+    // Doubling it interpolates halfway, one frame behind
+    let mut resampler = Resampler::new(24_000, 48_000, 1, ResampleQuality::Linear).unwrap();
+    let out = resampler.process(&[Sample(0), Sample(100)]).unwrap();
+    assert_eq!(out, vec![Sample(0), Sample(50)]);
+    assert_eq!(resampler.offset_micros(), -1_000_000 / 24_000);
+}
+
+#[cfg(not(feature = "resample"))]
+#[test]
+fn test_sinc_needs_feature() {
+    assert!(Resampler::new(44_100, 48_000, 2, ResampleQuality::Balanced).is_err());
+}
+
+#[cfg(feature = "resample")]
+mod rubato {
+    use super::*;
+
+    /// Half a second of a 1kHz stereo sine at 44.1kHz
+    fn sine() -> Vec<Sample> {
+        (0..22_050)
+            .map(|i| (i as f64 * 2.0 * std::f64::consts::PI * 1000.0 / 44_100.0).sin())
+            .flat_map(|s| [Sample::from_f32(s as f32 * 0.5); 2])
+            .collect()
+    }
+
+    #[test]
+    fn test_sinc_and_polynomial_keep_pitch_and_level() {
+        for quality in [
+            ResampleQuality::Fast,
+            ResampleQuality::Balanced,
+            ResampleQuality::Best,
+        ] {
+            let mut resampler = Resampler::new(44_100, 48_000, 2, quality).unwrap();
+            let mut out = Vec::new();
+            for chunk in sine().chunks(2 * 441) {
+                out.extend(resampler.process(chunk).unwrap());
+                // At most a block and the filter delay held back
+                assert!((-10_000..0).contains(&resampler.offset_micros()));
+            }
+            let left: Vec<i32> = out.chunks_exact(2).map(|f| f[0].0).collect();
+            // What is missing is what the offset accounts for
+            let missing = 24_000 - left.len() as i64;
+            let offset_frames = -resampler.offset_micros() * 48_000 / 1_000_000;
+            assert!(
+                (missing - offset_frames).abs() <= 1,
+                "{:?}: {} frames short, offset {} frames",
+                quality,
+                missing,
+                offset_frames
+            );
+
+            // Skip the filter ramp-up, then check level and zero crossings
+            let steady = &left[2_400..];
+            let peak = steady.iter().map(|s| s.abs()).max().unwrap();
+            let half_scale = Sample::MAX.0 / 2;
+            assert!((peak - half_scale).abs() < half_scale / 50, "{:?}", quality);
+            let crossings = steady
+                .windows(2)
+                .filter(|w| (w[0] < 0) != (w[1] < 0))
+                .count();
+            let expected = 2 * 1000 * steady.len() / 48_000;
+            assert!(crossings.abs_diff(expected) <= 2, "{:?}", quality);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use sendspin::audio::{AudioBuffer, AudioFormat, Codec, Sample};
-use sendspin::scheduler::AudioScheduler;
-use std::sync::Arc;
+use sendspin::scheduler::{
+    AudioScheduler, LatencyMonitor, LatencyProfile, Scheduler, SchedulerStats,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 #[test]
@@ -13,13 +16,7 @@ fn test_scheduler_creation() {
 fn test_scheduler_schedule_and_ready() {
     let scheduler = AudioScheduler::new();
 
-    let format = AudioFormat {
-        codec: Codec::Pcm,
-        sample_rate: 48000,
-        channels: 2,
-        bit_depth: 24,
-        codec_header: None,
-    };
+    let format = AudioFormat::new(Codec::Pcm, 48000, 2, 24);
 
     let samples = vec![Sample::ZERO; 960];
     let buffer = AudioBuffer {
@@ -40,3 +37,197 @@ fn test_scheduler_schedule_and_ready() {
     let ready = scheduler.next_ready();
     assert!(ready.is_some());
 }
+
+fn buffer_at(timestamp: i64, play_at: Instant) -> AudioBuffer {
+    AudioBuffer {
+        timestamp,
+        play_at,
+        samples: Arc::from(vec![Sample::ZERO; 480].into_boxed_slice()),
+        format: AudioFormat::new(Codec::Pcm, 48000, 2, 16),
+    }
+}
+
+#[test]
+fn test_scheduler_drops_late_buffers() {
+    let profile = LatencyProfile::low_latency();
+    let scheduler = AudioScheduler::new().with_max_late(profile.max_late.unwrap());
+    let now = Instant::now();
+
+    scheduler.schedule(buffer_at(0, now - Duration::from_millis(50)));
+    scheduler.schedule(buffer_at(10_000, now - Duration::from_millis(40)));
+    scheduler.schedule(buffer_at(20_000, now));
+
+    assert_eq!(scheduler.next_ready().unwrap().timestamp, 20_000);
+    assert_eq!(scheduler.dropped(), 2);
+
+    // Without a limit, late buffers still play
+    let scheduler = AudioScheduler::new();
+    scheduler.schedule(buffer_at(0, now - Duration::from_millis(50)));
+    assert_eq!(scheduler.next_ready().unwrap().timestamp, 0);
+    assert_eq!(scheduler.dropped(), 0);
+}
+
+#[test]
+fn test_scheduler_next_ready_at_virtual_clock() {
+    let scheduler = AudioScheduler::new().with_max_late(Duration::from_millis(10));
+    let start = Instant::now();
+    scheduler.schedule(buffer_at(0, start + Duration::from_millis(5)));
+    scheduler.schedule(buffer_at(10_000, start + Duration::from_millis(20)));
+
+    assert!(scheduler.next_ready_at(start).is_none());
+    assert_eq!(
+        scheduler
+            .next_ready_at(start + Duration::from_millis(4))
+            .unwrap()
+            .timestamp,
+        0
+    );
+    assert!(scheduler
+        .next_ready_at(start + Duration::from_millis(31))
+        .is_none());
+    assert_eq!(scheduler.dropped(), 1);
+}
+
+#[test]
+fn test_latency_profiles() {
+    let tv = LatencyProfile::from_name("tv").unwrap();
+    assert_eq!(tv, LatencyProfile::low_latency());
+    assert!(tv.target_latency <= Duration::from_millis(80));
+    // Sender lead plus one late chunk and a typical output buffer fit the target
+    assert!(
+        tv.pipeline_latency(tv.max_late.unwrap(), Duration::from_millis(20)) <= tv.target_latency
+    );
+    assert_eq!(
+        LatencyProfile::from_name("standard"),
+        Some(LatencyProfile::default())
+    );
+    assert!(LatencyProfile::from_name("fast").is_none());
+}
+
+#[test]
+fn test_latency_monitor_report() {
+    let mut monitor = LatencyMonitor::new(LatencyProfile::low_latency());
+    assert!(!monitor.report().meets_target());
+
+    monitor.record(Duration::from_millis(2), Duration::from_millis(10));
+    monitor.record(Duration::from_millis(30), Duration::from_millis(10));
+    monitor.record_dropped(3);
+
+    let report = monitor.report();
+    assert_eq!(report.samples, 2);
+    assert_eq!(report.mean, Duration::from_millis(76));
+    assert_eq!(report.max, Duration::from_millis(90));
+    assert_eq!(report.over_target, 1);
+    assert_eq!(report.dropped, 3);
+    assert!(report.meets_target());
+
+    monitor.reset();
+    assert_eq!(monitor.report().samples, 0);
+}
+
+#[test]
+fn test_audio_scheduler_trait_stats_and_clear() {
+    let scheduler = AudioScheduler::new();
+    let now = Instant::now();
+    let due = now + Duration::from_millis(30);
+
+    Scheduler::schedule(&scheduler, buffer_at(20_000, due));
+    Scheduler::schedule(&scheduler, buffer_at(0, now));
+    assert_eq!(Scheduler::next_deadline(&scheduler), Some(now));
+    assert_eq!(Scheduler::next_ready(&scheduler).unwrap().timestamp, 0);
+    assert_eq!(Scheduler::next_deadline(&scheduler), Some(due));
+
+    Scheduler::clear(&scheduler);
+    assert!(Scheduler::is_empty(&scheduler));
+    assert_eq!(
+        scheduler.stats(),
+        SchedulerStats {
+            scheduled: 2,
+            played: 1,
+            dropped: 1,
+            queued: 0,
+        }
+    );
+    assert_eq!(Scheduler::next_deadline(&scheduler), None);
+}
+
+#[test]
+fn test_discard_before_drops_stale_buffers() {
+    let scheduler = AudioScheduler::new();
+    let now = Instant::now();
+    for ts in [0, 20_000, 40_000, 60_000] {
+        scheduler.schedule(buffer_at(ts, now));
+    }
+
+    // Seek: the new position starts at 40ms on the server timeline
+    scheduler.discard_before(40_000);
+    assert_eq!(scheduler.stats().queued, 2);
+    // A chunk for the old position still in flight is ignored
+    scheduler.schedule(buffer_at(30_000, now));
+    scheduler.schedule(buffer_at(80_000, now));
+
+    let played: Vec<i64> = std::iter::from_fn(|| scheduler.next_ready_at(now))
+        .map(|b| b.timestamp)
+        .collect();
+    assert_eq!(played, [40_000, 60_000, 80_000]);
+    assert_eq!(scheduler.stats().dropped, 3);
+
+    // Lifting the cutoff lets earlier timestamps through again
+    scheduler.discard_before(i64::MIN);
+    scheduler.schedule(buffer_at(0, now));
+    assert_eq!(scheduler.next_ready_at(now).unwrap().timestamp, 0);
+}
+
+/// Plays buffers in arrival order as soon as they are queued
+#[derive(Default)]
+struct FifoScheduler {
+    queue: Mutex<VecDeque<AudioBuffer>>,
+    scheduled: Mutex<u64>,
+}
+
+impl Scheduler for FifoScheduler {
+    fn schedule(&self, buffer: AudioBuffer) {
+        *self.scheduled.lock().unwrap() += 1;
+        self.queue.lock().unwrap().push_back(buffer);
+    }
+
+    fn next_ready(&self) -> Option<AudioBuffer> {
+        self.queue.lock().unwrap().pop_front()
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.queue.lock().unwrap().front().map(|b| b.play_at)
+    }
+
+    fn clear(&self) {
+        self.queue.lock().unwrap().clear();
+    }
+
+    fn stats(&self) -> SchedulerStats {
+        SchedulerStats {
+            scheduled: *self.scheduled.lock().unwrap(),
+            queued: self.queue.lock().unwrap().len(),
+            ..SchedulerStats::default()
+        }
+    }
+}
+
+#[test]
+fn test_custom_scheduler_is_swappable() {
+    let schedulers: Vec<Arc<dyn Scheduler>> = vec![
+        Arc::new(AudioScheduler::new()),
+        Arc::new(FifoScheduler::default()),
+    ];
+    for scheduler in schedulers {
+        scheduler.schedule(buffer_at(0, Instant::now()));
+        assert!(!scheduler.is_empty());
+        assert_eq!(scheduler.next_ready().unwrap().timestamp, 0);
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.stats().scheduled, 1);
+
+        // Every scheduler drops stale buffers after a seek
+        scheduler.schedule(buffer_at(0, Instant::now()));
+        scheduler.discard_before(20_000);
+        assert!(scheduler.is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+// ABOUTME: Tests for the protocol schema export (requires the schema feature)
+// ABOUTME: Validates coverage of all messages and that the checked-in schema files are current
+#![cfg(feature = "schema")]
+
+use sendspin::protocol::schema;
+
+const MESSAGE_TYPES: &[&str] = &[
+    "client/hello",
+    "server/hello",
+    "client/time",
+    "server/time",
+    "client/state",
+    "server/state",
+    "server/command",
+    "client/command",
+    "stream/start",
+    "stream/end",
+    "stream/clear",
+    "stream/request-format",
+    "group/update",
+    "client/goodbye",
+    "server/goodbye",
+];
+
+#[test]
+fn test_json_schema_covers_every_message() {
+    let text = schema::json_schema().to_string();
+    for message_type in MESSAGE_TYPES {
+        assert!(
+            text.contains(&format!("\"{}\"", message_type)),
+            "{}",
+            message_type
+        );
+    }
+}
+
+#[test]
+fn test_typescript_declares_message_union() {
+    let ts = schema::typescript();
+    assert!(ts.contains("export type Message = {"));
+    assert!(ts.contains("\"player@v1_support\"?: PlayerV1Support | null;"));
+    for message_type in MESSAGE_TYPES {
+        assert!(
+            ts.contains(&format!("type: \"{}\";", message_type)),
+            "{}",
+            message_type
+        );
+    }
+}
+
+#[test]
+fn test_checked_in_schema_is_current() {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let json = std::fs::read_to_string(format!("{}/schema/sendspin.schema.json", root)).unwrap();
+    let ts = std::fs::read_to_string(format!("{}/schema/sendspin.d.ts", root)).unwrap();
+    let hint = "regenerate with `cargo run --features schema --example export_schema`";
+    assert_eq!(
+        json,
+        serde_json::to_string_pretty(&schema::json_schema()).unwrap() + "\n",
+        "{}",
+        hint
+    );
+    assert_eq!(ts, schema::typescript(), "{}", hint);
+}
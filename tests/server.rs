@@ -0,0 +1,715 @@
+// ABOUTME: Tests for the Sendspin server component
+// ABOUTME: Validates format parsing, reader sources, handshake, time sync, and streaming
+
+use sendspin::audio::decode::PcmEndian;
+use sendspin::audio::{AudioFormat, Codec};
+use sendspin::error::Error;
+use sendspin::protocol::client::ProtocolClient;
+use sendspin::protocol::messages::{
+    AudioFormatSpec, ClientGoodbye, ClientHello, ClientState, ClientTime, GoodbyeReason,
+    GroupUpdate, Message, PlaybackState, PlayerState, PlayerSyncState, PlayerV1Support,
+};
+use sendspin::protocol::Role;
+use sendspin::scheduler::LatencyProfile;
+use sendspin::server::crossfade::{mix, mix_float};
+use sendspin::server::silence::{float_peak_level, peak_level};
+use sendspin::server::{
+    parse_pcm_format, AudioSource, ClientSettings, ReaderSource, Server, ServerClock, ServerConfig,
+    SilenceConfig, ToneSource, DEFAULT_GROUP,
+};
+use std::io::Cursor;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn hello() -> ClientHello {
+    hello_for("test-player", &[])
+}
+
+fn hello_for(client_id: &str, commands: &[&str]) -> ClientHello {
+    ClientHello::new(client_id, "Test Player", vec![Role::Player(1)]).with_player_support(
+        PlayerV1Support {
+            supported_formats: vec![AudioFormatSpec {
+                codec: "pcm".to_string(),
+                channels: 2,
+                sample_rate: 48000,
+                bit_depth: 16,
+                endian: None,
+            }],
+            buffer_capacity: 100,
+            supported_commands: commands.iter().map(|c| c.to_string()).collect(),
+        },
+    )
+}
+
+/// Next message, skipping group/update notifications
+async fn next_non_group(client: &mut ProtocolClient) -> Message {
+    loop {
+        let msg = timeout(Duration::from_secs(2), client.recv_message())
+            .await
+            .unwrap()
+            .unwrap();
+        if !matches!(msg, Message::GroupUpdate(_)) {
+            return msg;
+        }
+    }
+}
+
+/// Next group/update, skipping other messages
+async fn next_group_update(client: &mut ProtocolClient) -> GroupUpdate {
+    loop {
+        let msg = timeout(Duration::from_secs(2), client.recv_message())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::GroupUpdate(update) = msg {
+            return update;
+        }
+    }
+}
+
+#[test]
+fn test_parse_pcm_format() {
+    let format = parse_pcm_format("s24le,44100,1").unwrap();
+    assert_eq!(format.codec, Codec::Pcm);
+    assert_eq!(format.bit_depth, 24);
+    assert_eq!(format.sample_rate, 44100);
+    assert_eq!(format.channels, 1);
+
+    let format = parse_pcm_format("s32le,48000,2").unwrap();
+    assert_eq!((format.codec, format.bit_depth), (Codec::Pcm, 32));
+    let format = parse_pcm_format("f32le,48000,2").unwrap();
+    assert_eq!((format.codec, format.bit_depth), (Codec::PcmFloat, 32));
+
+    assert!(parse_pcm_format("f64le,48000,2").is_err());
+    assert!(parse_pcm_format("s16le,0,2").is_err());
+    assert!(parse_pcm_format("s16le,48000").is_err());
+}
+
+#[test]
+fn test_reader_source_chunks_whole_frames() {
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    // 10 frames plus a trailing partial frame
+    let mut source = ReaderSource::new(Cursor::new(vec![1u8; 42]), format).unwrap();
+
+    assert_eq!(source.next_chunk(4).unwrap().unwrap().len(), 16);
+    assert_eq!(source.next_chunk(4).unwrap().unwrap().len(), 16);
+    assert_eq!(source.next_chunk(4).unwrap().unwrap().len(), 8);
+    assert!(source.next_chunk(4).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_server_streams_to_player() {
+    let config = ServerConfig {
+        name: "Test Server".to_string(),
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    assert_eq!(client.server_hello().name, "Test Server");
+    assert_eq!(client.server_hello().active_roles, vec![Role::Player(1)]);
+
+    // Time sync is answered with server loop timestamps
+    client
+        .send_message(&Message::ClientTime(ClientTime {
+            client_transmitted: 42,
+        }))
+        .await
+        .unwrap();
+    let reply = next_non_group(&mut client).await;
+    match reply {
+        Message::ServerTime(time) => {
+            assert_eq!(time.client_transmitted, 42);
+            assert!(time.server_transmitted >= time.server_received);
+        }
+        other => panic!("Expected server/time, got {}", other.message_type()),
+    }
+
+    let handle = server.handle();
+    assert_eq!(handle.clients().len(), 1);
+    assert_eq!(handle.clients()[0].client_id, "test-player");
+
+    // 100ms of 48kHz stereo s16le
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let source = ReaderSource::new(Cursor::new(vec![0u8; 4800 * 4]), format).unwrap();
+    let serve = tokio::spawn(async move {
+        server.serve(source).await.unwrap();
+        server
+    });
+
+    let start = next_non_group(&mut client).await;
+    let Message::StreamStart(start) = start else {
+        panic!("Expected stream/start, got {}", start.message_type());
+    };
+    // The server states the byte order so players need not guess
+    assert_eq!(start.player.unwrap().endian, Some(PcmEndian::Little));
+
+    let mut timestamps = Vec::new();
+    while timestamps.len() < 5 {
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.data.len(), 960 * 4);
+        timestamps.push(chunk.timestamp);
+    }
+    // Consecutive 20ms chunks
+    for pair in timestamps.windows(2) {
+        assert_eq!(pair[1] - pair[0], 20_000);
+    }
+
+    let _server = serve.await.unwrap();
+    let end = next_non_group(&mut client).await;
+    assert!(matches!(end, Message::StreamEnd(_)));
+}
+
+#[tokio::test]
+async fn test_manual_clock_paces_stream() {
+    let clock = ServerClock::manual();
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    }
+    .with_clock(clock.clone());
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+    let handle = server.handle();
+    while handle.clients().is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let source = ToneSource::new(format).unwrap();
+    tokio::spawn(async move { server.serve(source).await });
+    let start = next_non_group(&mut client).await;
+    assert!(matches!(start, Message::StreamStart(_)));
+
+    // Stopped at zero, the server sends only the chunk that is due now
+    let first = client.recv_audio_chunk().await.unwrap();
+    assert_eq!(first.timestamp, 100_000);
+    assert!(
+        timeout(Duration::from_millis(100), client.recv_audio_chunk())
+            .await
+            .is_err()
+    );
+
+    clock.advance(Duration::from_millis(40));
+    for expected in [120_000, 140_000] {
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.timestamp, expected);
+    }
+    assert_eq!(handle.clock().now_micros(), 40_000);
+}
+
+#[test]
+fn test_client_settings_effective_volume() {
+    let settings = ClientSettings {
+        volume_trim: -15,
+        ..ClientSettings::default()
+    };
+    assert_eq!(settings.effective_volume(80), 65);
+    assert_eq!(settings.effective_volume(10), 0);
+
+    let boosted = ClientSettings {
+        volume_trim: 30,
+        ..ClientSettings::default()
+    };
+    assert_eq!(boosted.effective_volume(90), 100);
+}
+
+async fn next_command(client: &mut ProtocolClient) -> (String, Option<u8>, Option<bool>) {
+    loop {
+        let msg = timeout(Duration::from_secs(2), client.recv_message())
+            .await
+            .unwrap()
+            .unwrap();
+        if let Message::ServerCommand(cmd) = msg {
+            let player = cmd.player.unwrap();
+            return (player.command.to_string(), player.volume, player.mute);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_per_client_offsets() {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let handle = server.handle();
+
+    // Configured before the client connects
+    handle.set_client_settings(
+        "bluetooth",
+        ClientSettings {
+            delay: Duration::from_millis(50),
+            volume_trim: -10,
+            muted: false,
+        },
+    );
+    handle.set_volume(80);
+
+    let mut wired = ProtocolClient::connect(&url, hello_for("wired", &["volume", "mute"]))
+        .await
+        .unwrap();
+    let mut bluetooth = ProtocolClient::connect(&url, hello_for("bluetooth", &["volume", "mute"]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        next_command(&mut wired).await,
+        ("volume".to_string(), Some(80), None)
+    );
+    assert_eq!(
+        next_command(&mut wired).await,
+        ("mute".to_string(), None, Some(false))
+    );
+    assert_eq!(
+        next_command(&mut bluetooth).await,
+        ("volume".to_string(), Some(70), None)
+    );
+    assert_eq!(
+        next_command(&mut bluetooth).await,
+        ("mute".to_string(), None, Some(false))
+    );
+
+    // Muting one client only affects that client
+    handle.set_client_muted("bluetooth", true);
+    assert_eq!(
+        next_command(&mut bluetooth).await,
+        ("volume".to_string(), Some(70), None)
+    );
+    assert_eq!(
+        next_command(&mut bluetooth).await,
+        ("mute".to_string(), None, Some(true))
+    );
+
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let source = ReaderSource::new(Cursor::new(vec![0u8; 960 * 4]), format).unwrap();
+    server.serve(source).await.unwrap();
+
+    let wired_chunk = timeout(Duration::from_secs(2), wired.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    let bluetooth_chunk = timeout(Duration::from_secs(2), bluetooth.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(wired_chunk.timestamp - bluetooth_chunk.timestamp, 50_000);
+}
+
+#[tokio::test]
+async fn test_groups_stream_independent_sources() {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let handle = server.handle();
+
+    handle.create_group("kitchen", "Kitchen").unwrap();
+    assert!(handle.create_group("kitchen", "Again").is_err());
+    assert!(handle.assign_client("x", "missing").is_err());
+    assert!(handle.remove_group(DEFAULT_GROUP).is_err());
+    handle.assign_client("kitchen-player", "kitchen").unwrap();
+
+    let mut living = ProtocolClient::connect(&url, hello_for("living-player", &[]))
+        .await
+        .unwrap();
+    let mut kitchen = ProtocolClient::connect(&url, hello_for("kitchen-player", &[]))
+        .await
+        .unwrap();
+
+    let update = next_group_update(&mut living).await;
+    assert_eq!(update.group_id.as_deref(), Some(DEFAULT_GROUP));
+    let update = next_group_update(&mut kitchen).await;
+    assert_eq!(update.group_id.as_deref(), Some("kitchen"));
+    assert_eq!(update.group_name.as_deref(), Some("Kitchen"));
+    assert_eq!(update.playback_state, Some(PlaybackState::Stopped));
+
+    // Each group plays its own source
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let living_source = ReaderSource::new(Cursor::new(vec![1u8; 960 * 4]), format.clone()).unwrap();
+    let kitchen_source = ReaderSource::new(Cursor::new(vec![2u8; 960 * 4]), format).unwrap();
+    let (a, b) = tokio::join!(
+        server.serve(living_source),
+        server.serve_group("kitchen", kitchen_source)
+    );
+    a.unwrap();
+    b.unwrap();
+
+    let chunk = timeout(Duration::from_secs(2), living.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(chunk.data.iter().all(|&b| b == 1));
+    let chunk = timeout(Duration::from_secs(2), kitchen.recv_audio_chunk())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(chunk.data.iter().all(|&b| b == 2));
+
+    let groups = handle.groups();
+    let kitchen_info = groups.iter().find(|g| g.id == "kitchen").unwrap();
+    assert_eq!(kitchen_info.members, vec!["kitchen-player"]);
+
+    // Moving a client at runtime announces the new group
+    handle.assign_client("living-player", "kitchen").unwrap();
+    loop {
+        let update = next_group_update(&mut living).await;
+        if update.group_id.as_deref() == Some("kitchen") {
+            break;
+        }
+    }
+    assert_eq!(handle.client_group("living-player"), "kitchen");
+
+    // Removing a group returns its members to the default group
+    handle.remove_group("kitchen").unwrap();
+    loop {
+        let update = next_group_update(&mut kitchen).await;
+        if update.group_id.as_deref() == Some(DEFAULT_GROUP) {
+            break;
+        }
+    }
+    assert_eq!(handle.client_group("kitchen-player"), DEFAULT_GROUP);
+}
+
+/// Next group/update for `group_id` whose members match `ids`
+async fn wait_for_members(
+    client: &mut ProtocolClient,
+    group_id: &str,
+    ids: &[&str],
+) -> GroupUpdate {
+    loop {
+        let update = next_group_update(client).await;
+        let members: Vec<String> = update
+            .members
+            .iter()
+            .flatten()
+            .map(|m| m.client_id.clone())
+            .collect();
+        if update.group_id.as_deref() == Some(group_id) && members == ids {
+            return update;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_clients_join_and_leave_groups() {
+    let server = Server::bind("127.0.0.1:0", ServerConfig::default())
+        .await
+        .unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    server.handle().create_group("kitchen", "Kitchen").unwrap();
+
+    let mut sofa = ProtocolClient::connect(&url, hello_for("sofa", &[]))
+        .await
+        .unwrap();
+    let mut tv = ProtocolClient::connect(&url, hello_for("tv", &[]))
+        .await
+        .unwrap();
+    wait_for_members(&mut sofa, DEFAULT_GROUP, &["sofa", "tv"]).await;
+
+    // Reported volume shows up in the member list of the other clients
+    tv.send_message(&Message::ClientState(ClientState {
+        player: Some(PlayerState {
+            state: PlayerSyncState::Synchronized,
+            volume: Some(30),
+            muted: Some(true),
+            buffered_ms: None,
+        }),
+    }))
+    .await
+    .unwrap();
+    loop {
+        let update = wait_for_members(&mut sofa, DEFAULT_GROUP, &["sofa", "tv"]).await;
+        let tv = &update.members.unwrap()[1];
+        if tv.volume == Some(30) {
+            assert_eq!(tv.muted, Some(true));
+            assert_eq!(tv.name, "Test Player");
+            break;
+        }
+    }
+
+    sofa.join_group("kitchen").await.unwrap();
+    wait_for_members(&mut sofa, "kitchen", &["sofa"]).await;
+    wait_for_members(&mut tv, DEFAULT_GROUP, &["tv"]).await;
+    assert_eq!(server.handle().client_group("sofa"), "kitchen");
+
+    // Unknown groups are ignored
+    sofa.join_group("garage").await.unwrap();
+    sofa.leave_group().await.unwrap();
+    wait_for_members(&mut sofa, DEFAULT_GROUP, &["sofa", "tv"]).await;
+    assert_eq!(server.handle().client_group("sofa"), DEFAULT_GROUP);
+
+    sofa.send_message(&Message::ClientGoodbye(ClientGoodbye {
+        reason: GoodbyeReason::Shutdown,
+        telemetry: None,
+    }))
+    .await
+    .unwrap();
+    wait_for_members(&mut tv, DEFAULT_GROUP, &["tv"]).await;
+}
+
+#[test]
+fn test_peak_level() {
+    let quiet: Vec<u8> = [16i16, -32].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(peak_level(&quiet, 16), 32.0 / 32768.0);
+    let loud = (-8_388_608i32).to_le_bytes()[..3].to_vec();
+    assert_eq!(peak_level(&loud, 24), 1.0);
+    assert!(SilenceConfig::default().threshold_amplitude() > peak_level(&[0, 0], 16));
+    assert_eq!(peak_level(&i32::MIN.to_le_bytes(), 32), 1.0);
+    let float: Vec<u8> = [0.25f32, -0.5, f32::NAN]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    assert_eq!(float_peak_level(&float), 0.5);
+}
+
+#[tokio::test]
+async fn test_silence_suppression_restarts_stream() {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        silence: Some(SilenceConfig {
+            threshold_db: -40.0,
+            hold: Duration::from_millis(40),
+        }),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    // 2 loud chunks, 4 silent chunks, 2 loud chunks of 20ms each
+    let chunk = 960 * 4;
+    let mut pcm = vec![0x40u8; chunk * 2];
+    pcm.extend(vec![0u8; chunk * 4]);
+    pcm.extend(vec![0x40u8; chunk * 2]);
+    let format = parse_pcm_format("s16le,48000,2").unwrap();
+    let source = ReaderSource::new(Cursor::new(pcm), format).unwrap();
+    server.serve(source).await.unwrap();
+
+    let mut lifecycle = Vec::new();
+    while lifecycle.len() < 4 {
+        match next_non_group(&mut client).await {
+            Message::StreamStart(_) => lifecycle.push("start"),
+            Message::StreamEnd(_) => lifecycle.push("end"),
+            _ => {}
+        }
+    }
+    assert_eq!(lifecycle, ["start", "end", "start", "end"]);
+
+    // The first silent chunk is still sent; the rest of the silence is dropped
+    let mut silent = 0;
+    for _ in 0..5 {
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        if chunk.data.iter().all(|&b| b == 0) {
+            silent += 1;
+        }
+    }
+    assert_eq!(silent, 1);
+    assert!(
+        timeout(Duration::from_millis(100), client.recv_audio_chunk())
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn test_server_config_with_profile() {
+    let config = ServerConfig::default().with_profile(&LatencyProfile::low_latency());
+    assert_eq!(config.chunk_duration, Duration::from_millis(10));
+    assert_eq!(config.buffer_lead, Duration::from_millis(50));
+}
+
+/// Source producing a constant 16- or 24-bit level, endless unless limited
+struct LevelSource {
+    format: AudioFormat,
+    level: i32,
+    frames_left: Option<usize>,
+}
+
+impl LevelSource {
+    fn new(spec: &str, level: i32, frames: Option<usize>) -> Self {
+        Self {
+            format: parse_pcm_format(spec).unwrap(),
+            level,
+            frames_left: frames,
+        }
+    }
+}
+
+impl AudioSource for LevelSource {
+    fn format(&self) -> &AudioFormat {
+        &self.format
+    }
+
+    fn next_chunk(&mut self, max_frames: usize) -> Result<Option<Vec<u8>>, Error> {
+        let frames = self
+            .frames_left
+            .map_or(max_frames, |left| left.min(max_frames));
+        if frames == 0 {
+            return Ok(None);
+        }
+        if let Some(left) = self.frames_left.as_mut() {
+            *left -= frames;
+        }
+        let width = self.format.bit_depth as usize / 8;
+        let sample = &self.level.to_le_bytes()[..width];
+        let samples = frames * self.format.channels as usize;
+        Ok(Some(sample.repeat(samples)))
+    }
+}
+
+#[test]
+fn test_crossfade_mix() {
+    let old: Vec<u8> = [1000i16; 8].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let new: Vec<u8> = [3000i16; 8].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mixed = mix(&old, &new, 16, 2, 0, 4).unwrap();
+    let samples: Vec<i16> = mixed
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    assert_eq!(samples, [1000, 1000, 1500, 1500, 2000, 2000, 2500, 2500]);
+
+    // Past the end of the fade only the new source is heard; a short outgoing chunk is silence
+    let mixed = mix(&old[..4], &new, 16, 2, 4, 4).unwrap();
+    assert_eq!(mixed, new);
+    let mixed = mix(&[], &new[..4], 16, 2, 2, 4).unwrap();
+    assert_eq!(i16::from_le_bytes([mixed[0], mixed[1]]), 1500);
+
+    // Float PCM blends the same way
+    let old: Vec<u8> = [0.25f32; 2].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let new: Vec<u8> = [0.75f32; 2].iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mixed = mix_float(&old, &new, 1, 1, 2);
+    let value = f32::from_le_bytes([mixed[0], mixed[1], mixed[2], mixed[3]]);
+    assert!((value - 0.5).abs() < 1e-6);
+}
+
+#[tokio::test]
+async fn test_switch_source_with_new_format() {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let handle = server.handle();
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let idle = LevelSource::new("s16le,48000,2", 0, None);
+    assert!(handle.switch_source(idle, None).is_err());
+
+    let first = LevelSource::new("s16le,48000,2", 100, None);
+    let (served, ()) = tokio::join!(server.serve(first), async {
+        assert!(matches!(
+            next_non_group(&mut client).await,
+            Message::StreamStart(_)
+        ));
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.data.len(), 960 * 4);
+
+        let second = LevelSource::new("s24le,48000,2", -5000, Some(960 * 3));
+        handle.switch_source(second, None).unwrap();
+        assert!(matches!(
+            next_non_group(&mut client).await,
+            Message::StreamEnd(_)
+        ));
+        match next_non_group(&mut client).await {
+            Message::StreamStart(start) => {
+                assert_eq!(start.player.unwrap().bit_depth, 24);
+            }
+            other => panic!("Expected stream/start, got {:?}", other),
+        }
+
+        // The timeline continues across the switch
+        let mut previous = chunk.timestamp;
+        loop {
+            let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(chunk.timestamp - previous, 20_000);
+            previous = chunk.timestamp;
+            if chunk.data.len() == 960 * 6 {
+                assert_eq!(&chunk.data[..3], &(-5000i32).to_le_bytes()[..3]);
+                break;
+            }
+        }
+    });
+    served.unwrap();
+
+    // The same connection saw both streams end to end
+    assert!(matches!(
+        next_non_group(&mut client).await,
+        Message::StreamEnd(_)
+    ));
+    assert_eq!(handle.clients().len(), 1);
+    assert!(handle
+        .switch_source(LevelSource::new("s16le,48000,2", 0, None), None)
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_switch_source_crossfades_same_format() {
+    let config = ServerConfig {
+        buffer_lead: Duration::from_millis(100),
+        ..ServerConfig::default()
+    };
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let handle = server.handle();
+    let mut client = ProtocolClient::connect(&url, hello()).await.unwrap();
+
+    let first = LevelSource::new("s16le,48000,2", 1000, None);
+    let (served, ()) = tokio::join!(server.serve(first), async {
+        assert!(matches!(
+            next_non_group(&mut client).await,
+            Message::StreamStart(_)
+        ));
+        timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        let second = LevelSource::new("s16le,48000,2", 3000, Some(960 * 8));
+        handle
+            .switch_source(second, Some(Duration::from_millis(60)))
+            .unwrap();
+    });
+    served.unwrap();
+
+    // Levels ramp from the old source to the new one without restarting the stream
+    let mut levels = Vec::new();
+    while levels.last() != Some(&3000) {
+        let chunk = timeout(Duration::from_secs(2), client.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        levels.push(i16::from_le_bytes([chunk.data[0], chunk.data[1]]));
+    }
+    assert!(levels.windows(2).all(|w| w[0] <= w[1]), "{:?}", levels);
+    assert!(levels.iter().any(|&l| l > 1000 && l < 3000), "{:?}", levels);
+    assert!(matches!(
+        next_non_group(&mut client).await,
+        Message::StreamEnd(_)
+    ));
+    while let Ok(msg) = timeout(Duration::from_millis(100), client.recv_message()).await {
+        assert!(matches!(msg.unwrap(), Message::GroupUpdate(_)));
+    }
+}
@@ -0,0 +1,175 @@
+// ABOUTME: Tests for ServerPool failover between mock servers
+// ABOUTME: Covers skipping unreachable servers, failing over, and not failing over after close
+
+mod common;
+
+use common::{mock_server_with_hello, record, send};
+use sendspin::events::ConnectionStatus;
+use sendspin::protocol::messages::{
+    ClientHello, ClientTime, GoodbyeReason, Message, ServerGoodbye, ServerGoodbyeReason,
+    ServerHello, ServerState,
+};
+use sendspin::protocol::{MessageReceiver, ReconnectPolicy, Role, ServerPool};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+fn hello() -> ClientHello {
+    ClientHello::new("pool-test", "Pool Test", vec![Role::Player(1)])
+}
+
+/// Mock server named `name`: completes the hello exchange, sends `script`, and
+/// reports every later message from the client; closes after the script if `hang_up`
+async fn server(
+    name: &'static str,
+    script: Vec<Message>,
+    hang_up: bool,
+) -> (String, mpsc::UnboundedReceiver<Message>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let hello = ServerHello::new(name, name, vec![Role::Player(1)]);
+    let url = mock_server_with_hello(hello, move |mut ws| async move {
+        for msg in &script {
+            send(&mut ws, msg).await;
+        }
+        if !hang_up {
+            record(ws, tx).await;
+        }
+    })
+    .await;
+    (url, rx)
+}
+
+/// A URL nothing listens on
+async fn unreachable() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("ws://{}/sendspin", addr)
+}
+
+async fn next_message(messages: &mut MessageReceiver) -> Message {
+    timeout(Duration::from_secs(5), messages.recv())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+fn state() -> Message {
+    Message::ServerState(ServerState {
+        metadata: None,
+        controller: None,
+    })
+}
+
+#[tokio::test]
+async fn test_connects_to_first_reachable_server() {
+    let down = unreachable().await;
+    let (up, _rx) = server("standby", Vec::new(), false).await;
+    let client = ServerPool::new([down, up])
+        .hello(hello())
+        .retry(ReconnectPolicy::never())
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.session().server_name(), "standby");
+}
+
+#[tokio::test]
+async fn test_no_reachable_server_is_an_error() {
+    let result = ServerPool::new([unreachable().await, unreachable().await])
+        .hello(hello())
+        .retry(ReconnectPolicy::never())
+        .connect()
+        .await;
+    assert!(matches!(result, Err(sendspin::Error::Connection(_))));
+}
+
+#[tokio::test]
+async fn test_fails_over_and_keeps_receivers() {
+    let goodbye = Message::ServerGoodbye(ServerGoodbye {
+        reason: ServerGoodbyeReason::Shutdown,
+    });
+    let (main, _main_rx) = server("main", vec![goodbye], true).await;
+    let (standby, mut standby_rx) = server("standby", vec![state()], false).await;
+    let client = ServerPool::new([main, standby])
+        .hello(hello())
+        .retry(ReconnectPolicy::never())
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(client.session().server_name(), "main");
+    let mut sessions = client.watch_session();
+    let mut status = client.connection_status();
+    let parts = client.into_parts();
+    let mut messages = parts.messages;
+
+    assert!(matches!(
+        next_message(&mut messages).await,
+        Message::ServerGoodbye(_)
+    ));
+    // The old server's streams end before the new server's messages arrive
+    assert!(matches!(
+        next_message(&mut messages).await,
+        Message::StreamEnd(_)
+    ));
+    assert!(matches!(
+        next_message(&mut messages).await,
+        Message::ServerState(_)
+    ));
+
+    timeout(
+        Duration::from_secs(5),
+        sessions.wait_for(|s| s.server_name() == "standby"),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(*status.borrow_and_update(), ConnectionStatus::Connected);
+
+    // The sender from before the failover reaches the standby
+    let ping = Message::ClientTime(ClientTime {
+        client_transmitted: 42,
+    });
+    parts.sender.send_message(ping).await.unwrap();
+    let received = timeout(Duration::from_secs(5), standby_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        received,
+        Message::ClientTime(ClientTime {
+            client_transmitted: 42
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_close_does_not_fail_over() {
+    let (main, mut main_rx) = server("main", Vec::new(), false).await;
+    let (standby, _standby_rx) = server("standby", vec![state()], false).await;
+    let client = ServerPool::new([main, standby])
+        .hello(hello())
+        .retry(ReconnectPolicy::never())
+        .connect()
+        .await
+        .unwrap();
+    let mut status = client.connection_status();
+    let parts = client.into_parts();
+    let mut messages = parts.messages;
+    parts.sender.close(GoodbyeReason::Shutdown).await.unwrap();
+
+    assert!(matches!(
+        main_rx.recv().await,
+        Some(Message::ClientGoodbye(_))
+    ));
+    let end = timeout(Duration::from_secs(5), messages.recv())
+        .await
+        .unwrap();
+    assert!(
+        end.is_none(),
+        "Expected the receivers to close, got {:?}",
+        end
+    );
+    assert_eq!(*status.borrow_and_update(), ConnectionStatus::Disconnected);
+}
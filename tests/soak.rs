@@ -0,0 +1,329 @@
+// ABOUTME: Long-running soak test of the server and receive pipeline on a manual clock
+// ABOUTME: Run with `cargo test --release --test soak -- --ignored`; SS_SOAK_HOURS sets simulated duration
+
+use sendspin::audio::decode::{DecodeErrorTracker, Decoder, PcmDecoder};
+use sendspin::audio::integrity::{FrameLayout, IntegrityChecker};
+use sendspin::audio::{AudioBuffer, Codec};
+use sendspin::protocol::client::{AudioChunk, ProtocolClient};
+use sendspin::protocol::messages::{ClientHello, ClientTime, GoodbyeReason, Message, ServerTime};
+use sendspin::protocol::streams::{StreamRole, StreamTracker};
+use sendspin::protocol::Role;
+use sendspin::scheduler::AudioScheduler;
+use sendspin::server::{
+    parse_pcm_format, Server, ServerClock, ServerConfig, ServerHandle, ToneSource,
+};
+use sendspin::sync::{ClockSync, SyncQuality};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+// ============================================================================
+// Simulation parameters
+// ============================================================================
+
+const FORMAT: &str = "s16le,48000,2";
+const CHUNK_MICROS: i64 = 20_000;
+const FRAMES_PER_CHUNK: usize = 960;
+const CHANNELS: usize = 2;
+
+/// Server clock drift relative to the client, in parts per million
+const DRIFT_PPM: i64 = 50;
+/// Interval between client/time exchanges
+const SYNC_INTERVAL_MICROS: i64 = 5_000_000;
+/// Interval between reconnects
+const RECONNECT_INTERVAL_MICROS: i64 = 15 * 60 * 1_000_000;
+/// Every Nth chunk is truncated on arrival to exercise decode error recovery
+const CORRUPT_EVERY: u64 = 10_007;
+/// How far ahead of playback the server sends
+const LEAD_MICROS: i64 = 200_000;
+
+/// Maximum tolerated error when predicting server time from the last sync
+const MAX_DRIFT_ERROR_MICROS: i64 = 1_000;
+/// Maximum buffered audio at any point
+const MAX_BUFFERED_MICROS: i64 = LEAD_MICROS + CHUNK_MICROS;
+/// History bound used for the stream tracker
+const HISTORY_LIMIT: usize = 4;
+/// Real time allowed for any single server response
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn simulated_micros() -> i64 {
+    let hours: f64 = std::env::var("SS_SOAK_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(24.0);
+    (hours * 3_600_000_000.0) as i64
+}
+
+/// Server loop time at the given client time
+fn server_time_at(local_micros: i64) -> i64 {
+    local_micros + local_micros * DRIFT_PPM / 1_000_000
+}
+
+// ============================================================================
+// Simulated client
+// ============================================================================
+
+/// Connect as a new player, waiting until the server has registered it
+async fn connect(url: &str, handle: &ServerHandle, connection: u64) -> ProtocolClient {
+    let client_id = format!("soak-player-{}", connection);
+    let hello = ClientHello::new(client_id.clone(), "Soak Player", vec![Role::Player(1)]);
+    let client = timeout(RESPONSE_TIMEOUT, ProtocolClient::connect(url, hello))
+        .await
+        .expect("connect timed out")
+        .unwrap();
+    // Registration and the subscription to stream events happen together
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    while !handle.clients().iter().any(|c| c.client_id == client_id) {
+        assert!(
+            Instant::now() < deadline,
+            "server never registered the client"
+        );
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    client
+}
+
+async fn wait_for_stream_start(client: &mut ProtocolClient, streams: &StreamTracker) {
+    loop {
+        let msg = next_message(client).await;
+        streams.apply(&msg);
+        if matches!(msg, Message::StreamStart(_)) {
+            return;
+        }
+    }
+}
+
+async fn next_message(client: &mut ProtocolClient) -> Message {
+    timeout(RESPONSE_TIMEOUT, client.recv_message())
+        .await
+        .expect("server went quiet")
+        .expect("connection closed")
+}
+
+/// Offset estimate from the last time exchange
+struct SyncEstimate {
+    offset: i64,
+}
+
+impl SyncEstimate {
+    /// Run one client/time exchange with the server; the clock stands still meanwhile
+    async fn exchange(
+        client: &mut ProtocolClient,
+        streams: &StreamTracker,
+        clock: &mut ClockSync,
+        local_micros: i64,
+    ) -> Self {
+        let t1 = local_micros;
+        client
+            .send_message(&Message::ClientTime(ClientTime {
+                client_transmitted: t1,
+            }))
+            .await
+            .unwrap();
+        let ServerTime {
+            server_received: t2,
+            server_transmitted: t3,
+            ..
+        } = loop {
+            match next_message(client).await {
+                Message::ServerTime(time) if time.client_transmitted == t1 => break time,
+                other => streams.apply(&other),
+            }
+        };
+        let t4 = local_micros;
+        clock.update(t1, t2, t3, t4);
+        assert_eq!(clock.quality(), SyncQuality::Good);
+        Self {
+            offset: ((t2 - t1) + (t3 - t4)) / 2,
+        }
+    }
+
+    fn server_time(&self, local_micros: i64) -> i64 {
+        local_micros + self.offset
+    }
+}
+
+// ============================================================================
+// Soak run
+// ============================================================================
+
+#[tokio::test]
+#[ignore]
+async fn soak_simulated_playback() {
+    let duration = simulated_micros();
+    let started = Instant::now();
+
+    let format = parse_pcm_format(FORMAT).unwrap();
+    let layout = FrameLayout::for_format(&format).unwrap();
+    let server_clock = ServerClock::manual();
+    let config = ServerConfig {
+        chunk_duration: Duration::from_micros(CHUNK_MICROS as u64),
+        buffer_lead: Duration::from_micros(LEAD_MICROS as u64),
+        ..ServerConfig::default()
+    }
+    .with_clock(server_clock.clone());
+    let server = Server::bind("127.0.0.1:0", config).await.unwrap();
+    let url = format!("ws://{}/sendspin", server.local_addr());
+    let handle = server.handle();
+
+    let decoder = PcmDecoder::new(format.bit_depth);
+    let scheduler = AudioScheduler::new();
+    let streams = StreamTracker::with_history_limit(HISTORY_LIMIT);
+    let mut integrity = IntegrityChecker::new(layout);
+    let mut errors = DecodeErrorTracker::default();
+    let mut clock = ClockSync::new();
+
+    let mut client = connect(&url, &handle, 0).await;
+    let source = ToneSource::new(format.clone()).unwrap();
+    let serve = tokio::spawn(async move { server.serve(source).await });
+    wait_for_stream_start(&mut client, &streams).await;
+
+    let mut local_now: i64 = 0;
+    let mut sync = SyncEstimate::exchange(&mut client, &streams, &mut clock, local_now).await;
+    let mut last_sync = local_now;
+    let mut last_reconnect = local_now;
+    // Timestamps of buffered chunks, oldest first
+    let mut queued: VecDeque<i64> = VecDeque::new();
+    let mut newest_timestamp = i64::MIN;
+
+    let mut chunks: u64 = 0;
+    let mut injected: u64 = 0;
+    let mut played_samples: u64 = 0;
+    let mut reconnects: u64 = 0;
+    let mut max_drift_error: i64 = 0;
+
+    while local_now < duration {
+        // Reconnect: new connection, new clock sync, fresh stream
+        if local_now - last_reconnect >= RECONNECT_INTERVAL_MICROS {
+            client.close(GoodbyeReason::Restart).await.unwrap();
+            reconnects += 1;
+            client = connect(&url, &handle, reconnects).await;
+            wait_for_stream_start(&mut client, &streams).await;
+            assert!(
+                !streams.current().is_idle(),
+                "reconnect did not restart the stream"
+            );
+
+            clock = ClockSync::new();
+            sync = SyncEstimate::exchange(&mut client, &streams, &mut clock, local_now).await;
+            last_sync = local_now;
+            while scheduler.next_ready().is_some() {}
+            queued.clear();
+            integrity.reset();
+            errors.reset();
+            last_reconnect = local_now;
+        }
+
+        if local_now - last_sync >= SYNC_INTERVAL_MICROS {
+            sync = SyncEstimate::exchange(&mut client, &streams, &mut clock, local_now).await;
+            last_sync = local_now;
+        }
+
+        // Take every chunk the server sends by now, up to its lead
+        let server_now = server_clock.now_micros();
+        while newest_timestamp + CHUNK_MICROS <= server_now + LEAD_MICROS {
+            let AudioChunk {
+                timestamp, data, ..
+            } = timeout(RESPONSE_TIMEOUT, client.recv_audio_chunk())
+                .await
+                .expect("server stopped sending audio")
+                .expect("connection closed");
+            assert!(timestamp > server_now, "chunk arrived after its play time");
+            newest_timestamp = timestamp;
+            chunks += 1;
+            let mut data = data.to_vec();
+            if chunks.is_multiple_of(CORRUPT_EVERY) {
+                data.pop();
+            }
+
+            match integrity.check(timestamp, &data) {
+                Ok(report) => {
+                    if let Some(delta) = report.discontinuity_micros {
+                        assert_eq!(delta, 0, "timestamp gap at chunk {}", chunks);
+                    }
+                    let samples = decoder.decode(&data).unwrap();
+                    assert_eq!(samples.len(), FRAMES_PER_CHUNK * CHANNELS);
+                    errors.record_success();
+
+                    // Predicted play time must track the drifting server clock
+                    let error = (sync.server_time(local_now) - server_now).abs();
+                    max_drift_error = max_drift_error.max(error);
+                    assert!(
+                        error <= MAX_DRIFT_ERROR_MICROS,
+                        "drift error {}µs at {}s",
+                        error,
+                        local_now / 1_000_000
+                    );
+
+                    scheduler.schedule(AudioBuffer {
+                        timestamp,
+                        play_at: Instant::now(),
+                        samples,
+                        format: format.clone(),
+                    });
+                    queued.push_back(timestamp);
+                }
+                Err(_) => {
+                    injected += 1;
+                    let event = errors.record_failure(
+                        Codec::Pcm,
+                        &sendspin::error::Error::Protocol("truncated".to_string()),
+                        timestamp,
+                    );
+                    assert_eq!(event.consecutive, 1, "isolated errors must not accumulate");
+                    assert!(errors.fallback_request(&event).is_none());
+                    // A dropped chunk leaves a gap; resynchronise continuity
+                    integrity.reset();
+                }
+            }
+            let buffered = newest_timestamp + CHUNK_MICROS - server_now;
+            assert!(
+                buffered <= MAX_BUFFERED_MICROS,
+                "buffer occupancy {}µs exceeds bound",
+                buffered
+            );
+        }
+
+        // Output plays whatever has come due
+        while queued.front().is_some_and(|&ts| ts <= server_now) {
+            queued.pop_front();
+            let buf = scheduler
+                .next_ready()
+                .expect("scheduled chunk went missing");
+            played_samples += buf.samples.len() as u64;
+        }
+
+        local_now += CHUNK_MICROS;
+        let target = server_time_at(local_now);
+        server_clock.advance(Duration::from_micros(
+            (target - server_clock.now_micros()) as u64,
+        ));
+    }
+
+    // Nothing accumulates beyond the configured bounds
+    assert!(streams.history(StreamRole::Player).len() <= HISTORY_LIMIT);
+    assert_eq!(injected, integrity.stats().misaligned);
+    assert_eq!(errors.consecutive_failures(), 0);
+    while scheduler.next_ready().is_some() {}
+    assert!(scheduler.is_empty());
+    assert!(played_samples > 0);
+    // Connections from earlier reconnects were all released
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    while handle.clients().len() > 1 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(handle.clients().len(), 1);
+
+    client.close(GoodbyeReason::Shutdown).await.unwrap();
+    serve.abort();
+
+    eprintln!(
+        "soak: {:.1}h simulated in {:.1}s, {} chunks, {} reconnects, {} injected errors, max drift error {}µs",
+        duration as f64 / 3_600_000_000.0,
+        started.elapsed().as_secs_f64(),
+        chunks,
+        reconnects,
+        injected,
+        max_drift_error
+    );
+}
@@ -0,0 +1,236 @@
+// ABOUTME: Tests for the strict/lenient spec-compliance switch
+// ABOUTME: Uses a scripted mock server to trigger role, parsing, and timestamp violations
+
+mod common;
+
+use common::{idle, mock_server};
+use futures_util::SinkExt;
+use sendspin::protocol::client::{ClientConfig, ProtocolClient};
+use sendspin::protocol::messages::{ClientHello, Message, PlayerV1Support};
+use sendspin::protocol::{strict, Role, SpecCompliance};
+use sendspin::Error;
+use std::time::Duration;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+fn hello() -> ClientHello {
+    ClientHello::new("compliance-test", "Compliance Test", vec![Role::Player(1)])
+}
+
+fn audio_frame(timestamp: i64) -> WsMessage {
+    let mut frame = vec![0x04];
+    frame.extend_from_slice(&timestamp.to_be_bytes());
+    frame.extend_from_slice(&[0; 4]);
+    WsMessage::Binary(frame)
+}
+
+/// Serve one connection: answer client/hello with `roles`, then send `script`
+async fn scripted_server(roles: &[&str], script: Vec<WsMessage>) -> String {
+    let roles = roles.iter().map(|r| Role::from(*r)).collect();
+    mock_server(roles, |mut ws| async move {
+        // Give the client time to set up (e.g., clock sync) before the script runs
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        for msg in script {
+            if ws.send(msg).await.is_err() {
+                return;
+            }
+        }
+        // Keep the connection open until the client goes away
+        idle(ws).await;
+    })
+    .await
+}
+
+fn config(compliance: SpecCompliance) -> ClientConfig {
+    ClientConfig {
+        compliance,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_unsupported_role_rejected_only_when_strict() {
+    let url = scripted_server(&["player@v1", "controller@v1"], vec![]).await;
+    let result =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Strict)).await;
+    assert!(result.is_err());
+
+    let url = scripted_server(&["player@v1", "controller@v1"], vec![]).await;
+    let result =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Lenient)).await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_backwards_timestamps_close_strict_connection() {
+    let script = || vec![audio_frame(2_000), audio_frame(1_000), audio_frame(3_000)];
+
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut lenient =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Lenient))
+            .await
+            .unwrap();
+    for expected in [2_000, 1_000, 3_000] {
+        let chunk = timeout(Duration::from_secs(2), lenient.recv_audio_chunk())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk.timestamp, expected);
+    }
+
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut strict =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Strict))
+            .await
+            .unwrap();
+    let mut received = 0;
+    while timeout(Duration::from_secs(2), strict.recv_audio_chunk())
+        .await
+        .unwrap()
+        .is_some()
+    {
+        received += 1;
+    }
+    // The offending chunk is the last one delivered before the connection closes
+    assert_eq!(received, 2);
+}
+
+#[tokio::test]
+async fn test_unparseable_message_closes_strict_connection() {
+    let script = || {
+        vec![
+            WsMessage::Text("{\"type\":\"server/bogus\"}".to_string()),
+            WsMessage::Text(r#"{"type":"stream/end","payload":{}}"#.to_string()),
+        ]
+    };
+
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut lenient =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Lenient))
+            .await
+            .unwrap();
+    let msg = timeout(Duration::from_secs(2), lenient.recv_message())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::StreamEnd(_)));
+
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut strict =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Strict))
+            .await
+            .unwrap();
+    let msg = timeout(Duration::from_secs(2), strict.recv_message())
+        .await
+        .unwrap();
+    assert!(msg.is_none());
+}
+
+#[tokio::test]
+async fn test_buffer_capacity_enforced_when_strict() {
+    let mut hello = hello();
+    hello.player_v1_support = Some(PlayerV1Support {
+        supported_formats: vec![],
+        buffer_capacity: 8,
+        supported_commands: vec![],
+    });
+    // Three 4-byte chunks due well in the future overflow an 8-byte buffer
+    let script = vec![
+        audio_frame(10_000_000),
+        audio_frame(10_020_000),
+        audio_frame(10_040_000),
+        audio_frame(10_060_000),
+    ];
+
+    let url = scripted_server(&["player@v1"], script).await;
+    let mut strict =
+        ProtocolClient::connect_with_config(&url, hello, config(SpecCompliance::Strict))
+            .await
+            .unwrap();
+    // Server loop time zero is now
+    strict.clock_sync().lock().await.update(0, 0, 0, 0);
+
+    let mut received = 0;
+    while timeout(Duration::from_secs(2), strict.recv_audio_chunk())
+        .await
+        .unwrap()
+        .is_some()
+    {
+        received += 1;
+    }
+    assert_eq!(received, 3);
+}
+
+#[test]
+fn test_default_is_lenient() {
+    assert_eq!(ClientConfig::default().compliance, SpecCompliance::Lenient);
+    assert!(SpecCompliance::Strict.is_strict());
+    assert!(SpecCompliance::Lenient.violation("x".to_string()).is_ok());
+    assert!(SpecCompliance::Strict.violation("x".to_string()).is_err());
+}
+
+#[test]
+fn test_strict_parse_reports_unknown_fields() {
+    let known = r#"{"type":"stream/start","payload":{"player":{"codec":"pcm","sample_rate":48000,"channels":2,"bit_depth":16,"codec_header":null}}}"#;
+    assert!(strict::parse(known).is_ok());
+
+    let drifted = r#"{"type":"stream/start","x_trace":"abc","payload":{"player":{"codec":"pcm","sample_rate":48000,"channels":2,"bit_depth":16,"x_dither":true},"artwork":{"channels":[0],"x_size":[]}}}"#;
+    // Normal parsing ignores the extra fields
+    assert!(serde_json::from_str::<Message>(drifted).is_ok());
+    match strict::parse(drifted) {
+        Err(Error::SpecDrift {
+            message_type,
+            fields,
+        }) => {
+            assert_eq!(message_type, "stream/start");
+            assert_eq!(fields, vec!["payload.player.x_dither", "x_trace"]);
+        }
+        other => panic!("Expected spec drift, got {:?}", other),
+    }
+
+    let hello = r#"{"type":"server/hello","payload":{"server_id":"s","name":"S","version":1,
+        "active_roles":["player@v1"],"connection_reason":"playback","x_region":"eu"}}"#;
+    let err = strict::parse(hello).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Spec drift in server/hello: unknown fields payload.x_region"
+    );
+}
+
+#[tokio::test]
+async fn test_strict_fields_follow_compliance_mode() {
+    let script = || {
+        vec![
+            WsMessage::Text(r#"{"type":"stream/end","payload":{"x_reason":"done"}}"#.to_string()),
+            WsMessage::Text(r#"{"type":"stream/clear","payload":{}}"#.to_string()),
+        ]
+    };
+    let config = |compliance| ClientConfig {
+        compliance,
+        strict_fields: true,
+        ..Default::default()
+    };
+
+    // Lenient: the drift is logged and the message still delivered
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut lenient =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Lenient))
+            .await
+            .unwrap();
+    let msg = timeout(Duration::from_secs(2), lenient.recv_message())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(msg, Message::StreamEnd(_)));
+
+    // Strict: the drifting message ends the connection
+    let url = scripted_server(&["player@v1"], script()).await;
+    let mut strict =
+        ProtocolClient::connect_with_config(&url, hello(), config(SpecCompliance::Strict))
+            .await
+            .unwrap();
+    let msg = timeout(Duration::from_secs(2), strict.recv_message())
+        .await
+        .unwrap();
+    assert!(msg.is_none());
+}
@@ -0,0 +1,58 @@
+// ABOUTME: Tests for the pluggable storage backends
+// ABOUTME: Runs the same contract against filesystem and in-memory storage
+
+use sendspin::storage::{self, FsStorage, MemoryStorage, Storage};
+
+fn exercise(storage: &dyn Storage) {
+    assert_eq!(storage.get("artwork", "cover.jpg").unwrap(), None);
+
+    storage.put("artwork", "cover.jpg", b"image").unwrap();
+    storage.put("artwork", "thumb.png", b"small").unwrap();
+    storage.put("state", "volume", b"80").unwrap();
+
+    assert_eq!(
+        storage.get("artwork", "cover.jpg").unwrap(),
+        Some(b"image".to_vec())
+    );
+    assert_eq!(
+        storage.keys("artwork").unwrap(),
+        vec!["cover.jpg".to_string(), "thumb.png".to_string()]
+    );
+
+    storage.put("artwork", "cover.jpg", b"replaced").unwrap();
+    assert_eq!(
+        storage.get("artwork", "cover.jpg").unwrap(),
+        Some(b"replaced".to_vec())
+    );
+
+    storage.remove("artwork", "cover.jpg").unwrap();
+    storage.remove("artwork", "cover.jpg").unwrap();
+    assert_eq!(storage.get("artwork", "cover.jpg").unwrap(), None);
+    assert!(storage.keys("empty").unwrap().is_empty());
+
+    // Names that could escape the storage root are rejected
+    assert!(storage.put("..", "key", b"x").is_err());
+    assert!(storage.put("ns", "a/b", b"x").is_err());
+    assert!(storage.get("ns", "").is_err());
+}
+
+#[test]
+fn test_memory_storage_contract() {
+    exercise(&MemoryStorage::new());
+}
+
+#[test]
+fn test_fs_storage_contract() {
+    let root = std::env::temp_dir().join(format!("sendspin-storage-{}", std::process::id()));
+    exercise(&FsStorage::new(&root));
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[test]
+fn test_client_id_is_persisted() {
+    let storage = MemoryStorage::new();
+    let first = storage::client_id(&storage).unwrap();
+    let second = storage::client_id(&storage).unwrap();
+    assert_eq!(first, second);
+    assert!(!first.is_empty());
+}
@@ -0,0 +1,80 @@
+// ABOUTME: Tests for negotiated stream format tracking
+// ABOUTME: Validates current stream descriptors and per-role history
+
+use sendspin::protocol::messages::{
+    Message, StreamArtworkConfig, StreamEnd, StreamPlayerConfig, StreamStart,
+};
+use sendspin::protocol::streams::{StreamRole, StreamTracker};
+use sendspin::protocol::Role;
+
+fn player_start(sample_rate: u32) -> Message {
+    Message::StreamStart(StreamStart {
+        player: Some(StreamPlayerConfig {
+            codec: "pcm".to_string(),
+            sample_rate,
+            channels: 2,
+            bit_depth: 24,
+            codec_header: None,
+            endian: None,
+        }),
+        artwork: Some(StreamArtworkConfig { channels: vec![0] }),
+        visualizer: None,
+    })
+}
+
+#[test]
+fn test_tracker_retains_current_stream() {
+    let tracker = StreamTracker::new();
+    assert!(tracker.current().is_idle());
+
+    tracker.apply(&player_start(48000));
+
+    let current = tracker.current();
+    let player = current.player.expect("Expected player stream");
+    assert_eq!(player.sample_rate, 48000);
+    assert_eq!(current.artwork.unwrap().channels, vec![0]);
+    assert!(current.visualizer.is_none());
+}
+
+#[test]
+fn test_tracker_stream_end_per_role() {
+    let tracker = StreamTracker::new();
+    tracker.apply(&player_start(48000));
+
+    tracker.apply(&Message::StreamEnd(StreamEnd {
+        roles: Some(vec![Role::Player(1)]),
+    }));
+
+    let current = tracker.current();
+    assert!(current.player.is_none());
+    assert!(current.artwork.is_some());
+
+    // No roles means all streams ended
+    tracker.apply(&Message::StreamEnd(StreamEnd { roles: None }));
+    assert!(tracker.current().is_idle());
+}
+
+#[test]
+fn test_tracker_history_is_bounded() {
+    let tracker = StreamTracker::with_history_limit(2);
+    tracker.apply(&player_start(44100));
+    tracker.apply(&player_start(48000));
+    tracker.apply(&player_start(96000));
+
+    let history = tracker.history(StreamRole::Player);
+    assert_eq!(history.len(), 2);
+    // Artwork history is tracked independently
+    assert_eq!(tracker.history(StreamRole::Artwork).len(), 2);
+}
+
+#[test]
+fn test_current_stream_display() {
+    let tracker = StreamTracker::new();
+    tracker.apply(&player_start(48000));
+
+    let status = tracker.current().to_string();
+    assert_eq!(
+        status,
+        "player=pcm 48000Hz 2ch 24bit, artwork=channels [0], visualizer=none"
+    );
+}
@@ -0,0 +1,73 @@
+// ABOUTME: Tests for the symphonia decoder backend
+// ABOUTME: Runs only with the "symphonia" feature
+#![cfg(feature = "symphonia")]
+
+use sendspin::audio::decode::{Decoder, SymphoniaDecoder};
+use sendspin::audio::{AudioFormat, Codec, Sample};
+
+fn format(codec: Codec, codec_header: Option<Vec<u8>>) -> AudioFormat {
+    let mut format = AudioFormat::new(codec, 44_100, 2, 16);
+    format.codec_header = codec_header;
+    format
+}
+
+/// ALAC magic cookie for 16-bit stereo 44.1kHz, inside an `alac` atom
+fn alac_cookie() -> Vec<u8> {
+    let mut atom = 36u32.to_be_bytes().to_vec();
+    atom.extend_from_slice(b"alac");
+    atom.extend_from_slice(&[0; 4]);
+    atom.extend_from_slice(&4096u32.to_be_bytes());
+    atom.extend_from_slice(&[0, 16, 40, 10, 14, 2]);
+    atom.extend_from_slice(&255u16.to_be_bytes());
+    atom.extend_from_slice(&[0; 8]);
+    atom.extend_from_slice(&44_100u32.to_be_bytes());
+    atom
+}
+
+/// Uncompressed stereo ALAC frame of 2 samples: (1000, -1000), (-2, 3)
+fn alac_frame() -> Vec<u8> {
+    // CPE tag, instance 0, unused 12 bits, partial frame + escape flags,
+    // 32-bit sample count, then 16-bit samples, then the END tag
+    let mut bits = String::from("001");
+    bits += &"0".repeat(16);
+    bits += "1001";
+    bits += &format!("{:032b}", 2);
+    for s in [1000i16, -1000, -2, 3] {
+        bits += &format!("{:016b}", s as u16);
+    }
+    bits += "111";
+    while bits.len() % 8 != 0 {
+        bits.push('0');
+    }
+    (0..bits.len())
+        .step_by(8)
+        .map(|i| u8::from_str_radix(&bits[i..i + 8], 2).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_decodes_alac_through_symphonia() {
+    let decoder = SymphoniaDecoder::new(&format(Codec::Alac, Some(alac_cookie()))).unwrap();
+    let samples = decoder.decode(&alac_frame()).unwrap();
+    let expected: Vec<Sample> = [1000, -1000, -2, 3]
+        .into_iter()
+        .map(Sample::from_i16)
+        .collect();
+    assert_eq!(&samples[..], &expected[..]);
+}
+
+#[test]
+fn test_codecs_needing_headers_reject_missing_ones() {
+    for codec in [Codec::Flac, Codec::Alac, Codec::Vorbis] {
+        assert!(SymphoniaDecoder::new(&format(codec, None)).is_err());
+    }
+    // MP3 needs no header; ADTS AAC carries its config in every frame
+    assert!(SymphoniaDecoder::new(&format(Codec::Mp3, None)).is_ok());
+    assert!(SymphoniaDecoder::new(&format(Codec::Aac, None)).is_ok());
+}
+
+#[test]
+fn test_rejects_codecs_outside_the_backend() {
+    assert!(SymphoniaDecoder::new(&format(Codec::Opus, None)).is_err());
+    assert!(SymphoniaDecoder::new(&format(Codec::Pcm, None)).is_err());
+}
@@ -0,0 +1,51 @@
+// ABOUTME: Tests for opt-in session telemetry
+// ABOUTME: Validates counter aggregation and the client/goodbye extension field
+
+use sendspin::protocol::messages::{ClientGoodbye, GoodbyeReason, Message, SessionTelemetry};
+use sendspin::protocol::TelemetryRecorder;
+
+#[test]
+fn test_telemetry_recorder_summary() {
+    let telemetry = TelemetryRecorder::new();
+    assert_eq!(telemetry.summary(), SessionTelemetry::default());
+
+    let clone = telemetry.clone();
+    telemetry.record_rtt(1000);
+    clone.record_rtt(3000);
+    telemetry.record_underrun();
+    telemetry.record_dropped(4);
+    clone.record_dropped(1);
+    clone.record_reconnect();
+
+    let summary = telemetry.summary();
+    assert_eq!(summary.underruns, 1);
+    assert_eq!(summary.dropped_chunks, 5);
+    assert_eq!(summary.reconnects, 1);
+    assert_eq!(summary.avg_rtt_micros, Some(2000));
+}
+
+#[test]
+fn test_goodbye_carries_telemetry() {
+    let telemetry = TelemetryRecorder::new();
+    telemetry.record_underrun();
+    let goodbye = ClientGoodbye::new(GoodbyeReason::Shutdown).with_telemetry(telemetry.summary());
+
+    let value = serde_json::to_value(Message::ClientGoodbye(goodbye)).unwrap();
+    assert_eq!(value["payload"]["reason"], "shutdown");
+    assert_eq!(value["payload"]["x_telemetry"]["underruns"], 1);
+    assert!(value["payload"]["x_telemetry"]
+        .get("avg_rtt_micros")
+        .is_none());
+
+    let Message::ClientGoodbye(parsed) = serde_json::from_value(value).unwrap() else {
+        panic!("Expected client/goodbye");
+    };
+    assert_eq!(parsed.telemetry.unwrap().underruns, 1);
+
+    // Goodbyes without the extension still parse
+    let json = r#"{"type":"client/goodbye","payload":{"reason":"shutdown"}}"#;
+    let Message::ClientGoodbye(parsed) = serde_json::from_str(json).unwrap() else {
+        panic!("Expected client/goodbye");
+    };
+    assert!(parsed.telemetry.is_none());
+}
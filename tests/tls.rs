@@ -0,0 +1,130 @@
+// ABOUTME: Tests for wss:// connections with custom TLS settings
+// ABOUTME: Covers custom root CAs, rejected and accepted self-signed servers, and client certificates
+#![cfg(feature = "tls")]
+
+mod common;
+
+use common::{answer_hello, idle};
+use sendspin::protocol::client::{ClientConfig, ProtocolClient};
+use sendspin::protocol::messages::{ClientHello, ServerHello};
+use sendspin::protocol::{Role, TlsConfig};
+use sendspin::Error;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{self, RootCertStore};
+use tokio_rustls::TlsAcceptor;
+
+const CA: &[u8] = include_bytes!("fixtures/tls/ca.pem");
+const SERVER_CERT: &[u8] = include_bytes!("fixtures/tls/server.pem");
+const SERVER_KEY: &[u8] = include_bytes!("fixtures/tls/server.key");
+const CLIENT_CERT: &[u8] = include_bytes!("fixtures/tls/client.pem");
+const CLIENT_KEY: &[u8] = include_bytes!("fixtures/tls/client.key");
+
+fn hello() -> ClientHello {
+    ClientHello::new("tls-player", "TLS Player", vec![Role::Player(1)])
+}
+
+/// Serve one wss:// connection, optionally requiring a client certificate from the test CA
+async fn tls_server(require_client_cert: bool) -> String {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = if require_client_cert {
+        let mut roots = RootCertStore::empty();
+        roots
+            .add(CertificateDer::from_pem_slice(CA).unwrap())
+            .unwrap();
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+            .build()
+            .unwrap();
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+    let chain = CertificateDer::pem_slice_iter(SERVER_CERT)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_slice(SERVER_KEY).unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(builder.with_single_cert(chain, key).unwrap()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!(
+        "wss://localhost:{}/sendspin",
+        listener.local_addr().unwrap().port()
+    );
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let Ok(stream) = acceptor.accept(stream).await else {
+            return;
+        };
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        let hello = ServerHello::new("tls", "TLS Server", vec![Role::Player(1)]);
+        if answer_hello(&mut ws, hello).await {
+            idle(ws).await;
+        }
+    });
+    url
+}
+
+async fn connect(url: &str, tls: TlsConfig) -> Result<ProtocolClient, Error> {
+    let config = ClientConfig {
+        tls: Some(tls),
+        ..ClientConfig::default()
+    };
+    ProtocolClient::connect_with_config(url, hello(), config).await
+}
+
+#[test]
+fn test_invalid_pem_is_rejected() {
+    assert!(TlsConfig::new().add_root_pem(b"not a certificate").is_err());
+    assert!(TlsConfig::new()
+        .with_client_identity_pem(CLIENT_CERT, b"no key here")
+        .is_err());
+    assert!(matches!(
+        TlsConfig::new().add_root_pem_file("/nonexistent/ca.pem"),
+        Err(Error::Io(_))
+    ));
+    assert!(TlsConfig::new().verifies_certs());
+    assert!(!TlsConfig::new()
+        .danger_accept_invalid_certs(true)
+        .verifies_certs());
+}
+
+#[tokio::test]
+async fn test_custom_root_ca() {
+    let url = tls_server(false).await;
+    let tls = TlsConfig::new().add_root_pem(CA).unwrap();
+    let client = connect(&url, tls).await.unwrap();
+    assert_eq!(client.server_hello().name, "TLS Server");
+}
+
+#[tokio::test]
+async fn test_untrusted_server_needs_escape_hatch() {
+    let url = tls_server(false).await;
+    let result = connect(&url, TlsConfig::new()).await;
+    assert!(matches!(result, Err(Error::Connection(_))));
+
+    let url = tls_server(false).await;
+    let tls = TlsConfig::new()
+        .without_builtin_roots()
+        .danger_accept_invalid_certs(true);
+    assert!(connect(&url, tls).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_client_certificate() {
+    let url = tls_server(true).await;
+    let tls = TlsConfig::new().add_root_pem(CA).unwrap();
+    let result = connect(&url, tls.clone()).await;
+    assert!(result.is_err());
+
+    let url = tls_server(true).await;
+    let tls = tls
+        .with_client_identity_pem(CLIENT_CERT, CLIENT_KEY)
+        .unwrap();
+    assert!(connect(&url, tls).await.is_ok());
+}
@@ -0,0 +1,118 @@
+// ABOUTME: Tests for the reference tone source and verifier
+// ABOUTME: Validates phase continuity, gap detection, and frequency error measurement
+
+use sendspin::audio::convert::to_samples;
+use sendspin::audio::decode::PcmEndian;
+use sendspin::audio::{AudioFormat, Codec, Sample, ToneVerifier};
+use sendspin::server::{AudioSource, ToneSource};
+use std::time::Duration;
+
+fn format(bit_depth: u8) -> AudioFormat {
+    AudioFormat::new(Codec::Pcm, 48000, 2, bit_depth)
+}
+
+/// Pull `frames` frames from a source in uneven chunks
+fn tone_samples(source: &mut ToneSource, frames: usize) -> Vec<Sample> {
+    let bit_depth = source.format().bit_depth;
+    let mut samples = Vec::new();
+    let mut chunk = 7;
+    while samples.len() < frames * 2 {
+        let data = source.next_chunk(chunk).unwrap().unwrap();
+        samples.extend(to_samples(&data, bit_depth, PcmEndian::Little).unwrap());
+        chunk = chunk * 3 % 1000 + 1;
+    }
+    samples.truncate(frames * 2);
+    samples
+}
+
+#[test]
+fn test_clean_tone_passes() {
+    for bit_depth in [16, 24] {
+        let mut source = ToneSource::new(format(bit_depth)).unwrap();
+        let samples = tone_samples(&mut source, 48000);
+
+        let mut verifier = ToneVerifier::new(source.frequency(), 48000, 2).unwrap();
+        for chunk in samples.chunks(960) {
+            verifier.push(chunk);
+        }
+        let report = verifier.report();
+        assert!(report.is_clean(), "{}", report);
+        assert_eq!(report.frames, 48000);
+        assert_eq!(report.silent_frames, 0);
+        assert!(
+            report.frequency_error_ppm().unwrap().abs() < 1.0,
+            "{}",
+            report
+        );
+    }
+}
+
+#[test]
+fn test_detects_dropped_and_inserted_frames() {
+    let mut source = ToneSource::new(format(24)).unwrap();
+    let mut samples = tone_samples(&mut source, 20000);
+    // Drop 10 frames after frame 5000, then repeat 5 frames after (new) frame 12000
+    samples.drain(10000..10020);
+    let repeated = samples[24000..24010].to_vec();
+    samples.splice(24010..24010, repeated);
+
+    let mut verifier = ToneVerifier::new(440.0, 48000, 2).unwrap();
+    verifier.push(&samples);
+    let report = verifier.report();
+    assert_eq!(report.discontinuities.len(), 2, "{}", report);
+    assert_eq!(report.discontinuities[0].frame, 5000);
+    assert_eq!(report.discontinuities[0].offset, 10);
+    assert_eq!(report.discontinuities[1].frame, 12005);
+    assert_eq!(report.discontinuities[1].offset, -5);
+    assert_eq!((report.dropped, report.inserted), (10, 5));
+}
+
+#[test]
+fn test_measures_frequency_error() {
+    // A sender clock running 100ppm fast
+    let mut source = ToneSource::new(format(24))
+        .unwrap()
+        .with_frequency(440.0 * 1.0001)
+        .unwrap();
+    let samples = tone_samples(&mut source, 96000);
+
+    let mut verifier = ToneVerifier::new(440.0, 48000, 2).unwrap();
+    verifier.push(&samples);
+    let report = verifier.report();
+    assert!(report.is_clean(), "{}", report);
+    let ppm = report.frequency_error_ppm().unwrap();
+    assert!((ppm - 100.0).abs() < 5.0, "{}", report);
+}
+
+#[test]
+fn test_ignores_level_changes_and_silence() {
+    let mut source = ToneSource::new(format(24)).unwrap();
+    let mut samples = vec![Sample::ZERO; 2000];
+    let tone = tone_samples(&mut source, 10000);
+    samples.extend(tone[..10000].iter().copied());
+    // Volume drop to a quarter halfway through
+    samples.extend(tone[10000..].iter().map(|s| Sample(s.0 / 4)));
+
+    let mut verifier = ToneVerifier::new(440.0, 48000, 2).unwrap();
+    verifier.push(&samples);
+    let report = verifier.report();
+    assert!(report.is_clean(), "{}", report);
+    assert!(report.silent_frames >= 999);
+}
+
+#[test]
+fn test_tone_source_limits() {
+    let mut source = ToneSource::new(format(16))
+        .unwrap()
+        .with_duration(Duration::from_millis(10));
+    assert_eq!(source.next_chunk(300).unwrap().unwrap().len(), 300 * 4);
+    assert_eq!(source.next_chunk(300).unwrap().unwrap().len(), 180 * 4);
+    assert!(source.next_chunk(300).unwrap().is_none());
+
+    assert!(ToneSource::new(format(16))
+        .unwrap()
+        .with_frequency(30000.0)
+        .is_err());
+    assert!(ToneSource::new(format(32)).is_err());
+    assert!(ToneVerifier::new(0.0, 48000, 2).is_err());
+}
@@ -0,0 +1,108 @@
+// ABOUTME: Tests for track change hooks and spoken announcements
+// ABOUTME: Validates change detection, hook invocation, and announcement text
+
+use sendspin::metadata::announce::announcement_text;
+use sendspin::metadata::{MetadataTracker, SpeechAnnouncer};
+use sendspin::player::{Player, PlayerConfig};
+use sendspin::protocol::messages::MetadataState;
+use std::time::Duration;
+
+fn track(title: &str, artist: Option<&str>) -> MetadataState {
+    MetadataState {
+        timestamp: 0,
+        title: Some(title.to_string()),
+        artist: artist.map(str::to_string),
+        album: None,
+        artwork_url: None,
+        year: None,
+        track: None,
+        progress: None,
+        repeat: None,
+        shuffle: None,
+        genre: None,
+        composer: None,
+        disc_number: None,
+        content_type: None,
+        source: None,
+        lyrics: None,
+    }
+}
+
+#[test]
+fn test_update_reports_track_changes_only() {
+    let tracker = MetadataTracker::new();
+
+    let first = tracker
+        .update(&track("One", Some("Band")))
+        .expect("First track");
+    assert!(first.previous.is_none());
+
+    // Same track with new progress is not a change
+    assert!(tracker.update(&track("One", Some("Band"))).is_none());
+
+    let second = tracker
+        .update(&track("Two", Some("Band")))
+        .expect("Second track");
+    assert_eq!(second.previous.unwrap().title, Some("One".to_string()));
+    assert_eq!(second.current.title, Some("Two".to_string()));
+}
+
+#[tokio::test]
+async fn test_track_change_hook_invoked() {
+    let tracker = MetadataTracker::new();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tracker.on_track_change(move |change| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(change.current.title);
+        }
+    });
+
+    tracker.update(&track("Song", None));
+
+    let title = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .expect("Hook not invoked");
+    assert_eq!(title, Some(Some("Song".to_string())));
+}
+
+#[test]
+fn test_announcement_text() {
+    let tracker = MetadataTracker::new();
+    let change = tracker.update(&track("Song", Some("Artist"))).unwrap();
+
+    assert_eq!(
+        announcement_text(&change.current),
+        Some("Now playing Song by Artist".to_string())
+    );
+
+    let announcer = SpeechAnnouncer::new("say").with_preamble("Track change");
+    assert_eq!(
+        announcer.text_for(&change),
+        Some("Track change. Now playing Song by Artist".to_string())
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_announcement_ducks_player_output() {
+    let tracker = MetadataTracker::new();
+    let change = tracker.update(&track("Song", Some("Artist"))).unwrap();
+    let volume = Player::new(PlayerConfig::default()).volume();
+    volume.set_volume(80);
+    let gain = volume.gain_db();
+
+    // The text lands in $0 of the script, which only waits
+    let announcer = SpeechAnnouncer::new("sh")
+        .arg("-c")
+        .arg("sleep 0.3")
+        .with_ducking(volume.clone(), 12.0);
+    let speaking = tokio::spawn(async move { announcer.announce(&change).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(volume.duck_db(), 12.0);
+    assert_eq!(volume.gain_db(), gain);
+
+    speaking.await.unwrap().unwrap();
+    assert_eq!(volume.duck_db(), 0.0);
+    assert_eq!(volume.gain_db(), gain);
+}
@@ -0,0 +1,134 @@
+// ABOUTME: Tests for running the client over transports other than a ws:// URL
+// ABOUTME: In-memory pipes, Unix domain sockets, and caller-provided TCP streams
+
+use futures_util::{SinkExt, StreamExt};
+use sendspin::protocol::client::{ClientConfig, ProtocolClient};
+use sendspin::protocol::messages::{ClientHello, Message, ServerHello};
+use sendspin::protocol::{HandshakeRequest, Role, StreamTransport};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+fn hello() -> ClientHello {
+    ClientHello::new("transport-test", "Transport Test", vec![Role::Player(1)])
+}
+
+/// Complete the hello exchange on `stream`; reports the handshake path and the client's hello
+fn serve<S>(stream: S) -> oneshot::Receiver<(String, ClientHello)>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let mut path = String::new();
+        #[allow(clippy::result_large_err)]
+        let record = |request: &Request, response: Response| {
+            path = request.uri().to_string();
+            Ok(response)
+        };
+        let mut ws = tokio_tungstenite::accept_hdr_async(stream, record)
+            .await
+            .unwrap();
+        let Some(Ok(WsMessage::Text(text))) = ws.next().await else {
+            panic!("Expected client/hello");
+        };
+        let Ok(Message::ClientHello(client_hello)) = serde_json::from_str(&text) else {
+            panic!("Expected client/hello, got {}", text);
+        };
+        let hello = Message::ServerHello(ServerHello::new("mock", "Local", vec![Role::Player(1)]));
+        let json = serde_json::to_string(&hello).unwrap();
+        ws.send(WsMessage::Text(json)).await.unwrap();
+        let _ = tx.send((path, client_hello));
+        while ws.next().await.is_some() {}
+    });
+    rx
+}
+
+#[tokio::test]
+async fn test_connect_in_memory() {
+    let (transport, server_end) = StreamTransport::memory(64 * 1024);
+    let served = serve(server_end);
+    let client =
+        ProtocolClient::connect_with_transport(transport, hello(), ClientConfig::default())
+            .await
+            .unwrap();
+    assert_eq!(client.server_hello().name, "Local");
+    let (path, client_hello) = served.await.unwrap();
+    assert_eq!(path, "/sendspin");
+    assert_eq!(client_hello.client_id, "transport-test");
+}
+
+#[tokio::test]
+async fn test_stream_transport_applies_handshake_request() {
+    let (transport, server_end) = StreamTransport::memory(64 * 1024);
+    let served = serve(server_end);
+    let config = ClientConfig {
+        request: HandshakeRequest::new().query("room", "kitchen"),
+        ..ClientConfig::default()
+    };
+    let transport = transport.with_url("ws://speaker.local/custom");
+    ProtocolClient::connect_with_transport(transport, hello(), config)
+        .await
+        .unwrap();
+    let (path, _) = served.await.unwrap();
+    assert_eq!(path, "/custom?room=kitchen");
+}
+
+#[tokio::test]
+async fn test_connect_over_caller_tcp_stream() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let accepted = tokio::spawn(async move { listener.accept().await.unwrap().0 });
+    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    let served = serve(accepted.await.unwrap());
+    let client = ProtocolClient::connect_with_transport(
+        StreamTransport::new(stream),
+        hello(),
+        ClientConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(client.server_hello().server_id, "mock");
+    served.await.unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_connect_over_unix_socket() {
+    use sendspin::protocol::UnixTransport;
+
+    let path = std::env::temp_dir().join(format!("sendspin-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    let served = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        serve(stream).await.unwrap()
+    });
+    let client = ProtocolClient::connect_with_transport(
+        UnixTransport::new(&path),
+        hello(),
+        ClientConfig::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(client.server_hello().name, "Local");
+    let (uri, _) = served.await.unwrap();
+    assert_eq!(uri, "/sendspin");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_missing_unix_socket_is_a_connection_error() {
+    use sendspin::protocol::UnixTransport;
+
+    let path = std::env::temp_dir().join("sendspin-test-missing.sock");
+    let result = ProtocolClient::connect_with_transport(
+        UnixTransport::new(&path),
+        hello(),
+        ClientConfig::default(),
+    )
+    .await;
+    assert!(matches!(result, Err(sendspin::Error::Connection(_))));
+}
@@ -0,0 +1,94 @@
+// ABOUTME: Tests for typed visualizer frames
+// ABOUTME: Covers payload parsing and the VisualizerStream against a mock server
+
+mod common;
+
+use common::{mock_server, text};
+use futures_util::{SinkExt, StreamExt};
+use sendspin::protocol::client::{ProtocolClient, VisualizerChunk};
+use sendspin::protocol::messages::{
+    ClientHello, FftWindow, Message, StreamStart, StreamVisualizerConfig,
+};
+use sendspin::protocol::{Role, VisualizerFrame, VisualizerStream};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+fn config(bins: u16) -> StreamVisualizerConfig {
+    StreamVisualizerConfig {
+        bins,
+        rate_hz: 30,
+        window: FftWindow::Hann,
+        min_frequency_hz: 20.0,
+        max_frequency_hz: 20_000.0,
+    }
+}
+
+fn chunk(data: &[u8]) -> VisualizerChunk {
+    VisualizerChunk {
+        timestamp: 42,
+        data: Arc::from(data),
+        received_at: Instant::now(),
+    }
+}
+
+#[test]
+fn test_frame_splits_channels_and_scales_bins() {
+    let frame = VisualizerFrame::parse(&chunk(&[0, 255, 51, 255, 0, 153]), &config(3)).unwrap();
+    assert_eq!(frame.timestamp, 42);
+    assert_eq!(
+        frame.channels,
+        vec![vec![0.0, 1.0, 0.2], vec![1.0, 0.0, 0.6]]
+    );
+    assert_eq!(frame.mixed(), vec![0.5, 0.5, 0.4]);
+
+    assert!(VisualizerFrame::parse(&chunk(&[1, 2, 3, 4]), &config(3)).is_err());
+    assert!(VisualizerFrame::parse(&chunk(&[]), &config(3)).is_err());
+}
+
+#[tokio::test]
+async fn test_stream_parses_with_stream_start_config() {
+    let url = mock_server(vec![Role::Visualizer(1)], |mut ws| async move {
+        let start = Message::StreamStart(StreamStart {
+            player: None,
+            artwork: None,
+            visualizer: Some(config(2)),
+        });
+        let frames = vec![
+            // Before stream/start there is no config to read it with
+            WsMessage::Binary(VisualizerChunk::encode(1, &[255, 255])),
+            text(&start),
+            WsMessage::Binary(VisualizerChunk::encode(2, &[255, 0, 0, 255])),
+            WsMessage::Binary(VisualizerChunk::encode(3, &[1, 2, 3])),
+            WsMessage::Binary(VisualizerChunk::encode(4, &[0, 0])),
+        ];
+        for frame in frames {
+            ws.send(frame).await.unwrap();
+        }
+        let _ = ws.close(None).await;
+    })
+    .await;
+
+    let hello = ClientHello::new(
+        "visualizer-test",
+        "Visualizer Test",
+        vec![Role::Visualizer(1)],
+    );
+    let client = ProtocolClient::connect(&url, hello).await.unwrap();
+    let streams = client.stream_tracker();
+    let mut frames = VisualizerStream::new(client.into_parts().visualizer, streams);
+
+    let first = timeout(Duration::from_secs(5), frames.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.timestamp, 2);
+    assert_eq!(first.channels, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+    let second = timeout(Duration::from_secs(5), frames.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(second.timestamp, 4);
+    assert_eq!(frames.skipped(), 2);
+}
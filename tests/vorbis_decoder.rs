@@ -0,0 +1,83 @@
+// ABOUTME: Tests for the Vorbis codec header and the lewton-backed decoder
+// ABOUTME: Decoding tests run only with the "vorbis" feature
+
+use sendspin::audio::decode::VorbisHeaders;
+
+/// Identification header for stereo 48kHz
+fn identification() -> Vec<u8> {
+    let mut packet = vec![1];
+    packet.extend_from_slice(b"vorbis");
+    packet.extend_from_slice(&0u32.to_le_bytes());
+    packet.push(2);
+    packet.extend_from_slice(&48_000u32.to_le_bytes());
+    packet.extend_from_slice(&[0; 12]);
+    // blocksizes 256/2048, framing bit
+    packet.extend_from_slice(&[0xB8, 1]);
+    packet
+}
+
+fn packet(kind: u8, len: usize) -> Vec<u8> {
+    let mut packet = vec![kind];
+    packet.extend_from_slice(b"vorbis");
+    packet.resize(len, 0);
+    packet
+}
+
+/// Xiph-laced header with a comment packet long enough to need a 255 run
+fn laced(setup: &[u8]) -> Vec<u8> {
+    let id = identification();
+    let comment = packet(3, 300);
+    let mut header = vec![2, id.len() as u8, 255, 45];
+    header.extend_from_slice(&id);
+    header.extend_from_slice(&comment);
+    header.extend_from_slice(setup);
+    header
+}
+
+#[test]
+fn test_split_xiph_laced_headers() {
+    let headers = VorbisHeaders::parse(&laced(&packet(5, 40))).unwrap();
+    assert_eq!(headers.identification, identification());
+    assert_eq!(headers.comment.len(), 300);
+    assert_eq!(headers.setup.len(), 40);
+    assert_eq!(headers.channels_and_rate().unwrap(), (2, 48_000));
+}
+
+#[test]
+fn test_reject_bad_headers() {
+    assert!(VorbisHeaders::parse(&[]).is_err());
+    // Setup packet missing
+    assert!(VorbisHeaders::parse(&laced(&[])).is_err());
+    // Sizes longer than the data
+    let mut truncated = laced(&packet(5, 40));
+    truncated.truncate(100);
+    assert!(VorbisHeaders::parse(&truncated).is_err());
+    // Comment packet where the identification should be
+    let mut swapped = laced(&packet(5, 40));
+    swapped[4] = 3;
+    assert!(VorbisHeaders::parse(&swapped).is_err());
+}
+
+#[cfg(feature = "vorbis")]
+mod lewton {
+    use super::{laced, packet};
+    use sendspin::audio::decode::VorbisDecoder;
+    use sendspin::audio::{AudioFormat, Codec};
+
+    fn format(codec_header: Option<Vec<u8>>) -> AudioFormat {
+        let mut format = AudioFormat::new(Codec::Vorbis, 48_000, 2, 16);
+        format.codec_header = codec_header;
+        format
+    }
+
+    #[test]
+    fn test_decoder_needs_valid_headers() {
+        assert!(VorbisDecoder::new(&format(None)).is_err());
+        // A setup packet without codebooks is rejected
+        assert!(VorbisDecoder::new(&format(Some(laced(&packet(5, 40))))).is_err());
+
+        let mut mono = format(Some(laced(&packet(5, 40))));
+        mono.channels = 1;
+        assert!(VorbisDecoder::new(&mono).is_err());
+    }
+}
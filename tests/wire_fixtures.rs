@@ -0,0 +1,122 @@
+// ABOUTME: Wire-format compatibility tests against Go reference implementation fixtures
+// ABOUTME: Parses and re-encodes every captured frame in tests/fixtures/go-reference
+
+use sendspin::audio::decode::{Decoder, PcmDecoder};
+use sendspin::audio::Sample;
+use sendspin::protocol::client::BinaryFrame;
+use sendspin::protocol::messages::Message;
+use serde_json::Value;
+
+const FIXTURES: &str = include_str!("fixtures/go-reference/frames.jsonl");
+
+fn fixtures() -> Vec<Value> {
+    FIXTURES
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn case_name(fixture: &Value) -> &str {
+    fixture["case"].as_str().unwrap()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_fixture_set_covers_the_handshake_sync_and_audio() {
+    let cases: Vec<String> = fixtures()
+        .iter()
+        .map(|f| case_name(f).to_string())
+        .collect();
+    for required in [
+        "client_hello_player",
+        "server_hello",
+        "client_time",
+        "server_time",
+    ] {
+        assert!(cases.iter().any(|c| c == required), "missing {}", required);
+    }
+    assert!(cases.iter().any(|c| c.starts_with("audio_")));
+
+    let mut unique = cases.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), cases.len(), "duplicate case names");
+}
+
+#[test]
+fn test_text_frames_round_trip() {
+    for fixture in fixtures() {
+        let Some(text) = fixture["text"].as_str() else {
+            continue;
+        };
+        let case = case_name(&fixture);
+        let wire: Value = serde_json::from_str(text).unwrap();
+
+        let msg: Message = serde_json::from_str(text)
+            .unwrap_or_else(|e| panic!("{}: failed to parse: {}", case, e));
+        assert_eq!(msg.message_type(), wire["type"], "{}", case);
+        msg.validate()
+            .unwrap_or_else(|e| panic!("{}: failed validation: {}", case, e));
+
+        // Semantic equality: no field may be lost, renamed, or added
+        assert_eq!(serde_json::to_value(&msg).unwrap(), wire, "{}", case);
+    }
+}
+
+#[test]
+fn test_binary_frames_parse_and_re_encode() {
+    for fixture in fixtures() {
+        let Some(hex) = fixture["hex"].as_str() else {
+            continue;
+        };
+        let case = case_name(&fixture);
+        let bytes = from_hex(hex);
+        let expect = &fixture["expect"];
+        assert_eq!(
+            bytes[0] as u64,
+            expect["type"].as_u64().unwrap(),
+            "{}",
+            case
+        );
+        let timestamp = expect["timestamp"].as_i64().unwrap();
+
+        let frame = BinaryFrame::from_bytes(&bytes)
+            .unwrap_or_else(|e| panic!("{}: failed to parse: {}", case, e));
+        let (parsed_timestamp, payload, encoded) = match frame {
+            BinaryFrame::Audio(chunk) => (chunk.timestamp, chunk.data.clone(), chunk.to_bytes()),
+            BinaryFrame::Artwork(chunk) => (chunk.timestamp, chunk.data.clone(), chunk.to_bytes()),
+            BinaryFrame::Visualizer(chunk) => {
+                (chunk.timestamp, chunk.data.clone(), Ok(chunk.to_bytes()))
+            }
+            BinaryFrame::Unknown { type_id, .. } => {
+                panic!("{}: unknown binary type {}", case, type_id)
+            }
+        };
+        assert_eq!(parsed_timestamp, timestamp, "{}: timestamp", case);
+        assert_eq!(encoded.unwrap(), bytes, "{}: re-encoded bytes", case);
+
+        if let Some(samples) = expect["samples"].as_array() {
+            let bit_depth = expect["bit_depth"].as_u64().unwrap() as u8;
+            let expected: Vec<Sample> = samples
+                .iter()
+                .map(|s| s.as_i64().unwrap() as i32)
+                .map(|s| match bit_depth {
+                    16 => Sample::from_i16(s as i16),
+                    _ => Sample(s),
+                })
+                .collect();
+            let decoded = PcmDecoder::new(bit_depth).decode(&payload).unwrap();
+            assert_eq!(&decoded[..], &expected[..], "{}: samples", case);
+        } else {
+            let expected = from_hex(expect["payload"].as_str().unwrap());
+            assert_eq!(&payload[..], &expected[..], "{}: payload", case);
+        }
+    }
+}